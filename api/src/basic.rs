@@ -111,6 +111,10 @@ impl BasicApi {
             "internal_indexer_config".to_string(),
             serde_json::to_value(&self.context.node_config.indexer_db_config).unwrap(),
         );
+        info.insert(
+            "storage_pruner_config".to_string(),
+            serde_json::to_value(self.context.node_config.storage.storage_pruner_config).unwrap(),
+        );
 
         Json(info)
     }
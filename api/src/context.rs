@@ -16,6 +16,7 @@ use aptos_api_types::{
     AptosErrorCode, AsConverter, BcsBlock, GasEstimation, LedgerInfo, ResourceGroup,
     TransactionOnChainData,
 };
+use aptos_block_executor::code_cache_global_manager::AptosModuleCacheManager;
 use aptos_config::config::{GasEstimationConfig, NodeConfig, RoleType};
 use aptos_crypto::HashValue;
 use aptos_gas_schedule::{AptosGasParameters, FromOnChainGasSchedule};
@@ -40,7 +41,7 @@ use aptos_types::{
     state_store::{
         state_key::{inner::StateKeyInner, prefix::StateKeyPrefix, StateKey},
         state_value::StateValue,
-        TStateView,
+        StateView, TStateView,
     },
     transaction::{
         block_epilogue::BlockEndInfo,
@@ -48,6 +49,7 @@ use aptos_types::{
         SignedTransaction, Transaction, TransactionWithProof, Version,
     },
 };
+use aptos_vm::simulation_cache::SimulationCache;
 use futures::{channel::oneshot, SinkExt};
 use mini_moka::sync::Cache;
 use move_core_types::{
@@ -79,6 +81,7 @@ pub struct Context {
     gas_limit_cache: Arc<RwLock<GasLimitCache>>,
     view_function_stats: Arc<FunctionStats>,
     simulate_txn_stats: Arc<FunctionStats>,
+    simulation_cache: Arc<SimulationCache>,
     pub indexer_reader: Option<Arc<dyn IndexerReader>>,
     pub wait_for_hash_active_connections: Arc<AtomicUsize>,
 }
@@ -110,6 +113,10 @@ impl Context {
                 )),
             )
         };
+        let simulation_cache = Arc::new(SimulationCache::new(
+            node_config.api.simulation_output_cache_size,
+            Arc::new(AptosModuleCacheManager::new()),
+        ));
         Self {
             chain_id,
             db,
@@ -131,6 +138,7 @@ impl Context {
             })),
             view_function_stats,
             simulate_txn_stats,
+            simulation_cache,
             indexer_reader,
             wait_for_hash_active_connections: Arc::new(AtomicUsize::new(0)),
         }
@@ -769,6 +777,17 @@ impl Context {
         }
 
         let state_view = self.latest_state_view_poem(ledger_info)?;
+        self.render_transactions_non_sequential_with_state_view(ledger_info, &state_view, data)
+    }
+
+    /// Same as [Self::render_transactions_non_sequential], but decodes the transactions using
+    /// the given state view, e.g., one with simulation overrides applied.
+    pub fn render_transactions_non_sequential_with_state_view<E: InternalError, S: StateView>(
+        &self,
+        ledger_info: &LedgerInfo,
+        state_view: &S,
+        data: Vec<TransactionOnChainData>,
+    ) -> Result<Vec<aptos_api_types::Transaction>, E> {
         let converter = state_view.as_converter(self.db.clone(), self.indexer_reader.clone());
         let txns: Vec<aptos_api_types::Transaction> = data
             .into_iter()
@@ -1554,6 +1573,10 @@ impl Context {
     pub fn simulate_txn_stats(&self) -> &FunctionStats {
         &self.simulate_txn_stats
     }
+
+    pub fn simulation_cache(&self) -> &SimulationCache {
+        &self.simulation_cache
+    }
 }
 
 pub struct GasScheduleCache {
@@ -105,3 +105,12 @@ pub static WAIT_TRANSACTION_POLL_TIME: Lazy<HistogramVec> = Lazy::new(|| {
     )
     .unwrap()
 });
+
+pub static SIMULATION_CACHE: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_api_simulation_cache",
+        "Simulation cache lookups grouped by cache (output or module) and result",
+        &["cache", "result"]
+    )
+    .unwrap()
+});
@@ -0,0 +1,30 @@
+module addr::config {
+    use std::signer;
+    use aptos_std::table::{Self, Table};
+
+    /// The stored value is not the expected one.
+    const EVALUE_MISMATCH: u64 = 1;
+
+    struct Config has key {
+        value: u64,
+        items: Table<u64, u64>,
+    }
+
+    public entry fun create(account: &signer) {
+        move_to(account, Config {
+            value: 0,
+            items: table::new(),
+        });
+    }
+
+    public entry fun check_value(account: &signer, expected: u64) acquires Config {
+        let config = borrow_global<Config>(signer::address_of(account));
+        assert!(config.value == expected, EVALUE_MISMATCH);
+    }
+
+    public entry fun check_item(account: &signer, key: u64, expected: u64) acquires Config {
+        let items = &borrow_global<Config>(signer::address_of(account)).items;
+        assert!(table::contains(items, key), 2);
+        assert!(*table::borrow(items, key) == expected, 3);
+    }
+}
@@ -2,8 +2,12 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use super::new_test_context;
+use crate::metrics;
 use aptos_api_test_context::{current_function_name, pretty, TestContext};
+use aptos_api_types::HexEncodedBytes;
 use aptos_crypto::ed25519::Ed25519Signature;
+use aptos_framework::BuiltPackage;
+use aptos_sdk::types::LocalAccount;
 use aptos_types::{
     account_address::AccountAddress,
     transaction::{
@@ -426,3 +430,483 @@ async fn test_bcs_execute_fee_payer_transaction_no_authenticator_fail() {
         .unwrap()
         .contains("INVALID_SIGNATURE"));
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_simulate_transaction_with_timestamp_override() {
+    let mut context = new_test_context(current_function_name!());
+    let alice = &mut context.gen_account();
+    let bob = &mut context.gen_account();
+    let txn = context.mint_user_account(alice).await;
+    context.commit_block(&vec![txn]).await;
+
+    let txn = context.account_transfer_to(alice, bob.address(), SMALL_TRANSFER_AMOUNT);
+    if let TransactionAuthenticator::Ed25519 {
+        public_key,
+        signature: _,
+    } = txn.authenticator_ref()
+    {
+        let request = |timestamp_usecs: u64| {
+            json!({
+                "transaction": {
+                    "sender": txn.sender().to_string(),
+                    "sequence_number": txn.sequence_number().to_string(),
+                    "max_gas_amount": txn.max_gas_amount().to_string(),
+                    "gas_unit_price": txn.gas_unit_price().to_string(),
+                    "expiration_timestamp_secs": txn.expiration_timestamp_secs().to_string(),
+                    "payload": {
+                        "type": "entry_function_payload",
+                        "function": "0x1::aptos_account::transfer",
+                        "type_arguments": [],
+                        "arguments": [
+                            bob.address().to_standard_string(), SMALL_TRANSFER_AMOUNT.to_string(),
+                        ]
+                    },
+                    "signature": {
+                        "type": "ed25519_signature",
+                        "public_key": public_key.to_string(),
+                        "signature": Ed25519Signature::dummy_signature().to_string(),
+                    }
+                },
+                "state_overrides": {
+                    "timestamp_usecs": timestamp_usecs.to_string(),
+                }
+            })
+        };
+
+        // A timestamp before the expiration time does not change the outcome.
+        let timestamp_usecs = context.get_latest_ledger_info().timestamp();
+        let resp = context
+            .expect_status_code(200)
+            .post(
+                "/transactions/simulate_with_overrides",
+                request(timestamp_usecs),
+            )
+            .await;
+        assert!(resp[0]["success"].as_bool().is_some_and(|v| v));
+
+        // A timestamp after the expiration time makes the transaction expire.
+        let expired_timestamp_usecs = (txn.expiration_timestamp_secs() + 1) * 1_000_000;
+        let resp = context
+            .expect_status_code(200)
+            .post(
+                "/transactions/simulate_with_overrides",
+                request(expired_timestamp_usecs),
+            )
+            .await;
+        assert!(!resp[0]["success"].as_bool().is_some_and(|v| v));
+        assert!(resp[0]["vm_status"]
+            .as_str()
+            .is_some_and(|status| status.contains("TRANSACTION_EXPIRED")));
+    } else {
+        unreachable!("Simulation uses Ed25519 authenticator.");
+    }
+}
+
+/// Simulates calling a function of the `pack_state_overrides` package published by the account,
+/// and returns whether the simulated transaction succeeded.
+async fn simulate_config_call_with_overrides(
+    context: &mut TestContext,
+    account: &LocalAccount,
+    function: &str,
+    arguments: Vec<serde_json::Value>,
+    state_overrides: serde_json::Value,
+) -> bool {
+    let resp = context
+        .expect_status_code(200)
+        .post(
+            "/transactions/simulate_with_overrides",
+            json!({
+                "transaction": {
+                    "sender": account.address().to_hex_literal(),
+                    "sequence_number": account.sequence_number().to_string(),
+                    "gas_unit_price": "100",
+                    "max_gas_amount": "1000000",
+                    "expiration_timestamp_secs": "16373698888888",
+                    "payload": {
+                        "type": "entry_function_payload",
+                        "function": format!("{}::config::{}", account.address().to_hex_literal(), function),
+                        "type_arguments": [],
+                        "arguments": arguments,
+                    },
+                    "signature": {
+                        "type": "ed25519_signature",
+                        "public_key": account.public_key().to_string(),
+                        "signature": Ed25519Signature::dummy_signature().to_string(),
+                    }
+                },
+                "state_overrides": state_overrides,
+            }),
+        )
+        .await;
+    resp[0]["success"].as_bool().unwrap()
+}
+
+async fn publish_state_overrides_package(context: &mut TestContext) -> LocalAccount {
+    let mut account = context.create_account().await;
+    let named_addresses = vec![("addr".to_string(), account.address())];
+    let txn = TestContext::build_package(
+        PathBuf::from(std::env!("CARGO_MANIFEST_DIR")).join("src/tests/move/pack_state_overrides"),
+        named_addresses,
+    );
+    context.publish_package(&mut account, txn).await;
+    context
+        .api_execute_entry_function(
+            &mut account,
+            &format!("{}::config::create", account.address().to_hex_literal()),
+            json!([]),
+            json!([]),
+        )
+        .await;
+    account
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_simulate_transaction_with_resource_override() {
+    let mut context = new_test_context(current_function_name!());
+    let account = publish_state_overrides_package(&mut context).await;
+
+    let config_type = format!("{}::config::Config", account.address().to_hex_literal());
+    let mut config = context
+        .get(&format!(
+            "/accounts/{}/resource/{}",
+            account.address(),
+            config_type
+        ))
+        .await;
+    config["data"]["value"] = json!("42");
+
+    assert!(
+        !simulate_config_call_with_overrides(
+            &mut context,
+            &account,
+            "check_value",
+            vec![json!("42")],
+            json!({}),
+        )
+        .await
+    );
+    assert!(
+        simulate_config_call_with_overrides(
+            &mut context,
+            &account,
+            "check_value",
+            vec![json!("42")],
+            json!({
+                "resources": [{
+                    "address": account.address().to_hex_literal(),
+                    "type": config_type,
+                    "data": config["data"],
+                }]
+            }),
+        )
+        .await
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_simulate_transaction_with_table_item_override() {
+    let mut context = new_test_context(current_function_name!());
+    let account = publish_state_overrides_package(&mut context).await;
+
+    let config = context
+        .get(&format!(
+            "/accounts/{}/resource/{}::config::Config",
+            account.address(),
+            account.address().to_hex_literal()
+        ))
+        .await;
+    let handle = config["data"]["items"]["handle"].clone();
+
+    assert!(
+        !simulate_config_call_with_overrides(
+            &mut context,
+            &account,
+            "check_item",
+            vec![json!("1"), json!("7")],
+            json!({}),
+        )
+        .await
+    );
+    assert!(
+        simulate_config_call_with_overrides(
+            &mut context,
+            &account,
+            "check_item",
+            vec![json!("1"), json!("7")],
+            json!({
+                "table_items": [{
+                    "handle": handle,
+                    "key": HexEncodedBytes::from(bcs::to_bytes(&1u64).unwrap()),
+                    "value": HexEncodedBytes::from(bcs::to_bytes(&7u64).unwrap()),
+                }]
+            }),
+        )
+        .await
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_simulate_transaction_with_module_override() {
+    let mut context = new_test_context(current_function_name!());
+    let account = publish_state_overrides_package(&mut context).await;
+
+    let mut build_options = aptos_framework::BuildOptions::default();
+    build_options
+        .named_addresses
+        .insert("addr".to_string(), account.address());
+    let upgraded_package = BuiltPackage::build(
+        PathBuf::from(std::env!("CARGO_MANIFEST_DIR"))
+            .join("src/tests/move/pack_state_overrides_upgrade"),
+        build_options,
+    )
+    .unwrap();
+    let modules: Vec<_> = upgraded_package
+        .extract_code()
+        .into_iter()
+        .map(HexEncodedBytes::from)
+        .collect();
+
+    // The published module checks the stored value, the upgraded one is off by one.
+    assert!(
+        simulate_config_call_with_overrides(
+            &mut context,
+            &account,
+            "check_value",
+            vec![json!("0")],
+            json!({}),
+        )
+        .await
+    );
+    assert!(
+        !simulate_config_call_with_overrides(
+            &mut context,
+            &account,
+            "check_value",
+            vec![json!("0")],
+            json!({ "modules": modules }),
+        )
+        .await
+    );
+    assert!(
+        simulate_config_call_with_overrides(
+            &mut context,
+            &account,
+            "check_value",
+            vec![json!("1")],
+            json!({ "modules": modules }),
+        )
+        .await
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_bcs_simulate_bundle() {
+    let mut context = new_test_context(current_function_name!());
+    let alice = &mut context.gen_account();
+    let bob = &mut context.gen_account();
+    let carol = &mut context.gen_account();
+    let txn = context.mint_user_account(alice).await;
+    context.commit_block(&vec![txn]).await;
+
+    let without_signature = |txn: SignedTransaction| {
+        SignedTransaction::new_signed_transaction(
+            txn.into_raw_transaction(),
+            TransactionAuthenticator::SingleSender {
+                sender: AccountAuthenticator::NoAccountAuthenticator,
+            },
+        )
+    };
+    // Bob can only pay for his transfer once he has been funded by Alice.
+    let fund_bob =
+        without_signature(context.account_transfer_to(alice, bob.address(), 100_000_000));
+    let pay_carol =
+        without_signature(context.account_transfer_to(bob, carol.address(), SMALL_TRANSFER_AMOUNT));
+
+    let body = bcs::to_bytes(&vec![fund_bob.clone(), pay_carol.clone()]).unwrap();
+    let resp = context
+        .expect_status_code(200)
+        .post_bcs_txn("/transactions/simulate_bundle", body)
+        .await;
+    let results = resp.as_array().unwrap();
+    assert_eq!(results.len(), 2, "{}", pretty(&resp));
+    assert!(results
+        .iter()
+        .all(|result| result["success"].as_bool().unwrap()));
+
+    // In the opposite order, Bob's transfer fails and the simulation stops there.
+    let body = bcs::to_bytes(&vec![pay_carol.clone(), fund_bob.clone()]).unwrap();
+    let resp = context
+        .expect_status_code(200)
+        .post_bcs_txn("/transactions/simulate_bundle", body)
+        .await;
+    let results = resp.as_array().unwrap();
+    assert_eq!(results.len(), 1, "{}", pretty(&resp));
+    assert!(!results[0]["success"].as_bool().unwrap());
+
+    // Unless the simulation is asked to continue past failures.
+    let body = bcs::to_bytes(&vec![pay_carol, fund_bob]).unwrap();
+    let resp = context
+        .expect_status_code(200)
+        .post_bcs_txn(
+            "/transactions/simulate_bundle?continue_on_failure=true",
+            body,
+        )
+        .await;
+    let results = resp.as_array().unwrap();
+    assert_eq!(results.len(), 2, "{}", pretty(&resp));
+    assert!(!results[0]["success"].as_bool().unwrap());
+    assert!(results[1]["success"].as_bool().unwrap());
+}
+
+/// Returns a JSON transaction request with a dummy signature, calling the entry function with the
+/// given arguments.
+fn json_transaction_request(
+    account: &LocalAccount,
+    sequence_number: u64,
+    function: String,
+    arguments: Vec<serde_json::Value>,
+) -> serde_json::Value {
+    json!({
+        "sender": account.address().to_hex_literal(),
+        "sequence_number": sequence_number.to_string(),
+        "gas_unit_price": "100",
+        "max_gas_amount": "1000000",
+        "expiration_timestamp_secs": "16373698888888",
+        "payload": {
+            "type": "entry_function_payload",
+            "function": function,
+            "type_arguments": [],
+            "arguments": arguments,
+        },
+        "signature": {
+            "type": "ed25519_signature",
+            "public_key": account.public_key().to_string(),
+            "signature": Ed25519Signature::dummy_signature().to_string(),
+        }
+    })
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_simulate_bundle_with_module_upgrade() {
+    let mut context = new_test_context(current_function_name!());
+    let account = publish_state_overrides_package(&mut context).await;
+
+    let upgrade_payload = TestContext::build_package(
+        PathBuf::from(std::env!("CARGO_MANIFEST_DIR"))
+            .join("src/tests/move/pack_state_overrides_upgrade"),
+        vec![("addr".to_string(), account.address())],
+    );
+    let TransactionPayload::EntryFunction(upgrade) = upgrade_payload else {
+        unreachable!("Packages are published with an entry function");
+    };
+    let metadata: Vec<u8> = bcs::from_bytes(&upgrade.args()[0]).unwrap();
+    let code: Vec<Vec<u8>> = bcs::from_bytes(&upgrade.args()[1]).unwrap();
+
+    // Both checks fail, the first one against the published module and the second one against
+    // the upgraded module.
+    let check_value = format!(
+        "{}::config::check_value",
+        account.address().to_hex_literal()
+    );
+    let sequence_number = account.sequence_number();
+    let bundle = json!([
+        json_transaction_request(&account, sequence_number, check_value.clone(), vec![json!(
+            "1"
+        )]),
+        json_transaction_request(
+            &account,
+            sequence_number + 1,
+            "0x1::code::publish_package_txn".to_string(),
+            vec![
+                json!(HexEncodedBytes::from(metadata)),
+                json!(code
+                    .into_iter()
+                    .map(HexEncodedBytes::from)
+                    .collect::<Vec<_>>()),
+            ],
+        ),
+        json_transaction_request(&account, sequence_number + 2, check_value, vec![json!("0")]),
+    ]);
+    let resp = context
+        .expect_status_code(200)
+        .post(
+            "/transactions/simulate_bundle?continue_on_failure=true",
+            bundle,
+        )
+        .await;
+    let results = resp.as_array().unwrap();
+    assert_eq!(results.len(), 3, "{}", pretty(&resp));
+    assert!(
+        results[1]["success"].as_bool().unwrap(),
+        "{}",
+        pretty(&resp)
+    );
+
+    // Each transaction is rendered against the state it was simulated on, so the aborts are
+    // explained by the version of the module which aborted.
+    let vm_status = |i: usize| results[i]["vm_status"].as_str().unwrap().to_string();
+    assert!(!results[0]["success"].as_bool().unwrap());
+    assert!(
+        vm_status(0).contains("The stored value is not the expected one."),
+        "{}",
+        pretty(&resp)
+    );
+    assert!(!results[2]["success"].as_bool().unwrap());
+    assert!(
+        vm_status(2).contains("The stored value plus one is not the expected one."),
+        "{}",
+        pretty(&resp)
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_bcs_simulate_cached_output() {
+    let mut context = new_test_context(current_function_name!());
+    let alice = &mut context.gen_account();
+    let bob = &mut context.gen_account();
+    let txn = context.mint_user_account(alice).await;
+    context.commit_block(&vec![txn]).await;
+
+    let txn = context.account_transfer_to(alice, bob.address(), SMALL_TRANSFER_AMOUNT);
+    let public_key = match txn.authenticator_ref() {
+        TransactionAuthenticator::Ed25519 { public_key, .. } => public_key.clone(),
+        _ => unreachable!("Simulation uses Ed25519 authenticator."),
+    };
+    let simulated = SignedTransaction::new_signed_transaction(
+        txn.clone().into_raw_transaction(),
+        TransactionAuthenticator::Ed25519 {
+            public_key,
+            signature: Ed25519Signature::dummy_signature(),
+        },
+    );
+    let body = bcs::to_bytes(&simulated).unwrap();
+
+    let output_hits = metrics::SIMULATION_CACHE.with_label_values(&["output", "hit"]);
+    let first = context
+        .expect_status_code(200)
+        .post_bcs_txn("/transactions/simulate", body.clone())
+        .await;
+    assert!(first[0]["success"].as_bool().unwrap(), "{}", pretty(&first));
+
+    // Simulating the same transaction at the same version is served from the cache.
+    let hits_before = output_hits.get();
+    let second = context
+        .expect_status_code(200)
+        .post_bcs_txn("/transactions/simulate", body.clone())
+        .await;
+    assert!(output_hits.get() > hits_before);
+    assert_eq!(first[0]["vm_status"], second[0]["vm_status"]);
+    assert_eq!(first[0]["gas_used"], second[0]["gas_used"]);
+    assert_eq!(first[0]["changes"], second[0]["changes"]);
+
+    // Once the transaction is committed, the cached output must not be reused.
+    context.commit_block(&vec![txn]).await;
+    let resp = context
+        .expect_status_code(200)
+        .post_bcs_txn("/transactions/simulate", body)
+        .await;
+    assert!(!resp[0]["success"].as_bool().unwrap());
+    assert!(resp[0]["vm_status"]
+        .as_str()
+        .is_some_and(|status| status.contains("SEQUENCE_NUMBER_TOO_OLD")));
+}
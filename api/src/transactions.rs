@@ -23,22 +23,28 @@ use anyhow::Context as AnyhowContext;
 use aptos_api_types::{
     verify_function_identifier, verify_module_identifier, Address, AptosError, AptosErrorCode,
     AsConverter, EncodeSubmissionRequest, GasEstimation, GasEstimationBcs, HashValue,
-    HexEncodedBytes, LedgerInfo, MoveType, PendingTransaction, SubmitTransactionRequest,
-    Transaction, TransactionData, TransactionOnChainData, TransactionsBatchSingleSubmissionFailure,
+    HexEncodedBytes, LedgerInfo, MoveType, PendingTransaction,
+    SimulateTransactionWithOverridesRequest, StateOverrides, SubmitTransactionRequest, Transaction,
+    TransactionData, TransactionOnChainData, TransactionsBatchSingleSubmissionFailure,
     TransactionsBatchSubmissionResult, UserTransaction, VerifyInput, VerifyInputWithRecursion, U64,
 };
 use aptos_crypto::{hash::CryptoHash, signing_message};
 use aptos_types::{
     account_address::AccountAddress,
     mempool_status::MempoolStatusCode,
+    state_store::{
+        state_overrides::{StateOverrides as RawStateOverrides, StateViewWithOverrides},
+        StateView,
+    },
     transaction::{
         EntryFunction, ExecutionStatus, MultisigTransactionPayload, RawTransaction,
-        RawTransactionWithData, SignedTransaction, TransactionPayload,
+        RawTransactionWithData, SignedTransaction, TransactionOutput, TransactionPayload,
+        TransactionStatus, Version,
     },
     vm_status::StatusCode,
     AptosCoinType, CoinType,
 };
-use aptos_vm::{AptosSimulationVM, AptosVM};
+use aptos_vm::AptosVM;
 use move_core_types::{ident_str, language_storage::ModuleId, vm_status::VMStatus};
 use poem_openapi::{
     param::{Path, Query},
@@ -505,118 +511,172 @@ impl TransactionsApi {
         self.context
             .check_api_output_enabled("Simulate transaction", &accept_type)?;
 
+        let api = self.clone();
+        api_spawn_blocking(move || {
+            api.simulate_with_gas_estimation(
+                &accept_type,
+                estimate_max_gas_amount.0.unwrap_or_default(),
+                estimate_gas_unit_price.0.unwrap_or_default(),
+                estimate_prioritized_gas_unit_price.0.unwrap_or_default(),
+                data,
+                StateOverrides::default(),
+            )
+        })
+        .await
+    }
+
+    /// Simulate transaction with state overrides
+    ///
+    /// Same as the simulate endpoint, but the transaction is executed against the latest
+    /// on-chain state with the given overrides applied on top of it. Resources, table items and
+    /// modules can be overridden, as well as the timestamp of the block the transaction is
+    /// executed in. This can be used to preview a transaction against hypothetical state, e.g.,
+    /// a different account balance or an upgraded module. The overrides are never persisted.
+    ///
+    /// The transaction must have a zero-padded signature, as for the simulate endpoint.
+    #[oai(
+        path = "/transactions/simulate_with_overrides",
+        method = "post",
+        operation_id = "simulate_transaction_with_overrides",
+        tag = "ApiTags::Transactions"
+    )]
+    async fn simulate_transaction_with_overrides(
+        &self,
+        accept_type: AcceptType,
+        /// If set to true, the max gas value in the transaction will be ignored
+        /// and the maximum possible gas will be used
+        estimate_max_gas_amount: Query<Option<bool>>,
+        /// If set to true, the gas unit price in the transaction will be ignored
+        /// and the estimated value will be used
+        estimate_gas_unit_price: Query<Option<bool>>,
+        /// If set to true, the transaction will use a higher price than the original
+        /// estimate.
+        estimate_prioritized_gas_unit_price: Query<Option<bool>>,
+        data: Json<SimulateTransactionWithOverridesRequest>,
+    ) -> SimulateTransactionResult<Vec<UserTransaction>> {
+        data.0
+            .verify()
+            .context("Simulated transaction invalid")
+            .map_err(|err| {
+                SubmitTransactionError::bad_request_with_code_no_info(
+                    err,
+                    AptosErrorCode::InvalidInput,
+                )
+            })?;
+        fail_point_poem("endpoint_simulate_transaction_with_overrides")?;
+        if !self.context.node_config.api.transaction_simulation_enabled {
+            return Err(api_disabled("Simulate transaction"));
+        }
+        self.context
+            .check_api_output_enabled("Simulate transaction", &accept_type)?;
+
+        let SimulateTransactionWithOverridesRequest {
+            transaction,
+            state_overrides,
+        } = data.0;
+        let api = self.clone();
+        api_spawn_blocking(move || {
+            api.simulate_with_gas_estimation(
+                &accept_type,
+                estimate_max_gas_amount.0.unwrap_or_default(),
+                estimate_gas_unit_price.0.unwrap_or_default(),
+                estimate_prioritized_gas_unit_price.0.unwrap_or_default(),
+                SubmitTransactionPost::Json(Json(transaction)),
+                state_overrides,
+            )
+        })
+        .await
+    }
+
+    /// Simulate transaction bundle
+    ///
+    /// Simulates a list of transactions which are executed in order, each one on top of the
+    /// writes of the previous ones. This can be used to preview a sequence of transactions where
+    /// later transactions depend on earlier ones, e.g., an approval followed by a swap. The output
+    /// of each simulated transaction is returned, including its events, changes and gas used.
+    /// The `X-Aptos-Gas-Used` header contains the total gas used by the bundle.
+    ///
+    /// By default, simulation stops at the first transaction which does not succeed, which is
+    /// then the last one returned. If `continue_on_failure` is set, the remaining transactions
+    /// are simulated as well, on top of the writes of the failed transaction (e.g., its gas
+    /// charge).
+    ///
+    /// As with the simulate endpoint, all transactions must have a zero-padded signature. To use
+    /// this endpoint with BCS, you must submit a vector of SignedTransaction encoded as BCS.
+    #[oai(
+        path = "/transactions/simulate_bundle",
+        method = "post",
+        operation_id = "simulate_transaction_bundle",
+        tag = "ApiTags::Transactions"
+    )]
+    async fn simulate_transaction_bundle(
+        &self,
+        accept_type: AcceptType,
+        /// If set to true, transactions after a failed transaction are still simulated
+        continue_on_failure: Query<Option<bool>>,
+        data: SubmitTransactionsBatchPost,
+    ) -> SimulateTransactionResult<Vec<UserTransaction>> {
+        data.verify()
+            .context("Simulated transactions invalid")
+            .map_err(|err| {
+                SubmitTransactionError::bad_request_with_code_no_info(
+                    err,
+                    AptosErrorCode::InvalidInput,
+                )
+            })?;
+        fail_point_poem("endpoint_simulate_transaction_bundle")?;
+        if !self.context.node_config.api.transaction_simulation_enabled {
+            return Err(api_disabled("Simulate transaction bundle"));
+        }
+        self.context
+            .check_api_output_enabled("Simulate transaction bundle", &accept_type)?;
+
         let api = self.clone();
         let context = self.context.clone();
         api_spawn_blocking(move || {
             let ledger_info = context.get_latest_ledger_info()?;
-            let mut signed_transaction = api.get_signed_transaction(&ledger_info, data)?;
-
-            // Confirm the simulation filter allows the transaction. We use HashValue::zero()
-            // here for the block ID because we don't allow filtering by block ID for the
-            // simulation filters. See the ConfigSanitizer for ApiConfig.
-            if !context.node_config.api.simulation_filter.allows(
-                aptos_crypto::HashValue::zero(),
-                ledger_info.timestamp(),
-                &signed_transaction,
-            ) {
-                return Err(SubmitTransactionError::forbidden_with_code(
-                    "Transaction not allowed by simulation filter",
+            let signed_transactions = api.get_signed_transactions_batch(&ledger_info, data)?;
+            if signed_transactions.is_empty() {
+                return Err(SubmitTransactionError::bad_request_with_code(
+                    "No transactions to simulate",
+                    AptosErrorCode::InvalidInput,
+                    &ledger_info,
+                ));
+            }
+            if context.max_submit_transaction_batch_size() < signed_transactions.len() {
+                return Err(SubmitTransactionError::bad_request_with_code(
+                    format!(
+                        "Simulated too many transactions: {}, while limit is {}",
+                        signed_transactions.len(),
+                        context.max_submit_transaction_batch_size(),
+                    ),
                     AptosErrorCode::InvalidInput,
                     &ledger_info,
                 ));
             }
 
-            let estimated_gas_unit_price = match (
-                estimate_gas_unit_price.0.unwrap_or_default(),
-                estimate_prioritized_gas_unit_price.0.unwrap_or_default(),
-            ) {
-                (_, true) => {
-                    let gas_estimation = context.estimate_gas_price(&ledger_info)?;
-                    // The prioritized gas estimate should always be set, but if it's not use the gas estimate
-                    Some(
-                        gas_estimation
-                            .prioritized_gas_estimate
-                            .unwrap_or(gas_estimation.gas_estimate),
-                    )
-                },
-                (true, false) => Some(context.estimate_gas_price(&ledger_info)?.gas_estimate),
-                (false, false) => None,
-            };
-
-            // If estimate max gas amount is provided, we will just make it the maximum value
-            let estimated_max_gas_amount = if estimate_max_gas_amount.0.unwrap_or_default() {
-                // Retrieve max possible gas units
-                let (_, gas_params) = context.get_gas_schedule(&ledger_info)?;
-                let min_number_of_gas_units =
-                    u64::from(gas_params.vm.txn.min_transaction_gas_units)
-                        / u64::from(gas_params.vm.txn.gas_unit_scaling_factor);
-                let max_number_of_gas_units =
-                    u64::from(gas_params.vm.txn.maximum_number_of_gas_units);
-
-                // Retrieve account balance to determine max gas available, right now this is using
-                // a view function, but we may want to re-evaluate this based on performance
-                let (_, _, state_view) = context
-                    .state_view::<BasicErrorWith404>(Option::None)
-                    .map_err(|err| {
-                        SubmitTransactionError::bad_request_with_code_no_info(
-                            err,
-                            AptosErrorCode::InvalidInput,
-                        )
-                    })?;
-                let output = AptosVM::execute_view_function(
-                    &state_view,
-                    ModuleId::new(AccountAddress::ONE, ident_str!("coin").into()),
-                    ident_str!("balance").into(),
-                    vec![AptosCoinType::type_tag()],
-                    vec![signed_transaction.sender().to_vec()],
-                    context.node_config.api.max_gas_view_function,
-                );
-                let values = output.values.map_err(|err| {
-                    SubmitTransactionError::bad_request_with_code_no_info(
-                        err,
-                        AptosErrorCode::InvalidInput,
-                    )
-                })?;
-                let balance: u64 = bcs::from_bytes(&values[0]).map_err(|err| {
-                    SubmitTransactionError::bad_request_with_code_no_info(
-                        err,
+            // Confirm the simulation filter allows all the transactions. See
+            // simulate_with_gas_estimation for why the block ID is zero.
+            for signed_transaction in &signed_transactions {
+                if !context.node_config.api.simulation_filter.allows(
+                    aptos_crypto::HashValue::zero(),
+                    ledger_info.timestamp(),
+                    signed_transaction,
+                ) {
+                    return Err(SubmitTransactionError::forbidden_with_code(
+                        "Transaction not allowed by simulation filter",
                         AptosErrorCode::InvalidInput,
-                    )
-                })?;
-
-                let gas_unit_price =
-                    estimated_gas_unit_price.unwrap_or_else(|| signed_transaction.gas_unit_price());
-
-                // With 0 gas price, we set it to max gas units, since we can't divide by 0
-                let max_account_gas_units = if gas_unit_price == 0 {
-                    balance
-                } else {
-                    balance / gas_unit_price
-                };
-
-                // To give better error messaging, we should not go below the minimum number of gas units
-                let max_account_gas_units =
-                    std::cmp::max(min_number_of_gas_units, max_account_gas_units);
-
-                // Minimum of the max account and the max total needs to be used for estimation
-                Some(std::cmp::min(
-                    max_account_gas_units,
-                    max_number_of_gas_units,
-                ))
-            } else {
-                None
-            };
-
-            // If there is an estimation of either, replace the values
-            if estimated_max_gas_amount.is_some() || estimated_gas_unit_price.is_some() {
-                signed_transaction = override_gas_parameters(
-                    &signed_transaction,
-                    estimated_max_gas_amount,
-                    estimated_gas_unit_price,
-                );
+                        &ledger_info,
+                    ));
+                }
             }
 
-            api.simulate(&accept_type, ledger_info, signed_transaction)
+            api.simulate_bundle(
+                &accept_type,
+                ledger_info,
+                signed_transactions,
+                continue_on_failure.0.unwrap_or_default(),
+            )
         })
         .await
     }
@@ -1365,6 +1425,138 @@ impl TransactionsApi {
         ))
     }
 
+    /// Simulates a transaction against the latest state with the given overrides applied,
+    /// estimating its gas parameters first if requested.
+    fn simulate_with_gas_estimation(
+        &self,
+        accept_type: &AcceptType,
+        estimate_max_gas_amount: bool,
+        estimate_gas_unit_price: bool,
+        estimate_prioritized_gas_unit_price: bool,
+        data: SubmitTransactionPost,
+        state_overrides: StateOverrides,
+    ) -> SimulateTransactionResult<Vec<UserTransaction>> {
+        let ledger_info = self.context.get_latest_ledger_info()?;
+        let mut signed_transaction = self.get_signed_transaction(&ledger_info, data)?;
+
+        // Confirm the simulation filter allows the transaction. We use HashValue::zero()
+        // here for the block ID because we don't allow filtering by block ID for the
+        // simulation filters. See the ConfigSanitizer for ApiConfig.
+        if !self.context.node_config.api.simulation_filter.allows(
+            aptos_crypto::HashValue::zero(),
+            ledger_info.timestamp(),
+            &signed_transaction,
+        ) {
+            return Err(SubmitTransactionError::forbidden_with_code(
+                "Transaction not allowed by simulation filter",
+                AptosErrorCode::InvalidInput,
+                &ledger_info,
+            ));
+        }
+
+        let base_state_view = self.context.latest_state_view_poem(&ledger_info)?;
+        let state_overrides = state_overrides
+            .try_into_raw_overrides(&base_state_view)
+            .context("Invalid state overrides")
+            .map_err(|err| {
+                SubmitTransactionError::bad_request_with_code(
+                    err,
+                    AptosErrorCode::InvalidInput,
+                    &ledger_info,
+                )
+            })?;
+        let state_view = StateViewWithOverrides::new(&base_state_view, &state_overrides);
+        // Simulation caches are only used for simulations against the unmodified state at the
+        // version, as state overrides can also override code.
+        let cache_version = state_overrides.is_empty().then(|| ledger_info.version());
+
+        let estimated_gas_unit_price =
+            match (estimate_gas_unit_price, estimate_prioritized_gas_unit_price) {
+                (_, true) => {
+                    let gas_estimation = self.context.estimate_gas_price(&ledger_info)?;
+                    // The prioritized gas estimate should always be set, but if it's not use the gas estimate
+                    Some(
+                        gas_estimation
+                            .prioritized_gas_estimate
+                            .unwrap_or(gas_estimation.gas_estimate),
+                    )
+                },
+                (true, false) => Some(self.context.estimate_gas_price(&ledger_info)?.gas_estimate),
+                (false, false) => None,
+            };
+
+        // If estimate max gas amount is provided, we will just make it the maximum value
+        let estimated_max_gas_amount = if estimate_max_gas_amount {
+            // Retrieve max possible gas units
+            let (_, gas_params) = self.context.get_gas_schedule(&ledger_info)?;
+            let min_number_of_gas_units = u64::from(gas_params.vm.txn.min_transaction_gas_units)
+                / u64::from(gas_params.vm.txn.gas_unit_scaling_factor);
+            let max_number_of_gas_units = u64::from(gas_params.vm.txn.maximum_number_of_gas_units);
+
+            // Retrieve account balance to determine max gas available, right now this is using
+            // a view function, but we may want to re-evaluate this based on performance
+            let output = AptosVM::execute_view_function(
+                &state_view,
+                ModuleId::new(AccountAddress::ONE, ident_str!("coin").into()),
+                ident_str!("balance").into(),
+                vec![AptosCoinType::type_tag()],
+                vec![signed_transaction.sender().to_vec()],
+                self.context.node_config.api.max_gas_view_function,
+            );
+            let values = output.values.map_err(|err| {
+                SubmitTransactionError::bad_request_with_code_no_info(
+                    err,
+                    AptosErrorCode::InvalidInput,
+                )
+            })?;
+            let balance: u64 = bcs::from_bytes(&values[0]).map_err(|err| {
+                SubmitTransactionError::bad_request_with_code_no_info(
+                    err,
+                    AptosErrorCode::InvalidInput,
+                )
+            })?;
+
+            let gas_unit_price =
+                estimated_gas_unit_price.unwrap_or_else(|| signed_transaction.gas_unit_price());
+
+            // With 0 gas price, we set it to max gas units, since we can't divide by 0
+            let max_account_gas_units = if gas_unit_price == 0 {
+                balance
+            } else {
+                balance / gas_unit_price
+            };
+
+            // To give better error messaging, we should not go below the minimum number of gas units
+            let max_account_gas_units =
+                std::cmp::max(min_number_of_gas_units, max_account_gas_units);
+
+            // Minimum of the max account and the max total needs to be used for estimation
+            Some(std::cmp::min(
+                max_account_gas_units,
+                max_number_of_gas_units,
+            ))
+        } else {
+            None
+        };
+
+        // If there is an estimation of either, replace the values
+        if estimated_max_gas_amount.is_some() || estimated_gas_unit_price.is_some() {
+            signed_transaction = override_gas_parameters(
+                &signed_transaction,
+                estimated_max_gas_amount,
+                estimated_gas_unit_price,
+            );
+        }
+
+        self.simulate(
+            accept_type,
+            ledger_info,
+            signed_transaction,
+            &state_view,
+            cache_version,
+        )
+    }
+
     // TODO: This function leverages a lot of types from aptos_types, use the
     // local API types and just return those directly, instead of converting
     // from these types in render_transactions.
@@ -1372,26 +1564,145 @@ impl TransactionsApi {
     ///
     /// Note: this returns a `Vec<UserTransaction>`, but for backwards compatibility, this can't
     /// be removed even though, there is only one possible transaction
+    ///
+    /// If `cache_version` is set, it must be the version of the state view, and the simulation
+    /// uses the simulation caches for it.
     pub fn simulate(
         &self,
         accept_type: &AcceptType,
         ledger_info: LedgerInfo,
         txn: SignedTransaction,
+        state_view: &impl StateView,
+        cache_version: Option<Version>,
+    ) -> SimulateTransactionResult<Vec<UserTransaction>> {
+        let (vm_status, output, simulated_txn) =
+            self.simulate_signed_transaction(&ledger_info, txn, state_view, cache_version)?;
+
+        let result = match accept_type {
+            AcceptType::Json => {
+                let user_transactions = self.render_simulated_transactions(
+                    &ledger_info,
+                    state_view,
+                    vec![(vm_status, simulated_txn)],
+                )?;
+                BasicResponse::try_from_json((
+                    user_transactions,
+                    &ledger_info,
+                    BasicResponseStatus::Ok,
+                ))
+            },
+            AcceptType::Bcs => {
+                BasicResponse::try_from_bcs((simulated_txn, &ledger_info, BasicResponseStatus::Ok))
+            },
+        };
+
+        result.map(|r| r.with_gas_used(Some(output.gas_used())))
+    }
+
+    /// Simulate a bundle of transactions in the VM
+    ///
+    /// The transactions are executed in order, each one on top of the writes of the previous
+    /// ones. Execution stops after the first transaction which does not succeed, unless
+    /// `continue_on_failure` is set. Only the executed transactions are returned, each one
+    /// rendered against the state it was simulated on.
+    pub fn simulate_bundle(
+        &self,
+        accept_type: &AcceptType,
+        ledger_info: LedgerInfo,
+        txns: Vec<SignedTransaction>,
+        continue_on_failure: bool,
     ) -> SimulateTransactionResult<Vec<UserTransaction>> {
+        let base_state_view = self.context.latest_state_view_poem(&ledger_info)?;
+
+        // Writes of the already simulated transactions, observed by the later ones
+        let mut bundle_writes = RawStateOverrides::new();
+        let mut total_gas_used = 0u64;
+        let mut user_transactions = Vec::new();
+        let mut simulated_txns = Vec::new();
+        for txn in txns {
+            let (output, success) = {
+                let state_view = StateViewWithOverrides::new(&base_state_view, &bundle_writes);
+                let (vm_status, output, simulated_txn) =
+                    self.simulate_signed_transaction(&ledger_info, txn, &state_view, None)?;
+                let success = matches!(
+                    output.status(),
+                    TransactionStatus::Keep(ExecutionStatus::Success)
+                );
+
+                // Like a single simulated transaction, each transaction is rendered against the
+                // state it was simulated on, so that later transactions (e.g., module upgrades)
+                // do not change how it is rendered.
+                match accept_type {
+                    AcceptType::Json => user_transactions.extend(
+                        self.render_simulated_transactions(&ledger_info, &state_view, vec![(
+                            vm_status,
+                            simulated_txn,
+                        )])?,
+                    ),
+                    AcceptType::Bcs => simulated_txns.push(simulated_txn),
+                }
+                (output, success)
+            };
+            total_gas_used = total_gas_used.saturating_add(output.gas_used());
+
+            if let TransactionStatus::Keep(_) = output.status() {
+                bundle_writes.apply_write_set(output.write_set());
+            }
+            if !success && !continue_on_failure {
+                break;
+            }
+        }
+
+        let result = match accept_type {
+            AcceptType::Json => BasicResponse::try_from_json((
+                user_transactions,
+                &ledger_info,
+                BasicResponseStatus::Ok,
+            )),
+            AcceptType::Bcs => {
+                BasicResponse::try_from_bcs((simulated_txns, &ledger_info, BasicResponseStatus::Ok))
+            },
+        };
+
+        result.map(|r| r.with_gas_used(Some(total_gas_used)))
+    }
+
+    /// Simulates a single transaction against the given state view, and builds up the
+    /// transaction data from its output. The simulation caches are used for `cache_version`, if
+    /// set.
+    fn simulate_signed_transaction(
+        &self,
+        ledger_info: &LedgerInfo,
+        txn: SignedTransaction,
+        state_view: &impl StateView,
+        cache_version: Option<Version>,
+    ) -> Result<(VMStatus, TransactionOutput, TransactionOnChainData), SubmitTransactionError> {
         // The caller must ensure that the signature is not valid, as otherwise
         // a malicious actor could execute the transaction without their knowledge
         if txn.verify_signature().is_ok() {
             return Err(SubmitTransactionError::bad_request_with_code(
                 "Simulated transactions must not have a valid signature",
                 AptosErrorCode::InvalidInput,
-                &ledger_info,
+                ledger_info,
             ));
         }
 
         // Simulate transaction
-        let state_view = self.context.latest_state_view_poem(&ledger_info)?;
-        let (vm_status, output) =
-            AptosSimulationVM::create_vm_and_simulate_signed_transaction(&txn, &state_view);
+        let (vm_status, output, cache_stats) = self
+            .context
+            .simulation_cache()
+            .simulate_signed_transaction(&txn, state_view, cache_version);
+        if let Some(hit) = cache_stats.output_cache_hit {
+            metrics::SIMULATION_CACHE
+                .with_label_values(&["output", if hit { "hit" } else { "miss" }])
+                .inc();
+        }
+        metrics::SIMULATION_CACHE
+            .with_label_values(&["module", "hit"])
+            .inc_by(cache_stats.module_cache_hits as u64);
+        metrics::SIMULATION_CACHE
+            .with_label_values(&["module", "miss"])
+            .inc_by(cache_stats.module_cache_misses as u64);
         let version = ledger_info.version();
 
         // Ensure that all known statuses return their values in the output (even if they aren't supposed to)
@@ -1454,54 +1765,56 @@ impl TransactionsApi {
             changes: output.write_set().clone(),
         };
 
-        let result = match accept_type {
-            AcceptType::Json => {
-                let transactions = self
-                    .context
-                    .render_transactions_non_sequential(&ledger_info, vec![simulated_txn])?;
-
-                // Users can only make requests to simulate UserTransactions, so unpack
-                // the Vec<Transaction> into Vec<UserTransaction>.
-                let mut user_transactions = Vec::new();
-                for transaction in transactions.into_iter() {
-                    match transaction {
-                        Transaction::UserTransaction(mut user_txn) => {
-                            match &vm_status {
-                                VMStatus::Error {
-                                    message: Some(msg), ..
-                                }
-                                | VMStatus::ExecutionFailure {
-                                    message: Some(msg), ..
-                                } => {
-                                    user_txn.info.vm_status +=
-                                        format!("\nExecution failed with message: {}", msg)
-                                            .as_str();
-                                },
-                                _ => (),
-                            }
-                            user_transactions.push(user_txn);
-                        },
-                        _ => {
-                            return Err(SubmitTransactionError::internal_with_code(
-                                "Simulation transaction resulted in a non-UserTransaction",
-                                AptosErrorCode::InternalError,
-                                &ledger_info,
-                            ))
+        Ok((vm_status, output, simulated_txn))
+    }
+
+    /// Renders simulated transactions as JSON, appending the VM error messages (if any) to
+    /// their VM status.
+    fn render_simulated_transactions(
+        &self,
+        ledger_info: &LedgerInfo,
+        state_view: &impl StateView,
+        simulated_txns: Vec<(VMStatus, TransactionOnChainData)>,
+    ) -> Result<Vec<UserTransaction>, SubmitTransactionError> {
+        let (vm_statuses, simulated_txns): (Vec<_>, Vec<_>) = simulated_txns.into_iter().unzip();
+        let transactions = self
+            .context
+            .render_transactions_non_sequential_with_state_view(
+                ledger_info,
+                state_view,
+                simulated_txns,
+            )?;
+
+        // Users can only make requests to simulate UserTransactions, so unpack
+        // the Vec<Transaction> into Vec<UserTransaction>.
+        let mut user_transactions = Vec::new();
+        for (transaction, vm_status) in transactions.into_iter().zip(vm_statuses) {
+            match transaction {
+                Transaction::UserTransaction(mut user_txn) => {
+                    match &vm_status {
+                        VMStatus::Error {
+                            message: Some(msg), ..
+                        }
+                        | VMStatus::ExecutionFailure {
+                            message: Some(msg), ..
+                        } => {
+                            user_txn.info.vm_status +=
+                                format!("\nExecution failed with message: {}", msg).as_str();
                         },
+                        _ => (),
                     }
-                }
-                BasicResponse::try_from_json((
-                    user_transactions,
-                    &ledger_info,
-                    BasicResponseStatus::Ok,
-                ))
-            },
-            AcceptType::Bcs => {
-                BasicResponse::try_from_bcs((simulated_txn, &ledger_info, BasicResponseStatus::Ok))
-            },
-        };
-
-        result.map(|r| r.with_gas_used(Some(output.gas_used())))
+                    user_transactions.push(user_txn);
+                },
+                _ => {
+                    return Err(SubmitTransactionError::internal_with_code(
+                        "Simulation transaction resulted in a non-UserTransaction",
+                        AptosErrorCode::InternalError,
+                        ledger_info,
+                    ))
+                },
+            }
+        }
+        Ok(user_transactions)
     }
 
     /// Encode message as BCS
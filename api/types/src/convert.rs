@@ -67,7 +67,7 @@ const OBJECT_STRUCT: &IdentStr = ident_str!("Object");
 /// JSON outputs
 pub struct MoveConverter<'a, S> {
     inner: AptosValueAnnotator<'a, S>,
-    db: Arc<dyn DbReader>,
+    db: Option<Arc<dyn DbReader>>,
     indexer_reader: Option<Arc<dyn IndexerReader>>,
 }
 
@@ -79,11 +79,21 @@ impl<'a, S: StateView> MoveConverter<'a, S> {
     ) -> Self {
         Self {
             inner: AptosValueAnnotator::new(inner),
-            db,
+            db: Some(db),
             indexer_reader,
         }
     }
 
+    /// Creates a converter which is not backed by a DB, e.g., to parse user input against a
+    /// state view outside of a node. Converting committed transactions fails with an error.
+    pub fn new_without_db(inner: &'a S) -> Self {
+        Self {
+            inner: AptosValueAnnotator::new(inner),
+            db: None,
+            indexer_reader: None,
+        }
+    }
+
     pub fn try_into_resources<'b>(
         &self,
         data: impl Iterator<Item = (StructTag, &'b [u8])>,
@@ -111,6 +121,11 @@ impl<'a, S: StateView> MoveConverter<'a, S> {
         false
     }
 
+    /// Returns the resource group the given resource is a member of, if any.
+    pub fn resource_group_of(&self, tag: &StructTag) -> Option<StructTag> {
+        self.inner.view_resource_group_member(tag)
+    }
+
     pub fn find_resource(
         &self,
         state_view: &impl StateView,
@@ -187,6 +202,8 @@ impl<'a, S: StateView> MoveConverter<'a, S> {
         };
         let aux_data = self
             .db
+            .as_ref()
+            .ok_or_else(|| format_err!("Cannot convert transactions without a DB"))?
             .get_transaction_auxiliary_data_by_version(data.version)?;
         let info = self.into_transaction_info(
             data.version,
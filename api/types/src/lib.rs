@@ -18,6 +18,7 @@ mod ledger_info;
 pub mod mime_types;
 mod move_types;
 mod state;
+mod state_overrides;
 mod table;
 pub mod transaction;
 mod view;
@@ -42,6 +43,9 @@ pub use move_types::{
 };
 use serde::{Deserialize, Deserializer};
 pub use state::RawStateValueRequest;
+pub use state_overrides::{
+    ResourceOverride, SimulateTransactionWithOverridesRequest, StateOverrides, TableItemOverride,
+};
 use std::str::FromStr;
 pub use table::{RawTableItemRequest, TableItemRequest};
 pub use transaction::{
@@ -0,0 +1,160 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    Address, HexEncodedBytes, MoveConverter, MoveStructTag, SubmitTransactionRequest, VerifyInput,
+    VerifyInputWithRecursion, U64,
+};
+use anyhow::Context;
+use aptos_types::state_store::{
+    state_overrides::{StateOverrides as RawStateOverrides, StateViewWithOverrides},
+    table::TableHandle,
+    StateView,
+};
+use move_binary_format::CompiledModule;
+use move_core_types::{
+    account_address::AccountAddress,
+    language_storage::{StructTag, TypeTag},
+};
+use poem_openapi::Object;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Overrides of the on-chain state used when simulating transactions
+///
+/// The overrides are layered on top of the state the transactions are simulated against, and
+/// are never persisted.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct StateOverrides {
+    /// Resources to override, with values in the same JSON format as returned by the API
+    #[serde(default)]
+    #[oai(default)]
+    pub resources: Vec<ResourceOverride>,
+    /// Table items to override, with BCS encoded keys and values
+    #[serde(default)]
+    #[oai(default)]
+    pub table_items: Vec<TableItemOverride>,
+    /// Modules to publish or upgrade, as BCS encoded bytecode
+    #[serde(default)]
+    #[oai(default)]
+    pub modules: Vec<HexEncodedBytes>,
+    /// Timestamp in microseconds of the block the transactions are simulated in
+    #[oai(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp_usecs: Option<U64>,
+}
+
+/// Override of a single resource
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct ResourceOverride {
+    /// Address the resource is stored under
+    pub address: Address,
+    /// Type of the resource
+    #[serde(rename = "type")]
+    #[oai(rename = "type")]
+    pub typ: MoveStructTag,
+    /// JSON value of the resource
+    pub data: Value,
+}
+
+/// Override of a single table item
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct TableItemOverride {
+    /// Table handle
+    pub handle: Address,
+    /// BCS encoded key of the table item
+    pub key: HexEncodedBytes,
+    /// BCS encoded value of the table item
+    pub value: HexEncodedBytes,
+}
+
+impl VerifyInput for StateOverrides {
+    fn verify(&self) -> anyhow::Result<()> {
+        for resource in &self.resources {
+            resource.typ.verify(0)?;
+        }
+        Ok(())
+    }
+}
+
+impl StateOverrides {
+    pub fn is_empty(&self) -> bool {
+        self.resources.is_empty()
+            && self.table_items.is_empty()
+            && self.modules.is_empty()
+            && self.timestamp_usecs.is_none()
+    }
+
+    /// Converts the overrides into raw state value overrides on top of `state_view`.
+    ///
+    /// Modules are applied first, so that resources are encoded using the struct layouts of the
+    /// overridden modules. Only the state view is needed, so overrides can also be converted
+    /// outside of a node, e.g., by the CLI.
+    pub fn try_into_raw_overrides<S: StateView>(
+        self,
+        state_view: &S,
+    ) -> anyhow::Result<RawStateOverrides> {
+        let mut code_overrides = RawStateOverrides::new();
+        for code in self.modules {
+            let module = CompiledModule::deserialize(&code.0)
+                .context("Failed to deserialize overridden module")?;
+            code_overrides.set_module(module.self_addr(), module.self_name(), code.0.into());
+        }
+
+        let code_view = StateViewWithOverrides::new(state_view, &code_overrides);
+        let converter = MoveConverter::new_without_db(&code_view);
+
+        let mut overrides = code_overrides.clone();
+        for resource in self.resources {
+            let address: AccountAddress = resource.address.into();
+            let struct_tag: StructTag = resource.typ.try_into()?;
+            let value = converter
+                .try_into_vm_value(
+                    &TypeTag::Struct(Box::new(struct_tag.clone())),
+                    resource.data,
+                )
+                .with_context(|| format!("Failed to parse overridden resource {}", struct_tag))?;
+            let bytes = value
+                .simple_serialize()
+                .with_context(|| format!("Failed to serialize resource {}", struct_tag))?
+                .into();
+            match converter.resource_group_of(&struct_tag) {
+                Some(group_tag) => overrides.set_resource_group_member(
+                    &code_view,
+                    &address,
+                    &group_tag,
+                    &struct_tag,
+                    bytes,
+                )?,
+                None => overrides.set_resource(&address, &struct_tag, bytes)?,
+            }
+        }
+
+        for item in self.table_items {
+            overrides.set_table_item(
+                &TableHandle(item.handle.into()),
+                &item.key.0,
+                item.value.0.into(),
+            );
+        }
+
+        if let Some(timestamp_usecs) = self.timestamp_usecs {
+            overrides.set_timestamp_usecs(timestamp_usecs.0)?;
+        }
+
+        Ok(overrides)
+    }
+}
+
+/// A request to simulate a transaction with overridden on-chain state
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct SimulateTransactionWithOverridesRequest {
+    pub transaction: SubmitTransactionRequest,
+    pub state_overrides: StateOverrides,
+}
+
+impl VerifyInput for SimulateTransactionWithOverridesRequest {
+    fn verify(&self) -> anyhow::Result<()> {
+        self.transaction.verify()?;
+        self.state_overrides.verify()
+    }
+}
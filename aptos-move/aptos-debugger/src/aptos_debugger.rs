@@ -2,7 +2,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use anyhow::{bail, format_err};
-use aptos_block_executor::txn_provider::{default::DefaultTxnProvider, TxnProvider};
+use aptos_block_executor::{
+    code_cache_global_manager::AptosModuleCacheManager,
+    txn_provider::{default::DefaultTxnProvider, TxnProvider},
+};
 use aptos_gas_profiling::{GasProfiler, TransactionGasLog};
 use aptos_rest_client::Client;
 use aptos_types::{
@@ -12,7 +15,10 @@ use aptos_types::{
         transaction_slice_metadata::TransactionSliceMetadata,
     },
     contract_event::ContractEvent,
-    state_store::TStateView,
+    state_store::{
+        state_overrides::{StateOverrides, StateViewWithOverrides},
+        StateView, TStateView,
+    },
     transaction::{
         signature_verified_transaction::SignatureVerifiedTransaction, BlockOutput,
         SignedTransaction, Transaction, TransactionInfo, TransactionOutput, TransactionPayload,
@@ -24,7 +30,8 @@ use aptos_validator_interface::{
     AptosValidatorInterface, DBDebuggerInterface, DebuggerStateView, RestDebuggerInterface,
 };
 use aptos_vm::{
-    aptos_vm::AptosVMBlockExecutor, data_cache::AsMoveResolver, AptosVM, VMBlockExecutor,
+    aptos_vm::AptosVMBlockExecutor, data_cache::AsMoveResolver, simulation_cache::SimulationCache,
+    AptosVM, VMBlockExecutor,
 };
 use aptos_vm_environment::environment::AptosEnvironment;
 use aptos_vm_logging::log_schema::AdapterLogSchema;
@@ -32,13 +39,23 @@ use aptos_vm_types::{module_and_script_storage::AsAptosCodeStorage, output::VMOu
 use itertools::Itertools;
 use std::{path::Path, sync::Arc, time::Instant};
 
+/// Maximum number of simulation outputs cached by the debugger.
+const MAX_CACHED_SIMULATION_OUTPUTS: usize = 1_000;
+
 pub struct AptosDebugger {
     debugger: Arc<dyn AptosValidatorInterface + Send>,
+    simulation_cache: SimulationCache,
 }
 
 impl AptosDebugger {
     pub fn new(debugger: Arc<dyn AptosValidatorInterface + Send>) -> Self {
-        Self { debugger }
+        Self {
+            debugger,
+            simulation_cache: SimulationCache::new(
+                MAX_CACHED_SIMULATION_OUTPUTS,
+                Arc::new(AptosModuleCacheManager::new()),
+            ),
+        }
     }
 
     pub fn rest_client(rest_client: Client) -> anyhow::Result<Self> {
@@ -112,6 +129,61 @@ impl AptosDebugger {
         Ok(result)
     }
 
+    /// Executes a block of transactions on top of the state at the given version, where some of
+    /// the state is replaced by the overrides, e.g., to replay past transactions with a different
+    /// gas schedule or feature flags.
+    pub fn execute_transactions_at_version_with_overrides(
+        &self,
+        version: Version,
+        txns: Vec<Transaction>,
+        overrides: &StateOverrides,
+        concurrency_level: usize,
+    ) -> anyhow::Result<Vec<TransactionOutput>> {
+        let sig_verified_txns: Vec<SignatureVerifiedTransaction> =
+            txns.into_iter().map(|x| x.into()).collect::<Vec<_>>();
+        let txn_provider = DefaultTxnProvider::new(sig_verified_txns);
+        let state_view = DebuggerStateView::new(self.debugger.clone(), version);
+        let state_view = StateViewWithOverrides::new(&state_view, overrides);
+
+        let result = execute_block_no_limit(&txn_provider, &state_view, concurrency_level)
+            .map_err(|err| format_err!("Unexpected VM Error: {:?}", err))?;
+        assert_eq!(txn_provider.num_txns(), result.len());
+        Ok(result)
+    }
+
+    /// Simulates a transaction (i.e., executes it without verifying its signature) on top of the
+    /// state at the given version. Outputs and verified code are cached across simulations, so
+    /// repeated simulations at the same version are not executed from scratch.
+    /// *Precondition:* the transaction must **not** have a valid signature.
+    pub fn simulate_transaction_at_version(
+        &self,
+        version: Version,
+        txn: &SignedTransaction,
+    ) -> (VMStatus, TransactionOutput) {
+        let state_view = DebuggerStateView::new(self.debugger.clone(), version);
+        let (vm_status, output, _) =
+            self.simulation_cache
+                .simulate_signed_transaction(txn, &state_view, Some(version));
+        (vm_status, output)
+    }
+
+    /// Same as [Self::simulate_transaction_at_version], but some of the state is replaced by the
+    /// overrides. Such simulations do not use the caches, as overrides can also override code.
+    /// *Precondition:* the transaction must **not** have a valid signature.
+    pub fn simulate_transaction_at_version_with_overrides(
+        &self,
+        version: Version,
+        txn: &SignedTransaction,
+        overrides: &StateOverrides,
+    ) -> (VMStatus, TransactionOutput) {
+        let state_view = DebuggerStateView::new(self.debugger.clone(), version);
+        let state_view = StateViewWithOverrides::new(&state_view, overrides);
+        let (vm_status, output, _) =
+            self.simulation_cache
+                .simulate_signed_transaction(txn, &state_view, None);
+        (vm_status, output)
+    }
+
     pub fn execute_transaction_at_version_with_gas_profiler(
         &self,
         version: Version,
@@ -430,7 +502,7 @@ fn is_reconfiguration(vm_output: &TransactionOutput) -> bool {
 
 fn execute_block_no_limit(
     txn_provider: &DefaultTxnProvider<SignatureVerifiedTransaction>,
-    state_view: &DebuggerStateView,
+    state_view: &(impl StateView + Sync),
     concurrency_level: usize,
 ) -> Result<Vec<TransactionOutput>, VMStatus> {
     let executor = AptosVMBlockExecutor::new();
@@ -0,0 +1,574 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Replays past transactions under two gas schedules and reports how a gas schedule change
+//! would affect real traffic: per-transaction gas deltas, transactions which would newly run out
+//! of gas, and a summary per module and entry function.
+//!
+//! Both schedules are applied as state overrides on top of the pre-block state of every replayed
+//! block, so transactions observe the same state as on-chain except for the gas schedule.
+
+use crate::{aptos_debugger::AptosDebugger, common::Opts};
+use anyhow::{bail, Context, Result};
+use aptos_gas_schedule::{
+    AptosGasParameters, InitialGasSchedule, ToOnChainGasSchedule, LATEST_GAS_FEATURE_VERSION,
+};
+use aptos_rest_client::Client;
+use aptos_types::{
+    on_chain_config::{DiffItem, GasScheduleV2, OnChainConfig},
+    state_store::{state_key::StateKey, state_overrides::StateOverrides},
+    transaction::{
+        ExecutionStatus, MultisigTransactionPayload, SignedTransaction, Transaction,
+        TransactionInfo, TransactionOutput, TransactionPayload, TransactionStatus, Version,
+    },
+};
+use clap::Parser;
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+use url::Url;
+
+/// Replays committed transactions with a baseline and a proposed gas schedule. Transactions are
+/// fetched from a REST endpoint or a local DB, a backup can be analyzed after restoring it into a
+/// DB with `aptos-debugger aptos-db restore`.
+#[derive(Parser)]
+#[clap(group(clap::ArgGroup::new("baseline")
+        .multiple(false)
+        .args(&["baseline_gas_feature_version", "baseline_gas_schedule_file"]),
+))]
+#[clap(group(clap::ArgGroup::new("proposed")
+        .required(true)
+        .multiple(false)
+        .args(&["gas_feature_version", "gas_schedule_file"]),
+))]
+pub struct Command {
+    #[clap(flatten)]
+    opts: Opts,
+
+    #[clap(long)]
+    begin_version: u64,
+
+    #[clap(long)]
+    limit: u64,
+
+    /// Gas feature version of the baseline schedule, built from the gas parameters of this
+    /// release. If no baseline is set, transactions are replayed with the on-chain gas schedule.
+    ///
+    /// Schedules built from the gas parameters of this release only differ in the parameters
+    /// gated by their feature versions, use gas schedule files to compare parameter values.
+    #[clap(long, group = "baseline")]
+    baseline_gas_feature_version: Option<u64>,
+
+    /// Path to a baseline `GasScheduleV2`, either as JSON or BCS (if the extension is `.bcs`).
+    #[clap(long, group = "baseline")]
+    baseline_gas_schedule_file: Option<PathBuf>,
+
+    /// Gas feature version of the proposed schedule, built from the gas parameters of this
+    /// release.
+    #[clap(long, group = "proposed")]
+    gas_feature_version: Option<u64>,
+
+    /// Path to a proposed `GasScheduleV2`, either as JSON or BCS (if the extension is `.bcs`).
+    #[clap(long, group = "proposed")]
+    gas_schedule_file: Option<PathBuf>,
+
+    /// Number of entry functions and modules to list in the summary.
+    #[clap(long, default_value_t = 20)]
+    top: usize,
+
+    /// If set, the full report including per-transaction gas deltas is written to this file as
+    /// JSON.
+    #[clap(long)]
+    output: Option<PathBuf>,
+}
+
+impl Command {
+    pub async fn run(self) -> Result<()> {
+        let debugger = if let Some(rest_endpoint) = self.opts.target.rest_endpoint {
+            AptosDebugger::rest_client(Client::new(Url::parse(&rest_endpoint)?))?
+        } else if let Some(db_path) = self.opts.target.db_path {
+            AptosDebugger::db(db_path)?
+        } else {
+            unreachable!("Must provide one target.");
+        };
+        let concurrency_level = self.opts.concurrency_level.first().copied().unwrap_or(1);
+
+        let onchain_gas_schedule =
+            GasScheduleV2::fetch_config(&debugger.state_view_at_version(self.begin_version))
+                .context("Failed to fetch the on-chain gas schedule")?;
+        let baseline_gas_schedule = load_gas_schedule(
+            self.baseline_gas_feature_version,
+            self.baseline_gas_schedule_file.as_deref(),
+        )?;
+        let proposed_gas_schedule =
+            load_gas_schedule(self.gas_feature_version, self.gas_schedule_file.as_deref())?
+                .context("Must provide a proposed gas schedule")?;
+
+        print_gas_schedule_diff(
+            baseline_gas_schedule
+                .as_ref()
+                .unwrap_or(&onchain_gas_schedule),
+            &proposed_gas_schedule,
+        );
+
+        let baseline_overrides = match &baseline_gas_schedule {
+            Some(gas_schedule) => gas_schedule_override(gas_schedule)?,
+            None => StateOverrides::new(),
+        };
+        let proposed_overrides = gas_schedule_override(&proposed_gas_schedule)?;
+
+        let (txns, txn_infos) = debugger
+            .get_committed_transactions(self.begin_version, self.limit)
+            .await?;
+
+        let mut report = GasImpactReport::default();
+        let mut num_mismatches = 0;
+        for (version, block, block_txn_infos) in
+            split_into_blocks(self.begin_version, txns, txn_infos)
+        {
+            let baseline_outputs = debugger.execute_transactions_at_version_with_overrides(
+                version,
+                block.clone(),
+                &baseline_overrides,
+                concurrency_level,
+            )?;
+            let proposed_outputs = debugger.execute_transactions_at_version_with_overrides(
+                version,
+                block.clone(),
+                &proposed_overrides,
+                concurrency_level,
+            )?;
+
+            for (idx, txn) in block.iter().enumerate() {
+                let txn_version = version + idx as Version;
+                if baseline_gas_schedule.is_none()
+                    && baseline_outputs[idx]
+                        .ensure_match_transaction_info(
+                            txn_version,
+                            &block_txn_infos[idx],
+                            None,
+                            None,
+                        )
+                        .is_err()
+                {
+                    num_mismatches += 1;
+                }
+                if let Some(txn) = txn.try_as_signed_user_txn() {
+                    report.add(TransactionGasImpact::new(
+                        txn_version,
+                        txn,
+                        &baseline_outputs[idx],
+                        &proposed_outputs[idx],
+                    ));
+                }
+            }
+            report.num_blocks += 1;
+        }
+
+        if num_mismatches > 0 {
+            println!(
+                "WARNING: {} replayed transactions do not match their on-chain outputs, the \
+                 baseline may not be accurate",
+                num_mismatches
+            );
+        }
+        report.print_summary(self.top);
+
+        if let Some(output) = self.output {
+            fs::write(&output, serde_json::to_vec_pretty(&report)?)
+                .with_context(|| format!("Failed to write the report to {}", output.display()))?;
+            println!("Report written to {}", output.display());
+        }
+        Ok(())
+    }
+}
+
+/// Constructs the gas schedule of this release for the given gas feature version.
+fn release_gas_schedule(feature_version: u64) -> Result<GasScheduleV2> {
+    if feature_version > LATEST_GAS_FEATURE_VERSION {
+        bail!(
+            "Gas feature version {} is newer than the latest supported version {}",
+            feature_version,
+            LATEST_GAS_FEATURE_VERSION
+        );
+    }
+    Ok(GasScheduleV2 {
+        feature_version,
+        entries: AptosGasParameters::initial().to_on_chain_gas_schedule(feature_version),
+    })
+}
+
+/// Returns the gas schedule given either by its gas feature version or by its file, or [None] if
+/// neither is given.
+fn load_gas_schedule(
+    feature_version: Option<u64>,
+    path: Option<&Path>,
+) -> Result<Option<GasScheduleV2>> {
+    match (feature_version, path) {
+        (Some(feature_version), None) => release_gas_schedule(feature_version).map(Some),
+        (None, Some(path)) => read_gas_schedule(path).map(Some),
+        (None, None) => Ok(None),
+        (Some(_), Some(_)) => {
+            bail!("A gas schedule can be given either by its feature version or by its file")
+        },
+    }
+}
+
+fn read_gas_schedule(path: &Path) -> Result<GasScheduleV2> {
+    let bytes = fs::read(path)
+        .with_context(|| format!("Failed to read gas schedule from {}", path.display()))?;
+    let gas_schedule = if path.extension().is_some_and(|ext| ext == "bcs") {
+        bcs::from_bytes(&bytes)?
+    } else {
+        serde_json::from_slice(&bytes)?
+    };
+    Ok(gas_schedule)
+}
+
+fn gas_schedule_override(gas_schedule: &GasScheduleV2) -> Result<StateOverrides> {
+    let mut overrides = StateOverrides::new();
+    overrides.set(
+        StateKey::on_chain_config::<GasScheduleV2>()?,
+        bcs::to_bytes(gas_schedule)?.into(),
+    );
+    Ok(overrides)
+}
+
+fn print_gas_schedule_diff(baseline: &GasScheduleV2, proposed: &GasScheduleV2) {
+    println!(
+        "Gas feature version: {} -> {}",
+        baseline.feature_version, proposed.feature_version
+    );
+    let diff = GasScheduleV2::diff(baseline, proposed);
+    if diff.is_empty() && baseline.feature_version == proposed.feature_version {
+        println!("WARNING: The baseline and proposed gas schedules are identical");
+    }
+    println!("Gas schedule changes ({} parameters):", diff.len());
+    for (name, item) in diff {
+        match item {
+            DiffItem::Add { new_val } => println!("    + {}: {}", name, new_val),
+            DiffItem::Delete { old_val } => println!("    - {}: {}", name, old_val),
+            DiffItem::Modify { old_val, new_val } => {
+                println!("    ~ {}: {} -> {}", name, old_val, new_val)
+            },
+        }
+    }
+}
+
+/// Splits transactions into the blocks they were committed in, together with the version of the
+/// first transaction in every block.
+fn split_into_blocks(
+    begin: Version,
+    txns: Vec<Transaction>,
+    txn_infos: Vec<TransactionInfo>,
+) -> Vec<(Version, Vec<Transaction>, Vec<TransactionInfo>)> {
+    let mut blocks: Vec<(Version, Vec<Transaction>, Vec<TransactionInfo>)> = vec![];
+    let mut version = begin;
+    for (txn, txn_info) in txns.into_iter().zip(txn_infos) {
+        match blocks.last_mut() {
+            Some((_, block, block_txn_infos)) if !txn.is_block_start() => {
+                block.push(txn);
+                block_txn_infos.push(txn_info);
+            },
+            _ => blocks.push((version, vec![txn], vec![txn_info])),
+        }
+        version += 1;
+    }
+    blocks
+}
+
+/// Name of the function called by the transaction, used to group transactions in the summary.
+fn function_name(txn: &SignedTransaction) -> (String, String) {
+    let entry_function = match txn.payload() {
+        TransactionPayload::EntryFunction(entry_function) => Some(entry_function),
+        TransactionPayload::Multisig(multisig) => {
+            multisig
+                .transaction_payload
+                .as_ref()
+                .map(|payload| match payload {
+                    MultisigTransactionPayload::EntryFunction(entry_function) => entry_function,
+                })
+        },
+        TransactionPayload::Script(_) | TransactionPayload::ModuleBundle(_) => None,
+    };
+    match entry_function {
+        Some(entry_function) => {
+            let module = entry_function.module().short_str_lossless();
+            let function = format!("{}::{}", module, entry_function.function());
+            (module, function)
+        },
+        None => {
+            let name = match txn.payload() {
+                TransactionPayload::Multisig(_) => "multisig",
+                _ => "script",
+            };
+            (name.to_string(), name.to_string())
+        },
+    }
+}
+
+fn is_out_of_gas(status: &TransactionStatus) -> bool {
+    matches!(status, TransactionStatus::Keep(ExecutionStatus::OutOfGas))
+}
+
+/// Gas used by a single transaction under both gas schedules.
+#[derive(Debug, Serialize)]
+struct TransactionGasImpact {
+    version: Version,
+    module: String,
+    function: String,
+    max_gas_amount: u64,
+    baseline_gas_used: u64,
+    proposed_gas_used: u64,
+    baseline_status: String,
+    proposed_status: String,
+    newly_out_of_gas: bool,
+}
+
+impl TransactionGasImpact {
+    fn new(
+        version: Version,
+        txn: &SignedTransaction,
+        baseline: &TransactionOutput,
+        proposed: &TransactionOutput,
+    ) -> Self {
+        let (module, function) = function_name(txn);
+        Self {
+            version,
+            module,
+            function,
+            max_gas_amount: txn.max_gas_amount(),
+            baseline_gas_used: baseline.gas_used(),
+            proposed_gas_used: proposed.gas_used(),
+            baseline_status: format!("{:?}", baseline.status()),
+            proposed_status: format!("{:?}", proposed.status()),
+            newly_out_of_gas: is_out_of_gas(proposed.status()) && !is_out_of_gas(baseline.status()),
+        }
+    }
+
+    fn status_changed(&self) -> bool {
+        self.baseline_status != self.proposed_status
+    }
+}
+
+/// Aggregated gas usage of a group of transactions.
+#[derive(Debug, Default, Serialize)]
+struct GasImpactSummary {
+    num_txns: usize,
+    baseline_gas_used: u64,
+    proposed_gas_used: u64,
+    num_newly_out_of_gas: usize,
+}
+
+impl GasImpactSummary {
+    fn add(&mut self, impact: &TransactionGasImpact) {
+        self.num_txns += 1;
+        self.baseline_gas_used += impact.baseline_gas_used;
+        self.proposed_gas_used += impact.proposed_gas_used;
+        if impact.newly_out_of_gas {
+            self.num_newly_out_of_gas += 1;
+        }
+    }
+
+    fn delta(&self) -> i128 {
+        self.proposed_gas_used as i128 - self.baseline_gas_used as i128
+    }
+
+    fn delta_percentage(&self) -> f64 {
+        if self.baseline_gas_used == 0 {
+            return 0.0;
+        }
+        self.delta() as f64 * 100.0 / self.baseline_gas_used as f64
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
+struct GasImpactReport {
+    num_blocks: usize,
+    total: GasImpactSummary,
+    by_module: BTreeMap<String, GasImpactSummary>,
+    by_function: BTreeMap<String, GasImpactSummary>,
+    transactions: Vec<TransactionGasImpact>,
+}
+
+impl GasImpactReport {
+    fn add(&mut self, impact: TransactionGasImpact) {
+        self.total.add(&impact);
+        self.by_module
+            .entry(impact.module.clone())
+            .or_default()
+            .add(&impact);
+        self.by_function
+            .entry(impact.function.clone())
+            .or_default()
+            .add(&impact);
+        self.transactions.push(impact);
+    }
+
+    fn print_summary(&self, top: usize) {
+        println!(
+            "Replayed {} user transactions in {} blocks",
+            self.total.num_txns, self.num_blocks
+        );
+        println!(
+            "    gas used: {} -> {} ({:+.2}%)",
+            self.total.baseline_gas_used,
+            self.total.proposed_gas_used,
+            self.total.delta_percentage()
+        );
+        let count = |f: fn(&TransactionGasImpact) -> bool| {
+            self.transactions.iter().filter(|impact| f(impact)).count()
+        };
+        println!(
+            "    increased: {}, decreased: {}, unchanged: {}",
+            count(|impact| impact.proposed_gas_used > impact.baseline_gas_used),
+            count(|impact| impact.proposed_gas_used < impact.baseline_gas_used),
+            count(|impact| impact.proposed_gas_used == impact.baseline_gas_used),
+        );
+        println!(
+            "    newly out of gas: {}, other status changes: {}",
+            self.total.num_newly_out_of_gas,
+            count(|impact| impact.status_changed() && !impact.newly_out_of_gas),
+        );
+
+        for impact in self
+            .transactions
+            .iter()
+            .filter(|impact| impact.status_changed())
+        {
+            println!(
+                "    [{}] {}: {} -> {}, gas used {} -> {} (max {})",
+                impact.version,
+                impact.function,
+                impact.baseline_status,
+                impact.proposed_status,
+                impact.baseline_gas_used,
+                impact.proposed_gas_used,
+                impact.max_gas_amount,
+            );
+        }
+
+        print_top_summaries("Entry functions", &self.by_function, top);
+        print_top_summaries("Modules", &self.by_module, top);
+    }
+}
+
+/// Prints the groups with the largest absolute change in total gas used.
+fn print_top_summaries(title: &str, summaries: &BTreeMap<String, GasImpactSummary>, top: usize) {
+    let mut summaries = summaries.iter().collect::<Vec<_>>();
+    summaries.sort_by_key(|(_, summary)| std::cmp::Reverse(summary.delta().abs()));
+
+    println!("{} with the largest gas delta:", title);
+    println!(
+        "    {:>8} {:>14} {:>14} {:>9} {:>10}  name",
+        "txns", "baseline", "proposed", "delta", "newly oog"
+    );
+    for (name, summary) in summaries.into_iter().take(top) {
+        println!(
+            "    {:>8} {:>14} {:>14} {:>+8.2}% {:>10}  {}",
+            summary.num_txns,
+            summary.baseline_gas_used,
+            summary.proposed_gas_used,
+            summary.delta_percentage(),
+            summary.num_newly_out_of_gas,
+            name,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_temppath::TempPath;
+
+    fn impact(function: &str, baseline: u64, proposed: u64, oog: bool) -> TransactionGasImpact {
+        TransactionGasImpact {
+            version: 0,
+            module: function.rsplit_once("::").unwrap().0.to_string(),
+            function: function.to_string(),
+            max_gas_amount: 100,
+            baseline_gas_used: baseline,
+            proposed_gas_used: proposed,
+            baseline_status: "Keep(Success)".to_string(),
+            proposed_status: if oog {
+                "Keep(OutOfGas)"
+            } else {
+                "Keep(Success)"
+            }
+            .to_string(),
+            newly_out_of_gas: oog,
+        }
+    }
+
+    #[test]
+    fn test_report_aggregates_by_module_and_function() {
+        let mut report = GasImpactReport::default();
+        report.add(impact("0x1::coin::transfer", 10, 15, false));
+        report.add(impact("0x1::coin::transfer", 20, 30, false));
+        report.add(impact("0x1::coin::burn", 40, 100, true));
+        report.add(impact("0x1::object::transfer", 30, 30, false));
+
+        assert_eq!(report.total.num_txns, 4);
+        assert_eq!(report.total.delta(), 75);
+        assert_eq!(report.total.num_newly_out_of_gas, 1);
+
+        let transfer = &report.by_function["0x1::coin::transfer"];
+        assert_eq!(transfer.num_txns, 2);
+        assert_eq!(transfer.delta_percentage(), 50.0);
+
+        let coin = &report.by_module["0x1::coin"];
+        assert_eq!(coin.num_txns, 3);
+        assert_eq!(coin.delta(), 75);
+        assert_eq!(coin.num_newly_out_of_gas, 1);
+        assert_eq!(report.by_module["0x1::object"].delta(), 0);
+    }
+
+    #[test]
+    fn test_release_gas_schedule_rejects_unknown_version() {
+        assert!(release_gas_schedule(LATEST_GAS_FEATURE_VERSION).is_ok());
+        assert!(release_gas_schedule(LATEST_GAS_FEATURE_VERSION + 1).is_err());
+    }
+
+    #[test]
+    fn test_load_gas_schedules_from_files() {
+        let baseline = release_gas_schedule(LATEST_GAS_FEATURE_VERSION).unwrap();
+        let mut proposed = baseline.clone();
+        let (name, value) = proposed
+            .entries
+            .iter_mut()
+            .find(|(_, value)| *value > 0)
+            .unwrap();
+        *value *= 2;
+        let name = name.clone();
+
+        let dir = TempPath::new();
+        dir.create_as_dir().unwrap();
+        let baseline_path = dir.path().join("baseline.json");
+        let proposed_path = dir.path().join("proposed.bcs");
+        fs::write(&baseline_path, serde_json::to_vec(&baseline).unwrap()).unwrap();
+        fs::write(&proposed_path, bcs::to_bytes(&proposed).unwrap()).unwrap();
+
+        let loaded_baseline = load_gas_schedule(None, Some(&baseline_path))
+            .unwrap()
+            .unwrap();
+        let loaded_proposed = load_gas_schedule(None, Some(&proposed_path))
+            .unwrap()
+            .unwrap();
+        assert_eq!(loaded_baseline, baseline);
+        assert_eq!(loaded_proposed, proposed);
+
+        // Only the modified parameter differs between the schedules.
+        let diff = GasScheduleV2::diff(&loaded_baseline, &loaded_proposed);
+        assert_eq!(diff.len(), 1);
+        assert!(matches!(
+            diff[name.as_str()],
+            DiffItem::Modify { old_val, new_val } if new_val == 2 * old_val
+        ));
+
+        assert!(load_gas_schedule(None, None).unwrap().is_none());
+        assert!(load_gas_schedule(Some(LATEST_GAS_FEATURE_VERSION), Some(&baseline_path)).is_err());
+    }
+}
@@ -6,3 +6,4 @@ pub mod bcs_txn_decoder;
 pub mod common;
 pub mod execute_past_transactions;
 pub mod execute_pending_block;
+pub mod gas_schedule_impact;
@@ -2,7 +2,8 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use anyhow::{bail, format_err, Result};
+use crate::forked_state::{AdapterStorage, ForkedState};
+use anyhow::{bail, format_err, Context, Result};
 use aptos_api_types::AsConverter;
 use aptos_block_executor::txn_provider::default::DefaultTxnProvider;
 use aptos_crypto::{
@@ -11,7 +12,6 @@ use aptos_crypto::{
     ValidCryptoMaterialStringExt,
 };
 use aptos_gas_schedule::{InitialGasSchedule, TransactionGasParameters};
-use aptos_language_e2e_tests::data_store::{FakeDataStore, GENESIS_CHANGE_SET_HEAD};
 use aptos_resource_viewer::{AnnotatedMoveValue, AptosValueAnnotator};
 use aptos_types::{
     account_config::{aptos_test_root_address, AccountResource, CoinStoreResource},
@@ -63,14 +63,15 @@ use move_transactional_test_runner::{
 use move_vm_runtime::session::SerializedReturnValues;
 use once_cell::sync::Lazy;
 use std::{
-    collections::{BTreeMap, BTreeSet, HashMap},
+    collections::{BTreeMap, BTreeSet},
     convert::TryFrom,
     fmt,
-    path::Path,
+    path::{Path, PathBuf},
     string::String,
     sync::Arc,
 };
 use tempfile::NamedTempFile;
+use url::Url;
 
 /**
  * Definitions
@@ -84,7 +85,7 @@ use tempfile::NamedTempFile;
 ///   - It executes transactions through AptosVM, instead of MoveVM directly
 struct AptosTestAdapter<'a> {
     compiled_state: CompiledState<'a>,
-    storage: FakeDataStore,
+    storage: AdapterStorage,
     default_syntax: SyntaxChoice,
     private_key_mapping: BTreeMap<String, Ed25519PrivateKey>,
     #[allow(unused)]
@@ -157,6 +158,16 @@ struct AptosInitArgs {
     private_keys: Option<Vec<(Identifier, Ed25519PrivateKey)>>,
     #[clap(long = "initial-coins")]
     initial_coins: Option<u64>,
+    /// Fixture file recording the remote state the test is forked from. The test runs against
+    /// genesis state if not set.
+    #[clap(long = "fork-fixture")]
+    fork_fixture: Option<PathBuf>,
+    /// REST endpoint to fetch the forked state from, when recording the fixture.
+    #[clap(long = "fork-url", requires = "fork_fixture")]
+    fork_url: Option<Url>,
+    /// Version to fork the state at. Defaults to the latest version when recording the fixture.
+    #[clap(long = "fork-version", requires = "fork_fixture")]
+    fork_version: Option<u64>,
 }
 
 /// A raw private key -- either a literal or an unresolved name.
@@ -612,7 +623,7 @@ impl<'a> MoveTestAdapter<'a> for AptosTestAdapter<'a> {
         pre_compiled_deps_v1: Option<&'a (FullyCompiledProgram, Vec<PackagePaths>)>,
         pre_compiled_deps_v2: Option<&'a PrecompiledFilesModules>,
         task_opt: Option<TaskInput<(InitCommand, Self::ExtraInitArgs)>>,
-    ) -> (Self, Option<String>) {
+    ) -> Result<(Self, Option<String>)> {
         set_paranoid_type_checks(true);
         // Named address mapping
         let additional_named_address_mapping = match task_opt.as_ref().map(|t| &t.command) {
@@ -631,9 +642,19 @@ impl<'a> MoveTestAdapter<'a> for AptosTestAdapter<'a> {
             named_address_mapping.insert(name, addr);
         }
 
-        // Genesis modules
-        let mut storage = FakeDataStore::new(HashMap::new());
-        storage.add_write_set(GENESIS_CHANGE_SET_HEAD.write_set());
+        // Genesis modules, or the remote state the test is forked from
+        let storage = match task_opt.as_ref().map(|t| &t.command.1) {
+            Some(AptosInitArgs {
+                fork_fixture: Some(fixture_path),
+                fork_url,
+                fork_version,
+                ..
+            }) => AdapterStorage::new_forked(
+                ForkedState::new(fixture_path, fork_url.clone(), *fork_version)
+                    .context("Failed to fork remote state")?,
+            ),
+            _ => AdapterStorage::new_genesis(),
+        };
 
         // Builtin private key mapping
         let mut private_key_mapping = BTreeMap::new();
@@ -682,10 +703,20 @@ impl<'a> MoveTestAdapter<'a> for AptosTestAdapter<'a> {
         };
 
         for (_, addr) in additional_named_address_mapping {
-            adapter.create_and_fund_account(addr.into_inner(), coins_to_mint);
+            if adapter.storage.is_forked() {
+                adapter
+                    .storage
+                    .create_and_fund_forked_account(addr.into_inner(), coins_to_mint)?;
+            } else {
+                adapter.create_and_fund_account(addr.into_inner(), coins_to_mint);
+            }
         }
 
-        (adapter, None)
+        Ok((adapter, None))
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.storage.flush()
     }
 
     fn compile_module(
@@ -0,0 +1,505 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Support for running transactional tests against a fork of remote (e.g., mainnet) state.
+//!
+//! Remote state is fetched lazily at a fixed version, the same way the debugger does it, and
+//! every fetched value is recorded into a fixture file. Later runs replay the state from the
+//! fixture, so they run offline and deterministically. Run with `UB=1` to re-record a fixture.
+//!
+//! Fixtures are stored as JSON with one entry per state key, sorted by key, so that changes to a
+//! fixture can be reviewed. Every entry has a human-readable description of the key next to the
+//! BCS-encoded key and value.
+
+use anyhow::{bail, ensure, format_err, Context, Result};
+use aptos_language_e2e_tests::data_store::{FakeDataStore, GENESIS_CHANGE_SET_HEAD};
+use aptos_rest_client::Client;
+use aptos_types::{
+    account_config::{AccountResource, CoinStoreResource},
+    chain_id::ChainId,
+    event::{EventHandle, EventKey},
+    state_store::{
+        errors::StateViewError, state_key::StateKey, state_storage_usage::StateStorageUsage,
+        state_value::StateValue, TStateView,
+    },
+    transaction::Version,
+    write_set::{TransactionWrite, WriteSet},
+    AptosCoinType,
+};
+use aptos_validator_interface::{AptosValidatorInterface, RestDebuggerInterface};
+use move_command_line_common::testing::read_env_update_baseline;
+use move_core_types::{
+    account_address::AccountAddress, language_storage::ModuleId, move_resource::MoveStructType,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+use tokio::runtime::Runtime;
+use url::Url;
+
+/// All state values read from the fork, at a fixed version.
+#[derive(Debug, Default, PartialEq)]
+struct StateFixture {
+    version: Version,
+    values: BTreeMap<StateKey, Option<StateValue>>,
+}
+
+/// On-disk format of a [StateFixture].
+#[derive(Deserialize, Serialize)]
+struct FixtureFile {
+    version: Version,
+    values: Vec<FixtureEntry>,
+}
+
+#[derive(Deserialize, Serialize)]
+struct FixtureEntry {
+    /// Description of the state key, for reviewers only. Ignored when the fixture is read.
+    key: String,
+    key_bcs: String,
+    /// BCS-encoded state value, or [None] if the key does not exist in the forked state.
+    value_bcs: Option<String>,
+}
+
+impl StateFixture {
+    fn to_json(&self) -> Result<String> {
+        let values = self
+            .values
+            .iter()
+            .map(|(state_key, state_value)| {
+                Ok(FixtureEntry {
+                    key: format!("{:?}", state_key),
+                    key_bcs: hex::encode(bcs::to_bytes(state_key)?),
+                    value_bcs: state_value
+                        .as_ref()
+                        .map(|value| bcs::to_bytes(value).map(hex::encode))
+                        .transpose()?,
+                })
+            })
+            .collect::<Result<_>>()?;
+        let file = FixtureFile {
+            version: self.version,
+            values,
+        };
+        Ok(serde_json::to_string_pretty(&file)? + "\n")
+    }
+
+    fn from_json(json: &str) -> Result<Self> {
+        let file: FixtureFile = serde_json::from_str(json)?;
+        let values = file
+            .values
+            .into_iter()
+            .map(|entry| {
+                let state_key: StateKey = bcs::from_bytes(&hex::decode(&entry.key_bcs)?)
+                    .with_context(|| format!("Invalid state key {}", entry.key))?;
+                let state_value = entry
+                    .value_bcs
+                    .map(|value| -> Result<StateValue> {
+                        Ok(bcs::from_bytes(&hex::decode(value)?)?)
+                    })
+                    .transpose()
+                    .with_context(|| format!("Invalid state value of {}", entry.key))?;
+                Ok((state_key, state_value))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            version: file.version,
+            values,
+        })
+    }
+}
+
+/// Source of the state values which are not recorded in the fixture yet.
+trait RemoteState: Send + Sync {
+    fn latest_version(&self) -> Result<Version>;
+
+    fn get_state_value(&self, state_key: &StateKey, version: Version)
+        -> Result<Option<StateValue>>;
+}
+
+struct RestRemoteState {
+    runtime: Runtime,
+    interface: RestDebuggerInterface,
+}
+
+impl RestRemoteState {
+    fn new(url: Url) -> Result<Self> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        Ok(Self {
+            runtime,
+            interface: RestDebuggerInterface::new(Client::new(url)),
+        })
+    }
+}
+
+impl RemoteState for RestRemoteState {
+    fn latest_version(&self) -> Result<Version> {
+        self.runtime
+            .block_on(self.interface.get_latest_ledger_info_version())
+    }
+
+    fn get_state_value(
+        &self,
+        state_key: &StateKey,
+        version: Version,
+    ) -> Result<Option<StateValue>> {
+        self.runtime.block_on(
+            self.interface
+                .get_state_value_by_version(state_key, version),
+        )
+    }
+}
+
+/// Remote state at a fixed version, backed by a fixture file.
+pub(crate) struct ForkedState {
+    fixture_path: PathBuf,
+    fixture: Mutex<StateFixture>,
+    /// Endpoint used to fetch values missing from the fixture. [None] when replaying.
+    remote: Option<Box<dyn RemoteState>>,
+    /// Whether values have been fetched since the fixture was last written.
+    dirty: Mutex<bool>,
+}
+
+impl ForkedState {
+    /// Replays the fixture at `fixture_path` if it exists, otherwise (or if the baseline is being
+    /// updated) records a new one by fetching state from `url` at `version`, which defaults to
+    /// the latest version.
+    pub fn new(fixture_path: &Path, url: Option<Url>, version: Option<Version>) -> Result<Self> {
+        if fixture_path.exists() && !read_env_update_baseline() {
+            return Self::replay(fixture_path, version);
+        }
+
+        let url = url.ok_or_else(|| {
+            format_err!(
+                "Recording fixture {} requires a `--fork-url`",
+                fixture_path.display()
+            )
+        })?;
+        Self::record(fixture_path, Box::new(RestRemoteState::new(url)?), version)
+    }
+
+    fn replay(fixture_path: &Path, version: Option<Version>) -> Result<Self> {
+        let fixture = StateFixture::from_json(&fs::read_to_string(fixture_path)?)
+            .with_context(|| format!("Failed to parse fixture {}", fixture_path.display()))?;
+        if let Some(version) = version {
+            ensure!(
+                version == fixture.version,
+                "Fixture {} was recorded at version {}, but version {} was requested. \
+                Run with `UB=1` to re-record it.",
+                fixture_path.display(),
+                fixture.version,
+                version
+            );
+        }
+        Ok(Self {
+            fixture_path: fixture_path.to_path_buf(),
+            fixture: Mutex::new(fixture),
+            remote: None,
+            dirty: Mutex::new(false),
+        })
+    }
+
+    fn record(
+        fixture_path: &Path,
+        remote: Box<dyn RemoteState>,
+        version: Option<Version>,
+    ) -> Result<Self> {
+        let version = match version {
+            Some(version) => version,
+            None => remote.latest_version()?,
+        };
+        Ok(Self {
+            fixture_path: fixture_path.to_path_buf(),
+            fixture: Mutex::new(StateFixture {
+                version,
+                values: BTreeMap::new(),
+            }),
+            remote: Some(remote),
+            // Always write the fixture when recording, even if nothing is read from the fork.
+            dirty: Mutex::new(true),
+        })
+    }
+
+    /// Writes all values fetched so far to the fixture file. Values are only recorded in memory
+    /// while the test runs, so this must be called once the test has finished.
+    pub fn flush(&self) -> Result<()> {
+        // Same lock order as when fetching values.
+        let fixture = self.fixture.lock().unwrap();
+        let mut dirty = self.dirty.lock().unwrap();
+        if !*dirty {
+            return Ok(());
+        }
+        if let Some(parent) = self.fixture_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let json = fixture.to_json()?;
+        fs::write(&self.fixture_path, json)
+            .with_context(|| format!("Failed to write fixture {}", self.fixture_path.display()))?;
+        *dirty = false;
+        Ok(())
+    }
+
+    fn get_state_value(&self, state_key: &StateKey) -> Result<Option<StateValue>> {
+        let mut fixture = self.fixture.lock().unwrap();
+        if let Some(state_value) = fixture.values.get(state_key) {
+            return Ok(state_value.clone());
+        }
+
+        let remote = match &self.remote {
+            Some(remote) => remote,
+            None => bail!(
+                "State key {:?} is not recorded in fixture {}. Run with `UB=1` to re-record it.",
+                state_key,
+                self.fixture_path.display()
+            ),
+        };
+        let state_value = remote.get_state_value(state_key, fixture.version)?;
+        fixture
+            .values
+            .insert(state_key.clone(), state_value.clone());
+        *self.dirty.lock().unwrap() = true;
+        Ok(state_value)
+    }
+}
+
+/// Storage of the test adapter. Writes of executed transactions are kept in memory, on top of
+/// either the genesis state or a fork of remote state.
+#[derive(Clone)]
+pub(crate) struct AdapterStorage {
+    local: FakeDataStore,
+    /// Keys deleted locally, which must not be read from the fork.
+    deleted: HashSet<StateKey>,
+    fork: Option<Arc<ForkedState>>,
+}
+
+impl AdapterStorage {
+    pub fn new_genesis() -> Self {
+        let mut local = FakeDataStore::new(HashMap::new());
+        local.add_write_set(GENESIS_CHANGE_SET_HEAD.write_set());
+        Self {
+            local,
+            deleted: HashSet::new(),
+            fork: None,
+        }
+    }
+
+    pub fn new_forked(fork: ForkedState) -> Self {
+        let mut local = FakeDataStore::new(HashMap::new());
+        // Transactions of the test adapter are always signed for the test chain.
+        local.set_chain_id(ChainId::test());
+        Self {
+            local,
+            deleted: HashSet::new(),
+            fork: Some(Arc::new(fork)),
+        }
+    }
+
+    pub fn is_forked(&self) -> bool {
+        self.fork.is_some()
+    }
+
+    /// Records the state read from the fork, if any, into its fixture.
+    pub fn flush(&self) -> Result<()> {
+        match &self.fork {
+            Some(fork) => fork.flush(),
+            None => Ok(()),
+        }
+    }
+
+    pub fn add_write_set(&mut self, write_set: &WriteSet) {
+        for (state_key, write_op) in write_set {
+            match write_op.as_state_value() {
+                None => {
+                    self.local.remove(state_key);
+                    self.deleted.insert(state_key.clone());
+                },
+                Some(state_value) => {
+                    self.local.set(state_key.clone(), state_value);
+                    self.deleted.remove(state_key);
+                },
+            }
+        }
+    }
+
+    pub fn add_module(&mut self, module_id: &ModuleId, blob: Vec<u8>) {
+        self.deleted.remove(&StateKey::module_id(module_id));
+        self.local.add_module(module_id, blob);
+    }
+
+    /// Creates an account holding `amount` of AptosCoin, unless the account already exists.
+    ///
+    /// Accounts in the fork cannot be funded by minting, because the test root account does not
+    /// hold the mint capability there, so the resources are written directly instead.
+    pub fn create_and_fund_forked_account(
+        &mut self,
+        address: AccountAddress,
+        amount: u64,
+    ) -> Result<()> {
+        let account_key = StateKey::resource_typed::<AccountResource>(&address)?;
+        if self.get_state_value(&account_key)?.is_some() {
+            return Ok(());
+        }
+
+        let account = AccountResource::new(
+            0,
+            address.to_vec(),
+            EventHandle::new(EventKey::new(0, address), 0),
+            EventHandle::new(EventKey::new(1, address), 0),
+        );
+        let coin_store = CoinStoreResource::<AptosCoinType>::new(
+            amount,
+            false,
+            EventHandle::new(EventKey::new(2, address), 0),
+            EventHandle::new(EventKey::new(3, address), 0),
+        );
+        self.local
+            .set_legacy(account_key.clone(), bcs::to_bytes(&account)?);
+        self.local.set_legacy(
+            StateKey::resource(&address, &CoinStoreResource::<AptosCoinType>::struct_tag())?,
+            bcs::to_bytes(&coin_store)?,
+        );
+        Ok(())
+    }
+}
+
+impl TStateView for AdapterStorage {
+    type Key = StateKey;
+
+    fn get_state_value(&self, state_key: &StateKey) -> Result<Option<StateValue>, StateViewError> {
+        if let Some(state_value) = self.local.get_state_value(state_key)? {
+            return Ok(Some(state_value));
+        }
+        match &self.fork {
+            Some(fork) if !self.deleted.contains(state_key) => Ok(fork.get_state_value(state_key)?),
+            _ => Ok(None),
+        }
+    }
+
+    fn get_usage(&self) -> Result<StateStorageUsage, StateViewError> {
+        self.local.get_usage()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Remote state served from memory, counting the number of fetched values.
+    struct MockRemoteState {
+        values: HashMap<StateKey, StateValue>,
+        num_fetches: Arc<AtomicUsize>,
+    }
+
+    impl RemoteState for MockRemoteState {
+        fn latest_version(&self) -> Result<Version> {
+            Ok(100)
+        }
+
+        fn get_state_value(
+            &self,
+            state_key: &StateKey,
+            version: Version,
+        ) -> Result<Option<StateValue>> {
+            assert_eq!(version, 100);
+            self.num_fetches.fetch_add(1, Ordering::Relaxed);
+            Ok(self.values.get(state_key).cloned())
+        }
+    }
+
+    fn module_key(name: &str) -> StateKey {
+        StateKey::module(
+            &AccountAddress::ONE,
+            &move_core_types::identifier::Identifier::new(name).unwrap(),
+        )
+    }
+
+    fn record_fixture(fixture_path: &Path) -> Arc<AtomicUsize> {
+        let num_fetches = Arc::new(AtomicUsize::new(0));
+        let remote = MockRemoteState {
+            values: [(module_key("a"), StateValue::from(vec![1, 2, 3]))]
+                .into_iter()
+                .collect(),
+            num_fetches: num_fetches.clone(),
+        };
+        let fork = ForkedState::record(fixture_path, Box::new(remote), None).unwrap();
+
+        assert_eq!(
+            fork.get_state_value(&module_key("a")).unwrap(),
+            Some(StateValue::from(vec![1, 2, 3]))
+        );
+        assert_eq!(fork.get_state_value(&module_key("b")).unwrap(), None);
+        // Values are fetched once, and not written before the fixture is flushed.
+        assert_eq!(
+            fork.get_state_value(&module_key("a"))
+                .unwrap()
+                .unwrap()
+                .bytes()
+                .len(),
+            3
+        );
+        assert_eq!(num_fetches.load(Ordering::Relaxed), 2);
+        assert!(!fixture_path.exists());
+
+        fork.flush().unwrap();
+        num_fetches
+    }
+
+    #[test]
+    fn test_replay_recorded_fixture() {
+        let dir = tempfile::tempdir().unwrap();
+        let fixture_path = dir.path().join("fork.json");
+        record_fixture(&fixture_path);
+
+        let fork = ForkedState::replay(&fixture_path, Some(100)).unwrap();
+        assert_eq!(
+            fork.get_state_value(&module_key("a")).unwrap(),
+            Some(StateValue::from(vec![1, 2, 3]))
+        );
+        // Keys which do not exist remotely are recorded as well.
+        assert_eq!(fork.get_state_value(&module_key("b")).unwrap(), None);
+        // Keys which were never read cannot be replayed.
+        let err = fork.get_state_value(&module_key("c")).unwrap_err();
+        assert!(err.to_string().contains("UB=1"));
+    }
+
+    #[test]
+    fn test_fixture_is_reviewable_json() {
+        let dir = tempfile::tempdir().unwrap();
+        let fixture_path = dir.path().join("fork.json");
+        record_fixture(&fixture_path);
+
+        let json = fs::read_to_string(&fixture_path).unwrap();
+        let file: FixtureFile = serde_json::from_str(&json).unwrap();
+        assert_eq!(file.version, 100);
+        assert_eq!(file.values.len(), 2);
+        assert!(json.contains(&format!("{:?}", module_key("a"))));
+
+        let fixture = StateFixture::from_json(&json).unwrap();
+        assert_eq!(fixture.to_json().unwrap(), json);
+    }
+
+    #[test]
+    fn test_replay_rejects_other_version() {
+        let dir = tempfile::tempdir().unwrap();
+        let fixture_path = dir.path().join("fork.json");
+        record_fixture(&fixture_path);
+
+        assert!(ForkedState::replay(&fixture_path, Some(101)).is_err());
+        assert!(ForkedState::replay(&fixture_path, None).is_ok());
+    }
+
+    #[test]
+    fn test_recording_requires_url() {
+        let dir = tempfile::tempdir().unwrap();
+        let err = ForkedState::new(&dir.path().join("missing.json"), None, None)
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("--fork-url"));
+    }
+}
@@ -3,5 +3,6 @@
 // SPDX-License-Identifier: Apache-2.0
 
 mod aptos_test_harness;
+mod forked_state;
 
 pub use aptos_test_harness::{run_aptos_test, run_aptos_test_with_config};
@@ -0,0 +1,5 @@
+//# init --fork-fixture tests/aptos_test_harness/forked_state.fixture.json --fork-version 1000
+
+//# view --address 0xcafe --resource 0xcafe::m::S
+
+//# view --address 0xbeef --resource 0xcafe::m::S
@@ -49,7 +49,7 @@ use aptos_types::{
     block_executor::{
         config::{
             BlockExecutorConfig, BlockExecutorConfigFromOnchain, BlockExecutorLocalConfig,
-            BlockExecutorModuleCacheLocalConfig,
+            BlockExecutorModuleCacheLocalConfig, ConflictAwareExecution,
         },
         partitioner::PartitionedTransactions,
         transaction_slice_metadata::TransactionSliceMetadata,
@@ -128,6 +128,7 @@ use std::{
     cmp::{max, min},
     collections::{BTreeMap, BTreeSet},
     marker::Sync,
+    path::PathBuf,
     sync::Arc,
 };
 
@@ -135,7 +136,9 @@ static EXECUTION_CONCURRENCY_LEVEL: OnceCell<usize> = OnceCell::new();
 static NUM_EXECUTION_SHARD: OnceCell<usize> = OnceCell::new();
 static NUM_PROOF_READING_THREADS: OnceCell<usize> = OnceCell::new();
 static DISCARD_FAILED_BLOCKS: OnceCell<bool> = OnceCell::new();
+static CONFLICT_AWARE_EXECUTION: OnceCell<ConflictAwareExecution> = OnceCell::new();
 static PROCESSED_TRANSACTIONS_DETAILED_COUNTERS: OnceCell<bool> = OnceCell::new();
+static EXECUTION_TRACE_DIR: OnceCell<PathBuf> = OnceCell::new();
 
 macro_rules! deprecated_module_bundle {
     () => {
@@ -359,6 +362,18 @@ impl AptosVM {
         }
     }
 
+    /// Sets when blocks are executed along predicted conflict chains, when invoked the first time.
+    pub fn set_conflict_aware_execution_once(mode: ConflictAwareExecution) {
+        // Only the first call succeeds, due to OnceCell semantics.
+        CONFLICT_AWARE_EXECUTION.set(mode).ok();
+    }
+
+    /// Get when blocks are executed along predicted conflict chains if already set, otherwise
+    /// return default (disabled)
+    pub fn get_conflict_aware_execution() -> ConflictAwareExecution {
+        CONFLICT_AWARE_EXECUTION.get().copied().unwrap_or_default()
+    }
+
     /// Sets the # of async proof reading threads.
     pub fn set_num_proof_reading_threads_once(mut num_threads: usize) {
         // TODO(grao): Do more analysis to tune this magic number.
@@ -390,6 +405,18 @@ impl AptosVM {
         }
     }
 
+    /// Sets the directory to save parallel execution traces to when invoked the first time.
+    pub fn set_execution_trace_dir_once(dir: PathBuf) {
+        // Only the first call succeeds, due to OnceCell semantics.
+        EXECUTION_TRACE_DIR.set(dir).ok();
+    }
+
+    /// Get the directory to save parallel execution traces to, if set. Traces are not recorded
+    /// otherwise.
+    pub fn get_execution_trace_dir() -> Option<PathBuf> {
+        EXECUTION_TRACE_DIR.get().cloned()
+    }
+
     /// Returns the internal gas schedule if it has been loaded, or an error if it hasn't.
     #[cfg(any(test, feature = "testing"))]
     pub fn gas_params_for_test(&self) -> Result<&AptosGasParameters, VMStatus> {
@@ -2843,7 +2870,9 @@ impl VMBlockExecutor for AptosVMBlockExecutor {
                 concurrency_level: AptosVM::get_concurrency_level(),
                 allow_fallback: true,
                 discard_failed_blocks: AptosVM::get_discard_failed_blocks(),
+                conflict_aware_execution: AptosVM::get_conflict_aware_execution(),
                 module_cache_config: BlockExecutorModuleCacheLocalConfig::default(),
+                execution_trace_dir: AptosVM::get_execution_trace_dir(),
             },
             onchain: onchain_config,
         };
@@ -3008,13 +3037,25 @@ impl AptosSimulationVM {
 
         let env = AptosEnvironment::new(state_view);
         let vm = Self::new(env.clone(), state_view);
-        let log_context = AdapterLogSchema::new(state_view.id(), 0);
+        let code_storage = state_view.as_aptos_code_storage(env);
+        vm.simulate_signed_transaction(transaction, state_view, &code_storage)
+    }
 
+    /// Simulates a signed transaction, resolving code from the provided code storage, which must
+    /// be consistent with the state view.
+    /// *Precondition:* the transaction must **not** have a valid signature.
+    pub(crate) fn simulate_signed_transaction(
+        &self,
+        transaction: &SignedTransaction,
+        state_view: &impl StateView,
+        code_storage: &impl AptosCodeStorage,
+    ) -> (VMStatus, TransactionOutput) {
+        let log_context = AdapterLogSchema::new(state_view.id(), 0);
         let resolver = state_view.as_move_resolver();
-        let code_storage = state_view.as_aptos_code_storage(env);
 
         let (vm_status, vm_output) =
-            vm.0.execute_user_transaction(&resolver, &code_storage, transaction, &log_context);
+            self.0
+                .execute_user_transaction(&resolver, code_storage, transaction, &log_context);
         let txn_output = vm_output
             .try_materialize_into_transaction_output(&resolver)
             .expect("Materializing aggregator V1 deltas should never fail");
@@ -10,6 +10,7 @@ use aptos_aggregator::{
 };
 use aptos_block_executor::{
     code_cache_global_manager::AptosModuleCacheManager,
+    conflict_chains::{ConflictChains, ConflictHistory},
     errors::BlockExecutionError,
     executor::BlockExecutor,
     task::{ExecutorTask, TransactionOutput as BlockExecutorTransactionOutput},
@@ -17,7 +18,9 @@ use aptos_block_executor::{
     txn_provider::TxnProvider,
     types::InputOutputKey,
 };
+use aptos_block_partitioner::conflict_chains::predict_conflict_chains;
 use aptos_infallible::Mutex;
+use aptos_mvhashmap::types::TxnIndex;
 use aptos_types::{
     block_executor::{
         config::BlockExecutorConfig, transaction_slice_metadata::TransactionSliceMetadata,
@@ -52,6 +55,11 @@ use std::{
 };
 use vm_wrapper::AptosExecutorTask;
 
+/// Contention of the recently executed blocks, kept across blocks to decide when to execute them
+/// along predicted conflict chains.
+static CONFLICT_HISTORY: Lazy<Arc<ConflictHistory>> =
+    Lazy::new(|| Arc::new(ConflictHistory::default()));
+
 static RAYON_EXEC_POOL: Lazy<Arc<rayon::ThreadPool>> = Lazy::new(|| {
     Arc::new(
         rayon::ThreadPoolBuilder::new()
@@ -443,7 +451,9 @@ impl<
                 config,
                 executor_thread_pool,
                 transaction_commit_listener,
-            );
+            )
+            .with_conflict_predictor(Self::predict_block_conflict_chains::<TP>)
+            .with_conflict_history(CONFLICT_HISTORY.clone());
 
         let ret = executor.execute_block(
             signature_verified_block,
@@ -480,6 +490,22 @@ impl<
         }
     }
 
+    /// Predicts the conflict chains of a block from the declared read and write hints, senders and
+    /// called contracts of its transactions, used for conflict aware execution.
+    fn predict_block_conflict_chains<TP: TxnProvider<SignatureVerifiedTransaction>>(
+        block: &TP,
+    ) -> ConflictChains {
+        let predecessors = predict_conflict_chains(
+            (0..block.num_txns()).map(|txn_idx| block.get_txn(txn_idx as TxnIndex)),
+        );
+        ConflictChains::new(
+            predecessors
+                .into_iter()
+                .map(|predecessor| predecessor.map(|txn_idx| txn_idx as TxnIndex))
+                .collect(),
+        )
+    }
+
     /// Uses shared thread pool to execute blocks.
     pub(crate) fn execute_block<
         S: StateView + Sync,
@@ -11,7 +11,13 @@ use aptos_types::{
     },
     transaction::analyzed_transaction::AnalyzedTransaction,
 };
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
 
 /// A state view for reading cross shard state values. It is backed by a state view
 /// and a hashmap of cross shard state keys. When a cross shard state value is not
@@ -20,6 +26,7 @@ use std::collections::{HashMap, HashSet};
 pub struct CrossShardStateView<'a, S> {
     cross_shard_data: HashMap<StateKey, RemoteStateValue>,
     base_view: &'a S,
+    aborted: Arc<AtomicBool>,
 }
 
 impl<'a, S: StateView + Sync + Send> CrossShardStateView<'a, S> {
@@ -35,6 +42,7 @@ impl<'a, S: StateView + Sync + Send> CrossShardStateView<'a, S> {
         Self {
             cross_shard_data,
             base_view,
+            aborted: Arc::new(AtomicBool::new(false)),
         }
     }
 
@@ -55,6 +63,17 @@ impl<'a, S: StateView + Sync + Send> CrossShardStateView<'a, S> {
         // trace!("waiting count for shard id {} is {}", self.shard_id, self.waiting_count());
     }
 
+    /// Fails the reads of the cross shard state values that are still awaited, and of all the
+    /// values read afterwards, once the execution of the block was aborted.
+    pub fn abort(&self) {
+        self.aborted.store(true, Ordering::SeqCst);
+        for value in self.cross_shard_data.values() {
+            if !value.is_ready() {
+                value.set_value(None);
+            }
+        }
+    }
+
     pub fn create_cross_shard_state_view(
         base_view: &'a S,
         transactions: &[TransactionWithDependencies<AnalyzedTransaction>],
@@ -76,7 +95,13 @@ impl<'a, S: StateView + Sync + Send> TStateView for CrossShardStateView<'a, S> {
 
     fn get_state_value(&self, state_key: &StateKey) -> Result<Option<StateValue>, StateViewError> {
         if let Some(value) = self.cross_shard_data.get(state_key) {
-            return Ok(value.get_value());
+            let value = value.get_value();
+            if self.aborted.load(Ordering::SeqCst) {
+                return Err(StateViewError::Other(
+                    "Cross shard execution was aborted".to_string(),
+                ));
+            }
+            return Ok(value);
         }
         self.base_view.get_state_value(state_key)
     }
@@ -138,4 +163,30 @@ mod tests {
 
         wait_thread.join().unwrap();
     }
+
+    #[test]
+    fn test_cross_shard_state_view_abort() {
+        let state_key = StateKey::raw(b"key1");
+        let state_key_clone = state_key.clone();
+
+        let mut state_keys = HashSet::new();
+        state_keys.insert(state_key.clone());
+
+        let cross_shard_state_view = Arc::new(CrossShardStateView::new(state_keys, &EmptyView));
+        let cross_shard_state_view_clone = cross_shard_state_view.clone();
+
+        let wait_thread = thread::spawn(move || {
+            let value = cross_shard_state_view_clone.get_state_value(&state_key_clone);
+            assert!(value.is_err());
+        });
+
+        // Simulate some processing time before aborting
+        thread::sleep(Duration::from_millis(100));
+
+        cross_shard_state_view.abort();
+        assert_eq!(cross_shard_state_view.waiting_count(), 0);
+        assert!(cross_shard_state_view.get_state_value(&state_key).is_err());
+
+        wait_thread.join().unwrap();
+    }
 }
@@ -8,6 +8,9 @@ use serde::{Deserialize, Serialize};
 pub enum CrossShardMsg {
     RemoteTxnWriteMsg(RemoteTxnWrite),
     StopMsg,
+    /// The execution of the block was aborted, and the cross shard messages still awaited will
+    /// never be received.
+    AbortMsg,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -0,0 +1,719 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Caches for transaction simulation. Simulations are usually repeated many times against the
+//! same few states (e.g., wallets simulating a transaction on every change of its arguments), so
+//! two caches are kept across simulations:
+//!   1. A transaction-level cache of simulation outputs, keyed by the state version and the
+//!      transaction without its sequence number, expiration time and signatures.
+//!   2. The global module cache of verified code, managed by [AptosModuleCacheManager] like for
+//!      block execution, and kept warm as long as the execution environment does not change.
+//!      Because simulations run against arbitrary versions, every cached module is checked
+//!      against the state the simulation runs on before it is used. For the same reason, the
+//!      manager must not be shared with a block executor, which expects the cached modules to
+//!      match the state of the previously executed block.
+//!
+//! Simulations against states which are not committed versions (e.g., with state overrides) use
+//! neither of the caches.
+
+use crate::aptos_vm::AptosSimulationVM;
+use aptos_block_executor::code_cache_global_manager::AptosModuleCacheManager;
+use aptos_crypto::HashValue;
+use aptos_infallible::Mutex;
+use aptos_logger::error;
+use aptos_types::{
+    block_executor::config::BlockExecutorModuleCacheLocalConfig,
+    chain_id::ChainId,
+    state_store::{state_key::StateKey, state_value::StateValueMetadata, StateView},
+    transaction::{
+        authenticator::{AccountAuthenticator, AnySignature},
+        SignedTransaction, TransactionOutput, Version,
+    },
+    vm::modules::AptosModuleExtension,
+    vm_status::{StatusCode, VMStatus},
+};
+use aptos_vm_types::module_and_script_storage::module_storage::AptosModuleStorage;
+use lru::LruCache;
+use move_binary_format::{
+    errors::{PartialVMResult, VMResult},
+    file_format::CompiledScript,
+    CompiledModule,
+};
+use move_core_types::{
+    account_address::AccountAddress, identifier::IdentStr, language_storage::ModuleId,
+};
+use move_vm_runtime::{Module, RuntimeEnvironment, Script, WithRuntimeEnvironment};
+use move_vm_types::{
+    code::{
+        Code, ModuleCache, ModuleCode, ModuleCodeBuilder, ScriptCache, UnsyncModuleCache,
+        UnsyncScriptCache, WithBytes,
+    },
+    module_storage_error,
+};
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    sync::Arc,
+};
+
+type SimulationModuleCode = ModuleCode<CompiledModule, Module, AptosModuleExtension>;
+
+/// Cache hits and misses of a single simulation.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct SimulationCacheStats {
+    /// Whether the output was served from the transaction-level cache, or [None] if the output
+    /// can not be cached.
+    pub output_cache_hit: Option<bool>,
+    /// Number of distinct modules served from the global module cache.
+    pub module_cache_hits: usize,
+    /// Number of distinct modules which had to be loaded from the state.
+    pub module_cache_misses: usize,
+}
+
+/// Identifies a simulation: the state it runs on, and everything in the transaction the output
+/// depends on, except for the sequence number and the expiration time. Those change between
+/// otherwise identical simulations, and are checked against the cached output instead (see
+/// [CachedOutput::is_output_of]). Signatures are not verified when simulating, so they are left
+/// out, with the exception of their size, which is charged for.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+struct SimulationCacheKey {
+    version: Version,
+    sender: AccountAddress,
+    payload_hash: HashValue,
+    max_gas_amount: u64,
+    gas_unit_price: u64,
+    chain_id: ChainId,
+    signers: Vec<(u8, Vec<u8>, usize)>,
+    txn_size: usize,
+}
+
+impl SimulationCacheKey {
+    /// Returns the key of the simulation, or [None] if its output depends on the contents of the
+    /// signatures, e.g., keyless signatures carry the claims the account is authenticated with.
+    fn new(version: Version, transaction: &SignedTransaction) -> Option<Self> {
+        let authenticator = transaction.authenticator_ref();
+        let mut signers = vec![];
+        for signer in authenticator.all_signers() {
+            if !is_plain_signature(&signer) {
+                return None;
+            }
+            signers.push((
+                signer.scheme() as u8,
+                signer.public_key_bytes(),
+                signer.number_of_signatures(),
+            ));
+        }
+        // Multi-agent and fee payer transactions also authenticate the addresses of the other
+        // signers, which are not part of the raw transaction.
+        let mut other_signers = authenticator.secondary_signer_addresses();
+        other_signers.extend(authenticator.fee_payer_address());
+        signers.extend(
+            other_signers
+                .into_iter()
+                .map(|address| (u8::MAX, address.to_vec(), 0)),
+        );
+
+        let payload_bytes =
+            bcs::to_bytes(transaction.payload()).expect("Transaction payload must serialize");
+        Some(Self {
+            version,
+            sender: transaction.sender(),
+            payload_hash: HashValue::sha3_256_of(&payload_bytes),
+            max_gas_amount: transaction.max_gas_amount(),
+            gas_unit_price: transaction.gas_unit_price(),
+            chain_id: transaction.chain_id(),
+            signers,
+            txn_size: transaction.txn_bytes_len(),
+        })
+    }
+}
+
+/// Returns true if the authenticator only carries public keys and signatures over the transaction.
+fn is_plain_signature(authenticator: &AccountAuthenticator) -> bool {
+    let is_plain = |signature: &AnySignature| {
+        matches!(
+            signature,
+            AnySignature::Ed25519 { .. } | AnySignature::Secp256k1Ecdsa { .. }
+        )
+    };
+    match authenticator {
+        AccountAuthenticator::Ed25519 { .. }
+        | AccountAuthenticator::MultiEd25519 { .. }
+        | AccountAuthenticator::NoAccountAuthenticator => true,
+        AccountAuthenticator::SingleKey { authenticator } => is_plain(authenticator.signature()),
+        AccountAuthenticator::MultiKey { authenticator } => authenticator
+            .signatures()
+            .into_iter()
+            .all(|(_, signature)| is_plain(signature)),
+    }
+}
+
+/// A cached simulation output, with the sequence number and expiration time of the simulated
+/// transaction.
+struct CachedOutput {
+    sequence_number: u64,
+    expiration_timestamp_secs: u64,
+    vm_status: VMStatus,
+    output: TransactionOutput,
+}
+
+impl CachedOutput {
+    /// Returns true if the cached output is also the output of the given transaction, which has
+    /// the same key but may have a different sequence number or expiration time.
+    fn is_output_of(&self, transaction: &SignedTransaction) -> bool {
+        // At a given version, there is a single sequence number passing the prologue, and the
+        // output of a transaction with it contains the sequence number update.
+        if transaction.sequence_number() != self.sequence_number {
+            return false;
+        }
+        // A transaction which has not expired does not expire with a later expiration time, and
+        // the expiration time does not affect the output otherwise.
+        transaction.expiration_timestamp_secs() == self.expiration_timestamp_secs
+            || (transaction.expiration_timestamp_secs() > self.expiration_timestamp_secs
+                && self.vm_status.status_code() != StatusCode::TRANSACTION_EXPIRED)
+    }
+}
+
+/// Caches simulation outputs and verified modules across simulations. Thread-safe, simulations
+/// can run concurrently.
+pub struct SimulationCache {
+    outputs: Option<Mutex<LruCache<SimulationCacheKey, CachedOutput>>>,
+    module_cache_manager: Arc<AptosModuleCacheManager>,
+    config: BlockExecutorModuleCacheLocalConfig,
+}
+
+impl SimulationCache {
+    /// Returns an empty cache, holding up to the given number of simulation outputs. If the number
+    /// is 0, outputs are not cached, only modules. Modules are cached by the given manager, which
+    /// can be shared with other simulation caches, but not with block executors.
+    pub fn new(
+        max_cached_outputs: usize,
+        module_cache_manager: Arc<AptosModuleCacheManager>,
+    ) -> Self {
+        Self {
+            outputs: (max_cached_outputs > 0)
+                .then(|| Mutex::new(LruCache::new(max_cached_outputs))),
+            module_cache_manager,
+            config: BlockExecutorModuleCacheLocalConfig::default(),
+        }
+    }
+
+    /// Simulates a signed transaction (i.e., executes it without performing signature
+    /// verification), like [AptosSimulationVM::create_vm_and_simulate_signed_transaction], but
+    /// with cached outputs and code. The version must be the committed version the state view
+    /// reads, or [None] if the state view is not the one of a committed version (e.g., because
+    /// it has state overrides), in which case the caches are not used.
+    /// *Precondition:* the transaction must **not** have a valid signature.
+    pub fn simulate_signed_transaction(
+        &self,
+        transaction: &SignedTransaction,
+        state_view: &impl StateView,
+        version: Option<Version>,
+    ) -> (VMStatus, TransactionOutput, SimulationCacheStats) {
+        let Some(version) = version else {
+            let (vm_status, output) = AptosSimulationVM::create_vm_and_simulate_signed_transaction(
+                transaction,
+                state_view,
+            );
+            return (vm_status, output, SimulationCacheStats::default());
+        };
+
+        let key = self
+            .outputs
+            .as_ref()
+            .and_then(|_| SimulationCacheKey::new(version, transaction));
+        if let (Some(outputs), Some(key)) = (&self.outputs, &key) {
+            if let Some(cached) = outputs.lock().get(key) {
+                if cached.is_output_of(transaction) {
+                    let stats = SimulationCacheStats {
+                        output_cache_hit: Some(true),
+                        ..SimulationCacheStats::default()
+                    };
+                    return (cached.vm_status.clone(), cached.output.clone(), stats);
+                }
+            }
+        }
+
+        let (vm_status, output, mut stats) =
+            self.simulate_with_module_cache(transaction, state_view);
+        if let (Some(outputs), Some(key)) = (&self.outputs, key) {
+            outputs.lock().put(key, CachedOutput {
+                sequence_number: transaction.sequence_number(),
+                expiration_timestamp_secs: transaction.expiration_timestamp_secs(),
+                vm_status: vm_status.clone(),
+                output: output.clone(),
+            });
+            stats.output_cache_hit = Some(false);
+        }
+        (vm_status, output, stats)
+    }
+
+    fn simulate_with_module_cache(
+        &self,
+        transaction: &SignedTransaction,
+        state_view: &impl StateView,
+    ) -> (VMStatus, TransactionOutput, SimulationCacheStats) {
+        let environment = match self
+            .module_cache_manager
+            .check_ready_for_simulation(state_view, &self.config)
+        {
+            Ok(environment) => environment,
+            Err(err) => {
+                error!("Failed to prepare module cache for simulation: {:?}", err);
+                let (vm_status, output) =
+                    AptosSimulationVM::create_vm_and_simulate_signed_transaction(
+                        transaction,
+                        state_view,
+                    );
+                return (vm_status, output, SimulationCacheStats::default());
+            },
+        };
+
+        let vm = AptosSimulationVM::new(environment.clone(), state_view);
+        let code_storage = SimulationCodeStorage::new(
+            state_view,
+            environment.runtime_environment(),
+            &self.module_cache_manager,
+        );
+        let (vm_status, output) =
+            vm.simulate_signed_transaction(transaction, state_view, &code_storage);
+        let stats = code_storage.stats();
+
+        let verified_modules = code_storage.into_verified_modules();
+        if !verified_modules.is_empty() {
+            if let Err(err) = self
+                .module_cache_manager
+                .insert_verified_modules_for_simulation(&environment, verified_modules)
+            {
+                // Should never happen, as the cached modules are filtered out.
+                error!("Failed to cache modules verified in simulation: {:?}", err);
+            }
+        }
+        (vm_status, output, stats)
+    }
+}
+
+/// Code storage used for a single simulation. Modules are resolved from the global module cache
+/// if they match the state the simulation runs on, and are loaded from the state and cached
+/// locally otherwise.
+struct SimulationCodeStorage<'a, S> {
+    state_view: &'a S,
+    runtime_environment: &'a RuntimeEnvironment,
+    module_cache_manager: &'a AptosModuleCacheManager,
+    /// Modules read from the global cache, or [None] if the cached module does not exist or does
+    /// not match the state. Ensures every module is checked against the state only once.
+    global_module_reads: RefCell<HashMap<ModuleId, Option<Arc<SimulationModuleCode>>>>,
+    module_cache: UnsyncModuleCache<ModuleId, CompiledModule, Module, AptosModuleExtension, ()>,
+    script_cache: UnsyncScriptCache<[u8; 32], CompiledScript, Script>,
+    module_cache_hits: Cell<usize>,
+    module_cache_misses: Cell<usize>,
+}
+
+impl<'a, S: StateView> SimulationCodeStorage<'a, S> {
+    fn new(
+        state_view: &'a S,
+        runtime_environment: &'a RuntimeEnvironment,
+        module_cache_manager: &'a AptosModuleCacheManager,
+    ) -> Self {
+        Self {
+            state_view,
+            runtime_environment,
+            module_cache_manager,
+            global_module_reads: RefCell::new(HashMap::new()),
+            module_cache: UnsyncModuleCache::empty(),
+            script_cache: UnsyncScriptCache::empty(),
+            module_cache_hits: Cell::new(0),
+            module_cache_misses: Cell::new(0),
+        }
+    }
+
+    fn stats(&self) -> SimulationCacheStats {
+        SimulationCacheStats {
+            output_cache_hit: None,
+            module_cache_hits: self.module_cache_hits.get(),
+            module_cache_misses: self.module_cache_misses.get(),
+        }
+    }
+
+    /// Returns the modules loaded from the state and verified during the simulation.
+    fn into_verified_modules(self) -> Vec<(ModuleId, Arc<SimulationModuleCode>)> {
+        self.module_cache
+            .into_modules_iter()
+            .filter(|(_, module)| module.code().is_verified())
+            .collect()
+    }
+
+    /// Returns the module from the global cache, if it is cached and matches the state. Cached
+    /// modules which do not match the state are marked as overridden.
+    fn get_global_module(&self, key: &ModuleId) -> VMResult<Option<Arc<SimulationModuleCode>>> {
+        if let Some(read) = self.global_module_reads.borrow().get(key) {
+            return Ok(read.clone());
+        }
+
+        let read = match self.module_cache_manager.get_module_for_simulation(key) {
+            Some(module) => {
+                let (address, name) = (key.address(), key.name());
+                let state_value = self
+                    .state_view
+                    .get_state_value(&StateKey::module_id(key))
+                    .map_err(|err| module_storage_error!(address, name, err))?;
+                let matches_state = state_value
+                    .is_some_and(|state_value| state_value.bytes() == module.extension().bytes());
+                if matches_state {
+                    Some(module)
+                } else {
+                    self.module_cache_manager
+                        .mark_overridden_for_simulation(key);
+                    None
+                }
+            },
+            None => None,
+        };
+
+        if read.is_some() {
+            self.module_cache_hits.set(self.module_cache_hits.get() + 1);
+        } else {
+            self.module_cache_misses
+                .set(self.module_cache_misses.get() + 1);
+        }
+        self.global_module_reads
+            .borrow_mut()
+            .insert(key.clone(), read.clone());
+        Ok(read)
+    }
+}
+
+impl<'a, S: StateView> WithRuntimeEnvironment for SimulationCodeStorage<'a, S> {
+    fn runtime_environment(&self) -> &RuntimeEnvironment {
+        self.runtime_environment
+    }
+}
+
+impl<'a, S: StateView> ModuleCodeBuilder for SimulationCodeStorage<'a, S> {
+    type Deserialized = CompiledModule;
+    type Extension = AptosModuleExtension;
+    type Key = ModuleId;
+    type Verified = Module;
+
+    fn build(
+        &self,
+        key: &Self::Key,
+    ) -> VMResult<Option<ModuleCode<Self::Deserialized, Self::Verified, Self::Extension>>> {
+        let (address, name) = (key.address(), key.name());
+        self.state_view
+            .get_state_value(&StateKey::module_id(key))
+            .map_err(|err| module_storage_error!(address, name, err))?
+            .map(|state_value| {
+                let extension = Arc::new(AptosModuleExtension::new(state_value));
+                let compiled_module = self
+                    .runtime_environment()
+                    .deserialize_into_compiled_module(extension.bytes())?;
+                Ok(ModuleCode::from_deserialized(compiled_module, extension))
+            })
+            .transpose()
+    }
+}
+
+impl<'a, S: StateView> ModuleCache for SimulationCodeStorage<'a, S> {
+    type Deserialized = CompiledModule;
+    type Extension = AptosModuleExtension;
+    type Key = ModuleId;
+    type Verified = Module;
+    type Version = ();
+
+    fn insert_deserialized_module(
+        &self,
+        key: Self::Key,
+        deserialized_code: Self::Deserialized,
+        extension: Arc<Self::Extension>,
+        version: Self::Version,
+    ) -> VMResult<()> {
+        self.module_cache
+            .insert_deserialized_module(key, deserialized_code, extension, version)
+    }
+
+    fn insert_verified_module(
+        &self,
+        key: Self::Key,
+        verified_code: Self::Verified,
+        extension: Arc<Self::Extension>,
+        version: Self::Version,
+    ) -> VMResult<Arc<ModuleCode<Self::Deserialized, Self::Verified, Self::Extension>>> {
+        self.module_cache
+            .insert_verified_module(key, verified_code, extension, version)
+    }
+
+    fn get_module_or_build_with(
+        &self,
+        key: &Self::Key,
+        builder: &dyn ModuleCodeBuilder<
+            Key = Self::Key,
+            Deserialized = Self::Deserialized,
+            Verified = Self::Verified,
+            Extension = Self::Extension,
+        >,
+    ) -> VMResult<
+        Option<(
+            Arc<ModuleCode<Self::Deserialized, Self::Verified, Self::Extension>>,
+            Self::Version,
+        )>,
+    > {
+        if let Some(module) = self.get_global_module(key)? {
+            return Ok(Some((module, ())));
+        }
+        self.module_cache.get_module_or_build_with(key, builder)
+    }
+
+    fn num_modules(&self) -> usize {
+        self.module_cache.num_modules()
+    }
+}
+
+impl<'a, S: StateView> ScriptCache for SimulationCodeStorage<'a, S> {
+    type Deserialized = CompiledScript;
+    type Key = [u8; 32];
+    type Verified = Script;
+
+    fn insert_deserialized_script(
+        &self,
+        key: Self::Key,
+        deserialized_script: Self::Deserialized,
+    ) -> Arc<Self::Deserialized> {
+        self.script_cache
+            .insert_deserialized_script(key, deserialized_script)
+    }
+
+    fn insert_verified_script(
+        &self,
+        key: Self::Key,
+        verified_script: Self::Verified,
+    ) -> Arc<Self::Verified> {
+        self.script_cache
+            .insert_verified_script(key, verified_script)
+    }
+
+    fn get_script(&self, key: &Self::Key) -> Option<Code<Self::Deserialized, Self::Verified>> {
+        self.script_cache.get_script(key)
+    }
+
+    fn num_scripts(&self) -> usize {
+        self.script_cache.num_scripts()
+    }
+}
+
+impl<'a, S: StateView> AptosModuleStorage for SimulationCodeStorage<'a, S> {
+    fn fetch_state_value_metadata(
+        &self,
+        address: &AccountAddress,
+        module_name: &IdentStr,
+    ) -> PartialVMResult<Option<StateValueMetadata>> {
+        let id = ModuleId::new(*address, module_name.to_owned());
+        let state_value_metadata = self
+            .get_module_or_build_with(&id, self)
+            .map_err(|err| err.to_partial())?
+            .map(|(module, _)| module.extension().state_value_metadata().clone());
+        Ok(state_value_metadata)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use aptos_crypto::{
+        ed25519::{Ed25519PrivateKey, Ed25519Signature},
+        PrivateKey,
+    };
+    use aptos_types::{
+        on_chain_config::{FeatureFlag, Features, OnChainConfig},
+        state_store::{state_value::StateValue, MockStateView},
+        transaction::{RawTransaction, Script, TransactionPayload},
+    };
+    use claims::{assert_ok, assert_some};
+    use move_binary_format::file_format::{empty_module, Constant, SignatureToken};
+    use move_vm_runtime::ModuleStorage;
+
+    fn transaction(
+        sequence_number: u64,
+        expiration_timestamp_secs: u64,
+        code: u8,
+    ) -> SignedTransaction {
+        let private_key = Ed25519PrivateKey::try_from([1u8; 32].as_slice()).unwrap();
+        let raw_transaction = RawTransaction::new(
+            AccountAddress::ONE,
+            sequence_number,
+            TransactionPayload::Script(Script::new(vec![code], vec![], vec![])),
+            1_000,
+            100,
+            expiration_timestamp_secs,
+            ChainId::test(),
+        );
+        SignedTransaction::new(
+            raw_transaction,
+            private_key.public_key(),
+            Ed25519Signature::dummy_signature(),
+        )
+    }
+
+    #[test]
+    fn test_simulation_cache_key() {
+        let key = |version, transaction| SimulationCacheKey::new(version, &transaction).unwrap();
+
+        // Transactions differing only in their sequence numbers or expiration times share keys.
+        assert_eq!(
+            key(1, transaction(0, 100, 0)),
+            key(1, transaction(1, 200, 0))
+        );
+        assert_ne!(
+            key(1, transaction(0, 100, 0)),
+            key(1, transaction(0, 100, 1))
+        );
+        assert_ne!(
+            key(1, transaction(0, 100, 0)),
+            key(2, transaction(0, 100, 0))
+        );
+
+        let cached_output = |vm_status| CachedOutput {
+            sequence_number: 0,
+            expiration_timestamp_secs: 100,
+            vm_status,
+            output: TransactionOutput::new_empty_success(),
+        };
+
+        // The output is reused for the same sequence number, and a later expiration time.
+        let executed = cached_output(VMStatus::Executed);
+        assert!(executed.is_output_of(&transaction(0, 100, 0)));
+        assert!(executed.is_output_of(&transaction(0, 200, 0)));
+        assert!(!executed.is_output_of(&transaction(0, 50, 0)));
+        assert!(!executed.is_output_of(&transaction(1, 100, 0)));
+
+        // Unless the transaction had expired.
+        let expired = cached_output(VMStatus::error(StatusCode::TRANSACTION_EXPIRED, None));
+        assert!(expired.is_output_of(&transaction(0, 100, 0)));
+        assert!(!expired.is_output_of(&transaction(0, 200, 0)));
+    }
+
+    /// Returns the id and the bytes of a module, which differ for different values.
+    fn module(value: u64) -> (ModuleId, Vec<u8>) {
+        let mut module = empty_module();
+        module.constant_pool.push(Constant {
+            type_: SignatureToken::U64,
+            data: bcs::to_bytes(&value).unwrap(),
+        });
+        let mut bytes = vec![];
+        assert_ok!(module.serialize(&mut bytes));
+        (module.self_id(), bytes)
+    }
+
+    fn state_view_with_module(
+        module_id: &ModuleId,
+        bytes: &[u8],
+        features: Features,
+    ) -> MockStateView<StateKey> {
+        MockStateView::new(HashMap::from([
+            (
+                StateKey::module_id(module_id),
+                StateValue::new_legacy(bytes.to_vec().into()),
+            ),
+            (
+                StateKey::resource(Features::address(), &Features::struct_tag()).unwrap(),
+                StateValue::new_legacy(bcs::to_bytes(&features).unwrap().into()),
+            ),
+        ]))
+    }
+
+    /// Loads and verifies the module like a simulation against the state would, and caches it.
+    /// Returns the bytes of the loaded module, and the module cache hits and misses.
+    fn simulate_module_load(
+        manager: &AptosModuleCacheManager,
+        state_view: &MockStateView<StateKey>,
+        module_id: &ModuleId,
+    ) -> (Vec<u8>, usize, usize) {
+        let config = BlockExecutorModuleCacheLocalConfig::default();
+        let environment = assert_ok!(manager.check_ready_for_simulation(state_view, &config));
+        let code_storage =
+            SimulationCodeStorage::new(state_view, environment.runtime_environment(), manager);
+
+        let (address, name) = (module_id.address(), module_id.name());
+        assert_some!(assert_ok!(code_storage.fetch_verified_module(address, name)));
+        let bytes = assert_some!(assert_ok!(code_storage.fetch_module_bytes(address, name)));
+        let stats = code_storage.stats();
+
+        assert_ok!(manager.insert_verified_modules_for_simulation(
+            &environment,
+            code_storage.into_verified_modules()
+        ));
+        (
+            bytes.to_vec(),
+            stats.module_cache_hits,
+            stats.module_cache_misses,
+        )
+    }
+
+    #[test]
+    fn test_module_upgraded_by_newer_commit_is_reloaded() {
+        let manager = AptosModuleCacheManager::new();
+        let (module_id, bytes_1) = module(1);
+        let (_, bytes_2) = module(2);
+        let state_view_1 = state_view_with_module(&module_id, &bytes_1, Features::default());
+        let state_view_2 = state_view_with_module(&module_id, &bytes_2, Features::default());
+
+        // The module is loaded from the state once, and is then served from the cache.
+        assert_eq!(
+            simulate_module_load(&manager, &state_view_1, &module_id),
+            (bytes_1.clone(), 0, 1)
+        );
+        assert_eq!(
+            simulate_module_load(&manager, &state_view_1, &module_id),
+            (bytes_1.clone(), 1, 0)
+        );
+
+        // Once a newer commit upgrades the module, the cached module does not match the state
+        // and the module is reloaded. The upgraded module then replaces the cached one.
+        assert_eq!(
+            simulate_module_load(&manager, &state_view_2, &module_id),
+            (bytes_2.clone(), 0, 1)
+        );
+        assert_eq!(
+            simulate_module_load(&manager, &state_view_2, &module_id),
+            (bytes_2.clone(), 1, 0)
+        );
+
+        // Simulating against the older version still uses the code stored at that version.
+        assert_eq!(
+            simulate_module_load(&manager, &state_view_1, &module_id),
+            (bytes_1, 0, 1)
+        );
+    }
+
+    #[test]
+    fn test_modules_verified_in_replaced_environment_are_not_cached() {
+        let manager = AptosModuleCacheManager::new();
+        let config = BlockExecutorModuleCacheLocalConfig::default();
+        let (module_id, bytes) = module(1);
+        let state_view = state_view_with_module(&module_id, &bytes, Features::default());
+
+        // The module is verified in a simulation, while a concurrent simulation replaces the
+        // environment.
+        let environment = assert_ok!(manager.check_ready_for_simulation(&state_view, &config));
+        let code_storage =
+            SimulationCodeStorage::new(&state_view, environment.runtime_environment(), &manager);
+        assert_some!(assert_ok!(
+            code_storage.fetch_verified_module(module_id.address(), module_id.name())
+        ));
+
+        let mut features = Features::default();
+        if features.is_enabled(FeatureFlag::EMIT_FEE_STATEMENT) {
+            features.disable(FeatureFlag::EMIT_FEE_STATEMENT);
+        } else {
+            features.enable(FeatureFlag::EMIT_FEE_STATEMENT);
+        }
+        let other_state_view = state_view_with_module(&module_id, &bytes, features);
+        assert_ok!(manager.check_ready_for_simulation(&other_state_view, &config));
+
+        assert_ok!(manager.insert_verified_modules_for_simulation(
+            &environment,
+            code_storage.into_verified_modules()
+        ));
+        assert!(manager.get_module_for_simulation(&module_id).is_none());
+    }
+}
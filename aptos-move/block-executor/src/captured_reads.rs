@@ -621,6 +621,41 @@ where
         })
     }
 
+    /// Returns the first data read that no longer validates, along with the transaction whose
+    /// write invalidated it, when known. Used to attribute aborts in execution traces, so that
+    /// validation itself stays a plain check.
+    pub(crate) fn find_data_read_conflict(
+        &self,
+        data_map: &VersionedData<T::Key, T::Value>,
+        idx_to_validate: TxnIndex,
+    ) -> Option<(T::Key, Option<TxnIndex>)> {
+        use MVDataError::*;
+        use MVDataOutput::*;
+        // If the previous read observed a write that is gone, that writer caused the conflict.
+        let previous_writer = |read: &DataRead<T::Value>| match read {
+            DataRead::Versioned(Ok((txn_idx, _)), _, _) => Some(*txn_idx),
+            _ => None,
+        };
+        self.data_reads.iter().find_map(|(k, r)| {
+            let aborted_by = match data_map.fetch_data(k, idx_to_validate) {
+                Ok(Versioned(version, v)) => {
+                    let writer = version.as_ref().ok().map(|(txn_idx, _)| *txn_idx);
+                    match DataRead::from_value_with_layout(version, v).contains(r) {
+                        DataReadComparison::Contains => return None,
+                        _ => writer.or_else(|| previous_writer(r)),
+                    }
+                },
+                Ok(Resolved(value)) => match DataRead::Resolved(value).contains(r) {
+                    DataReadComparison::Contains => return None,
+                    _ => None,
+                },
+                Err(Dependency(txn_idx)) => Some(txn_idx),
+                Err(Unresolved(_)) | Err(DeltaApplicationFailure) | Err(Uninitialized) => None,
+            };
+            Some((k.clone(), aborted_by))
+        })
+    }
+
     /// Records the read to global cache that spans across multiple blocks.
     pub(crate) fn capture_global_cache_read(&mut self, key: K, read: Arc<ModuleCode<DC, VC, S>>) {
         self.module_reads.insert(key, ModuleRead::GlobalCache(read));
@@ -28,7 +28,7 @@ use move_core_types::{
     account_address::AccountAddress, ident_str, language_storage::ModuleId, vm_status::VMStatus,
 };
 use move_vm_runtime::{Module, ModuleStorage, WithRuntimeEnvironment};
-use move_vm_types::code::WithSize;
+use move_vm_types::code::{ModuleCode, WithSize};
 use parking_lot::{Mutex, MutexGuard};
 use std::{hash::Hash, ops::Deref, sync::Arc};
 
@@ -212,6 +212,103 @@ impl AptosModuleCacheManager {
 
         Ok(guard)
     }
+
+    /// Checks if the manager is ready for a simulation against the given state, and returns the
+    /// environment to simulate with. Unlike block executions, simulations run concurrently and
+    /// against arbitrary versions. Hence:
+    ///   1. The manager is only locked for short periods of time, not for the whole simulation.
+    ///   2. Struct name caches are never flushed, as concurrent simulations may be using them.
+    ///      Instead, the environment is replaced with a new one (and module cache is flushed).
+    ///   3. Cached modules are not necessarily the ones stored in the simulated state, and must be
+    ///      checked against it before use.
+    pub fn check_ready_for_simulation(
+        &self,
+        state_view: &impl StateView,
+        config: &BlockExecutorModuleCacheLocalConfig,
+    ) -> Result<AptosEnvironment, VMStatus> {
+        let storage_environment = AptosEnvironment::new(state_view);
+        let mut manager = self.inner.lock();
+
+        let environment_requires_update = match manager.environment.as_ref() {
+            Some(environment) => {
+                let struct_name_index_map_size = environment
+                    .runtime_environment()
+                    .struct_name_index_map_size()
+                    .map_err(|err| err.finish(Location::Undefined).into_vm_status())?;
+                environment != &storage_environment
+                    || struct_name_index_map_size > config.max_struct_name_index_map_num_entries
+            },
+            None => true,
+        };
+        if environment_requires_update {
+            manager.environment = Some(storage_environment);
+            manager.module_cache.flush();
+        }
+        if manager.module_cache.size_in_bytes() > config.max_module_cache_size_in_bytes {
+            manager.module_cache.flush();
+        }
+
+        let environment = manager
+            .environment
+            .clone()
+            .expect("Environment must be set");
+        if environment.features().is_loader_v2_enabled()
+            && manager.module_cache.num_modules() == 0
+            && config.prefetch_framework_code
+        {
+            let code_storage = state_view.as_aptos_code_storage(environment.clone());
+            prefetch_aptos_framework(code_storage, &mut manager.module_cache).map_err(|err| {
+                alert_or_println!("Failed to load Aptos framework to module cache: {:?}", err);
+                VMError::from(err).into_vm_status()
+            })?;
+        }
+        Ok(environment)
+    }
+
+    /// Returns the cached module for a simulation, if it exists and is not overridden. See
+    /// [AptosModuleCacheManager::check_ready_for_simulation].
+    pub fn get_module_for_simulation(
+        &self,
+        key: &ModuleId,
+    ) -> Option<Arc<ModuleCode<CompiledModule, Module, AptosModuleExtension>>> {
+        self.inner.lock().module_cache.get(key)
+    }
+
+    /// Marks the cached module as overridden, if a simulation found that it does not match the
+    /// simulated state. The module is then reloaded by the next simulation using it.
+    pub fn mark_overridden_for_simulation(&self, key: &ModuleId) {
+        self.inner.lock().module_cache.mark_overridden(key);
+    }
+
+    /// Caches the modules verified by a simulation with the given environment, unless the
+    /// environment has been replaced since (in which case the modules may refer to struct names
+    /// of the old one).
+    pub fn insert_verified_modules_for_simulation(
+        &self,
+        environment: &AptosEnvironment,
+        modules: Vec<(
+            ModuleId,
+            Arc<ModuleCode<CompiledModule, Module, AptosModuleExtension>>,
+        )>,
+    ) -> Result<(), PanicError> {
+        let mut manager = self.inner.lock();
+        let is_current_environment = manager.environment.as_ref().is_some_and(|current| {
+            std::ptr::eq(
+                current.runtime_environment(),
+                environment.runtime_environment(),
+            )
+        });
+        if !is_current_environment {
+            return Ok(());
+        }
+
+        // Modules could have been cached by concurrent simulations in the meantime.
+        let modules = modules
+            .into_iter()
+            .filter(|(key, _)| !manager.module_cache.contains_not_overridden(key))
+            .collect::<Vec<_>>();
+        manager.module_cache.insert_verified(modules.into_iter())
+    }
 }
 
 /// A guard that can be acquired from [AptosModuleCacheManager]. Variants represent successful and
@@ -545,6 +642,54 @@ mod test {
         assert_struct_name_index_map_size_eq(&manager, 0);
     }
 
+    #[test]
+    fn test_check_ready_for_simulation() {
+        let manager = AptosModuleCacheManager::new();
+        let state_view = MockStateView::empty();
+        let config = BlockExecutorModuleCacheLocalConfig {
+            prefetch_framework_code: false,
+            max_module_cache_size_in_bytes: 32,
+            max_struct_name_index_map_num_entries: 2,
+        };
+
+        // Case 1: The environment is kept across simulations.
+        let environment_1 = assert_ok!(manager.check_ready_for_simulation(&state_view, &config));
+        let environment_2 = assert_ok!(manager.check_ready_for_simulation(&state_view, &config));
+        assert!(std::ptr::eq(
+            environment_1.runtime_environment(),
+            environment_2.runtime_environment()
+        ));
+
+        for name in ["foo", "bar", "baz"] {
+            assert_ok!(environment_1
+                .runtime_environment()
+                .struct_name_to_idx_for_test(StructIdentifier {
+                    module: ModuleId::new(AccountAddress::ZERO, Identifier::new("m").unwrap()),
+                    name: Identifier::new(name).unwrap()
+                }));
+        }
+
+        // Case 2: Type cache is too large. The environment is replaced, while the struct names of
+        //         the previous environment are kept for the simulations which may still use it.
+        let environment_3 = assert_ok!(manager.check_ready_for_simulation(&state_view, &config));
+        assert!(!std::ptr::eq(
+            environment_1.runtime_environment(),
+            environment_3.runtime_environment()
+        ));
+        assert_eq!(
+            assert_ok!(environment_1
+                .runtime_environment()
+                .struct_name_index_map_size()),
+            3
+        );
+        assert_eq!(
+            assert_ok!(environment_3
+                .runtime_environment()
+                .struct_name_index_map_size()),
+            0
+        );
+    }
+
     #[test]
     fn test_try_lock_inner_single_thread() {
         let manager = AptosModuleCacheManager::new();
@@ -0,0 +1,93 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_mvhashmap::types::TxnIndex;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Predicted conflicts between the transactions of a block, used when parallel execution of a
+/// block fails and it is re-executed before falling back to sequential execution. Every
+/// transaction may have a predecessor, the closest preceding transaction it is predicted to
+/// conflict with, which links the transactions of the block into conflict chains. Execution
+/// of a transaction waits until its predecessor is executed, so transactions of the same chain
+/// are executed in order, while different chains are executed in parallel.
+///
+/// Predictions only affect scheduling: reads are validated as usual, so wrong predictions may
+/// only cost performance.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ConflictChains {
+    predecessors: Vec<Option<TxnIndex>>,
+}
+
+impl ConflictChains {
+    /// Creates the chains from the predicted predecessor of every transaction. Predecessors that
+    /// do not precede the transaction are ignored.
+    pub fn new(predecessors: Vec<Option<TxnIndex>>) -> Self {
+        let predecessors = predecessors
+            .into_iter()
+            .enumerate()
+            .map(|(txn_idx, predecessor)| {
+                predecessor.filter(|predecessor| (*predecessor as usize) < txn_idx)
+            })
+            .collect();
+        Self { predecessors }
+    }
+
+    pub fn num_txns(&self) -> usize {
+        self.predecessors.len()
+    }
+
+    /// Returns the number of chains, i.e., the number of transactions without a predecessor.
+    pub fn num_chains(&self) -> usize {
+        self.predecessors
+            .iter()
+            .filter(|predecessor| predecessor.is_none())
+            .count()
+    }
+
+    pub fn predecessor(&self, txn_idx: TxnIndex) -> Option<TxnIndex> {
+        self.predecessors.get(txn_idx as usize).copied().flatten()
+    }
+}
+
+/// Predicts the conflict chains of a block of transactions.
+pub type ConflictPredictor<TP> = fn(&TP) -> ConflictChains;
+
+/// Blocks are considered contended once their transactions are re-executed half of the time.
+const CONTENDED_REEXECUTIONS_PER_MILLE: u64 = 500;
+
+/// The contention of recently executed blocks, measured by the number of re-executions per
+/// transaction during parallel execution. It is kept across blocks, so that blocks following
+/// contended ones are executed along the predicted conflict chains from the start, rather than
+/// only after their parallel execution failed. The contention is averaged over recent blocks, so
+/// that a single contended block does not change how the following blocks are executed.
+///
+/// Blocks executed along conflict chains are re-executed less, so their contention is recorded as
+/// well: this lets the history recover when a workload stops being contended, at the cost of the
+/// occasional block executed without chains while it still is.
+#[derive(Debug, Default)]
+pub struct ConflictHistory {
+    // Exponential moving average of the number of re-executions per 1000 transactions.
+    reexecutions_per_mille: AtomicU64,
+}
+
+impl ConflictHistory {
+    /// Records the number of re-executions of a block executed in parallel.
+    pub fn record_block(&self, num_txns: usize, num_reexecutions: usize) {
+        if num_txns == 0 {
+            return;
+        }
+        let block_reexecutions_per_mille = (num_reexecutions as u64 * 1000) / num_txns as u64;
+        // Blocks are only executed one at a time, so this does not need to be atomic as a whole.
+        let average = self.reexecutions_per_mille.load(Ordering::Relaxed);
+        self.reexecutions_per_mille.store(
+            (average * 7 + block_reexecutions_per_mille) / 8,
+            Ordering::Relaxed,
+        );
+    }
+
+    /// Returns true iff recent blocks were contended enough to execute the next one along the
+    /// predicted conflict chains.
+    pub fn is_contended(&self) -> bool {
+        self.reexecutions_per_mille.load(Ordering::Relaxed) >= CONTENDED_REEXECUTIONS_PER_MILLE
+    }
+}
@@ -70,6 +70,27 @@ pub static MODULE_PUBLISHING_FALLBACK_COUNT: Lazy<IntCounter> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Count of conflict-aware re-executions of blocks whose parallel execution failed, by outcome.
+pub static CONFLICT_AWARE_FALLBACK_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_execution_conflict_aware_fallback_count",
+        "Count of times a block was re-executed along predicted conflict chains after parallel \
+        execution failed, before falling back to sequential execution",
+        &["outcome"]
+    )
+    .unwrap()
+});
+
+/// Count of blocks executed along predicted conflict chains because recent blocks were contended.
+pub static CONFLICT_AWARE_CONTENDED_BLOCK_COUNT: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "aptos_execution_conflict_aware_contended_block_count",
+        "Count of blocks executed along predicted conflict chains from the start, because \
+        recent blocks were contended"
+    )
+    .unwrap()
+});
+
 /// Count of speculative transaction re-executions due to a failed validation.
 pub static SPECULATIVE_ABORT_COUNT: Lazy<IntCounter> = Lazy::new(|| {
     register_int_counter!(
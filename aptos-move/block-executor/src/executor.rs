@@ -5,6 +5,7 @@
 use crate::{
     code_cache_global::GlobalModuleCache,
     code_cache_global_manager::AptosModuleCacheManagerGuard,
+    conflict_chains::{ConflictChains, ConflictHistory, ConflictPredictor},
     counters::{
         self, BLOCK_EXECUTOR_INNER_EXECUTE_BLOCK, PARALLEL_EXECUTION_SECONDS,
         RAYON_EXECUTION_SECONDS, TASK_EXECUTE_SECONDS, TASK_VALIDATE_SECONDS, VM_INIT_SECONDS,
@@ -16,11 +17,12 @@ use crate::{
     limit_processor::BlockGasLimitProcessor,
     scheduler::{DependencyStatus, ExecutionTaskType, Scheduler, SchedulerTask, Wave},
     task::{ExecutionStatus, ExecutorTask, TransactionOutput},
+    trace::BlockTraceCollector,
     txn_commit_hook::TransactionCommitHook,
     txn_last_input_output::{KeyKind, TxnLastInputOutput},
     txn_provider::TxnProvider,
     types::ReadWriteSummary,
-    view::{LatestView, ParallelState, SequentialState, ViewState},
+    view::{wait_for_dependency, LatestView, ParallelState, SequentialState, ViewState},
 };
 use aptos_aggregator::{
     delayed_change::{ApplyBase, DelayedChange},
@@ -35,7 +37,7 @@ use aptos_mvhashmap::{
     MVHashMap,
 };
 use aptos_types::{
-    block_executor::config::BlockExecutorConfig,
+    block_executor::config::{BlockExecutorConfig, ConflictAwareExecution},
     error::{code_invariant_error, expect_ok, PanicError, PanicOr},
     executable::Executable,
     on_chain_config::BlockGasLimitType,
@@ -67,7 +69,7 @@ use std::{
     collections::{BTreeMap, HashMap, HashSet},
     marker::{PhantomData, Sync},
     sync::{
-        atomic::{AtomicBool, AtomicU32, Ordering},
+        atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering},
         Arc,
     },
 };
@@ -78,6 +80,11 @@ pub struct BlockExecutor<T, E, S, L, X, TP> {
     config: BlockExecutorConfig,
     executor_thread_pool: Arc<rayon::ThreadPool>,
     transaction_commit_hook: Option<L>,
+    // If set, used to predict conflict chains when conflict aware execution is enabled.
+    conflict_predictor: Option<ConflictPredictor<TP>>,
+    // If set, records the contention of the blocks executed in parallel and decides when blocks
+    // are executed along conflict chains in the fallback mode.
+    conflict_history: Option<Arc<ConflictHistory>>,
     phantom: PhantomData<(T, E, S, L, X, TP)>,
 }
 
@@ -106,10 +113,26 @@ where
             config,
             executor_thread_pool,
             transaction_commit_hook,
+            conflict_predictor: None,
+            conflict_history: None,
             phantom: PhantomData,
         }
     }
 
+    /// Sets the predictor of the conflict chains used when conflict aware execution is enabled,
+    /// see `ConflictAwareExecution`.
+    pub fn with_conflict_predictor(mut self, conflict_predictor: ConflictPredictor<TP>) -> Self {
+        self.conflict_predictor = Some(conflict_predictor);
+        self
+    }
+
+    /// Sets the conflict history kept across blocks, which lets the fallback mode of conflict
+    /// aware execution execute blocks along conflict chains while recent blocks are contended.
+    pub fn with_conflict_history(mut self, conflict_history: Arc<ConflictHistory>) -> Self {
+        self.conflict_history = Some(conflict_history);
+        self
+    }
+
     fn execute(
         idx_to_execute: TxnIndex,
         incarnation: Incarnation,
@@ -493,10 +516,19 @@ where
         versioned_cache: &MVHashMap<T::Key, T::Tag, T::Value, X, DelayedFieldID>,
         scheduler: &Scheduler,
         runtime_environment: &RuntimeEnvironment,
+        trace_collector: Option<&BlockTraceCollector>,
     ) -> Result<SchedulerTask, PanicError> {
         let aborted = !valid && scheduler.try_abort(txn_idx, incarnation);
 
         if aborted {
+            if let Some(trace_collector) = trace_collector {
+                // Must happen before the writes of the aborted incarnation are marked as
+                // estimates, while the read set is still the one that failed validation.
+                let conflict = last_input_output.read_set(txn_idx).and_then(|read_set| {
+                    read_set.find_data_read_conflict(versioned_cache.data(), txn_idx)
+                });
+                trace_collector.record_abort(txn_idx, incarnation, conflict);
+            }
             Self::update_transaction_on_abort(
                 txn_idx,
                 last_input_output,
@@ -580,17 +612,22 @@ where
         executor: &E,
         block: &TP,
         num_workers: usize,
+        trace_collector: Option<&BlockTraceCollector>,
     ) -> Result<(), PanicOr<ParallelBlockExecutionError>> {
         let mut block_limit_processor = shared_commit_state.acquire();
 
         while let Some((txn_idx, incarnation)) = scheduler.try_commit() {
             let mut executed_at_commit = false;
+            let mut committed_incarnation = incarnation;
             if !Self::validate_and_commit_delayed_fields(
                 txn_idx,
                 versioned_cache,
                 last_input_output,
             )? {
                 // Transaction needs to be re-executed, one final time.
+                if let Some(trace_collector) = trace_collector {
+                    trace_collector.record_abort::<T::Key>(txn_idx, incarnation, None);
+                }
 
                 Self::update_transaction_on_abort(
                     txn_idx,
@@ -602,9 +639,10 @@ where
                 // are executing immediately, and will reduce it unconditionally
                 // after execution, inside finish_execution_during_commit.
                 // Because of that, we can also ignore _needs_suffix_validation result.
+                committed_incarnation = incarnation + 1;
                 let _needs_suffix_validation = Self::execute(
                     txn_idx,
-                    incarnation + 1,
+                    committed_incarnation,
                     block,
                     last_input_output,
                     versioned_cache,
@@ -686,6 +724,14 @@ where
             // Handle a potential vm error, then check invariants on the recorded outputs.
             last_input_output.check_execution_status_during_commit(txn_idx)?;
 
+            if let Some(trace_collector) = trace_collector {
+                trace_collector.record_commit(
+                    txn_idx,
+                    committed_incarnation,
+                    &last_input_output.get_txn_read_write_summary(txn_idx),
+                );
+            }
+
             if let Some(fee_statement) = last_input_output.fee_statement(txn_idx) {
                 let approx_output_size = block_gas_limit_type.block_output_limit().and_then(|_| {
                     last_input_output
@@ -968,6 +1014,9 @@ where
         shared_commit_state: &ExplicitSyncWrapper<BlockGasLimitProcessor<T>>,
         final_results: &ExplicitSyncWrapper<Vec<E::Output>>,
         num_workers: usize,
+        trace_collector: Option<&BlockTraceCollector>,
+        conflict_chains: Option<&ConflictChains>,
+        num_reexecutions: &AtomicUsize,
     ) -> Result<(), PanicOr<ParallelBlockExecutionError>> {
         // Make executor for each task. TODO: fast concurrent executor.
         let num_txns = block.num_txns();
@@ -1026,6 +1075,7 @@ where
                     &executor,
                     block,
                     num_workers,
+                    trace_collector,
                 )?;
                 scheduler.queueing_commits_mark_done();
             }
@@ -1034,6 +1084,8 @@ where
 
             scheduler_task = match scheduler_task {
                 SchedulerTask::ValidationTask(txn_idx, incarnation, wave) => {
+                    // Allows injecting delays to test different interleavings.
+                    fail_point!("block-executor-before-validation");
                     let valid = Self::validate(
                         txn_idx,
                         last_input_output,
@@ -1050,6 +1102,7 @@ where
                         versioned_cache,
                         scheduler,
                         runtime_environment,
+                        trace_collector,
                     )?
                 },
                 SchedulerTask::ExecutionTask(
@@ -1057,24 +1110,41 @@ where
                     incarnation,
                     ExecutionTaskType::Execution,
                 ) => {
-                    let needs_suffix_validation = Self::execute(
-                        txn_idx,
-                        incarnation,
-                        block,
-                        last_input_output,
-                        versioned_cache,
-                        &executor,
-                        base_view,
-                        global_module_cache,
-                        runtime_environment,
-                        ParallelState::new(
+                    // When executing along conflict chains, first wait for the predecessor of
+                    // the transaction in its chain to be executed, same as for a read dependency.
+                    let execution_halted =
+                        match conflict_chains.and_then(|chains| chains.predecessor(txn_idx)) {
+                            Some(dep_idx) => !wait_for_dependency(scheduler, txn_idx, dep_idx)?,
+                            None => false,
+                        };
+
+                    if execution_halted {
+                        scheduler.next_task()
+                    } else {
+                        if incarnation > 0 {
+                            num_reexecutions.fetch_add(1, Ordering::Relaxed);
+                        }
+                        // Allows injecting delays to test different interleavings.
+                        fail_point!("block-executor-before-execution");
+                        let needs_suffix_validation = Self::execute(
+                            txn_idx,
+                            incarnation,
+                            block,
+                            last_input_output,
                             versioned_cache,
-                            scheduler,
-                            start_shared_counter,
-                            shared_counter,
-                        ),
-                    )?;
-                    scheduler.finish_execution(txn_idx, incarnation, needs_suffix_validation)?
+                            &executor,
+                            base_view,
+                            global_module_cache,
+                            runtime_environment,
+                            ParallelState::new(
+                                versioned_cache,
+                                scheduler,
+                                start_shared_counter,
+                                shared_counter,
+                            ),
+                        )?;
+                        scheduler.finish_execution(txn_idx, incarnation, needs_suffix_validation)?
+                    }
                 },
                 SchedulerTask::ExecutionTask(_, _, ExecutionTaskType::Wakeup(condvar)) => {
                     {
@@ -1103,6 +1173,23 @@ where
         signature_verified_block: &TP,
         base_view: &S,
         module_cache_manager_guard: &mut AptosModuleCacheManagerGuard,
+    ) -> Result<BlockOutput<E::Output>, ()> {
+        self.execute_transactions_parallel_with_conflict_chains(
+            signature_verified_block,
+            base_view,
+            module_cache_manager_guard,
+            None,
+        )
+    }
+
+    /// Executes the block in parallel. If conflict chains are provided, the execution of every
+    /// transaction waits for its predecessor in the chain to be executed.
+    pub(crate) fn execute_transactions_parallel_with_conflict_chains(
+        &self,
+        signature_verified_block: &TP,
+        base_view: &S,
+        module_cache_manager_guard: &mut AptosModuleCacheManagerGuard,
+        conflict_chains: Option<&ConflictChains>,
     ) -> Result<BlockOutput<E::Output>, ()> {
         let _timer = PARALLEL_EXECUTION_SECONDS.start_timer();
         // Using parallel execution with 1 thread currently will not work as it
@@ -1130,6 +1217,7 @@ where
             num_txns,
         ));
         let shared_maybe_error = AtomicBool::new(false);
+        let num_reexecutions = AtomicUsize::new(0);
 
         let final_results = ExplicitSyncWrapper::new(Vec::with_capacity(num_txns));
 
@@ -1143,6 +1231,12 @@ where
 
         let last_input_output = TxnLastInputOutput::new(num_txns);
         let scheduler = Scheduler::new(num_txns);
+        let trace_collector = self
+            .config
+            .local
+            .execution_trace_dir
+            .is_some()
+            .then(|| BlockTraceCollector::new(num_txns, num_workers));
 
         let timer = RAYON_EXECUTION_SECONDS.start_timer();
         self.executor_thread_pool.scope(|s| {
@@ -1161,6 +1255,9 @@ where
                         &shared_commit_state,
                         &final_results,
                         num_workers,
+                        trace_collector.as_ref(),
+                        conflict_chains,
+                        &num_reexecutions,
                     ) {
                         // If there are multiple errors, they all get logged:
                         // ModulePathReadWriteError and FatalVMError variant is logged at construction,
@@ -1209,6 +1306,23 @@ where
             None
         };
 
+        if let (Some(trace_collector), Some(dir)) =
+            (trace_collector, &self.config.local.execution_trace_dir)
+        {
+            // Blocks that fall back to sequential execution are not traced, as their parallel
+            // execution did not complete.
+            if !shared_maybe_error.load(Ordering::SeqCst) {
+                trace_collector.save(dir);
+            }
+        }
+
+        if let Some(conflict_history) = &self.conflict_history {
+            if !shared_maybe_error.load(Ordering::SeqCst) {
+                conflict_history
+                    .record_block(num_txns as usize, num_reexecutions.load(Ordering::Relaxed));
+            }
+        }
+
         (!shared_maybe_error.load(Ordering::SeqCst))
             .then(|| BlockOutput::new(final_results.into_inner(), block_end_info))
             .ok_or(())
@@ -1704,6 +1818,100 @@ where
         }
     }
 
+    /// Clears the logs and flushes the caches after a failed parallel execution, so that the
+    /// block can be re-executed from a clean state.
+    fn reset_after_failed_parallel_execution(
+        signature_verified_block: &TP,
+        module_cache_manager_guard: &mut AptosModuleCacheManagerGuard,
+    ) {
+        // All logs from the parallel execution should be cleared and not reported.
+        // Clear by re-initializing the speculative logs.
+        init_speculative_logs(signature_verified_block.num_txns());
+
+        // Flush all caches to re-run from the "clean" state.
+        module_cache_manager_guard
+            .environment()
+            .runtime_environment()
+            .flush_struct_name_and_info_caches();
+        module_cache_manager_guard.module_cache_mut().flush();
+    }
+
+    fn predict_conflict_chains(&self, signature_verified_block: &TP) -> Option<ConflictChains> {
+        let conflict_predictor = self.conflict_predictor?;
+        let conflict_chains = conflict_predictor(signature_verified_block);
+        (conflict_chains.num_txns() == signature_verified_block.num_txns())
+            .then_some(conflict_chains)
+    }
+
+    /// Predicts the conflict chains of the block if recent blocks were contended, so that it is
+    /// executed along them from the start. Returns None otherwise, or if the block would be
+    /// executed along a single chain.
+    fn predict_conflict_chains_if_contended(
+        &self,
+        signature_verified_block: &TP,
+    ) -> Option<ConflictChains> {
+        if !self
+            .conflict_history
+            .as_ref()
+            .is_some_and(|conflict_history| conflict_history.is_contended())
+        {
+            return None;
+        }
+        let conflict_chains = self
+            .predict_conflict_chains(signature_verified_block)
+            .filter(|conflict_chains| conflict_chains.num_chains() > 1)?;
+        counters::CONFLICT_AWARE_CONTENDED_BLOCK_COUNT.inc();
+        Some(conflict_chains)
+    }
+
+    /// Re-executes a block whose parallel execution failed in parallel again, but along the
+    /// predicted conflict chains. Returns None if the block should be executed sequentially.
+    fn execute_transactions_along_conflict_chains(
+        &self,
+        signature_verified_block: &TP,
+        base_view: &S,
+        module_cache_manager_guard: &mut AptosModuleCacheManagerGuard,
+    ) -> Option<BlockOutput<E::Output>> {
+        let conflict_chains = match self.predict_conflict_chains(signature_verified_block) {
+            Some(conflict_chains) if conflict_chains.num_chains() > 1 => conflict_chains,
+            _ => {
+                // With a single chain, execution would be sequential anyway.
+                counters::CONFLICT_AWARE_FALLBACK_COUNT
+                    .with_label_values(&["skipped"])
+                    .inc();
+                return None;
+            },
+        };
+
+        info!(
+            "re-executing block along {} predicted conflict chains",
+            conflict_chains.num_chains()
+        );
+        match self.execute_transactions_parallel_with_conflict_chains(
+            signature_verified_block,
+            base_view,
+            module_cache_manager_guard,
+            Some(&conflict_chains),
+        ) {
+            Ok(output) => {
+                counters::CONFLICT_AWARE_FALLBACK_COUNT
+                    .with_label_values(&["success"])
+                    .inc();
+                Some(output)
+            },
+            Err(()) => {
+                counters::CONFLICT_AWARE_FALLBACK_COUNT
+                    .with_label_values(&["failure"])
+                    .inc();
+                Self::reset_after_failed_parallel_execution(
+                    signature_verified_block,
+                    module_cache_manager_guard,
+                );
+                None
+            },
+        }
+    }
+
     pub fn execute_block(
         &self,
         signature_verified_block: &TP,
@@ -1713,10 +1921,20 @@ where
         let _timer = BLOCK_EXECUTOR_INNER_EXECUTE_BLOCK.start_timer();
 
         if self.config.local.concurrency_level > 1 {
-            let parallel_result = self.execute_transactions_parallel(
+            let conflict_chains = match self.config.local.conflict_aware_execution {
+                ConflictAwareExecution::Always => {
+                    self.predict_conflict_chains(signature_verified_block)
+                },
+                ConflictAwareExecution::Fallback => {
+                    self.predict_conflict_chains_if_contended(signature_verified_block)
+                },
+                ConflictAwareExecution::Disabled => None,
+            };
+            let parallel_result = self.execute_transactions_parallel_with_conflict_chains(
                 signature_verified_block,
                 base_view,
                 module_cache_manager_guard,
+                conflict_chains.as_ref(),
             );
 
             // If parallel gave us result, return it
@@ -1728,18 +1946,21 @@ where
                 panic!("Parallel execution failed and fallback is not allowed");
             }
 
-            // All logs from the parallel execution should be cleared and not reported.
-            // Clear by re-initializing the speculative logs.
-            init_speculative_logs(signature_verified_block.num_txns());
-
-            // Flush all caches to re-run from the "clean" state.
-            module_cache_manager_guard
-                .environment()
-                .runtime_environment()
-                .flush_struct_name_and_info_caches();
-            module_cache_manager_guard.module_cache_mut().flush();
-
+            Self::reset_after_failed_parallel_execution(
+                signature_verified_block,
+                module_cache_manager_guard,
+            );
             info!("parallel execution requiring fallback");
+
+            if self.config.local.conflict_aware_execution == ConflictAwareExecution::Fallback {
+                if let Some(output) = self.execute_transactions_along_conflict_chains(
+                    signature_verified_block,
+                    base_view,
+                    module_cache_manager_guard,
+                ) {
+                    return Ok(output);
+                }
+            }
         }
 
         // If we didn't run parallel, or it didn't finish successfully - run sequential
@@ -143,6 +143,7 @@ mod captured_reads;
 mod code_cache;
 pub mod code_cache_global;
 pub mod code_cache_global_manager;
+pub mod conflict_chains;
 pub mod counters;
 pub mod errors;
 pub mod executor;
@@ -153,6 +154,7 @@ mod limit_processor;
 pub mod proptest_types;
 mod scheduler;
 pub mod task;
+pub mod trace;
 pub mod txn_commit_hook;
 pub mod txn_last_input_output;
 pub mod txn_provider;
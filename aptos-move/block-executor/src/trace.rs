@@ -0,0 +1,159 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Opt-in traces of parallel execution. For every transaction of a block, a trace records the
+//! read and write sets of the committed incarnation, the number of incarnations, and for every
+//! abort the key that failed validation and the transaction that wrote it. Traces are meant to be
+//! analyzed offline, e.g., as a conflict graph, to tune contracts for parallelism.
+
+use crate::types::{InputOutputKey, ReadWriteSummary};
+use anyhow::Context;
+use aptos_logger::error;
+use aptos_mvhashmap::types::{Incarnation, TxnIndex};
+use aptos_types::transaction::BlockExecutableTransaction as Transaction;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashSet,
+    fmt::Debug,
+    fs,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+/// Number of block traces saved by this process, used to name the trace files.
+static NUM_SAVED_BLOCK_TRACES: AtomicU64 = AtomicU64::new(0);
+
+/// An abort of a transaction incarnation, after its validation failed.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct AbortTrace {
+    pub incarnation: Incarnation,
+    /// The key whose read failed validation, if the abort was caused by a data read. Group,
+    /// module and delayed field conflicts are not attributed to a key.
+    pub conflicting_key: Option<String>,
+    /// The transaction whose write invalidated the read, if known.
+    pub aborted_by: Option<TxnIndex>,
+}
+
+/// Execution trace of a single transaction.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct TransactionTrace {
+    pub txn_idx: TxnIndex,
+    /// Number of times the transaction was executed, 0 if it was not committed.
+    pub incarnations: u32,
+    /// Keys read by the committed incarnation.
+    pub reads: Vec<String>,
+    /// Keys written by the committed incarnation.
+    pub writes: Vec<String>,
+    pub aborts: Vec<AbortTrace>,
+}
+
+/// Execution trace of a block executed in parallel.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct BlockTrace {
+    pub num_workers: usize,
+    pub transactions: Vec<TransactionTrace>,
+}
+
+impl BlockTrace {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let bytes = fs::read(path).with_context(|| format!("Unable to read {:?}", path))?;
+        serde_json::from_slice(&bytes).with_context(|| format!("Unable to parse {:?}", path))
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        fs::write(path, serde_json::to_vec(self)?)
+            .with_context(|| format!("Unable to write {:?}", path))
+    }
+}
+
+/// Collects the trace of a block while it is executed by several workers.
+pub(crate) struct BlockTraceCollector {
+    num_workers: usize,
+    transactions: Vec<Mutex<TransactionTrace>>,
+}
+
+impl BlockTraceCollector {
+    pub(crate) fn new(num_txns: u32, num_workers: usize) -> Self {
+        Self {
+            num_workers,
+            transactions: (0..num_txns)
+                .map(|txn_idx| {
+                    Mutex::new(TransactionTrace {
+                        txn_idx,
+                        ..TransactionTrace::default()
+                    })
+                })
+                .collect(),
+        }
+    }
+
+    pub(crate) fn record_abort<K: Debug>(
+        &self,
+        txn_idx: TxnIndex,
+        incarnation: Incarnation,
+        conflict: Option<(K, Option<TxnIndex>)>,
+    ) {
+        let (conflicting_key, aborted_by) = match conflict {
+            Some((key, aborted_by)) => (Some(format!("{:?}", key)), aborted_by),
+            None => (None, None),
+        };
+        self.transactions[txn_idx as usize]
+            .lock()
+            .aborts
+            .push(AbortTrace {
+                incarnation,
+                conflicting_key,
+                aborted_by,
+            });
+    }
+
+    pub(crate) fn record_commit<T: Transaction>(
+        &self,
+        txn_idx: TxnIndex,
+        incarnation: Incarnation,
+        summary: &ReadWriteSummary<T>,
+    ) {
+        let format_keys = |keys: &HashSet<InputOutputKey<T::Key, T::Tag>>| {
+            let mut keys: Vec<_> = keys.iter().map(format_key).collect();
+            keys.sort();
+            keys
+        };
+        let mut trace = self.transactions[txn_idx as usize].lock();
+        trace.incarnations = incarnation + 1;
+        trace.reads = format_keys(summary.reads());
+        trace.writes = format_keys(summary.writes());
+    }
+
+    /// Saves the collected trace to a new file in the given directory. Failures are only logged,
+    /// as tracing must not affect execution.
+    pub(crate) fn save(self, dir: &Path) {
+        let trace = BlockTrace {
+            num_workers: self.num_workers,
+            transactions: self
+                .transactions
+                .into_iter()
+                .map(Mutex::into_inner)
+                .collect(),
+        };
+        let path = block_trace_path(dir, NUM_SAVED_BLOCK_TRACES.fetch_add(1, Ordering::Relaxed));
+        if let Err(err) = fs::create_dir_all(dir)
+            .map_err(anyhow::Error::from)
+            .and_then(|_| trace.save(&path))
+        {
+            error!("[BlockSTM] Unable to save execution trace: {:?}", err);
+        }
+    }
+}
+
+fn block_trace_path(dir: &Path, block_number: u64) -> PathBuf {
+    dir.join(format!("block_trace_{:08}.json", block_number))
+}
+
+fn format_key<K: Debug, T: Debug>(key: &InputOutputKey<K, T>) -> String {
+    match key {
+        InputOutputKey::Resource(key) => format!("{:?}", key),
+        InputOutputKey::Group(key, tag) => format!("{:?}/{:?}", key, tag),
+        InputOutputKey::DelayedField(id) => format!("{:?}", id),
+    }
+}
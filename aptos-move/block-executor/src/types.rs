@@ -25,6 +25,14 @@ impl<T: Transaction> ReadWriteSummary<T> {
         Self { reads, writes }
     }
 
+    pub fn reads(&self) -> &HashSet<InputOutputKey<T::Key, T::Tag>> {
+        &self.reads
+    }
+
+    pub fn writes(&self) -> &HashSet<InputOutputKey<T::Key, T::Tag>> {
+        &self.writes
+    }
+
     pub fn conflicts_with_previous(&self, previous: &Self) -> bool {
         !self.reads.is_disjoint(&previous.writes)
     }
@@ -6,6 +6,7 @@ mod code_cache_tests;
 
 use crate::{
     code_cache_global_manager::AptosModuleCacheManagerGuard,
+    conflict_chains::{ConflictChains, ConflictHistory},
     errors::SequentialBlockExecutionError,
     executor::BlockExecutor,
     proptest_types::{
@@ -18,6 +19,7 @@ use crate::{
     scheduler::{
         DependencyResult, ExecutionTaskType, Scheduler, SchedulerTask, TWaitForDependency,
     },
+    trace::BlockTrace,
     txn_commit_hook::NoOpTransactionCommitHook,
     txn_provider::default::DefaultTxnProvider,
 };
@@ -27,6 +29,7 @@ use aptos_aggregator::{
     delta_math::DeltaHistory,
 };
 use aptos_mvhashmap::types::TxnIndex;
+use aptos_temppath::TempPath;
 use aptos_types::{
     block_executor::config::BlockExecutorConfig,
     contract_event::TransactionEvent,
@@ -34,7 +37,7 @@ use aptos_types::{
     state_store::state_value::StateValueMetadata,
     write_set::WriteOpKind,
 };
-use claims::{assert_matches, assert_ok};
+use claims::{assert_lt, assert_matches, assert_ok};
 use fail::FailScenario;
 use rand::{prelude::*, random};
 use std::{
@@ -561,6 +564,172 @@ fn one_writes_all_barrier() {
     run_and_assert(transactions)
 }
 
+#[test]
+fn execution_trace() {
+    let key = KeyType(random::<[u8; 32]>(), false);
+    // Every transaction reads and writes the same key, so all but the first may be aborted.
+    let transactions: Vec<_> = (0..TXN_PER_BLOCK)
+        .map(|_| {
+            MockTransaction::from_behavior(MockIncarnation::<KeyType<[u8; 32]>, MockEvent>::new(
+                vec![key],                        // reads
+                vec![(key, random_value(false))], // writes
+                vec![],
+                vec![],
+                1, // gas
+            ))
+        })
+        .collect();
+
+    let trace_dir = TempPath::new();
+    let mut config = BlockExecutorConfig::new_no_block_limit(num_cpus::get().max(2));
+    config.local.execution_trace_dir = Some(trace_dir.path().to_path_buf());
+
+    let data_view = DeltaDataView::<KeyType<[u8; 32]>> {
+        phantom: PhantomData,
+    };
+    let executor_thread_pool = Arc::new(
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(num_cpus::get())
+            .build()
+            .unwrap(),
+    );
+    let mut guard = AptosModuleCacheManagerGuard::none();
+    let txn_provider = DefaultTxnProvider::new(transactions);
+    let output = BlockExecutor::<
+        MockTransaction<KeyType<[u8; 32]>, MockEvent>,
+        MockTask<KeyType<[u8; 32]>, MockEvent>,
+        DeltaDataView<KeyType<[u8; 32]>>,
+        NoOpTransactionCommitHook<MockOutput<KeyType<[u8; 32]>, MockEvent>, usize>,
+        ExecutableTestType,
+        _,
+    >::new(config, executor_thread_pool, None)
+    .execute_transactions_parallel(&txn_provider, &data_view, &mut guard);
+    assert_ok!(output);
+
+    let trace_files: Vec<_> = std::fs::read_dir(trace_dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    assert_eq!(trace_files.len(), 1);
+    let trace = BlockTrace::load(&trace_files[0]).unwrap();
+
+    assert_eq!(trace.transactions.len(), TXN_PER_BLOCK as usize);
+    let formatted_key = format!("{:?}", key);
+    for (txn_idx, txn_trace) in trace.transactions.iter().enumerate() {
+        assert_eq!(txn_trace.txn_idx as usize, txn_idx);
+        assert_eq!(txn_trace.reads, vec![formatted_key.clone()]);
+        assert_eq!(txn_trace.writes, vec![formatted_key.clone()]);
+        assert!(txn_trace.incarnations as usize > txn_trace.aborts.len());
+        for abort in &txn_trace.aborts {
+            // Only earlier transactions can invalidate a read.
+            if let Some(aborted_by) = abort.aborted_by {
+                assert_lt!(aborted_by as usize, txn_idx);
+            }
+            if let Some(conflicting_key) = &abort.conflicting_key {
+                assert_eq!(conflicting_key, &formatted_key);
+            }
+        }
+    }
+}
+
+#[test]
+fn conflict_chains() {
+    let chains = ConflictChains::new(vec![None, Some(0), Some(2), Some(0), None, Some(3)]);
+    assert_eq!(chains.num_txns(), 6);
+    // Predecessors that do not precede the transaction are ignored.
+    assert_eq!(chains.predecessor(2), None);
+    assert_eq!(chains.predecessor(5), Some(3));
+    assert_eq!(chains.predecessor(6), None);
+    assert_eq!(chains.num_chains(), 3);
+}
+
+#[test]
+fn conflict_history() {
+    let history = ConflictHistory::default();
+    assert!(!history.is_contended());
+    history.record_block(0, 0);
+    assert!(!history.is_contended());
+
+    // A single contended block is not enough.
+    history.record_block(100, 100);
+    assert!(!history.is_contended());
+    for _ in 0..5 {
+        history.record_block(100, 100);
+    }
+    assert!(history.is_contended());
+
+    // Once blocks stop being contended, the history recovers.
+    for _ in 0..5 {
+        history.record_block(100, 0);
+    }
+    assert!(!history.is_contended());
+}
+
+#[test]
+fn execution_along_conflict_chains() {
+    let num_chains = 4;
+    let keys: Vec<KeyType<[u8; 32]>> = (0..num_chains)
+        .map(|_| KeyType(random::<[u8; 32]>(), false))
+        .collect();
+    let transactions: Vec<_> = (0..TXN_PER_BLOCK as usize)
+        .map(|txn_idx| {
+            let key = keys[txn_idx % num_chains];
+            // Every few transactions also read a key of another chain, which the predicted
+            // chains miss, so that the execution still has to be validated.
+            let mut reads = vec![key];
+            if txn_idx % 7 == 0 {
+                reads.push(keys[(txn_idx + 1) % num_chains]);
+            }
+            MockTransaction::from_behavior(MockIncarnation::<KeyType<[u8; 32]>, MockEvent>::new(
+                reads,
+                vec![(key, random_value(false))], // writes
+                vec![],
+                vec![],
+                1, // gas
+            ))
+        })
+        .collect();
+    let conflict_chains = ConflictChains::new(
+        (0..TXN_PER_BLOCK as TxnIndex)
+            .map(|txn_idx| txn_idx.checked_sub(num_chains as TxnIndex))
+            .collect(),
+    );
+    assert_eq!(conflict_chains.num_chains(), num_chains);
+
+    let data_view = DeltaDataView::<KeyType<[u8; 32]>> {
+        phantom: PhantomData,
+    };
+    let executor_thread_pool = Arc::new(
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(num_cpus::get())
+            .build()
+            .unwrap(),
+    );
+    let mut guard = AptosModuleCacheManagerGuard::none();
+    let txn_provider = DefaultTxnProvider::new(transactions);
+    let output = BlockExecutor::<
+        MockTransaction<KeyType<[u8; 32]>, MockEvent>,
+        MockTask<KeyType<[u8; 32]>, MockEvent>,
+        DeltaDataView<KeyType<[u8; 32]>>,
+        NoOpTransactionCommitHook<MockOutput<KeyType<[u8; 32]>, MockEvent>, usize>,
+        ExecutableTestType,
+        _,
+    >::new(
+        BlockExecutorConfig::new_no_block_limit(num_cpus::get().max(2)),
+        executor_thread_pool,
+        None,
+    )
+    .execute_transactions_parallel_with_conflict_chains(
+        &txn_provider,
+        &data_view,
+        &mut guard,
+        Some(&conflict_chains),
+    );
+
+    let baseline = BaselineOutput::generate(txn_provider.get_txns(), None);
+    baseline.assert_parallel_output(&output);
+}
+
 #[test]
 fn early_aborts() {
     let mut transactions = vec![];
@@ -32,7 +32,7 @@ use aptos_types::{
     block_executor::{
         config::{
             BlockExecutorConfig, BlockExecutorConfigFromOnchain, BlockExecutorLocalConfig,
-            BlockExecutorModuleCacheLocalConfig,
+            BlockExecutorModuleCacheLocalConfig, ConflictAwareExecution,
         },
         transaction_slice_metadata::TransactionSliceMetadata,
     },
@@ -673,7 +673,9 @@ impl FakeExecutor {
                 },
                 allow_fallback: self.allow_block_executor_fallback,
                 discard_failed_blocks: false,
+                conflict_aware_execution: ConflictAwareExecution::Disabled,
                 module_cache_config: BlockExecutorModuleCacheLocalConfig::default(),
+                execution_trace_dir: None,
             },
             onchain: onchain_config,
         };
@@ -10,7 +10,7 @@ use aptos_logger::error;
 use aptos_types::{
     block_executor::config::{
         BlockExecutorConfig, BlockExecutorConfigFromOnchain, BlockExecutorLocalConfig,
-        BlockExecutorModuleCacheLocalConfig,
+        BlockExecutorModuleCacheLocalConfig, ConflictAwareExecution,
     },
     state_store::{state_key::StateKey, state_value::StateValue, StateView},
     transaction::{TransactionOutput, Version},
@@ -25,7 +25,9 @@ fn block_execution_config(concurrency_level: usize) -> BlockExecutorConfig {
             concurrency_level,
             allow_fallback: true,
             discard_failed_blocks: false,
+            conflict_aware_execution: ConflictAwareExecution::Disabled,
             module_cache_config: BlockExecutorModuleCacheLocalConfig::default(),
+            execution_trace_dir: None,
         },
         // For replay, there is no block limit.
         onchain: BlockExecutorConfigFromOnchain::new_no_block_limit(),
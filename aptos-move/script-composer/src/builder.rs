@@ -33,11 +33,13 @@ use move_core_types::{
     transaction_argument::TransactionArgument,
 };
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
 use serde_json::Value;
 use std::{collections::BTreeMap, str::FromStr};
+#[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
 
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 #[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
 pub struct AllocatedLocal {
     op_type: ArgumentOperation,
@@ -46,7 +48,7 @@ pub struct AllocatedLocal {
 }
 
 #[derive(Clone, Debug)]
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 struct BuilderCall {
     type_args: Vec<SignatureToken>,
     call_idx: FunctionHandleIndex,
@@ -59,7 +61,7 @@ struct BuilderCall {
 }
 
 #[derive(Clone, Debug)]
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 pub struct TransactionComposer {
     modules: BTreeMap<ModuleId, CompiledModule>,
     builder: CompiledScriptBuilder,
@@ -74,7 +76,7 @@ pub struct TransactionComposer {
     signer_count: u16,
 }
 
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 impl TransactionComposer {
     /// Create a builder with one distinct signer available. This should be the default configuration.
     pub fn single_signer() -> Self {
@@ -126,7 +128,11 @@ impl TransactionComposer {
         self.generate_batched_calls_impl(with_metadata)
             .map_err(|e| e.to_string())
     }
+}
 
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+impl TransactionComposer {
     /// Load up a module from a remote endpoint. Will need to invoke this function prior to the
     /// call.
     pub async fn load_module(
@@ -438,6 +444,7 @@ impl TransactionComposer {
         .unwrap())
     }
 
+    #[cfg(feature = "wasm")]
     async fn load_module_impl(&mut self, api_url: &str, module_id: ModuleId) -> anyhow::Result<()> {
         let url = format!(
             "{}/{}/{}/{}/{}",
@@ -475,6 +482,7 @@ impl TransactionComposer {
         }
     }
 
+    #[cfg(feature = "wasm")]
     async fn load_type_tag_impl(
         &mut self,
         api_url: &str,
@@ -573,6 +581,7 @@ impl AllocatedLocal {
     }
 }
 
+#[cfg(feature = "wasm")]
 #[derive(Clone, Debug)]
 pub enum ArgumentType {
     Signer,
@@ -581,6 +590,7 @@ pub enum ArgumentType {
 }
 
 /// WASM Representation of CallArgument. This is because wasm_bindgen can only support c-style enum.
+#[cfg(feature = "wasm")]
 #[wasm_bindgen(js_name = "CallArgument")]
 #[derive(Clone, Debug)]
 pub struct CallArgumentWasm {
@@ -590,6 +600,7 @@ pub struct CallArgumentWasm {
     previous_result: Option<PreviousResult>,
 }
 
+#[cfg(feature = "wasm")]
 impl From<CallArgument> for CallArgumentWasm {
     fn from(value: CallArgument) -> Self {
         match value {
@@ -615,6 +626,7 @@ impl From<CallArgument> for CallArgumentWasm {
     }
 }
 
+#[cfg(feature = "wasm")]
 impl From<CallArgumentWasm> for CallArgument {
     fn from(value: CallArgumentWasm) -> Self {
         match value.ty {
@@ -627,6 +639,7 @@ impl From<CallArgumentWasm> for CallArgument {
     }
 }
 
+#[cfg(feature = "wasm")]
 #[wasm_bindgen(js_class = "CallArgument")]
 impl CallArgumentWasm {
     pub fn new_bytes(bytes: Vec<u8>) -> Self {
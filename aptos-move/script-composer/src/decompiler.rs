@@ -21,6 +21,7 @@ use move_core_types::{
     transaction_argument::{convert_txn_args, TransactionArgument},
 };
 use std::collections::BTreeMap;
+#[cfg(feature = "wasm")]
 use wasm_bindgen::{prelude::*, JsValue};
 
 struct LocalState {
@@ -273,6 +274,7 @@ pub fn generate_batched_call_payload_serialized(
 }
 
 /// Wrapper to decompile script in its serialized form and wrap it with wasm errors.
+#[cfg(feature = "wasm")]
 #[wasm_bindgen]
 pub fn generate_batched_call_payload_wasm(
     script: Vec<u8>,
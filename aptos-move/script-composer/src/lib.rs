@@ -2,15 +2,18 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+#[cfg(feature = "wasm")]
+pub use crate::decompiler::generate_batched_call_payload_wasm;
 pub use crate::{
     builder::TransactionComposer,
-    decompiler::{generate_batched_call_payload, generate_batched_call_payload_wasm},
+    decompiler::{generate_batched_call_payload, generate_batched_call_payload_serialized},
 };
 use move_core_types::{
     identifier::Identifier,
     language_storage::{ModuleId, TypeTag},
 };
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::wasm_bindgen;
 
 mod builder;
@@ -25,7 +28,7 @@ pub mod tests;
 pub static APTOS_SCRIPT_COMPOSER_KEY: &[u8] = "aptos::script_composer".as_bytes();
 
 /// Representing a returned value from a previous list of `MoveFunctionCall`s.
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 #[derive(Clone, Debug, Hash, Eq, PartialEq, Serialize, Deserialize)]
 pub struct PreviousResult {
     /// Refering to the return value in the `call_idx`th call.
@@ -69,7 +72,7 @@ pub enum ArgumentOperation {
 ///
 /// Similar to a public entry function call, but the arguments could specified as `CallArgument`,
 /// which can be a return value of a previous `MoveFunctionCall`.
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 #[derive(Clone, Debug, Hash, Eq, PartialEq, Serialize, Deserialize)]
 pub struct MoveFunctionCall {
     module: ModuleId,
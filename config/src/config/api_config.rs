@@ -83,6 +83,10 @@ pub struct ApiConfig {
     pub view_filter: ViewFilter,
     /// Periodically log stats for view function and simulate transaction usage
     pub periodic_function_stats_sec: Option<u64>,
+    /// Maximum number of simulation outputs to cache, by state version and transaction.
+    ///
+    /// Set to 0 to disable caching of outputs. Verified modules are cached regardless.
+    pub simulation_output_cache_size: usize,
     /// The time wait_by_hash will wait before returning 404.
     pub wait_by_hash_timeout_ms: u64,
     /// The interval at which wait_by_hash will poll the storage for the transaction.
@@ -138,6 +142,7 @@ impl Default for ApiConfig {
             simulation_filter: Filter::default(),
             view_filter: ViewFilter::default(),
             periodic_function_stats_sec: Some(60),
+            simulation_output_cache_size: 1_000,
             wait_by_hash_timeout_ms: 1_000,
             wait_by_hash_poll_interval_ms: 20,
             wait_by_hash_max_active_connections: 100,
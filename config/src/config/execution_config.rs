@@ -8,7 +8,10 @@ use crate::config::{
     node_config_loader::NodeType, transaction_filter_type::Filter, utils::RootPath, Error,
     NodeConfig,
 };
-use aptos_types::{chain_id::ChainId, transaction::Transaction, waypoint::Waypoint};
+use aptos_types::{
+    block_executor::config::ConflictAwareExecution, chain_id::ChainId, transaction::Transaction,
+    waypoint::Waypoint,
+};
 use serde::{Deserialize, Serialize};
 use serde_yaml::Value;
 use std::{
@@ -46,6 +49,10 @@ pub struct ExecutionConfig {
     pub paranoid_type_verification: bool,
     /// Enabled discarding blocks that fail execution due to BlockSTM/VM issue.
     pub discard_failed_blocks: bool,
+    /// When to execute blocks along predicted conflict chains, e.g., while recent blocks are
+    /// contended, or to re-execute blocks whose parallel execution failed before falling back to
+    /// sequential execution.
+    pub conflict_aware_execution: ConflictAwareExecution,
     /// Enables paranoid mode for hot potatoes, which adds extra runtime VM checks
     pub paranoid_hot_potato_verification: bool,
     /// Enables enhanced metrics around processed transactions
@@ -83,6 +90,7 @@ impl Default for ExecutionConfig {
             paranoid_type_verification: true,
             paranoid_hot_potato_verification: true,
             discard_failed_blocks: false,
+            conflict_aware_execution: ConflictAwareExecution::Disabled,
             processed_transactions_detailed_counters: false,
             transaction_filter: Filter::empty(),
             genesis_waypoint: None,
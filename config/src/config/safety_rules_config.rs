@@ -12,7 +12,7 @@ use crate::{
     keys::ConfigKey,
 };
 use anyhow::bail;
-use aptos_crypto::{bls12381, Uniform};
+use aptos_crypto::{bls12381, x25519, Uniform};
 use aptos_types::{chain_id::ChainId, network_address::NetworkAddress, waypoint::Waypoint, PeerId};
 use rand::rngs::StdRng;
 use serde::{Deserialize, Serialize};
@@ -202,7 +202,7 @@ impl InitialSafetyRulesConfig {
 }
 
 /// Defines how safety rules should be executed
-#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(rename_all = "snake_case", tag = "type")]
 pub enum SafetyRulesService {
     /// This runs safety rules in the same thread as event processor
@@ -223,10 +223,14 @@ impl SafetyRulesService {
     }
 }
 
-#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct RemoteService {
     pub server_address: NetworkAddress,
+    /// If set, the connection between the node and the remote safety rules service is mutually
+    /// authenticated and encrypted using a Noise IK handshake.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub noise: Option<RemoteServiceNoiseConfig>,
 }
 
 impl RemoteService {
@@ -239,6 +243,15 @@ impl RemoteService {
     }
 }
 
+/// The Noise keys for one end of the connection to the remote safety rules service. The node and
+/// the service each hold their own static key and the public key of the other end.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct RemoteServiceNoiseConfig {
+    pub private_key: ConfigKey<x25519::PrivateKey>,
+    pub remote_public_key: x25519::PublicKey,
+}
+
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct SafetyRulesTestConfig {
     pub author: PeerId,
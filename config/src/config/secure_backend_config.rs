@@ -3,7 +3,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::config::Error;
-use aptos_secure_storage::{InMemoryStorage, Namespaced, OnDiskStorage, Storage, VaultStorage};
+use aptos_secure_storage::{
+    Bls12381Mechanisms, InMemoryStorage, Namespaced, OnDiskStorage, Pkcs11Storage, Storage,
+    VaultStorage,
+};
 use serde::{Deserialize, Serialize};
 use std::{
     fs::File,
@@ -20,15 +23,15 @@ pub enum SecureBackend {
     InMemoryStorage,
     Vault(VaultConfig),
     OnDiskStorage(OnDiskStorageConfig),
+    Pkcs11(Pkcs11Config),
 }
 
 impl SecureBackend {
     pub fn namespace(&self) -> Option<&str> {
         match self {
             SecureBackend::Vault(VaultConfig { namespace, .. })
-            | SecureBackend::OnDiskStorage(OnDiskStorageConfig { namespace, .. }) => {
-                namespace.as_deref()
-            },
+            | SecureBackend::OnDiskStorage(OnDiskStorageConfig { namespace, .. })
+            | SecureBackend::Pkcs11(Pkcs11Config { namespace, .. }) => namespace.as_deref(),
             SecureBackend::InMemoryStorage => None,
         }
     }
@@ -36,7 +39,8 @@ impl SecureBackend {
     pub fn clear_namespace(&mut self) {
         match self {
             SecureBackend::Vault(VaultConfig { namespace, .. })
-            | SecureBackend::OnDiskStorage(OnDiskStorageConfig { namespace, .. }) => {
+            | SecureBackend::OnDiskStorage(OnDiskStorageConfig { namespace, .. })
+            | SecureBackend::Pkcs11(Pkcs11Config { namespace, .. }) => {
                 *namespace = None;
             },
             SecureBackend::InMemoryStorage => {},
@@ -97,6 +101,23 @@ pub struct OnDiskStorageConfig {
     data_dir: PathBuf,
 }
 
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Pkcs11Config {
+    /// Path to the PKCS#11 module (shared library) of the HSM, e.g., libsofthsm2.so.
+    pub library_path: PathBuf,
+    /// Label of the token that holds the keys.
+    pub token_label: String,
+    /// The user PIN for logging into the token.
+    pub user_pin: Token,
+    /// A namespace is an optional prefix for the labels of the objects stored on the token. For
+    /// example, a key, S, without a namespace would be labelled S, with a namespace, N, it would
+    /// be labelled N/S.
+    pub namespace: Option<String>,
+    /// The vendor-defined mechanisms for BLS12-381 keys, if the HSM supports them.
+    pub bls12381_mechanisms: Option<Bls12381Mechanisms>,
+}
+
 /// Tokens can either be directly within this config or stored somewhere on disk.
 #[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
 #[serde(rename_all = "snake_case")]
@@ -191,6 +212,24 @@ impl From<&SecureBackend> for Storage {
                     storage
                 }
             },
+            SecureBackend::Pkcs11(config) => {
+                // The session is opened on first use, so any failure to read the user PIN or to
+                // open the session is returned by the storage operations.
+                let storage = Storage::from(Pkcs11Storage::new(
+                    config.library_path.clone(),
+                    config.token_label.clone(),
+                    config
+                        .user_pin
+                        .read_token()
+                        .map_err(|error| error.to_string()),
+                    config.bls12381_mechanisms.clone(),
+                ));
+                if let Some(namespace) = &config.namespace {
+                    Storage::from(Namespaced::new(namespace, Box::new(storage)))
+                } else {
+                    storage
+                }
+            },
         }
     }
 }
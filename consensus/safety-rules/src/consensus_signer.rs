@@ -0,0 +1,51 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{error::Error, persistent_safety_storage::PersistentSafetyStorage};
+use aptos_consensus_types::common::Author;
+use aptos_crypto::{bls12381, hash::CryptoHash};
+use aptos_types::validator_signer::ValidatorSigner;
+use serde::Serialize;
+
+/// Signs on behalf of the validator with its consensus key. The key is either loaded from the
+/// secure storage, or, for storages that keep BLS12-381 keys as key handles (e.g., HSMs), left
+/// there and signed with through the storage, so it never leaves it.
+pub(crate) enum ConsensusSigner {
+    Local(ValidatorSigner),
+    Storage {
+        author: Author,
+        public_key: bls12381::PublicKey,
+        key_name: String,
+    },
+}
+
+impl ConsensusSigner {
+    pub fn author(&self) -> Author {
+        match self {
+            ConsensusSigner::Local(signer) => signer.author(),
+            ConsensusSigner::Storage { author, .. } => *author,
+        }
+    }
+
+    pub fn public_key(&self) -> bls12381::PublicKey {
+        match self {
+            ConsensusSigner::Local(signer) => signer.public_key(),
+            ConsensusSigner::Storage { public_key, .. } => public_key.clone(),
+        }
+    }
+
+    pub fn sign<T: Serialize + CryptoHash>(
+        &self,
+        storage: &PersistentSafetyStorage,
+        message: &T,
+    ) -> Result<bls12381::Signature, Error> {
+        match self {
+            ConsensusSigner::Local(signer) => signer
+                .sign(message)
+                .map_err(|err| Error::SerializationError(err.to_string())),
+            ConsensusSigner::Storage { key_name, .. } => {
+                storage.sign_with_consensus_key(key_name, message)
+            },
+        }
+    }
+}
@@ -21,6 +21,8 @@ pub enum Error {
     InvalidAccumulatorExtension(String),
     #[error("Invalid EpochChangeProof: {0}")]
     InvalidEpochChangeProof(String),
+    #[error("Invalid safety data interchange: {0}")]
+    InvalidSafetyDataInterchange(String),
     #[error("Internal error: {0}")]
     InternalError(String),
     #[error("No next_epoch_state specified in the provided Ledger Info")]
@@ -57,6 +59,8 @@ pub enum Error {
     InvalidOrderedLedgerInfo(String),
     #[error("Waypoint out of date: Previous waypoint version {0}, updated version {1}, current epoch {2}, provided epoch {3}")]
     WaypointOutOfDate(u64, u64, u64, u64),
+    #[error("Imported safety data is from epoch {0}, which is older than the on-chain epoch {1}. Export the safety data again from the previous host after stopping it")]
+    StaleImportedSafetyData(u64, u64),
     #[error("Invalid Timeout: {0}")]
     InvalidTimeout(String),
     #[error("Incorrect 1-chain Quorum Certificate provided for signing order votes. Quorum Certificate: {0}, block id: {1}")]
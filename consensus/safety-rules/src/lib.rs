@@ -4,6 +4,7 @@
 
 #![forbid(unsafe_code)]
 
+mod consensus_signer;
 mod consensus_state;
 mod counters;
 mod error;
@@ -16,13 +17,18 @@ mod safety_rules;
 mod safety_rules_2chain;
 pub mod safety_rules_manager;
 mod serializer;
+mod slashing_protection;
 mod t_safety_rules;
 mod thread;
 
 pub use crate::{
-    consensus_state::ConsensusState, error::Error,
-    persistent_safety_storage::PersistentSafetyStorage, process::Process,
-    safety_rules::SafetyRules, safety_rules_manager::SafetyRulesManager,
+    consensus_state::ConsensusState,
+    error::Error,
+    persistent_safety_storage::PersistentSafetyStorage,
+    process::Process,
+    safety_rules::SafetyRules,
+    safety_rules_manager::SafetyRulesManager,
+    slashing_protection::{SafetyDataInterchange, SAFETY_DATA_INTERCHANGE_VERSION},
     t_safety_rules::TSafetyRules,
 };
 
@@ -0,0 +1,66 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use aptos_config::config::NodeConfig;
+use aptos_logger::{info, Level, Logger};
+use aptos_safety_rules::{safety_rules_manager, Process, SafetyDataInterchange};
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+
+/// Runs safety rules as a standalone remote signer, and moves its safety data between hosts.
+#[derive(Parser)]
+#[clap(name = "aptos-safety-rules", author, version)]
+struct Args {
+    /// Path to the node config containing the safety rules config
+    #[clap(long, short = 'f', value_parser)]
+    config: PathBuf,
+
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Runs the safety rules service configured as `process` in the node config
+    Run,
+    /// Exports the safety data, so that it can be imported on a new host. Stop the safety rules
+    /// service before exporting, so that it cannot vote after the export.
+    ExportSafetyData {
+        #[clap(long, value_parser)]
+        output_file: PathBuf,
+    },
+    /// Imports the safety data exported on the previous host. Safety rules will refuse to
+    /// initialize if the imported data turns out to be older than the on-chain epoch.
+    ImportSafetyData {
+        #[clap(long, value_parser)]
+        input_file: PathBuf,
+    },
+}
+
+fn main() -> Result<()> {
+    Logger::new().level(Level::Info).init();
+
+    let args = Args::parse();
+    let node_config = NodeConfig::load_from_path(&args.config)?;
+    let config = node_config.consensus.safety_rules;
+
+    match args.command {
+        Command::Run => Process::new(config).start(),
+        Command::ExportSafetyData { output_file } => {
+            let mut storage = safety_rules_manager::storage(&config);
+            let interchange = storage.export_safety_data()?;
+            interchange.to_file(&output_file)?;
+            info!(
+                "Exported safety data for epoch {} to {:?}",
+                interchange.epoch, output_file
+            );
+        },
+        Command::ImportSafetyData { input_file } => {
+            let interchange = SafetyDataInterchange::from_file(&input_file)?;
+            let mut storage = safety_rules_manager::storage(&config);
+            storage.import_safety_data(&interchange)?;
+        },
+    }
+    Ok(())
+}
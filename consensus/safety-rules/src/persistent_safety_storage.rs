@@ -5,14 +5,18 @@
 use crate::{
     counters,
     logging::{self, LogEntry, LogEvent},
+    slashing_protection::SafetyDataInterchange,
     Error,
 };
 use aptos_consensus_types::{common::Author, safety_data::SafetyData};
-use aptos_crypto::{bls12381, PrivateKey};
-use aptos_global_constants::{CONSENSUS_KEY, OWNER_ACCOUNT, SAFETY_DATA, WAYPOINT};
+use aptos_crypto::{bls12381, hash::CryptoHash, PrivateKey};
+use aptos_global_constants::{
+    CONSENSUS_KEY, OWNER_ACCOUNT, SAFETY_DATA, SAFETY_DATA_IMPORTED, WAYPOINT,
+};
 use aptos_logger::prelude::*;
-use aptos_secure_storage::{KVStorage, Storage};
+use aptos_secure_storage::{Bls12381Storage, KVStorage, Storage};
 use aptos_types::waypoint::Waypoint;
+use serde::Serialize;
 
 /// SafetyRules needs an abstract storage interface to act as a common utility for storing
 /// persistent data to local disk, cloud, secrets managers, or even memory (for tests)
@@ -66,7 +70,13 @@ impl PersistentSafetyStorage {
         author: Author,
         consensus_private_key: bls12381::PrivateKey,
     ) -> Result<(), Error> {
-        let result = internal_store.set(CONSENSUS_KEY, consensus_private_key);
+        // Storages that can keep BLS12-381 keys as key handles (e.g., HSMs) hold on to the
+        // consensus key, so that it never leaves them.
+        let result = if internal_store.supports_bls12381_keys() {
+            internal_store.import_bls12381_key(CONSENSUS_KEY, consensus_private_key)
+        } else {
+            internal_store.set(CONSENSUS_KEY, consensus_private_key)
+        };
         // Attempting to re-initialize existing storage. This can happen in environments like
         // forge. Rather than be rigid here, leave it up to the developer to detect
         // inconsistencies or why they did not reset storage between rounds. Do not repeat the
@@ -96,6 +106,49 @@ impl PersistentSafetyStorage {
         Ok(self.internal_store.get(OWNER_ACCOUNT).map(|v| v.value)?)
     }
 
+    /// Returns true iff the consensus key is kept as a key handle in the internal store. Such a
+    /// key can't be exported, so it is signed with through `sign_with_consensus_key` instead.
+    pub fn consensus_key_in_storage(&self) -> bool {
+        self.internal_store.supports_bls12381_keys()
+    }
+
+    /// Returns the name of the consensus key handle for the given public key, preferring the key
+    /// stored explicitly for it over the default one.
+    pub fn consensus_key_name_by_pk(&self, pk: &bls12381::PublicKey) -> Result<String, Error> {
+        let _timer = counters::start_timer("get", CONSENSUS_KEY);
+        let explicit_storage_key = explicit_consensus_key_name(pk);
+        let explicit_pk = self
+            .internal_store
+            .get_bls12381_public_key(&explicit_storage_key);
+        let default_pk = self.internal_store.get_bls12381_public_key(CONSENSUS_KEY);
+        let (name, public_key) = match (explicit_pk, default_pk) {
+            (Ok(pk_0), _) => (explicit_storage_key, pk_0),
+            (Err(_), Ok(pk_1)) => (CONSENSUS_KEY.to_string(), pk_1),
+            (Err(_), Err(_)) => {
+                return Err(Error::ValidatorKeyNotFound("not found!".to_string()));
+            },
+        };
+        if &public_key != pk {
+            return Err(Error::SecureStorageMissingDataError(format!(
+                "Incorrect key saved for {:?} the expected pk",
+                pk
+            )));
+        }
+        Ok(name)
+    }
+
+    /// Signs the message with the named consensus key handle of the internal store.
+    pub fn sign_with_consensus_key<T: Serialize + CryptoHash>(
+        &self,
+        name: &str,
+        message: &T,
+    ) -> Result<bls12381::Signature, Error> {
+        let _timer = counters::start_timer("sign", CONSENSUS_KEY);
+        Ok(self.internal_store.sign_bls12381(name, message)?)
+    }
+
+    /// Returns the consensus private key stored in the internal store. This fails for consensus
+    /// keys kept as key handles (see `consensus_key_in_storage`), as those can't be exported.
     pub fn default_consensus_sk(
         &self,
     ) -> Result<bls12381::PrivateKey, aptos_secure_storage::Error> {
@@ -109,8 +162,7 @@ impl PersistentSafetyStorage {
         pk: bls12381::PublicKey,
     ) -> Result<bls12381::PrivateKey, Error> {
         let _timer = counters::start_timer("get", CONSENSUS_KEY);
-        let pk_hex = hex::encode(pk.to_bytes());
-        let explicit_storage_key = format!("{}_{}", CONSENSUS_KEY, pk_hex);
+        let explicit_storage_key = explicit_consensus_key_name(&pk);
         let explicit_sk = self
             .internal_store
             .get::<bls12381::PrivateKey>(explicit_storage_key.as_str())
@@ -170,6 +222,50 @@ impl PersistentSafetyStorage {
         }
     }
 
+    /// Exports the SafetyData so that it can be imported on another host.
+    pub fn export_safety_data(&mut self) -> Result<SafetyDataInterchange, Error> {
+        let author = self.author()?;
+        let safety_data = self.safety_data()?;
+        Ok(SafetyDataInterchange::new(author, &safety_data))
+    }
+
+    /// Imports SafetyData exported from another host, merging it with the local SafetyData. The
+    /// imported data is marked as such until SafetyRules confirms that it is not older than the
+    /// on-chain epoch.
+    pub fn import_safety_data(&mut self, interchange: &SafetyDataInterchange) -> Result<(), Error> {
+        let author = self.author()?;
+        if interchange.author != author {
+            return Err(Error::InvalidSafetyDataInterchange(format!(
+                "Imported safety data belongs to {}, but this storage belongs to {}",
+                interchange.author, author
+            )));
+        }
+
+        let safety_data = interchange.merge_into(self.safety_data()?)?;
+        self.set_safety_data(safety_data)?;
+        self.set_safety_data_imported(true)?;
+        info!(
+            "Imported safety data for epoch {}, last voted round {}",
+            interchange.epoch, interchange.last_voted_round
+        );
+        Ok(())
+    }
+
+    /// Returns true iff the SafetyData was imported and has not been checked against the
+    /// on-chain epoch yet.
+    pub fn safety_data_imported(&self) -> Result<bool, Error> {
+        match self.internal_store.get::<bool>(SAFETY_DATA_IMPORTED) {
+            Ok(response) => Ok(response.value),
+            Err(aptos_secure_storage::Error::KeyNotSet(_)) => Ok(false),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    pub fn set_safety_data_imported(&mut self, imported: bool) -> Result<(), Error> {
+        self.internal_store.set(SAFETY_DATA_IMPORTED, imported)?;
+        Ok(())
+    }
+
     pub fn waypoint(&self) -> Result<Waypoint, Error> {
         let _timer = counters::start_timer("get", WAYPOINT);
         Ok(self.internal_store.get(WAYPOINT).map(|v| v.value)?)
@@ -188,6 +284,16 @@ impl PersistentSafetyStorage {
     pub fn internal_store(&mut self) -> &mut Storage {
         &mut self.internal_store
     }
+
+    pub fn into_internal_store(self) -> Storage {
+        self.internal_store
+    }
+}
+
+/// Consensus keys other than the default one are stored under names derived from their public
+/// keys.
+fn explicit_consensus_key_name(pk: &bls12381::PublicKey) -> String {
+    format!("{}_{}", CONSENSUS_KEY, hex::encode(pk.to_bytes()))
 }
 
 #[cfg(test)]
@@ -222,6 +328,48 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_export_import_safety_data() {
+        let author = Author::random();
+        let create_storage = || {
+            PersistentSafetyStorage::initialize(
+                Storage::from(InMemoryStorage::new()),
+                author,
+                ValidatorSigner::from_int(0).private_key().clone(),
+                Waypoint::default(),
+                true,
+            )
+        };
+
+        let mut old_host = create_storage();
+        old_host
+            .set_safety_data(SafetyData::new(3, 10, 8, 9, None, 7))
+            .unwrap();
+        let interchange = old_host.export_safety_data().unwrap();
+
+        let mut new_host = create_storage();
+        assert!(!new_host.safety_data_imported().unwrap());
+        new_host.import_safety_data(&interchange).unwrap();
+        assert!(new_host.safety_data_imported().unwrap());
+        assert_eq!(
+            new_host.safety_data().unwrap(),
+            SafetyData::new(3, 10, 8, 9, None, 7)
+        );
+
+        // Safety data of another validator is rejected
+        let mut other_validator = PersistentSafetyStorage::initialize(
+            Storage::from(InMemoryStorage::new()),
+            Author::random(),
+            ValidatorSigner::from_int(0).private_key().clone(),
+            Waypoint::default(),
+            true,
+        );
+        assert!(matches!(
+            other_validator.import_safety_data(&interchange),
+            Err(Error::InvalidSafetyDataInterchange(_))
+        ));
+    }
+
     fn test_safety_data_counters(safety_storage: &mut PersistentSafetyStorage) {
         let safety_data = safety_storage.safety_data().unwrap();
         assert_eq!(safety_data.epoch, 1);
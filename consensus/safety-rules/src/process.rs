@@ -7,7 +7,8 @@ use crate::{
     remote_service::{self, RemoteService},
     safety_rules_manager,
 };
-use aptos_config::config::{SafetyRulesConfig, SafetyRulesService};
+use aptos_config::config::{RemoteServiceNoiseConfig, SafetyRulesConfig, SafetyRulesService};
+use aptos_secure_net::NoiseKeys;
 use std::net::SocketAddr;
 
 pub struct Process {
@@ -29,13 +30,19 @@ impl Process {
                 server_addr,
                 storage,
                 network_timeout: config.network_timeout_ms,
+                noise: service.noise.clone(),
             }),
         }
     }
 
     pub fn start(&mut self) {
         let data = self.data.take().expect("Unable to retrieve ProcessData");
-        remote_service::execute(data.storage, data.server_addr, data.network_timeout);
+        remote_service::execute(
+            data.storage,
+            data.server_addr,
+            data.network_timeout,
+            data.noise.as_ref().map(noise_keys),
+        );
     }
 }
 
@@ -44,18 +51,25 @@ struct ProcessData {
     storage: PersistentSafetyStorage,
     // Timeout in Seconds for network operations
     network_timeout: u64,
+    noise: Option<RemoteServiceNoiseConfig>,
 }
 
 pub struct ProcessService {
     server_addr: SocketAddr,
     network_timeout_ms: u64,
+    noise: Option<RemoteServiceNoiseConfig>,
 }
 
 impl ProcessService {
-    pub fn new(server_addr: SocketAddr, network_timeout: u64) -> Self {
+    pub fn new(
+        server_addr: SocketAddr,
+        network_timeout: u64,
+        noise: Option<RemoteServiceNoiseConfig>,
+    ) -> Self {
         Self {
             server_addr,
             network_timeout_ms: network_timeout,
+            noise,
         }
     }
 }
@@ -68,4 +82,12 @@ impl RemoteService for ProcessService {
     fn network_timeout_ms(&self) -> u64 {
         self.network_timeout_ms
     }
+
+    fn noise_keys(&self) -> Option<NoiseKeys> {
+        self.noise.as_ref().map(noise_keys)
+    }
+}
+
+fn noise_keys(config: &RemoteServiceNoiseConfig) -> NoiseKeys {
+    NoiseKeys::new(config.private_key.private_key(), config.remote_public_key)
 }
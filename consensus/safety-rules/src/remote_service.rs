@@ -8,16 +8,19 @@ use crate::{
     Error, SafetyRules, TSafetyRules,
 };
 use aptos_logger::warn;
-use aptos_secure_net::{NetworkClient, NetworkServer};
+use aptos_secure_net::{NetworkClient, NetworkServer, NoiseKeys};
 use std::net::SocketAddr;
 
 pub trait RemoteService {
     fn client(&self) -> SerializerClient {
-        let network_client = NetworkClient::new(
+        let mut network_client = NetworkClient::new(
             "safety-rules".to_string(),
             self.server_address(),
             self.network_timeout_ms(),
         );
+        if let Some(noise_keys) = self.noise_keys() {
+            network_client = network_client.with_noise_keys(noise_keys);
+        }
         let service = Box::new(RemoteClient::new(network_client));
         SerializerClient::new_client(service)
     }
@@ -26,9 +29,20 @@ pub trait RemoteService {
 
     /// Network Timeout in milliseconds.
     fn network_timeout_ms(&self) -> u64;
+
+    /// The keys used to authenticate to the service. If not set, the connection is neither
+    /// authenticated nor encrypted.
+    fn noise_keys(&self) -> Option<NoiseKeys> {
+        None
+    }
 }
 
-pub fn execute(storage: PersistentSafetyStorage, listen_addr: SocketAddr, network_timeout_ms: u64) {
+pub fn execute(
+    storage: PersistentSafetyStorage,
+    listen_addr: SocketAddr,
+    network_timeout_ms: u64,
+    noise_keys: Option<NoiseKeys>,
+) {
     let mut safety_rules = SafetyRules::new(storage);
     if let Err(e) = safety_rules.consensus_state() {
         warn!("Unable to print consensus state: {}", e);
@@ -37,6 +51,9 @@ pub fn execute(storage: PersistentSafetyStorage, listen_addr: SocketAddr, networ
     let mut serializer_service = SerializerService::new(safety_rules);
     let mut network_server =
         NetworkServer::new("safety-rules".to_string(), listen_addr, network_timeout_ms);
+    if let Some(noise_keys) = noise_keys {
+        network_server = network_server.with_noise_keys(noise_keys);
+    }
 
     loop {
         if let Err(e) = process_one_message(&mut network_server, &mut serializer_service) {
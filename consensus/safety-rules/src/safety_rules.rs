@@ -3,6 +3,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    consensus_signer::ConsensusSigner,
     consensus_state::ConsensusState,
     counters,
     error::Error,
@@ -41,7 +42,7 @@ pub(crate) fn next_round(round: Round) -> Result<Round, Error> {
 /// @TODO consider a cache of verified QCs to cut down on verification costs
 pub struct SafetyRules {
     pub(crate) persistent_storage: PersistentSafetyStorage,
-    pub(crate) validator_signer: Option<ValidatorSigner>,
+    pub(crate) validator_signer: Option<ConsensusSigner>,
     pub(crate) epoch_state: Option<EpochState>,
 }
 
@@ -109,13 +110,10 @@ impl SafetyRules {
         &self,
         message: &T,
     ) -> Result<bls12381::Signature, Error> {
-        let signer = self.signer()?;
-        signer
-            .sign(message)
-            .map_err(|err| Error::SerializationError(err.to_string()))
+        self.signer()?.sign(&self.persistent_storage, message)
     }
 
-    pub(crate) fn signer(&self) -> Result<&ValidatorSigner, Error> {
+    pub(crate) fn signer(&self) -> Result<&ConsensusSigner, Error> {
         self.validator_signer
             .as_ref()
             .ok_or_else(|| Error::NotInitialized("validator_signer".into()))
@@ -274,6 +272,23 @@ impl SafetyRules {
         }
 
         let current_epoch = self.persistent_storage.safety_data()?.epoch;
+        if self.persistent_storage.safety_data_imported()? {
+            // Safety data exported before the previous host stopped voting would be older than
+            // the on-chain epoch if the previous host kept running. Starting a new epoch from it
+            // could then sign votes that conflict with those of the previous host.
+            // The mark is only cleared once the imported epoch is confirmed to be the on-chain
+            // one, so that a proof for a newer epoch is still checked against the imported data.
+            match current_epoch.cmp(&epoch_state.epoch) {
+                Ordering::Less => {
+                    return Err(Error::StaleImportedSafetyData(
+                        current_epoch,
+                        epoch_state.epoch,
+                    ));
+                },
+                Ordering::Equal => self.persistent_storage.set_safety_data_imported(false)?,
+                Ordering::Greater => (),
+            }
+        }
         match current_epoch.cmp(&epoch_state.epoch) {
             Ordering::Greater => {
                 // waypoint is not up to the current epoch.
@@ -315,11 +330,29 @@ impl SafetyRules {
                     );
                     Ok(())
                 } else {
-                    // Try to export the consensus key directly from storage.
-                    match self.persistent_storage.consensus_sk_by_pk(expected_key) {
-                        Ok(consensus_key) => {
-                            self.validator_signer =
-                                Some(ValidatorSigner::new(author, Arc::new(consensus_key)));
+                    // Keys kept as key handles in storage are signed with through the storage,
+                    // otherwise try to export the consensus key directly from storage.
+                    let signer = if self.persistent_storage.consensus_key_in_storage() {
+                        self.persistent_storage
+                            .consensus_key_name_by_pk(&expected_key)
+                            .map(|key_name| ConsensusSigner::Storage {
+                                author,
+                                public_key: expected_key,
+                                key_name,
+                            })
+                    } else {
+                        self.persistent_storage
+                            .consensus_sk_by_pk(expected_key)
+                            .map(|consensus_key| {
+                                ConsensusSigner::Local(ValidatorSigner::new(
+                                    author,
+                                    Arc::new(consensus_key),
+                                ))
+                            })
+                    };
+                    match signer {
+                        Ok(signer) => {
+                            self.validator_signer = Some(signer);
                             Ok(())
                         },
                         Err(Error::SecureStorageMissingDataError(error)) => {
@@ -11,7 +11,9 @@ use crate::{
     thread::ThreadService,
     SafetyRules, TSafetyRules,
 };
-use aptos_config::config::{InitialSafetyRulesConfig, SafetyRulesConfig, SafetyRulesService};
+use aptos_config::config::{
+    InitialSafetyRulesConfig, RemoteServiceNoiseConfig, SafetyRulesConfig, SafetyRulesService,
+};
 use aptos_crypto::bls12381::PublicKey;
 use aptos_global_constants::CONSENSUS_KEY;
 use aptos_infallible::RwLock;
@@ -58,10 +60,8 @@ pub fn storage(config: &SafetyRulesConfig) -> PersistentSafetyStorage {
                 .expect("No identity blob in initial safety rules config");
             let waypoint = config.initial_safety_rules_config.waypoint();
 
-            let backend = &config.backend;
-            let internal_storage: Storage = backend.into();
             PersistentSafetyStorage::initialize(
-                internal_storage,
+                storage.into_internal_store(),
                 identity_blob
                     .account_address
                     .expect("AccountAddress needed for safety rules"),
@@ -117,7 +117,11 @@ pub struct SafetyRulesManager {
 impl SafetyRulesManager {
     pub fn new(config: &SafetyRulesConfig) -> Self {
         if let SafetyRulesService::Process(conf) = &config.service {
-            return Self::new_process(conf.server_address(), config.network_timeout_ms);
+            return Self::new_process(
+                conf.server_address(),
+                config.network_timeout_ms,
+                conf.noise.clone(),
+            );
         }
 
         let storage = storage(config);
@@ -136,8 +140,12 @@ impl SafetyRulesManager {
         }
     }
 
-    pub fn new_process(server_addr: SocketAddr, timeout_ms: u64) -> Self {
-        let process_service = ProcessService::new(server_addr, timeout_ms);
+    pub fn new_process(
+        server_addr: SocketAddr,
+        timeout_ms: u64,
+        noise: Option<RemoteServiceNoiseConfig>,
+    ) -> Self {
+        let process_service = ProcessService::new(server_addr, timeout_ms, noise);
         Self {
             internal_safety_rules: SafetyRulesWrapper::Process(process_service),
         }
@@ -0,0 +1,138 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::Error;
+use aptos_consensus_types::{common::Author, safety_data::SafetyData};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+/// The current version of the safety data interchange format.
+pub const SAFETY_DATA_INTERCHANGE_VERSION: u64 = 1;
+
+/// A portable snapshot of the SafetyData of a validator, used to carry its voting history over
+/// when it moves to a new host. The last vote itself is intentionally not included: the new host
+/// only needs the rounds to refuse any vote that could conflict with one already cast.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct SafetyDataInterchange {
+    pub version: u64,
+    pub author: Author,
+    pub epoch: u64,
+    pub last_voted_round: u64,
+    pub preferred_round: u64,
+    pub one_chain_round: u64,
+    pub highest_timeout_round: u64,
+}
+
+impl SafetyDataInterchange {
+    pub fn new(author: Author, safety_data: &SafetyData) -> Self {
+        Self {
+            version: SAFETY_DATA_INTERCHANGE_VERSION,
+            author,
+            epoch: safety_data.epoch,
+            last_voted_round: safety_data.last_voted_round,
+            preferred_round: safety_data.preferred_round,
+            one_chain_round: safety_data.one_chain_round,
+            highest_timeout_round: safety_data.highest_timeout_round,
+        }
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self, Error> {
+        let contents = fs::read(path).map_err(|error| {
+            Error::InvalidSafetyDataInterchange(format!("Unable to read {:?}: {}", path, error))
+        })?;
+        let interchange: Self = serde_json::from_slice(&contents)?;
+        if interchange.version != SAFETY_DATA_INTERCHANGE_VERSION {
+            return Err(Error::InvalidSafetyDataInterchange(format!(
+                "Unsupported version {}, expected {}",
+                interchange.version, SAFETY_DATA_INTERCHANGE_VERSION
+            )));
+        }
+        Ok(interchange)
+    }
+
+    pub fn to_file(&self, path: &Path) -> Result<(), Error> {
+        let contents = serde_json::to_vec_pretty(self)?;
+        fs::write(path, contents).map_err(|error| {
+            Error::InvalidSafetyDataInterchange(format!("Unable to write {:?}: {}", path, error))
+        })
+    }
+
+    /// Merges the imported rounds into the local SafetyData, keeping the most conservative value
+    /// of each. Imported data from an older epoch than the local data carries no information
+    /// about the current epoch, so it is rejected rather than silently dropped.
+    pub fn merge_into(&self, local: SafetyData) -> Result<SafetyData, Error> {
+        if self.epoch < local.epoch {
+            return Err(Error::InvalidSafetyDataInterchange(format!(
+                "Imported safety data is from epoch {}, but the local safety data is already at epoch {}",
+                self.epoch, local.epoch
+            )));
+        }
+        if self.epoch > local.epoch {
+            return Ok(SafetyData::new(
+                self.epoch,
+                self.last_voted_round,
+                self.preferred_round,
+                self.one_chain_round,
+                None,
+                self.highest_timeout_round,
+            ));
+        }
+        Ok(SafetyData::new(
+            local.epoch,
+            local.last_voted_round.max(self.last_voted_round),
+            local.preferred_round.max(self.preferred_round),
+            local.one_chain_round.max(self.one_chain_round),
+            // The local last vote is only useful if no later round was voted on elsewhere
+            local
+                .last_vote
+                .filter(|_| local.last_voted_round >= self.last_voted_round),
+            local.highest_timeout_round.max(self.highest_timeout_round),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn interchange(epoch: u64, round: u64) -> SafetyDataInterchange {
+        SafetyDataInterchange::new(
+            Author::ZERO,
+            &SafetyData::new(epoch, round, round - 1, round - 1, None, round),
+        )
+    }
+
+    #[test]
+    fn test_merge_same_epoch_keeps_highest_rounds() {
+        let local = SafetyData::new(3, 10, 8, 9, None, 4);
+        let merged = interchange(3, 7).merge_into(local).unwrap();
+        assert_eq!(merged, SafetyData::new(3, 10, 8, 9, None, 7));
+    }
+
+    #[test]
+    fn test_merge_newer_epoch_replaces() {
+        let local = SafetyData::new(3, 10, 8, 9, None, 4);
+        let merged = interchange(4, 2).merge_into(local).unwrap();
+        assert_eq!(merged, SafetyData::new(4, 2, 1, 1, None, 2));
+    }
+
+    #[test]
+    fn test_merge_older_epoch_is_rejected() {
+        let local = SafetyData::new(3, 10, 8, 9, None, 4);
+        assert!(matches!(
+            interchange(2, 20).merge_into(local),
+            Err(Error::InvalidSafetyDataInterchange(_))
+        ));
+    }
+
+    #[test]
+    fn test_file_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("safety_data.json");
+
+        let exported = interchange(5, 11);
+        exported.to_file(&path).unwrap();
+        assert_eq!(SafetyDataInterchange::from_file(&path).unwrap(), exported);
+    }
+}
@@ -5,6 +5,7 @@
 extern crate claims;
 mod local;
 mod networking;
+mod process;
 mod safety_rules;
 mod serializer;
 mod suite;
@@ -0,0 +1,100 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    process::ProcessService, remote_service::RemoteService, test_utils, tests::suite, Process,
+};
+use aptos_config::{
+    config::{
+        RemoteService as RemoteServiceConfig, RemoteServiceNoiseConfig, SafetyRulesConfig,
+        SafetyRulesService, SafetyRulesTestConfig,
+    },
+    keys::ConfigKey,
+    utils,
+};
+use aptos_crypto::{x25519, Uniform};
+use aptos_secure_net::{NetworkClient, NoiseKeys};
+use aptos_types::validator_signer::ValidatorSigner;
+use std::{
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    thread,
+};
+
+/// Test value for network_timeout, in milliseconds.
+const NETWORK_TIMEOUT: u64 = 5_000;
+
+#[test]
+fn test() {
+    suite::run_test_suite(&safety_rules());
+}
+
+/// A client that doesn't hold the key the service expects is rejected during the handshake.
+#[test]
+fn test_unexpected_client() {
+    let signer = ValidatorSigner::from_int(0);
+    let mut rng = rand::rngs::OsRng;
+    let node_key = x25519::PrivateKey::generate(&mut rng);
+    let service_key = x25519::PrivateKey::generate(&mut rng);
+    let service_public_key = service_key.public_key();
+    let server_addr = start_process(&signer, service_key, node_key.public_key());
+
+    let other_key = x25519::PrivateKey::generate(&mut rng);
+    let mut client = NetworkClient::new("test".to_string(), server_addr, NETWORK_TIMEOUT)
+        .with_noise_keys(NoiseKeys::new(other_key, service_public_key));
+    client.write(&[0, 1, 2, 3]).unwrap_err();
+}
+
+/// Runs safety rules as a remote service authenticated with Noise, and connects to it the same
+/// way consensus does.
+fn safety_rules() -> suite::Callback {
+    Box::new(move || {
+        let signer = ValidatorSigner::from_int(0);
+        let mut rng = rand::rngs::OsRng;
+        let node_key = x25519::PrivateKey::generate(&mut rng);
+        let service_key = x25519::PrivateKey::generate(&mut rng);
+        let service_public_key = service_key.public_key();
+        let server_addr = start_process(&signer, service_key, node_key.public_key());
+
+        let service = ProcessService::new(
+            server_addr,
+            NETWORK_TIMEOUT,
+            Some(RemoteServiceNoiseConfig {
+                private_key: ConfigKey::new(node_key),
+                remote_public_key: service_public_key,
+            }),
+        );
+        (Box::new(service.client()), signer)
+    })
+}
+
+/// Starts the safety rules service on its own thread, which runs for the rest of the test.
+fn start_process(
+    signer: &ValidatorSigner,
+    service_key: x25519::PrivateKey,
+    node_public_key: x25519::PublicKey,
+) -> SocketAddr {
+    let server_port = utils::get_available_port();
+    let server_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), server_port);
+
+    let mut test_config = SafetyRulesTestConfig::new(signer.author());
+    test_config.consensus_key(signer.private_key().clone());
+    test_config.waypoint = Some(test_utils::validator_signers_to_waypoint(&[signer]));
+    let config = SafetyRulesConfig {
+        service: SafetyRulesService::Process(RemoteServiceConfig {
+            server_address: format!("/ip4/127.0.0.1/tcp/{}", server_port)
+                .parse()
+                .unwrap(),
+            noise: Some(RemoteServiceNoiseConfig {
+                private_key: ConfigKey::new(service_key),
+                remote_public_key: node_public_key,
+            }),
+        }),
+        test: Some(test_config),
+        network_timeout_ms: NETWORK_TIMEOUT,
+        ..SafetyRulesConfig::default()
+    };
+
+    let mut process = Process::new(config);
+    thread::spawn(move || process.start());
+    server_addr
+}
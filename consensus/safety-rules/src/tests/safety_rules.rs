@@ -2,7 +2,8 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{test_utils, tests::suite, SafetyRules};
+use crate::{test_utils, tests::suite, Error, SafetyRules, TSafetyRules};
+use aptos_consensus_types::safety_data::SafetyData;
 use aptos_types::validator_signer::ValidatorSigner;
 
 #[test]
@@ -18,3 +19,51 @@ fn safety_rules() -> suite::Callback {
         (safety_rules, signer)
     })
 }
+
+#[test]
+fn test_imported_safety_data_epoch() {
+    let signer = ValidatorSigner::from_int(0);
+    let (proof, _genesis_qc) = test_utils::make_genesis(&signer);
+
+    // Safety data exported in the on-chain epoch is accepted
+    let mut storage = test_utils::test_storage(&signer);
+    let interchange = storage.export_safety_data().unwrap();
+    storage.import_safety_data(&interchange).unwrap();
+    let mut safety_rules = SafetyRules::new(storage);
+    safety_rules.initialize(&proof).unwrap();
+    assert!(!safety_rules
+        .persistent_storage
+        .safety_data_imported()
+        .unwrap());
+
+    // Safety data exported in a later epoch than the proof keeps its mark, so that it is still
+    // checked against the proof of the on-chain epoch
+    let mut storage = test_utils::test_storage(&signer);
+    storage
+        .set_safety_data(SafetyData::new(2, 5, 4, 4, None, 0))
+        .unwrap();
+    let interchange = storage.export_safety_data().unwrap();
+    storage.import_safety_data(&interchange).unwrap();
+    let mut safety_rules = SafetyRules::new(storage);
+    assert!(matches!(
+        safety_rules.initialize(&proof).unwrap_err(),
+        Error::WaypointOutOfDate(_, _, 2, 1)
+    ));
+    assert!(safety_rules
+        .persistent_storage
+        .safety_data_imported()
+        .unwrap());
+
+    // Safety data exported before the on-chain epoch is rejected
+    let mut storage = test_utils::test_storage(&signer);
+    storage
+        .set_safety_data(SafetyData::new(0, 5, 4, 4, None, 0))
+        .unwrap();
+    let interchange = storage.export_safety_data().unwrap();
+    storage.import_safety_data(&interchange).unwrap();
+    let mut safety_rules = SafetyRules::new(storage);
+    assert_eq!(
+        safety_rules.initialize(&proof).unwrap_err(),
+        Error::StaleImportedSafetyData(0, 1)
+    );
+}
@@ -28,13 +28,14 @@ impl MemoryRatelimitCheckerConfig {
 
 /// Simple in memory storage that rejects if we've ever seen a request from an
 /// IP that has succeeded. This does not support JWT-based ratelimiting.
+/// Requests for each asset are limited separately.
 pub struct MemoryRatelimitChecker {
     pub max_requests_per_day: u32,
 
-    /// Map of IP to how many requests they've submitted today (where the
-    /// response wasn't a 500). To avoid OOMing the server, we set a limit
-    /// on how many entries we have in the table.
-    pub ip_to_requests_today: Mutex<LruCache<IpAddr, u32>>,
+    /// Map of IP and requested asset (None for APT) to how many requests they've
+    /// submitted today (where the response wasn't a 500). To avoid OOMing the
+    /// server, we set a limit on how many entries we have in the table.
+    pub ip_to_requests_today: Mutex<LruCache<(IpAddr, Option<String>), u32>>,
 
     /// Used for tracking daily ratelimit. See the comment in RedisRatelimitChecker
     /// for more information on how we track daily limits.
@@ -74,12 +75,18 @@ impl CheckerTrait for MemoryRatelimitChecker {
 
         let mut ip_to_requests_today = self.ip_to_requests_today.lock().await;
 
-        let requests_today = ip_to_requests_today.get_or_insert_mut(data.source_ip, || 1);
+        let requests_today =
+            ip_to_requests_today.get_or_insert_mut((data.source_ip, data.asset.clone()), || 1);
         if *requests_today >= self.max_requests_per_day {
             return Ok(vec![RejectionReason::new(
                 format!(
-                    "IP {} has exceeded the daily limit of {} requests",
-                    data.source_ip, self.max_requests_per_day
+                    "IP {} has exceeded the daily limit of {} requests{}",
+                    data.source_ip,
+                    self.max_requests_per_day,
+                    data.asset
+                        .as_ref()
+                        .map(|asset| format!(" for {}", asset))
+                        .unwrap_or_default()
                 ),
                 RejectionReasonCode::UsageLimitExhausted,
             )]);
@@ -92,11 +99,10 @@ impl CheckerTrait for MemoryRatelimitChecker {
 
     async fn complete(&self, data: CompleteData) -> Result<(), AptosTapError> {
         if data.response_is_500 {
-            *self
-                .ip_to_requests_today
-                .lock()
-                .await
-                .get_or_insert_mut(data.checker_data.source_ip, || 1) -= 1;
+            *self.ip_to_requests_today.lock().await.get_or_insert_mut(
+                (data.checker_data.source_ip, data.checker_data.asset.clone()),
+                || 1,
+            ) -= 1;
         }
         Ok(())
     }
@@ -0,0 +1,298 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Hashcash style proof of work. Unlike the captcha Checkers this doesn't need
+//! a browser or a third party service, so CLI and CI users can solve it too.
+//! Like the TapCaptchaChecker, challenges are kept in memory, so clients
+//! should use cookies to benefit from cookie based sticky routing.
+
+use super::{CheckerData, CheckerTrait};
+use crate::endpoints::{AptosTapError, AptosTapErrorCode, RejectionReason, RejectionReasonCode};
+use anyhow::{bail, Result};
+use aptos_sdk::{
+    rest_client::faucet::{proof_of_work_bits, POW_CHALLENGE, POW_SOLUTION},
+    types::account_address::AccountAddress,
+};
+use async_trait::async_trait;
+use futures::lock::Mutex;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, net::IpAddr, sync::Arc};
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ProofOfWorkCheckerConfig {
+    /// Number of leading zero bits the hash of a solution must have. Every
+    /// extra bit doubles the work it takes to solve a challenge.
+    pub base_difficulty: u8,
+
+    /// If set, every doubling of the amount of APT requested beyond this many
+    /// OCTA adds a bit of difficulty. Requests that don't give an amount are
+    /// treated as asking for the maximum amount the Funder gives.
+    #[serde(default)]
+    pub amount_per_extra_bit: Option<u64>,
+
+    /// Every challenge the requesting IP already has outstanding adds this many
+    /// bits of difficulty, so farming challenges gets expensive quickly.
+    #[serde(default = "ProofOfWorkCheckerConfig::default_bits_per_outstanding_challenge")]
+    pub bits_per_outstanding_challenge: u8,
+
+    /// Difficulty is never higher than this, no matter the amount or requester.
+    #[serde(default = "ProofOfWorkCheckerConfig::default_max_difficulty")]
+    pub max_difficulty: u8,
+
+    /// How long a challenge can be used for after it is issued.
+    #[serde(default = "ProofOfWorkCheckerConfig::default_challenge_ttl_secs")]
+    pub challenge_ttl_secs: u64,
+
+    /// Maximum number of challenges kept at once. Once this is reached, new
+    /// challenges are refused until old ones are used or expire.
+    #[serde(default = "ProofOfWorkCheckerConfig::default_max_outstanding_challenges")]
+    pub max_outstanding_challenges: usize,
+
+    /// Maximum number of challenges a single IP can have outstanding. Once
+    /// this is reached, the IP is refused new challenges until its old ones
+    /// are used or expire, so one IP can't fill up the challenges.
+    #[serde(default = "ProofOfWorkCheckerConfig::default_max_outstanding_challenges_per_ip")]
+    pub max_outstanding_challenges_per_ip: usize,
+}
+
+impl ProofOfWorkCheckerConfig {
+    fn default_bits_per_outstanding_challenge() -> u8 {
+        1
+    }
+
+    fn default_max_difficulty() -> u8 {
+        24
+    }
+
+    fn default_challenge_ttl_secs() -> u64 {
+        300
+    }
+
+    fn default_max_outstanding_challenges() -> usize {
+        100_000
+    }
+
+    fn default_max_outstanding_challenges_per_ip() -> usize {
+        10
+    }
+}
+
+pub struct ProofOfWorkChecker {
+    /// Reference to the one proof of work manager. This must be passed in
+    /// because we need to be able to use it from the challenge endpoint too.
+    proof_of_work_manager: Arc<Mutex<ProofOfWorkManager>>,
+}
+
+impl ProofOfWorkChecker {
+    pub fn new(proof_of_work_manager: Arc<Mutex<ProofOfWorkManager>>) -> Result<Self> {
+        Ok(Self {
+            proof_of_work_manager,
+        })
+    }
+}
+
+#[async_trait]
+impl CheckerTrait for ProofOfWorkChecker {
+    async fn check(
+        &self,
+        data: CheckerData,
+        dry_run: bool,
+    ) -> Result<Vec<RejectionReason>, AptosTapError> {
+        let challenge = match data.headers.get(POW_CHALLENGE) {
+            Some(header_value) => match header_value.to_str() {
+                Ok(value) => value.to_string(),
+                Err(e) => {
+                    return Ok(vec![RejectionReason::new(
+                        format!("Proof of work challenge not valid: {:#}", e),
+                        RejectionReasonCode::ProofOfWorkInvalid,
+                    )])
+                },
+            },
+            None => {
+                return Ok(vec![RejectionReason::new(
+                    format!("Proof of work header {} not found", POW_CHALLENGE),
+                    RejectionReasonCode::ProofOfWorkInvalid,
+                )])
+            },
+        };
+
+        let solution = match data.headers.get(POW_SOLUTION) {
+            Some(header_value) => match header_value
+                .to_str()
+                .ok()
+                .and_then(|value| value.parse::<u64>().ok())
+            {
+                Some(value) => value,
+                None => {
+                    return Ok(vec![RejectionReason::new(
+                        "Proof of work solution not a number".to_string(),
+                        RejectionReasonCode::ProofOfWorkInvalid,
+                    )])
+                },
+            },
+            None => {
+                return Ok(vec![RejectionReason::new(
+                    format!("Proof of work header {} not found", POW_SOLUTION),
+                    RejectionReasonCode::ProofOfWorkInvalid,
+                )])
+            },
+        };
+
+        if let Err(e) = self
+            .proof_of_work_manager
+            .lock()
+            .await
+            .check_solution(&challenge, solution, &data, dry_run)
+        {
+            return Ok(vec![RejectionReason::new(
+                format!("{:#}", e),
+                RejectionReasonCode::ProofOfWorkInvalid,
+            )]);
+        }
+
+        Ok(vec![])
+    }
+
+    fn cost(&self) -> u8 {
+        3
+    }
+}
+
+/// A challenge that has been issued, along with the request it was issued for.
+/// A solution is only accepted for the same request from the same IP.
+#[derive(Debug)]
+struct IssuedChallenge {
+    receiver: AccountAddress,
+    asset: Option<String>,
+    amount: Option<u64>,
+    source_ip: IpAddr,
+    difficulty: u8,
+    expiration_timestamp_secs: u64,
+}
+
+/// ProofOfWorkManager is responsible for creating proof of work challenges
+/// and later checking solutions to them.
+#[derive(Debug)]
+pub struct ProofOfWorkManager {
+    config: ProofOfWorkCheckerConfig,
+
+    /// Map from the hex encoded challenge to what it was issued for.
+    challenges: HashMap<String, IssuedChallenge>,
+}
+
+impl ProofOfWorkManager {
+    pub fn new(config: ProofOfWorkCheckerConfig) -> Self {
+        Self {
+            config,
+            challenges: HashMap::new(),
+        }
+    }
+
+    /// Create a new challenge for a request. `apt_amount` is the amount of APT
+    /// the request would be funded with, or None if it is for another asset.
+    /// Returns the hex encoded challenge, its difficulty and its expiration.
+    pub fn create_challenge(
+        &mut self,
+        data: &CheckerData,
+        apt_amount: Option<u64>,
+    ) -> Result<(String, u8, u64), AptosTapError> {
+        let now = data.time_request_received_secs;
+        self.challenges
+            .retain(|_, issued| issued.expiration_timestamp_secs > now);
+        if self.challenges.len() >= self.config.max_outstanding_challenges {
+            return Err(AptosTapError::new(
+                "Too many outstanding proof of work challenges, please try again later".to_string(),
+                AptosTapErrorCode::ServerOverloaded,
+            ));
+        }
+        let outstanding = self.outstanding_challenges(data.source_ip);
+        if outstanding >= self.config.max_outstanding_challenges_per_ip {
+            return Err(AptosTapError::new(
+                "Request rejected by 1 checkers".to_string(),
+                AptosTapErrorCode::Rejected,
+            )
+            .rejection_reasons(vec![RejectionReason::new(
+                format!(
+                    "IP {} has {} outstanding proof of work challenges, solve or wait for them to expire first",
+                    data.source_ip, outstanding
+                ),
+                RejectionReasonCode::UsageLimitExhausted,
+            )]));
+        }
+
+        let difficulty = self.difficulty(outstanding, apt_amount);
+        let challenge = hex::encode(rand::thread_rng().gen::<[u8; 32]>());
+        let expiration_timestamp_secs = now + self.config.challenge_ttl_secs;
+        self.challenges.insert(challenge.clone(), IssuedChallenge {
+            receiver: data.receiver,
+            asset: data.asset.clone(),
+            amount: data.amount,
+            source_ip: data.source_ip,
+            difficulty,
+            expiration_timestamp_secs,
+        });
+
+        Ok((challenge, difficulty, expiration_timestamp_secs))
+    }
+
+    fn outstanding_challenges(&self, source_ip: IpAddr) -> usize {
+        self.challenges
+            .values()
+            .filter(|issued| issued.source_ip == source_ip)
+            .count()
+    }
+
+    /// The difficulty of a new challenge, given how many challenges the
+    /// requesting IP already has outstanding.
+    fn difficulty(&self, outstanding: usize, apt_amount: Option<u64>) -> u8 {
+        let amount_bits = match (self.config.amount_per_extra_bit, apt_amount) {
+            (Some(amount_per_extra_bit), Some(amount)) if amount > amount_per_extra_bit => {
+                // The number of times amount_per_extra_bit doubles before
+                // reaching the amount, rounding up.
+                let ratio = amount.div_ceil(amount_per_extra_bit.max(1));
+                (u64::BITS - (ratio - 1).leading_zeros()) as u64
+            },
+            _ => 0,
+        };
+        let requester_bits = outstanding as u64 * self.config.bits_per_outstanding_challenge as u64;
+        (self.config.base_difficulty as u64 + amount_bits + requester_bits)
+            .min(self.config.max_difficulty as u64) as u8
+    }
+
+    /// Check a solution to a challenge. Unless this is a dry run, the challenge
+    /// is used up if the solution is correct.
+    pub fn check_solution(
+        &mut self,
+        challenge: &str,
+        solution: u64,
+        data: &CheckerData,
+        dry_run: bool,
+    ) -> Result<()> {
+        let issued = match self.challenges.get(challenge) {
+            Some(issued) => issued,
+            None => bail!("Proof of work challenge unknown: {}", challenge),
+        };
+        if issued.expiration_timestamp_secs <= data.time_request_received_secs {
+            self.challenges.remove(challenge);
+            bail!("Proof of work challenge expired");
+        }
+        if issued.receiver != data.receiver
+            || issued.asset != data.asset
+            || issued.amount != data.amount
+        {
+            bail!("Proof of work challenge was issued for a different request");
+        }
+        if issued.source_ip != data.source_ip {
+            bail!("Proof of work challenge was issued to a different IP");
+        }
+        let challenge_bytes = hex::decode(challenge)?;
+        if proof_of_work_bits(&challenge_bytes, solution) < issued.difficulty as usize {
+            bail!("Proof of work solution {} incorrect", solution);
+        }
+        if !dry_run {
+            self.challenges.remove(challenge);
+        }
+        Ok(())
+    }
+}
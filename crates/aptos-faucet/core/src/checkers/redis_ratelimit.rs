@@ -182,20 +182,32 @@ impl RedisRatelimitChecker {
         })
     }
 
-    // Returns the key and the seconds until the next day.
+    // Returns the key and the seconds until the next day. Requests for assets
+    // other than APT are counted under their own key, so each asset has its own
+    // limit. The key for APT is unchanged from before assets were supported.
     fn get_key_and_secs_until_next_day(
         &self,
         ratelimit_key_prefix: &str,
         ratelimit_key_value: &str,
+        asset: Option<&str>,
     ) -> (String, u64) {
         let now_secs = get_current_time_secs();
         let seconds_until_next_day = seconds_until_next_day(now_secs);
-        let key = format!(
-            "{}:{}:{}",
-            ratelimit_key_prefix,
-            ratelimit_key_value,
-            days_since_tap_epoch(now_secs)
-        );
+        let key = match asset {
+            Some(asset) => format!(
+                "{}:{}:{}:{}",
+                ratelimit_key_prefix,
+                ratelimit_key_value,
+                asset,
+                days_since_tap_epoch(now_secs)
+            ),
+            None => format!(
+                "{}:{}:{}",
+                ratelimit_key_prefix,
+                ratelimit_key_value,
+                days_since_tap_epoch(now_secs)
+            ),
+        };
         (key, seconds_until_next_day)
     }
 
@@ -240,7 +252,7 @@ impl CheckerTrait for RedisRatelimitChecker {
             .ratelimit_key_value(&data)
             .await?;
         let (key, seconds_until_next_day) =
-            self.get_key_and_secs_until_next_day(key_prefix, &key_value);
+            self.get_key_and_secs_until_next_day(key_prefix, &key_value, data.asset.as_deref());
 
         // Get the value for the key, indicating how many non-500 requests we have
         // serviced for it today.
@@ -323,7 +335,11 @@ impl CheckerTrait for RedisRatelimitChecker {
             .ratelimit_key_provider
             .ratelimit_key_value(&data.checker_data)
             .await?;
-        let (key, _) = self.get_key_and_secs_until_next_day(key_prefix, &key_value);
+        let (key, _) = self.get_key_and_secs_until_next_day(
+            key_prefix,
+            &key_value,
+            data.checker_data.asset.as_deref(),
+        );
 
         conn.decr(&key, 1).await.map_err(|e| {
             AptosTapError::new_with_error_code(
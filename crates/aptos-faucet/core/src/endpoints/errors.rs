@@ -260,4 +260,7 @@ pub enum RejectionReasonCode {
 
     /// Referer was in the blocklist.
     RefererBlocklisted = 108,
+
+    /// The proof of work solution was missing or incorrect.
+    ProofOfWorkInvalid = 109,
 }
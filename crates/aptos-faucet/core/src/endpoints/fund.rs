@@ -44,6 +44,10 @@ pub struct FundRequest {
 
     /// Either this or `auth_key` / `address` must be provided.
     pub pub_key: Option<String>,
+
+    /// Name of the asset to fund the account with, as configured in the faucet.
+    /// If not set, the account is funded with APT.
+    pub asset: Option<String>,
 }
 
 #[derive(Clone, Debug, Object)]
@@ -83,7 +87,13 @@ impl FundRequest {
 
 impl std::fmt::Display for FundRequest {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "<Fund {:?} to {:?}>", self.amount, self.receiver())
+        write!(
+            f,
+            "<Fund {:?} {} to {:?}>",
+            self.amount,
+            self.asset.as_deref().unwrap_or("APT"),
+            self.receiver()
+        )
     }
 }
 
@@ -158,7 +168,13 @@ impl FundApi {
         // to fund the account.
         self.components
             .funder
-            .fund(fund_request.amount, checker_data.receiver, true, bypass)
+            .fund(
+                fund_request.amount,
+                checker_data.receiver,
+                fund_request.asset.clone(),
+                true,
+                bypass,
+            )
             .await?;
 
         Ok(())
@@ -232,8 +248,21 @@ impl FundApiComponents {
             },
         };
 
+        // Reject assets the Funder wasn't configured with before anything
+        // keys on the asset, e.g. the ratelimits.
+        if let Some(asset) = &fund_request.asset {
+            if !self.funder.supports_asset(asset) {
+                return Err(AptosTapError::new(
+                    format!("Asset {} is not supported by this faucet", asset),
+                    AptosTapErrorCode::InvalidRequest,
+                ));
+            }
+        }
+
         let checker_data = CheckerData {
             receiver,
+            asset: fund_request.asset.clone(),
+            amount: fund_request.amount,
             source_ip,
             headers: Arc::new(header_map.clone()),
             time_request_received_secs: get_current_time_secs(),
@@ -295,7 +324,13 @@ impl FundApiComponents {
         // Fund the account.
         let fund_result = self
             .funder
-            .fund(fund_request.amount, checker_data.receiver, false, bypass)
+            .fund(
+                fund_request.amount,
+                checker_data.receiver,
+                fund_request.asset.clone(),
+                false,
+                bypass,
+            )
             .await;
 
         // This might be empty if there is an error and we never got to the
@@ -311,6 +346,7 @@ impl FundApiComponents {
             jwt_sub = jwt_sub(checker_data.headers.clone()).ok(),
             address = checker_data.receiver,
             requested_amount = fund_request.amount,
+            requested_asset = fund_request.asset,
             txn_hashes = txn_hashes,
             success = fund_result.is_ok(),
         );
@@ -349,6 +385,7 @@ pub struct MintRequest {
     auth_key: Option<String>,
     address: Option<String>,
     pub_key: Option<String>,
+    asset: Option<String>,
     return_txns: Option<bool>,
 }
 
@@ -373,6 +410,7 @@ pub async fn mint(
         auth_key,
         address,
         pub_key,
+        asset,
         return_txns,
     }): poem::web::Query<MintRequest>,
     // This automagically uses FromRequest to get this data from the request.
@@ -390,6 +428,7 @@ pub async fn mint(
         auth_key,
         address,
         pub_key,
+        asset,
     };
     let txns = fund_api_components
         .0
@@ -7,6 +7,7 @@ mod captcha;
 mod error_converter;
 mod errors;
 mod fund;
+mod proof_of_work;
 
 pub use self::captcha::{CaptchaApi, CAPTCHA_KEY, CAPTCHA_VALUE};
 pub use api::build_openapi_service;
@@ -17,6 +18,7 @@ pub use errors::{
 };
 pub use fund::{mint, FundApi, FundApiComponents, FundRequest, FundResponse};
 use poem_openapi::Tags;
+pub use proof_of_work::{ProofOfWorkApi, ProofOfWorkChallenge};
 
 /// API categories for the OpenAPI spec
 #[derive(Tags)]
@@ -29,4 +31,7 @@ pub enum ApiTags {
 
     /// Captcha API
     Captcha,
+
+    /// Proof of work API
+    ProofOfWork,
 }
@@ -0,0 +1,123 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! This API is for the ProofOfWorkChecker.
+
+use super::{
+    errors::AptosTapErrorResponse, ApiTags, AptosTapError, AptosTapErrorCode, FundRequest,
+};
+use crate::{
+    checkers::{CheckerData, ProofOfWorkManager},
+    funder::{Funder, FunderTrait},
+    helpers::get_current_time_secs,
+};
+use futures::lock::Mutex;
+use poem::{http::HeaderMap, web::RealIp};
+use poem_openapi::{payload::Json, Object, OpenApi};
+use std::sync::Arc;
+
+pub struct ProofOfWorkApi {
+    pub funder: Arc<Funder>,
+    /// This is only set if the ProofOfWorkChecker is enabled.
+    pub proof_of_work_manager: Option<Arc<Mutex<ProofOfWorkManager>>>,
+}
+
+#[derive(Clone, Debug, Object)]
+pub struct ProofOfWorkChallenge {
+    /// Hex encoded random bytes. The solution is a number such that the
+    /// SHA3-256 hash of these bytes followed by the number as 8 little endian
+    /// bytes starts with `difficulty` zero bits.
+    pub challenge: String,
+    /// Number of leading zero bits the hash of the solution must have.
+    pub difficulty: u8,
+    /// The challenge is not accepted after this time.
+    pub expiration_timestamp_secs: u64,
+}
+
+#[OpenApi]
+impl ProofOfWorkApi {
+    /// Request a proof of work challenge
+    ///
+    /// With this endpoint you can get a proof of work challenge for a funding
+    /// request. The body must be the same as the body of the request to `/fund`
+    /// the challenge is for. Include the challenge in the `POW_CHALLENGE`
+    /// header and the solution in the `POW_SOLUTION` header of that request.
+    /// The difficulty depends on the amount requested and on how many
+    /// challenges the requester already has outstanding. This endpoint is only
+    /// relevant if the ProofOfWorkChecker is enabled.
+    #[oai(
+        path = "/request_pow_challenge",
+        method = "post",
+        operation_id = "request_pow_challenge",
+        tag = "ApiTags::ProofOfWork"
+    )]
+    async fn request_pow_challenge(
+        &self,
+        fund_request: Json<FundRequest>,
+        // This automagically uses FromRequest to get this data from the request.
+        // It takes into things like X-Forwarded-IP and X-Real-IP.
+        source_ip: RealIp,
+        // Same thing, this uses FromRequest.
+        header_map: &HeaderMap,
+    ) -> poem::Result<Json<ProofOfWorkChallenge>, AptosTapErrorResponse> {
+        let proof_of_work_manager = match &self.proof_of_work_manager {
+            Some(proof_of_work_manager) => proof_of_work_manager,
+            None => {
+                return Err(AptosTapError::new(
+                    "The ProofOfWorkChecker is not enabled".to_string(),
+                    AptosTapErrorCode::EndpointNotEnabled,
+                )
+                .into())
+            },
+        };
+
+        let source_ip = match source_ip.0 {
+            Some(ip) => ip,
+            None => {
+                return Err(AptosTapError::new(
+                    "No source IP found in the request".to_string(),
+                    AptosTapErrorCode::SourceIpMissing,
+                )
+                .into())
+            },
+        };
+
+        let receiver = match fund_request.receiver() {
+            Some(receiver) => receiver,
+            None => {
+                return Err(AptosTapError::new(
+                    "Account address, auth key, or pub key must be provided and valid".to_string(),
+                    AptosTapErrorCode::InvalidRequest,
+                )
+                .into())
+            },
+        };
+
+        let checker_data = CheckerData {
+            receiver,
+            asset: fund_request.asset.clone(),
+            amount: fund_request.amount,
+            source_ip,
+            headers: Arc::new(header_map.clone()),
+            time_request_received_secs: get_current_time_secs(),
+        };
+
+        // Only APT amounts make a challenge harder, other assets are limited by
+        // the amount configured for them.
+        let apt_amount = match fund_request.asset {
+            Some(_) => None,
+            None => Some(self.funder.get_amount(fund_request.amount, false)),
+        };
+
+        let (challenge, difficulty, expiration_timestamp_secs) = proof_of_work_manager
+            .lock()
+            .await
+            .create_challenge(&checker_data, apt_amount)?;
+
+        Ok(Json(ProofOfWorkChallenge {
+            challenge,
+            difficulty,
+            expiration_timestamp_secs,
+        }))
+    }
+}
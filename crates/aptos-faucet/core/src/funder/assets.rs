@@ -0,0 +1,206 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::endpoints::{AptosTapError, AptosTapErrorCode};
+use anyhow::{Context, Result};
+use aptos_sdk::{
+    bcs,
+    move_types::{
+        ident_str,
+        identifier::Identifier,
+        language_storage::{ModuleId, TypeTag},
+    },
+    transaction_builder::aptos_stdlib,
+    types::{
+        account_address::AccountAddress,
+        transaction::{EntryFunction, TransactionPayload},
+    },
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, str::FromStr};
+
+/// An asset other than APT that a Funder can fund accounts with. These are
+/// declared by name in the Funder config, and requests ask for them by name.
+/// Requests for these assets are ratelimited separately from requests for APT.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(tag = "type")]
+pub enum AssetConfig {
+    /// A coin held by the funder account, transferred with
+    /// `0x1::aptos_account::transfer_coins`.
+    Coin {
+        /// The coin type, e.g. `0x123::test_coin::TestCoin`.
+        coin_type: String,
+
+        /// Maximum amount of the coin to give an account.
+        amount_to_fund: u64,
+    },
+
+    /// A fungible asset held in the funder account's primary store, transferred
+    /// with `0x1::aptos_account::transfer_fungible_assets`.
+    FungibleAsset {
+        /// Address of the fungible asset's metadata object.
+        metadata_address: AccountAddress,
+
+        /// Maximum amount of the fungible asset to give an account.
+        amount_to_fund: u64,
+    },
+
+    /// An asset minted by calling an entry function as the funder account, with
+    /// the receiver address and the amount as arguments. For example, this can
+    /// mint test coins or fungible assets that the funder account has the mint
+    /// capability for, or tokens in a test NFT collection, where the amount is
+    /// the number of tokens.
+    Mint {
+        /// The entry function, e.g. `0x123::test_nft::mint_to`.
+        function: String,
+
+        /// Type arguments to call the entry function with, if any.
+        #[serde(default)]
+        type_args: Vec<String>,
+
+        /// Maximum amount to mint for an account.
+        amount_to_fund: u64,
+    },
+}
+
+impl AssetConfig {
+    pub fn build(&self) -> Result<Asset> {
+        let (kind, amount_to_fund) = match self {
+            AssetConfig::Coin {
+                coin_type,
+                amount_to_fund,
+            } => (
+                AssetKind::Coin(
+                    TypeTag::from_str(coin_type)
+                        .with_context(|| format!("Invalid coin type {}", coin_type))?,
+                ),
+                *amount_to_fund,
+            ),
+            AssetConfig::FungibleAsset {
+                metadata_address,
+                amount_to_fund,
+            } => (AssetKind::FungibleAsset(*metadata_address), *amount_to_fund),
+            AssetConfig::Mint {
+                function,
+                type_args,
+                amount_to_fund,
+            } => {
+                let (module, function_name) = function
+                    .rsplit_once("::")
+                    .with_context(|| format!("Invalid entry function {}", function))?;
+                let type_args = type_args
+                    .iter()
+                    .map(|type_arg| {
+                        TypeTag::from_str(type_arg)
+                            .with_context(|| format!("Invalid type argument {}", type_arg))
+                    })
+                    .collect::<Result<_>>()?;
+                (
+                    AssetKind::Mint {
+                        module: ModuleId::from_str(module)
+                            .with_context(|| format!("Invalid module in {}", function))?,
+                        function: Identifier::new(function_name)
+                            .with_context(|| format!("Invalid function name in {}", function))?,
+                        type_args,
+                    },
+                    *amount_to_fund,
+                )
+            },
+        };
+        Ok(Asset {
+            kind,
+            amount_to_fund,
+        })
+    }
+}
+
+/// Builds the assets declared in a Funder config, keyed by name.
+pub fn build_assets(configs: &HashMap<String, AssetConfig>) -> Result<HashMap<String, Asset>> {
+    configs
+        .iter()
+        .map(|(name, config)| {
+            let asset = config
+                .build()
+                .with_context(|| format!("Failed to build asset {}", name))?;
+            Ok((name.clone(), asset))
+        })
+        .collect()
+}
+
+/// Looks up the asset a request asked for, rejecting requests for assets that
+/// the Funder wasn't configured with.
+pub fn get_asset<'a>(
+    assets: &'a HashMap<String, Asset>,
+    name: &str,
+) -> Result<&'a Asset, AptosTapError> {
+    assets.get(name).ok_or_else(|| {
+        AptosTapError::new(
+            format!("Asset {} is not supported by this faucet", name),
+            AptosTapErrorCode::InvalidRequest,
+        )
+    })
+}
+
+#[derive(Clone, Debug)]
+enum AssetKind {
+    Coin(TypeTag),
+    FungibleAsset(AccountAddress),
+    Mint {
+        module: ModuleId,
+        function: Identifier,
+        type_args: Vec<TypeTag>,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub struct Asset {
+    kind: AssetKind,
+    amount_to_fund: u64,
+}
+
+impl Asset {
+    /// Given a requested amount, determine the amount that can be funded.
+    pub fn get_amount(&self, amount: Option<u64>) -> u64 {
+        match amount {
+            Some(amount) => std::cmp::min(amount, self.amount_to_fund),
+            None => self.amount_to_fund,
+        }
+    }
+
+    /// Builds the payload of the transaction, sent by the funder account, that
+    /// funds the receiver with the given amount of this asset.
+    pub fn payload(&self, receiver_address: AccountAddress, amount: u64) -> TransactionPayload {
+        match &self.kind {
+            AssetKind::Coin(coin_type) => aptos_stdlib::aptos_account_transfer_coins(
+                coin_type.clone(),
+                receiver_address,
+                amount,
+            ),
+            AssetKind::FungibleAsset(metadata_address) => {
+                TransactionPayload::EntryFunction(EntryFunction::new(
+                    ModuleId::new(AccountAddress::ONE, ident_str!("aptos_account").to_owned()),
+                    ident_str!("transfer_fungible_assets").to_owned(),
+                    vec![],
+                    vec![
+                        bcs::to_bytes(metadata_address).unwrap(),
+                        bcs::to_bytes(&receiver_address).unwrap(),
+                        bcs::to_bytes(&amount).unwrap(),
+                    ],
+                ))
+            },
+            AssetKind::Mint {
+                module,
+                function,
+                type_args,
+            } => TransactionPayload::EntryFunction(EntryFunction::new(
+                module.clone(),
+                function.clone(),
+                type_args.clone(),
+                vec![
+                    bcs::to_bytes(&receiver_address).unwrap(),
+                    bcs::to_bytes(&amount).unwrap(),
+                ],
+            )),
+        }
+    }
+}
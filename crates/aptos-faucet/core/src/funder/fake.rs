@@ -6,11 +6,27 @@ use crate::endpoints::AptosTapError;
 use aptos_sdk::types::{account_address::AccountAddress, transaction::SignedTransaction};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
-pub struct FakeFunderConfig {}
+pub struct FakeFunderConfig {
+    /// Names of the assets this funder pretends to fund accounts with.
+    #[serde(default)]
+    pub assets: Vec<String>,
+}
 
-pub struct FakeFunder;
+impl FakeFunderConfig {
+    pub fn build_funder(self) -> FakeFunder {
+        FakeFunder {
+            assets: self.assets.into_iter().collect(),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct FakeFunder {
+    assets: HashSet<String>,
+}
 
 #[async_trait]
 impl FunderTrait for FakeFunder {
@@ -18,6 +34,7 @@ impl FunderTrait for FakeFunder {
         &self,
         _amount: Option<u64>,
         _receiver_address: AccountAddress,
+        _asset: Option<String>,
         _check_only: bool,
         _did_bypass_checkers: bool,
     ) -> Result<Vec<SignedTransaction>, AptosTapError> {
@@ -27,4 +44,8 @@ impl FunderTrait for FakeFunder {
     fn get_amount(&self, amount: Option<u64>, _did_bypass_checkers: bool) -> u64 {
         amount.unwrap_or(100)
     }
+
+    fn supports_asset(&self, asset: &str) -> bool {
+        self.assets.contains(asset)
+    }
 }
@@ -2,7 +2,10 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use super::{FunderHealthMessage, FunderTrait};
+use super::{
+    assets::{build_assets, get_asset, Asset, AssetConfig},
+    FunderHealthMessage, FunderTrait,
+};
 use crate::endpoints::{AptosTapError, AptosTapErrorCode};
 use anyhow::{Context, Result};
 use aptos_logger::info;
@@ -15,6 +18,7 @@ use aptos_sdk::{
         chain_id::ChainId,
         transaction::{
             authenticator::AuthenticationKey, Script, SignedTransaction, TransactionArgument,
+            TransactionPayload,
         },
         LocalAccount,
     },
@@ -22,6 +26,7 @@ use aptos_sdk::{
 use async_trait::async_trait;
 use reqwest::Url;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use tokio::sync::RwLock;
 
 static MINTER_SCRIPT: &[u8] = include_bytes!(
@@ -50,6 +55,12 @@ pub struct MintFunderConfig {
     /// Just use the account given in funder args, don't make a new one and
     /// delegate the mint capability to it.
     pub do_not_delegate: bool,
+
+    /// Assets other than APT that this funder can fund accounts with, by name.
+    /// Note that unless `do_not_delegate` is set, these are sent from the new
+    /// delegated account, so they must be minted rather than transferred.
+    #[serde(default)]
+    pub assets: HashMap<String, AssetConfig>,
 }
 
 impl MintFunderConfig {
@@ -70,6 +81,7 @@ impl MintFunderConfig {
             self.transaction_submission_config,
             faucet_account,
         );
+        minter.assets = build_assets(&self.assets)?;
 
         if !self.do_not_delegate {
             minter
@@ -97,6 +109,9 @@ pub struct MintFunder {
     /// When recovering from being overloaded, this struct ensures we handle
     /// requests in the order they came in.
     outstanding_requests: RwLock<Vec<(AccountAddress, u64)>>,
+
+    /// Assets other than APT that this funder can fund accounts with, by name.
+    assets: HashMap<String, Asset>,
 }
 
 impl MintFunder {
@@ -118,6 +133,7 @@ impl MintFunder {
             transaction_factory,
             gas_unit_price_manager,
             outstanding_requests: RwLock::new(vec![]),
+            assets: HashMap::new(),
         }
     }
 
@@ -157,6 +173,7 @@ impl MintFunder {
                 .authentication_key()
                 .clone()
                 .account_address(),
+            None,
             false,
             true,
         )
@@ -206,11 +223,13 @@ impl MintFunder {
         Client::new(self.node_url.clone())
     }
 
+    /// Funds the receiver with APT by minting it, or with the given asset.
     pub async fn process(
         &self,
         client: &Client,
         amount: u64,
         receiver_address: AccountAddress,
+        asset: Option<&Asset>,
         check_only: bool,
         wait_for_transactions: bool,
     ) -> Result<Vec<SignedTransaction>, AptosTapError> {
@@ -224,7 +243,7 @@ impl MintFunder {
         )
         .await?;
 
-        if receiver_seq.is_some() && amount == 0 {
+        if asset.is_none() && receiver_seq.is_some() && amount == 0 {
             return Err(AptosTapError::new(
                 format!(
                     "Account {} already exists and amount asked for is 0",
@@ -238,17 +257,18 @@ impl MintFunder {
             return Ok(vec![]);
         }
 
-        let txn =
-            {
-                let faucet_account = self.faucet_account.write().await;
-                let transaction_factory = self.get_transaction_factory().await?;
-                faucet_account.sign_with_transaction_builder(transaction_factory.script(
-                    Script::new(MINTER_SCRIPT.to_vec(), vec![], vec![
-                        TransactionArgument::Address(receiver_address),
-                        TransactionArgument::U64(amount),
-                    ]),
-                ))
-            };
+        let payload = match asset {
+            Some(asset) => asset.payload(receiver_address, amount),
+            None => TransactionPayload::Script(Script::new(MINTER_SCRIPT.to_vec(), vec![], vec![
+                TransactionArgument::Address(receiver_address),
+                TransactionArgument::U64(amount),
+            ])),
+        };
+        let txn = {
+            let faucet_account = self.faucet_account.write().await;
+            let transaction_factory = self.get_transaction_factory().await?;
+            faucet_account.sign_with_transaction_builder(transaction_factory.payload(payload))
+        };
 
         Ok(vec![
             submit_transaction(
@@ -269,15 +289,23 @@ impl FunderTrait for MintFunder {
         &self,
         amount: Option<u64>,
         receiver_address: AccountAddress,
+        asset: Option<String>,
         check_only: bool,
         did_bypass_checkers: bool,
     ) -> Result<Vec<SignedTransaction>, AptosTapError> {
         let client = self.get_api_client();
-        let amount = self.get_amount(amount, did_bypass_checkers);
+        let asset = asset
+            .map(|name| get_asset(&self.assets, &name))
+            .transpose()?;
+        let amount = match asset {
+            Some(asset) => asset.get_amount(amount),
+            None => self.get_amount(amount, did_bypass_checkers),
+        };
         self.process(
             &client,
             amount,
             receiver_address,
+            asset,
             check_only,
             self.txn_config.wait_for_transactions,
         )
@@ -296,6 +324,10 @@ impl FunderTrait for MintFunder {
         }
     }
 
+    fn supports_asset(&self, asset: &str) -> bool {
+        self.assets.contains_key(asset)
+    }
+
     /// Assert the funder account actually exists.
     async fn is_healthy(&self) -> FunderHealthMessage {
         let account_address = self.faucet_account.read().await.address();
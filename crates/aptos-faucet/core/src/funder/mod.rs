@@ -1,12 +1,14 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
+mod assets;
 mod common;
 mod fake;
 mod mint;
 mod transfer;
 
 pub use self::{
+    assets::AssetConfig,
     common::{ApiConnectionConfig, TransactionSubmissionConfig},
     mint::MintFunderConfig,
 };
@@ -31,19 +33,23 @@ pub trait FunderTrait: Sync + Send + 'static {
     /// anything that we can check on chain), and if everything looks good,
     /// creating and funding the account.
     ///
+    /// If `asset` is set, the account is funded with that asset from the Funder's
+    /// configured assets instead of with APT. The account may already exist.
+    ///
     /// If `check_only` is set, this function will only do the initial checks
     /// without actually submitting any transactions.
     async fn fund(
         &self,
         amount: Option<u64>,
         receiver_address: AccountAddress,
+        asset: Option<String>,
         check_only: bool,
         // True if a Bypasser let this request bypass the Checkers.
         did_bypass_checkers: bool,
     ) -> Result<Vec<SignedTransaction>, AptosTapError>;
 
     /// Given a requested amount and any configuration internal to this funder,
-    /// determine the amount that can be funded.
+    /// determine the amount of APT that can be funded.
     fn get_amount(
         &self,
         amount: Option<u64>,
@@ -51,6 +57,11 @@ pub trait FunderTrait: Sync + Send + 'static {
         did_bypass_checkers: bool,
     ) -> u64;
 
+    /// Whether the Funder was configured with the asset, so it can fund
+    /// accounts with it. Requests for other assets are rejected before any
+    /// Checkers run.
+    fn supports_asset(&self, asset: &str) -> bool;
+
     /// This should return whether the Funder is healthy and able to accept
     /// requests. With this a Funder can indicate some issue that will get
     /// exposed at the `/` (the healthcheck endpoint), e.g. that that it
@@ -80,7 +91,7 @@ pub enum FunderConfig {
 impl FunderConfig {
     pub async fn build(self) -> Result<Arc<Funder>> {
         match self {
-            FunderConfig::FakeFunder(_) => Ok(Arc::new(Funder::from(FakeFunder))),
+            FunderConfig::FakeFunder(config) => Ok(Arc::new(Funder::from(config.build_funder()))),
             FunderConfig::MintFunder(config) => Ok(Arc::new(Funder::from(
                 config
                     .build_funder()
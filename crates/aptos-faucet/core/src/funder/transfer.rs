@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use super::{
+    assets::{build_assets, get_asset, Asset, AssetConfig},
     common::{
         submit_transaction, ApiConnectionConfig, GasUnitPriceManager, TransactionSubmissionConfig,
     },
@@ -28,7 +29,7 @@ use aptos_sdk::{
 use async_trait::async_trait;
 use reqwest::Url;
 use serde::{Deserialize, Serialize};
-use std::{str::FromStr, time::Duration};
+use std::{collections::HashMap, str::FromStr, time::Duration};
 use tokio::sync::RwLock;
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -46,6 +47,11 @@ pub struct TransferFunderConfig {
 
     /// The amount of coins to fund the receiver account.
     pub amount_to_fund: AmountToFund,
+
+    /// Assets other than APT that this funder can fund accounts with, by name.
+    /// The funder account must hold these, or be able to mint them.
+    #[serde(default)]
+    pub assets: HashMap<String, AssetConfig>,
 }
 
 impl TransferFunderConfig {
@@ -59,7 +65,7 @@ impl TransferFunderConfig {
         // Build local representation of account.
         let faucet_account = LocalAccount::new(account_address, key, 0);
 
-        let funder = TransferFunder::new(
+        let mut funder = TransferFunder::new(
             faucet_account,
             self.api_connection_config.chain_id,
             self.api_connection_config.node_url.clone(),
@@ -75,6 +81,7 @@ impl TransferFunderConfig {
                 .wait_for_outstanding_txns_secs,
             self.transaction_submission_config.wait_for_transactions,
         );
+        funder.assets = build_assets(&self.assets)?;
 
         Ok(funder)
     }
@@ -110,6 +117,9 @@ pub struct TransferFunder {
 
     /// If set, we won't return responses until the transaction is processed.
     wait_for_transactions: bool,
+
+    /// Assets other than APT that this funder can fund accounts with, by name.
+    assets: HashMap<String, Asset>,
 }
 
 impl TransferFunder {
@@ -142,6 +152,7 @@ impl TransferFunder {
             outstanding_requests: RwLock::new(vec![]),
             wait_for_outstanding_txns_secs,
             wait_for_transactions,
+            assets: HashMap::new(),
         }
     }
 
@@ -227,12 +238,15 @@ impl FunderTrait for TransferFunder {
     /// prevalence of transaction failure. The transaction we submit ensures
     /// that the account doesn't exist already, so that's our real guarantee,
     /// the prior checks are just to avoid paying gas if we don't need to.
+    /// Requests for other assets may fund existing accounts, since they're
+    /// usually for accounts that already got APT.
     /// If check_only is set, we only do the initial checks without actually
     /// submitting any transactions.
     async fn fund(
         &self,
         amount: Option<u64>,
         receiver_address: AccountAddress,
+        asset: Option<String>,
         check_only: bool,
         did_bypass_checkers: bool,
     ) -> Result<Vec<SignedTransaction>, AptosTapError> {
@@ -244,8 +258,14 @@ impl FunderTrait for TransferFunder {
 
         let client = self.get_api_client();
 
-        // Determine amount to fund.
-        let amount = self.get_amount(amount, did_bypass_checkers);
+        // Determine asset and amount to fund.
+        let asset = asset
+            .map(|name| get_asset(&self.assets, &name))
+            .transpose()?;
+        let amount = match asset {
+            Some(asset) => asset.get_amount(amount),
+            None => self.get_amount(amount, did_bypass_checkers),
+        };
 
         // Update the sequence numbers of the accounts.
         let (_funder_seq_num, receiver_seq_num) = update_sequence_numbers(
@@ -260,7 +280,7 @@ impl FunderTrait for TransferFunder {
 
         // When updating the sequence numbers, we expect that the receiver sequence
         // number should be None, because the account should not exist yet.
-        if receiver_seq_num.is_some() {
+        if asset.is_none() && receiver_seq_num.is_some() {
             return Err(AptosTapError::new(
                 "Account ineligible".to_string(),
                 AptosTapErrorCode::Rejected,
@@ -271,18 +291,18 @@ impl FunderTrait for TransferFunder {
             )]));
         }
 
-        // This Move function checks if the account exists, and if it does,
-        // returns an error. If not, it creates the account and transfers the
-        // requested amount of coins to it.
+        // For APT, this Move function checks if the account exists, and if it
+        // does, returns an error. If not, it creates the account and transfers
+        // the requested amount of coins to it.
         let transactions = if check_only {
             vec![]
         } else {
+            let payload = match asset {
+                Some(asset) => asset.payload(receiver_address, amount),
+                None => aptos_stdlib::aptos_account_transfer(receiver_address, amount),
+            };
             let txn = self
-                .execute_transaction(
-                    &client,
-                    aptos_stdlib::aptos_account_transfer(receiver_address, amount),
-                    &receiver_address,
-                )
+                .execute_transaction(&client, payload, &receiver_address)
                 .await?;
             info!(
                 hash = txn.committed_hash().to_hex_literal(),
@@ -309,6 +329,10 @@ impl FunderTrait for TransferFunder {
         }
     }
 
+    fn supports_asset(&self, asset: &str) -> bool {
+        self.assets.contains_key(asset)
+    }
+
     /// Assert funder account actually exists and has the minimum funds.
     async fn is_healthy(&self) -> FunderHealthMessage {
         let account_address = self.faucet_account.read().await.address();
@@ -3,7 +3,9 @@
 
 use crate::{
     checkers::CaptchaManager,
-    endpoints::{build_openapi_service, BasicApi, CaptchaApi, FundApi, FundApiComponents},
+    endpoints::{
+        build_openapi_service, BasicApi, CaptchaApi, FundApi, FundApiComponents, ProofOfWorkApi,
+    },
     funder::{FakeFunder, Funder},
 };
 use anyhow::Result;
@@ -19,7 +21,7 @@ pub struct GenerateOpenapi {
 
 impl GenerateOpenapi {
     pub async fn generate_openapi(&self) -> Result<()> {
-        let funder = Arc::new(Funder::from(FakeFunder));
+        let funder = Arc::new(Funder::from(FakeFunder::default()));
         let fund_api = FundApi {
             components: Arc::new(FundApiComponents {
                 bypassers: Vec::new(),
@@ -33,13 +35,17 @@ impl GenerateOpenapi {
         let api_service = build_openapi_service(
             BasicApi {
                 concurrent_requests_semaphore: None,
-                funder,
+                funder: funder.clone(),
             },
             CaptchaApi {
                 enabled: false,
                 captcha_manager: Arc::new(Mutex::new(CaptchaManager::new())),
             },
             fund_api,
+            ProofOfWorkApi {
+                funder,
+                proof_of_work_manager: None,
+            },
         );
 
         let spec = match self.output_args.format {
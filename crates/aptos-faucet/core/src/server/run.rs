@@ -4,10 +4,10 @@
 use super::server_args::ServerConfig;
 use crate::{
     bypasser::{Bypasser, BypasserConfig},
-    checkers::{CaptchaManager, Checker, CheckerConfig, CheckerTrait},
+    checkers::{CaptchaManager, Checker, CheckerConfig, CheckerTrait, ProofOfWorkManager},
     endpoints::{
         build_openapi_service, convert_error, mint, BasicApi, CaptchaApi, FundApi,
-        FundApiComponents,
+        FundApiComponents, ProofOfWorkApi,
     },
     funder::{ApiConnectionConfig, FunderConfig, MintFunderConfig, TransactionSubmissionConfig},
     middleware::middleware_log,
@@ -104,6 +104,17 @@ impl RunConfig {
         // Create a CaptchaManager.
         let captcha_manager = Arc::new(Mutex::new(CaptchaManager::new()));
 
+        // Create a ProofOfWorkManager if the ProofOfWorkChecker is enabled.
+        let proof_of_work_manager = self.checker_configs.iter().find_map(|checker| {
+            if let CheckerConfig::ProofOfWork(config) = checker {
+                Some(Arc::new(Mutex::new(ProofOfWorkManager::new(
+                    config.clone(),
+                ))))
+            } else {
+                None
+            }
+        });
+
         // Build Bypassers.
         let mut bypassers: Vec<Bypasser> = Vec::new();
         for bypasser_config in &self.bypasser_configs {
@@ -122,7 +133,7 @@ impl RunConfig {
         for checker_config in &self.checker_configs {
             let checker = checker_config
                 .clone()
-                .build(captcha_manager.clone())
+                .build(captcha_manager.clone(), proof_of_work_manager.clone())
                 .await
                 .with_context(|| {
                     format!("Failed to build Checker with args: {:?}", checker_config)
@@ -161,7 +172,14 @@ impl RunConfig {
             captcha_manager,
         };
 
-        let api_service = build_openapi_service(basic_api, captcha_api, fund_api);
+        // Build the ProofOfWorkApi.
+        let proof_of_work_api = ProofOfWorkApi {
+            funder: fund_api_components.funder.clone(),
+            proof_of_work_manager,
+        };
+
+        let api_service =
+            build_openapi_service(basic_api, captcha_api, fund_api, proof_of_work_api);
         let spec_json = api_service.spec_endpoint();
         let spec_yaml = api_service.spec_endpoint_yaml();
 
@@ -385,6 +403,9 @@ mod test {
     use anyhow::{bail, Result};
     use aptos_sdk::{
         crypto::{ed25519::Ed25519PrivateKey, HashValue, PrivateKey, Uniform},
+        rest_client::faucet::{
+            solve_proof_of_work, ProofOfWorkChallenge, POW_CHALLENGE, POW_SOLUTION,
+        },
         types::{account_address::AccountAddress, transaction::authenticator::AuthenticationKey},
     };
     use once_cell::sync::OnceCell;
@@ -690,6 +711,146 @@ mod test {
         Ok(())
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_memory_ratelimiter_per_asset() -> Result<()> {
+        init();
+        let config_content = include_str!("../../../configs/testing_memory_ratelimit.yaml");
+        let (port, _handle) = start_server(config_content).await?;
+
+        let fund = |asset: Option<&str>| {
+            let fund_request = FundRequest {
+                asset: asset.map(|asset| asset.to_string()),
+                ..get_fund_request(Some(10))
+            };
+            reqwest::Client::new()
+                .post(get_fund_endpoint(port))
+                .body(fund_request.to_json_string())
+                .header(CONTENT_TYPE, "application/json")
+                .send()
+        };
+
+        // Assert that one request per asset works, since each asset has its own limit.
+        unwrap_reqwest_result(fund(None).await).await?;
+        unwrap_reqwest_result(fund(Some("test_coin")).await).await?;
+
+        // But a second request for either does not.
+        for asset in [None, Some("test_coin")] {
+            let response = fund(asset).await?;
+            assert_eq!(response.status(), reqwest::StatusCode::TOO_MANY_REQUESTS);
+        }
+
+        // Requests for assets the funder wasn't configured with are rejected
+        // before they reach the ratelimiter, so they never get a limit of their
+        // own.
+        for _ in 0..3 {
+            let response = fund(Some("unknown_coin")).await?;
+            assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+            let aptos_error = AptosTapError::parse_from_json_string(&response.text().await?)
+                .expect("Failed to read response as AptosError");
+            assert_eq!(aptos_error.error_code, AptosTapErrorCode::InvalidRequest);
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_proof_of_work_checker() -> Result<()> {
+        init();
+        let config_content = include_str!("../../../configs/testing_proof_of_work.yaml");
+        let (port, _handle) = start_server(config_content).await?;
+
+        // Assert that a request without a solution is rejected.
+        let fund_request = get_fund_request(Some(10));
+        let response = reqwest::Client::new()
+            .post(get_fund_endpoint(port))
+            .body(fund_request.to_json_string())
+            .header(CONTENT_TYPE, "application/json")
+            .send()
+            .await?;
+        let aptos_error = AptosTapError::parse_from_json_string(&response.text().await?)
+            .expect("Failed to read response as AptosError");
+        let rejection_reason_codes: HashSet<RejectionReasonCode> = aptos_error
+            .rejection_reasons
+            .into_iter()
+            .map(|r| r.get_code())
+            .collect();
+        assert!(rejection_reason_codes.contains(&RejectionReasonCode::ProofOfWorkInvalid));
+
+        // Get a challenge for the request and solve it.
+        let challenge: ProofOfWorkChallenge = unwrap_reqwest_result(
+            reqwest::Client::new()
+                .post(format!("{}/request_pow_challenge", get_root_endpoint(port)))
+                .body(fund_request.to_json_string())
+                .header(CONTENT_TYPE, "application/json")
+                .send()
+                .await,
+        )
+        .await?
+        .json()
+        .await?;
+        assert_eq!(challenge.difficulty, 8);
+        let solution =
+            solve_proof_of_work(&hex::decode(&challenge.challenge)?, challenge.difficulty);
+        let fund = |fund_request: &FundRequest| {
+            reqwest::Client::new()
+                .post(get_fund_endpoint(port))
+                .body(fund_request.to_json_string())
+                .header(CONTENT_TYPE, "application/json")
+                .header(POW_CHALLENGE, challenge.challenge.clone())
+                .header(POW_SOLUTION, solution)
+                .send()
+        };
+
+        // Assert that the solution isn't accepted for a different request.
+        let other_fund_request = FundRequest {
+            amount: Some(20),
+            ..fund_request.clone()
+        };
+        assert!(unwrap_reqwest_result(fund(&other_fund_request).await)
+            .await
+            .is_err());
+
+        // Assert that the solution isn't accepted from a different IP.
+        assert!(unwrap_reqwest_result(
+            reqwest::Client::new()
+                .post(get_fund_endpoint(port))
+                .body(fund_request.to_json_string())
+                .header(CONTENT_TYPE, "application/json")
+                .header("X-Forwarded-For", "10.0.0.1")
+                .header(POW_CHALLENGE, challenge.challenge.clone())
+                .header(POW_SOLUTION, solution)
+                .send()
+                .await
+        )
+        .await
+        .is_err());
+
+        // Assert that an IP can only have so many challenges outstanding, while
+        // other IPs still get challenges.
+        let request_challenge = |ip: &'static str| {
+            reqwest::Client::new()
+                .post(format!("{}/request_pow_challenge", get_root_endpoint(port)))
+                .body(fund_request.to_json_string())
+                .header(CONTENT_TYPE, "application/json")
+                .header("X-Forwarded-For", ip)
+                .send()
+        };
+        unwrap_reqwest_result(request_challenge("10.0.0.2").await).await?;
+        unwrap_reqwest_result(request_challenge("10.0.0.2").await).await?;
+        let response = request_challenge("10.0.0.2").await?;
+        assert_eq!(response.status(), reqwest::StatusCode::TOO_MANY_REQUESTS);
+        unwrap_reqwest_result(request_challenge("10.0.0.3").await).await?;
+
+        // Assert that the solution is accepted for the request it was for, but
+        // only once.
+        unwrap_reqwest_result(fund(&fund_request).await).await?;
+        assert!(unwrap_reqwest_result(fund(&fund_request).await)
+            .await
+            .is_err());
+
+        Ok(())
+    }
+
     // We skip this for now since we have no current need to use the TransferFunder.
     #[ignore]
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
@@ -21,5 +21,10 @@ export type FundRequest = {
      * Either this or `auth_key` / `address` must be provided.
      */
     pub_key?: string;
+    /**
+     * Name of the asset to fund the account with, as configured in the faucet.
+     * If not set, the account is funded with APT.
+     */
+    asset?: string;
 };
 
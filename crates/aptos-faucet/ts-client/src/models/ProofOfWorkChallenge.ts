@@ -0,0 +1,21 @@
+/* istanbul ignore file */
+/* tslint:disable */
+/* eslint-disable */
+
+export type ProofOfWorkChallenge = {
+    /**
+     * Hex encoded random bytes. The solution is a number such that the
+     * SHA3-256 hash of these bytes followed by the number as 8 little endian
+     * bytes starts with `difficulty` zero bits.
+     */
+    challenge: string;
+    /**
+     * Number of leading zero bits the hash of the solution must have.
+     */
+    difficulty: number;
+    /**
+     * The challenge is not accepted after this time.
+     */
+    expiration_timestamp_secs: number;
+};
+
@@ -22,5 +22,10 @@ export const $FundRequest = {
             type: 'string',
             description: `Either this or \`auth_key\` / \`address\` must be provided.`,
         },
+        asset: {
+            type: 'string',
+            description: `Name of the asset to fund the account with, as configured in the faucet.
+            If not set, the account is funded with APT.`,
+        },
     },
 } as const;
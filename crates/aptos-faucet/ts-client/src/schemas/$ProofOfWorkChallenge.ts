@@ -0,0 +1,26 @@
+/* istanbul ignore file */
+/* tslint:disable */
+/* eslint-disable */
+export const $ProofOfWorkChallenge = {
+    properties: {
+        challenge: {
+            type: 'string',
+            description: `Hex encoded random bytes. The solution is a number such that the
+            SHA3-256 hash of these bytes followed by the number as 8 little endian
+            bytes starts with \`difficulty\` zero bits.`,
+            isRequired: true,
+        },
+        difficulty: {
+            type: 'number',
+            description: `Number of leading zero bits the hash of the solution must have.`,
+            isRequired: true,
+            format: 'uint8',
+        },
+        expiration_timestamp_secs: {
+            type: 'number',
+            description: `The challenge is not accepted after this time.`,
+            isRequired: true,
+            format: 'uint64',
+        },
+    },
+} as const;
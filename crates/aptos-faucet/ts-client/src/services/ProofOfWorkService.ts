@@ -0,0 +1,41 @@
+/* istanbul ignore file */
+/* tslint:disable */
+/* eslint-disable */
+import type { AptosTapError } from '../models/AptosTapError';
+import type { FundRequest } from '../models/FundRequest';
+import type { ProofOfWorkChallenge } from '../models/ProofOfWorkChallenge';
+
+import type { CancelablePromise } from '../core/CancelablePromise';
+import type { BaseHttpRequest } from '../core/BaseHttpRequest';
+
+export class ProofOfWorkService {
+
+    constructor(public readonly httpRequest: BaseHttpRequest) {}
+
+    /**
+     * Request a proof of work challenge
+     * With this endpoint you can get a proof of work challenge for a funding
+     * request. The body must be the same as the body of the request to `/fund`
+     * the challenge is for. Include the challenge in the `POW_CHALLENGE`
+     * header and the solution in the `POW_SOLUTION` header of that request.
+     * The difficulty depends on the amount requested and on how many
+     * challenges the requester already has outstanding. This endpoint is only
+     * relevant if the ProofOfWorkChecker is enabled.
+     * @returns ProofOfWorkChallenge
+     * @returns AptosTapError
+     * @throws ApiError
+     */
+    public requestPowChallenge({
+        requestBody,
+    }: {
+        requestBody: FundRequest,
+    }): CancelablePromise<ProofOfWorkChallenge | AptosTapError> {
+        return this.httpRequest.request({
+            method: 'POST',
+            url: '/request_pow_challenge',
+            body: requestBody,
+            mediaType: 'application/json',
+        });
+    }
+
+}
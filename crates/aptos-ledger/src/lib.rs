@@ -7,14 +7,21 @@
 
 #![deny(missing_docs)]
 
-use aptos_crypto::ed25519::Ed25519Signature;
+#[cfg(any(test, feature = "testing"))]
+mod mock;
+
 pub use aptos_crypto::{ed25519::Ed25519PublicKey, ValidCryptoMaterialStringExt};
+use aptos_crypto::{ed25519::Ed25519Signature, signing_message, HashValue};
+use aptos_types::transaction::{EntryFunction, MultisigTransactionPayload, TransactionPayload};
 pub use aptos_types::{
-    account_address::AccountAddress, transaction::authenticator::AuthenticationKey,
+    account_address::AccountAddress,
+    transaction::{authenticator::AuthenticationKey, RawTransaction, RawTransactionWithData},
 };
 use hex::encode;
-use ledger_apdu::APDUCommand;
+use ledger_apdu::{APDUAnswer, APDUCommand};
 use ledger_transport_hid::{hidapi::HidApi, LedgerHIDError, TransportNativeHID};
+#[cfg(any(test, feature = "testing"))]
+pub use mock::MockLedgerTransport;
 use std::{
     collections::BTreeMap,
     fmt,
@@ -234,64 +241,231 @@ pub fn validate_derivation_path(input: &str) -> bool {
     false
 }
 
-/// Returns the current version of the Aptos app on Ledger
-pub fn get_app_version() -> Result<Version, AptosLedgerError> {
-    // Open connection to ledger
-    let transport = open_ledger_transport()?;
-
-    match transport.exchange(&APDUCommand {
-        cla: CLA_APTOS,
-        ins: INS_GET_VERSION,
-        p1: P1_NON_CONFIRM,
-        p2: P2_LAST,
-        data: vec![],
-    }) {
-        Ok(response) => {
-            // Received response from Ledger
-            if response.retcode() == APDU_CODE_SUCCESS {
-                let major = response.data()[0];
-                let minor = response.data()[1];
-                let patch = response.data()[2];
-                Ok(Version {
-                    major,
-                    minor,
-                    patch,
-                })
-            } else {
-                let error_code = AptosLedgerStatusCode::map_status_code(response.retcode());
-                Err(AptosLedgerError::AptosError(error_code))
+/// A connection to a Ledger device, over which APDU commands are exchanged
+pub trait LedgerTransport: Send + Sync {
+    /// Sends a command to the device and waits for its answer
+    fn exchange(
+        &self,
+        command: &APDUCommand<Vec<u8>>,
+    ) -> Result<APDUAnswer<Vec<u8>>, AptosLedgerError>;
+}
+
+impl LedgerTransport for TransportNativeHID {
+    fn exchange(
+        &self,
+        command: &APDUCommand<Vec<u8>>,
+    ) -> Result<APDUAnswer<Vec<u8>>, AptosLedgerError> {
+        TransportNativeHID::exchange(self, command).map_err(AptosLedgerError::from)
+    }
+}
+
+/// A handle to the Aptos app on a Ledger device
+pub struct AptosLedger {
+    transport: Box<dyn LedgerTransport>,
+}
+
+impl Debug for AptosLedger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AptosLedger").finish()
+    }
+}
+
+impl AptosLedger {
+    /// Opens a connection to the first Ledger device found over USB
+    pub fn open() -> Result<Self, AptosLedgerError> {
+        Ok(Self::new(open_ledger_transport()?))
+    }
+
+    /// Creates a handle over the given transport, e.g., a mock device for testing
+    pub fn new<T: LedgerTransport + 'static>(transport: T) -> Self {
+        Self {
+            transport: Box::new(transport),
+        }
+    }
+
+    /// Sends a command and returns the data of the answer, mapping error status codes
+    fn exchange(
+        &self,
+        ins: u8,
+        p1: u8,
+        p2: u8,
+        data: Vec<u8>,
+    ) -> Result<Vec<u8>, AptosLedgerError> {
+        let response = self.transport.exchange(&APDUCommand {
+            cla: CLA_APTOS,
+            ins,
+            p1,
+            p2,
+            data,
+        })?;
+        if response.retcode() == APDU_CODE_SUCCESS {
+            Ok(response.data().to_vec())
+        } else {
+            let error_code = AptosLedgerStatusCode::map_status_code(response.retcode());
+            Err(AptosLedgerError::AptosError(error_code))
+        }
+    }
+
+    /// Returns the current version of the Aptos app on Ledger
+    pub fn get_app_version(&self) -> Result<Version, AptosLedgerError> {
+        let data = self.exchange(INS_GET_VERSION, P1_NON_CONFIRM, P2_LAST, vec![])?;
+        if data.len() < 3 {
+            return Err(AptosLedgerError::UnexpectedError(
+                "Version response is too short".to_string(),
+                Some(APDU_CODE_SUCCESS),
+            ));
+        }
+        Ok(Version {
+            major: data[0],
+            minor: data[1],
+            patch: data[2],
+        })
+    }
+
+    /// Returns the official app name register in Ledger
+    pub fn get_app_name(&self) -> Result<String, AptosLedgerError> {
+        let data = self.exchange(INS_GET_APP_NAME, P1_NON_CONFIRM, P2_LAST, vec![])?;
+        match str::from_utf8(&data) {
+            Ok(app_name) => Ok(app_name.to_string()),
+            Err(e) => Err(AptosLedgerError::UnexpectedError(e.to_string(), None)),
+        }
+    }
+
+    /// Returns the public key of the account at the given derivation path
+    ///
+    /// # Arguments
+    ///
+    /// * `display` - If true, the public key will be displayed on the Ledger device, and confirmation is needed
+    pub fn get_public_key(
+        &self,
+        path: &str,
+        display: bool,
+    ) -> Result<Ed25519PublicKey, AptosLedgerError> {
+        // APDU command's instruction parameter 1 or p1
+        let p1: u8 = match display {
+            true => P1_CONFIRM,
+            false => P1_NON_CONFIRM,
+        };
+        let response_buffer = self.exchange(INS_GET_PUB_KEY, p1, P2_LAST, serialize_bip32(path))?;
+
+        // Extract the Public key from the response data
+        let mut offset = 0;
+        let pub_key_len: usize = (response_buffer[offset] - 1).into();
+        offset += 1;
+
+        // Skipping weird 0x04 - because of how the Aptos Ledger parse works when return pub key
+        offset += 1;
+
+        let pub_key_buffer = response_buffer[offset..offset + pub_key_len].to_vec();
+        let hex_string = encode(pub_key_buffer);
+        Ed25519PublicKey::from_encoded_string(&hex_string).map_err(|err| {
+            AptosLedgerError::UnexpectedError(err.to_string(), Some(APDU_CODE_SUCCESS))
+        })
+    }
+
+    /// Returns the batch/HashMap of the accounts for the account index in index_range
+    /// Note: We only allow a range of 10 for performance purpose
+    ///
+    /// # Arguments
+    ///
+    /// * `index_range` - start(inclusive) - end(exclusive) acounts, that you want to fetch, if None default to 0-10
+    pub fn fetch_batch_accounts(
+        &self,
+        index_range: Option<Range<u32>>,
+    ) -> Result<BTreeMap<String, AccountAddress>, AptosLedgerError> {
+        let range = index_range.unwrap_or(0..10);
+
+        // Make sure the range is within 10 counts
+        if range.end - range.start > 10 {
+            return Err(AptosLedgerError::UnexpectedError(
+                "Unexpected Error: Make sure the range is less than or equal to 10".to_string(),
+                None,
+            ));
+        }
+
+        let mut accounts = BTreeMap::new();
+        for i in range {
+            let path = DERIVATION_PATH.replace("{index}", &i.to_string());
+            let public_key = self.get_public_key(&path, false)?;
+            accounts.insert(path, account_address_from_public_key(&public_key));
+        }
+        Ok(accounts)
+    }
+
+    /// Returns the signature of an arbitrary message. Unless the message is a signing message
+    /// the app knows how to parse, the user has to enable blind signing on the device.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - derivation path of the ledger account
+    /// * `raw_message` - the raw message that need to be signed
+    pub fn sign_message(
+        &self,
+        path: &str,
+        raw_message: &[u8],
+    ) -> Result<Ed25519Signature, AptosLedgerError> {
+        // Send the derivation path over as first message
+        self.exchange(INS_SIGN_TXN, P1_START, P2_MORE, serialize_bip32(path))?;
+
+        let chunks = raw_message.chunks(MAX_APDU_LEN);
+        let chunks_count = chunks.len();
+
+        for (i, chunk) in chunks.enumerate() {
+            let is_last_chunk = chunks_count == i + 1;
+            let response_buffer = self.exchange(
+                INS_SIGN_TXN,
+                (i + 1) as u8,
+                if is_last_chunk { P2_LAST } else { P2_MORE },
+                chunk.to_vec(),
+            )?;
+            if is_last_chunk {
+                let signature_len: usize = response_buffer[0] as usize;
+                let signature_buffer = &response_buffer[1..1 + signature_len];
+                return Ed25519Signature::try_from(signature_buffer)
+                    .map_err(|err| AptosLedgerError::UnexpectedError(err.to_string(), None));
             }
-        },
-        Err(err) => Err(AptosLedgerError::from(err)),
+        }
+        Err(AptosLedgerError::UnexpectedError(
+            "Unable to process request".to_string(),
+            None,
+        ))
+    }
+
+    /// Returns the signature of a transaction. The transaction is sent in its BCS form behind
+    /// its signing prefix, so that the app can parse it and display its fields for the user to
+    /// review before approving.
+    pub fn sign_transaction(
+        &self,
+        path: &str,
+        raw_txn: &RawTransaction,
+    ) -> Result<Ed25519Signature, AptosLedgerError> {
+        let message = signing_message(raw_txn)
+            .map_err(|err| AptosLedgerError::UnexpectedError(err.to_string(), None))?;
+        self.sign_message(path, &message)
     }
+
+    /// Returns the signature of a multi-agent or fee payer transaction, which is what both the
+    /// secondary signers and the fee payer sign. As with [`AptosLedger::sign_transaction`], the
+    /// app displays the transaction, including the other signers, before approving.
+    pub fn sign_transaction_with_data(
+        &self,
+        path: &str,
+        raw_txn_with_data: &RawTransactionWithData,
+    ) -> Result<Ed25519Signature, AptosLedgerError> {
+        let message = signing_message(raw_txn_with_data)
+            .map_err(|err| AptosLedgerError::UnexpectedError(err.to_string(), None))?;
+        self.sign_message(path, &message)
+    }
+}
+
+/// Returns the current version of the Aptos app on Ledger
+pub fn get_app_version() -> Result<Version, AptosLedgerError> {
+    AptosLedger::open()?.get_app_version()
 }
 
 /// Returns the official app name register in Ledger
 pub fn get_app_name() -> Result<String, AptosLedgerError> {
-    // Open connection to ledger
-    let transport = open_ledger_transport()?;
-
-    match transport.exchange(&APDUCommand {
-        cla: CLA_APTOS,
-        ins: INS_GET_APP_NAME,
-        p1: P1_NON_CONFIRM,
-        p2: P2_LAST,
-        data: vec![],
-    }) {
-        Ok(response) => {
-            if response.retcode() == APDU_CODE_SUCCESS {
-                let app_name = match str::from_utf8(response.data()) {
-                    Ok(v) => v,
-                    Err(e) => return Err(AptosLedgerError::UnexpectedError(e.to_string(), None)),
-                };
-                Ok(app_name.to_string())
-            } else {
-                let error_code = AptosLedgerStatusCode::map_status_code(response.retcode());
-                Err(AptosLedgerError::AptosError(error_code))
-            }
-        },
-        Err(err) => Err(AptosLedgerError::from(err)),
-    }
+    AptosLedger::open()?.get_app_name()
 }
 
 /// Returns the batch/HashMap of the accounts for the account index in index_range
@@ -303,68 +477,7 @@ pub fn get_app_name() -> Result<String, AptosLedgerError> {
 pub fn fetch_batch_accounts(
     index_range: Option<Range<u32>>,
 ) -> Result<BTreeMap<String, AccountAddress>, AptosLedgerError> {
-    let range = if let Some(range) = index_range {
-        range
-    } else {
-        0..10
-    };
-
-    // Make sure the range is within 10 counts
-    if range.end - range.start > 10 {
-        return Err(AptosLedgerError::UnexpectedError(
-            "Unexpected Error: Make sure the range is less than or equal to 10".to_string(),
-            None,
-        ));
-    }
-
-    // Open connection to ledger
-    let transport = open_ledger_transport()?;
-
-    let mut accounts = BTreeMap::new();
-    for i in range {
-        let path = DERIVATION_PATH.replace("{index}", &i.to_string());
-        let cdata = serialize_bip32(&path);
-
-        match transport.exchange(&APDUCommand {
-            cla: CLA_APTOS,
-            ins: INS_GET_PUB_KEY,
-            p1: P1_NON_CONFIRM,
-            p2: P2_LAST,
-            data: cdata,
-        }) {
-            Ok(response) => {
-                // Got the response from ledger after user has confirmed on the ledger wallet
-                if response.retcode() == APDU_CODE_SUCCESS {
-                    // Extract the Public key from the response data
-                    let mut offset = 0;
-                    let response_buffer = response.data();
-                    let pub_key_len: usize = (response_buffer[offset] - 1).into();
-                    offset += 1;
-
-                    // Skipping weird 0x04 - because of how the Aptos Ledger parse works when return pub key
-                    offset += 1;
-
-                    let pub_key_buffer = response_buffer[offset..offset + pub_key_len].to_vec();
-                    let hex_string = encode(pub_key_buffer);
-                    let public_key = match Ed25519PublicKey::from_encoded_string(&hex_string) {
-                        Ok(pk) => Ok(pk),
-                        Err(err) => Err(AptosLedgerError::UnexpectedError(
-                            err.to_string(),
-                            Some(response.retcode()),
-                        )),
-                    };
-                    let account = account_address_from_public_key(&public_key?);
-                    accounts.insert(path, account);
-                } else {
-                    let error_code = AptosLedgerStatusCode::map_status_code(response.retcode());
-                    return Err(AptosLedgerError::AptosError(error_code));
-                }
-            },
-            Err(err) => return Err(AptosLedgerError::from(err)),
-        }
-    }
-
-    Ok(accounts)
+    AptosLedger::open()?.fetch_batch_accounts(index_range)
 }
 
 /// Returns the public key of your Aptos account in Ledger device at index 0
@@ -373,50 +486,7 @@ pub fn fetch_batch_accounts(
 ///
 /// * `display` - If true, the public key will be displayed on the Ledger device, and confirmation is needed
 pub fn get_public_key(path: &str, display: bool) -> Result<Ed25519PublicKey, AptosLedgerError> {
-    // Open connection to ledger
-    let transport = open_ledger_transport()?;
-
-    // Serialize the derivation path
-    let cdata = serialize_bip32(path);
-
-    // APDU command's instruction parameter 1 or p1
-    let p1: u8 = match display {
-        true => P1_CONFIRM,
-        false => P1_NON_CONFIRM,
-    };
-
-    match transport.exchange(&APDUCommand {
-        cla: CLA_APTOS,
-        ins: INS_GET_PUB_KEY,
-        p1,
-        p2: 0,
-        data: cdata,
-    }) {
-        Ok(response) => {
-            // Got the response from ledger after user has confirmed on the ledger wallet
-            if response.retcode() == APDU_CODE_SUCCESS {
-                // Extract the Public key from the response data
-                let mut offset = 0;
-                let response_buffer = response.data();
-                let pub_key_len: usize = (response_buffer[offset] - 1).into();
-                offset += 1;
-
-                // Skipping weird 0x04 - because of how the Aptos Ledger parse works when return pub key
-                offset += 1;
-
-                let pub_key_buffer = response_buffer[offset..offset + pub_key_len].to_vec();
-                let hex_string = encode(pub_key_buffer);
-                match Ed25519PublicKey::from_encoded_string(&hex_string) {
-                    Ok(pk) => Ok(pk),
-                    Err(err) => Err(AptosLedgerError::UnexpectedError(err.to_string(), None)),
-                }
-            } else {
-                let error_code = AptosLedgerStatusCode::map_status_code(response.retcode());
-                Err(AptosLedgerError::AptosError(error_code))
-            }
-        },
-        Err(err) => Err(AptosLedgerError::from(err)),
-    }
+    AptosLedger::open()?.get_public_key(path, display)
 }
 
 /// Returns the signed signature of the raw transaction user provided
@@ -426,61 +496,136 @@ pub fn get_public_key(path: &str, display: bool) -> Result<Ed25519PublicKey, Apt
 /// * `path` - derivation path of the ledger account
 /// * `raw_message` - the raw message that need to be signed
 pub fn sign_message(path: &str, raw_message: &[u8]) -> Result<Ed25519Signature, AptosLedgerError> {
-    // open connection to ledger
-    let transport = open_ledger_transport()?;
-
-    // Serialize the derivation path
-    let derivation_path_bytes = serialize_bip32(path);
-
-    // Send the derivation path over as first message
-    let sign_start = transport.exchange(&APDUCommand {
-        cla: CLA_APTOS,
-        ins: INS_SIGN_TXN,
-        p1: P1_START,
-        p2: P2_MORE,
-        data: derivation_path_bytes,
-    });
-
-    if let Err(err) = sign_start {
-        return Err(AptosLedgerError::UnexpectedError(err.to_string(), None));
+    AptosLedger::open()?.sign_message(path, raw_message)
+}
+
+/// Returns the signature of a transaction, after the user reviewed it on the device
+///
+/// # Arguments
+///
+/// * `path` - derivation path of the ledger account
+/// * `raw_txn` - the transaction that needs to be signed
+pub fn sign_transaction(
+    path: &str,
+    raw_txn: &RawTransaction,
+) -> Result<Ed25519Signature, AptosLedgerError> {
+    AptosLedger::open()?.sign_transaction(path, raw_txn)
+}
+
+/// Returns the signature of a multi-agent or fee payer transaction, after the user reviewed it on
+/// the device
+///
+/// # Arguments
+///
+/// * `path` - derivation path of the ledger account
+/// * `raw_txn_with_data` - the transaction, with its secondary signers and fee payer
+pub fn sign_transaction_with_data(
+    path: &str,
+    raw_txn_with_data: &RawTransactionWithData,
+) -> Result<Ed25519Signature, AptosLedgerError> {
+    AptosLedger::open()?.sign_transaction_with_data(path, raw_txn_with_data)
+}
+
+/// Returns the fields of a transaction in the order the app displays them, so that they can be
+/// shown to the user for comparison with what the device displays
+pub fn describe_transaction(raw_txn: &RawTransaction) -> Vec<(String, String)> {
+    let mut fields = vec![
+        ("Sender".to_string(), raw_txn.sender().to_standard_string()),
+        (
+            "Sequence number".to_string(),
+            raw_txn.sequence_number().to_string(),
+        ),
+    ];
+    match raw_txn.payload() {
+        TransactionPayload::EntryFunction(entry_function) => {
+            describe_entry_function(&mut fields, entry_function)
+        },
+        TransactionPayload::Multisig(multisig) => {
+            fields.push((
+                "Multisig account".to_string(),
+                multisig.multisig_address.to_standard_string(),
+            ));
+            match &multisig.transaction_payload {
+                Some(MultisigTransactionPayload::EntryFunction(entry_function)) => {
+                    describe_entry_function(&mut fields, entry_function)
+                },
+                None => fields.push((
+                    "Function".to_string(),
+                    "Payload stored on chain".to_string(),
+                )),
+            }
+        },
+        TransactionPayload::Script(script) => fields.push((
+            "Script hash".to_string(),
+            HashValue::sha3_256_of(script.code()).to_hex_literal(),
+        )),
+        TransactionPayload::ModuleBundle(_) => {
+            fields.push(("Payload".to_string(), "Module bundle".to_string()))
+        },
     }
+    fields.extend([
+        (
+            "Max gas amount".to_string(),
+            raw_txn.max_gas_amount().to_string(),
+        ),
+        (
+            "Gas unit price".to_string(),
+            raw_txn.gas_unit_price().to_string(),
+        ),
+        (
+            "Expiration timestamp".to_string(),
+            raw_txn.expiration_timestamp_secs().to_string(),
+        ),
+        ("Chain ID".to_string(), raw_txn.chain_id().to_string()),
+    ]);
+    fields
+}
 
-    let chunks = raw_message.chunks(MAX_APDU_LEN);
-    let chunks_count = chunks.len();
+/// Same as [`describe_transaction`], including the secondary signers and the fee payer
+pub fn describe_transaction_with_data(
+    raw_txn_with_data: &RawTransactionWithData,
+) -> Vec<(String, String)> {
+    let (raw_txn, secondary_signer_addresses, fee_payer_address) = match raw_txn_with_data {
+        RawTransactionWithData::MultiAgent {
+            raw_txn,
+            secondary_signer_addresses,
+        } => (raw_txn, secondary_signer_addresses, None),
+        RawTransactionWithData::MultiAgentWithFeePayer {
+            raw_txn,
+            secondary_signer_addresses,
+            fee_payer_address,
+        } => (raw_txn, secondary_signer_addresses, Some(fee_payer_address)),
+    };
+    let mut fields = describe_transaction(raw_txn);
+    for address in secondary_signer_addresses {
+        fields.push(("Secondary signer".to_string(), address.to_standard_string()));
+    }
+    if let Some(fee_payer_address) = fee_payer_address {
+        fields.push((
+            "Fee payer".to_string(),
+            fee_payer_address.to_standard_string(),
+        ));
+    }
+    fields
+}
 
-    for (i, chunk) in chunks.enumerate() {
-        let is_last_chunk = chunks_count == i + 1;
-        match transport.exchange(&APDUCommand {
-            cla: CLA_APTOS,
-            ins: INS_SIGN_TXN,
-            p1: (i + 1) as u8,
-            p2: if is_last_chunk { P2_LAST } else { P2_MORE },
-            data: chunk.to_vec(),
-        }) {
-            Ok(response) => {
-                // success response
-                if response.retcode() == APDU_CODE_SUCCESS {
-                    if is_last_chunk {
-                        let response_buffer = response.data();
-
-                        let signature_len: usize = response_buffer[0] as usize;
-                        let signature_buffer = &response_buffer[1..1 + signature_len];
-                        return Ed25519Signature::try_from(signature_buffer).map_err(|err| {
-                            AptosLedgerError::UnexpectedError(err.to_string(), None)
-                        });
-                    }
-                } else {
-                    let error_code = AptosLedgerStatusCode::map_status_code(response.retcode());
-                    return Err(AptosLedgerError::AptosError(error_code));
-                }
-            },
-            Err(err) => return Err(AptosLedgerError::from(err)),
-        };
+fn describe_entry_function(fields: &mut Vec<(String, String)>, entry_function: &EntryFunction) {
+    fields.push((
+        "Function".to_string(),
+        format!(
+            "{}::{}",
+            entry_function.module().short_str_lossless(),
+            entry_function.function()
+        ),
+    ));
+    if !entry_function.ty_args().is_empty() {
+        let ty_args: Vec<_> = entry_function
+            .ty_args()
+            .iter()
+            .map(|ty_arg| ty_arg.to_string())
+            .collect();
+        fields.push(("Type arguments".to_string(), ty_args.join(", ")));
     }
-    Err(AptosLedgerError::UnexpectedError(
-        "Unable to process request".to_string(),
-        None,
-    ))
 }
 
 /// This is the Rust version of the serialization of BIP32 from Petra Wallet
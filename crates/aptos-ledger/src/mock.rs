@@ -0,0 +1,324 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! A mock of the Aptos app on a Ledger device, so that signing flows can be tested without
+//! hardware. Keys are derived deterministically from a seed and the derivation path.
+
+use crate::{
+    describe_transaction, describe_transaction_with_data, serialize_bip32, AptosLedgerError,
+    AptosLedgerStatusCode, LedgerTransport, APDU_CODE_SUCCESS, CLA_APTOS, INS_GET_APP_NAME,
+    INS_GET_PUB_KEY, INS_GET_VERSION, INS_SIGN_TXN, P1_CONFIRM, P1_START, P2_LAST,
+};
+use aptos_crypto::{
+    ed25519::{Ed25519PrivateKey, Ed25519PublicKey},
+    hash::{CryptoHash, CryptoHasher},
+    HashValue, PrivateKey, SigningKey,
+};
+use aptos_types::transaction::{RawTransaction, RawTransactionWithData};
+use ledger_apdu::{APDUAnswer, APDUCommand};
+use std::sync::Mutex;
+
+/// A mock Ledger device running the Aptos app
+pub struct MockLedgerTransport {
+    seed: [u8; 32],
+    state: Mutex<MockState>,
+}
+
+#[derive(Default)]
+struct MockState {
+    /// The derivation path and the message received so far of the signing request in progress
+    signing_request: Option<(Vec<u8>, Vec<u8>)>,
+    /// The fields of every transaction displayed for review
+    displayed_transactions: Vec<Vec<(String, String)>>,
+    /// Whether the user rejects requests that need confirmation
+    reject: bool,
+    /// Whether the user enabled signing of messages that the app cannot parse
+    blind_signing: bool,
+}
+
+impl MockLedgerTransport {
+    /// Creates a mock device whose keys are derived from the given seed
+    pub fn new(seed: [u8; 32]) -> Self {
+        Self {
+            seed,
+            state: Mutex::new(MockState::default()),
+        }
+    }
+
+    /// Sets whether the user rejects the requests that need confirmation on the device
+    pub fn set_reject(&self, reject: bool) {
+        self.state.lock().unwrap().reject = reject;
+    }
+
+    /// Sets whether the user enabled blind signing on the device
+    pub fn set_blind_signing(&self, blind_signing: bool) {
+        self.state.lock().unwrap().blind_signing = blind_signing;
+    }
+
+    /// Returns the fields of every transaction the device displayed for review so far
+    pub fn displayed_transactions(&self) -> Vec<Vec<(String, String)>> {
+        self.state.lock().unwrap().displayed_transactions.clone()
+    }
+
+    /// Returns the public key of the account at the given derivation path
+    pub fn public_key(&self, path: &str) -> Ed25519PublicKey {
+        self.private_key(&serialize_bip32(path)).public_key()
+    }
+
+    fn private_key(&self, serialized_path: &[u8]) -> Ed25519PrivateKey {
+        let mut bytes = self.seed.to_vec();
+        bytes.extend_from_slice(serialized_path);
+        Ed25519PrivateKey::try_from(HashValue::sha3_256_of(&bytes).as_ref())
+            .expect("Any 32 bytes are a valid Ed25519 private key")
+    }
+
+    /// Parses a signing message the way the app does, returning the fields to display
+    fn parse_signing_message(message: &[u8]) -> Option<Vec<(String, String)>> {
+        let raw_txn_seed = <RawTransaction as CryptoHash>::Hasher::seed();
+        let raw_txn_with_data_seed = <RawTransactionWithData as CryptoHash>::Hasher::seed();
+        if let Some(bytes) = message.strip_prefix(&raw_txn_seed[..]) {
+            bcs::from_bytes::<RawTransaction>(bytes)
+                .ok()
+                .map(|raw_txn| describe_transaction(&raw_txn))
+        } else if let Some(bytes) = message.strip_prefix(&raw_txn_with_data_seed[..]) {
+            bcs::from_bytes::<RawTransactionWithData>(bytes)
+                .ok()
+                .map(|raw_txn_with_data| describe_transaction_with_data(&raw_txn_with_data))
+        } else {
+            None
+        }
+    }
+
+    fn handle(&self, command: &APDUCommand<Vec<u8>>) -> Result<Vec<u8>, AptosLedgerStatusCode> {
+        if command.cla != CLA_APTOS {
+            return Err(AptosLedgerStatusCode::ClaNotSupported);
+        }
+
+        let mut state = self.state.lock().unwrap();
+        match command.ins {
+            INS_GET_VERSION => Ok(vec![0, 6, 0]),
+            INS_GET_APP_NAME => Ok(b"Aptos".to_vec()),
+            INS_GET_PUB_KEY => {
+                if command.p1 == P1_CONFIRM && state.reject {
+                    return Err(AptosLedgerStatusCode::Deny);
+                }
+                let public_key = self.private_key(&command.data).public_key();
+                let mut response = vec![33, 0x04];
+                response.extend_from_slice(&public_key.to_bytes());
+                Ok(response)
+            },
+            INS_SIGN_TXN => {
+                if command.p1 == P1_START {
+                    state.signing_request = Some((command.data.clone(), vec![]));
+                    return Ok(vec![]);
+                }
+                let (serialized_path, message) = state
+                    .signing_request
+                    .as_mut()
+                    .ok_or(AptosLedgerStatusCode::BadState)?;
+                message.extend_from_slice(&command.data);
+                if command.p2 != P2_LAST {
+                    return Ok(vec![]);
+                }
+
+                let (serialized_path, message) = (serialized_path.clone(), message.clone());
+                state.signing_request = None;
+                match Self::parse_signing_message(&message) {
+                    Some(fields) => state.displayed_transactions.push(fields),
+                    None if state.blind_signing => (),
+                    None => return Err(AptosLedgerStatusCode::TxnParsingFail),
+                }
+                if state.reject {
+                    return Err(AptosLedgerStatusCode::Deny);
+                }
+
+                let signature = self
+                    .private_key(&serialized_path)
+                    .sign_arbitrary_message(&message);
+                let mut response = vec![64];
+                response.extend_from_slice(&signature.to_bytes());
+                Ok(response)
+            },
+            _ => Err(AptosLedgerStatusCode::InsNotSupported),
+        }
+    }
+}
+
+impl LedgerTransport for MockLedgerTransport {
+    fn exchange(
+        &self,
+        command: &APDUCommand<Vec<u8>>,
+    ) -> Result<APDUAnswer<Vec<u8>>, AptosLedgerError> {
+        let (mut answer, retcode) = match self.handle(command) {
+            Ok(data) => (data, APDU_CODE_SUCCESS),
+            Err(status_code) => (vec![], status_code as u16),
+        };
+        answer.extend_from_slice(&retcode.to_be_bytes());
+        APDUAnswer::from_answer(answer).map_err(|_| {
+            AptosLedgerError::UnexpectedError("Invalid mock answer".to_string(), Some(retcode))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AptosLedger, DERIVATION_PATH};
+    use aptos_crypto::Signature;
+    use aptos_types::{
+        account_address::AccountAddress,
+        chain_id::ChainId,
+        transaction::{EntryFunction, Multisig, MultisigTransactionPayload, TransactionPayload},
+    };
+    use move_core_types::{ident_str, language_storage::ModuleId};
+    use std::sync::Arc;
+
+    /// Lets the tests keep a handle on the mock after moving it into an AptosLedger
+    struct SharedMock(Arc<MockLedgerTransport>);
+
+    impl LedgerTransport for SharedMock {
+        fn exchange(
+            &self,
+            command: &APDUCommand<Vec<u8>>,
+        ) -> Result<APDUAnswer<Vec<u8>>, AptosLedgerError> {
+            self.0.exchange(command)
+        }
+    }
+
+    fn ledger() -> (AptosLedger, Arc<MockLedgerTransport>) {
+        let mock = Arc::new(MockLedgerTransport::new([7; 32]));
+        (AptosLedger::new(SharedMock(mock.clone())), mock)
+    }
+
+    fn path() -> String {
+        DERIVATION_PATH.replace("{index}", "0")
+    }
+
+    fn raw_txn(payload: TransactionPayload) -> RawTransaction {
+        RawTransaction::new(
+            AccountAddress::ONE,
+            5,
+            payload,
+            1_000,
+            100,
+            1_000_000,
+            ChainId::test(),
+        )
+    }
+
+    fn transfer() -> EntryFunction {
+        EntryFunction::new(
+            ModuleId::new(AccountAddress::ONE, ident_str!("aptos_account").to_owned()),
+            ident_str!("transfer").to_owned(),
+            vec![],
+            // Large enough to be sent in several chunks
+            vec![vec![1; 300], vec![2; 8]],
+        )
+    }
+
+    #[test]
+    fn test_get_public_key() {
+        let (ledger, mock) = ledger();
+        assert_eq!(
+            ledger.get_public_key(&path(), false).unwrap(),
+            mock.public_key(&path())
+        );
+
+        mock.set_reject(true);
+        assert!(matches!(
+            ledger.get_public_key(&path(), true),
+            Err(AptosLedgerError::AptosError(AptosLedgerStatusCode::Deny))
+        ));
+    }
+
+    #[test]
+    fn test_sign_transaction() {
+        let (ledger, mock) = ledger();
+        let raw_txn = raw_txn(TransactionPayload::EntryFunction(transfer()));
+
+        let signature = ledger.sign_transaction(&path(), &raw_txn).unwrap();
+        signature
+            .verify(&raw_txn, &mock.public_key(&path()))
+            .unwrap();
+        assert_eq!(mock.displayed_transactions(), vec![describe_transaction(
+            &raw_txn
+        )]);
+        assert!(mock.displayed_transactions()[0].contains(&(
+            "Function".to_string(),
+            "0x1::aptos_account::transfer".to_string()
+        )));
+    }
+
+    #[test]
+    fn test_sign_transaction_with_data() {
+        let (ledger, mock) = ledger();
+        let raw_txn = raw_txn(TransactionPayload::EntryFunction(transfer()));
+        let fee_payer_txn =
+            RawTransactionWithData::new_fee_payer(raw_txn.clone(), vec![], AccountAddress::TWO);
+        let multi_agent_txn =
+            RawTransactionWithData::new_multi_agent(raw_txn, vec![AccountAddress::THREE]);
+
+        for txn in [fee_payer_txn, multi_agent_txn] {
+            let signature = ledger.sign_transaction_with_data(&path(), &txn).unwrap();
+            signature.verify(&txn, &mock.public_key(&path())).unwrap();
+        }
+        let displayed = mock.displayed_transactions();
+        assert!(displayed[0].contains(&(
+            "Fee payer".to_string(),
+            AccountAddress::TWO.to_standard_string()
+        )));
+        assert!(displayed[1].contains(&(
+            "Secondary signer".to_string(),
+            AccountAddress::THREE.to_standard_string()
+        )));
+    }
+
+    #[test]
+    fn test_sign_multisig_transaction() {
+        let (ledger, mock) = ledger();
+        let raw_txn = raw_txn(TransactionPayload::Multisig(Multisig {
+            multisig_address: AccountAddress::TWO,
+            transaction_payload: Some(MultisigTransactionPayload::EntryFunction(transfer())),
+        }));
+
+        let signature = ledger.sign_transaction(&path(), &raw_txn).unwrap();
+        signature
+            .verify(&raw_txn, &mock.public_key(&path()))
+            .unwrap();
+        assert!(mock.displayed_transactions()[0].contains(&(
+            "Multisig account".to_string(),
+            AccountAddress::TWO.to_standard_string()
+        )));
+    }
+
+    #[test]
+    fn test_sign_opaque_message() {
+        let (ledger, mock) = ledger();
+        let message = b"not a transaction";
+
+        assert!(matches!(
+            ledger.sign_message(&path(), message),
+            Err(AptosLedgerError::AptosError(
+                AptosLedgerStatusCode::TxnParsingFail
+            ))
+        ));
+
+        mock.set_blind_signing(true);
+        let signature = ledger.sign_message(&path(), message).unwrap();
+        signature
+            .verify_arbitrary_msg(message, &mock.public_key(&path()))
+            .unwrap();
+        assert!(mock.displayed_transactions().is_empty());
+    }
+
+    #[test]
+    fn test_rejected_transaction() {
+        let (ledger, mock) = ledger();
+        mock.set_reject(true);
+        let raw_txn = raw_txn(TransactionPayload::EntryFunction(transfer()));
+        assert!(matches!(
+            ledger.sign_transaction(&path(), &raw_txn),
+            Err(AptosLedgerError::AptosError(AptosLedgerStatusCode::Deny))
+        ));
+    }
+}
@@ -3,16 +3,57 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{error::FaucetClientError, Client, Result};
+use aptos_crypto::HashValue;
 use aptos_types::transaction::SignedTransaction;
 use move_core_types::account_address::AccountAddress;
 use reqwest::{Client as ReqwestClient, Response, Url};
+use serde::{Deserialize, Serialize};
 use std::time::Duration;
+use tokio::sync::OnceCell;
+
+/// Headers that carry the solution to a proof of work challenge, for faucets
+/// that run the ProofOfWorkChecker.
+pub const POW_CHALLENGE: &str = "POW_CHALLENGE";
+pub const POW_SOLUTION: &str = "POW_SOLUTION";
+
+/// A proof of work challenge issued by the faucet for a specific request.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ProofOfWorkChallenge {
+    /// Hex encoded random bytes to solve the challenge for.
+    pub challenge: String,
+    /// Number of leading zero bits the hash of the solution must have.
+    pub difficulty: u8,
+    /// When the challenge stops being accepted by the faucet.
+    pub expiration_timestamp_secs: u64,
+}
+
+/// The number of leading zero bits in the SHA3-256 hash of the challenge
+/// followed by the solution as little endian bytes. A solution is valid if
+/// this is at least the difficulty of the challenge.
+pub fn proof_of_work_bits(challenge: &[u8], solution: u64) -> usize {
+    let mut bytes = challenge.to_vec();
+    bytes.extend_from_slice(&solution.to_le_bytes());
+    HashValue::sha3_256_of(&bytes)
+        .iter_bits()
+        .take_while(|bit| !bit)
+        .count()
+}
+
+/// Finds a solution to a proof of work challenge by brute force. On average
+/// this takes 2^difficulty hashes.
+pub fn solve_proof_of_work(challenge: &[u8], difficulty: u8) -> u64 {
+    (0..=u64::MAX)
+        .find(|solution| proof_of_work_bits(challenge, *solution) >= difficulty as usize)
+        .expect("Every difficulty up to 256 bits has a solution")
+}
 
 pub struct FaucetClient {
     faucet_url: Url,
     inner: ReqwestClient,
     rest_client: Client,
     token: Option<String>,
+    /// Whether the faucet requires proof of work, once it has said so either way.
+    proof_of_work_required: OnceCell<bool>,
 }
 
 impl FaucetClient {
@@ -34,6 +75,7 @@ impl FaucetClient {
                 .version_path_base("/".to_string())
                 .unwrap(),
             token: None,
+            proof_of_work_required: OnceCell::new(),
         }
     }
 
@@ -46,6 +88,7 @@ impl FaucetClient {
                 .unwrap(),
             rest_client,
             token: None,
+            proof_of_work_required: OnceCell::new(),
         }
     }
 
@@ -57,12 +100,7 @@ impl FaucetClient {
 
     /// Create an account with zero balance.
     pub async fn create_account(&self, address: AccountAddress) -> Result<()> {
-        let mut url = self.faucet_url.clone();
-        url.set_path("mint");
-        let query = format!("auth_key={}&amount=0&return_txns=true", address);
-        url.set_query(Some(&query));
-
-        let response = self.build_and_submit_request(url).await?;
+        let response = self.build_and_submit_request(address, 0).await?;
         let status_code = response.status();
         let body = response.text().await.map_err(FaucetClientError::decode)?;
         if !status_code.is_success() {
@@ -83,14 +121,9 @@ impl FaucetClient {
 
     /// Fund an account with the given amount.
     pub async fn fund(&self, address: AccountAddress, amount: u64) -> Result<()> {
-        let mut url = self.faucet_url.clone();
-        url.set_path("mint");
-        let query = format!("auth_key={}&amount={}&return_txns=true", address, amount);
-        url.set_query(Some(&query));
-
         // Faucet returns the transaction that creates the account and needs to be waited on before
         // returning.
-        let response = self.build_and_submit_request(url).await?;
+        let response = self.build_and_submit_request(address, amount).await?;
         let status_code = response.status();
         let body = response.text().await.map_err(FaucetClientError::decode)?;
         if !status_code.is_success() {
@@ -117,13 +150,148 @@ impl FaucetClient {
         Ok(())
     }
 
-    // Helper to carry out requests.
-    async fn build_and_submit_request(&self, url: Url) -> Result<Response> {
+    /// Request a proof of work challenge for funding the account with the given
+    /// amount. Returns None if the faucet doesn't issue challenges, in which
+    /// case the endpoint is either disabled or, for older faucets, missing.
+    pub async fn request_proof_of_work_challenge(
+        &self,
+        address: AccountAddress,
+        amount: u64,
+    ) -> Result<Option<ProofOfWorkChallenge>> {
+        let response = self
+            .build_json_request("request_pow_challenge", address, amount)
+            .send()
+            .await
+            .map_err(FaucetClientError::request)?;
+        let status_code = response.status();
+        if status_code.is_success() {
+            let challenge = response
+                .json::<ProofOfWorkChallenge>()
+                .await
+                .map_err(FaucetClientError::decode)?;
+            return Ok(Some(challenge));
+        }
+
+        let body = response.text().await.map_err(FaucetClientError::decode)?;
+        let endpoint_not_enabled = serde_json::from_str::<serde_json::Value>(&body)
+            .ok()
+            .and_then(|error| error.get("error_code").cloned())
+            .map_or(false, |error_code| error_code == "EndpointNotEnabled");
+        if status_code == reqwest::StatusCode::NOT_FOUND || endpoint_not_enabled {
+            return Ok(None);
+        }
+        Err(anyhow::anyhow!(
+            "Failed to request proof of work challenge, status: {}, body: {}",
+            status_code,
+            body
+        ))
+    }
+
+    /// Whether the faucet requires proof of work for funding requests. This asks
+    /// `/is_eligible`, which doesn't use up any ratelimits, and remembers the
+    /// answer once the faucet has given one either way.
+    pub async fn is_proof_of_work_required(
+        &self,
+        address: AccountAddress,
+        amount: u64,
+    ) -> Result<bool> {
+        if let Some(required) = self.proof_of_work_required.get() {
+            return Ok(*required);
+        }
+
+        let response = self
+            .build_json_request("is_eligible", address, amount)
+            .send()
+            .await
+            .map_err(FaucetClientError::request)?;
+        let status_code = response.status();
+        // Older faucets don't have the endpoint, nor proof of work.
+        if status_code.is_success() || status_code == reqwest::StatusCode::NOT_FOUND {
+            let _ = self.proof_of_work_required.set(false);
+            return Ok(false);
+        }
+
+        let body = response.text().await.map_err(FaucetClientError::decode)?;
+        let rejection_codes: Vec<String> = serde_json::from_str::<serde_json::Value>(&body)
+            .ok()
+            .and_then(|error| error.get("rejection_reasons").cloned())
+            .and_then(|reasons| reasons.as_array().cloned())
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|reason| reason.get("code")?.as_str().map(str::to_string))
+            .collect();
+        if rejection_codes
+            .iter()
+            .any(|code| code == "ProofOfWorkInvalid")
+        {
+            let _ = self.proof_of_work_required.set(true);
+            return Ok(true);
+        }
+        if status_code.is_server_error() {
+            return Err(anyhow::anyhow!(
+                "Failed to check eligibility, status: {}, body: {}",
+                status_code,
+                body
+            ));
+        }
+        // The request was rejected for other reasons, either by checkers that
+        // ran first or by the Funder after the checkers passed. Either way the
+        // request itself fails with an explanation, so don't fetch a challenge.
+        Ok(false)
+    }
+
+    fn build_json_request(
+        &self,
+        path: &str,
+        address: AccountAddress,
+        amount: u64,
+    ) -> reqwest::RequestBuilder {
+        let mut url = self.faucet_url.clone();
+        url.set_path(path);
+        let mut request = self.inner.post(url).json(&serde_json::json!({
+            "address": address.to_hex_literal(),
+            "amount": amount,
+        }));
+        if let Some(token) = &self.token {
+            request = request.header("Authorization", format!("Bearer {}", token));
+        }
+        request
+    }
+
+    // Helper to carry out requests. If the faucet requires proof of work, this
+    // solves a challenge for the request first.
+    async fn build_and_submit_request(
+        &self,
+        address: AccountAddress,
+        amount: u64,
+    ) -> Result<Response> {
+        let mut url = self.faucet_url.clone();
+        url.set_path("mint");
+        let query = format!("auth_key={}&amount={}&return_txns=true", address, amount);
+        url.set_query(Some(&query));
+
         // build request
         let mut request = self.inner.post(url).header("content-length", 0);
         if let Some(token) = &self.token {
             request = request.header("Authorization", format!("Bearer {}", token));
         }
+        if self.is_proof_of_work_required(address, amount).await? {
+            let challenge = self
+                .request_proof_of_work_challenge(address, amount)
+                .await?
+                .ok_or_else(|| {
+                    anyhow::anyhow!("Faucet requires proof of work but issues no challenges")
+                })?;
+            let bytes = hex::decode(&challenge.challenge).map_err(FaucetClientError::decode)?;
+            let difficulty = challenge.difficulty;
+            let solution =
+                tokio::task::spawn_blocking(move || solve_proof_of_work(&bytes, difficulty))
+                    .await
+                    .map_err(FaucetClientError::unknown)?;
+            request = request
+                .header(POW_CHALLENGE, challenge.challenge)
+                .header(POW_SOLUTION, solution);
+        }
 
         // carry out and return response
         let response = request.send().await.map_err(FaucetClientError::request)?;
@@ -1613,6 +1613,19 @@ impl Client {
         Ok(Response::new(json, state))
     }
 
+    /// Retrieves basic info about the node's configuration, such as its storage format and
+    /// internal indexer config.  This carries no ledger state, so there's no [`State`].
+    pub async fn get_node_info(&self) -> AptosResult<BTreeMap<String, Value>> {
+        let url = self.build_path("info")?;
+        let response = self.inner.get(url).send().await?;
+
+        if !response.status().is_success() {
+            Err(parse_error(response).await)
+        } else {
+            Ok(response.json().await.map_err(anyhow::Error::from)?)
+        }
+    }
+
     pub async fn health_check(&self, seconds: u64) -> AptosResult<()> {
         let url = self.build_path("-/healthy")?;
         let response = self
@@ -8,7 +8,8 @@
 
 use crate::{
     common::{
-        check_network, get_block_index_from_request, handle_request, native_coin, with_context,
+        check_network, fa_only_address, get_block_index_from_request, handle_request, native_coin,
+        resolve_fa_currency, with_context,
     },
     error::{ApiError, ApiResult},
     types::{AccountBalanceRequest, AccountBalanceResponse, Amount, Currency, *},
@@ -115,7 +116,24 @@ async fn get_balances(
 
     // Filter currencies to lookup
     let currencies_to_lookup = if let Some(currencies) = maybe_filter_currencies {
-        currencies.into_iter().collect()
+        let mut currencies_to_lookup = HashSet::new();
+        for currency in currencies {
+            match fa_only_address(&currency) {
+                // Fungible assets are looked up by metadata address, so the balance is reported
+                // with the same currency as its operations
+                Some(metadata_address) if server_context.support_all_fungible_assets => {
+                    if let Some(currency) =
+                        resolve_fa_currency(server_context, metadata_address, Some(version)).await?
+                    {
+                        currencies_to_lookup.insert(currency);
+                    }
+                },
+                _ => {
+                    currencies_to_lookup.insert(currency);
+                },
+            }
+        }
+        currencies_to_lookup
     } else {
         server_context.currencies.clone()
     };
@@ -157,7 +175,7 @@ async fn get_balances(
     ))
 }
 
-async fn get_sequence_number(
+pub(crate) async fn get_sequence_number(
     rest_client: &Client,
     owner_address: AccountAddress,
     version: u64,
@@ -316,6 +334,9 @@ async fn get_base_balances(
                     }),
                 ..
             } => {
+                // This is the balance of the store, which is consistent with the operations, as
+                // dispatchable fungible assets with derived balances are only supported when
+                // they're configured
                 let response = rest_client
                     .view_bcs::<Vec<u64>>(
                         &ViewFunction {
@@ -201,6 +201,18 @@ impl BlockRetriever {
         Ok(BlockInfo::from_block(&block, chain_id))
     }
 
+    /// Retrieves the block containing the version, without its transactions
+    pub async fn get_block_by_version(
+        &self,
+        version: u64,
+    ) -> ApiResult<aptos_rest_client::aptos_api_types::BcsBlock> {
+        Ok(self
+            .rest_client
+            .get_block_by_version_bcs(version, false)
+            .await?
+            .into_inner())
+    }
+
     /// Retrieves the block by height
     pub async fn get_block_by_height(
         &self,
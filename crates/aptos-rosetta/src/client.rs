@@ -11,9 +11,10 @@ use crate::{
         ConstructionParseRequest, ConstructionParseResponse, ConstructionPayloadsRequest,
         ConstructionPayloadsResponse, ConstructionPreprocessRequest,
         ConstructionPreprocessResponse, ConstructionSubmitRequest, ConstructionSubmitResponse,
-        Currency, Error, MetadataRequest, NetworkIdentifier, NetworkListResponse,
-        NetworkOptionsResponse, NetworkRequest, NetworkStatusResponse, Operation,
-        PreprocessMetadata, PublicKey, Signature, SignatureType, TransactionIdentifier,
+        Currency, Error, EventsBlocksRequest, EventsBlocksResponse, MetadataRequest,
+        NetworkIdentifier, NetworkListResponse, NetworkOptionsResponse, NetworkRequest,
+        NetworkStatusResponse, Operation, PreprocessMetadata, PublicKey, SearchTransactionsRequest,
+        SearchTransactionsResponse, Signature, SignatureType, TransactionIdentifier,
         TransactionIdentifierResponse,
     },
 };
@@ -110,6 +111,20 @@ impl RosettaClient {
         self.make_call("construction/submit", request).await
     }
 
+    pub async fn events_blocks(
+        &self,
+        request: &EventsBlocksRequest,
+    ) -> anyhow::Result<EventsBlocksResponse> {
+        self.make_call("events/blocks", request).await
+    }
+
+    pub async fn search_transactions(
+        &self,
+        request: &SearchTransactionsRequest,
+    ) -> anyhow::Result<SearchTransactionsResponse> {
+        self.make_call("search/transactions", request).await
+    }
+
     pub async fn network_list(&self) -> anyhow::Result<NetworkListResponse> {
         self.make_call("network/list", &MetadataRequest {}).await
     }
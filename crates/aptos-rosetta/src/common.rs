@@ -4,14 +4,19 @@
 use crate::{
     error::{ApiError, ApiResult},
     types::{
-        Currency, CurrencyMetadata, MetadataRequest, NetworkIdentifier, PartialBlockIdentifier,
-        APTOS_COIN_MODULE, APTOS_COIN_RESOURCE,
+        Currency, CurrencyMetadata, FungibleAssetMetadata, MetadataRequest, NetworkIdentifier,
+        PartialBlockIdentifier, APTOS_COIN_MODULE, APTOS_COIN_RESOURCE,
+        DISPATCH_FUNCTION_STORE_RESOURCE, FUNGIBLE_ASSET_METADATA_RESOURCE, FUNGIBLE_ASSET_MODULE,
     },
     RosettaContext,
 };
 use aptos_crypto::{ValidCryptoMaterial, ValidCryptoMaterialStringExt};
 use aptos_logger::debug;
-use aptos_rest_client::{Account, Response};
+use aptos_rest_client::{
+    aptos_api_types::{AptosError, AptosErrorCode},
+    error::{AptosErrorResponse, RestError},
+    Account, Response,
+};
 use aptos_sdk::move_types::{
     ident_str,
     language_storage::{StructTag, TypeTag},
@@ -221,6 +226,149 @@ pub fn find_fa_currency(
     }
 }
 
+/// Provides the [Currency] of a fungible asset from its on-chain metadata
+pub fn fa_currency(metadata_address: AccountAddress, metadata: &FungibleAssetMetadata) -> Currency {
+    Currency {
+        symbol: metadata.symbol.clone(),
+        decimals: metadata.decimals,
+        metadata: Some(CurrencyMetadata {
+            move_type: None,
+            fa_address: Some(metadata_address.to_string()),
+        }),
+    }
+}
+
+/// Retrieves the metadata address of a currency that is only a fungible asset, and not a coin
+pub fn fa_only_address(currency: &Currency) -> Option<AccountAddress> {
+    if let Some(CurrencyMetadata {
+        move_type: None,
+        fa_address: Some(ref fa_address),
+    }) = currency.metadata
+    {
+        AccountAddress::from_str(fa_address).ok()
+    } else {
+        None
+    }
+}
+
+/// Checks that a currency provided in a request is supported, without looking anything up
+///
+/// If all fungible assets are supported, any currency that is only a fungible asset is accepted
+/// here, and has to be checked against its on-chain metadata with [verify_currency] when online.
+pub fn is_supported_currency(server_context: &RosettaContext, currency: &Currency) -> bool {
+    server_context.currencies.contains(currency)
+        || (server_context.support_all_fungible_assets && fa_only_address(currency).is_some())
+}
+
+/// Checks that a currency provided in a request is supported, and matches the latest on-chain
+/// metadata if it's a fungible asset that's not configured
+pub async fn verify_currency(
+    server_context: &RosettaContext,
+    currency: &Currency,
+) -> ApiResult<()> {
+    if server_context.currencies.contains(currency) {
+        return Ok(());
+    }
+    if let Some(metadata_address) = fa_only_address(currency) {
+        if resolve_fa_currency(server_context, metadata_address, None)
+            .await?
+            .is_some_and(|resolved| {
+                resolved.symbol == currency.symbol && resolved.decimals == currency.decimals
+            })
+        {
+            return Ok(());
+        }
+    }
+    Err(ApiError::UnsupportedCurrency(Some(currency.symbol.clone())))
+}
+
+/// Finds the [Currency] of the fungible asset with the given metadata address, at the given
+/// version, or the latest one
+///
+/// Configured currencies take precedence.  Otherwise, if all fungible assets are supported, the
+/// currency is built from the on-chain metadata of the fungible asset at that version, as the
+/// metadata can be mutated.
+///
+/// Dispatchable fungible assets are rejected unless they're configured, as their hooks can
+/// withdraw or deposit different amounts than requested, and their balance can be derived
+/// rather than stored, so their operations can't be trusted to add up.
+pub async fn resolve_fa_currency(
+    server_context: &RosettaContext,
+    metadata_address: AccountAddress,
+    version: Option<u64>,
+) -> ApiResult<Option<Currency>> {
+    if let Some(currency) = find_fa_currency(&server_context.currencies, metadata_address) {
+        return Ok(Some(currency));
+    }
+    // Without a full node, there's no metadata to look up
+    if !server_context.support_all_fungible_assets || server_context.rest_client.is_none() {
+        return Ok(None);
+    }
+
+    let rest_client = server_context.rest_client()?;
+    let Some(metadata) = get_fa_resource_bytes(
+        &rest_client,
+        metadata_address,
+        FUNGIBLE_ASSET_METADATA_RESOURCE,
+        version,
+    )
+    .await?
+    else {
+        // Not a fungible asset
+        return Ok(None);
+    };
+    if get_fa_resource_bytes(
+        &rest_client,
+        metadata_address,
+        DISPATCH_FUNCTION_STORE_RESOURCE,
+        version,
+    )
+    .await?
+    .is_some()
+    {
+        debug!(
+            "Dispatchable fungible asset {} is not supported unless configured",
+            metadata_address
+        );
+        return Ok(None);
+    }
+
+    let metadata: FungibleAssetMetadata = bcs::from_bytes(&metadata)
+        .map_err(|_| ApiError::deserialization_failed("FungibleAssetMetadata"))?;
+    Ok(Some(fa_currency(metadata_address, &metadata)))
+}
+
+/// Retrieves a resource of the fungible asset module at the metadata address, if it exists
+async fn get_fa_resource_bytes(
+    rest_client: &aptos_rest_client::Client,
+    metadata_address: AccountAddress,
+    resource: &str,
+    version: Option<u64>,
+) -> ApiResult<Option<Vec<u8>>> {
+    let resource_type = format!("0x1::{}::{}", FUNGIBLE_ASSET_MODULE, resource);
+    let response = if let Some(version) = version {
+        rest_client
+            .get_account_resource_at_version_bytes(metadata_address, &resource_type, version)
+            .await
+    } else {
+        rest_client
+            .get_account_resource_bytes(metadata_address, &resource_type)
+            .await
+    };
+    match response {
+        Ok(response) => Ok(Some(response.into_inner())),
+        Err(RestError::Api(AptosErrorResponse {
+            error:
+                AptosError {
+                    error_code: AptosErrorCode::AccountNotFound | AptosErrorCode::ResourceNotFound,
+                    ..
+                },
+            ..
+        })) => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
 /// Determines which block to pull for the request
 ///
 /// Inputs can give hash, index, or both
@@ -26,8 +26,8 @@
 
 use crate::{
     common::{
-        check_network, decode_bcs, decode_key, encode_bcs, find_fa_currency, get_account,
-        handle_request, native_coin, parse_coin_currency, with_context,
+        check_network, decode_bcs, decode_key, encode_bcs, get_account, handle_request,
+        native_coin, parse_coin_currency, resolve_fa_currency, verify_currency, with_context,
     },
     error::{ApiError, ApiResult},
     types::{InternalOperation, *},
@@ -469,6 +469,11 @@ async fn construction_metadata(
         response.inner().sequence_number
     };
 
+    // Currencies of fungible assets that aren't configured can only be checked online
+    if let InternalOperation::Transfer(ref transfer) = request.options.internal_operation {
+        verify_currency(&server_context, &transfer.currency).await?;
+    }
+
     // We have to cheat the set operator and set voter operations right here
     let internal_operation = fill_in_operator(
         rest_client.as_ref(),
@@ -563,11 +568,17 @@ async fn construction_parse(
                 (AccountAddress::ONE, APTOS_ACCOUNT_MODULE, CREATE_ACCOUNT_FUNCTION) => {
                     parse_create_account_operation(sender, &type_args, &args)?
                 },
-                (AccountAddress::ONE, PRIMARY_FUNGIBLE_STORE_MODULE, TRANSFER_FUNCTION) => {
-                    parse_primary_fa_transfer_operation(&server_context, sender, &type_args, &args)?
+                (AccountAddress::ONE, PRIMARY_FUNGIBLE_STORE_MODULE, TRANSFER_FUNCTION)
+                | (
+                    AccountAddress::ONE,
+                    PRIMARY_FUNGIBLE_STORE_MODULE,
+                    TRANSFER_ASSERT_MINIMUM_DEPOSIT_FUNCTION,
+                ) => {
+                    parse_primary_fa_transfer_operation(&server_context, sender, &type_args, &args)
+                        .await?
                 },
                 (AccountAddress::ONE, FUNGIBLE_ASSET_MODULE, TRANSFER_FUNCTION) => {
-                    parse_fa_transfer_operation(&server_context, sender, &type_args, &args)?
+                    parse_fa_transfer_operation(&server_context, sender, &type_args, &args).await?
                 },
                 (
                     AccountAddress::ONE,
@@ -767,7 +778,11 @@ fn parse_account_transfer_operation(
 }
 
 /// Parses 0x1::primary_fungible_store::transfer(metadata: address, receiver: address, amount: u64)
-fn parse_primary_fa_transfer_operation(
+///
+/// Also parses 0x1::primary_fungible_store::transfer_assert_minimum_deposit, which has the same
+/// leading arguments.  Dispatchable fungible assets are only supported when they're configured,
+/// see [resolve_fa_currency].
+async fn parse_primary_fa_transfer_operation(
     server_context: &RosettaContext,
     sender: AccountAddress,
     type_args: &[TypeTag],
@@ -807,7 +822,7 @@ fn parse_primary_fa_transfer_operation(
 
     // Grab currency accordingly
 
-    let maybe_currency = find_fa_currency(&server_context.currencies, metadata);
+    let maybe_currency = resolve_fa_currency(server_context, metadata, None).await?;
 
     if let Some(currency) = maybe_currency {
         operations.push(Operation::withdraw(
@@ -833,7 +848,7 @@ fn parse_primary_fa_transfer_operation(
 /// Parses 0x1::fungible_asset::transfer(metadata: address, receiver: address, amount: u64)
 ///
 /// This is only for using directly from a store, please prefer using primary fa.
-fn parse_fa_transfer_operation(
+async fn parse_fa_transfer_operation(
     server_context: &RosettaContext,
     sender: AccountAddress,
     type_args: &[TypeTag],
@@ -873,7 +888,7 @@ fn parse_fa_transfer_operation(
 
     // Grab currency accordingly
 
-    let maybe_currency = find_fa_currency(&server_context.currencies, metadata);
+    let maybe_currency = resolve_fa_currency(server_context, metadata, None).await?;
 
     if let Some(currency) = maybe_currency {
         operations.push(Operation::withdraw(
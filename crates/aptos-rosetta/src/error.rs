@@ -34,6 +34,7 @@ pub enum ApiError {
     TransactionParseError(Option<String>),
     InternalError(Option<String>),
     CoinTypeFailedToBeFetched(Option<String>),
+    IndexerNotEnabled,
 
     // Below here are codes directly from the REST API
     AccountNotFound(Option<String>),
@@ -85,6 +86,7 @@ impl ApiError {
             TransactionParseError(None),
             InternalError(None),
             CoinTypeFailedToBeFetched(None),
+            IndexerNotEnabled,
             AccountNotFound(None),
             ResourceNotFound(None),
             ModuleNotFound(None),
@@ -142,6 +144,7 @@ impl ApiError {
             MempoolIsFull(_) => 32,
             CoinTypeFailedToBeFetched(_) => 33,
             StateValueNotFound(_) => 34,
+            IndexerNotEnabled => 35,
         }
     }
 
@@ -201,6 +204,7 @@ impl ApiError {
             ApiError::VmError(_) => "Transaction submission failed due to VM error",
             ApiError::MempoolIsFull(_) => "Mempool is full all accounts",
             ApiError::GasEstimationFailed(_) => "Gas estimation failed",
+            ApiError::IndexerNotEnabled => "This API is unavailable because the node doesn't serve its internal indexer DB",
         }
     }
 
@@ -0,0 +1,451 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Optional Rosetta indexer APIs
+//!
+//! [API Spec](https://www.rosetta-api.org/docs/indexers.html)
+
+use crate::{
+    account::get_sequence_number,
+    common::{check_network, handle_request, with_context, BlockHash},
+    error::{ApiError, ApiResult},
+    types::{
+        BlockEvent, BlockEventType, BlockIdentifier, BlockTransaction, EventsBlocksRequest,
+        EventsBlocksResponse, SearchTransactionsRequest, SearchTransactionsResponse,
+        SubAccountIdentifier, Transaction, COIN_MODULE, COIN_STORE_RESOURCE,
+    },
+    RosettaContext,
+};
+use aptos_crypto::HashValue;
+use aptos_logger::{debug, trace};
+use aptos_rest_client::{
+    aptos_api_types::{AptosError, AptosErrorCode, TransactionData},
+    error::{AptosErrorResponse, RestError},
+    Client,
+};
+use aptos_types::{account_address::AccountAddress, account_config::CoinStoreResourceUntyped};
+use std::{collections::BTreeSet, ops::Range, str::FromStr};
+use warp::Filter;
+
+/// Default and maximum number of transactions searched in one `/search/transactions` call
+const DEFAULT_SEARCH_LIMIT: u64 = 25;
+const MAX_SEARCH_LIMIT: u64 = 100;
+/// Maximum number of events returned in one `/events/blocks` call
+const MAX_EVENTS_LIMIT: u64 = 1000;
+
+pub fn events_blocks_route(
+    server_context: RosettaContext,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("events" / "blocks")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_context(server_context))
+        .and_then(handle_request(events_blocks))
+}
+
+pub fn search_transactions_route(
+    server_context: RosettaContext,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("search" / "transactions")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_context(server_context))
+        .and_then(handle_request(search_transactions))
+}
+
+/// Retrieves the stream of block events, so blocks can be synced without polling
+///
+/// Blocks are final in Aptos, so every block has exactly one `block_added` event, with the
+/// block height as its sequence.  This doesn't need the indexer, as there's nothing to look up.
+///
+/// [API Spec](https://www.rosetta-api.org/docs/EventsApi.html#eventsblocks)
+async fn events_blocks(
+    request: EventsBlocksRequest,
+    server_context: RosettaContext,
+) -> ApiResult<EventsBlocksResponse> {
+    debug!("/events/blocks");
+    trace!(
+        request = ?request,
+        server_context = ?server_context,
+        "/events/blocks",
+    );
+
+    check_network(request.network_identifier, &server_context)?;
+
+    let state = server_context
+        .rest_client()?
+        .get_ledger_information()
+        .await?
+        .into_inner();
+    let heights = block_event_heights(
+        request.offset,
+        request.limit,
+        state.oldest_block_height,
+        state.block_height,
+    )?;
+
+    let events = heights
+        .map(|height| BlockEvent {
+            sequence: height,
+            block_identifier: BlockIdentifier {
+                index: height,
+                hash: BlockHash::new(server_context.chain_id, height).to_string(),
+            },
+            event_type: BlockEventType::BlockAdded,
+        })
+        .collect();
+
+    Ok(EventsBlocksResponse {
+        max_sequence: state.block_height,
+        events,
+    })
+}
+
+/// Heights of the blocks to return events for, starting from the offset if there is one, and
+/// otherwise ending at the latest block
+pub(crate) fn block_event_heights(
+    offset: Option<u64>,
+    limit: Option<u64>,
+    oldest_block_height: u64,
+    latest_block_height: u64,
+) -> ApiResult<Range<u64>> {
+    let limit = limit.unwrap_or(MAX_EVENTS_LIMIT).min(MAX_EVENTS_LIMIT);
+    let end = latest_block_height + 1;
+    let start = match offset {
+        Some(offset) => offset,
+        None => end.saturating_sub(limit).max(oldest_block_height),
+    };
+    if start < oldest_block_height {
+        return Err(ApiError::BlockPruned(Some(format!(
+            "Block {} is pruned, the oldest block is {}",
+            start, oldest_block_height
+        ))));
+    }
+
+    Ok(start..end.min(start.saturating_add(limit)).max(start))
+}
+
+/// Searches for committed transactions
+///
+/// Searches are backed by the node's internal indexer DB, so this isn't supported at all
+/// without it, and results are never silently missing.  Searches go backwards from `offset`,
+/// which is the absolute version to continue from, and defaults to the last version of
+/// `max_block`.  Searching by account looks up the transactions sent by the account, and the
+/// ones emitting deposit or withdraw events on its coin stores.  Searches by neither walk whole
+/// blocks backwards, and are bounded by `limit` transactions.
+///
+/// [API Spec](https://www.rosetta-api.org/docs/SearchApi.html#searchtransactions)
+async fn search_transactions(
+    request: SearchTransactionsRequest,
+    server_context: RosettaContext,
+) -> ApiResult<SearchTransactionsResponse> {
+    debug!("/search/transactions");
+    trace!(
+        request = ?request,
+        server_context = ?server_context,
+        "/search/transactions",
+    );
+
+    check_network(request.network_identifier.clone(), &server_context)?;
+
+    if !server_context.indexer_enabled().await {
+        return Err(ApiError::IndexerNotEnabled);
+    }
+
+    let rest_client = server_context.rest_client()?;
+    let block_cache = server_context.block_cache()?;
+    let chain_id = server_context.chain_id;
+    let filter = SearchFilter::from_request(&request)?;
+    let limit = request
+        .limit
+        .unwrap_or(DEFAULT_SEARCH_LIMIT)
+        .clamp(1, MAX_SEARCH_LIMIT);
+    let state = rest_client.get_ledger_information().await?.into_inner();
+    let max_block = request
+        .max_block
+        .unwrap_or(state.block_height)
+        .min(state.block_height);
+    let max_version = block_cache
+        .get_block_by_height(max_block, false)
+        .await?
+        .last_version
+        .min(state.version);
+    let offset = request
+        .offset
+        .map_or(max_version, |offset| offset.min(max_version));
+
+    let mut transactions = vec![];
+    let next_offset = if let Some(ref transaction_identifier) = request.transaction_identifier {
+        let hash = HashValue::from_str(transaction_identifier.hash.trim_start_matches("0x"))
+            .map_err(|_| ApiError::InvalidInput(Some("Invalid transaction hash".to_string())))?;
+        match rest_client.get_transaction_by_hash_bcs(hash).await {
+            Ok(response) => {
+                // Pending transactions are never in a block, so they can't match
+                if let TransactionData::OnChain(txn) = response.into_inner() {
+                    if txn.version <= offset {
+                        let block = block_cache.get_block_by_version(txn.version).await?;
+                        let transaction =
+                            Transaction::from_transaction(&server_context, txn).await?;
+                        if filter.matches(&transaction) {
+                            transactions.push(BlockTransaction {
+                                block_identifier: BlockIdentifier::from_block(&block, chain_id),
+                                transaction,
+                            });
+                        }
+                    }
+                }
+            },
+            Err(err) => match ApiError::from(err) {
+                ApiError::TransactionNotFound(_) => (),
+                err => return Err(err),
+            },
+        }
+        None
+    } else if let Some(address) = filter.address {
+        let (versions, next_offset) =
+            account_versions(&server_context, &rest_client, address, offset, limit).await?;
+        for version in versions {
+            // Versions at or below the offset are always committed
+            let TransactionData::OnChain(txn) = rest_client
+                .get_transaction_by_version_bcs(version)
+                .await?
+                .into_inner()
+            else {
+                return Err(ApiError::InternalError(Some(format!(
+                    "Transaction at version {} is not committed",
+                    version
+                ))));
+            };
+            let block = block_cache.get_block_by_version(version).await?;
+            let transaction = Transaction::from_transaction(&server_context, txn).await?;
+            if filter.matches(&transaction) {
+                transactions.push(BlockTransaction {
+                    block_identifier: BlockIdentifier::from_block(&block, chain_id),
+                    transaction,
+                });
+            }
+        }
+        next_offset
+    } else {
+        let mut searched_transactions = 0;
+        let mut next_offset = None;
+        let start_height = block_cache.get_block_by_version(offset).await?.block_height;
+        for height in (state.oldest_block_height..=start_height).rev() {
+            let block = block_cache.get_block_by_height(height, true).await?;
+            let block_identifier = BlockIdentifier::from_block(&block, chain_id);
+            for txn in block.transactions.unwrap_or_default() {
+                if txn.version > offset {
+                    continue;
+                }
+                searched_transactions += 1;
+                let transaction = Transaction::from_transaction(&server_context, txn).await?;
+                if filter.matches(&transaction) {
+                    transactions.push(BlockTransaction {
+                        block_identifier: block_identifier.clone(),
+                        transaction,
+                    });
+                }
+            }
+            if searched_transactions >= limit {
+                // Only the genesis block starts at version 0
+                next_offset = (height > state.oldest_block_height).then(|| block.first_version - 1);
+                break;
+            }
+        }
+        next_offset
+    };
+
+    transactions.sort_by_key(|txn| txn.transaction.metadata.version.0);
+    Ok(SearchTransactionsResponse {
+        total_count: transactions.len() as u64,
+        transactions,
+        next_offset,
+    })
+}
+
+/// Versions of up to `limit` of the latest transactions at or below `offset` involving the
+/// account, along with the offset to continue from, if there may be more
+///
+/// These are the transactions sent by the account, and the ones emitting deposit or withdraw
+/// events on its coin stores for the supported coins.  Fungible asset events are emitted by
+/// the store rather than the account, so they aren't indexed by account, and aren't found.
+///
+/// Each source is paged separately, so only the versions down to the lowest version that every
+/// source with more transactions has reached can be returned.  Below that, one of the sources
+/// may have missed transactions.
+async fn account_versions(
+    server_context: &RosettaContext,
+    rest_client: &Client,
+    address: AccountAddress,
+    offset: u64,
+    limit: u64,
+) -> ApiResult<(Vec<u64>, Option<u64>)> {
+    let page_size = limit as u16;
+    // Versions from each source, and whether the source has older ones
+    let mut sources: Vec<(Vec<u64>, bool)> = vec![];
+
+    let sequence_number = get_sequence_number(rest_client, address, offset).await?;
+    if sequence_number > 0 {
+        let start = sequence_number.saturating_sub(limit);
+        let txns = rest_client
+            .get_account_transactions_bcs(address, Some(start), Some(page_size))
+            .await?
+            .into_inner();
+        sources.push((txns.iter().map(|txn| txn.version).collect(), start > 0));
+    }
+
+    for coin_type in server_context
+        .currencies
+        .iter()
+        .filter_map(|currency| currency.metadata.as_ref()?.move_type.as_ref())
+    {
+        let coin_store_type = format!(
+            "0x1::{}::{}<{}>",
+            COIN_MODULE, COIN_STORE_RESOURCE, coin_type
+        );
+        let Some(coin_store) =
+            get_coin_store(rest_client, address, &coin_store_type, offset).await?
+        else {
+            continue;
+        };
+        for (field_name, handle) in [
+            ("deposit_events", coin_store.deposit_events()),
+            ("withdraw_events", coin_store.withdraw_events()),
+        ] {
+            if handle.count() == 0 {
+                continue;
+            }
+            let start = handle.count().saturating_sub(limit);
+            let events = rest_client
+                .get_account_events_bcs(
+                    address,
+                    &coin_store_type,
+                    field_name,
+                    Some(start),
+                    Some(page_size),
+                )
+                .await?
+                .into_inner();
+            sources.push((
+                events
+                    .iter()
+                    .map(|event| event.transaction_version)
+                    .collect(),
+                start > 0,
+            ));
+        }
+    }
+
+    Ok(merge_account_versions(sources, offset, limit))
+}
+
+/// Merges the versions found by each source, see [`account_versions`]
+pub(crate) fn merge_account_versions(
+    sources: Vec<(Vec<u64>, bool)>,
+    offset: u64,
+    limit: u64,
+) -> (Vec<u64>, Option<u64>) {
+    let cutoff = sources
+        .iter()
+        .filter(|(_, has_more)| *has_more)
+        .filter_map(|(versions, _)| versions.iter().min().copied())
+        .max();
+    let candidates: BTreeSet<u64> = sources
+        .into_iter()
+        .flat_map(|(versions, _)| versions)
+        .filter(|version| *version <= offset && cutoff.map_or(true, |cutoff| *version >= cutoff))
+        .collect();
+
+    if candidates.len() as u64 > limit {
+        let versions: Vec<u64> = candidates.into_iter().rev().take(limit as usize).collect();
+        let next_offset = versions.last().and_then(|version| version.checked_sub(1));
+        (versions, next_offset)
+    } else {
+        let next_offset = cutoff.and_then(|cutoff| cutoff.checked_sub(1));
+        (candidates.into_iter().rev().collect(), next_offset)
+    }
+}
+
+/// Retrieves the account's coin store at the version, if it exists
+async fn get_coin_store(
+    rest_client: &Client,
+    address: AccountAddress,
+    coin_store_type: &str,
+    version: u64,
+) -> ApiResult<Option<CoinStoreResourceUntyped>> {
+    match rest_client
+        .get_account_resource_at_version_bcs(address, coin_store_type, version)
+        .await
+    {
+        Ok(response) => Ok(Some(response.into_inner())),
+        Err(RestError::Api(AptosErrorResponse {
+            error:
+                AptosError {
+                    error_code: AptosErrorCode::AccountNotFound | AptosErrorCode::ResourceNotFound,
+                    ..
+                },
+            ..
+        })) => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Filters on the transactions found by a search
+#[derive(Clone, Debug)]
+pub(crate) struct SearchFilter {
+    /// Account an operation must be on
+    address: Option<AccountAddress>,
+    /// Sub account an operation must be on, if there's an account
+    sub_account: Option<SubAccountIdentifier>,
+    /// Type an operation must be of
+    operation_type: Option<String>,
+    /// Whether the transaction must have succeeded
+    success: Option<bool>,
+}
+
+impl SearchFilter {
+    pub(crate) fn from_request(request: &SearchTransactionsRequest) -> ApiResult<SearchFilter> {
+        let address = request
+            .account_identifier
+            .as_ref()
+            .map(|account| account.account_address())
+            .transpose()?;
+        Ok(SearchFilter {
+            address,
+            sub_account: request
+                .account_identifier
+                .as_ref()
+                .and_then(|account| account.sub_account.clone()),
+            operation_type: request.operation_type.clone(),
+            success: request.success,
+        })
+    }
+
+    /// A transaction matches if it has the required status, and any of its operations is on the
+    /// account and of the type, if either is required
+    pub(crate) fn matches(&self, transaction: &Transaction) -> bool {
+        if let Some(success) = self.success {
+            if transaction.metadata.failed == success {
+                return false;
+            }
+        }
+        if self.address.is_none() && self.operation_type.is_none() {
+            return true;
+        }
+
+        transaction.operations.iter().any(|operation| {
+            let account_matches = match (self.address, &operation.account) {
+                (None, _) => true,
+                (Some(address), Some(account)) => {
+                    account.account_address().ok() == Some(address)
+                        && account.sub_account == self.sub_account
+                },
+                (Some(_), None) => false,
+            };
+            let type_matches = self.operation_type.as_ref().map_or(true, |operation_type| {
+                &operation.operation_type == operation_type
+            });
+            account_matches && type_matches
+        })
+    }
+}
@@ -12,11 +12,11 @@ use crate::{
     types::Currency,
 };
 use aptos_config::config::ApiConfig;
-use aptos_logger::debug;
+use aptos_logger::{debug, warn};
 use aptos_types::{account_address::AccountAddress, chain_id::ChainId};
 use aptos_warp_webserver::{logger, Error, WebServer};
 use std::{collections::HashSet, convert::Infallible, sync::Arc};
-use tokio::task::JoinHandle;
+use tokio::{sync::RwLock, task::JoinHandle};
 use warp::{
     http::{HeaderValue, Method, StatusCode},
     reply, Filter, Rejection, Reply,
@@ -25,6 +25,7 @@ use warp::{
 mod account;
 mod block;
 mod construction;
+mod indexer;
 mod network;
 
 pub mod client;
@@ -49,6 +50,11 @@ pub struct RosettaContext {
     pub block_cache: Option<Arc<BlockRetriever>>,
     /// Set of supported currencies
     pub currencies: HashSet<Currency>,
+    /// Whether fungible assets other than the supported currencies are supported, with their
+    /// symbol and decimals looked up from their on-chain metadata
+    pub support_all_fungible_assets: bool,
+    /// Whether the node serves the indexer APIs from its internal indexer DB, looked up once
+    indexer_enabled: Arc<RwLock<Option<bool>>>,
 }
 
 impl RosettaContext {
@@ -57,6 +63,7 @@ impl RosettaContext {
         chain_id: ChainId,
         block_cache: Option<Arc<BlockRetriever>>,
         mut currencies: HashSet<Currency>,
+        support_all_fungible_assets: bool,
     ) -> Self {
         // Always add APT
         currencies.insert(native_coin());
@@ -66,6 +73,8 @@ impl RosettaContext {
             chain_id,
             block_cache,
             currencies,
+            support_all_fungible_assets,
+            indexer_enabled: Arc::new(RwLock::new(None)),
         }
     }
 
@@ -84,6 +93,52 @@ impl RosettaContext {
             Err(ApiError::NodeIsOffline)
         }
     }
+
+    /// Whether the node can serve the indexer APIs, which are backed by the node's internal
+    /// indexer DB.  The node only serves lookups from it with storage sharding enabled, and it
+    /// must index transactions, events, and the translated coin events, for searches by account
+    /// to be complete.
+    ///
+    /// This is always false without a node, and is looked up again if the node can't be reached.
+    async fn indexer_enabled(&self) -> bool {
+        let Some(ref rest_client) = self.rest_client else {
+            return false;
+        };
+        if let Some(enabled) = *self.indexer_enabled.read().await {
+            return enabled;
+        }
+
+        let info = match rest_client.get_node_info().await {
+            Ok(info) => info,
+            Err(err) => {
+                warn!(
+                    "Failed to retrieve node info to check for the indexer: {:?}",
+                    err
+                );
+                return false;
+            },
+        };
+        let sharded = info
+            .get("new_storage_format")
+            .and_then(|value| value.as_bool())
+            .unwrap_or_default();
+        let indexer_config = info.get("internal_indexer_config");
+        let enabled = sharded
+            && [
+                "enable_transaction",
+                "enable_event",
+                "enable_event_v2_translation",
+            ]
+            .iter()
+            .all(|field| {
+                indexer_config
+                    .and_then(|config| config.get(field))
+                    .and_then(|value| value.as_bool())
+                    .unwrap_or_default()
+            });
+        *self.indexer_enabled.write().await = Some(enabled);
+        enabled
+    }
 }
 
 /// Creates HTTP server (warp-based) for Rosetta
@@ -92,6 +147,7 @@ pub fn bootstrap(
     api_config: ApiConfig,
     rest_client: Option<aptos_rest_client::Client>,
     supported_currencies: HashSet<Currency>,
+    support_all_fungible_assets: bool,
 ) -> anyhow::Result<tokio::runtime::Runtime> {
     let runtime = aptos_runtimes::spawn_named_runtime("rosetta".into(), None);
 
@@ -102,6 +158,7 @@ pub fn bootstrap(
         api_config,
         rest_client,
         supported_currencies,
+        support_all_fungible_assets,
     ));
     Ok(runtime)
 }
@@ -112,6 +169,7 @@ pub async fn bootstrap_async(
     api_config: ApiConfig,
     rest_client: Option<aptos_rest_client::Client>,
     supported_currencies: HashSet<Currency>,
+    support_all_fungible_assets: bool,
 ) -> anyhow::Result<JoinHandle<()>> {
     debug!("Starting up Rosetta server with {:?}", api_config);
 
@@ -146,6 +204,7 @@ pub async fn bootstrap_async(
             chain_id,
             block_cache,
             supported_currencies,
+            support_all_fungible_assets,
         )
         .await;
         api.serve(routes(context)).await;
@@ -167,6 +226,8 @@ pub fn routes(
         .or(construction::payloads_route(context.clone()))
         .or(construction::preprocess_route(context.clone()))
         .or(construction::submit_route(context.clone()))
+        .or(indexer::events_blocks_route(context.clone()))
+        .or(indexer::search_transactions_route(context.clone()))
         .or(network::list_route(context.clone()))
         .or(network::options_route(context.clone()))
         .or(network::status_route(context.clone()))
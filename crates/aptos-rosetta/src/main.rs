@@ -94,6 +94,7 @@ async fn main() {
         args.api_config(),
         args.rest_client(),
         args.supported_currencies(),
+        args.support_all_fungible_assets(),
     )
     .expect("aptos-rosetta: Should bootstrap rosetta server");
 
@@ -118,6 +119,9 @@ trait ServerArgs {
 
     /// Supported currencies for the service
     fn supported_currencies(&self) -> HashSet<Currency>;
+
+    /// Whether fungible assets other than the supported currencies are supported
+    fn support_all_fungible_assets(&self) -> bool;
 }
 
 /// Aptos Rosetta API Server
@@ -166,6 +170,14 @@ impl ServerArgs for CommandArgs {
             CommandArgs::Online(args) => args.supported_currencies(),
         }
     }
+
+    fn support_all_fungible_assets(&self) -> bool {
+        match self {
+            CommandArgs::OnlineRemote(args) => args.support_all_fungible_assets(),
+            CommandArgs::Offline(args) => args.support_all_fungible_assets(),
+            CommandArgs::Online(args) => args.support_all_fungible_assets(),
+        }
+    }
 }
 
 #[derive(Debug, Parser)]
@@ -215,6 +227,13 @@ pub struct OfflineArgs {
     /// ```
     #[clap(long)]
     currency_config_file: Option<PathBuf>,
+    /// Support any fungible asset by its metadata address, not only the currencies above
+    ///
+    /// The symbol and decimals of these fungible assets are looked up from their on-chain
+    /// metadata, which requires the server to be online.  Offline, only the metadata address
+    /// of such currencies is checked.
+    #[clap(long)]
+    support_all_fungible_assets: bool,
 }
 
 impl ServerArgs for OfflineArgs {
@@ -271,6 +290,10 @@ impl ServerArgs for OfflineArgs {
 
         supported_currencies
     }
+
+    fn support_all_fungible_assets(&self) -> bool {
+        self.support_all_fungible_assets
+    }
 }
 
 #[derive(Debug, Parser)]
@@ -301,6 +324,10 @@ impl ServerArgs for OnlineRemoteArgs {
     fn supported_currencies(&self) -> HashSet<Currency> {
         self.offline_args.supported_currencies()
     }
+
+    fn support_all_fungible_assets(&self) -> bool {
+        self.offline_args.support_all_fungible_assets
+    }
 }
 
 #[derive(Debug, Parser)]
@@ -329,6 +356,10 @@ impl ServerArgs for OnlineLocalArgs {
     fn supported_currencies(&self) -> HashSet<Currency> {
         self.online_args.offline_args.supported_currencies()
     }
+
+    fn support_all_fungible_assets(&self) -> bool {
+        self.online_args.offline_args.support_all_fungible_assets
+    }
 }
 
 #[test]
@@ -2,10 +2,13 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    common::native_coin,
+    common::{is_supported_currency, native_coin},
+    error::ApiError,
+    indexer::{block_event_heights, merge_account_versions, SearchFilter},
     types::{
-        Currency, CurrencyMetadata, OperationType, Transaction, FUNGIBLE_ASSET_MODULE,
-        FUNGIBLE_STORE_RESOURCE, OBJECT_CORE_RESOURCE, OBJECT_MODULE, OBJECT_RESOURCE_GROUP,
+        AccountIdentifier, Currency, CurrencyMetadata, OperationType, SearchTransactionsRequest,
+        Transaction, FUNGIBLE_ASSET_MODULE, FUNGIBLE_STORE_RESOURCE, OBJECT_CORE_RESOURCE,
+        OBJECT_MODULE, OBJECT_RESOURCE_GROUP,
     },
     RosettaContext,
 };
@@ -48,7 +51,7 @@ async fn test_rosetta_context() -> RosettaContext {
     let mut currencies = HashSet::new();
     currencies.insert(OTHER_CURRENCY.clone());
 
-    RosettaContext::new(None, ChainId::test(), None, currencies).await
+    RosettaContext::new(None, ChainId::test(), None, currencies, false).await
 }
 
 fn test_transaction(
@@ -444,3 +447,192 @@ async fn test_fa_transfer_other_currency() {
     assert_eq!(operation_3.amount.as_ref().unwrap().currency, native_coin());
     // TODO: Check fee
 }
+
+#[tokio::test]
+async fn test_fa_transfer_unknown_currency() {
+    // Without a full node, fungible assets that aren't configured can't be looked up
+    let mut context = test_rosetta_context().await;
+    context.support_all_fungible_assets = true;
+
+    let version = 0;
+    let amount = 100;
+    let sender = AccountAddress::random();
+    let receiver = AccountAddress::random();
+    let (changes, events) = transfer_fa_output(
+        sender,
+        AccountAddress::random(),
+        AccountAddress::random(),
+        amount * 2,
+        receiver,
+        AccountAddress::random(),
+        0,
+        amount,
+    );
+    let input = test_transaction(sender, version, changes, events);
+
+    let result = Transaction::from_transaction(&context, input).await;
+    let expected_txn = result.expect("Must succeed");
+    assert_eq!(1, expected_txn.operations.len(), "Ops: {:#?}", expected_txn);
+    let operation = expected_txn.operations.first().unwrap();
+    assert_eq!(operation.operation_type, OperationType::Fee.to_string());
+}
+
+#[tokio::test]
+async fn test_supported_currencies() {
+    let mut context = test_rosetta_context().await;
+    let unknown_fa = Currency {
+        symbol: "UNK".to_string(),
+        decimals: 6,
+        metadata: Some(CurrencyMetadata {
+            move_type: None,
+            fa_address: Some(AccountAddress::random().to_string()),
+        }),
+    };
+    let invalid_fa = Currency {
+        symbol: "INV".to_string(),
+        decimals: 6,
+        metadata: Some(CurrencyMetadata {
+            move_type: None,
+            fa_address: Some("not an address".to_string()),
+        }),
+    };
+
+    assert!(is_supported_currency(&context, &native_coin()));
+    assert!(is_supported_currency(&context, &OTHER_CURRENCY));
+    assert!(!is_supported_currency(&context, &unknown_fa));
+    assert!(!is_supported_currency(&context, &invalid_fa));
+
+    context.support_all_fungible_assets = true;
+    assert!(is_supported_currency(&context, &unknown_fa));
+    assert!(!is_supported_currency(&context, &invalid_fa));
+}
+
+#[tokio::test]
+async fn test_search_filter() {
+    let context = test_rosetta_context().await;
+
+    let amount = 100;
+    let sender = AccountAddress::random();
+    let receiver = AccountAddress::random();
+    let (changes, events) = transfer_fa_output(
+        sender,
+        APT_ADDRESS,
+        AccountAddress::random(),
+        amount * 2,
+        receiver,
+        AccountAddress::random(),
+        0,
+        amount,
+    );
+    let input = test_transaction(sender, 0, changes, events);
+    let transaction = Transaction::from_transaction(&context, input)
+        .await
+        .expect("Must succeed");
+
+    let search = |account: Option<AccountAddress>,
+                  operation_type: Option<OperationType>,
+                  success: Option<bool>| {
+        let request = SearchTransactionsRequest {
+            network_identifier: ChainId::test().into(),
+            max_block: None,
+            offset: None,
+            limit: None,
+            transaction_identifier: None,
+            account_identifier: account.map(AccountIdentifier::base_account),
+            operation_type: operation_type.map(|operation_type| operation_type.to_string()),
+            success,
+        };
+        SearchFilter::from_request(&request)
+            .unwrap()
+            .matches(&transaction)
+    };
+
+    assert!(search(None, None, None));
+    assert!(search(None, None, Some(true)));
+    assert!(!search(None, None, Some(false)));
+    assert!(search(Some(sender), None, None));
+    assert!(search(Some(receiver), None, None));
+    assert!(!search(Some(AccountAddress::random()), None, None));
+    assert!(search(None, Some(OperationType::Deposit), None));
+    assert!(!search(None, Some(OperationType::CreateAccount), None));
+    // The account and type must match on the same operation
+    assert!(search(Some(sender), Some(OperationType::Withdraw), None));
+    assert!(!search(Some(sender), Some(OperationType::Deposit), None));
+    assert!(search(
+        Some(receiver),
+        Some(OperationType::Deposit),
+        Some(true)
+    ));
+}
+
+#[test]
+fn test_block_event_heights() {
+    // From the offset, capped at the latest block
+    assert_eq!(
+        block_event_heights(Some(5), Some(10), 0, 100).unwrap(),
+        5..15
+    );
+    assert_eq!(
+        block_event_heights(Some(95), Some(10), 0, 100).unwrap(),
+        95..101
+    );
+    assert_eq!(
+        block_event_heights(Some(200), Some(10), 0, 100).unwrap(),
+        200..200
+    );
+
+    // Without an offset, the latest blocks, capped at the oldest block
+    assert_eq!(
+        block_event_heights(None, Some(10), 0, 100).unwrap(),
+        91..101
+    );
+    assert_eq!(
+        block_event_heights(None, Some(10), 95, 100).unwrap(),
+        95..101
+    );
+    assert_eq!(block_event_heights(None, None, 0, 100).unwrap(), 0..101);
+
+    // Pruned blocks can't be returned
+    assert!(matches!(
+        block_event_heights(Some(5), Some(10), 10, 100),
+        Err(ApiError::BlockPruned(_))
+    ));
+}
+
+#[test]
+fn test_merge_account_versions() {
+    // Everything is returned, newest first, when no source has more
+    assert_eq!(
+        merge_account_versions(vec![(vec![3, 7], false), (vec![5, 7], false)], 10, 10),
+        (vec![7, 5, 3], None)
+    );
+
+    // Versions above the offset are never returned
+    assert_eq!(
+        merge_account_versions(vec![(vec![3, 7, 12], false)], 10, 10),
+        (vec![7, 3], None)
+    );
+
+    // Only versions down to where every source with more has reached are returned
+    assert_eq!(
+        merge_account_versions(vec![(vec![6, 8], true), (vec![2, 4, 9], false)], 10, 10),
+        (vec![9, 8, 6], Some(5))
+    );
+    assert_eq!(
+        merge_account_versions(vec![(vec![6, 8], true), (vec![4, 9], true)], 10, 10),
+        (vec![9, 8, 6], Some(5))
+    );
+
+    // The limit applies across sources
+    assert_eq!(
+        merge_account_versions(vec![(vec![1, 2], false), (vec![3, 4], false)], 10, 3),
+        (vec![4, 3, 2], Some(1))
+    );
+
+    // Nothing is left below version 0
+    assert_eq!(
+        merge_account_versions(vec![(vec![0, 1], true)], 10, 10),
+        (vec![1, 0], None)
+    );
+    assert_eq!(merge_account_versions(vec![], 10, 10), (vec![], None));
+}
@@ -29,6 +29,8 @@ pub const STAKE_POOL_RESOURCE: &str = "StakePool";
 pub const STAKING_CONTRACT_RESOURCE: &str = "StakingContract";
 pub const STORE_RESOURCE: &str = "Store";
 pub const FUNGIBLE_STORE_RESOURCE: &str = "FungibleStore";
+pub const FUNGIBLE_ASSET_METADATA_RESOURCE: &str = "Metadata";
+pub const DISPATCH_FUNCTION_STORE_RESOURCE: &str = "DispatchFunctionStore";
 pub const STAKING_GROUP_UPDATE_COMMISSION_RESOURCE: &str = "StakingGroupUpdateCommissionEvent";
 pub const VESTING_RESOURCE: &str = "Vesting";
 pub const DELEGATION_POOL_RESOURCE: &str = "DelegationPool";
@@ -40,6 +42,7 @@ pub const OBJECT_RESOURCE_GROUP: &str = "ObjectGroup";
 pub const CREATE_ACCOUNT_FUNCTION: &str = "create_account";
 pub const TRANSFER_FUNCTION: &str = "transfer";
 pub const TRANSFER_COINS_FUNCTION: &str = "transfer_coins";
+pub const TRANSFER_ASSERT_MINIMUM_DEPOSIT_FUNCTION: &str = "transfer_assert_minimum_deposit";
 pub const BALANCE_FUNCTION: &str = "balance";
 
 // Staking Contract
@@ -66,6 +69,16 @@ pub const SET_OPERATOR_EVENTS_FIELD: &str = "set_operator_events";
 pub const SEQUENCE_NUMBER_FIELD: &str = "sequence_number";
 pub const SYMBOL_FIELD: &str = "symbol";
 
+// Fungible Asset
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FungibleAssetMetadata {
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u8,
+    pub icon_uri: String,
+    pub project_uri: String,
+}
+
 // Staking Contract
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StakingContract {
@@ -6,7 +6,7 @@
 //! [Spec](https://www.rosetta-api.org/docs/api_objects.html)
 
 use crate::{
-    common::{find_coin_currency, find_fa_currency, native_coin},
+    common::{find_coin_currency, is_supported_currency, native_coin, resolve_fa_currency},
     construction::{
         parse_create_stake_pool_operation, parse_delegation_pool_add_stake_operation,
         parse_delegation_pool_unlock_operation, parse_delegation_pool_withdraw_operation,
@@ -51,7 +51,7 @@ use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 use std::{
     cmp::Ordering,
-    collections::{BTreeMap, HashMap, HashSet},
+    collections::{BTreeMap, HashMap},
     convert::TryFrom,
     fmt::{Display, Formatter},
     hash::Hash,
@@ -140,6 +140,30 @@ pub struct Block {
     pub transactions: Vec<Transaction>,
 }
 
+/// An event in the stream of blocks, used to sync blocks without polling `/network/status`
+///
+/// [API Spec](https://www.rosetta-api.org/docs/models/BlockEvent.html)
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct BlockEvent {
+    /// Position in the stream of events.  In Aptos, this is the block height
+    pub sequence: u64,
+    /// Block added or removed
+    pub block_identifier: BlockIdentifier,
+    /// Whether the block was added or removed
+    #[serde(rename = "type")]
+    pub event_type: BlockEventType,
+}
+
+/// Type of a [`BlockEvent`].  Blocks are final in Aptos, so they're never removed
+///
+/// [API Spec](https://www.rosetta-api.org/docs/models/BlockEventType.html)
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockEventType {
+    BlockAdded,
+    BlockRemoved,
+}
+
 /// A combination of a transaction and the block associated.  In Aptos, this is just the same
 /// as the version associated with the transaction
 ///
@@ -147,9 +171,9 @@ pub struct Block {
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct BlockTransaction {
     /// Block associated with transaction
-    block_identifier: BlockIdentifier,
+    pub block_identifier: BlockIdentifier,
     /// Transaction associated with block
-    transaction: Transaction,
+    pub transaction: Transaction,
 }
 
 /// Currency represented as atomic units including decimals
@@ -931,22 +955,31 @@ impl Transaction {
         let mut operation_index: u64 = 0;
         if successful {
             let mut object_to_owner = HashMap::new();
-            let mut store_to_currency = HashMap::new();
+            let mut store_to_metadata = HashMap::new();
             let mut framework_changes = vec![];
             // Not the most efficient, parse all store owners, and assets associated with stores
             for (state_key, write_op) in &txn.changes {
                 let new_changes = preprocess_write_set(
-                    server_context,
                     state_key,
                     write_op,
                     maybe_user_txn.map(|inner| inner.payload()),
                     txn.version,
                     &mut object_to_owner,
-                    &mut store_to_currency,
+                    &mut store_to_metadata,
                 );
                 framework_changes.extend(new_changes);
             }
 
+            // Find the currencies of the stores, which may have to be looked up on-chain
+            let mut store_to_currency = HashMap::new();
+            for (store, metadata_address) in store_to_metadata {
+                if let Some(currency) =
+                    resolve_fa_currency(server_context, metadata_address, Some(txn.version)).await?
+                {
+                    store_to_currency.insert(store, currency);
+                }
+            }
+
             // Parse all operations from the writeset changes in a success
             for (struct_tag, account_address, data) in &framework_changes {
                 let mut ops = parse_operations_from_write_set(
@@ -985,11 +1018,13 @@ impl Transaction {
             // Parse all failed operations from the payload
             if let Some(user_txn) = maybe_user_txn {
                 let mut ops = parse_failed_operations_from_txn_payload(
-                    &server_context.currencies,
+                    server_context,
+                    txn.version,
                     operation_index,
                     user_txn.sender(),
                     user_txn.payload(),
-                );
+                )
+                .await?;
                 operation_index += ops.len() as u64;
                 operations.append(&mut ops);
             }
@@ -1031,12 +1066,14 @@ impl Transaction {
 ///
 /// This case only occurs if the transaction failed, and that's because it's less accurate
 /// than just following the state changes
-fn parse_failed_operations_from_txn_payload(
-    currencies: &HashSet<Currency>,
+async fn parse_failed_operations_from_txn_payload(
+    server_context: &RosettaContext,
+    version: u64,
     operation_index: u64,
     sender: AccountAddress,
     payload: &TransactionPayload,
-) -> Vec<Operation> {
+) -> ApiResult<Vec<Operation>> {
+    let currencies = &server_context.currencies;
     let mut operations = vec![];
     if let TransactionPayload::EntryFunction(inner) = payload {
         match (
@@ -1070,15 +1107,21 @@ fn parse_failed_operations_from_txn_payload(
                     operation_index,
                 )
             },
-            (AccountAddress::ONE, PRIMARY_FUNGIBLE_STORE_MODULE, TRANSFER_FUNCTION) => {
+            (AccountAddress::ONE, PRIMARY_FUNGIBLE_STORE_MODULE, TRANSFER_FUNCTION)
+            | (
+                AccountAddress::ONE,
+                PRIMARY_FUNGIBLE_STORE_MODULE,
+                TRANSFER_ASSERT_MINIMUM_DEPOSIT_FUNCTION,
+            ) => {
                 // Primary transfer has the same interface as coin transfer, but it's a metadata address instead of a coin type generic
                 let maybe_metadata_address = inner
                     .args()
                     .first()
                     .map(|encoded| bcs::from_bytes::<AccountAddress>(encoded));
                 if let Some(Ok(addr)) = maybe_metadata_address {
-                    // Find currency from type tag
-                    let maybe_currency = find_fa_currency(currencies, addr);
+                    // Find currency from the metadata address, as of the transaction
+                    let maybe_currency =
+                        resolve_fa_currency(server_context, addr, Some(version)).await?;
 
                     if let Some(currency) = maybe_currency {
                         operations = parse_primary_fa_transfer_from_txn_payload(
@@ -1230,7 +1273,7 @@ fn parse_failed_operations_from_txn_payload(
             },
         }
     }
-    operations
+    Ok(operations)
 }
 
 /// Parses a 0x1::coin::transfer to a Withdraw and Deposit
@@ -1434,13 +1477,12 @@ fn parse_write_set<'a>(
 }
 
 fn preprocess_write_set<'a>(
-    server_context: &RosettaContext,
     state_key: &'a StateKey,
     write_op: &'a WriteOp,
     _maybe_payload: Option<&TransactionPayload>,
     version: u64,
     object_to_owner: &mut HashMap<AccountAddress, AccountAddress>,
-    store_to_currency: &mut HashMap<AccountAddress, Currency>,
+    store_to_metadata: &mut HashMap<AccountAddress, AccountAddress>,
 ) -> Vec<(StructTag, AccountAddress, Vec<u8>)> {
     let write_set_data = parse_write_set(state_key, write_op);
     if write_set_data.is_none() {
@@ -1479,13 +1521,7 @@ fn preprocess_write_set<'a>(
                         parse_object_owner(address, bytes, object_to_owner);
                     },
                     (AccountAddress::ONE, FUNGIBLE_ASSET_MODULE, FUNGIBLE_STORE_RESOURCE) => {
-                        parse_fungible_store_metadata(
-                            &server_context.currencies,
-                            version,
-                            address,
-                            bytes,
-                            store_to_currency,
-                        );
+                        parse_fungible_store_metadata(version, address, bytes, store_to_metadata);
                     },
                     _ => {},
                 }
@@ -2066,11 +2102,10 @@ fn parse_coinstore_changes(
 }
 
 fn parse_fungible_store_metadata(
-    currencies: &HashSet<Currency>,
     version: u64,
     address: AccountAddress,
     data: &[u8],
-    store_to_currency: &mut HashMap<AccountAddress, Currency>,
+    store_to_metadata: &mut HashMap<AccountAddress, AccountAddress>,
 ) {
     let fungible_store: FungibleStoreResource = if let Ok(fungible_store) = bcs::from_bytes(data) {
         fungible_store
@@ -2084,11 +2119,7 @@ fn parse_fungible_store_metadata(
         return;
     };
 
-    let metadata_address = fungible_store.metadata();
-    let maybe_currency = find_fa_currency(currencies, metadata_address);
-    if let Some(currency) = maybe_currency {
-        store_to_currency.insert(address, currency);
-    }
+    store_to_metadata.insert(address, fungible_store.metadata());
 }
 
 /// Parses fungible store direct changes, for withdraws and deposits
@@ -2827,10 +2858,7 @@ impl Transfer {
         }
 
         // Check that the currency is supported
-        if !server_context
-            .currencies
-            .contains(&withdraw_amount.currency)
-        {
+        if !is_supported_currency(server_context, &withdraw_amount.currency) {
             return Err(ApiError::UnsupportedCurrency(Some(
                 withdraw_amount.currency.symbol.clone(),
             )));
@@ -3,9 +3,10 @@
 
 use crate::{
     types::{
-        AccountIdentifier, Allow, Amount, Block, BlockIdentifier, Currency, InternalOperation,
-        NetworkIdentifier, Operation, PartialBlockIdentifier, Peer, PublicKey, Signature,
-        SigningPayload, SyncStatus, Transaction, TransactionIdentifier, Version,
+        AccountIdentifier, Allow, Amount, Block, BlockEvent, BlockIdentifier, BlockTransaction,
+        Currency, InternalOperation, NetworkIdentifier, Operation, PartialBlockIdentifier, Peer,
+        PublicKey, Signature, SigningPayload, SyncStatus, Transaction, TransactionIdentifier,
+        Version,
     },
     AccountAddress, ApiError,
 };
@@ -468,6 +469,32 @@ pub struct ConstructionSubmitResponse {
     pub transaction_identifier: TransactionIdentifier,
 }
 
+/// Request for block events, either from an offset or backwards from the latest block
+///
+/// [API Spec](https://www.rosetta-api.org/docs/models/EventsBlocksRequest.html)
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct EventsBlocksRequest {
+    /// Network identifier describing the blockchain and the chain id
+    pub network_identifier: NetworkIdentifier,
+    /// Sequence of the first event to return.  If not present, the last `limit` events are returned
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<u64>,
+    /// Maximum number of events to return
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u64>,
+}
+
+/// Response with block events in order of sequence
+///
+/// [API Spec](https://www.rosetta-api.org/docs/models/EventsBlocksResponse.html)
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct EventsBlocksResponse {
+    /// Sequence of the latest event
+    pub max_sequence: u64,
+    /// Block events
+    pub events: Vec<BlockEvent>,
+}
+
 /// Request for all transactions in mempool
 ///
 /// [API Spec](https://www.rosetta-api.org/docs/models/MempoolRequest.html)
@@ -560,6 +587,54 @@ pub struct NetworkStatusResponse {
     pub peers: Vec<Peer>,
 }
 
+/// Request to search for committed transactions.  All provided filters must match.
+///
+/// A search is either by transaction hash, by account, or by walking blocks backwards.  Every
+/// search goes backwards from `offset`, the absolute version to continue from, which defaults
+/// to the last version of `max_block`.
+///
+/// [API Spec](https://www.rosetta-api.org/docs/models/SearchTransactionsRequest.html)
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SearchTransactionsRequest {
+    /// Network identifier describing the blockchain and the chain id
+    pub network_identifier: NetworkIdentifier,
+    /// Highest block to search, defaults to the latest block
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_block: Option<u64>,
+    /// Highest version to search, as returned by `next_offset` to continue a search
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<u64>,
+    /// Maximum number of transactions to search
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u64>,
+    /// Hash of the transaction
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transaction_identifier: Option<TransactionIdentifier>,
+    /// Account which an operation must be on
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account_identifier: Option<AccountIdentifier>,
+    /// Type which an operation must be of
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub operation_type: Option<String>,
+    /// Whether the transaction must have succeeded or failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub success: Option<bool>,
+}
+
+/// Response with the transactions matching a search, ordered by version
+///
+/// [API Spec](https://www.rosetta-api.org/docs/models/SearchTransactionsResponse.html)
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SearchTransactionsResponse {
+    /// Transactions matching the search
+    pub transactions: Vec<BlockTransaction>,
+    /// Number of transactions returned
+    pub total_count: u64,
+    /// Version to continue the search from, if there may be older transactions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_offset: Option<u64>,
+}
+
 /// Response with a transaction that was hashed or submitted
 ///
 /// [API Spec](https://www.rosetta-api.org/docs/models/TransactionIdentifierResponse.html)
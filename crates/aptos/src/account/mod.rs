@@ -11,6 +11,7 @@ pub mod derive_resource_account;
 pub mod fund;
 pub mod key_rotation;
 pub mod list;
+pub mod multikey;
 pub mod multisig_account;
 pub mod transfer;
 
@@ -27,6 +28,8 @@ pub enum AccountTool {
     Balance(balance::Balance),
     List(list::ListAccount),
     LookupAddress(key_rotation::LookupAddress),
+    #[clap(subcommand)]
+    Multikey(MultikeyAccountTool),
     RotateKey(key_rotation::RotateKey),
     Transfer(transfer::TransferCoins),
 }
@@ -41,12 +44,36 @@ impl AccountTool {
             AccountTool::Balance(tool) => tool.execute_serialized().await,
             AccountTool::List(tool) => tool.execute_serialized().await,
             AccountTool::LookupAddress(tool) => tool.execute_serialized().await,
+            AccountTool::Multikey(tool) => tool.execute().await,
             AccountTool::RotateKey(tool) => tool.execute_serialized().await,
             AccountTool::Transfer(tool) => tool.execute_serialized().await,
         }
     }
 }
 
+/// Tool for accounts authenticated by several keys
+///
+/// Transactions of these accounts are signed offline: a transaction is saved to a file, each
+/// signer adds their signature to it, and it is submitted once enough signatures are collected.
+#[derive(Debug, Subcommand)]
+pub enum MultikeyAccountTool {
+    Create(multikey::Create),
+    CreateTransaction(multikey::CreateTransaction),
+    Sign(multikey::Sign),
+    Submit(multikey::Submit),
+}
+
+impl MultikeyAccountTool {
+    pub async fn execute(self) -> CliResult {
+        match self {
+            MultikeyAccountTool::Create(tool) => tool.execute_serialized().await,
+            MultikeyAccountTool::CreateTransaction(tool) => tool.execute_serialized().await,
+            MultikeyAccountTool::Sign(tool) => tool.execute_serialized().await,
+            MultikeyAccountTool::Submit(tool) => tool.execute_serialized().await,
+        }
+    }
+}
+
 /// Tool for interacting with multisig accounts
 #[derive(Debug, Subcommand)]
 pub enum MultisigAccountTool {
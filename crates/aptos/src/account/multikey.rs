@@ -0,0 +1,606 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Commands for accounts authenticated by several Ed25519 keys, either with the legacy
+//! `MultiEd25519` scheme or the `MultiKey` scheme.
+//!
+//! Signing happens offline: a transaction is first written to a file, each signer then adds their
+//! signature to the file, and once enough signatures are collected the transaction is submitted.
+
+use crate::common::{
+    types::{
+        CliCommand, CliError, CliTypedResult, EncodingOptions, EntryFunctionArguments,
+        PrivateKeyInputOptions, ProfileOptions, PromptOptions, RestOptions, TransactionOptions,
+        TransactionSummary,
+    },
+    utils::{
+        check_if_file_exists, get_account_with_state, parse_json_file, prompt_yes_with_override,
+        write_to_file,
+    },
+};
+use aptos_cached_packages::aptos_stdlib;
+use aptos_crypto::{
+    ed25519::{Ed25519PublicKey, Ed25519Signature},
+    multi_ed25519::{MultiEd25519PublicKey, MultiEd25519Signature},
+    PrivateKey, SigningKey, ValidCryptoMaterialStringExt,
+};
+use aptos_rest_client::aptos_api_types::HexEncodedBytes;
+use aptos_sdk::transaction_builder::TransactionFactory;
+use aptos_types::{
+    account_address::AccountAddress,
+    chain_id::ChainId,
+    transaction::{
+        authenticator::{
+            AccountAuthenticator, AnyPublicKey, AnySignature, AuthenticationKey, MultiKey,
+            MultiKeyAuthenticator,
+        },
+        EntryFunction, RawTransaction, SignedTransaction, TransactionPayload,
+    },
+};
+use async_trait::async_trait;
+use clap::{Parser, ValueEnum};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Default number of seconds before a multikey transaction expires, leaving time to collect the
+/// signatures offline
+const DEFAULT_MULTIKEY_EXPIRATION_SECS: u64 = 3600;
+
+/// Authentication scheme of a multikey account
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize, ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum MultikeyScheme {
+    /// The legacy scheme, limited to Ed25519 keys
+    MultiEd25519,
+    /// The scheme of `SingleSender` transactions, which accepts any key type
+    MultiKey,
+}
+
+/// The public keys of a multikey account and how many of them must sign
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct MultikeyAccount {
+    pub scheme: MultikeyScheme,
+    /// The public keys, in order. The index of a key is the index of its signatures.
+    pub public_keys: Vec<Ed25519PublicKey>,
+    pub signatures_required: u8,
+}
+
+impl MultikeyAccount {
+    pub fn new(
+        scheme: MultikeyScheme,
+        public_keys: Vec<Ed25519PublicKey>,
+        signatures_required: u8,
+    ) -> CliTypedResult<Self> {
+        let account = Self {
+            scheme,
+            public_keys,
+            signatures_required,
+        };
+        // Building the authentication key validates the keys and the threshold
+        account.authentication_key()?;
+        Ok(account)
+    }
+
+    pub fn authentication_key(&self) -> CliTypedResult<AuthenticationKey> {
+        Ok(match self.scheme {
+            MultikeyScheme::MultiEd25519 => {
+                AuthenticationKey::multi_ed25519(&self.multi_ed25519_public_key()?)
+            },
+            MultikeyScheme::MultiKey => AuthenticationKey::multi_key(self.multi_key()?),
+        })
+    }
+
+    pub fn address(&self) -> CliTypedResult<AccountAddress> {
+        Ok(self.authentication_key()?.account_address())
+    }
+
+    /// Returns the index of the given key, i.e., the index of its signatures
+    pub fn key_index(&self, public_key: &Ed25519PublicKey) -> Option<u8> {
+        self.public_keys
+            .iter()
+            .position(|key| key == public_key)
+            .map(|index| index as u8)
+    }
+
+    /// Assembles the signatures into a signed transaction
+    pub fn sign_transaction(
+        &self,
+        raw_txn: RawTransaction,
+        signatures: &BTreeMap<u8, Ed25519Signature>,
+    ) -> CliTypedResult<SignedTransaction> {
+        if signatures.len() < self.signatures_required as usize {
+            return Err(CliError::CommandArgumentError(format!(
+                "Only {} of the {} required signatures were collected",
+                signatures.len(),
+                self.signatures_required
+            )));
+        }
+
+        let signed_txn = match self.scheme {
+            MultikeyScheme::MultiEd25519 => {
+                let signature = MultiEd25519Signature::new(
+                    signatures
+                        .iter()
+                        .map(|(index, signature)| (signature.clone(), *index))
+                        .collect(),
+                )?;
+                SignedTransaction::new_multisig(
+                    raw_txn,
+                    self.multi_ed25519_public_key()?,
+                    signature,
+                )
+            },
+            MultikeyScheme::MultiKey => {
+                let authenticator = MultiKeyAuthenticator::new(
+                    self.multi_key()?,
+                    signatures
+                        .iter()
+                        .map(|(index, signature)| {
+                            (*index, AnySignature::ed25519(signature.clone()))
+                        })
+                        .collect(),
+                )?;
+                SignedTransaction::new_single_sender(
+                    raw_txn,
+                    AccountAuthenticator::multi_key(authenticator),
+                )
+            },
+        };
+        signed_txn.verify_signature().map_err(|err| {
+            CliError::CommandArgumentError(format!("Invalid signatures: {}", err))
+        })?;
+        Ok(signed_txn)
+    }
+
+    fn multi_ed25519_public_key(&self) -> CliTypedResult<MultiEd25519PublicKey> {
+        Ok(MultiEd25519PublicKey::new(
+            self.public_keys.clone(),
+            self.signatures_required,
+        )?)
+    }
+
+    fn multi_key(&self) -> CliTypedResult<MultiKey> {
+        Ok(MultiKey::new(
+            self.public_keys
+                .iter()
+                .cloned()
+                .map(AnyPublicKey::ed25519)
+                .collect(),
+            self.signatures_required,
+        )?)
+    }
+}
+
+/// A transaction of a multikey account along with the signatures collected so far
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PartiallySignedTransaction {
+    pub account: MultikeyAccount,
+    /// The BCS encoded `RawTransaction`
+    pub raw_transaction: HexEncodedBytes,
+    /// The signatures collected so far, by key index
+    pub signatures: BTreeMap<u8, Ed25519Signature>,
+}
+
+impl PartiallySignedTransaction {
+    fn new(account: MultikeyAccount, raw_txn: &RawTransaction) -> CliTypedResult<Self> {
+        Ok(Self {
+            account,
+            raw_transaction: bcs::to_bytes(raw_txn)?.into(),
+            signatures: BTreeMap::new(),
+        })
+    }
+
+    /// Decodes the raw transaction, which must be sent by the multikey account. Single key and
+    /// multikey accounts sign the same message, so a signature on a transaction of another
+    /// sender would be valid for the single key account of the signer.
+    fn raw_transaction(&self) -> CliTypedResult<RawTransaction> {
+        let raw_txn: RawTransaction = bcs::from_bytes(self.raw_transaction.inner())
+            .map_err(|err| CliError::BCS("raw_transaction", err))?;
+        let address = self.account.address()?;
+        if raw_txn.sender() != address {
+            return Err(CliError::CommandArgumentError(format!(
+                "The transaction is sent by {}, not by the multikey account {}",
+                raw_txn.sender(),
+                address
+            )));
+        }
+        Ok(raw_txn)
+    }
+
+    fn load(path: &Path) -> CliTypedResult<Self> {
+        parse_json_file(path)
+    }
+
+    fn save(&self, path: &Path) -> CliTypedResult<()> {
+        let bytes = serde_json::to_vec_pretty(self)
+            .map_err(|err| CliError::UnexpectedError(err.to_string()))?;
+        write_to_file(path, "Multikey transaction", &bytes)
+    }
+}
+
+/// The keys and threshold of a multikey account
+#[derive(Debug, Parser)]
+pub struct MultikeyAccountOptions {
+    /// Ed25519 public keys of the signers, in order, hex encoded
+    ///
+    /// The index of a key in this list is the index of its signatures, so the order must be the
+    /// same everywhere the account is used.
+    #[clap(long, num_args = 1.., required = true, value_parser = parse_public_key)]
+    pub(crate) public_keys: Vec<Ed25519PublicKey>,
+    /// Number of signatures required to authorize a transaction
+    #[clap(long)]
+    pub(crate) signatures_required: u8,
+    /// Authentication scheme of the account
+    #[clap(long, value_enum, default_value_t = MultikeyScheme::MultiKey)]
+    pub(crate) scheme: MultikeyScheme,
+}
+
+impl TryFrom<&MultikeyAccountOptions> for MultikeyAccount {
+    type Error = CliError;
+
+    fn try_from(options: &MultikeyAccountOptions) -> Result<Self, Self::Error> {
+        MultikeyAccount::new(
+            options.scheme,
+            options.public_keys.clone(),
+            options.signatures_required,
+        )
+    }
+}
+
+fn parse_public_key(str: &str) -> Result<Ed25519PublicKey, CliError> {
+    Ed25519PublicKey::from_encoded_string(str)
+        .map_err(|err| CliError::UnableToParse("public key", err.to_string()))
+}
+
+/// Create a multikey account on-chain
+///
+/// The address of the account is derived from its public keys, threshold and scheme. The account
+/// is created by the transaction sender, who pays for the creation.
+#[derive(Debug, Parser)]
+pub struct Create {
+    #[clap(flatten)]
+    pub(crate) account_options: MultikeyAccountOptions,
+    /// Path to save the account definition to, for use by `create-transaction`
+    #[clap(long, value_parser)]
+    pub(crate) account_file: Option<PathBuf>,
+    #[clap(flatten)]
+    pub(crate) txn_options: TransactionOptions,
+}
+
+/// The address of a new multikey account and the transaction that created it
+#[derive(Clone, Debug, Serialize)]
+pub struct CreateSummary {
+    pub multikey_address: AccountAddress,
+    pub authentication_key: AuthenticationKey,
+    #[serde(flatten)]
+    pub transaction_summary: TransactionSummary,
+}
+
+#[async_trait]
+impl CliCommand<CreateSummary> for Create {
+    fn command_name(&self) -> &'static str {
+        "CreateMultikeyAccount"
+    }
+
+    async fn execute(self) -> CliTypedResult<CreateSummary> {
+        let account = MultikeyAccount::try_from(&self.account_options)?;
+        let multikey_address = account.address()?;
+        if let Some(ref account_file) = self.account_file {
+            check_if_file_exists(account_file, self.txn_options.prompt_options)?;
+            let bytes = serde_json::to_vec_pretty(&account)
+                .map_err(|err| CliError::UnexpectedError(err.to_string()))?;
+            write_to_file(account_file, "Multikey account", &bytes)?;
+        }
+
+        let transaction_summary = self
+            .txn_options
+            .submit_transaction(aptos_stdlib::aptos_account_create_account(multikey_address))
+            .await
+            .map(TransactionSummary::from)?;
+        Ok(CreateSummary {
+            multikey_address,
+            authentication_key: account.authentication_key()?,
+            transaction_summary,
+        })
+    }
+}
+
+/// Create a transaction for a multikey account and save it to a file to collect signatures
+///
+/// The signers then add their signatures with `sign`, and the transaction is submitted with
+/// `submit` once enough signatures are collected.
+#[derive(Debug, Parser)]
+pub struct CreateTransaction {
+    /// Path of the account definition saved by `create`
+    #[clap(long, value_parser)]
+    pub(crate) account_file: PathBuf,
+    #[clap(flatten)]
+    pub(crate) entry_function_args: EntryFunctionArguments,
+    /// Path to save the transaction to
+    #[clap(long, value_parser)]
+    pub(crate) output_file: PathBuf,
+    /// Gas multiplier per unit of gas
+    ///
+    /// Without a value, it will determine the price based on the current estimated price
+    #[clap(long)]
+    pub(crate) gas_unit_price: Option<u64>,
+    /// Maximum amount of gas units to be used to send this transaction
+    ///
+    /// Without a value, the default maximum of the SDK is used, as the transaction cannot be
+    /// simulated before it is signed.
+    #[clap(long)]
+    pub(crate) max_gas: Option<u64>,
+    /// Number of seconds before the transaction expires
+    ///
+    /// All signatures must be collected and the transaction submitted within this time.
+    #[clap(long, default_value_t = DEFAULT_MULTIKEY_EXPIRATION_SECS)]
+    pub(crate) expiration_secs: u64,
+    #[clap(flatten)]
+    pub(crate) rest_options: RestOptions,
+    #[clap(flatten)]
+    pub(crate) profile_options: ProfileOptions,
+    #[clap(flatten)]
+    pub(crate) prompt_options: PromptOptions,
+}
+
+/// The state of a multikey transaction file
+#[derive(Clone, Debug, Serialize)]
+pub struct PartialSignatureSummary {
+    pub transaction_file: PathBuf,
+    pub sender: AccountAddress,
+    pub sequence_number: u64,
+    pub signatures_collected: usize,
+    pub signatures_required: u8,
+}
+
+impl PartialSignatureSummary {
+    fn new(
+        transaction_file: PathBuf,
+        transaction: &PartiallySignedTransaction,
+    ) -> CliTypedResult<Self> {
+        let raw_txn = transaction.raw_transaction()?;
+        Ok(Self {
+            transaction_file,
+            sender: raw_txn.sender(),
+            sequence_number: raw_txn.sequence_number(),
+            signatures_collected: transaction.signatures.len(),
+            signatures_required: transaction.account.signatures_required,
+        })
+    }
+}
+
+#[async_trait]
+impl CliCommand<PartialSignatureSummary> for CreateTransaction {
+    fn command_name(&self) -> &'static str {
+        "CreateMultikeyTransaction"
+    }
+
+    async fn execute(self) -> CliTypedResult<PartialSignatureSummary> {
+        check_if_file_exists(&self.output_file, self.prompt_options)?;
+        let account: MultikeyAccount = parse_json_file(&self.account_file)?;
+        let sender = account.address()?;
+        let entry_function: EntryFunction = self.entry_function_args.try_into()?;
+        let payload = TransactionPayload::EntryFunction(entry_function);
+
+        let client = self.rest_options.client(&self.profile_options)?;
+        let (onchain_account, state) = get_account_with_state(&client, sender).await?;
+        let gas_unit_price = match self.gas_unit_price {
+            Some(gas_unit_price) => gas_unit_price,
+            None => client.estimate_gas_price().await?.into_inner().gas_estimate,
+        };
+        let mut transaction_factory = TransactionFactory::new(ChainId::new(state.chain_id))
+            .with_gas_unit_price(gas_unit_price);
+        if let Some(max_gas) = self.max_gas {
+            transaction_factory = transaction_factory.with_max_gas_amount(max_gas);
+        }
+        let expiration_timestamp_secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|err| CliError::UnexpectedError(err.to_string()))?
+            .as_secs()
+            + self.expiration_secs;
+        let raw_txn = transaction_factory
+            .payload(payload)
+            .sender(sender)
+            .sequence_number(onchain_account.sequence_number)
+            .expiration_timestamp_secs(expiration_timestamp_secs)
+            .build();
+
+        let transaction = PartiallySignedTransaction::new(account, &raw_txn)?;
+        transaction.save(&self.output_file)?;
+        PartialSignatureSummary::new(self.output_file, &transaction)
+    }
+}
+
+/// Add a signature to a multikey transaction file
+///
+/// This does not need network access, so it can be done on an offline machine. The signing key
+/// must be one of the keys of the account.
+#[derive(Debug, Parser)]
+pub struct Sign {
+    /// Path of the transaction file, which is updated with the signature
+    #[clap(long, value_parser)]
+    pub(crate) transaction_file: PathBuf,
+    #[clap(flatten)]
+    pub(crate) private_key_options: PrivateKeyInputOptions,
+    #[clap(flatten)]
+    pub(crate) encoding_options: EncodingOptions,
+    #[clap(flatten)]
+    pub(crate) profile_options: ProfileOptions,
+    #[clap(flatten)]
+    pub(crate) prompt_options: PromptOptions,
+}
+
+#[async_trait]
+impl CliCommand<PartialSignatureSummary> for Sign {
+    fn command_name(&self) -> &'static str {
+        "SignMultikeyTransaction"
+    }
+
+    async fn execute(self) -> CliTypedResult<PartialSignatureSummary> {
+        let mut transaction = PartiallySignedTransaction::load(&self.transaction_file)?;
+        let private_key = self
+            .private_key_options
+            .extract_private_key(self.encoding_options.encoding, &self.profile_options)?;
+        let index = transaction
+            .account
+            .key_index(&private_key.public_key())
+            .ok_or_else(|| {
+                CliError::CommandArgumentError(
+                    "The private key is not one of the keys of the multikey account".to_string(),
+                )
+            })?;
+
+        let raw_txn = transaction.raw_transaction()?;
+        eprintln!("Transaction:");
+        for (field, value) in aptos_ledger::describe_transaction(&raw_txn) {
+            eprintln!("  {}: {}", field, value);
+        }
+        prompt_yes_with_override(
+            "Do you want to sign the above transaction?",
+            self.prompt_options,
+        )?;
+        let signature = private_key.sign(&raw_txn)?;
+        transaction.signatures.insert(index, signature);
+        transaction.save(&self.transaction_file)?;
+        PartialSignatureSummary::new(self.transaction_file, &transaction)
+    }
+}
+
+/// Submit a multikey transaction once enough signatures are collected
+#[derive(Debug, Parser)]
+pub struct Submit {
+    /// Path of the transaction file
+    #[clap(long, value_parser)]
+    pub(crate) transaction_file: PathBuf,
+    #[clap(flatten)]
+    pub(crate) rest_options: RestOptions,
+    #[clap(flatten)]
+    pub(crate) profile_options: ProfileOptions,
+}
+
+#[async_trait]
+impl CliCommand<TransactionSummary> for Submit {
+    fn command_name(&self) -> &'static str {
+        "SubmitMultikeyTransaction"
+    }
+
+    async fn execute(self) -> CliTypedResult<TransactionSummary> {
+        let transaction = PartiallySignedTransaction::load(&self.transaction_file)?;
+        let signed_txn = transaction
+            .account
+            .sign_transaction(transaction.raw_transaction()?, &transaction.signatures)?;
+
+        let client = self.rest_options.client(&self.profile_options)?;
+        client.submit_bcs(&signed_txn).await?;
+        let response = client
+            .wait_for_signed_transaction(&signed_txn)
+            .await
+            .map_err(|err| CliError::ApiError(err.to_string()))?;
+        Ok(TransactionSummary::from(&response.into_inner()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_crypto::{ed25519::Ed25519PrivateKey, Uniform};
+
+    fn signers(count: usize) -> Vec<Ed25519PrivateKey> {
+        (0..count)
+            .map(|_| Ed25519PrivateKey::generate_for_testing())
+            .collect()
+    }
+
+    fn raw_txn(sender: AccountAddress) -> RawTransaction {
+        TransactionFactory::new(ChainId::test())
+            .payload(aptos_stdlib::aptos_coin_transfer(AccountAddress::ONE, 1))
+            .sender(sender)
+            .sequence_number(0)
+            .build()
+    }
+
+    #[test]
+    fn test_sign_and_assemble() {
+        for scheme in [MultikeyScheme::MultiEd25519, MultikeyScheme::MultiKey] {
+            let private_keys = signers(3);
+            let account = MultikeyAccount::new(
+                scheme,
+                private_keys.iter().map(|key| key.public_key()).collect(),
+                2,
+            )
+            .unwrap();
+            let raw_txn = raw_txn(account.address().unwrap());
+            let mut transaction = PartiallySignedTransaction::new(account, &raw_txn).unwrap();
+
+            // Signatures are collected through the file format, out of key order
+            for private_key in [&private_keys[2], &private_keys[0]] {
+                let index = transaction
+                    .account
+                    .key_index(&private_key.public_key())
+                    .unwrap();
+                transaction
+                    .signatures
+                    .insert(index, private_key.sign(&raw_txn).unwrap());
+                transaction =
+                    serde_json::from_slice(&serde_json::to_vec_pretty(&transaction).unwrap())
+                        .unwrap();
+            }
+
+            let signed_txn = transaction
+                .account
+                .sign_transaction(
+                    transaction.raw_transaction().unwrap(),
+                    &transaction.signatures,
+                )
+                .unwrap();
+            assert_eq!(signed_txn.sender(), transaction.account.address().unwrap());
+        }
+    }
+
+    #[test]
+    fn test_not_enough_signatures() {
+        let private_keys = signers(3);
+        let account = MultikeyAccount::new(
+            MultikeyScheme::MultiKey,
+            private_keys.iter().map(|key| key.public_key()).collect(),
+            2,
+        )
+        .unwrap();
+        let raw_txn = raw_txn(account.address().unwrap());
+        let signatures = BTreeMap::from([(1, private_keys[1].sign(&raw_txn).unwrap())]);
+        assert!(matches!(
+            account.sign_transaction(raw_txn, &signatures),
+            Err(CliError::CommandArgumentError(_))
+        ));
+    }
+
+    #[test]
+    fn test_other_sender() {
+        let private_keys = signers(2);
+        let account = MultikeyAccount::new(
+            MultikeyScheme::MultiKey,
+            private_keys.iter().map(|key| key.public_key()).collect(),
+            1,
+        )
+        .unwrap();
+        // A transaction of the single key account of one of the signers
+        let single_key_address =
+            AuthenticationKey::ed25519(&private_keys[0].public_key()).account_address();
+        let transaction =
+            PartiallySignedTransaction::new(account, &raw_txn(single_key_address)).unwrap();
+        assert!(matches!(
+            transaction.raw_transaction(),
+            Err(CliError::CommandArgumentError(_))
+        ));
+    }
+
+    #[test]
+    fn test_invalid_threshold() {
+        let public_keys = signers(2).iter().map(|key| key.public_key()).collect();
+        assert!(MultikeyAccount::new(MultikeyScheme::MultiEd25519, public_keys, 3).is_err());
+    }
+}
@@ -2,10 +2,13 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::common::types::{CliError, CliTypedResult};
-use aptos_crypto::HashValue;
+use aptos_api_types::StateOverrides;
+use aptos_crypto::{ed25519::Ed25519Signature, HashValue};
 use aptos_gas_profiling::FrameName;
 use aptos_move_debugger::aptos_debugger::AptosDebugger;
-use aptos_types::transaction::SignedTransaction;
+use aptos_types::transaction::{
+    authenticator::TransactionAuthenticator, SignedTransaction, TransactionOutput,
+};
 use aptos_vm::{data_cache::AsMoveResolver, AptosVM};
 use aptos_vm_environment::environment::AptosEnvironment;
 use aptos_vm_logging::log_schema::AdapterLogSchema;
@@ -35,6 +38,65 @@ pub fn run_transaction_using_debugger(
     Ok((vm_status, vm_output))
 }
 
+/// Simulates the transaction (i.e., executes it without verifying its signature) against the
+/// remote state, with the given overrides layered on top of it. Without overrides, the simulation
+/// goes through the simulation caches of the debugger.
+pub fn simulate_transaction_using_debugger(
+    debugger: &AptosDebugger,
+    version: u64,
+    transaction: SignedTransaction,
+    _hash: HashValue,
+    state_overrides: StateOverrides,
+) -> CliTypedResult<(VMStatus, TransactionOutput)> {
+    // Simulated transactions must not have a valid signature.
+    let public_key = match transaction.authenticator() {
+        TransactionAuthenticator::Ed25519 { public_key, .. } => public_key,
+        _ => {
+            return Err(CliError::UnexpectedError(
+                "Only Ed25519 transactions can be simulated locally".to_string(),
+            ))
+        },
+    };
+    let transaction = SignedTransaction::new(
+        transaction.into_raw_transaction(),
+        public_key,
+        Ed25519Signature::try_from([0u8; 64].as_ref()).unwrap(),
+    );
+
+    if state_overrides.is_empty() {
+        return Ok(debugger.simulate_transaction_at_version(version, &transaction));
+    }
+    let state_overrides = state_overrides
+        .try_into_raw_overrides(&debugger.state_view_at_version(version))
+        .map_err(|err| {
+            CliError::CommandArgumentError(format!("Invalid state overrides: {:#}", err))
+        })?;
+    Ok(debugger.simulate_transaction_at_version_with_overrides(
+        version,
+        &transaction,
+        &state_overrides,
+    ))
+}
+
+/// Materializes the output of a transaction executed against the remote state at the given
+/// version.
+pub fn materialize_output_using_debugger(
+    debugger: &AptosDebugger,
+    version: u64,
+    vm_output: VMOutput,
+) -> CliTypedResult<TransactionOutput> {
+    let state_view = debugger.state_view_at_version(version);
+    let resolver = state_view.as_move_resolver();
+    vm_output
+        .try_materialize_into_transaction_output(&resolver)
+        .map_err(|err| {
+            CliError::UnexpectedError(format!(
+                "Failed to materialize into transaction output: {}",
+                err
+            ))
+        })
+}
+
 pub fn benchmark_transaction_using_debugger(
     debugger: &AptosDebugger,
     version: u64,
@@ -19,7 +19,7 @@ use crate::{
     move_tool::{ArgWithType, FunctionArgType, MemberId},
 };
 use anyhow::{bail, Context};
-use aptos_api_types::ViewFunction;
+use aptos_api_types::{StateOverrides, ViewFunction};
 use aptos_crypto::{
     ed25519::{Ed25519PrivateKey, Ed25519PublicKey, Ed25519Signature},
     encoding_type::{EncodingError, EncodingType},
@@ -42,11 +42,11 @@ use aptos_sdk::{
 use aptos_types::{
     chain_id::ChainId,
     transaction::{
-        authenticator::AuthenticationKey, EntryFunction, MultisigTransactionPayload, Script,
-        SignedTransaction, TransactionArgument, TransactionPayload, TransactionStatus,
+        authenticator::{AccountAuthenticator, AuthenticationKey},
+        EntryFunction, MultisigTransactionPayload, Script, SignedTransaction, TransactionArgument,
+        TransactionOutput, TransactionPayload, TransactionStatus,
     },
 };
-use aptos_vm_types::output::VMOutput;
 use async_trait::async_trait;
 use clap::{Parser, ValueEnum};
 use hex::FromHexError;
@@ -1661,6 +1661,14 @@ pub struct TransactionOptions {
     #[clap(long)]
     pub(crate) local: bool,
 
+    /// Path to a JSON file with state overrides to apply when simulating locally
+    ///
+    /// The file uses the same format as `state_overrides` of the REST API's
+    /// `/transactions/simulate_with_overrides` endpoint. Resources, table items, modules and
+    /// the block timestamp can be overridden.
+    #[clap(long, requires = "local", value_parser)]
+    pub(crate) state_overrides_file: Option<PathBuf>,
+
     /// If this option is set, benchmark the transaction locally.
     #[clap(long)]
     pub(crate) benchmark: bool,
@@ -1870,8 +1878,13 @@ impl TransactionOptions {
                     HardwareWalletType::Ledger,
                     sequence_number,
                 );
-                sender_account
-                    .sign_with_transaction_builder(transaction_factory.payload(payload))?
+                let raw_txn =
+                    sender_account.build_transaction(transaction_factory.payload(payload))?;
+                eprintln!("Review and approve the transaction on your Ledger device:");
+                for (field, value) in aptos_ledger::describe_transaction(&raw_txn) {
+                    eprintln!("  {}: {}", field, value);
+                }
+                sender_account.sign_transaction(raw_txn)?
             },
             Err(err) => return Err(err),
         };
@@ -1917,6 +1930,100 @@ impl TransactionOptions {
         Ok(response.into_inner())
     }
 
+    /// Submits a multi-agent transaction, signed by the sender's private key and by each of the
+    /// `secondary_signers` in order.
+    pub async fn submit_multi_agent_transaction(
+        &self,
+        payload: TransactionPayload,
+        secondary_signers: &[LocalAccount],
+    ) -> CliTypedResult<Transaction> {
+        if self.profile_gas || self.benchmark || self.local {
+            return Err(CliError::CommandArgumentError(
+                "Multi-agent transactions can't be simulated locally, benchmarked or gas profiled"
+                    .to_string(),
+            ));
+        }
+
+        let client = self.rest_client()?;
+        let (private_key, sender_address) = self.get_key_and_address()?;
+        let gas_unit_price = if let Some(gas_unit_price) = self.gas_options.gas_unit_price {
+            gas_unit_price
+        } else {
+            client.estimate_gas_price().await?.into_inner().gas_estimate
+        };
+        let (account, state) = get_account_with_state(&client, sender_address).await?;
+        let sender_account =
+            LocalAccount::new(sender_address, private_key, account.sequence_number);
+        let transaction_factory = TransactionFactory::new(ChainId::new(state.chain_id))
+            .with_gas_unit_price(gas_unit_price)
+            .with_transaction_expiration_time(self.gas_options.expiration_secs);
+
+        let max_gas = if let Some(max_gas) = self.gas_options.max_gas {
+            max_gas
+        } else {
+            // Simulate without signatures to estimate the gas used
+            let unsigned = |public_key: &Ed25519PublicKey| {
+                AccountAuthenticator::ed25519(
+                    public_key.clone(),
+                    Ed25519Signature::try_from([0u8; 64].as_ref()).unwrap(),
+                )
+            };
+            let signed_transaction = SignedTransaction::new_multi_agent(
+                transaction_factory
+                    .payload(payload.clone())
+                    .sender(sender_address)
+                    .sequence_number(account.sequence_number)
+                    .build(),
+                unsigned(sender_account.public_key()),
+                secondary_signers
+                    .iter()
+                    .map(LocalAccount::address)
+                    .collect(),
+                secondary_signers
+                    .iter()
+                    .map(|signer| unsigned(signer.public_key()))
+                    .collect(),
+            );
+            let txns = client
+                .simulate_with_gas_estimation(&signed_transaction, true, false)
+                .await?
+                .into_inner();
+            let simulated_txn = txns.first().unwrap();
+            if !simulated_txn.info.success {
+                return Err(CliError::SimulationError(
+                    simulated_txn.info.vm_status.clone(),
+                ));
+            }
+            adjust_gas_headroom(
+                simulated_txn.info.gas_used.0,
+                simulated_txn.request.max_gas_amount.0,
+            )
+        };
+        let message = format!(
+            "Do you want to submit a transaction for a maximum of {} Octas at a gas unit price of {} Octas?",
+            max_gas * gas_unit_price,
+            gas_unit_price
+        );
+        prompt_yes_with_override(&message, self.prompt_options)?;
+
+        let transaction = sender_account.sign_multi_agent_with_transaction_builder(
+            secondary_signers.iter().collect(),
+            transaction_factory
+                .with_max_gas_amount(max_gas)
+                .payload(payload),
+        );
+        client
+            .submit_bcs(&transaction)
+            .await
+            .map_err(|err| CliError::ApiError(err.to_string()))?;
+        let response = client
+            .wait_for_signed_transaction(&transaction)
+            .await
+            .map_err(|err| CliError::ApiError(err.to_string()))?;
+
+        Ok(response.into_inner())
+    }
+
     /// Simulates a transaction locally, using the debugger to fetch required data from remote.
     async fn simulate_using_debugger<F>(
         &self,
@@ -1929,7 +2036,7 @@ impl TransactionOptions {
             u64,
             SignedTransaction,
             aptos_crypto::HashValue,
-        ) -> CliTypedResult<(VMStatus, VMOutput)>,
+        ) -> CliTypedResult<(VMStatus, TransactionOutput)>,
     {
         let client = self.rest_client()?;
 
@@ -1972,16 +2079,16 @@ impl TransactionOptions {
         let hash = transaction.committed_hash();
 
         let debugger = AptosDebugger::rest_client(client).unwrap();
-        let (vm_status, vm_output) = execute(&debugger, version, transaction, hash)?;
+        let (vm_status, txn_output) = execute(&debugger, version, transaction, hash)?;
 
-        let success = match vm_output.status() {
+        let success = match txn_output.status() {
             TransactionStatus::Keep(exec_status) => Some(exec_status.is_success()),
             TransactionStatus::Discard(_) | TransactionStatus::Retry => None,
         };
 
         let summary = TransactionSummary {
             transaction_hash: hash.into(),
-            gas_used: Some(vm_output.gas_used()),
+            gas_used: Some(txn_output.gas_used()),
             gas_unit_price: Some(gas_unit_price),
             pending: None,
             sender: Some(sender_address),
@@ -2003,8 +2110,20 @@ impl TransactionOptions {
         println!();
         println!("Simulating transaction locally...");
 
-        self.simulate_using_debugger(payload, local_simulation::run_transaction_using_debugger)
-            .await
+        let state_overrides: StateOverrides = match &self.state_overrides_file {
+            Some(path) => parse_json_file(path)?,
+            None => StateOverrides::default(),
+        };
+        self.simulate_using_debugger(payload, |debugger, version, transaction, hash| {
+            local_simulation::simulate_transaction_using_debugger(
+                debugger,
+                version,
+                transaction,
+                hash,
+                state_overrides,
+            )
+        })
+        .await
     }
 
     /// Benchmarks the transaction payload locally.
@@ -2017,10 +2136,17 @@ impl TransactionOptions {
         println!();
         println!("Benchmarking transaction locally...");
 
-        self.simulate_using_debugger(
-            payload,
-            local_simulation::benchmark_transaction_using_debugger,
-        )
+        self.simulate_using_debugger(payload, |debugger, version, transaction, hash| {
+            let (vm_status, vm_output) = local_simulation::benchmark_transaction_using_debugger(
+                debugger,
+                version,
+                transaction,
+                hash,
+            )?;
+            let txn_output =
+                local_simulation::materialize_output_using_debugger(debugger, version, vm_output)?;
+            Ok((vm_status, txn_output))
+        })
         .await
     }
 
@@ -2032,10 +2158,17 @@ impl TransactionOptions {
         println!();
         println!("Simulating transaction locally using the gas profiler...");
 
-        self.simulate_using_debugger(
-            payload,
-            local_simulation::profile_transaction_using_debugger,
-        )
+        self.simulate_using_debugger(payload, |debugger, version, transaction, hash| {
+            let (vm_status, vm_output) = local_simulation::profile_transaction_using_debugger(
+                debugger,
+                version,
+                transaction,
+                hash,
+            )?;
+            let txn_output =
+                local_simulation::materialize_output_using_debugger(debugger, version, vm_output)?;
+            Ok((vm_status, txn_output))
+        })
         .await
     }
 
@@ -28,6 +28,7 @@ use move_command_line_common::files::{
     MOVE_COMPILED_EXTENSION, MOVE_EXTENSION, SOURCE_MAP_EXTENSION,
 };
 use move_coverage::coverage_map::CoverageMap;
+use move_decompiler::round_trip;
 use move_disassembler::disassembler::{Disassembler, DisassemblerOptions};
 use move_ir_types::location::Spanned;
 use move_model::metadata::{CompilationMetadata, CompilerVersion, LanguageVersion};
@@ -94,6 +95,13 @@ pub struct BytecodeCommand {
     /// only print out the metadata and bytecode version of the target bytecode
     #[clap(long)]
     pub print_metadata_only: bool,
+
+    /// Only for decompile: recompile the decompiled source and compare the result with the
+    /// original bytecode. Every discrepancy found is reported, and the command fails if there
+    /// is any. Modules of the package and of the Aptos framework are available as dependencies
+    /// of the decompiled source. Not supported for scripts.
+    #[clap(long, conflicts_with = "is_script")]
+    pub verify: bool,
 }
 
 /// Allows to ensure that either one of both is selected (via  the `group` attribute).
@@ -166,7 +174,19 @@ impl BytecodeCommand {
             return self.print_metadata(&inputs[0]);
         }
 
+        let verify_dependencies = if self.verify {
+            if !matches!(command_type, BytecodeCommandType::Decompile) {
+                return Err(CliError::CommandArgumentError(
+                    "--verify is only supported when decompiling".to_string(),
+                ));
+            }
+            Some(self.verify_dependencies(&inputs)?)
+        } else {
+            None
+        };
+
         let mut report = vec![];
+        let mut failed_verifications = vec![];
         let mut last_out_dir = String::new();
         for bytecode_path in inputs {
             let bytecode_path = bytecode_path.as_path();
@@ -185,7 +205,15 @@ impl BytecodeCommand {
                     (self.disassemble(bytecode_path)?, DISASSEMBLER_EXTENSION)
                 },
                 BytecodeCommandType::Decompile => {
-                    (self.decompile(bytecode_path)?, DECOMPILER_EXTENSION)
+                    let output = self.decompile(bytecode_path)?;
+                    if let Some(dependencies) = &verify_dependencies {
+                        failed_verifications.extend(Self::verify_decompiled(
+                            bytecode_path,
+                            &output,
+                            dependencies,
+                        )?);
+                    }
+                    (output, DECOMPILER_EXTENSION)
                 },
             };
 
@@ -219,6 +247,13 @@ impl BytecodeCommand {
             );
         }
 
+        if !failed_verifications.is_empty() {
+            return Err(CliError::UnexpectedError(format!(
+                "Verification of the decompiled sources failed:\n{}",
+                failed_verifications.join("\n")
+            )));
+        }
+
         Ok(match report.len() {
             0 => "no bytecode modules found".to_owned(),
             1 => format!("{}/{}", last_out_dir, report[0]),
@@ -353,6 +388,41 @@ impl BytecodeCommand {
         }
     }
 
+    /// Returns the modules which decompiled sources may depend on: the input modules, and the
+    /// modules of the Aptos framework.
+    fn verify_dependencies(&self, inputs: &[PathBuf]) -> CliTypedResult<Vec<CompiledModule>> {
+        let mut dependencies = aptos_cached_packages::head_release_bundle().compiled_modules();
+        for path in inputs {
+            let module = CompiledModule::deserialize(&read_from_file(path)?).map_err(|e| {
+                CliError::UnableToParse("module", format!("cannot deserialize: {}", e))
+            })?;
+            // Modules of the package take precedence over framework modules with the same id.
+            dependencies.retain(|dep| dep.self_id() != module.self_id());
+            dependencies.push(module);
+        }
+        Ok(dependencies)
+    }
+
+    /// Recompiles the decompiled `source` of the module at `bytecode_path`, and returns the
+    /// discrepancies between the result and the original module.
+    fn verify_decompiled(
+        bytecode_path: &Path,
+        source: &str,
+        dependencies: &[CompiledModule],
+    ) -> CliTypedResult<Vec<String>> {
+        let module = CompiledModule::deserialize(&read_from_file(bytecode_path)?)
+            .map_err(|e| CliError::UnableToParse("module", format!("cannot deserialize: {}", e)))?;
+        Ok(
+            match round_trip::verify_round_trip(&module, source, dependencies, None) {
+                Ok(discrepancies) => discrepancies
+                    .into_iter()
+                    .map(|discrepancy| format!("{}: {}", bytecode_path.display(), discrepancy))
+                    .collect(),
+                Err(err) => vec![format!("{}: {:#}", bytecode_path.display(), err)],
+            },
+        )
+    }
+
     fn downgrade_to_v6(&self, file_path: &Path) -> Result<Option<NamedTempFile>, CliError> {
         let error_explanation = || {
             format!(
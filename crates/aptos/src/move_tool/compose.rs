@@ -0,0 +1,423 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    common::{
+        types::{
+            ArgWithTypeJSON, CliCommand, CliError, CliTypedResult, MovePackageDir,
+            PrivateKeyInputOptions, ProfileOptions, TransactionOptions, TransactionSummary,
+        },
+        utils::{profile_or_submit, read_from_file},
+    },
+    move_tool::{FunctionArgType, IncludedArtifacts, MemberId},
+};
+use aptos_framework::BuiltPackage;
+use aptos_script_composer::{CallArgument, TransactionComposer};
+use aptos_sdk::types::LocalAccount;
+use aptos_types::transaction::{Script, TransactionPayload};
+use async_trait::async_trait;
+use clap::Parser;
+use move_binary_format::{access::ModuleAccess, CompiledModule};
+use move_core_types::language_storage::{ModuleId, TypeTag};
+use serde::Deserialize;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::PathBuf,
+    str::FromStr,
+};
+
+/// Compose a script out of several Move function calls and run it
+///
+/// Values returned by a call can be passed as arguments to later calls, including values which
+/// can't be passed to or returned from entry functions (e.g. a `Coin` withdrawn in one call and
+/// deposited in another). Modules are loaded from the package in `--package-dir` if provided,
+/// and otherwise from chain.
+///
+/// Signer `0` is the sender of the transaction. Further signers are added with
+/// `--secondary-signer-profiles`, which turns the transaction into a multi-agent transaction.
+#[derive(Parser)]
+pub struct ComposeScript {
+    /// YAML or JSON file describing the calls to compose
+    ///
+    /// Example:
+    ///
+    /// calls:
+    ///   - function_id: 0x1::coin::withdraw
+    ///     type_args: [0x1::aptos_coin::AptosCoin]
+    ///     args: [{signer: 0}, {type: u64, value: 100}]
+    ///   - function_id: 0x1::coin::deposit
+    ///     type_args: [0x1::aptos_coin::AptosCoin]
+    ///     args: [{type: address, value: "0xcafe"}, {call: 0, index: 0}]
+    #[clap(long, value_parser, verbatim_doc_comment)]
+    pub(crate) calls_file: PathBuf,
+
+    /// Profiles of the secondary signers, in order
+    ///
+    /// Signer `i` in the calls file is the `i`th profile listed here, e.g. with
+    /// `--secondary-signer-profiles alice,bob` signer `1` is `alice` and signer `2` is `bob`.
+    #[clap(long, value_delimiter = ',')]
+    pub(crate) secondary_signer_profiles: Vec<String>,
+
+    #[clap(flatten)]
+    pub(crate) move_options: MovePackageDir,
+    #[clap(flatten)]
+    pub(crate) txn_options: TransactionOptions,
+}
+
+/// File format of the composed calls.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ComposedCallsFile {
+    calls: Vec<ComposedCall>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ComposedCall {
+    function_id: String,
+    #[serde(default)]
+    type_args: Vec<String>,
+    #[serde(default)]
+    args: Vec<ComposedArgument>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ComposedArgument {
+    /// The signer of the transaction.
+    Signer { signer: u16 },
+    /// The `index`th value returned by the `call`th call. The value is moved, or copied if it
+    /// has the `copy` and `drop` abilities, unless another operation is given.
+    PreviousResult {
+        call: u16,
+        index: u16,
+        #[serde(default)]
+        operation: Option<ResultOperation>,
+    },
+    /// A value in the same format as the arguments of `aptos move run --json-file`.
+    Value(ArgWithTypeJSON),
+}
+
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum ResultOperation {
+    Copy,
+    Borrow,
+    BorrowMut,
+}
+
+impl ComposedArgument {
+    fn into_call_argument(
+        self,
+        signer_count: u16,
+        results: &[Vec<CallArgument>],
+    ) -> CliTypedResult<CallArgument> {
+        match self {
+            ComposedArgument::Signer { signer } if signer >= signer_count => {
+                Err(CliError::CommandArgumentError(format!(
+                    "Signer {} is not available, the transaction has {} signer(s)",
+                    signer, signer_count
+                )))
+            },
+            ComposedArgument::Signer { signer } => Ok(CallArgument::new_signer(signer)),
+            ComposedArgument::PreviousResult {
+                call,
+                index,
+                operation,
+            } => {
+                let result = results
+                    .get(call as usize)
+                    .and_then(|returns| returns.get(index as usize))
+                    .ok_or_else(|| {
+                        CliError::CommandArgumentError(format!(
+                            "Call {} before the current one has no return value {}",
+                            call, index
+                        ))
+                    })?;
+                match operation {
+                    None => Ok(result.clone()),
+                    Some(ResultOperation::Copy) => result.copy(),
+                    Some(ResultOperation::Borrow) => result.borrow(),
+                    Some(ResultOperation::BorrowMut) => result.borrow_mut(),
+                }
+                .map_err(CliError::CommandArgumentError)
+            },
+            ComposedArgument::Value(arg) => Ok(CallArgument::new_bytes(
+                FunctionArgType::from_str(&arg.arg_type)?
+                    .parse_arg_json(&arg.value)?
+                    .arg,
+            )),
+        }
+    }
+}
+
+/// Collects the modules declaring the structs in `type_tag`.
+fn collect_module_ids(type_tag: &TypeTag, module_ids: &mut BTreeSet<ModuleId>) {
+    match type_tag {
+        TypeTag::Vector(inner) => collect_module_ids(inner, module_ids),
+        TypeTag::Struct(struct_tag) => {
+            module_ids.insert(struct_tag.module_id());
+            for type_arg in &struct_tag.type_args {
+                collect_module_ids(type_arg, module_ids);
+            }
+        },
+        _ => {},
+    }
+}
+
+impl ComposeScript {
+    /// Loads the modules which are called, or which declare the type arguments of a call.
+    async fn load_modules(&self, calls: &[ComposedCall]) -> CliTypedResult<Vec<CompiledModule>> {
+        let mut module_ids = BTreeSet::new();
+        for call in calls {
+            module_ids.insert(MemberId::from_str(&call.function_id)?.module_id);
+            for type_arg in &call.type_args {
+                let type_tag = TypeTag::from_str(type_arg)
+                    .map_err(|err| CliError::UnableToParse("type argument", err.to_string()))?;
+                collect_module_ids(&type_tag, &mut module_ids);
+            }
+        }
+
+        let mut local_modules = BTreeMap::new();
+        if self.move_options.package_dir.is_some() {
+            let package = BuiltPackage::build(
+                self.move_options.get_package_path()?,
+                IncludedArtifacts::None.build_options(&self.move_options)?,
+            )?;
+            for module in package.all_modules() {
+                local_modules.insert(module.self_id(), module.clone());
+            }
+        }
+
+        let client = self
+            .txn_options
+            .rest_options
+            .client(&self.txn_options.profile_options)?;
+        let mut modules = vec![];
+        for module_id in module_ids {
+            let module = match local_modules.remove(&module_id) {
+                Some(module) => module,
+                None => {
+                    let bytes = client
+                        .get_account_module_bcs(*module_id.address(), module_id.name().as_str())
+                        .await?
+                        .into_inner();
+                    CompiledModule::deserialize(&bytes)
+                        .map_err(|err| CliError::UnableToParse("module", err.to_string()))?
+                },
+            };
+            modules.push(module);
+        }
+        Ok(modules)
+    }
+}
+
+#[async_trait]
+impl CliCommand<TransactionSummary> for ComposeScript {
+    fn command_name(&self) -> &'static str {
+        "ComposeScript"
+    }
+
+    async fn execute(self) -> CliTypedResult<TransactionSummary> {
+        let calls_file: ComposedCallsFile =
+            serde_yaml::from_slice(&read_from_file(&self.calls_file)?).map_err(|err| {
+                CliError::UnableToReadFile(
+                    format!("{}", self.calls_file.display()),
+                    err.to_string(),
+                )
+            })?;
+        if calls_file.calls.is_empty() {
+            return Err(CliError::CommandArgumentError(
+                "At least one call must be provided".to_string(),
+            ));
+        }
+
+        let signer_count =
+            u16::try_from(self.secondary_signer_profiles.len() + 1).map_err(|_| {
+                CliError::CommandArgumentError("Too many secondary signers".to_string())
+            })?;
+        let modules = self.load_modules(&calls_file.calls).await?;
+        let payload = TransactionPayload::Script(compose(calls_file.calls, modules, signer_count)?);
+
+        if self.secondary_signer_profiles.is_empty() {
+            return profile_or_submit(payload, &self.txn_options).await;
+        }
+        let secondary_signers = self
+            .secondary_signer_profiles
+            .iter()
+            .map(|profile| {
+                let (private_key, address) = PrivateKeyInputOptions::default()
+                    .extract_private_key_and_address(
+                        self.txn_options.encoding_options.encoding,
+                        &ProfileOptions {
+                            profile: Some(profile.clone()),
+                        },
+                        None,
+                    )?;
+                Ok(LocalAccount::new(address, private_key, 0))
+            })
+            .collect::<CliTypedResult<Vec<_>>>()?;
+        self.txn_options
+            .submit_multi_agent_transaction(payload, &secondary_signers)
+            .await
+            .map(TransactionSummary::from)
+    }
+}
+
+/// Composes `calls` into a script taking `signer_count` signers, type checking the calls
+/// against `modules`.
+fn compose(
+    calls: Vec<ComposedCall>,
+    modules: Vec<CompiledModule>,
+    signer_count: u16,
+) -> CliTypedResult<Script> {
+    let mut composer = if signer_count == 1 {
+        TransactionComposer::single_signer()
+    } else {
+        TransactionComposer::multi_signer(signer_count)
+    };
+    for module in modules {
+        composer.insert_module(module);
+    }
+
+    let mut results = vec![];
+    for (idx, call) in calls.into_iter().enumerate() {
+        let function_id = MemberId::from_str(&call.function_id)?;
+        let args = call
+            .args
+            .into_iter()
+            .map(|arg| arg.into_call_argument(signer_count, &results))
+            .collect::<CliTypedResult<Vec<_>>>()?;
+        let returns = composer
+            .add_batched_call(
+                function_id.module_id.to_string(),
+                function_id.member_id.to_string(),
+                call.type_args,
+                args,
+            )
+            .map_err(|err| {
+                CliError::CommandArgumentError(format!(
+                    "Invalid call {} to {}: {:#}",
+                    idx, call.function_id, err
+                ))
+            })?;
+        results.push(returns);
+    }
+
+    let script = composer
+        .generate_batched_calls(true)
+        .map_err(|err| CliError::UnexpectedError(format!("Failed to compose script: {}", err)))?;
+    bcs::from_bytes(&script).map_err(|err| CliError::BCS("script", err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_script_composer::generate_batched_call_payload;
+
+    fn parse_calls(calls: &str) -> Vec<ComposedCall> {
+        serde_yaml::from_str::<ComposedCallsFile>(calls)
+            .unwrap()
+            .calls
+    }
+
+    fn compose_with_framework(calls: &str, signer_count: u16) -> CliTypedResult<Script> {
+        compose(
+            parse_calls(calls),
+            aptos_cached_packages::head_release_bundle().compiled_modules(),
+            signer_count,
+        )
+    }
+
+    #[test]
+    fn test_compose_single_signer() {
+        let script = compose_with_framework(
+            r#"
+calls:
+  - function_id: 0x1::coin::withdraw
+    type_args: [0x1::aptos_coin::AptosCoin]
+    args: [{signer: 0}, {type: u64, value: 100}]
+  - function_id: 0x1::coin::deposit
+    type_args: [0x1::aptos_coin::AptosCoin]
+    args: [{type: address, value: "0xcafe"}, {call: 0, index: 0}]
+"#,
+            1,
+        )
+        .unwrap();
+
+        let calls = generate_batched_call_payload(script.code(), script.args()).unwrap();
+        assert_eq!(calls.len(), 2);
+        let functions: Vec<_> = calls
+            .into_iter()
+            .map(|call| call.into_inner().1.to_string())
+            .collect();
+        assert_eq!(functions, vec!["withdraw", "deposit"]);
+    }
+
+    #[test]
+    fn test_compose_multiple_signers() {
+        let calls = r#"
+calls:
+  - function_id: 0x1::coin::withdraw
+    type_args: [0x1::aptos_coin::AptosCoin]
+    args: [{signer: 0}, {type: u64, value: 100}]
+  - function_id: 0x1::coin::withdraw
+    type_args: [0x1::aptos_coin::AptosCoin]
+    args: [{signer: 1}, {type: u64, value: 200}]
+  - function_id: 0x1::aptos_account::deposit_coins
+    type_args: [0x1::aptos_coin::AptosCoin]
+    args: [{type: address, value: "0xcafe"}, {call: 1, index: 0}]
+  - function_id: 0x1::aptos_account::deposit_coins
+    type_args: [0x1::aptos_coin::AptosCoin]
+    args: [{type: address, value: "0xbeef"}, {call: 0, index: 0}]
+"#;
+        let script = compose_with_framework(calls, 2).unwrap();
+        let calls = generate_batched_call_payload(script.code(), script.args()).unwrap();
+        assert_eq!(calls.len(), 4);
+
+        // The second signer only exists in a multi-agent transaction
+        assert!(matches!(
+            compose_with_framework(
+                r#"
+calls:
+  - function_id: 0x1::coin::withdraw
+    type_args: [0x1::aptos_coin::AptosCoin]
+    args: [{signer: 1}, {type: u64, value: 200}]
+"#,
+                1
+            ),
+            Err(CliError::CommandArgumentError(_))
+        ));
+    }
+
+    #[test]
+    fn test_compose_invalid_calls() {
+        // Results can only be used from earlier calls
+        assert!(matches!(
+            compose_with_framework(
+                r#"
+calls:
+  - function_id: 0x1::coin::deposit
+    type_args: [0x1::aptos_coin::AptosCoin]
+    args: [{type: address, value: "0xcafe"}, {call: 0, index: 0}]
+"#,
+                1
+            ),
+            Err(CliError::CommandArgumentError(_))
+        ));
+
+        // Arguments are type checked against the called function
+        assert!(matches!(
+            compose_with_framework(
+                r#"
+calls:
+  - function_id: 0x1::coin::withdraw
+    type_args: [0x1::aptos_coin::AptosCoin]
+    args: [{signer: 0}, {signer: 0}]
+"#,
+                1
+            ),
+            Err(CliError::CommandArgumentError(_))
+        ));
+    }
+}
@@ -20,6 +20,7 @@ use crate::{
     governance::CompileScriptFunction,
     move_tool::{
         bytecode::{Decompile, Disassemble},
+        compose::ComposeScript,
         coverage::SummaryCoverage,
         fmt::Fmt,
         lint::LintPackage,
@@ -53,7 +54,6 @@ use aptos_types::{
     on_chain_config::aptos_test_feature_flags_genesis,
     transaction::{Transaction, TransactionArgument, TransactionPayload, TransactionStatus},
 };
-use aptos_vm::data_cache::AsMoveResolver;
 use async_trait::async_trait;
 use clap::{Parser, Subcommand, ValueEnum};
 use colored::Colorize;
@@ -80,6 +80,7 @@ use url::Url;
 
 pub mod aptos_debug_natives;
 mod bytecode;
+mod compose;
 pub mod coverage;
 mod fmt;
 mod lint;
@@ -106,6 +107,7 @@ pub enum MoveTool {
     Compile(CompilePackage),
     #[clap(alias = "build-script")]
     CompileScript(CompileScript),
+    Compose(ComposeScript),
     #[clap(subcommand)]
     Coverage(coverage::CoveragePackage),
     CreateObjectAndPublishPackage(CreateObjectAndPublishPackage),
@@ -143,6 +145,7 @@ impl MoveTool {
             MoveTool::ClearStagingArea(tool) => tool.execute_serialized().await,
             MoveTool::Compile(tool) => tool.execute_serialized().await,
             MoveTool::CompileScript(tool) => tool.execute_serialized().await,
+            MoveTool::Compose(tool) => tool.execute_serialized().await,
             MoveTool::Coverage(tool) => tool.execute().await,
             MoveTool::CreateObjectAndPublishPackage(tool) => {
                 tool.execute_serialized_success().await
@@ -2290,17 +2293,8 @@ impl CliCommand<TransactionSummary> for Replay {
         };
 
         // Materialize into transaction output and check if the outputs match.
-        let state_view = debugger.state_view_at_version(self.txn_id);
-        let resolver = state_view.as_move_resolver();
-
-        let txn_output = vm_output
-            .try_materialize_into_transaction_output(&resolver)
-            .map_err(|err| {
-                CliError::UnexpectedError(format!(
-                    "Failed to materialize into transaction output: {}",
-                    err
-                ))
-            })?;
+        let txn_output =
+            local_simulation::materialize_output_using_debugger(&debugger, self.txn_id, vm_output)?;
 
         if !self.skip_comparison {
             txn_output
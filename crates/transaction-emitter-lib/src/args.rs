@@ -12,7 +12,7 @@ use std::{
     convert::TryFrom,
     fs::File,
     io::{BufRead, BufReader},
-    path::Path,
+    path::{Path, PathBuf},
 };
 use url::Url;
 
@@ -108,7 +108,7 @@ impl ClusterArgs {
 #[clap(group(
     ArgGroup::new("mode")
         .required(true)
-        .args(&["mempool_backlog", "target_tps"]),
+        .args(&["mempool_backlog", "target_tps", "replay_trace"]),
 ))]
 pub struct EmitArgs {
     #[clap(long)]
@@ -122,6 +122,30 @@ pub struct EmitArgs {
     #[clap(long)]
     pub target_tps: Option<usize>,
 
+    /// Replay the transactions of a trace (see `export-trace`) instead of the transaction
+    /// types, with the times between transactions of the trace. Accounts of the trace are
+    /// replaced with generated accounts, and the packages of the trace are deployed before
+    /// replaying.
+    #[clap(long)]
+    pub replay_trace: Option<PathBuf>,
+
+    /// Factor by which the rate of the replayed trace is scaled, i.e. the times between its
+    /// transactions are divided by it. Defaults to 1.0, i.e. the original rate.
+    #[clap(long, requires = "replay_trace")]
+    pub replay_rate_scale: Option<f64>,
+
+    /// Submit at --target-tps on a fixed schedule, without waiting for slow batches, so that
+    /// latency is not understated when the network falls behind. Arrivals that cannot be made,
+    /// because the previous batch of an account is still in flight, are reported as missed.
+    #[clap(long, requires = "target_tps")]
+    pub open_loop: bool,
+
+    /// File to write the latency histograms and totals of the run to, as JSON. The latency of a
+    /// sample of the transactions is split between stages, some of which are measured against
+    /// block timestamps, so they are subject to clock skew between the emitter and validators
+    #[clap(long)]
+    pub latency_report_file: Option<PathBuf>,
+
     #[clap(long, default_value_t = 30)]
     pub txn_expiration_time_secs: u64,
 
@@ -153,6 +177,11 @@ pub struct EmitArgs {
     #[clap(long)]
     pub sender_use_account_pool: Option<bool>,
 
+    /// Run the workload defined by a YAML or JSON spec file, instead of the transaction types.
+    /// See `aptos_transaction_generator_lib::workload_spec` for the format.
+    #[clap(long, conflicts_with_all = &["replay_trace", "transaction_weights", "transaction_phases"])]
+    pub workload_spec: Option<PathBuf>,
+
     #[clap(long, num_args = 0..)]
     pub transaction_weights: Vec<usize>,
 
@@ -238,6 +267,48 @@ pub struct AccountTypeArgs {
     pub keyless_jwt: Option<String>,
 }
 
+#[derive(Clone, Debug, Default, Deserialize, Parser, Serialize)]
+pub struct ExportTraceArgs {
+    /// REST endpoint of the node to export the transactions from
+    #[clap(long)]
+    pub url: Url,
+
+    /// Version of the first transaction to export
+    #[clap(long)]
+    pub start_version: u64,
+
+    /// Number of user transactions to export
+    #[clap(long)]
+    pub num_transactions: usize,
+
+    /// File to write the trace to
+    #[clap(long)]
+    pub output_file: PathBuf,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Parser, Serialize)]
+pub struct MaxTpsSearchArgs {
+    /// Highest p99 submit to commit latency, in milliseconds, at which a TPS is sustained
+    #[clap(long)]
+    pub p99_latency_target_ms: u64,
+
+    /// Lowest TPS to probe
+    #[clap(long, default_value_t = 10)]
+    pub min_tps: usize,
+
+    /// Highest TPS to probe
+    #[clap(long, default_value_t = 100_000)]
+    pub max_tps: usize,
+
+    /// The search stops once the highest sustained and lowest unsustained TPS are this close
+    #[clap(long, default_value_t = 100)]
+    pub precision_tps: usize,
+
+    /// File to write the results of all probes to, as JSON
+    #[clap(long)]
+    pub report_file: Option<PathBuf>,
+}
+
 #[derive(Clone, Debug, Default, Deserialize, Parser, Serialize)]
 pub struct CreateAccountsArgs {
     /// Number of accounts to create
@@ -42,6 +42,7 @@ use rand_core::SeedableRng;
 use std::{
     cmp::{max, min},
     collections::{HashMap, HashSet},
+    ops::Range,
     str::FromStr,
     sync::{
         atomic::{AtomicBool, Ordering},
@@ -83,6 +84,8 @@ pub struct EmitModeParams {
     pub wait_millis: u64,
     pub check_account_sequence_only_once_fraction: f32,
     pub check_account_sequence_sleep: Duration,
+    /// Whether workers keep to their schedule while previous batches are still in flight.
+    pub open_loop: bool,
 }
 
 #[derive(Clone, Debug)]
@@ -110,6 +113,11 @@ pub enum EmitJobMode {
         // number of waves within the wait_millis interval (which is txn_expiration_time + 180s)
         num_waves: usize,
     },
+    /// Like ConstTps, but arrivals are never delayed by slow batches. Arrivals that a worker
+    /// cannot make, because its previous batch is still in flight, are counted as missed.
+    OpenLoop {
+        tps: usize,
+    },
 }
 
 impl EmitJobMode {
@@ -420,6 +428,19 @@ impl EmitJobRequest {
         self.gas_price * self.init_gas_price_multiplier
     }
 
+    /// Number of accounts needed, in addition to the worker accounts, to replay the traces
+    /// of the transaction mix.
+    pub fn get_num_replay_accounts(&self) -> usize {
+        self.transaction_mix_per_phase
+            .iter()
+            .flatten()
+            .map(|(transaction_type, _)| match transaction_type {
+                TransactionType::ReplayTrace { trace, .. } => trace.accounts().len(),
+                _ => 0,
+            })
+            .sum()
+    }
+
     pub fn calculate_mode_params(&self) -> EmitModeParams {
         let clients_count = self.rest_clients.len();
         assert!(clients_count > 0, "No rest clients provided");
@@ -475,9 +496,11 @@ impl EmitJobRequest {
                     endpoints: clients_count,
                     check_account_sequence_only_once_fraction: 0.0,
                     check_account_sequence_sleep: self.latency_polling_interval,
+                    open_loop: false,
                 }
             },
             EmitJobMode::ConstTps { tps }
+            | EmitJobMode::OpenLoop { tps }
             | EmitJobMode::WaveTps {
                 average_tps: tps, ..
             } => {
@@ -569,6 +592,7 @@ impl EmitJobRequest {
                     endpoints: clients_count,
                     check_account_sequence_only_once_fraction: 1.0 - sample_latency_fraction,
                     check_account_sequence_sleep: self.latency_polling_interval,
+                    open_loop: matches!(self.mode, EmitJobMode::OpenLoop { .. }),
                 }
             },
         }
@@ -795,6 +819,9 @@ impl TxnEmitter {
             },
         };
 
+        // Accounts replacing the accounts of replayed traces are created together with the
+        // worker accounts, and handed to the transaction generators through the accounts pool.
+        let num_replay_accounts = req.get_num_replay_accounts();
         let mut all_accounts = bulk_create_accounts(
             root_account.clone(),
             &RestApiReliableTransactionSubmitter::new(
@@ -805,10 +832,11 @@ impl TxnEmitter {
             &init_txn_factory,
             account_generator,
             (&req).into(),
-            num_accounts,
+            num_accounts + num_replay_accounts,
             get_needed_balance_per_account_from_req(&req, num_accounts),
         )
         .await?;
+        let replay_accounts = all_accounts.split_off(num_accounts);
 
         let stop = Arc::new(AtomicBool::new(false));
         let stats = Arc::new(DynamicStatsTracking::new(stats_tracking_phases));
@@ -830,13 +858,13 @@ impl TxnEmitter {
             &req.transaction_mix_per_phase,
             source_account_manager,
             &mut all_accounts,
-            vec![],
+            replay_accounts,
             &txn_executor,
             &txn_factory,
             &init_txn_factory,
             stats.get_cur_phase_obj(),
         )
-        .await;
+        .await?;
 
         if !req.coordination_delay_between_instances.is_zero() {
             info!(
@@ -992,10 +1020,18 @@ fn pick_client(clients: &Vec<RestClient>) -> &RestClient {
     clients.choose(&mut rand::thread_rng()).unwrap()
 }
 
+/// Transactions of an account, which a single poll observed as committed.
+struct CommittedTransactions {
+    address: AccountAddress,
+    sequence_numbers: Range<u64>,
+    /// When the poll observed them, counted from the start_time of the wait.
+    millis_elapsed: u64,
+}
+
 /// This function waits for the submitted transactions to be committed, up to
 /// a wait_timeout (counted from the start_time passed in, not from the function call).
-/// It returns number of transactions that expired without being committed,
-/// and sum of completion timestamps for those that have.
+/// It returns the sequence numbers fetched for the accounts, and when the transactions
+/// that have been committed were observed.
 ///
 /// This function updates sequence_number for the account to match what
 /// we were able to fetch last.
@@ -1005,15 +1041,15 @@ async fn wait_for_accounts_sequence(
     account_seqs: &HashMap<AccountAddress, (u64, u64)>,
     txn_expiration_ts_secs: u64,
     sleep_between_cycles: Duration,
-) -> (HashMap<AccountAddress, u64>, u128) {
+) -> (HashMap<AccountAddress, u64>, Vec<CommittedTransactions>) {
     let mut pending_addresses: HashSet<_> = account_seqs.keys().copied().collect();
     let mut latest_fetched_counts = HashMap::new();
 
-    let mut sum_of_completion_timestamps_millis = 0u128;
+    let mut committed = Vec::new();
     loop {
         match query_sequence_numbers(client, pending_addresses.iter()).await {
             Ok((sequence_numbers, ledger_timestamp_secs)) => {
-                let millis_elapsed = start_time.elapsed().as_millis();
+                let millis_elapsed = start_time.elapsed().as_millis() as u64;
                 for (address, sequence_number) in sequence_numbers {
                     let (start_seq_num, end_seq_num) = account_seqs.get(&address).unwrap();
 
@@ -1023,8 +1059,13 @@ async fn wait_for_accounts_sequence(
                     // fetched sequence number that is older than one we already fetched.
                     // client connection probably moved to a different server.
                     if prev_sequence_number <= sequence_number {
-                        sum_of_completion_timestamps_millis +=
-                            millis_elapsed * (sequence_number - prev_sequence_number) as u128;
+                        if prev_sequence_number < sequence_number {
+                            committed.push(CommittedTransactions {
+                                address,
+                                sequence_numbers: prev_sequence_number..sequence_number,
+                                millis_elapsed,
+                            });
+                        }
 
                         if *end_seq_num == sequence_number {
                             pending_addresses.remove(&address);
@@ -1078,7 +1119,7 @@ async fn wait_for_accounts_sequence(
         time::sleep(sleep_between_cycles).await;
     }
 
-    (latest_fetched_counts, sum_of_completion_timestamps_millis)
+    (latest_fetched_counts, committed)
 }
 
 pub async fn query_sequence_number(client: &RestClient, address: AccountAddress) -> Result<u64> {
@@ -1,6 +1,9 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
+use aptos_infallible::Mutex;
+use hdrhistogram::Histogram;
+use serde::Serialize;
 use std::{
     fmt,
     ops::{Add, Sub},
@@ -17,9 +20,11 @@ pub struct TxnStats {
     pub committed: u64,
     pub expired: u64,
     pub failed_submission: u64,
+    pub missed_arrivals: u64, // open-loop only, transactions not submitted on schedule
     pub latency: u64,         // total milliseconds across all latency measurements
     pub latency_samples: u64, // number of events with latency measured
     pub latency_buckets: AtomicHistogramSnapshot, // millisecond snapshot buckets
+    pub latency_histograms: LatencyHistograms,
     pub lasted: Duration,
 }
 
@@ -29,6 +34,7 @@ pub struct TxnStatsRate {
     pub committed: f64,         // per second
     pub expired: f64,           // per second
     pub failed_submission: f64, // per second
+    pub missed_arrivals: f64,   // per second
     pub latency: f64,           // mean latency (milliseconds)
     pub latency_samples: u64,   // number latency-measured events
     pub p50_latency: u64,       // milliseconds, 50% this or better
@@ -41,11 +47,12 @@ impl fmt::Display for TxnStatsRate {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "committed: {:.2} txn/s{}{}{}, latency: {:.2} ms, (p50: {} ms, p70: {}, p90: {} ms, p99: {} ms), latency samples: {}",
+            "committed: {:.2} txn/s{}{}{}{}, latency: {:.2} ms, (p50: {} ms, p70: {}, p90: {} ms, p99: {} ms), latency samples: {}",
             self.committed,
             if self.submitted != self.committed { format!(", submitted: {:.2} txn/s", self.submitted) } else { "".to_string()},
             if self.failed_submission != 0.0 { format!(", failed submission: {:.2} txn/s", self.failed_submission) } else { "".to_string()},
             if self.expired != 0.0 { format!(", expired: {:.2} txn/s", self.expired) } else { "".to_string()},
+            if self.missed_arrivals != 0.0 { format!(", missed arrivals: {:.2} txn/s", self.missed_arrivals) } else { "".to_string()},
             self.latency, self.p50_latency, self.p70_latency, self.p90_latency, self.p99_latency, self.latency_samples,
         )
     }
@@ -59,6 +66,7 @@ impl TxnStats {
             committed: (self.committed as f64) / window_secs,
             expired: (self.expired as f64) / window_secs,
             failed_submission: (self.failed_submission as f64) / window_secs,
+            missed_arrivals: (self.missed_arrivals as f64) / window_secs,
             latency: if self.latency_samples == 0 {
                 0.0
             } else {
@@ -71,6 +79,28 @@ impl TxnStats {
             p99_latency: self.latency_buckets.percentile(99, 100),
         }
     }
+
+    /// Summary of the stats, with the full latency histograms, for exporting as JSON.
+    pub fn report(&self) -> TxnStatsReport {
+        TxnStatsReport {
+            duration_secs: self.lasted.as_secs_f64(),
+            submitted: self.submitted,
+            committed: self.committed,
+            expired: self.expired,
+            failed_submission: self.failed_submission,
+            missed_arrivals: self.missed_arrivals,
+            committed_tps: self.rate().committed,
+            latency: LatencyReport {
+                total: HistogramReport::new(&self.latency_histograms.total),
+                api_accept: HistogramReport::new(&self.latency_histograms.api_accept),
+                mempool: HistogramReport::new(&self.latency_histograms.mempool),
+                consensus_inclusion: HistogramReport::new(
+                    &self.latency_histograms.consensus_inclusion,
+                ),
+                commit: HistogramReport::new(&self.latency_histograms.commit),
+            },
+        }
+    }
 }
 
 impl fmt::Display for TxnStats {
@@ -92,9 +122,11 @@ impl Sub for &TxnStats {
             committed: self.committed - other.committed,
             expired: self.expired - other.expired,
             failed_submission: self.failed_submission - other.failed_submission,
+            missed_arrivals: self.missed_arrivals - other.missed_arrivals,
             latency: self.latency - other.latency,
             latency_samples: self.latency_samples - other.latency_samples,
             latency_buckets: &self.latency_buckets - &other.latency_buckets,
+            latency_histograms: &self.latency_histograms - &other.latency_histograms,
             lasted: self.lasted - other.lasted,
         }
     }
@@ -109,9 +141,11 @@ impl Add for &TxnStats {
             committed: self.committed + other.committed,
             expired: self.expired + other.expired,
             failed_submission: self.failed_submission + other.failed_submission,
+            missed_arrivals: self.missed_arrivals + other.missed_arrivals,
             latency: self.latency + other.latency,
             latency_samples: self.latency_samples + other.latency_samples,
             latency_buckets: &self.latency_buckets + &other.latency_buckets,
+            latency_histograms: &self.latency_histograms + &other.latency_histograms,
             lasted: self.lasted + other.lasted,
         }
     }
@@ -123,9 +157,11 @@ pub struct StatsAccumulator {
     pub committed: AtomicU64,
     pub expired: AtomicU64,
     pub failed_submission: AtomicU64,
+    pub missed_arrivals: AtomicU64,
     pub latency: AtomicU64, // total milliseconds across all latency measurements
     pub latency_samples: AtomicU64, // number of events with latency measured
     pub latencies: Arc<AtomicHistogramAccumulator>, // millisecond histogram buckets
+    pub latency_histograms: AtomicLatencyHistograms,
 }
 
 impl StatsAccumulator {
@@ -135,9 +171,11 @@ impl StatsAccumulator {
             committed: self.committed.load(Ordering::Relaxed),
             expired: self.expired.load(Ordering::Relaxed),
             failed_submission: self.failed_submission.load(Ordering::Relaxed),
+            missed_arrivals: self.missed_arrivals.load(Ordering::Relaxed),
             latency: self.latency.load(Ordering::Relaxed),
             latency_samples: self.latency_samples.load(Ordering::Relaxed),
             latency_buckets: self.latencies.snapshot(),
+            latency_histograms: self.latency_histograms.snapshot(),
             lasted,
         }
     }
@@ -274,6 +312,176 @@ impl AtomicHistogramSnapshot {
     }
 }
 
+/// HDR histograms of the latency of transactions, in milliseconds, broken down by stage.
+///
+/// The total latency is recorded for every committed transaction. The stages are recorded for a
+/// sample of them, a random transaction of every submitted request, and add up to the total
+/// latency of each sampled transaction.
+///
+/// The REST API only exposes the timestamps of blocks, which are set when they are proposed. So
+/// waiting in mempool and in quorum store batches are measured together until the proposal of
+/// the block, and consensus inclusion of the block is measured until the proposal of the next
+/// block, which carries its quorum certificate. Stages measured against block timestamps are
+/// subject to clock skew between the emitter and validators.
+#[derive(Clone, Debug)]
+pub struct LatencyHistograms {
+    /// From submission until the commit is observed by the emitter.
+    pub total: Histogram<u64>,
+    /// From submission until the API accepted the transaction.
+    pub api_accept: Histogram<u64>,
+    /// From the API accepting the transaction until its block is proposed.
+    pub mempool: Histogram<u64>,
+    /// From the proposal of the block until the proposal of the next block, which certifies it.
+    pub consensus_inclusion: Histogram<u64>,
+    /// From the certification of the block until the commit is observed by the emitter.
+    pub commit: Histogram<u64>,
+}
+
+impl Default for LatencyHistograms {
+    fn default() -> Self {
+        // Auto-resizing, with 3 significant digits.
+        let new_histogram = || Histogram::new(3).unwrap();
+        Self {
+            total: new_histogram(),
+            api_accept: new_histogram(),
+            mempool: new_histogram(),
+            consensus_inclusion: new_histogram(),
+            commit: new_histogram(),
+        }
+    }
+}
+
+impl LatencyHistograms {
+    fn zip_with(
+        &self,
+        other: &LatencyHistograms,
+        op: impl Fn(&mut Histogram<u64>, &Histogram<u64>),
+    ) -> LatencyHistograms {
+        let mut result = self.clone();
+        op(&mut result.total, &other.total);
+        op(&mut result.api_accept, &other.api_accept);
+        op(&mut result.mempool, &other.mempool);
+        op(&mut result.consensus_inclusion, &other.consensus_inclusion);
+        op(&mut result.commit, &other.commit);
+        result
+    }
+}
+
+impl Sub for &LatencyHistograms {
+    type Output = LatencyHistograms;
+
+    fn sub(self, other: &LatencyHistograms) -> LatencyHistograms {
+        self.zip_with(other, |result, other| {
+            result
+                .subtract(other)
+                .expect("Histogram snapshots must be monotonic")
+        })
+    }
+}
+
+impl Add for &LatencyHistograms {
+    type Output = LatencyHistograms;
+
+    fn add(self, other: &LatencyHistograms) -> LatencyHistograms {
+        self.zip_with(other, |result, other| {
+            result.add(other).expect("Histograms are auto-resizing")
+        })
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct AtomicLatencyHistograms {
+    histograms: Mutex<LatencyHistograms>,
+}
+
+impl AtomicLatencyHistograms {
+    pub fn snapshot(&self) -> LatencyHistograms {
+        self.histograms.lock().clone()
+    }
+
+    pub fn record_total(&self, latency_millis: u64, count: u64) {
+        self.histograms
+            .lock()
+            .total
+            .saturating_record_n(latency_millis, count);
+    }
+
+    pub fn record_stages(
+        &self,
+        api_accept_millis: u64,
+        mempool_millis: u64,
+        consensus_inclusion_millis: u64,
+        commit_millis: u64,
+    ) {
+        let mut histograms = self.histograms.lock();
+        histograms.api_accept.saturating_record(api_accept_millis);
+        histograms.mempool.saturating_record(mempool_millis);
+        histograms
+            .consensus_inclusion
+            .saturating_record(consensus_inclusion_millis);
+        histograms.commit.saturating_record(commit_millis);
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct HistogramReport {
+    pub samples: u64,
+    pub min_ms: u64,
+    pub mean_ms: f64,
+    pub p50_ms: u64,
+    pub p90_ms: u64,
+    pub p99_ms: u64,
+    pub p999_ms: u64,
+    pub max_ms: u64,
+    /// Recorded values, as (milliseconds, count) pairs.
+    pub values: Vec<(u64, u64)>,
+}
+
+impl HistogramReport {
+    fn new(histogram: &Histogram<u64>) -> Self {
+        Self {
+            samples: histogram.len(),
+            min_ms: histogram.min(),
+            mean_ms: histogram.mean(),
+            p50_ms: histogram.value_at_quantile(0.5),
+            p90_ms: histogram.value_at_quantile(0.9),
+            p99_ms: histogram.value_at_quantile(0.99),
+            p999_ms: histogram.value_at_quantile(0.999),
+            max_ms: histogram.max(),
+            values: histogram
+                .iter_recorded()
+                .map(|value| (value.value_iterated_to(), value.count_at_value()))
+                .collect(),
+        }
+    }
+}
+
+/// Latency histograms of a run, see [LatencyHistograms] for how the stages are measured.
+///
+/// Only `total` and `api_accept` are measured with the clock of the emitter alone. The other
+/// stages start or end at block timestamps, which are set by the clock of the proposing
+/// validator, so a skew between the clocks shifts latency between `mempool` and `commit`.
+#[derive(Debug, Serialize)]
+pub struct LatencyReport {
+    pub total: HistogramReport,
+    pub api_accept: HistogramReport,
+    pub mempool: HistogramReport,
+    pub consensus_inclusion: HistogramReport,
+    pub commit: HistogramReport,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TxnStatsReport {
+    pub duration_secs: f64,
+    pub submitted: u64,
+    pub committed: u64,
+    pub expired: u64,
+    pub failed_submission: u64,
+    pub missed_arrivals: u64,
+    pub committed_tps: f64,
+    pub latency: LatencyReport,
+}
+
 #[derive(Debug)]
 pub struct DynamicStatsTracking {
     num_phases: usize,
@@ -300,7 +508,11 @@ impl DynamicStatsTracking {
     }
 
     pub fn get_cur(&self) -> &StatsAccumulator {
-        self.stats.get(self.get_cur_phase()).unwrap()
+        self.get(self.get_cur_phase())
+    }
+
+    pub fn get(&self, phase: usize) -> &StatsAccumulator {
+        self.stats.get(phase).unwrap()
     }
 
     pub fn get_cur_phase(&self) -> usize {
@@ -334,8 +546,8 @@ impl DynamicStatsTracking {
 #[cfg(test)]
 mod test {
     use crate::emitter::stats::{
-        AtomicHistogramAccumulator, AtomicHistogramSnapshot, TxnStats, DEFAULT_HISTOGRAM_CAPACITY,
-        DEFAULT_HISTOGRAM_STEP_WIDTH,
+        AtomicHistogramAccumulator, AtomicHistogramSnapshot, AtomicLatencyHistograms,
+        LatencyHistograms, TxnStats, DEFAULT_HISTOGRAM_CAPACITY, DEFAULT_HISTOGRAM_STEP_WIDTH,
     };
     use std::time::Duration;
 
@@ -401,12 +613,43 @@ mod test {
             committed: 10,
             expired: 0,
             failed_submission: 0,
+            missed_arrivals: 0,
             latency: 0,
             latency_samples: 0,
             latency_buckets: histogram.snapshot(),
+            latency_histograms: LatencyHistograms::default(),
             lasted: Duration::from_secs(10),
         };
         let res = stat.latency_buckets.percentile(9, 10);
         assert_eq!(res, 900);
     }
+
+    #[test]
+    pub fn test_latency_histograms_delta() {
+        let histograms = AtomicLatencyHistograms::default();
+        histograms.record_total(1000, 10);
+        let prev = histograms.snapshot();
+        histograms.record_total(3000, 5);
+        histograms.record_stages(20, 500, 300, 1700);
+
+        let delta = &histograms.snapshot() - &prev;
+        assert_eq!(delta.total.len(), 5);
+        assert_eq!(delta.total.min(), delta.total.max());
+        assert_eq!(delta.api_accept.len(), 1);
+        assert_eq!(delta.mempool.len(), 1);
+        assert_eq!(delta.consensus_inclusion.len(), 1);
+        assert_eq!(delta.commit.len(), 1);
+
+        let sum = &delta + &prev;
+        assert_eq!(sum.total.len(), 15);
+        let report = TxnStats {
+            latency_histograms: sum,
+            lasted: Duration::from_secs(1),
+            ..TxnStats::default()
+        }
+        .report();
+        assert_eq!(report.latency.total.values.len(), 2);
+        assert!(report.latency.total.p50_ms < 1010);
+        assert!(report.latency.total.p99_ms > 2990);
+    }
 }
@@ -4,10 +4,12 @@
 use crate::{
     emitter::{
         stats::{DynamicStatsTracking, StatsAccumulator},
-        wait_for_accounts_sequence,
+        wait_for_accounts_sequence, CommittedTransactions,
     },
     EmitModeParams,
 };
+use anyhow::format_err;
+use aptos_infallible::Mutex;
 use aptos_logger::{debug, error, info, sample, sample::SampleRate, warn};
 use aptos_rest_client::Client as RestClient;
 use aptos_sdk::{
@@ -23,14 +25,17 @@ use core::{
 };
 use futures::future::join_all;
 use itertools::Itertools;
-use rand::seq::IteratorRandom;
+use rand::seq::{IteratorRandom, SliceRandom};
 use std::{
     borrow::Borrow,
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     sync::{atomic::AtomicU64, Arc},
-    time::Instant,
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+use tokio::{
+    task::JoinHandle,
+    time::{sleep, sleep_until},
 };
-use tokio::time::{sleep, sleep_until};
 
 const ALLOWED_EARLY: Duration = Duration::from_micros(500);
 
@@ -82,10 +87,15 @@ impl SubmissionWorker {
 
     #[allow(clippy::collapsible_if)]
     pub(crate) async fn run(mut self, start_instant: Instant) -> Vec<LocalAccount> {
+        if self.params.open_loop {
+            return self.run_open_loop(start_instant).await;
+        }
+
         let mut wait_until = start_instant + self.start_sleep_duration;
 
         self.sleep_check_done(wait_until).await;
         let wait_duration = Duration::from_millis(self.params.wait_millis);
+        let mut sampling: Vec<JoinHandle<()>> = Vec::new();
 
         while !self.stop.load(Ordering::Relaxed) {
             let loop_start_time = Instant::now();
@@ -95,25 +105,19 @@ impl SubmissionWorker {
             }
 
             let stats_clone = self.stats.clone();
-            let loop_stats = stats_clone.get_cur();
+            let phase = stats_clone.get_cur_phase();
+            let loop_stats = stats_clone.get(phase);
 
             let requests = self.gen_requests();
             if !requests.is_empty() {
-                let mut account_to_start_and_end_seq_num = HashMap::new();
-                for req in requests.iter() {
-                    let cur = req.sequence_number();
-                    let _ = *account_to_start_and_end_seq_num
-                        .entry(req.sender())
-                        .and_modify(|(start, end)| {
-                            if *start > cur {
-                                *start = cur;
-                            }
-                            if *end < cur + 1 {
-                                *end = cur + 1;
-                            }
-                        })
-                        .or_insert((cur, cur + 1));
-                }
+                let batch = SubmittedBatch::submit(
+                    self.client(),
+                    &requests,
+                    loop_start_time,
+                    &self.params,
+                    loop_stats,
+                )
+                .await;
                 // Some transaction generators use burner accounts, and will have different
                 // number of accounts per transaction, so useful to very rarely log.
                 sample!(
@@ -122,75 +126,31 @@ impl SubmissionWorker {
                         "[{:?}] txn_emitter worker: handling {} accounts, generated txns for: {}",
                         self.client().path_prefix_string(),
                         self.accounts.len(),
-                        account_to_start_and_end_seq_num.len(),
+                        batch.account_to_start_and_end_seq_num.len(),
                     )
                 );
 
-                let txn_expiration_time = requests
-                    .iter()
-                    .map(|txn| txn.expiration_timestamp_secs())
-                    .max()
-                    .unwrap_or(0);
-
-                let txn_offset_time = Arc::new(AtomicU64::new(0));
-
-                join_all(
-                    requests
-                        .chunks(self.params.max_submit_batch_size)
-                        .map(|reqs| {
-                            submit_transactions(
-                                self.client(),
-                                reqs,
-                                loop_start_time,
-                                txn_offset_time.clone(),
-                                loop_stats,
-                            )
-                        }),
-                )
-                .await;
-
-                let submitted_after = loop_start_time.elapsed();
-                if submitted_after.as_secs() > 5 {
-                    sample!(
-                        SampleRate::Duration(Duration::from_secs(30)),
-                        warn!(
-                            "[{:?}] txn_emitter worker waited for more than 5s to submit transactions: {}s after loop start",
-                            self.client().path_prefix_string(),
-                            submitted_after.as_secs(),
-                        )
-                    );
-                }
-
-                if self.skip_latency_stats {
-                    // we also don't want to be stuck waiting for txn_expiration_time_secs
-                    // after stop is called, so we sleep until time or stop is set.
-                    self.sleep_check_done(
-                        Instant::now()
-                            + Duration::from_secs(self.params.txn_expiration_time_secs + 3),
+                let (latest_fetched_counts, latency_samples) = batch
+                    .wait_and_update_stats(
+                        self.client(),
+                        &self.stop,
+                        &self.params,
+                        self.skip_latency_stats,
+                        loop_stats,
                     )
-                    .await
+                    .await;
+                self.update_account_seq_nums(&batch, &latest_fetched_counts);
+
+                if !latency_samples.is_empty() {
+                    // Fetching the blocks of the samples takes additional requests, which would
+                    // delay the next batch, so it happens on its own task.
+                    let client = self.client().clone();
+                    let stats = self.stats.clone();
+                    sampling.retain(|handle| !handle.is_finished());
+                    sampling.push(tokio::spawn(async move {
+                        record_latency_stages(&client, &latency_samples, stats.get(phase)).await
+                    }));
                 }
-
-                self.wait_and_update_stats(
-                    loop_start_time,
-                    txn_offset_time.load(Ordering::Relaxed) / (requests.len() as u64),
-                    account_to_start_and_end_seq_num,
-                    // skip latency if asked to check seq_num only once
-                    // even if we check more often due to stop (to not affect sampling)
-                    self.skip_latency_stats,
-                    txn_expiration_time,
-                    // if we don't care about latency, we can recheck less often.
-                    // generally, we should never need to recheck, as we wait enough time
-                    // before calling here, but in case of shutdown/or client we are talking
-                    // to being stale (having stale transaction_version), we might need to wait.
-                    if self.skip_latency_stats {
-                        (10 * self.params.check_account_sequence_sleep).max(Duration::from_secs(3))
-                    } else {
-                        self.params.check_account_sequence_sleep
-                    },
-                    loop_stats,
-                )
-                .await;
             }
 
             if wait_duration.as_secs() > 0 {
@@ -201,32 +161,123 @@ impl SubmissionWorker {
             }
         }
 
-        self.accounts
-            .into_iter()
-            .map(|account_arc_mutex| Arc::into_inner(account_arc_mutex).unwrap())
-            .collect()
+        for handle in sampling {
+            handle
+                .await
+                .expect("TxnEmitter latency sampling task failed");
+        }
+        self.into_accounts()
     }
 
-    // returns true if it returned early
-    async fn sleep_check_done(&self, sleep_until_time: Instant) {
-        // sleep has millisecond granularity - so round the sleep
-        let sleep_poll_interval = Duration::from_secs(1);
-        loop {
-            if self.stop.load(Ordering::Relaxed) {
-                return;
-            }
+    /// Submits batches at a fixed pace, irrespectively of how long submitting them and waiting
+    /// for them to commit takes. Every arrival is submitted and tracked on its own task, by
+    /// accounts which have no batch in flight, so that their sequence numbers are known. If not
+    /// enough accounts are idle when an arrival is due, the transactions they would have sent
+    /// are counted as missed, instead of delaying the arrivals after them.
+    async fn run_open_loop(mut self, start_instant: Instant) -> Vec<LocalAccount> {
+        let mut wait_until = start_instant + self.start_sleep_duration;
+        let wait_duration = Duration::from_millis(self.params.wait_millis);
+        let busy_accounts = Arc::new(Mutex::new(HashSet::new()));
+        let mut in_flight: Vec<JoinHandle<()>> = Vec::new();
+
+        self.sleep_check_done(wait_until).await;
+        while !self.stop.load(Ordering::Relaxed) {
+            in_flight.retain(|handle| !handle.is_finished());
 
-            let now = Instant::now();
-            if now + ALLOWED_EARLY > sleep_until_time {
-                return;
+            let batch_size = self.num_accounts_per_batch();
+            let accounts = {
+                let mut busy = busy_accounts.lock();
+                let accounts = self
+                    .accounts
+                    .iter()
+                    .filter(|account| !busy.contains(&account.address()))
+                    .cloned()
+                    .choose_multiple(&mut self.rng, batch_size);
+                busy.extend(accounts.iter().map(|account| account.address()));
+                accounts
+            };
+            if accounts.len() < batch_size {
+                self.stats.get_cur().missed_arrivals.fetch_add(
+                    ((batch_size - accounts.len()) * self.params.transactions_per_account) as u64,
+                    Ordering::Relaxed,
+                );
+                sample!(
+                    SampleRate::Duration(Duration::from_secs(60)),
+                    warn!(
+                        "[{:?}] txn_emitter worker missed part of its arrival, as the batches of its accounts are still in flight. More accounts are needed for the target TPS.",
+                        self.client().path_prefix_string(),
+                    )
+                );
             }
 
-            if sleep_until_time > now + sleep_poll_interval {
-                sleep(sleep_poll_interval).await;
+            let requests = self.gen_requests_for(&accounts);
+            if requests.is_empty() {
+                let mut busy = busy_accounts.lock();
+                for account in &accounts {
+                    busy.remove(&account.address());
+                }
             } else {
-                sleep_until(sleep_until_time.into()).await;
+                let client = self.client().clone();
+                let stop = self.stop.clone();
+                let params = self.params.clone();
+                let skip_latency_stats = self.skip_latency_stats;
+                let stats = self.stats.clone();
+                let phase = stats.get_cur_phase();
+                let busy_accounts = busy_accounts.clone();
+                in_flight.push(tokio::spawn(async move {
+                    let loop_stats = stats.get(phase);
+                    let batch = SubmittedBatch::submit(
+                        &client,
+                        &requests,
+                        Instant::now(),
+                        &params,
+                        loop_stats,
+                    )
+                    .await;
+                    let (latest_fetched_counts, latency_samples) = batch
+                        .wait_and_update_stats(
+                            &client,
+                            &stop,
+                            &params,
+                            skip_latency_stats,
+                            loop_stats,
+                        )
+                        .await;
+                    {
+                        let mut busy = busy_accounts.lock();
+                        for account in accounts {
+                            update_account_seq_num(
+                                &account,
+                                &batch.account_to_start_and_end_seq_num,
+                                &latest_fetched_counts,
+                            );
+                            busy.remove(&account.address());
+                        }
+                    }
+                    record_latency_stages(&client, &latency_samples, loop_stats).await;
+                }));
             }
+
+            wait_until += wait_duration;
+            self.sleep_check_done(wait_until).await;
         }
+
+        for handle in in_flight {
+            handle.await.expect("TxnEmitter batch task failed");
+        }
+        self.into_accounts()
+    }
+
+    fn into_accounts(self) -> Vec<LocalAccount> {
+        self.accounts
+            .into_iter()
+            .map(|account_arc_mutex| Arc::into_inner(account_arc_mutex).unwrap())
+            .collect()
+    }
+
+    // returns true if it returned early
+    async fn sleep_check_done(&self, sleep_until_time: Instant) {
+        sleep_until_or_stop(&self.stop, sleep_until_time).await
     }
 
     fn verify_loop_start_drift(&self, loop_start_time: Instant, wait_until: Instant) {
@@ -268,42 +319,216 @@ impl SubmissionWorker {
         }
     }
 
-    /// This function assumes that num_requests == num_accounts, which is
-    /// precisely how gen_requests works. If this changes, this code will
-    /// need to be fixed.
+    fn update_account_seq_nums(
+        &mut self,
+        batch: &SubmittedBatch,
+        latest_fetched_counts: &HashMap<AccountAddress, u64>,
+    ) {
+        for account in &self.accounts {
+            update_account_seq_num(
+                account,
+                &batch.account_to_start_and_end_seq_num,
+                latest_fetched_counts,
+            );
+        }
+    }
+
+    fn num_accounts_per_batch(&self) -> usize {
+        max(
+            1,
+            min(
+                self.params.max_submit_batch_size / self.params.transactions_per_account,
+                self.accounts.len(),
+            ),
+        )
+    }
+
+    fn gen_requests(&mut self) -> Vec<SignedTransaction> {
+        let batch_size = self.num_accounts_per_batch();
+        let accounts = self
+            .accounts
+            .iter()
+            .cloned()
+            .choose_multiple(&mut self.rng, batch_size);
+        self.gen_requests_for(&accounts)
+    }
+
+    fn gen_requests_for(&mut self, accounts: &[Arc<LocalAccount>]) -> Vec<SignedTransaction> {
+        accounts
+            .iter()
+            .flat_map(|account| {
+                self.txn_generator
+                    .generate_transactions(account.borrow(), self.params.transactions_per_account)
+            })
+            .collect()
+    }
+}
+
+async fn sleep_until_or_stop(stop: &AtomicBool, sleep_until_time: Instant) {
+    // sleep has millisecond granularity - so round the sleep
+    let sleep_poll_interval = Duration::from_secs(1);
+    loop {
+        if stop.load(Ordering::Relaxed) {
+            return;
+        }
+
+        let now = Instant::now();
+        if now + ALLOWED_EARLY > sleep_until_time {
+            return;
+        }
+
+        if sleep_until_time > now + sleep_poll_interval {
+            sleep(sleep_poll_interval).await;
+        } else {
+            sleep_until(sleep_until_time.into()).await;
+        }
+    }
+}
+
+/// Batch of transactions submitted by a worker, which is tracked until its transactions are
+/// committed or expired.
+struct SubmittedBatch {
+    loop_start_time: Instant,
+    loop_start_wall_time: SystemTime,
+    account_to_start_and_end_seq_num: HashMap<AccountAddress, (u64, u64)>,
+    txn_expiration_time: u64,
+    avg_txn_offset_time: u64,
+    /// A random transaction of every request accepted by the API, for sampling how the latency is
+    /// split between the stages.
+    latency_samples: Vec<SampledTransaction>,
+}
+
+/// Transaction whose latency is split between the stages, once it is committed.
+#[derive(Clone)]
+struct SampledTransaction {
+    txn: SignedTransaction,
+    submitted_at: SystemTime,
+    accepted_at: SystemTime,
+}
+
+impl SubmittedBatch {
+    async fn submit(
+        client: &RestClient,
+        requests: &[SignedTransaction],
+        loop_start_time: Instant,
+        params: &EmitModeParams,
+        loop_stats: &StatsAccumulator,
+    ) -> Self {
+        let loop_start_wall_time = SystemTime::now();
+        let mut account_to_start_and_end_seq_num = HashMap::new();
+        for req in requests.iter() {
+            let cur = req.sequence_number();
+            let _ = *account_to_start_and_end_seq_num
+                .entry(req.sender())
+                .and_modify(|(start, end)| {
+                    if *start > cur {
+                        *start = cur;
+                    }
+                    if *end < cur + 1 {
+                        *end = cur + 1;
+                    }
+                })
+                .or_insert((cur, cur + 1));
+        }
+
+        let txn_expiration_time = requests
+            .iter()
+            .map(|txn| txn.expiration_timestamp_secs())
+            .max()
+            .unwrap_or(0);
+
+        let txn_offset_time = Arc::new(AtomicU64::new(0));
+
+        let submitted = join_all(requests.chunks(params.max_submit_batch_size).map(|reqs| {
+            let txn_offset_time = txn_offset_time.clone();
+            // Any transaction of the request, so that the samples are not biased towards the
+            // ones at the front of each request.
+            let txn = reqs.choose(&mut rand::thread_rng()).cloned();
+            async move {
+                let submitted_at = SystemTime::now();
+                submit_transactions(client, reqs, loop_start_time, txn_offset_time, loop_stats)
+                    .await
+                    .zip(txn)
+                    .map(|(accepted_at, txn)| SampledTransaction {
+                        txn,
+                        submitted_at,
+                        accepted_at,
+                    })
+            }
+        }))
+        .await;
+
+        let submitted_after = loop_start_time.elapsed();
+        if submitted_after.as_secs() > 5 {
+            sample!(
+                SampleRate::Duration(Duration::from_secs(30)),
+                warn!(
+                    "[{:?}] txn_emitter worker waited for more than 5s to submit transactions: {}s after loop start",
+                    client.path_prefix_string(),
+                    submitted_after.as_secs(),
+                )
+            );
+        }
+
+        Self {
+            loop_start_time,
+            loop_start_wall_time,
+            account_to_start_and_end_seq_num,
+            txn_expiration_time,
+            avg_txn_offset_time: txn_offset_time.load(Ordering::Relaxed) / (requests.len() as u64),
+            latency_samples: submitted.into_iter().flatten().collect(),
+        }
+    }
+
+    /// Waits for the transactions of the batch to be committed or expired, and returns the
+    /// sequence numbers fetched for its accounts, together with the sampled transactions which
+    /// were committed and when their commit was observed.
     ///
     /// Note, the latency values are not accurate if --check-stats-at-end
     /// is used. There is no easy way around this accurately. As such, we
     /// don't update latency at all if that flag is set.
     async fn wait_and_update_stats(
-        &mut self,
-        start_time: Instant,
-        avg_txn_offset_time: u64,
-        account_to_start_and_end_seq_num: HashMap<AccountAddress, (u64, u64)>,
+        &self,
+        client: &RestClient,
+        stop: &AtomicBool,
+        params: &EmitModeParams,
         skip_latency_stats: bool,
-        txn_expiration_ts_secs: u64,
-        check_account_sleep_duration: Duration,
         loop_stats: &StatsAccumulator,
+    ) -> (
+        HashMap<AccountAddress, u64>,
+        Vec<(SampledTransaction, SystemTime)>,
     ) {
-        let (latest_fetched_counts, sum_of_completion_timestamps_millis) =
-            wait_for_accounts_sequence(
-                start_time,
-                self.client(),
-                &account_to_start_and_end_seq_num,
-                txn_expiration_ts_secs,
-                check_account_sleep_duration,
+        if skip_latency_stats {
+            // we also don't want to be stuck waiting for txn_expiration_time_secs
+            // after stop is called, so we sleep until time or stop is set.
+            sleep_until_or_stop(
+                stop,
+                Instant::now() + Duration::from_secs(params.txn_expiration_time_secs + 3),
             )
-            .await;
-
-        for account in self.accounts.iter_mut() {
-            update_account_seq_num(
-                Arc::get_mut(account).unwrap(),
-                &account_to_start_and_end_seq_num,
-                &latest_fetched_counts,
-            );
+            .await
         }
-        let (num_committed, num_expired) =
-            count_committed_expired_stats(account_to_start_and_end_seq_num, latest_fetched_counts);
+
+        let (latest_fetched_counts, committed) = wait_for_accounts_sequence(
+            self.loop_start_time,
+            client,
+            &self.account_to_start_and_end_seq_num,
+            self.txn_expiration_time,
+            // if we don't care about latency, we can recheck less often.
+            // generally, we should never need to recheck, as we wait enough time
+            // before calling here, but in case of shutdown/or client we are talking
+            // to being stale (having stale transaction_version), we might need to wait.
+            if skip_latency_stats {
+                (10 * params.check_account_sequence_sleep).max(Duration::from_secs(3))
+            } else {
+                params.check_account_sequence_sleep
+            },
+        )
+        .await;
+
+        let (num_committed, num_expired) = count_committed_expired_stats(
+            &self.account_to_start_and_end_seq_num,
+            &latest_fetched_counts,
+        );
 
         if num_expired > 0 {
             loop_stats
@@ -313,63 +538,135 @@ impl SubmissionWorker {
                 SampleRate::Duration(Duration::from_secs(60)),
                 warn!(
                     "[{:?}] Transactions were not committed before expiration: {:?}, for {:?}",
-                    self.client().path_prefix_string(),
+                    client.path_prefix_string(),
                     num_expired,
-                    self.accounts
-                        .iter()
-                        .map(|a| a.address())
+                    self.account_to_start_and_end_seq_num
+                        .keys()
                         .collect::<Vec<_>>(),
                 )
             );
         }
 
+        // skip latency if asked to check seq_num only once
+        // even if we check more often due to stop (to not affect sampling)
+        let mut latency_samples = vec![];
         if num_committed > 0 {
             loop_stats
                 .committed
                 .fetch_add(num_committed as u64, Ordering::Relaxed);
 
             if !skip_latency_stats {
-                let sum_latency = sum_of_completion_timestamps_millis
-                    - (avg_txn_offset_time as u128 * num_committed as u128);
-                let avg_latency = (sum_latency / num_committed as u128) as u64;
-                loop_stats
-                    .latency
-                    .fetch_add(sum_latency as u64, Ordering::Relaxed);
-                loop_stats
-                    .latency_samples
-                    .fetch_add(num_committed as u64, Ordering::Relaxed);
-                loop_stats
-                    .latencies
-                    .record_data_point(avg_latency, num_committed as u64);
+                // Each transaction's latency is from its submission until the poll which
+                // observed it committed.
+                for txns in &committed {
+                    let count = txns.sequence_numbers.end - txns.sequence_numbers.start;
+                    let latency = txns.millis_elapsed.saturating_sub(self.avg_txn_offset_time);
+                    loop_stats
+                        .latency
+                        .fetch_add(latency * count, Ordering::Relaxed);
+                    loop_stats
+                        .latency_samples
+                        .fetch_add(count, Ordering::Relaxed);
+                    loop_stats.latencies.record_data_point(latency, count);
+                    loop_stats.latency_histograms.record_total(latency, count);
+                }
+
+                latency_samples = self.committed_samples(&committed);
             }
         }
+
+        (latest_fetched_counts, latency_samples)
     }
 
-    fn gen_requests(&mut self) -> Vec<SignedTransaction> {
-        let batch_size = max(
-            1,
-            min(
-                self.params.max_submit_batch_size / self.params.transactions_per_account,
-                self.accounts.len(),
-            ),
-        );
-        let accounts = self
-            .accounts
+    /// The sampled transactions which were committed, with when the commit was observed.
+    fn committed_samples(
+        &self,
+        committed: &[CommittedTransactions],
+    ) -> Vec<(SampledTransaction, SystemTime)> {
+        self.latency_samples
             .iter()
-            .choose_multiple(&mut self.rng, batch_size);
-
-        accounts
-            .into_iter()
-            .flat_map(|account| {
-                self.txn_generator
-                    .generate_transactions(account.borrow(), self.params.transactions_per_account)
+            .filter_map(|sample| {
+                let observed = committed.iter().find(|txns| {
+                    txns.address == sample.txn.sender()
+                        && txns
+                            .sequence_numbers
+                            .contains(&sample.txn.sequence_number())
+                })?;
+                let committed_at =
+                    self.loop_start_wall_time + Duration::from_millis(observed.millis_elapsed);
+                Some((sample.clone(), committed_at))
             })
             .collect()
     }
 }
 
+/// Splits the latency of the committed sampled transactions between the stages, at the
+/// timestamps of the block each of them was committed in and of the next block.
+async fn record_latency_stages(
+    client: &RestClient,
+    samples: &[(SampledTransaction, SystemTime)],
+    loop_stats: &StatsAccumulator,
+) {
+    join_all(samples.iter().map(|(sample, committed_at)| {
+        record_sample_latency_stages(client, sample, *committed_at, loop_stats)
+    }))
+    .await;
+}
+
+async fn record_sample_latency_stages(
+    client: &RestClient,
+    sample: &SampledTransaction,
+    committed_at: SystemTime,
+    loop_stats: &StatsAccumulator,
+) {
+    let txn = &sample.txn;
+    let block_timestamps = async {
+        let version = client
+            .get_transaction_by_hash(txn.committed_hash())
+            .await?
+            .into_inner()
+            .version()
+            .ok_or_else(|| format_err!("Transaction is still pending"))?;
+        let block = client
+            .get_block_by_version_bcs(version, false)
+            .await?
+            .into_inner();
+        let next_block = client
+            .get_block_by_height_bcs(block.block_height + 1, false)
+            .await?
+            .into_inner();
+        anyhow::Ok((block.block_timestamp, next_block.block_timestamp))
+    };
+    match block_timestamps.await {
+        Ok((proposed_usecs, certified_usecs)) => {
+            let proposed_at = UNIX_EPOCH + Duration::from_micros(proposed_usecs);
+            let certified_at = UNIX_EPOCH + Duration::from_micros(certified_usecs);
+            let millis_between = |from: SystemTime, to: SystemTime| {
+                to.duration_since(from).unwrap_or_default().as_millis() as u64
+            };
+            loop_stats.latency_histograms.record_stages(
+                millis_between(sample.submitted_at, sample.accepted_at),
+                millis_between(sample.accepted_at, proposed_at),
+                millis_between(proposed_at, certified_at),
+                millis_between(certified_at, committed_at),
+            );
+        },
+        Err(e) => {
+            sample!(
+                SampleRate::Duration(Duration::from_secs(60)),
+                warn!(
+                    "[{:?}] Failed to fetch the blocks of committed transaction {}: {:?}",
+                    client.path_prefix_string(),
+                    txn.committed_hash(),
+                    e
+                )
+            );
+        },
+    }
+}
+
 fn update_account_seq_num(
-    account: &mut LocalAccount,
+    account: &LocalAccount,
     account_to_start_and_end_seq_num: &HashMap<AccountAddress, (u64, u64)>,
     latest_fetched_counts: &HashMap<AccountAddress, u64>,
 ) {
@@ -407,8 +704,8 @@ fn update_account_seq_num(
 }
 
 fn count_committed_expired_stats(
-    account_to_start_and_end_seq_num: HashMap<AccountAddress, (u64, u64)>,
-    latest_fetched_counts: HashMap<AccountAddress, u64>,
+    account_to_start_and_end_seq_num: &HashMap<AccountAddress, (u64, u64)>,
+    latest_fetched_counts: &HashMap<AccountAddress, u64>,
 ) -> (usize, usize) {
     account_to_start_and_end_seq_num
         .iter()
@@ -443,13 +740,14 @@ fn count_committed_expired_stats(
         )
 }
 
+/// Submits the transactions, returning when the API accepted them, if it did.
 pub async fn submit_transactions(
     client: &RestClient,
     txns: &[SignedTransaction],
     loop_start_time: Instant,
     txn_offset_time: Arc<AtomicU64>,
     stats: &StatsAccumulator,
-) {
+) -> Option<SystemTime> {
     let cur_time = Instant::now();
     let offset = cur_time - loop_start_time;
     txn_offset_time.fetch_add(
@@ -473,8 +771,13 @@ pub async fn submit_transactions(
                     e
                 )
             );
+            None
         },
         Ok(v) => {
+            let accepted_at = SystemTime::now();
+            stats
+                .latency_histograms
+                .record_api_accept(cur_time.elapsed().as_millis() as u64, txns.len() as u64);
             let failures = v.into_inner().transaction_failures;
 
             stats
@@ -527,6 +830,7 @@ pub async fn submit_transactions(
                     );
                 });
             }
+            Some(accepted_at)
         },
-    };
+    }
 }
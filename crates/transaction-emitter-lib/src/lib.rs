@@ -7,15 +7,24 @@ mod args;
 mod cluster;
 pub mod emitter;
 mod instance;
+mod max_tps_search;
+mod trace_export;
 mod wrappers;
 
 // These are the top level things you should need to run the emitter.
-pub use args::{ClusterArgs, CoinSourceArgs, CreateAccountsArgs, EmitArgs};
+pub use args::{
+    ClusterArgs, CoinSourceArgs, CreateAccountsArgs, EmitArgs, ExportTraceArgs, MaxTpsSearchArgs,
+};
 // We export these if you want finer grained control.
 pub use cluster::Cluster;
 pub use emitter::{
     query_sequence_number, query_sequence_numbers,
-    stats::{TxnStats, TxnStatsRate},
+    stats::{TxnStats, TxnStatsRate, TxnStatsReport},
     EmitJob, EmitJobMode, EmitJobRequest, EmitModeParams, TxnEmitter,
 };
-pub use wrappers::{create_accounts_command, emit_transactions, emit_transactions_with_cluster};
+pub use max_tps_search::{search_max_tps, MaxTpsProbe, MaxTpsSearchReport};
+pub use trace_export::export_workload_trace;
+pub use wrappers::{
+    create_accounts_command, emit_transactions, emit_transactions_with_cluster,
+    export_trace_command, search_max_tps_command,
+};
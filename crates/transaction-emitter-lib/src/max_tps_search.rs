@@ -0,0 +1,147 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    args::{EmitArgs, MaxTpsSearchArgs},
+    cluster::Cluster,
+    emitter::stats::{TxnStats, TxnStatsReport},
+    wrappers::emit_transactions_with_cluster,
+};
+use anyhow::{bail, Result};
+use aptos_logger::info;
+use serde::Serialize;
+
+/// Fraction of the submitted transactions which may fail, expire or miss their arrival at a
+/// sustained TPS.
+const MAX_UNSUCCESSFUL_FRACTION: f64 = 0.01;
+
+#[derive(Debug, Serialize)]
+pub struct MaxTpsProbe {
+    pub target_tps: usize,
+    pub sustained: bool,
+    pub stats: TxnStatsReport,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MaxTpsSearchReport {
+    pub p99_latency_target_ms: u64,
+    /// Highest probed TPS which was sustained, if any was.
+    pub max_tps: Option<usize>,
+    pub probes: Vec<MaxTpsProbe>,
+}
+
+/// Searches for the highest TPS at which the p99 submit to commit latency stays within the
+/// target. Each probe runs the emitter in open-loop mode for the duration of `emit_args`,
+/// starting from its target TPS, which is doubled or halved until the max TPS is bracketed,
+/// and then bisected.
+pub async fn search_max_tps(
+    cluster: &Cluster,
+    emit_args: &EmitArgs,
+    search_args: &MaxTpsSearchArgs,
+) -> Result<MaxTpsSearchReport> {
+    let Some(start_tps) = emit_args.target_tps else {
+        bail!("Searching for max TPS needs --target-tps to start from");
+    };
+    if emit_args.replay_trace.is_some() {
+        bail!("Searching for max TPS is not supported when replaying a trace");
+    }
+    if search_args.min_tps == 0 || search_args.min_tps > search_args.max_tps {
+        bail!(
+            "Invalid TPS range [{}, {}]",
+            search_args.min_tps,
+            search_args.max_tps
+        );
+    }
+
+    let mut report = MaxTpsSearchReport {
+        p99_latency_target_ms: search_args.p99_latency_target_ms,
+        max_tps: None,
+        probes: vec![],
+    };
+    let mut lowest_unsustained = None;
+    let mut next_tps = Some(start_tps.clamp(search_args.min_tps, search_args.max_tps));
+    while let Some(target_tps) = next_tps {
+        let mut probe_args = emit_args.clone();
+        probe_args.mempool_backlog = None;
+        probe_args.target_tps = Some(target_tps);
+        probe_args.open_loop = true;
+        probe_args.latency_report_file = None;
+
+        info!("Probing {} TPS", target_tps);
+        let stats = emit_transactions_with_cluster(cluster, &probe_args).await?;
+        let sustained = is_sustained(&stats, search_args.p99_latency_target_ms);
+        info!(
+            "{} TPS was {}: {}",
+            target_tps,
+            if sustained {
+                "sustained"
+            } else {
+                "not sustained"
+            },
+            stats.rate()
+        );
+        if sustained {
+            report.max_tps = Some(target_tps);
+        } else {
+            lowest_unsustained = Some(target_tps);
+        }
+        report.probes.push(MaxTpsProbe {
+            target_tps,
+            sustained,
+            stats: stats.report(),
+        });
+        next_tps = next_probe(report.max_tps, lowest_unsustained, search_args);
+    }
+    Ok(report)
+}
+
+fn is_sustained(stats: &TxnStats, p99_latency_target_ms: u64) -> bool {
+    let unsuccessful = stats.expired + stats.failed_submission + stats.missed_arrivals;
+    stats.committed > 0
+        && stats.latency_histograms.total.value_at_quantile(0.99) <= p99_latency_target_ms
+        && unsuccessful as f64
+            <= MAX_UNSUCCESSFUL_FRACTION * (stats.submitted + stats.missed_arrivals) as f64
+}
+
+fn next_probe(
+    highest_sustained: Option<usize>,
+    lowest_unsustained: Option<usize>,
+    search_args: &MaxTpsSearchArgs,
+) -> Option<usize> {
+    match (highest_sustained, lowest_unsustained) {
+        (Some(low), None) => {
+            (low < search_args.max_tps).then(|| (2 * low).min(search_args.max_tps))
+        },
+        (None, Some(high)) => {
+            (high > search_args.min_tps).then(|| (high / 2).max(search_args.min_tps))
+        },
+        (Some(low), Some(high)) => {
+            (high.saturating_sub(low) > search_args.precision_tps).then(|| low + (high - low) / 2)
+        },
+        (None, None) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_probe() {
+        let search_args = MaxTpsSearchArgs {
+            p99_latency_target_ms: 1000,
+            min_tps: 10,
+            max_tps: 1000,
+            precision_tps: 50,
+            report_file: None,
+        };
+        assert_eq!(next_probe(Some(100), None, &search_args), Some(200));
+        assert_eq!(next_probe(Some(800), None, &search_args), Some(1000));
+        assert_eq!(next_probe(Some(1000), None, &search_args), None);
+        assert_eq!(next_probe(None, Some(100), &search_args), Some(50));
+        assert_eq!(next_probe(None, Some(15), &search_args), Some(10));
+        assert_eq!(next_probe(None, Some(10), &search_args), None);
+        assert_eq!(next_probe(Some(200), Some(400), &search_args), Some(300));
+        assert_eq!(next_probe(Some(300), Some(350), &search_args), None);
+    }
+}
@@ -0,0 +1,217 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{Context, Result};
+use aptos_framework::natives::code::PackageRegistry;
+use aptos_logger::{info, warn};
+use aptos_rest_client::Client as RestClient;
+use aptos_sdk::move_types::language_storage::{ModuleId, TypeTag};
+use aptos_transaction_generator_lib::workload_trace::{
+    entry_function_argument_types, TracedPackage, WorkloadTrace,
+};
+use aptos_types::{
+    account_address::AccountAddress,
+    transaction::{Transaction, TransactionPayload},
+};
+use move_binary_format::CompiledModule;
+use std::collections::{BTreeSet, HashMap};
+
+const EXPORT_BATCH_SIZE: u16 = 100;
+
+/// Types of the arguments of an entry function payload, resolved with the module called as of
+/// `version`.
+async fn argument_types(
+    client: &RestClient,
+    modules: &mut HashMap<ModuleId, CompiledModule>,
+    payload: &TransactionPayload,
+    version: u64,
+) -> Result<Vec<TypeTag>> {
+    let TransactionPayload::EntryFunction(entry_function) = payload else {
+        return Ok(vec![]);
+    };
+    let module_id = entry_function.module();
+    if !modules.contains_key(module_id) {
+        let bytes = client
+            .get_account_module_bcs_at_version(
+                *module_id.address(),
+                module_id.name().as_str(),
+                version,
+            )
+            .await?
+            .into_inner();
+        let module = CompiledModule::deserialize(&bytes)
+            .with_context(|| format!("Failed to deserialize module {}", module_id))?;
+        modules.insert(module_id.clone(), module);
+    }
+    entry_function_argument_types(&modules[module_id], entry_function)
+}
+
+/// Timestamps transactions with the block they are in, as they are read in order.
+struct BlockClock {
+    timestamp_usecs: u64,
+}
+
+impl BlockClock {
+    /// Starts at the block containing `version`, so that an export starting in the middle of a
+    /// block does not timestamp the transactions before the next block with 0.
+    async fn at_version(client: &RestClient, version: u64) -> Result<Self> {
+        let block = client
+            .get_block_by_version_bcs(version, false)
+            .await
+            .with_context(|| format!("Failed to fetch the block of version {}", version))?
+            .into_inner();
+        Ok(Self {
+            timestamp_usecs: block.block_timestamp,
+        })
+    }
+
+    /// Timestamp of `txn`, which must directly follow the transaction seen before.
+    fn timestamp_usecs(&mut self, txn: &Transaction) -> u64 {
+        match txn {
+            Transaction::BlockMetadata(block) => self.timestamp_usecs = block.timestamp_usecs(),
+            Transaction::BlockMetadataExt(block) => self.timestamp_usecs = block.timestamp_usecs(),
+            _ => {},
+        }
+        self.timestamp_usecs
+    }
+}
+
+/// Exports `num_transactions` user transactions committed from `start_version` on, together
+/// with the packages they reference (and their dependencies), as of the last exported version.
+pub async fn export_workload_trace(
+    client: &RestClient,
+    start_version: u64,
+    num_transactions: usize,
+) -> Result<WorkloadTrace> {
+    let mut trace = WorkloadTrace::default();
+    let mut next_version = start_version;
+    let mut clock = BlockClock::at_version(client, start_version).await?;
+    let mut modules = HashMap::new();
+    while trace.transactions.len() < num_transactions {
+        let txns = client
+            .get_transactions_bcs(Some(next_version), Some(EXPORT_BATCH_SIZE))
+            .await?
+            .into_inner();
+        if txns.is_empty() {
+            break;
+        }
+        for txn in txns {
+            if trace.transactions.len() == num_transactions {
+                break;
+            }
+            next_version = txn.version + 1;
+            let timestamp_usecs = clock.timestamp_usecs(&txn.transaction);
+            if let Transaction::UserTransaction(user_txn) = &txn.transaction {
+                let argument_types =
+                    argument_types(client, &mut modules, user_txn.payload(), txn.version)
+                        .await
+                        .with_context(|| {
+                            format!("Failed to resolve arguments of version {}", txn.version)
+                        })?;
+                trace.push_transaction(timestamp_usecs, user_txn, argument_types)
+            }
+        }
+        info!(
+            "Exported {} transactions, up to version {}",
+            trace.transactions.len(),
+            next_version - 1
+        );
+    }
+
+    let version = next_version.saturating_sub(1);
+    let mut pending = trace
+        .referenced_package_addresses()
+        .into_iter()
+        .collect::<Vec<_>>();
+    let mut visited = BTreeSet::new();
+    while let Some(address) = pending.pop() {
+        if !visited.insert(address) {
+            continue;
+        }
+        let registry = match client
+            .get_account_resource_at_version_bcs::<PackageRegistry>(
+                address,
+                "0x1::code::PackageRegistry",
+                version,
+            )
+            .await
+        {
+            Ok(registry) => registry.into_inner(),
+            Err(err) => {
+                warn!("No packages found at {}: {:#}", address, err);
+                continue;
+            },
+        };
+        for metadata in registry.packages {
+            let mut modules = Vec::with_capacity(metadata.modules.len());
+            for module in &metadata.modules {
+                modules.push(
+                    client
+                        .get_account_module_bcs_at_version(address, &module.name, version)
+                        .await?
+                        .into_inner()
+                        .to_vec(),
+                );
+            }
+            pending.extend(
+                metadata
+                    .deps
+                    .iter()
+                    .map(|dep| dep.account)
+                    .filter(|account: &AccountAddress| !account.is_special()),
+            );
+            trace.packages.push(TracedPackage {
+                address,
+                metadata,
+                modules,
+            });
+        }
+    }
+    info!(
+        "Exported {} transactions and {} packages",
+        trace.transactions.len(),
+        trace.packages.len()
+    );
+    Ok(trace)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_crypto::HashValue;
+    use aptos_sdk::{transaction_builder::TransactionFactory, types::LocalAccount};
+    use aptos_types::{block_metadata::BlockMetadata, chain_id::ChainId};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn test_mid_block_timestamps() {
+        let account = LocalAccount::generate(&mut StdRng::from_seed([0; 32]));
+        let factory = TransactionFactory::new(ChainId::test());
+        let user_txn = || {
+            Transaction::UserTransaction(
+                account.sign_with_transaction_builder(factory.transfer(AccountAddress::ONE, 1)),
+            )
+        };
+        let block = |timestamp_usecs| {
+            Transaction::BlockMetadata(BlockMetadata::new(
+                HashValue::zero(),
+                0,
+                0,
+                AccountAddress::ZERO,
+                vec![],
+                vec![],
+                timestamp_usecs,
+            ))
+        };
+
+        // The export starts in the middle of the block at 1_000, as fetched for the start version.
+        let mut clock = BlockClock {
+            timestamp_usecs: 1_000,
+        };
+        let timestamps = [user_txn(), user_txn(), block(2_000), user_txn()]
+            .iter()
+            .map(|txn| clock.timestamp_usecs(txn))
+            .collect::<Vec<_>>();
+        assert_eq!(timestamps, vec![1_000, 1_000, 2_000, 2_000]);
+    }
+}
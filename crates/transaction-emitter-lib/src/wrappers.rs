@@ -13,12 +13,18 @@ use crate::{
         EmitJobMode, EmitJobRequest, NumAccountsMode, TxnEmitter,
     },
     instance::Instance,
-    CreateAccountsArgs,
+    max_tps_search::{search_max_tps, MaxTpsSearchReport},
+    trace_export::export_workload_trace,
+    CreateAccountsArgs, ExportTraceArgs, MaxTpsSearchArgs,
 };
 use anyhow::{bail, Context, Result};
 use aptos_logger::{error, info};
+use aptos_rest_client::Client as RestClient;
 use aptos_sdk::transaction_builder::TransactionFactory;
-use aptos_transaction_generator_lib::{args::TransactionTypeArg, AccountType, WorkflowProgress};
+use aptos_transaction_generator_lib::{
+    args::TransactionTypeArg, workload_spec::WorkloadSpec, workload_trace::WorkloadTrace,
+    AccountType, TransactionType, WorkflowProgress,
+};
 use aptos_types::{account_address::AccountAddress, keyless::test_utils::get_sample_esk};
 use rand::{rngs::StdRng, SeedableRng};
 use std::{
@@ -77,7 +83,35 @@ pub async fn emit_transactions_with_cluster(
     cluster: &Cluster,
     args: &EmitArgs,
 ) -> Result<TxnStats> {
-    let emitter_mode = EmitJobMode::create(args.mempool_backlog, args.target_tps);
+    let replay_trace = args
+        .replay_trace
+        .as_deref()
+        .map(WorkloadTrace::load)
+        .transpose()?
+        .map(Arc::new);
+    let replay_rate_scale = args.replay_rate_scale.unwrap_or(1.0);
+    let target_tps = match &replay_trace {
+        Some(trace) => {
+            let original_tps = trace
+                .original_tps()
+                .context("Trace must span a positive amount of time to be replayed")?;
+            // Transactions are submitted when they are due in the trace, so workers only need
+            // to keep up with its peak rate.
+            let peak_tps = trace.peak_tps() as f64 * replay_rate_scale;
+            info!(
+                "Replaying trace at {:.1} TPS on average and {:.1} TPS at peak, original average rate is {:.1} TPS",
+                original_tps * replay_rate_scale,
+                peak_tps,
+                original_tps
+            );
+            Some((peak_tps.ceil() as usize).max(1))
+        },
+        None => args.target_tps,
+    };
+    let emitter_mode = match EmitJobMode::create(args.mempool_backlog, target_tps) {
+        EmitJobMode::ConstTps { tps } if args.open_loop => EmitJobMode::OpenLoop { tps },
+        mode => mode,
+    };
 
     let duration = Duration::from_secs(args.duration);
     let client = cluster.random_instance().rest_client();
@@ -90,14 +124,30 @@ pub async fn emit_transactions_with_cluster(
         client,
     );
 
-    let transaction_mix_per_phase = TransactionTypeArg::args_to_transaction_mix_per_phase(
-        &args.transaction_type,
-        &args.transaction_weights,
-        &args.transaction_phases,
-        args.module_working_set_size.unwrap_or(1),
-        args.sender_use_account_pool.unwrap_or(false),
-        WorkflowProgress::when_done_default(),
-    );
+    let workload_spec = args
+        .workload_spec
+        .as_deref()
+        .map(WorkloadSpec::load)
+        .transpose()?
+        .map(Arc::new);
+    let transaction_mix_per_phase = match (replay_trace, workload_spec) {
+        (Some(trace), _) => vec![vec![(
+            TransactionType::ReplayTrace {
+                trace,
+                rate_scale: replay_rate_scale,
+            },
+            1,
+        )]],
+        (None, Some(spec)) => spec.transaction_mix_per_phase(),
+        (None, None) => TransactionTypeArg::args_to_transaction_mix_per_phase(
+            &args.transaction_type,
+            &args.transaction_weights,
+            &args.transaction_phases,
+            args.module_working_set_size.unwrap_or(1),
+            args.sender_use_account_pool.unwrap_or(false),
+            WorkflowProgress::when_done_default(),
+        ),
+    };
     let mut emit_job_request =
         EmitJobRequest::new(cluster.all_instances().map(Instance::rest_client).collect())
             .mode(emitter_mode)
@@ -197,6 +247,19 @@ pub async fn emit_transactions_with_cluster(
             (args.duration / 10).clamp(1, 10),
         )
         .await?;
+
+    if let Some(latency_report_file) = &args.latency_report_file {
+        std::fs::write(
+            latency_report_file,
+            serde_json::to_string_pretty(&stats.report())?,
+        )
+        .with_context(|| {
+            format!(
+                "Failed to write latency report to {}",
+                latency_report_file.display()
+            )
+        })?;
+    }
     Ok(stats)
 }
 
@@ -260,3 +323,32 @@ pub async fn create_accounts_command(
 
     Ok(())
 }
+
+pub async fn export_trace_command(args: &ExportTraceArgs) -> Result<()> {
+    let client = RestClient::new(args.url.clone());
+    let trace = export_workload_trace(&client, args.start_version, args.num_transactions).await?;
+    trace.save(&args.output_file)?;
+    info!(
+        "Wrote trace with {} transactions and {} packages to {}",
+        trace.transactions.len(),
+        trace.packages.len(),
+        args.output_file.display()
+    );
+    Ok(())
+}
+
+pub async fn search_max_tps_command(
+    cluster_args: &ClusterArgs,
+    emit_args: &EmitArgs,
+    search_args: &MaxTpsSearchArgs,
+) -> Result<MaxTpsSearchReport> {
+    let cluster = Cluster::try_from_cluster_args(cluster_args)
+        .await
+        .context("Failed to build cluster")?;
+    let report = search_max_tps(&cluster, emit_args, search_args).await?;
+    if let Some(report_file) = &search_args.report_file {
+        std::fs::write(report_file, serde_json::to_string_pretty(&report)?)
+            .with_context(|| format!("Failed to write report to {}", report_file.display()))?;
+    }
+    Ok(report)
+}
@@ -7,7 +7,8 @@ mod diag;
 use anyhow::{Context, Result};
 use aptos_logger::{Level, Logger};
 use aptos_transaction_emitter_lib::{
-    create_accounts_command, emit_transactions, Cluster, ClusterArgs, CreateAccountsArgs, EmitArgs,
+    create_accounts_command, emit_transactions, export_trace_command, search_max_tps_command,
+    Cluster, ClusterArgs, CreateAccountsArgs, EmitArgs, ExportTraceArgs, MaxTpsSearchArgs,
 };
 use clap::{Parser, Subcommand};
 use diag::diag;
@@ -28,6 +29,14 @@ enum TxnEmitterCommand {
     /// Create test accounts, for use with EmitTx
     CreateAccounts(CreateAccounts),
 
+    /// Export a trace of committed transactions from a node, which can be replayed
+    /// with EmitTx --replay-trace
+    ExportTrace(ExportTraceArgs),
+
+    /// Search for the highest TPS the network sustains within a p99 latency target, by
+    /// repeatedly running EmitTx in open-loop mode
+    SearchMaxTps(SearchMaxTps),
+
     /// This runs the transaction emitter in diag mode, where the focus is on
     /// FullNodes instead of ValidatorNodes. This performs a simple health check.
     Diag(Diag),
@@ -46,6 +55,18 @@ struct EmitTx {
     emit_args: EmitArgs,
 }
 
+#[derive(Parser, Debug)]
+struct SearchMaxTps {
+    #[clap(flatten)]
+    cluster_args: ClusterArgs,
+
+    #[clap(flatten)]
+    emit_args: EmitArgs,
+
+    #[clap(flatten)]
+    search_args: MaxTpsSearchArgs,
+}
+
 #[derive(Parser, Debug)]
 struct CreateAccounts {
     #[clap(flatten)]
@@ -91,6 +112,41 @@ pub async fn main() -> Result<()> {
                 .unwrap();
             Ok(())
         },
+        TxnEmitterCommand::SearchMaxTps(args) => {
+            let report =
+                search_max_tps_command(&args.cluster_args, &args.emit_args, &args.search_args)
+                    .await
+                    .context("Search for max TPS failed")?;
+            for probe in &report.probes {
+                println!(
+                    "{} TPS: {}, p99 latency: {} ms",
+                    probe.target_tps,
+                    if probe.sustained {
+                        "sustained"
+                    } else {
+                        "not sustained"
+                    },
+                    probe.stats.latency.total.p99_ms
+                );
+            }
+            match report.max_tps {
+                Some(max_tps) => println!(
+                    "Max TPS within p99 latency of {} ms: {}",
+                    report.p99_latency_target_ms, max_tps
+                ),
+                None => println!(
+                    "No probed TPS was sustained within p99 latency of {} ms",
+                    report.p99_latency_target_ms
+                ),
+            }
+            Ok(())
+        },
+        TxnEmitterCommand::ExportTrace(args) => {
+            export_trace_command(&args)
+                .await
+                .context("Export trace failed")?;
+            Ok(())
+        },
         TxnEmitterCommand::Diag(args) => {
             let cluster = Cluster::try_from_cluster_args(&args.cluster_args)
                 .await
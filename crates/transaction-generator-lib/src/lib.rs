@@ -35,8 +35,12 @@ mod entry_points;
 mod p2p_transaction_generator;
 pub mod publish_modules;
 pub mod publishing;
+mod replay_trace_generator;
 mod transaction_mix_generator;
 mod workflow_delegator;
+pub mod workload_spec;
+mod workload_spec_generator;
+pub mod workload_trace;
 use self::{
     account_generator::AccountGeneratorCreator,
     call_custom_modules::CustomModulesDelegationGeneratorCreator,
@@ -47,8 +51,13 @@ use self::{
 use crate::{
     accounts_pool_wrapper::AccountsPoolWrapperCreator,
     batch_transfer::BatchTransferTransactionGeneratorCreator,
-    entry_points::EntryPointTransactionGenerator, p2p_transaction_generator::SamplingMode,
+    entry_points::EntryPointTransactionGenerator,
+    p2p_transaction_generator::SamplingMode,
+    replay_trace_generator::ReplayTraceGeneratorCreator,
     workflow_delegator::WorkflowTxnGeneratorCreator,
+    workload_spec::WorkloadSpec,
+    workload_spec_generator::{DeployedWorkloadSpec, WorkloadSpecGeneratorCreator},
+    workload_trace::WorkloadTrace,
 };
 pub use publishing::module_simple::EntryPoints;
 
@@ -89,6 +98,19 @@ pub enum TransactionType {
         use_account_pool: bool,
         progress_type: WorkflowProgress,
     },
+    /// Replays the transactions of a trace, each account of the trace replaced by an account
+    /// of the accounts pool, with the times between them divided by `rate_scale`.
+    ReplayTrace {
+        trace: Arc<WorkloadTrace>,
+        rate_scale: f64,
+    },
+    /// Transaction `index` of phase `phase` of a workload spec. Packages and pools of the spec
+    /// are deployed once, for all of its transactions.
+    WorkloadSpec {
+        spec: Arc<WorkloadSpec>,
+        phase: usize,
+        index: usize,
+    },
 }
 
 #[derive(Debug, Copy, Clone, ValueEnum, Default, Deserialize, Parser, Serialize)]
@@ -246,11 +268,11 @@ pub async fn create_txn_generator_creator(
     txn_factory: &TransactionFactory,
     init_txn_factory: &TransactionFactory,
     cur_phase: Arc<AtomicUsize>,
-) -> (
+) -> Result<(
     Box<dyn TransactionGeneratorCreator>,
     Arc<ObjectPool<AccountAddress>>,
     Arc<ObjectPool<LocalAccount>>,
-) {
+)> {
     let addresses_pool = Arc::new(ObjectPool::new_initial(
         source_accounts
             .iter()
@@ -263,6 +285,7 @@ pub async fn create_txn_generator_creator(
     let mut txn_generator_creator_mix_per_phase: Vec<
         Vec<(Box<dyn TransactionGeneratorCreator>, usize)>,
     > = Vec::new();
+    let mut deployed_workload_specs = Vec::new();
 
     fn wrap_accounts_pool(
         inner: Box<dyn TransactionGeneratorCreator>,
@@ -396,20 +419,51 @@ pub async fn create_txn_generator_creator(
                     )
                     .await,
                 ),
+                TransactionType::ReplayTrace { trace, rate_scale } => Box::new(
+                    ReplayTraceGeneratorCreator::new(
+                        txn_factory.clone(),
+                        init_txn_factory.clone(),
+                        trace,
+                        *rate_scale,
+                        &accounts_pool,
+                        txn_executor,
+                    )
+                    .await?,
+                ),
+                TransactionType::WorkloadSpec { spec, phase, index } => {
+                    let deployment = DeployedWorkloadSpec::get_or_deploy(
+                        &mut deployed_workload_specs,
+                        spec,
+                        &root_account,
+                        txn_executor,
+                        init_txn_factory,
+                    )
+                    .await;
+                    let txn = &spec.phases[*phase][*index];
+                    wrap_accounts_pool(
+                        Box::new(WorkloadSpecGeneratorCreator::new(
+                            txn_factory.clone(),
+                            deployment,
+                            &txn.call,
+                        )),
+                        txn.use_account_pool,
+                        &accounts_pool,
+                    )
+                },
             };
             txn_generator_creator_mix.push((txn_generator_creator, *weight));
         }
         txn_generator_creator_mix_per_phase.push(txn_generator_creator_mix)
     }
 
-    (
+    Ok((
         Box::new(PhasedTxnMixGeneratorCreator::new(
             txn_generator_creator_mix_per_phase,
             cur_phase,
         )),
         addresses_pool,
         accounts_pool,
-    )
+    ))
 }
 
 /// Simple object pool structure, that you can add and remove from multiple threads.
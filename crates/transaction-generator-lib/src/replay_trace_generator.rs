@@ -0,0 +1,261 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    workload_trace::{AddressRemapper, WorkloadTrace},
+    ObjectPool, ReliableTransactionSubmitter, TransactionGenerator, TransactionGeneratorCreator,
+};
+use anyhow::{bail, ensure, Context, Result};
+use aptos_infallible::Mutex;
+use aptos_logger::info;
+use aptos_sdk::{
+    transaction_builder::TransactionFactory,
+    types::{
+        account_address::AccountAddress,
+        transaction::{SignedTransaction, TransactionPayload},
+        LocalAccount,
+    },
+};
+use rand::{rngs::StdRng, SeedableRng};
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+/// Transaction of the trace, with its accounts replaced by indices into the replay accounts.
+struct ReplayedTransaction {
+    /// Time since the first replayed transaction of the trace was committed.
+    offset: Duration,
+    sender: usize,
+    secondary_signers: Vec<usize>,
+    fee_payer: Option<usize>,
+    payload: TransactionPayload,
+}
+
+/// Position of the replay in the trace, which is replayed in a loop.
+struct ReplaySchedule {
+    /// When the first transaction was replayed.
+    start: Option<Instant>,
+    /// Position of the next transaction to replay.
+    next: usize,
+    /// Offset of the current loop over the trace.
+    loop_offset: Duration,
+}
+
+struct ReplayWorkload {
+    accounts: Vec<LocalAccount>,
+    transactions: Vec<ReplayedTransaction>,
+    /// Factor by which the time between transactions of the trace is divided.
+    rate_scale: f64,
+    /// Time between the starts of two loops over the trace.
+    period: Duration,
+    schedule: Mutex<ReplaySchedule>,
+}
+
+impl ReplayWorkload {
+    fn sign(
+        &self,
+        txn: &ReplayedTransaction,
+        txn_factory: &TransactionFactory,
+    ) -> SignedTransaction {
+        let sender = &self.accounts[txn.sender];
+        let builder = txn_factory.payload(txn.payload.clone());
+        let secondary_signers = txn
+            .secondary_signers
+            .iter()
+            .map(|idx| &self.accounts[*idx])
+            .collect::<Vec<_>>();
+        match txn.fee_payer {
+            Some(fee_payer) => sender.sign_fee_payer_with_transaction_builder(
+                secondary_signers,
+                &self.accounts[fee_payer],
+                builder,
+            ),
+            None if secondary_signers.is_empty() => sender.sign_with_transaction_builder(builder),
+            None => sender.sign_multi_agent_with_transaction_builder(secondary_signers, builder),
+        }
+    }
+}
+
+pub struct ReplayTraceGenerator {
+    txn_factory: TransactionFactory,
+    workload: Arc<ReplayWorkload>,
+}
+
+impl TransactionGenerator for ReplayTraceGenerator {
+    /// Returns the transactions of the trace which are due, at most `num_to_create`, so that
+    /// they are submitted with the (scaled) times between them in the trace.
+    fn generate_transactions(
+        &mut self,
+        _account: &LocalAccount,
+        num_to_create: usize,
+    ) -> Vec<SignedTransaction> {
+        let workload = &self.workload;
+        // Transactions are signed while holding the lock, so that sequence numbers of every
+        // account are assigned in trace order.
+        let mut schedule = workload.schedule.lock();
+        let start = *schedule.start.get_or_insert_with(Instant::now);
+        let replayed_until = start.elapsed().mul_f64(workload.rate_scale);
+        let mut requests = Vec::new();
+        while requests.len() < num_to_create {
+            let txn = &workload.transactions[schedule.next];
+            if schedule.loop_offset + txn.offset > replayed_until {
+                break;
+            }
+            requests.push(workload.sign(txn, &self.txn_factory));
+            schedule.next += 1;
+            if schedule.next == workload.transactions.len() {
+                schedule.next = 0;
+                schedule.loop_offset += workload.period;
+            }
+        }
+        requests
+    }
+}
+
+/// Replays the transactions of a trace, signed by generated accounts, with the times between
+/// them in the trace divided by `rate_scale`.
+///
+/// Each account of the trace is replaced by an account from the accounts pool, consistently
+/// across senders, arguments and package addresses. The packages of the trace are deployed
+/// before replaying starts.
+pub struct ReplayTraceGeneratorCreator {
+    txn_factory: TransactionFactory,
+    workload: Arc<ReplayWorkload>,
+}
+
+impl ReplayTraceGeneratorCreator {
+    pub async fn new(
+        txn_factory: TransactionFactory,
+        init_txn_factory: TransactionFactory,
+        trace: &WorkloadTrace,
+        rate_scale: f64,
+        accounts_pool: &ObjectPool<LocalAccount>,
+        txn_executor: &dyn ReliableTransactionSubmitter,
+    ) -> Result<Self> {
+        ensure!(rate_scale > 0.0, "Rate scale must be positive");
+        let mut rng = StdRng::from_entropy();
+        let original_accounts = trace.accounts();
+        let accounts = accounts_pool.take_from_pool(original_accounts.len(), false, &mut rng);
+        ensure!(
+            accounts.len() == original_accounts.len(),
+            "Replaying the trace requires {} accounts in the accounts pool, found {}",
+            original_accounts.len(),
+            accounts.len()
+        );
+        let account_indices: HashMap<AccountAddress, usize> = original_accounts
+            .iter()
+            .enumerate()
+            .map(|(idx, address)| (*address, idx))
+            .collect();
+        let remapper = AddressRemapper::new(
+            original_accounts
+                .iter()
+                .zip(accounts.iter())
+                .map(|(original, account)| (*original, account.address()))
+                .collect(),
+        );
+
+        let packages = trace.packages_in_deployment_order()?;
+        info!("Deploying {} packages of the trace", packages.len());
+        for package in packages {
+            let publisher = &accounts[account_indices[&package.address]];
+            let payload = remapper.publish_payload(package).with_context(|| {
+                format!(
+                    "Failed to remap package {} of the trace",
+                    package.metadata.name
+                )
+            })?;
+            // Packages may depend on each other, so they are published one at a time.
+            txn_executor
+                .execute_transactions(&[
+                    publisher.sign_with_transaction_builder(init_txn_factory.payload(payload))
+                ])
+                .await
+                .with_context(|| {
+                    format!(
+                        "Failed to deploy package {} of the trace",
+                        package.metadata.name
+                    )
+                })?;
+        }
+
+        let mut transactions = Vec::with_capacity(trace.transactions.len());
+        for txn in &trace.transactions {
+            // Transactions signed by framework accounts cannot be replayed.
+            let index = |address: &AccountAddress| account_indices.get(address).copied();
+            let Some(sender) = index(&txn.sender) else {
+                continue;
+            };
+            let Some(secondary_signers) = txn
+                .secondary_signers
+                .iter()
+                .map(index)
+                .collect::<Option<Vec<_>>>()
+            else {
+                continue;
+            };
+            let fee_payer = match txn.fee_payer.as_ref().map(index) {
+                Some(None) => continue,
+                fee_payer => fee_payer.flatten(),
+            };
+            let Some(payload) = remapper
+                .payload(txn)
+                .context("Failed to remap a transaction of the trace")?
+            else {
+                continue;
+            };
+            transactions.push(ReplayedTransaction {
+                offset: Duration::from_micros(txn.timestamp_usecs),
+                sender,
+                secondary_signers,
+                fee_payer,
+                payload,
+            });
+        }
+        info!(
+            "Replaying {} out of {} transactions of the trace, with {} accounts",
+            transactions.len(),
+            trace.transactions.len(),
+            accounts.len()
+        );
+
+        let (Some(first), Some(last)) = (transactions.first(), transactions.last()) else {
+            bail!("Trace has no transactions which can be replayed");
+        };
+        let (first, last) = (first.offset, last.offset);
+        if last <= first {
+            bail!("Transactions of the trace must span a positive amount of time to be replayed");
+        }
+        for txn in &mut transactions {
+            txn.offset -= first;
+        }
+        // The next loop starts one average time between transactions after the last one.
+        let period = (last - first) + (last - first) / (transactions.len() as u32 - 1);
+
+        Ok(Self {
+            txn_factory,
+            workload: Arc::new(ReplayWorkload {
+                accounts,
+                transactions,
+                rate_scale,
+                period,
+                schedule: Mutex::new(ReplaySchedule {
+                    start: None,
+                    next: 0,
+                    loop_offset: Duration::ZERO,
+                }),
+            }),
+        })
+    }
+}
+
+impl TransactionGeneratorCreator for ReplayTraceGeneratorCreator {
+    fn create_transaction_generator(&self) -> Box<dyn TransactionGenerator> {
+        Box::new(ReplayTraceGenerator {
+            txn_factory: self.txn_factory.clone(),
+            workload: self.workload.clone(),
+        })
+    }
+}
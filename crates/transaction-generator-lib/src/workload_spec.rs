@@ -0,0 +1,650 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Declarative workloads, calling entry functions of arbitrary packages.
+//!
+//! A spec is a YAML (or JSON) file, listing the transactions of each phase together with their
+//! weights in the mix, and how the arguments of every call are generated:
+//!
+//! ```yaml
+//! packages:
+//!   # Output of `aptos move build-publish-payload`, compiled for the given address, which is
+//!   # replaced with a generated publisher account.
+//!   - address: 0xcafe
+//!     publish_payload: build/marketplace.json
+//! # Transactions of the workload whose fees each publisher pays, when it is their fee payer.
+//! # Publishers are funded for them upfront.
+//! sponsored_transactions: 10000
+//! pools:
+//!   # Objects created upfront, which transactions can then pick from.
+//!   - name: listings
+//!     size: 1000
+//!     create:
+//!       function: 0xcafe::marketplace::create_named_listing
+//!       args:
+//!         - type: object_seed
+//!         - type: u64_range
+//!           min: 1
+//!           max: 1000
+//! phases:
+//!   - transactions:
+//!       - function: 0xcafe::marketplace::bid
+//!         weight: 3
+//!         args:
+//!           - type: pool_object
+//!             pool: listings
+//!           - type: u64_range
+//!             min: 1
+//!             max: 100
+//!       - function: 0xcafe::marketplace::claim_rewards
+//!         secondary_signers: [publisher]
+//!         use_account_pool: true
+//!         args:
+//!           - type: sender
+//!           - type: address
+//!             value: "0xcafe"
+//! ```
+
+use crate::{workload_trace::TracedPackage, TransactionType};
+use anyhow::{bail, ensure, format_err, Context, Result};
+use aptos_framework::natives::code::PackageMetadata;
+use aptos_sdk::{
+    bcs,
+    move_types::{identifier::Identifier, language_storage::TypeTag},
+    types::{account_address::AccountAddress, move_utils::MemberId},
+};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt, fs,
+    path::Path,
+    str::FromStr,
+    sync::Arc,
+};
+
+/// How an argument of a call is generated. Arguments are BCS encoded, in the same way as the
+/// arguments of entry functions are.
+///
+/// Addresses and hex strings must be quoted in the spec, so that they are not read as integers.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum ArgSpec {
+    Bool {
+        value: bool,
+    },
+    U8 {
+        value: u8,
+    },
+    U64 {
+        value: u64,
+    },
+    /// Uniformly random u64, in `[min, max]`.
+    U64Range {
+        min: u64,
+        max: u64,
+    },
+    /// Address. If it is the address of a package of the spec, it is replaced with the address
+    /// the package is published at.
+    Address {
+        value: AccountAddress,
+    },
+    String {
+        value: String,
+    },
+    /// Hex encoded `vector<u8>`.
+    Bytes {
+        value: String,
+    },
+    /// Address of the sender of the transaction.
+    Sender,
+    /// Address of the account the called package is published at.
+    Publisher,
+    /// Address of an object picked at random from the given pool.
+    PoolObject {
+        pool: String,
+    },
+    /// Seed, as `vector<u8>`, of the object created by a pool creation transaction.
+    ObjectSeed,
+}
+
+/// Accounts signing a transaction, in addition to the sender.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignerSpec {
+    /// Account the called package is published at.
+    Publisher,
+}
+
+/// Values of the arguments which depend on the transaction being generated.
+pub struct ArgContext<'a> {
+    pub sender: AccountAddress,
+    pub publisher: Option<AccountAddress>,
+    pub pools: &'a HashMap<String, Vec<AccountAddress>>,
+    pub object_seed: Option<&'a [u8]>,
+}
+
+impl ArgSpec {
+    /// Generates the BCS encoded value of the argument. Arguments are checked against the spec
+    /// when it is loaded, so that every value they require is in `ctx`.
+    pub fn generate(&self, ctx: &ArgContext, rng: &mut StdRng) -> Vec<u8> {
+        match self {
+            ArgSpec::Bool { value } => bcs::to_bytes(value),
+            ArgSpec::U8 { value } => bcs::to_bytes(value),
+            ArgSpec::U64 { value } => bcs::to_bytes(value),
+            ArgSpec::U64Range { min, max } => bcs::to_bytes(&rng.gen_range(*min, *max + 1)),
+            ArgSpec::Address { value } => bcs::to_bytes(value),
+            ArgSpec::String { value } => bcs::to_bytes(value),
+            ArgSpec::Bytes { value } => bcs::to_bytes(&decode_hex(value).unwrap()),
+            ArgSpec::Sender => bcs::to_bytes(&ctx.sender),
+            ArgSpec::Publisher => bcs::to_bytes(&ctx.publisher.unwrap()),
+            ArgSpec::PoolObject { pool } => bcs::to_bytes(ctx.pools[pool].choose(rng).unwrap()),
+            ArgSpec::ObjectSeed => bcs::to_bytes(&ctx.object_seed.unwrap()),
+        }
+        .unwrap()
+    }
+
+    fn validate(&self) -> Result<()> {
+        match self {
+            ArgSpec::U64Range { min, max } => {
+                ensure!(min <= max, "Range {}..={} is empty", min, max);
+                ensure!(*max < u64::MAX, "Range cannot include u64::MAX");
+            },
+            ArgSpec::Bytes { value } => {
+                decode_hex(value)?;
+            },
+            _ => {},
+        }
+        Ok(())
+    }
+}
+
+/// A call of an entry function.
+#[derive(Clone, Debug)]
+pub struct CallSpec {
+    pub function: MemberId,
+    pub ty_args: Vec<TypeTag>,
+    pub args: Vec<ArgSpec>,
+    pub secondary_signers: Vec<SignerSpec>,
+    pub fee_payer: Option<SignerSpec>,
+}
+
+impl CallSpec {
+    fn needs_publisher(&self) -> bool {
+        self.secondary_signers.contains(&SignerSpec::Publisher)
+            || self.fee_payer == Some(SignerSpec::Publisher)
+            || self
+                .args
+                .iter()
+                .any(|arg| matches!(arg, ArgSpec::Publisher))
+    }
+}
+
+/// Objects created before the workload starts, by `size` calls of `create`, each signed by the
+/// creator account of the pool.
+///
+/// The created objects must be named objects, created from the `object_seed` argument, e.g. with
+/// `object::create_named_object`, so that their addresses are known without querying the chain.
+#[derive(Clone, Debug)]
+pub struct PoolSpec {
+    pub name: String,
+    pub size: usize,
+    pub create: CallSpec,
+}
+
+#[derive(Clone, Debug)]
+pub struct TransactionSpec {
+    pub call: CallSpec,
+    pub weight: usize,
+    /// Whether the sender is taken from the accounts pool, instead of the accounts of the
+    /// emitter.
+    pub use_account_pool: bool,
+}
+
+/// Workload defined by a spec file, see the module documentation for the format.
+#[derive(Clone)]
+pub struct WorkloadSpec {
+    /// Packages published before the workload starts, in order.
+    pub packages: Vec<TracedPackage>,
+    /// Pools of objects, created in order before the workload starts.
+    pub pools: Vec<PoolSpec>,
+    pub phases: Vec<Vec<TransactionSpec>>,
+    /// Number of transactions of the phases each publisher pays the fees of, if it is the fee
+    /// payer of any of them.
+    pub sponsored_transactions: u64,
+}
+
+impl fmt::Debug for WorkloadSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WorkloadSpec")
+            .field("packages", &self.packages.len())
+            .field("pools", &self.pools.len())
+            .field("phases", &self.phases.len())
+            .field("sponsored_transactions", &self.sponsored_transactions)
+            .finish()
+    }
+}
+
+impl WorkloadSpec {
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read workload spec {}", path.display()))?;
+        Self::parse(&contents, path.parent().unwrap_or_else(|| Path::new(".")))
+            .with_context(|| format!("Invalid workload spec {}", path.display()))
+    }
+
+    /// Parses a spec, with the paths of the publish payloads relative to `base_dir`.
+    pub fn parse(contents: &str, base_dir: &Path) -> Result<Self> {
+        let file: SpecFile = serde_yaml::from_str(contents)?;
+        let packages = file
+            .packages
+            .iter()
+            .map(|package| {
+                load_publish_payload(package.address, &base_dir.join(&package.publish_payload))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let spec = Self {
+            packages,
+            pools: file
+                .pools
+                .into_iter()
+                .map(|pool| {
+                    Ok(PoolSpec {
+                        create: pool.create.resolve().with_context(|| {
+                            format!("Invalid creation call of pool {}", pool.name)
+                        })?,
+                        name: pool.name,
+                        size: pool.size,
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?,
+            phases: file
+                .phases
+                .into_iter()
+                .map(|phase| {
+                    phase
+                        .transactions
+                        .into_iter()
+                        .map(|txn| {
+                            Ok(TransactionSpec {
+                                call: txn.call.resolve()?,
+                                weight: txn.weight,
+                                use_account_pool: txn.use_account_pool,
+                            })
+                        })
+                        .collect::<Result<Vec<_>>>()
+                })
+                .collect::<Result<Vec<_>>>()?,
+            sponsored_transactions: file.sponsored_transactions,
+        };
+        spec.validate()?;
+        Ok(spec)
+    }
+
+    fn validate(&self) -> Result<()> {
+        let package_addresses: BTreeSet<_> = self.packages.iter().map(|p| p.address).collect();
+        ensure!(
+            package_addresses.len() == self.packages.len(),
+            "Only a single package can be published per address"
+        );
+        let check_call = |call: &CallSpec, pools: &BTreeSet<&str>, in_pool: bool| -> Result<()> {
+            let context = || format!("Invalid call of {}", member_id_to_string(&call.function));
+            for arg in &call.args {
+                arg.validate().with_context(context)?;
+                match arg {
+                    ArgSpec::PoolObject { pool } if !pools.contains(pool.as_str()) => {
+                        bail!("{}: pool {} is not defined before", context(), pool)
+                    },
+                    ArgSpec::ObjectSeed if !in_pool => {
+                        bail!(
+                            "{}: object_seed is only valid when creating pools",
+                            context()
+                        )
+                    },
+                    _ => {},
+                }
+            }
+            ensure!(
+                !call.needs_publisher()
+                    || package_addresses.contains(call.function.module_id.address()),
+                "{}: publisher is only defined for functions of the packages of the spec",
+                context()
+            );
+            Ok(())
+        };
+
+        let mut pools = BTreeSet::new();
+        for pool in &self.pools {
+            ensure!(pool.size > 0, "Pool {} must not be empty", pool.name);
+            check_call(&pool.create, &pools, true)?;
+            ensure!(
+                pool.create
+                    .args
+                    .iter()
+                    .any(|arg| matches!(arg, ArgSpec::ObjectSeed)),
+                "Creation call of pool {} must take the object_seed argument",
+                pool.name
+            );
+            ensure!(
+                pools.insert(pool.name.as_str()),
+                "Pool {} is defined twice",
+                pool.name
+            );
+        }
+        ensure!(!self.phases.is_empty(), "Spec must have at least one phase");
+        for (phase, txns) in self.phases.iter().enumerate() {
+            ensure!(!txns.is_empty(), "Phase {} has no transactions", phase);
+            for txn in txns {
+                ensure!(txn.weight > 0, "Weights must be positive");
+                check_call(&txn.call, &pools, false)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Number of transactions the publisher of each package pays the fees of: publishing the
+    /// package, creating the pools it is the fee payer of, and the sponsored transactions of the
+    /// phases.
+    pub fn publisher_paid_transactions(&self) -> Vec<u64> {
+        self.packages
+            .iter()
+            .map(|package| {
+                let pays = |call: &CallSpec| {
+                    call.fee_payer == Some(SignerSpec::Publisher)
+                        && call.function.module_id.address() == &package.address
+                };
+                let pool_creations: u64 = self
+                    .pools
+                    .iter()
+                    .filter(|pool| pays(&pool.create))
+                    .map(|pool| pool.size as u64)
+                    .sum();
+                let sponsored = if self.phases.iter().flatten().any(|txn| pays(&txn.call)) {
+                    self.sponsored_transactions
+                } else {
+                    0
+                };
+                1 + pool_creations + sponsored
+            })
+            .collect()
+    }
+
+    /// Transaction mix of every phase of the spec, for `create_txn_generator_creator`.
+    pub fn transaction_mix_per_phase(self: &Arc<Self>) -> Vec<Vec<(TransactionType, usize)>> {
+        self.phases
+            .iter()
+            .enumerate()
+            .map(|(phase, txns)| {
+                txns.iter()
+                    .enumerate()
+                    .map(|(index, txn)| {
+                        (
+                            TransactionType::WorkloadSpec {
+                                spec: self.clone(),
+                                phase,
+                                index,
+                            },
+                            txn.weight,
+                        )
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+pub(crate) fn member_id_to_string(member_id: &MemberId) -> String {
+    format!(
+        "{}::{}",
+        member_id.module_id.short_str_lossless(),
+        member_id.member_id
+    )
+}
+
+fn decode_hex(value: &str) -> Result<Vec<u8>> {
+    hex::decode(value.strip_prefix("0x").unwrap_or(value))
+        .with_context(|| format!("Invalid hex string {}", value))
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SpecFile {
+    #[serde(default)]
+    packages: Vec<PackageFile>,
+    #[serde(default)]
+    pools: Vec<PoolFile>,
+    phases: Vec<PhaseFile>,
+    #[serde(default = "default_sponsored_transactions")]
+    sponsored_transactions: u64,
+}
+
+fn default_sponsored_transactions() -> u64 {
+    10_000
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PackageFile {
+    address: AccountAddress,
+    publish_payload: String,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PoolFile {
+    name: String,
+    size: usize,
+    create: CallFile,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PhaseFile {
+    transactions: Vec<TransactionFile>,
+}
+
+#[derive(Deserialize)]
+struct TransactionFile {
+    #[serde(flatten)]
+    call: CallFile,
+    #[serde(default = "default_weight")]
+    weight: usize,
+    #[serde(default)]
+    use_account_pool: bool,
+}
+
+fn default_weight() -> usize {
+    1
+}
+
+#[derive(Deserialize)]
+struct CallFile {
+    function: String,
+    #[serde(default)]
+    type_args: Vec<String>,
+    #[serde(default)]
+    args: Vec<ArgSpec>,
+    #[serde(default)]
+    secondary_signers: Vec<SignerSpec>,
+    #[serde(default)]
+    fee_payer: Option<SignerSpec>,
+}
+
+impl CallFile {
+    fn resolve(self) -> Result<CallSpec> {
+        Ok(CallSpec {
+            function: MemberId::from_str(&self.function)
+                .with_context(|| format!("Invalid function {}", self.function))?,
+            ty_args: self
+                .type_args
+                .iter()
+                .map(|ty_arg| {
+                    TypeTag::from_str(ty_arg)
+                        .with_context(|| format!("Invalid type argument {}", ty_arg))
+                })
+                .collect::<Result<Vec<_>>>()?,
+            args: self.args,
+            secondary_signers: self.secondary_signers,
+            fee_payer: self.fee_payer,
+        })
+    }
+}
+
+/// Output of `aptos move build-publish-payload`.
+#[derive(Deserialize)]
+struct PublishPayloadFile {
+    function_id: String,
+    args: Vec<PublishPayloadArg>,
+}
+
+#[derive(Deserialize)]
+struct PublishPayloadArg {
+    value: PublishPayloadValue,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PublishPayloadValue {
+    Bytes(String),
+    BytesVector(Vec<String>),
+}
+
+fn load_publish_payload(address: AccountAddress, path: &Path) -> Result<TracedPackage> {
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read publish payload {}", path.display()))?;
+    // JSON is valid YAML, so a single parser is used for the spec and the payloads.
+    let payload: PublishPayloadFile = serde_yaml::from_str(&contents)
+        .with_context(|| format!("Failed to parse publish payload {}", path.display()))?;
+    let function = MemberId::from_str(&payload.function_id)?;
+    ensure!(
+        function.module_id.address() == &AccountAddress::ONE
+            && function.module_id.name().as_str() == "code"
+            && function.member_id == Identifier::new("publish_package_txn")?,
+        "{} is not a publish payload",
+        path.display()
+    );
+    let (metadata, modules) = match payload.args.as_slice() {
+        [PublishPayloadArg {
+            value: PublishPayloadValue::Bytes(metadata),
+        }, PublishPayloadArg {
+            value: PublishPayloadValue::BytesVector(modules),
+        }] => (metadata, modules),
+        _ => return Err(format_err!("{} is not a publish payload", path.display())),
+    };
+    let metadata: PackageMetadata = bcs::from_bytes(&decode_hex(metadata)?)
+        .with_context(|| format!("Invalid package metadata in {}", path.display()))?;
+    Ok(TracedPackage {
+        address,
+        metadata,
+        modules: modules
+            .iter()
+            .map(|module| decode_hex(module))
+            .collect::<Result<Vec<_>>>()?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    const SPEC: &str = r#"
+pools:
+  - name: tokens
+    size: 10
+    create:
+      function: 0xcafe::token::mint_named
+      args:
+        - type: object_seed
+        - type: string
+          value: "token"
+phases:
+  - transactions:
+      - function: 0xcafe::token::transfer
+        weight: 3
+        type_args: ["0x1::aptos_coin::AptosCoin"]
+        args:
+          - type: pool_object
+            pool: tokens
+          - type: sender
+      - function: 0x1::aptos_account::transfer
+        use_account_pool: true
+        args:
+          - type: address
+            value: "0xb0b"
+          - type: u64_range
+            min: 5
+            max: 5
+  - transactions:
+      - function: 0xcafe::token::burn
+        args:
+          - type: bytes
+            value: "0x0102"
+"#;
+
+    #[test]
+    fn test_parse_spec() {
+        let spec = Arc::new(WorkloadSpec::parse(SPEC, Path::new(".")).unwrap());
+        assert_eq!(spec.pools.len(), 1);
+        let mix = spec.transaction_mix_per_phase();
+        assert_eq!(
+            mix.iter()
+                .map(|phase| phase.iter().map(|(_, weight)| *weight).collect::<Vec<_>>())
+                .collect::<Vec<_>>(),
+            vec![vec![3, 1], vec![1]]
+        );
+        assert!(spec.phases[0][1].use_account_pool);
+        assert_eq!(spec.sponsored_transactions, 10_000);
+        assert_eq!(spec.phases[0][0].call.ty_args.len(), 1);
+
+        let pools = HashMap::from([("tokens".to_string(), vec![AccountAddress::TWO])]);
+        let ctx = ArgContext {
+            sender: AccountAddress::ONE,
+            publisher: None,
+            pools: &pools,
+            object_seed: None,
+        };
+        let mut rng = StdRng::seed_from_u64(0);
+        let generate = |arg: &ArgSpec, rng: &mut StdRng| arg.generate(&ctx, rng);
+        let args = &spec.phases[0][0].call.args;
+        assert_eq!(
+            generate(&args[0], &mut rng),
+            bcs::to_bytes(&AccountAddress::TWO).unwrap()
+        );
+        assert_eq!(
+            generate(&args[1], &mut rng),
+            bcs::to_bytes(&AccountAddress::ONE).unwrap()
+        );
+        assert_eq!(
+            generate(&spec.phases[0][1].call.args[1], &mut rng),
+            bcs::to_bytes(&5u64).unwrap()
+        );
+        assert_eq!(generate(&spec.phases[1][0].call.args[0], &mut rng), vec![
+            2, 1, 2
+        ]);
+    }
+
+    #[test]
+    fn test_invalid_specs() {
+        let invalid = [
+            // unknown pool
+            "phases: [{transactions: [{function: '0x1::m::f', args: [{type: pool_object, pool: p}]}]}]",
+            // object seed outside of pool creation
+            "phases: [{transactions: [{function: '0x1::m::f', args: [{type: object_seed}]}]}]",
+            // publisher of a package which is not part of the spec
+            "phases: [{transactions: [{function: '0x1::m::f', secondary_signers: [publisher]}]}]",
+            // pool which does not create named objects
+            "{pools: [{name: p, size: 1, create: {function: '0x1::m::f'}}], phases: [{transactions: [{function: '0x1::m::f'}]}]}",
+            // empty range
+            "phases: [{transactions: [{function: '0x1::m::f', args: [{type: u64_range, min: 2, max: 1}]}]}]",
+            "phases: []",
+        ];
+        for spec in invalid {
+            assert!(
+                WorkloadSpec::parse(spec, Path::new(".")).is_err(),
+                "{}",
+                spec
+            );
+        }
+    }
+}
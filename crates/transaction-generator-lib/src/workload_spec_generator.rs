@@ -0,0 +1,506 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    create_account_transaction,
+    workload_spec::{member_id_to_string, ArgContext, ArgSpec, CallSpec, SignerSpec, WorkloadSpec},
+    workload_trace::AddressRemapper,
+    ReliableTransactionSubmitter, RootAccountHandle, TransactionGenerator,
+    TransactionGeneratorCreator,
+};
+use aptos_logger::{error, info};
+use aptos_sdk::{
+    move_types::language_storage::ModuleId,
+    transaction_builder::TransactionFactory,
+    types::{
+        account_address::{create_object_address, AccountAddress},
+        move_utils::MemberId,
+        transaction::{EntryFunction, SignedTransaction, TransactionPayload},
+        LocalAccount,
+    },
+};
+use rand::{rngs::StdRng, SeedableRng};
+use std::{borrow::Borrow, collections::HashMap, sync::Arc};
+
+/// Call of a spec, with the addresses of the packages replaced by the addresses they are
+/// published at.
+struct ResolvedCall {
+    call: CallSpec,
+    /// Index of the publisher of the called package, if it is a package of the spec.
+    publisher: Option<usize>,
+}
+
+/// Packages and pools of a spec, deployed once and shared by all of its transactions.
+pub(crate) struct DeployedWorkloadSpec {
+    publishers: Vec<LocalAccount>,
+    publisher_indices: HashMap<AccountAddress, usize>,
+    remapper: AddressRemapper,
+    pools: HashMap<String, Vec<AccountAddress>>,
+}
+
+impl DeployedWorkloadSpec {
+    /// Returns the deployment of `spec`, deploying it if it is not in `deployed` yet.
+    pub(crate) async fn get_or_deploy(
+        deployed: &mut Vec<(Arc<WorkloadSpec>, Arc<DeployedWorkloadSpec>)>,
+        spec: &Arc<WorkloadSpec>,
+        root_account: &dyn RootAccountHandle,
+        txn_executor: &dyn ReliableTransactionSubmitter,
+        init_txn_factory: &TransactionFactory,
+    ) -> Arc<DeployedWorkloadSpec> {
+        if let Some((_, deployment)) = deployed.iter().find(|(s, _)| Arc::ptr_eq(s, spec)) {
+            return deployment.clone();
+        }
+        let deployment =
+            Arc::new(Self::deploy(spec, root_account, txn_executor, init_txn_factory).await);
+        deployed.push((spec.clone(), deployment.clone()));
+        deployment
+    }
+
+    async fn deploy(
+        spec: &WorkloadSpec,
+        root_account: &dyn RootAccountHandle,
+        txn_executor: &dyn ReliableTransactionSubmitter,
+        init_txn_factory: &TransactionFactory,
+    ) -> Self {
+        let mut rng = StdRng::from_entropy();
+        let max_txn_cost =
+            init_txn_factory.get_gas_unit_price() * init_txn_factory.get_max_gas_amount();
+        let publishers = (0..spec.packages.len())
+            .map(|_| LocalAccount::generate(&mut rng))
+            .collect::<Vec<_>>();
+        let creators = (0..spec.pools.len())
+            .map(|_| LocalAccount::generate(&mut rng))
+            .collect::<Vec<_>>();
+        let publisher_balances = spec
+            .publisher_paid_transactions()
+            .into_iter()
+            .map(|num_txns| (num_txns + 1) * max_txn_cost)
+            .collect::<Vec<_>>();
+        let creator_balances = spec
+            .pools
+            .iter()
+            .map(|pool| (pool.size as u64 + 1) * max_txn_cost)
+            .collect::<Vec<_>>();
+        root_account
+            .approve_funds(
+                publisher_balances.iter().sum::<u64>() + creator_balances.iter().sum::<u64>(),
+                "funding publishers and pool creators of the workload spec",
+            )
+            .await;
+        let requests_create = publishers
+            .iter()
+            .zip(publisher_balances)
+            .chain(creators.iter().zip(creator_balances))
+            .map(|(account, balance)| {
+                create_account_transaction(
+                    root_account.get_root_account().borrow(),
+                    account.address(),
+                    init_txn_factory,
+                    balance,
+                )
+            })
+            .collect::<Vec<_>>();
+        info!(
+            "Creating {} publisher and pool creator accounts",
+            requests_create.len()
+        );
+        for req_chunk in requests_create.chunks(100) {
+            txn_executor
+                .execute_transactions(req_chunk)
+                .await
+                .inspect_err(|err| {
+                    error!("Failed to create accounts of the workload spec: {:#}", err)
+                })
+                .unwrap();
+        }
+
+        let remapper = AddressRemapper::new(
+            spec.packages
+                .iter()
+                .zip(publishers.iter())
+                .map(|(package, publisher)| (package.address, publisher.address()))
+                .collect(),
+        );
+        for (package, publisher) in spec.packages.iter().zip(publishers.iter()) {
+            let payload = remapper
+                .publish_payload(package)
+                .expect("Package of the workload spec must be remappable");
+            // Packages are listed in dependency order, so they are published one at a time.
+            txn_executor
+                .execute_transactions(&[
+                    publisher.sign_with_transaction_builder(init_txn_factory.payload(payload))
+                ])
+                .await
+                .inspect_err(|err| {
+                    error!(
+                        "Failed to publish package {} of the workload spec: {:#}",
+                        package.metadata.name, err
+                    )
+                })
+                .unwrap();
+        }
+
+        let mut deployment = Self {
+            publishers,
+            publisher_indices: spec
+                .packages
+                .iter()
+                .enumerate()
+                .map(|(idx, package)| (package.address, idx))
+                .collect(),
+            remapper,
+            pools: HashMap::new(),
+        };
+        for (pool, creator) in spec.pools.iter().zip(creators.iter()) {
+            let call = deployment.resolve(&pool.create);
+            let seeds = (0..pool.size)
+                .map(|idx| format!("{}_{}", pool.name, idx).into_bytes())
+                .collect::<Vec<_>>();
+            let requests = seeds
+                .iter()
+                .map(|seed| {
+                    deployment.sign(
+                        &call,
+                        creator,
+                        Some(seed.as_slice()),
+                        init_txn_factory,
+                        &mut rng,
+                    )
+                })
+                .collect::<Vec<_>>();
+            info!("Creating {} objects of pool {}", pool.size, pool.name);
+            txn_executor
+                .execute_transactions(&requests)
+                .await
+                .inspect_err(|err| error!("Failed to create pool {}: {:#}", pool.name, err))
+                .unwrap();
+            let objects = seeds
+                .iter()
+                .map(|seed| create_object_address(creator.address(), seed))
+                .collect();
+            deployment.pools.insert(pool.name.clone(), objects);
+        }
+        deployment
+    }
+
+    fn resolve(&self, call: &CallSpec) -> ResolvedCall {
+        let module_id = &call.function.module_id;
+        ResolvedCall {
+            publisher: self.publisher_indices.get(module_id.address()).copied(),
+            call: CallSpec {
+                function: MemberId {
+                    module_id: ModuleId::new(
+                        self.remapper.address(*module_id.address()),
+                        module_id.name().to_owned(),
+                    ),
+                    member_id: call.function.member_id.clone(),
+                },
+                ty_args: call
+                    .ty_args
+                    .iter()
+                    .map(|ty_arg| self.remapper.type_tag(ty_arg))
+                    .collect(),
+                args: call
+                    .args
+                    .iter()
+                    .map(|arg| match arg {
+                        ArgSpec::Address { value } => ArgSpec::Address {
+                            value: self.remapper.address(*value),
+                        },
+                        arg => arg.clone(),
+                    })
+                    .collect(),
+                secondary_signers: call.secondary_signers.clone(),
+                fee_payer: call.fee_payer,
+            },
+        }
+    }
+
+    fn sign(
+        &self,
+        resolved: &ResolvedCall,
+        sender: &LocalAccount,
+        object_seed: Option<&[u8]>,
+        txn_factory: &TransactionFactory,
+        rng: &mut StdRng,
+    ) -> SignedTransaction {
+        let publisher = resolved.publisher.map(|idx| &self.publishers[idx]);
+        let ctx = ArgContext {
+            sender: sender.address(),
+            publisher: publisher.map(LocalAccount::address),
+            pools: &self.pools,
+            object_seed,
+        };
+        let call = &resolved.call;
+        let builder = txn_factory.payload(TransactionPayload::EntryFunction(EntryFunction::new(
+            call.function.module_id.clone(),
+            call.function.member_id.clone(),
+            call.ty_args.clone(),
+            call.args
+                .iter()
+                .map(|arg| arg.generate(&ctx, rng))
+                .collect(),
+        )));
+
+        let signer = |signer: &SignerSpec| match signer {
+            SignerSpec::Publisher => publisher.unwrap(),
+        };
+        let secondary_signers = call
+            .secondary_signers
+            .iter()
+            .map(signer)
+            .collect::<Vec<_>>();
+        match call.fee_payer.as_ref().map(signer) {
+            Some(fee_payer) => sender.sign_fee_payer_with_transaction_builder(
+                secondary_signers,
+                fee_payer,
+                builder,
+            ),
+            None if secondary_signers.is_empty() => sender.sign_with_transaction_builder(builder),
+            None => sender.sign_multi_agent_with_transaction_builder(secondary_signers, builder),
+        }
+    }
+}
+
+pub struct WorkloadSpecGenerator {
+    rng: StdRng,
+    txn_factory: TransactionFactory,
+    deployment: Arc<DeployedWorkloadSpec>,
+    call: Arc<ResolvedCall>,
+}
+
+impl TransactionGenerator for WorkloadSpecGenerator {
+    fn generate_transactions(
+        &mut self,
+        account: &LocalAccount,
+        num_to_create: usize,
+    ) -> Vec<SignedTransaction> {
+        (0..num_to_create)
+            .map(|_| {
+                self.deployment
+                    .sign(&self.call, account, None, &self.txn_factory, &mut self.rng)
+            })
+            .collect()
+    }
+}
+
+/// Generates the calls of a single transaction of a workload spec.
+pub struct WorkloadSpecGeneratorCreator {
+    txn_factory: TransactionFactory,
+    deployment: Arc<DeployedWorkloadSpec>,
+    call: Arc<ResolvedCall>,
+}
+
+impl WorkloadSpecGeneratorCreator {
+    pub(crate) fn new(
+        txn_factory: TransactionFactory,
+        deployment: Arc<DeployedWorkloadSpec>,
+        call: &CallSpec,
+    ) -> Self {
+        info!(
+            "Generating calls of {} from the workload spec",
+            member_id_to_string(&call.function)
+        );
+        Self {
+            txn_factory,
+            call: Arc::new(deployment.resolve(call)),
+            deployment,
+        }
+    }
+}
+
+impl TransactionGeneratorCreator for WorkloadSpecGeneratorCreator {
+    fn create_transaction_generator(&self) -> Box<dyn TransactionGenerator> {
+        Box::new(WorkloadSpecGenerator {
+            rng: StdRng::from_entropy(),
+            txn_factory: self.txn_factory.clone(),
+            deployment: self.deployment.clone(),
+            call: self.call.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        workload_spec::{PoolSpec, TransactionSpec},
+        workload_trace::TracedPackage,
+        AlwaysApproveRootAccountHandle, CounterState,
+    };
+    use anyhow::Result;
+    use aptos_framework::natives::code::{MoveOption, PackageMetadata, UpgradePolicy};
+    use aptos_infallible::Mutex;
+    use aptos_sdk::{bcs, transaction_builder::aptos_stdlib, types::chain_id::ChainId};
+    use std::{str::FromStr, sync::atomic::AtomicUsize};
+
+    /// Records the submitted transactions, as if they were all committed successfully.
+    #[derive(Default)]
+    struct RecordingSubmitter {
+        txns: Mutex<Vec<SignedTransaction>>,
+    }
+
+    #[async_trait::async_trait]
+    impl ReliableTransactionSubmitter for RecordingSubmitter {
+        async fn get_account_balance(&self, _account_address: AccountAddress) -> Result<u64> {
+            Ok(0)
+        }
+
+        async fn query_sequence_number(&self, _account_address: AccountAddress) -> Result<u64> {
+            Ok(0)
+        }
+
+        async fn execute_transactions_with_counter(
+            &self,
+            txns: &[SignedTransaction],
+            _state: &CounterState,
+        ) -> Result<()> {
+            self.txns.lock().extend_from_slice(txns);
+            Ok(())
+        }
+
+        fn create_counter_state(&self) -> CounterState {
+            CounterState {
+                submit_failures: vec![AtomicUsize::new(0)],
+                wait_failures: vec![AtomicUsize::new(0)],
+                successes: AtomicUsize::new(0),
+                by_client: HashMap::new(),
+            }
+        }
+    }
+
+    fn call(function: &str, args: Vec<ArgSpec>, fee_payer: Option<SignerSpec>) -> CallSpec {
+        CallSpec {
+            function: MemberId::from_str(function).unwrap(),
+            ty_args: vec![],
+            args,
+            secondary_signers: vec![],
+            fee_payer,
+        }
+    }
+
+    fn spec(fee_payer: Option<SignerSpec>) -> Arc<WorkloadSpec> {
+        let cafe = AccountAddress::from_hex_literal("0xcafe").unwrap();
+        Arc::new(WorkloadSpec {
+            packages: vec![TracedPackage {
+                address: cafe,
+                metadata: PackageMetadata {
+                    name: "items".to_string(),
+                    upgrade_policy: UpgradePolicy::compat(),
+                    upgrade_number: 0,
+                    source_digest: String::new(),
+                    manifest: vec![],
+                    modules: vec![],
+                    deps: vec![],
+                    extension: MoveOption::none(),
+                },
+                modules: vec![],
+            }],
+            pools: vec![PoolSpec {
+                name: "items".to_string(),
+                size: 3,
+                create: call("0xcafe::items::create", vec![ArgSpec::ObjectSeed], None),
+            }],
+            phases: vec![vec![TransactionSpec {
+                call: call(
+                    "0xcafe::items::use_item",
+                    vec![
+                        ArgSpec::PoolObject {
+                            pool: "items".to_string(),
+                        },
+                        ArgSpec::Address { value: cafe },
+                    ],
+                    fee_payer,
+                ),
+                weight: 1,
+                use_account_pool: false,
+            }]],
+            sponsored_transactions: 100,
+        })
+    }
+
+    #[test]
+    fn test_publisher_paid_transactions() {
+        assert_eq!(spec(None).publisher_paid_transactions(), vec![1]);
+        assert_eq!(
+            spec(Some(SignerSpec::Publisher)).publisher_paid_transactions(),
+            vec![101]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_deploy_and_generate() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let spec = spec(Some(SignerSpec::Publisher));
+        let root_account = AlwaysApproveRootAccountHandle {
+            root_account: Arc::new(LocalAccount::generate(&mut rng)),
+        };
+        let submitter = RecordingSubmitter::default();
+        let txn_factory = TransactionFactory::new(ChainId::test())
+            .with_gas_unit_price(100)
+            .with_max_gas_amount(1000);
+        let max_txn_cost = 100 * 1000;
+
+        let mut deployed = vec![];
+        let deployment = DeployedWorkloadSpec::get_or_deploy(
+            &mut deployed,
+            &spec,
+            &root_account,
+            &submitter,
+            &txn_factory,
+        )
+        .await;
+        let redeployment = DeployedWorkloadSpec::get_or_deploy(
+            &mut deployed,
+            &spec,
+            &root_account,
+            &submitter,
+            &txn_factory,
+        )
+        .await;
+        assert!(Arc::ptr_eq(&deployment, &redeployment));
+
+        // Publisher and pool creator accounts, then the package and the objects of the pool.
+        let txns = submitter.txns.lock().clone();
+        assert_eq!(txns.len(), 2 + 1 + 3);
+        let publisher = deployment.publishers[0].address();
+        assert_eq!(
+            txns[0].payload(),
+            &aptos_stdlib::aptos_account_transfer(publisher, 102 * max_txn_cost)
+        );
+        let TransactionPayload::EntryFunction(create_creator) = txns[1].payload() else {
+            panic!("Pool creator must be created by a transfer");
+        };
+        let creator: AccountAddress = bcs::from_bytes(&create_creator.args()[0]).unwrap();
+        assert_eq!(
+            bcs::from_bytes::<u64>(&create_creator.args()[1]).unwrap(),
+            4 * max_txn_cost
+        );
+        assert_eq!(txns[2].sender(), publisher);
+        assert!(txns[3..].iter().all(|txn| txn.sender() == creator));
+        let objects = (0..3)
+            .map(|idx| create_object_address(creator, format!("items_{}", idx).as_bytes()))
+            .collect::<Vec<_>>();
+        assert_eq!(deployment.pools["items"], objects);
+
+        let mut generator = WorkloadSpecGeneratorCreator::new(
+            txn_factory,
+            deployment.clone(),
+            &spec.phases[0][0].call,
+        )
+        .create_transaction_generator();
+        let sender = LocalAccount::generate(&mut rng);
+        let txns = generator.generate_transactions(&sender, 5);
+        assert_eq!(txns.len(), 5);
+        for txn in txns {
+            assert_eq!(txn.sender(), sender.address());
+            assert_eq!(txn.authenticator().fee_payer_address(), Some(publisher));
+            let TransactionPayload::EntryFunction(entry_function) = txn.payload() else {
+                panic!("Calls of the spec must be entry functions");
+            };
+            assert_eq!(entry_function.module().address(), &publisher);
+            assert!(objects.contains(&bcs::from_bytes(&entry_function.args()[0]).unwrap()));
+            assert_eq!(entry_function.args()[1], bcs::to_bytes(&publisher).unwrap());
+        }
+    }
+}
@@ -0,0 +1,659 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Traces of historical transactions, which can be replayed as a workload.
+//!
+//! A trace contains the user transactions of a range of the chain, together with the packages
+//! they reference. When replaying, every account of the trace is mapped to a generated account,
+//! and the packages are re-deployed under the accounts their original addresses are mapped to.
+
+use anyhow::{bail, Context, Result};
+use aptos_framework::{
+    natives::code::{PackageDep, PackageMetadata},
+    KnownAttribute, APTOS_METADATA_KEY, APTOS_METADATA_KEY_V1,
+};
+use aptos_sdk::{
+    bcs,
+    move_types::{
+        ident_str,
+        language_storage::{ModuleId, StructTag, TypeTag},
+        transaction_argument::TransactionArgument,
+    },
+    transaction_builder::aptos_stdlib,
+    types::{
+        account_address::AccountAddress,
+        transaction::{EntryFunction, Script, SignedTransaction, TransactionPayload},
+    },
+};
+use move_binary_format::{
+    access::ModuleAccess,
+    file_format::{CompiledScript, SignatureToken, StructHandleIndex},
+    CompiledModule,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fmt, fs,
+    path::Path,
+    time::Duration,
+};
+
+/// A user transaction of the trace, without its signature and sequence number.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct TracedTransaction {
+    /// Timestamp of the block the transaction was committed in.
+    pub timestamp_usecs: u64,
+    pub sender: AccountAddress,
+    pub secondary_signers: Vec<AccountAddress>,
+    pub fee_payer: Option<AccountAddress>,
+    pub payload: TransactionPayload,
+    /// Types of the arguments of an entry function payload, so that the accounts they refer
+    /// to can be remapped. Empty for other payloads, whose arguments are typed already.
+    pub argument_types: Vec<TypeTag>,
+}
+
+/// A package referenced by the transactions of a trace.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct TracedPackage {
+    pub address: AccountAddress,
+    pub metadata: PackageMetadata,
+    /// Code of the modules, in the order of `metadata.modules`.
+    pub modules: Vec<Vec<u8>>,
+}
+
+#[derive(Clone, Default, Deserialize, Serialize)]
+pub struct WorkloadTrace {
+    pub packages: Vec<TracedPackage>,
+    /// Transactions, in the order they were committed.
+    pub transactions: Vec<TracedTransaction>,
+}
+
+impl fmt::Debug for WorkloadTrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WorkloadTrace")
+            .field("packages", &self.packages.len())
+            .field("transactions", &self.transactions.len())
+            .finish()
+    }
+}
+
+impl WorkloadTrace {
+    pub fn load(path: &Path) -> Result<Self> {
+        let bytes =
+            fs::read(path).with_context(|| format!("Failed to read trace {}", path.display()))?;
+        bcs::from_bytes(&bytes).with_context(|| format!("Failed to parse trace {}", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, bcs::to_bytes(self)?)
+            .with_context(|| format!("Failed to write trace {}", path.display()))
+    }
+
+    pub fn push_transaction(
+        &mut self,
+        timestamp_usecs: u64,
+        txn: &SignedTransaction,
+        argument_types: Vec<TypeTag>,
+    ) {
+        let authenticator = txn.authenticator_ref();
+        self.transactions.push(TracedTransaction {
+            timestamp_usecs,
+            sender: txn.sender(),
+            secondary_signers: authenticator.secondary_signer_addresses(),
+            fee_payer: authenticator.fee_payer_address(),
+            payload: txn.payload().clone(),
+            argument_types,
+        });
+    }
+
+    /// Addresses of the accounts which sign transactions or hold packages. Each of them is
+    /// replaced by a generated account when replaying the trace.
+    pub fn accounts(&self) -> BTreeSet<AccountAddress> {
+        let mut accounts = BTreeSet::new();
+        for txn in &self.transactions {
+            accounts.insert(txn.sender);
+            accounts.extend(txn.secondary_signers.iter().copied());
+            accounts.extend(txn.fee_payer);
+        }
+        accounts.extend(self.packages.iter().map(|package| package.address));
+        accounts.retain(|address| !address.is_special());
+        accounts
+    }
+
+    /// Addresses of the modules called by the transactions, or declaring their type arguments,
+    /// which are not part of the framework.
+    pub fn referenced_package_addresses(&self) -> BTreeSet<AccountAddress> {
+        let mut addresses = BTreeSet::new();
+        for txn in &self.transactions {
+            let ty_args = match &txn.payload {
+                TransactionPayload::EntryFunction(entry_function) => {
+                    addresses.insert(*entry_function.module().address());
+                    entry_function.ty_args()
+                },
+                TransactionPayload::Script(script) => script.ty_args(),
+                _ => continue,
+            };
+            for ty_arg in ty_args {
+                collect_addresses(ty_arg, &mut addresses);
+            }
+        }
+        addresses.retain(|address| !address.is_special());
+        addresses
+    }
+
+    /// Average rate of the traced transactions, or [None] if the trace does not span any time.
+    pub fn original_tps(&self) -> Option<f64> {
+        let first = self.transactions.first()?.timestamp_usecs;
+        let last = self.transactions.last()?.timestamp_usecs;
+        (last > first).then(|| self.transactions.len() as f64 * 1_000_000.0 / (last - first) as f64)
+    }
+
+    /// Largest number of traced transactions committed within one second.
+    pub fn peak_tps(&self) -> usize {
+        let mut peak = 0;
+        let mut window_start = 0;
+        for (idx, txn) in self.transactions.iter().enumerate() {
+            while txn.timestamp_usecs - self.transactions[window_start].timestamp_usecs
+                >= Duration::from_secs(1).as_micros() as u64
+            {
+                window_start += 1;
+            }
+            peak = peak.max(idx + 1 - window_start);
+        }
+        peak
+    }
+
+    /// Packages in an order in which they can be deployed, i.e. every package comes after the
+    /// packages of the trace it depends on.
+    pub fn packages_in_deployment_order(&self) -> Result<Vec<&TracedPackage>> {
+        let mut remaining: BTreeMap<(AccountAddress, &str), &TracedPackage> = self
+            .packages
+            .iter()
+            .map(|package| ((package.address, package.metadata.name.as_str()), package))
+            .collect();
+        let mut ordered = Vec::with_capacity(remaining.len());
+        while !remaining.is_empty() {
+            let ready = remaining
+                .iter()
+                .filter(|(_, package)| {
+                    package.metadata.deps.iter().all(|dep| {
+                        !remaining.contains_key(&(dep.account, dep.package_name.as_str()))
+                    })
+                })
+                .map(|(key, _)| *key)
+                .collect::<Vec<_>>();
+            if ready.is_empty() {
+                bail!("Packages of the trace have cyclic dependencies");
+            }
+            for key in ready {
+                ordered.push(remaining.remove(&key).unwrap());
+            }
+        }
+        Ok(ordered)
+    }
+}
+
+/// Types of the arguments of `entry_function`, which is declared in `module`.
+pub fn entry_function_argument_types(
+    module: &CompiledModule,
+    entry_function: &EntryFunction,
+) -> Result<Vec<TypeTag>> {
+    let handle = module
+        .function_defs()
+        .iter()
+        .map(|def| module.function_handle_at(def.function))
+        .find(|handle| module.identifier_at(handle.name) == entry_function.function())
+        .with_context(|| {
+            format!(
+                "Function {}::{} not found",
+                entry_function.module(),
+                entry_function.function()
+            )
+        })?;
+    module
+        .signature_at(handle.parameters)
+        .0
+        .iter()
+        // Signers are not passed as arguments.
+        .filter(|token| !is_signer(token))
+        .map(|token| instantiate(module, token, entry_function.ty_args()))
+        .collect()
+}
+
+fn is_signer(token: &SignatureToken) -> bool {
+    match token {
+        SignatureToken::Signer => true,
+        SignatureToken::Reference(inner) => **inner == SignatureToken::Signer,
+        _ => false,
+    }
+}
+
+fn instantiate(
+    module: &CompiledModule,
+    token: &SignatureToken,
+    ty_args: &[TypeTag],
+) -> Result<TypeTag> {
+    let struct_tag = |idx: &StructHandleIndex, type_args: Vec<TypeTag>| {
+        let handle = module.struct_handle_at(*idx);
+        let module_id = module.module_id_for_handle(module.module_handle_at(handle.module));
+        TypeTag::Struct(Box::new(StructTag {
+            address: *module_id.address(),
+            module: module_id.name().to_owned(),
+            name: module.identifier_at(handle.name).to_owned(),
+            type_args,
+        }))
+    };
+    Ok(match token {
+        SignatureToken::Bool => TypeTag::Bool,
+        SignatureToken::U8 => TypeTag::U8,
+        SignatureToken::U16 => TypeTag::U16,
+        SignatureToken::U32 => TypeTag::U32,
+        SignatureToken::U64 => TypeTag::U64,
+        SignatureToken::U128 => TypeTag::U128,
+        SignatureToken::U256 => TypeTag::U256,
+        SignatureToken::Address => TypeTag::Address,
+        SignatureToken::Signer => TypeTag::Signer,
+        SignatureToken::Vector(inner) => {
+            TypeTag::Vector(Box::new(instantiate(module, inner, ty_args)?))
+        },
+        SignatureToken::Struct(idx) => struct_tag(idx, vec![]),
+        SignatureToken::StructInstantiation(idx, type_args) => struct_tag(
+            idx,
+            type_args
+                .iter()
+                .map(|ty| instantiate(module, ty, ty_args))
+                .collect::<Result<_>>()?,
+        ),
+        SignatureToken::TypeParameter(idx) => ty_args
+            .get(*idx as usize)
+            .cloned()
+            .with_context(|| format!("Missing type argument {}", idx))?,
+        SignatureToken::Reference(_) | SignatureToken::MutableReference(_) => {
+            bail!("Arguments of entry functions cannot be references")
+        },
+    })
+}
+
+/// Whether values of type `ty` are encoded as a single address, i.e. are addresses or objects.
+fn is_address_like(ty: &TypeTag) -> bool {
+    match ty {
+        TypeTag::Address => true,
+        TypeTag::Struct(struct_tag) => {
+            struct_tag.address == AccountAddress::ONE
+                && struct_tag.module.as_str() == "object"
+                && struct_tag.name.as_str() == "Object"
+        },
+        _ => false,
+    }
+}
+
+/// Whether values of type `ty` are encoded as a vector of addresses, i.e. are vectors or
+/// options of addresses or objects.
+fn is_address_vector_like(ty: &TypeTag) -> bool {
+    match ty {
+        TypeTag::Vector(inner) => is_address_like(inner),
+        TypeTag::Struct(struct_tag) => {
+            struct_tag.address == AccountAddress::ONE
+                && struct_tag.module.as_str() == "option"
+                && struct_tag.name.as_str() == "Option"
+                && struct_tag.type_args.first().is_some_and(is_address_like)
+        },
+        _ => false,
+    }
+}
+
+fn collect_addresses(ty: &TypeTag, addresses: &mut BTreeSet<AccountAddress>) {
+    match ty {
+        TypeTag::Vector(inner) => collect_addresses(inner, addresses),
+        TypeTag::Struct(struct_tag) => {
+            addresses.insert(struct_tag.address);
+            for ty_arg in &struct_tag.type_args {
+                collect_addresses(ty_arg, addresses);
+            }
+        },
+        _ => {},
+    }
+}
+
+/// Consistently replaces the addresses of a trace with the addresses of generated accounts.
+pub struct AddressRemapper {
+    mapping: HashMap<AccountAddress, AccountAddress>,
+}
+
+impl AddressRemapper {
+    pub fn new(mapping: HashMap<AccountAddress, AccountAddress>) -> Self {
+        Self { mapping }
+    }
+
+    pub fn address(&self, address: AccountAddress) -> AccountAddress {
+        self.mapping.get(&address).copied().unwrap_or(address)
+    }
+
+    pub fn type_tag(&self, ty: &TypeTag) -> TypeTag {
+        match ty {
+            TypeTag::Vector(inner) => TypeTag::Vector(Box::new(self.type_tag(inner))),
+            TypeTag::Struct(struct_tag) => TypeTag::Struct(Box::new(StructTag {
+                address: self.address(struct_tag.address),
+                module: struct_tag.module.clone(),
+                name: struct_tag.name.clone(),
+                type_args: struct_tag
+                    .type_args
+                    .iter()
+                    .map(|ty_arg| self.type_tag(ty_arg))
+                    .collect(),
+            })),
+            _ => ty.clone(),
+        }
+    }
+
+    /// Remaps a BCS encoded entry function argument of type `ty`. Addresses and objects, and
+    /// vectors and options of them, are remapped, while other values are kept as they are.
+    fn argument(&self, ty: &TypeTag, arg: &[u8]) -> Result<Vec<u8>> {
+        Ok(if is_address_like(ty) {
+            bcs::to_bytes(&self.address(bcs::from_bytes(arg)?))?
+        } else if is_address_vector_like(ty) {
+            let addresses = bcs::from_bytes::<Vec<AccountAddress>>(arg)?;
+            bcs::to_bytes(
+                &addresses
+                    .into_iter()
+                    .map(|address| self.address(address))
+                    .collect::<Vec<_>>(),
+            )?
+        } else {
+            arg.to_vec()
+        })
+    }
+
+    fn address_constant(&self, type_: &SignatureToken, data: &mut [u8]) {
+        match type_ {
+            SignatureToken::Address => {
+                if let Ok(address) = AccountAddress::from_bytes(&*data) {
+                    data.copy_from_slice(&self.address(address).into_bytes());
+                }
+            },
+            SignatureToken::Vector(inner) if **inner == SignatureToken::Address => {
+                if let Ok(addresses) = bcs::from_bytes::<Vec<AccountAddress>>(data) {
+                    let remapped = addresses
+                        .into_iter()
+                        .map(|address| self.address(address))
+                        .collect::<Vec<_>>();
+                    data.copy_from_slice(&bcs::to_bytes(&remapped).unwrap());
+                }
+            },
+            _ => {},
+        }
+    }
+
+    /// Remaps the payload of a transaction. Returns [None] for payloads which cannot be
+    /// replayed.
+    pub fn payload(&self, txn: &TracedTransaction) -> Result<Option<TransactionPayload>> {
+        Ok(match &txn.payload {
+            TransactionPayload::EntryFunction(entry_function) => {
+                // Packages are deployed from the trace before replaying.
+                if entry_function.module()
+                    == &ModuleId::new(AccountAddress::ONE, ident_str!("code").to_owned())
+                    && entry_function.function() == ident_str!("publish_package_txn")
+                {
+                    return Ok(None);
+                }
+                let (module, function, ty_args, args) = entry_function.clone().into_inner();
+                if args.len() != txn.argument_types.len() {
+                    bail!(
+                        "Call to {}::{} has {} arguments, but {} argument types",
+                        module,
+                        function,
+                        args.len(),
+                        txn.argument_types.len()
+                    );
+                }
+                Some(TransactionPayload::EntryFunction(EntryFunction::new(
+                    ModuleId::new(self.address(*module.address()), module.name().to_owned()),
+                    function,
+                    ty_args.iter().map(|ty| self.type_tag(ty)).collect(),
+                    txn.argument_types
+                        .iter()
+                        .zip(args.iter())
+                        .map(|(ty, arg)| self.argument(ty, arg))
+                        .collect::<Result<_>>()?,
+                )))
+            },
+            TransactionPayload::Script(script) => {
+                let mut compiled = CompiledScript::deserialize(script.code())
+                    .context("Script of the trace must deserialize")?;
+                for address in &mut compiled.address_identifiers {
+                    *address = self.address(*address);
+                }
+                for constant in &mut compiled.constant_pool {
+                    self.address_constant(&constant.type_, &mut constant.data);
+                }
+                let mut code = vec![];
+                compiled.serialize(&mut code)?;
+                Some(TransactionPayload::Script(Script::new(
+                    code,
+                    script
+                        .ty_args()
+                        .iter()
+                        .map(|ty| self.type_tag(ty))
+                        .collect(),
+                    script
+                        .args()
+                        .iter()
+                        .map(|arg| match arg {
+                            TransactionArgument::Address(address) => {
+                                TransactionArgument::Address(self.address(*address))
+                            },
+                            arg => arg.clone(),
+                        })
+                        .collect(),
+                )))
+            },
+            TransactionPayload::Multisig(_) | TransactionPayload::ModuleBundle(_) => None,
+        })
+    }
+
+    /// Returns the payload deploying `package` at the address it is remapped to.
+    pub fn publish_payload(&self, package: &TracedPackage) -> Result<TransactionPayload> {
+        let mut metadata = package.metadata.clone();
+        metadata.deps = metadata
+            .deps
+            .into_iter()
+            .map(|dep| PackageDep {
+                account: self.address(dep.account),
+                package_name: dep.package_name,
+            })
+            .collect();
+        let modules = package
+            .modules
+            .iter()
+            .map(|code| self.module(code))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(aptos_stdlib::code_publish_package_txn(
+            bcs::to_bytes(&metadata)?,
+            modules,
+        ))
+    }
+
+    fn module(&self, code: &[u8]) -> Result<Vec<u8>> {
+        let mut module =
+            CompiledModule::deserialize(code).context("Module of the trace must deserialize")?;
+        for address in &mut module.address_identifiers {
+            *address = self.address(*address);
+        }
+        for constant in &mut module.constant_pool {
+            self.address_constant(&constant.type_, &mut constant.data);
+        }
+        if let Some(mut metadata) = aptos_framework::get_metadata_from_compiled_module(&module) {
+            for attrs in metadata.struct_attributes.values_mut() {
+                for attr in attrs.iter_mut() {
+                    if let Some(member) = attr.get_resource_group_member() {
+                        let new_full_name = format!(
+                            "{}::{}::{}",
+                            self.address(member.address).to_standard_string(),
+                            member.module,
+                            member.name
+                        );
+                        *attr = KnownAttribute::resource_group_member(new_full_name);
+                    }
+                }
+            }
+            for metadata_holder in &mut module.metadata {
+                if metadata_holder.key == APTOS_METADATA_KEY_V1
+                    || metadata_holder.key == APTOS_METADATA_KEY
+                {
+                    metadata_holder.value = bcs::to_bytes(&metadata)?;
+                }
+            }
+        }
+        let mut code = vec![];
+        module.serialize(&mut code)?;
+        Ok(code)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_framework::natives::code::{MoveOption, UpgradePolicy};
+    use std::str::FromStr;
+
+    fn package(
+        address: AccountAddress,
+        name: &str,
+        deps: &[(AccountAddress, &str)],
+    ) -> TracedPackage {
+        TracedPackage {
+            address,
+            metadata: PackageMetadata {
+                name: name.to_string(),
+                upgrade_policy: UpgradePolicy::compat(),
+                upgrade_number: 0,
+                source_digest: String::new(),
+                manifest: vec![],
+                modules: vec![],
+                deps: deps
+                    .iter()
+                    .map(|(account, package_name)| PackageDep {
+                        account: *account,
+                        package_name: package_name.to_string(),
+                    })
+                    .collect(),
+                extension: MoveOption::none(),
+            },
+            modules: vec![],
+        }
+    }
+
+    #[test]
+    fn test_deployment_order() {
+        let a = AccountAddress::from_hex_literal("0xa11ce").unwrap();
+        let b = AccountAddress::from_hex_literal("0xb0b").unwrap();
+        let trace = WorkloadTrace {
+            packages: vec![
+                package(a, "app", &[
+                    (b, "lib"),
+                    (AccountAddress::ONE, "AptosFramework"),
+                ]),
+                package(b, "lib", &[(b, "base")]),
+                package(b, "base", &[]),
+            ],
+            transactions: vec![],
+        };
+        let order = trace
+            .packages_in_deployment_order()
+            .unwrap()
+            .into_iter()
+            .map(|package| package.metadata.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(order, vec!["base", "lib", "app"]);
+
+        let cyclic = WorkloadTrace {
+            packages: vec![package(a, "x", &[(b, "y")]), package(b, "y", &[(a, "x")])],
+            transactions: vec![],
+        };
+        assert!(cyclic.packages_in_deployment_order().is_err());
+    }
+
+    #[test]
+    fn test_remap_arguments() {
+        let original = AccountAddress::from_hex_literal("0xa11ce").unwrap();
+        let other = AccountAddress::from_hex_literal("0xb0b").unwrap();
+        let generated = AccountAddress::random();
+        let remapper = AddressRemapper::new(HashMap::from([(original, generated)]));
+        let remap = |ty: &str, arg: Vec<u8>| {
+            remapper
+                .argument(&TypeTag::from_str(ty).unwrap(), &arg)
+                .unwrap()
+        };
+
+        assert_eq!(
+            remap("address", bcs::to_bytes(&original).unwrap()),
+            bcs::to_bytes(&generated).unwrap()
+        );
+        assert_eq!(
+            remap(
+                "0x1::object::Object<0x1::fungible_asset::Metadata>",
+                bcs::to_bytes(&original).unwrap()
+            ),
+            bcs::to_bytes(&generated).unwrap()
+        );
+        // Addresses which are not accounts of the trace are kept.
+        assert_eq!(
+            remap(
+                "vector<address>",
+                bcs::to_bytes(&vec![original, other]).unwrap()
+            ),
+            bcs::to_bytes(&vec![generated, other]).unwrap()
+        );
+        assert_eq!(
+            remap(
+                "0x1::option::Option<address>",
+                bcs::to_bytes(&vec![original]).unwrap()
+            ),
+            bcs::to_bytes(&vec![generated]).unwrap()
+        );
+        // Values of other types are kept, even if they decode as an address.
+        let bytes = original.to_vec();
+        assert_eq!(
+            remap("vector<u8>", bcs::to_bytes(&bytes).unwrap()),
+            bcs::to_bytes(&bytes).unwrap()
+        );
+        let amount = bcs::to_bytes(&100u64).unwrap();
+        assert_eq!(remap("u64", amount.clone()), amount);
+    }
+
+    #[test]
+    fn test_remap_payload_requires_argument_types() {
+        let remapper = AddressRemapper::new(HashMap::new());
+        let mut txn = TracedTransaction {
+            timestamp_usecs: 0,
+            sender: AccountAddress::random(),
+            secondary_signers: vec![],
+            fee_payer: None,
+            payload: aptos_stdlib::aptos_account_transfer(AccountAddress::random(), 1),
+            argument_types: vec![],
+        };
+        assert!(remapper.payload(&txn).is_err());
+        txn.argument_types = vec![TypeTag::Address, TypeTag::U64];
+        assert!(remapper.payload(&txn).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_peak_tps() {
+        let txn = |timestamp_usecs| TracedTransaction {
+            timestamp_usecs,
+            sender: AccountAddress::ONE,
+            secondary_signers: vec![],
+            fee_payer: None,
+            payload: aptos_stdlib::aptos_account_transfer(AccountAddress::ONE, 1),
+            argument_types: vec![TypeTag::Address, TypeTag::U64],
+        };
+        let trace = WorkloadTrace {
+            packages: vec![],
+            transactions: [0, 100_000, 2_000_000, 2_100_000, 2_200_000, 3_100_000]
+                .into_iter()
+                .map(txn)
+                .collect(),
+        };
+        assert_eq!(trace.peak_tps(), 3);
+    }
+}
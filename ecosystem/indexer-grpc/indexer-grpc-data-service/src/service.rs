@@ -29,7 +29,7 @@ use aptos_protos::{
     indexer::v1::{raw_data_server::RawData, GetTransactionsRequest, TransactionsResponse},
     transaction::v1::{transaction::TxnData, Transaction},
 };
-use aptos_transaction_filter::{BooleanTransactionFilter, Filterable};
+use aptos_transaction_filter::{BooleanTransactionFilter, FieldMask, Filterable};
 use futures::Stream;
 use prost::Message;
 use redis::Client;
@@ -154,6 +154,17 @@ impl RawData for RawDataServerWrapper {
             Ok(request_metadata) => request_metadata,
             _ => return Result::Err(Status::aborted("Invalid request token")),
         };
+        let txns_to_strip_filter = get_txns_to_strip_filter(
+            &self.txns_to_strip_filter,
+            req.get_ref().transaction_filter.as_deref(),
+        )?;
+        let field_mask = req
+            .get_ref()
+            .field_mask
+            .as_ref()
+            .map(FieldMask::try_from)
+            .transpose()
+            .map_err(|e| Status::invalid_argument(format!("Invalid field mask: {}", e)))?;
         CONNECTION_COUNT
             .with_label_values(&request_metadata.get_label_values())
             .inc();
@@ -193,7 +204,6 @@ impl RawData for RawDataServerWrapper {
         let redis_client = self.redis_client.clone();
         let cache_storage_format = self.cache_storage_format;
         let request_metadata = Arc::new(request_metadata);
-        let txns_to_strip_filter = self.txns_to_strip_filter.clone();
         let in_memory_cache = self.in_memory_cache.clone();
         tokio::spawn({
             let request_metadata = request_metadata.clone();
@@ -206,6 +216,7 @@ impl RawData for RawDataServerWrapper {
                     transactions_count,
                     tx,
                     txns_to_strip_filter,
+                    field_mask,
                     current_version,
                     in_memory_cache,
                 )
@@ -394,6 +405,7 @@ async fn data_fetcher_task(
     transactions_count: Option<u64>,
     tx: tokio::sync::mpsc::Sender<Result<TransactionsResponse, Status>>,
     txns_to_strip_filter: BooleanTransactionFilter,
+    field_mask: Option<FieldMask>,
     mut current_version: u64,
     in_memory_cache: Arc<InMemoryCache>,
 ) {
@@ -532,6 +544,7 @@ async fn data_fetcher_task(
             transaction_data,
             chain_id as u32,
             &txns_to_strip_filter,
+            field_mask.as_ref(),
         );
         NUM_TRANSACTIONS_STRIPPED
             .with_label_values(&request_metadata.get_label_values())
@@ -685,14 +698,21 @@ fn ensure_sequential_transactions(mut batches: Vec<Vec<Transaction>>) -> Vec<Tra
 /// Builds the response for the get transactions request. Partial batch is ok, i.e., a
 /// batch with transactions < 1000.
 ///
-/// It also returns the number of txns that were stripped.
+/// Transactions that match the filter are stripped, and then the field mask, if any, is
+/// applied to all of them. It also returns the number of txns that were stripped.
 fn get_transactions_responses_builder(
     transactions: Vec<Transaction>,
     chain_id: u32,
     txns_to_strip_filter: &BooleanTransactionFilter,
+    field_mask: Option<&FieldMask>,
 ) -> (Vec<TransactionsResponse>, usize) {
-    let (stripped_transactions, num_stripped) =
+    let (mut stripped_transactions, num_stripped) =
         strip_transactions(transactions, txns_to_strip_filter);
+    if let Some(field_mask) = field_mask {
+        stripped_transactions
+            .iter_mut()
+            .for_each(|txn| field_mask.apply(txn));
+    }
     let chunks = chunk_transactions(stripped_transactions, MESSAGE_SIZE_LIMIT);
     let responses = chunks
         .into_iter()
@@ -970,6 +990,24 @@ async fn channel_send_multiple_with_timeout(
     Ok(())
 }
 
+/// Combines the filter of transactions to strip from the config with the filter
+/// expression of a request, if any. Transactions that don't match the filter of the
+/// request are stripped rather than skipped, so the client still sees every version.
+fn get_txns_to_strip_filter(
+    txns_to_strip_filter: &BooleanTransactionFilter,
+    transaction_filter: Option<&str>,
+) -> Result<BooleanTransactionFilter, Status> {
+    match transaction_filter {
+        Some(transaction_filter) => {
+            let filter = BooleanTransactionFilter::from_str(transaction_filter).map_err(|e| {
+                Status::invalid_argument(format!("Invalid transaction filter: {}", e))
+            })?;
+            Ok(txns_to_strip_filter.clone().or(filter.not()))
+        },
+        None => Ok(txns_to_strip_filter.clone()),
+    }
+}
+
 /// This function strips transactions that match the given filter. Stripping means we
 /// remove the payload, signature, events, and writesets. Note, the filter can be
 /// composed of many conditions, see `BooleanTransactionFilter` for more.
@@ -1316,4 +1354,52 @@ mod tests {
         assert_ne!(user_transaction.events.len(), 0);
         assert_ne!(txn.info.as_ref().unwrap().changes.len(), 0);
     }
+
+    #[test]
+    fn test_transactions_are_stripped_by_request_filter() {
+        let txn = create_test_transaction(
+            MODULE_ADDRESS.to_string(),
+            MODULE_NAME.to_string(),
+            FUNCTION_NAME.to_string(),
+        );
+        let config_filter = BooleanTransactionFilter::new_or(vec![]);
+
+        // Transactions that match the request filter are not stripped.
+        let filter = get_txns_to_strip_filter(
+            &config_filter,
+            Some(&format!(
+                "function ~ \"{}::{}::*\"",
+                MODULE_ADDRESS, MODULE_NAME
+            )),
+        )
+        .unwrap();
+        let (_, num_stripped) = strip_transactions(vec![txn.clone()], &filter);
+        assert_eq!(num_stripped, 0);
+
+        // Transactions that don't match the request filter are stripped.
+        let filter =
+            get_txns_to_strip_filter(&config_filter, Some("function ~ \"0x1::coin::*\"")).unwrap();
+        let (filtered_txns, num_stripped) = strip_transactions(vec![txn.clone()], &filter);
+        assert_eq!(num_stripped, 1);
+        assert_eq!(filtered_txns.len(), 1);
+
+        // Transactions that match the config filter are stripped regardless.
+        let config_filter =
+            BooleanTransactionFilter::from_str(&format!("function ~ \"{}::*::*\"", MODULE_ADDRESS))
+                .unwrap();
+        let filter = get_txns_to_strip_filter(
+            &config_filter,
+            Some(&format!(
+                "function ~ \"{}::{}::*\"",
+                MODULE_ADDRESS, MODULE_NAME
+            )),
+        )
+        .unwrap();
+        let (_, num_stripped) = strip_transactions(vec![txn], &filter);
+        assert_eq!(num_stripped, 1);
+
+        // Invalid filters are rejected.
+        let status = get_txns_to_strip_filter(&config_filter, Some("sender ==")).unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    }
 }
@@ -6,9 +6,11 @@ use aptos_logger::{error, info};
 use aptos_protos::{
     indexer::v1::{raw_data_server::RawData, GetTransactionsRequest, TransactionsResponse},
     internal::fullnode::v1::transactions_from_node_response,
+    transaction::v1::{transaction::TxnData, Transaction},
 };
+use aptos_transaction_filter::{BooleanTransactionFilter, FieldMask, Filterable};
 use futures::Stream;
-use std::pin::Pin;
+use std::{pin::Pin, str::FromStr};
 use tokio::sync::mpsc;
 use tokio_stream::wrappers::ReceiverStream;
 use tonic::{Request, Response, Status};
@@ -44,6 +46,18 @@ impl RawData for LocalnetDataService {
         let output_batch_size = self.service_context.output_batch_size;
         let ledger_chain_id = context.chain_id().id();
         let transactions_count = r.transactions_count;
+        let transaction_filter = r
+            .transaction_filter
+            .as_deref()
+            .map(BooleanTransactionFilter::from_str)
+            .transpose()
+            .map_err(|e| Status::invalid_argument(format!("Invalid transaction filter: {}", e)))?;
+        let field_mask = r
+            .field_mask
+            .as_ref()
+            .map(FieldMask::try_from)
+            .transpose()
+            .map_err(|e| Status::invalid_argument(format!("Invalid field mask: {}", e)))?;
         // Creates a channel to send the stream to the client
         let (tx, mut rx) = mpsc::channel(TRANSACTION_CHANNEL_SIZE);
         let (external_service_tx, external_service_rx) = mpsc::channel(TRANSACTION_CHANNEL_SIZE);
@@ -101,6 +115,17 @@ impl RawData for LocalnetDataService {
                                     transactions.into_iter().take(*count as usize).collect();
                                 *count = count.saturating_sub(current_transactions_count);
                             }
+                            if let Some(filter) = transaction_filter.as_ref() {
+                                transactions
+                                    .iter_mut()
+                                    .filter(|txn| !filter.is_allowed(&**txn))
+                                    .for_each(strip_transaction);
+                            }
+                            if let Some(field_mask) = field_mask.as_ref() {
+                                transactions
+                                    .iter_mut()
+                                    .for_each(|txn| field_mask.apply(txn));
+                            }
                             transactions
                         },
                         _ => panic!("Unexpected response type."),
@@ -125,3 +150,19 @@ impl RawData for LocalnetDataService {
         ))
     }
 }
+
+/// Like the data service, transactions that don't match the filter of the request are
+/// stripped rather than skipped, so the client still sees every version. Stripping removes
+/// the payload, signature, events and changes.
+fn strip_transaction(txn: &mut Transaction) {
+    if let Some(info) = txn.info.as_mut() {
+        info.changes = vec![];
+    }
+    if let Some(TxnData::User(user_transaction)) = txn.txn_data.as_mut() {
+        user_transaction.events = vec![];
+        if let Some(request) = user_transaction.request.as_mut() {
+            request.payload = None;
+            request.signature = None;
+        }
+    }
+}
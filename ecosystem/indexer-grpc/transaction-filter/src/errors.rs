@@ -95,3 +95,32 @@ impl Serialize for SerializableError {
         serializer.serialize_str(&self.inner.to_string())
     }
 }
+
+/// Error from parsing a filter expression, pointing at where in the expression it went wrong
+/// Example output looks like:
+/// ```text
+/// Expected a value after `==` but found `&&` at position 10
+/// sender == && success
+///           ^
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, ThisError)]
+pub struct ExpressionError {
+    pub expression: String,
+    /// Byte offset into the expression
+    pub position: usize,
+    pub message: String,
+}
+
+impl Display for ExpressionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let column = self.expression[..self.position].chars().count();
+        write!(
+            f,
+            "{} at position {}\n{}\n{}^",
+            self.message,
+            self.position,
+            self.expression,
+            " ".repeat(column)
+        )
+    }
+}
@@ -0,0 +1,599 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! A compact textual language for transaction filters, which parses into a
+//! `BooleanTransactionFilter`. For example:
+//! ```text
+//! sender == 0x1 && event.type ~ "0x1::coin::*" && !success
+//! ```
+//!
+//! Grammar:
+//! ```text
+//! expression := or
+//! or         := and ("||" and)*
+//! and        := unary ("&&" unary)*
+//! unary      := "!" unary | "(" expression ")" | "success" | field op value
+//! op         := "==" | "!=" | "~"
+//! value      := word | "quoted string"
+//! ```
+//!
+//! Fields:
+//! - `success`: `true` or `false`. On its own, `success` means `success == true`.
+//! - `type`: the transaction type, e.g. `user`, `block_metadata` or `genesis`.
+//! - `sender`: the sender of a user transaction.
+//! - `function`: the entry function of a user transaction, as `address::module::name`.
+//! - `event.type`: the struct type of events, as `address::module::name`.
+//! - `event.data`: the JSON data of events.
+//!
+//! `==` and `!=` compare exactly. `~` matches `function` and `event.type` against a
+//! pattern, where any of the address, module and name can be `*` to match anything.
+//! Addresses are normalized to the short form, e.g. `0x0001` is the same as `0x1`.
+
+use crate::{
+    boolean_transaction_filter::{APIFilter, BooleanTransactionFilter},
+    errors::ExpressionError,
+    filters::{
+        EntryFunctionFilter, EventFilter, MoveStructTagFilter, TransactionRootFilter,
+        UserTransactionFilter, UserTransactionPayloadFilter,
+    },
+};
+use aptos_protos::transaction::v1::transaction::TransactionType;
+use std::str::FromStr;
+
+/// Maximum nesting of parentheses and `!`, so parsing can't overflow the stack
+const MAX_DEPTH: usize = 64;
+
+const FIELDS: &[&str] = &[
+    "success",
+    "type",
+    "sender",
+    "function",
+    "event.type",
+    "event.data",
+];
+
+impl FromStr for BooleanTransactionFilter {
+    type Err = ExpressionError;
+
+    /// Parses a filter expression, see the `expression` module for the syntax
+    fn from_str(expression: &str) -> Result<Self, Self::Err> {
+        parse(expression)
+    }
+}
+
+/// Parses a filter expression, see the module docs for the syntax
+pub fn parse(expression: &str) -> Result<BooleanTransactionFilter, ExpressionError> {
+    let tokens = tokenize(expression)?;
+    let mut parser = Parser {
+        expression,
+        tokens,
+        index: 0,
+        depth: 0,
+    };
+    let filter = parser.parse_or()?;
+    let token = parser.peek();
+    if token.kind != TokenKind::End {
+        let message = format!("Unexpected {}", token.kind);
+        return Err(parser.error_at(token, message));
+    }
+    Ok(filter)
+}
+
+/// Parses a struct type pattern such as `0x1::coin::*`, like the value of `event.type ~`
+pub fn parse_struct_type_pattern(pattern: &str) -> Result<MoveStructTagFilter, ExpressionError> {
+    let (address, module, name) = parse_path(pattern, true).map_err(|message| ExpressionError {
+        expression: pattern.to_string(),
+        position: 0,
+        message,
+    })?;
+    Ok(MoveStructTagFilter {
+        address,
+        module,
+        name,
+    })
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum TokenKind {
+    LeftParen,
+    RightParen,
+    And,
+    Or,
+    Not,
+    Equal,
+    NotEqual,
+    Match,
+    Word(String),
+    Quoted(String),
+    End,
+}
+
+impl std::fmt::Display for TokenKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TokenKind::LeftParen => write!(f, "`(`"),
+            TokenKind::RightParen => write!(f, "`)`"),
+            TokenKind::And => write!(f, "`&&`"),
+            TokenKind::Or => write!(f, "`||`"),
+            TokenKind::Not => write!(f, "`!`"),
+            TokenKind::Equal => write!(f, "`==`"),
+            TokenKind::NotEqual => write!(f, "`!=`"),
+            TokenKind::Match => write!(f, "`~`"),
+            TokenKind::Word(word) => write!(f, "`{}`", word),
+            TokenKind::Quoted(value) => write!(f, "{:?}", value),
+            TokenKind::End => write!(f, "end of expression"),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Token {
+    kind: TokenKind,
+    /// Byte offset of the start of the token in the expression
+    position: usize,
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | ':' | '*')
+}
+
+fn tokenize(expression: &str) -> Result<Vec<Token>, ExpressionError> {
+    let error = |position: usize, message: String| ExpressionError {
+        expression: expression.to_string(),
+        position,
+        message,
+    };
+
+    let mut tokens = vec![];
+    let mut chars = expression.char_indices().peekable();
+    while let Some((position, c)) = chars.next() {
+        let kind = match c {
+            c if c.is_whitespace() => continue,
+            '(' => TokenKind::LeftParen,
+            ')' => TokenKind::RightParen,
+            '~' => TokenKind::Match,
+            '&' | '|' | '=' => {
+                if chars.next_if(|(_, next)| *next == c).is_none() {
+                    return Err(error(position, format!("Expected `{}{}`", c, c)));
+                }
+                match c {
+                    '&' => TokenKind::And,
+                    '|' => TokenKind::Or,
+                    _ => TokenKind::Equal,
+                }
+            },
+            '!' => {
+                if chars.next_if(|(_, next)| *next == '=').is_some() {
+                    TokenKind::NotEqual
+                } else {
+                    TokenKind::Not
+                }
+            },
+            '"' => {
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '"')) => break,
+                        Some((escape_position, '\\')) => match chars.next() {
+                            Some((_, escaped @ ('"' | '\\'))) => value.push(escaped),
+                            _ => {
+                                return Err(error(
+                                    escape_position,
+                                    "Only `\\\"` and `\\\\` can be escaped".to_string(),
+                                ))
+                            },
+                        },
+                        Some((_, c)) => value.push(c),
+                        None => return Err(error(position, "Unterminated string".to_string())),
+                    }
+                }
+                TokenKind::Quoted(value)
+            },
+            c if is_word_char(c) => {
+                let mut word = c.to_string();
+                while let Some((_, c)) = chars.next_if(|(_, c)| is_word_char(*c)) {
+                    word.push(c);
+                }
+                TokenKind::Word(word)
+            },
+            c => return Err(error(position, format!("Unexpected character `{}`", c))),
+        };
+        tokens.push(Token { kind, position });
+    }
+    tokens.push(Token {
+        kind: TokenKind::End,
+        position: expression.len(),
+    });
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    expression: &'a str,
+    tokens: Vec<Token>,
+    index: usize,
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Token {
+        self.tokens[self.index].clone()
+    }
+
+    fn next(&mut self) -> Token {
+        let token = self.peek();
+        // The last token is always End, which we never move past
+        if token.kind != TokenKind::End {
+            self.index += 1;
+        }
+        token
+    }
+
+    fn error_at(&self, token: Token, message: String) -> ExpressionError {
+        ExpressionError {
+            expression: self.expression.to_string(),
+            position: token.position,
+            message,
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<BooleanTransactionFilter, ExpressionError> {
+        let mut filters = vec![self.parse_and()?];
+        while self.peek().kind == TokenKind::Or {
+            self.next();
+            filters.push(self.parse_and()?);
+        }
+        Ok(if filters.len() == 1 {
+            filters.remove(0)
+        } else {
+            BooleanTransactionFilter::new_or(filters)
+        })
+    }
+
+    fn parse_and(&mut self) -> Result<BooleanTransactionFilter, ExpressionError> {
+        let mut filters = vec![self.parse_unary()?];
+        while self.peek().kind == TokenKind::And {
+            self.next();
+            filters.push(self.parse_unary()?);
+        }
+        Ok(if filters.len() == 1 {
+            filters.remove(0)
+        } else {
+            BooleanTransactionFilter::new_and(filters)
+        })
+    }
+
+    fn parse_unary(&mut self) -> Result<BooleanTransactionFilter, ExpressionError> {
+        let token = self.next();
+        if self.depth >= MAX_DEPTH {
+            return Err(self.error_at(token, "Expression is nested too deeply".to_string()));
+        }
+        match token.kind.clone() {
+            TokenKind::Not => {
+                self.depth += 1;
+                let filter = self.parse_unary()?;
+                self.depth -= 1;
+                Ok(filter.not())
+            },
+            TokenKind::LeftParen => {
+                self.depth += 1;
+                let filter = self.parse_or()?;
+                self.depth -= 1;
+                let closing = self.next();
+                if closing.kind != TokenKind::RightParen {
+                    return Err(self.error_at(
+                        closing.clone(),
+                        format!("Expected `)` but found {}", closing.kind),
+                    ));
+                }
+                Ok(filter)
+            },
+            TokenKind::Word(field) => self.parse_predicate(token, &field),
+            kind => Err(self.error_at(token, format!("Expected a filter but found {}", kind))),
+        }
+    }
+
+    fn parse_predicate(
+        &mut self,
+        field_token: Token,
+        field: &str,
+    ) -> Result<BooleanTransactionFilter, ExpressionError> {
+        if !FIELDS.contains(&field) {
+            return Err(self.error_at(
+                field_token,
+                format!(
+                    "Unknown field `{}`, expected one of {}",
+                    field,
+                    FIELDS.join(", ")
+                ),
+            ));
+        }
+
+        let op_token = self.peek();
+        let op = match &op_token.kind {
+            TokenKind::Equal | TokenKind::NotEqual | TokenKind::Match => self.next().kind,
+            // `success` on its own means the transaction succeeded
+            _ if field == "success" => return Ok(success_filter(true)),
+            kind => {
+                return Err(self.error_at(
+                    op_token.clone(),
+                    format!(
+                        "Expected `==`, `!=` or `~` after `{}` but found {}",
+                        field, kind
+                    ),
+                ))
+            },
+        };
+
+        let value_token = self.next();
+        let value = match &value_token.kind {
+            TokenKind::Word(value) | TokenKind::Quoted(value) => value.clone(),
+            kind => {
+                return Err(self.error_at(
+                    value_token.clone(),
+                    format!("Expected a value after {} but found {}", op, kind),
+                ))
+            },
+        };
+        let value_error = |message: String| self.error_at(value_token.clone(), message);
+
+        if op == TokenKind::Match && !matches!(field, "function" | "event.type") {
+            return Err(self.error_at(
+                op_token,
+                format!(
+                    "`~` is only supported for `function` and `event.type`, not `{}`",
+                    field
+                ),
+            ));
+        }
+
+        let filter: BooleanTransactionFilter = match field {
+            "success" => match value.as_str() {
+                "true" => success_filter(true),
+                "false" => success_filter(false),
+                _ => return Err(value_error("Expected `true` or `false`".to_string())),
+            },
+            "type" => {
+                let txn_type = TransactionType::from_str_name(&format!(
+                    "TRANSACTION_TYPE_{}",
+                    value.to_ascii_uppercase()
+                ))
+                .filter(|txn_type| *txn_type != TransactionType::Unspecified)
+                .ok_or_else(|| {
+                    value_error(format!(
+                        "Unknown transaction type `{}`, expected one of user, genesis, \
+                         block_metadata, state_checkpoint, validator, block_epilogue",
+                        value
+                    ))
+                })?;
+                TransactionRootFilter {
+                    success: None,
+                    txn_type: Some(txn_type),
+                }
+                .into()
+            },
+            "sender" => UserTransactionFilter {
+                sender: Some(normalize_address(&value).map_err(value_error)?),
+                payload: None,
+            }
+            .into(),
+            "function" => {
+                let (address, module, name) =
+                    parse_path(&value, op == TokenKind::Match).map_err(value_error)?;
+                UserTransactionFilter {
+                    sender: None,
+                    payload: Some(UserTransactionPayloadFilter {
+                        function: Some(EntryFunctionFilter {
+                            address,
+                            module,
+                            function: name,
+                        }),
+                    }),
+                }
+                .into()
+            },
+            "event.type" => {
+                let (address, module, name) =
+                    parse_path(&value, op == TokenKind::Match).map_err(value_error)?;
+                EventFilter {
+                    data: None,
+                    struct_type: Some(MoveStructTagFilter {
+                        address,
+                        module,
+                        name,
+                    }),
+                }
+                .into()
+            },
+            "event.data" => EventFilter {
+                data: Some(value),
+                struct_type: None,
+            }
+            .into(),
+            _ => unreachable!("Fields are checked above"),
+        };
+
+        Ok(if op == TokenKind::NotEqual {
+            filter.not()
+        } else {
+            filter
+        })
+    }
+}
+
+fn success_filter(success: bool) -> BooleanTransactionFilter {
+    BooleanTransactionFilter::new_filter(APIFilter::TransactionRootFilter(TransactionRootFilter {
+        success: Some(success),
+        txn_type: None,
+    }))
+}
+
+/// Normalizes an address to the short form used in transactions, e.g. `0x0001` to `0x1`
+fn normalize_address(address: &str) -> Result<String, String> {
+    let hex = address
+        .strip_prefix("0x")
+        .ok_or_else(|| format!("Address `{}` must start with 0x", address))?;
+    if hex.is_empty() || hex.len() > 64 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!(
+            "Address `{}` must be 0x followed by 1 to 64 hex characters",
+            address
+        ));
+    }
+    let trimmed = hex.trim_start_matches('0').to_ascii_lowercase();
+    Ok(if trimmed.is_empty() {
+        "0x0".to_string()
+    } else {
+        format!("0x{}", trimmed)
+    })
+}
+
+type Path = (Option<String>, Option<String>, Option<String>);
+
+/// Parses `address::module::name`. If wildcards are allowed, any part can be `*`, in which case
+/// it is None, but at least one part must be given.
+fn parse_path(path: &str, allow_wildcards: bool) -> Result<Path, String> {
+    let parts: Vec<&str> = path.split("::").collect();
+    if parts.len() != 3 {
+        return Err(format!(
+            "Expected `address::module::name` but found `{}`",
+            path
+        ));
+    }
+    let part = |part: &str| -> Result<Option<String>, String> {
+        if part == "*" {
+            if allow_wildcards {
+                return Ok(None);
+            }
+            return Err("Wildcards are only supported with `~`".to_string());
+        }
+        if part.is_empty() || !part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(format!("`{}` is not a valid identifier", part));
+        }
+        Ok(Some(part.to_string()))
+    };
+    let address = match parts[0] {
+        "*" => part("*")?,
+        address => Some(normalize_address(address)?),
+    };
+    let module = part(parts[1])?;
+    let name = part(parts[2])?;
+    if address.is_none() && module.is_none() && name.is_none() {
+        return Err("At least one of address, module or name must be given".to_string());
+    }
+    Ok((address, module, name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        filters::{EventFilterBuilder, MoveStructTagFilterBuilder, UserTransactionFilterBuilder},
+        traits::Filterable,
+    };
+
+    fn to_json(filter: &BooleanTransactionFilter) -> serde_json::Value {
+        serde_json::to_value(filter).unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        let filter: BooleanTransactionFilter =
+            "sender == 0x0001 && event.type ~ \"0x1::coin::*\" && !success"
+                .parse()
+                .unwrap();
+        let expected = BooleanTransactionFilter::new_and(vec![
+            UserTransactionFilterBuilder::default()
+                .sender("0x1")
+                .build()
+                .unwrap()
+                .into(),
+            EventFilterBuilder::default()
+                .struct_type(
+                    MoveStructTagFilterBuilder::default()
+                        .address("0x1")
+                        .module("coin")
+                        .build()
+                        .unwrap(),
+                )
+                .build()
+                .unwrap()
+                .into(),
+            success_filter(true).not(),
+        ]);
+        assert_eq!(to_json(&filter), to_json(&expected));
+        filter.is_valid().unwrap();
+    }
+
+    #[test]
+    fn test_precedence() {
+        let filter = parse("type == user || success && !(sender != 0x1)").unwrap();
+        let expected = BooleanTransactionFilter::new_or(vec![
+            TransactionRootFilter {
+                success: None,
+                txn_type: Some(TransactionType::User),
+            }
+            .into(),
+            BooleanTransactionFilter::new_and(vec![
+                success_filter(true),
+                BooleanTransactionFilter::from(
+                    UserTransactionFilterBuilder::default()
+                        .sender("0x1")
+                        .build()
+                        .unwrap(),
+                )
+                .not()
+                .not(),
+            ]),
+        ]);
+        assert_eq!(to_json(&filter), to_json(&expected));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = parse("sender == && success").unwrap_err();
+        assert_eq!(error.position, 10);
+        assert_eq!(
+            error.to_string(),
+            "Expected a value after `==` but found `&&` at position 10\nsender == && success\n          ^"
+        );
+
+        for (expression, position) in [
+            ("", 0),
+            ("(success", 8),
+            ("success)", 7),
+            ("success &", 8),
+            ("senderr == 0x1", 0),
+            ("sender == 1", 10),
+            ("type == fast", 8),
+            ("sender ~ 0x1", 7),
+            ("function == 0x1::coin::*", 12),
+            ("event.type ~ \"*::*::*\"", 13),
+            ("event.data == \"abc", 14),
+            ("success == maybe", 11),
+            ("sender == 0x1 $", 14),
+        ] {
+            assert_eq!(
+                parse(expression).unwrap_err().position,
+                position,
+                "{}",
+                expression
+            );
+        }
+
+        let nested = format!("{}success{}", "(".repeat(100), ")".repeat(100));
+        assert!(parse(&nested).is_err());
+    }
+
+    #[test]
+    fn test_parse_struct_type_pattern() {
+        assert_eq!(
+            parse_struct_type_pattern("0x0001::coin::*").unwrap(),
+            MoveStructTagFilterBuilder::default()
+                .address("0x1")
+                .module("coin")
+                .build()
+                .unwrap()
+        );
+        assert!(parse_struct_type_pattern("*::*::*").is_err());
+        assert!(parse_struct_type_pattern("0x1::coin").is_err());
+    }
+}
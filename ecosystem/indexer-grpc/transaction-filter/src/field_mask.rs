@@ -0,0 +1,264 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    errors::ExpressionError, expression::parse_struct_type_pattern, EventFilter, Filterable,
+    MoveStructTagFilter,
+};
+use aptos_protos::{
+    indexer::v1::TransactionFieldMask,
+    transaction::v1::{
+        transaction::TxnData, write_set_change::Change, Event, Transaction, WriteSetChange,
+    },
+};
+
+/// The parsed form of a `TransactionFieldMask` from a request. This strips the parts of
+/// transactions the client didn't ask for before they're sent, so neither side spends time
+/// encoding and decoding data that is thrown away.
+#[derive(Clone, Debug)]
+pub struct FieldMask {
+    include_payload: bool,
+    include_signature: bool,
+    /// None if events are stripped. Otherwise only events matching one of these filters are
+    /// kept, or all events if there are none.
+    events: Option<Vec<EventFilter>>,
+    /// None if write set changes are stripped. Otherwise only resource changes matching one of
+    /// these filters are kept, or all changes if there are none.
+    changes: Option<Vec<MoveStructTagFilter>>,
+}
+
+impl TryFrom<&TransactionFieldMask> for FieldMask {
+    type Error = ExpressionError;
+
+    fn try_from(mask: &TransactionFieldMask) -> Result<Self, Self::Error> {
+        let events = if mask.include_events {
+            let filters = mask
+                .event_types
+                .iter()
+                .map(|pattern| {
+                    Ok(EventFilter {
+                        data: None,
+                        struct_type: Some(parse_struct_type_pattern(pattern)?),
+                    })
+                })
+                .collect::<Result<Vec<_>, ExpressionError>>()?;
+            Some(filters)
+        } else {
+            None
+        };
+        let changes = if mask.include_changes {
+            let filters = mask
+                .change_types
+                .iter()
+                .map(String::as_str)
+                .map(parse_struct_type_pattern)
+                .collect::<Result<Vec<_>, ExpressionError>>()?;
+            Some(filters)
+        } else {
+            None
+        };
+        Ok(Self {
+            include_payload: mask.include_payload,
+            include_signature: mask.include_signature,
+            events,
+            changes,
+        })
+    }
+}
+
+impl FieldMask {
+    /// Strips the parts of the transaction that are not selected by the mask.
+    pub fn apply(&self, txn: &mut Transaction) {
+        if let Some(info) = txn.info.as_mut() {
+            match &self.changes {
+                None => info.changes = vec![],
+                Some(filters) if !filters.is_empty() => info
+                    .changes
+                    .retain(|change| Self::change_is_selected(change, filters)),
+                Some(_) => {},
+            }
+        }
+
+        let events = match txn.txn_data.as_mut() {
+            Some(TxnData::User(user_transaction)) => {
+                if let Some(request) = user_transaction.request.as_mut() {
+                    if !self.include_payload {
+                        request.payload = None;
+                    }
+                    if !self.include_signature {
+                        request.signature = None;
+                    }
+                }
+                Some(&mut user_transaction.events)
+            },
+            Some(TxnData::BlockMetadata(block_metadata)) => Some(&mut block_metadata.events),
+            Some(TxnData::Genesis(genesis)) => Some(&mut genesis.events),
+            Some(TxnData::Validator(validator)) => Some(&mut validator.events),
+            Some(TxnData::StateCheckpoint(_)) | Some(TxnData::BlockEpilogue(_)) | None => None,
+        };
+        if let Some(events) = events {
+            match &self.events {
+                None => events.clear(),
+                Some(filters) if !filters.is_empty() => {
+                    events.retain(|event| Self::event_is_selected(event, filters))
+                },
+                Some(_) => {},
+            }
+        }
+    }
+
+    fn event_is_selected(event: &Event, filters: &[EventFilter]) -> bool {
+        filters.iter().any(|filter| filter.is_allowed(event))
+    }
+
+    /// Only resource changes have a type, so other changes are never selected by type.
+    fn change_is_selected(change: &WriteSetChange, filters: &[MoveStructTagFilter]) -> bool {
+        let struct_tag = match &change.change {
+            Some(Change::WriteResource(write_resource)) => write_resource.r#type.as_ref(),
+            Some(Change::DeleteResource(delete_resource)) => delete_resource.r#type.as_ref(),
+            _ => None,
+        };
+        struct_tag
+            .is_some_and(|struct_tag| filters.iter().any(|filter| filter.is_allowed(struct_tag)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_protos::transaction::v1::{
+        move_type::Content, MoveStructTag, MoveType, Signature, TransactionInfo,
+        TransactionPayload, UserTransaction, UserTransactionRequest, WriteResource, WriteTableItem,
+    };
+
+    fn struct_tag(address: &str, module: &str, name: &str) -> MoveStructTag {
+        MoveStructTag {
+            address: address.to_string(),
+            module: module.to_string(),
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn event(address: &str, module: &str, name: &str) -> Event {
+        Event {
+            r#type: Some(MoveType {
+                content: Some(Content::Struct(struct_tag(address, module, name))),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn write_resource(address: &str, module: &str, name: &str) -> WriteSetChange {
+        WriteSetChange {
+            change: Some(Change::WriteResource(WriteResource {
+                r#type: Some(struct_tag(address, module, name)),
+                ..Default::default()
+            })),
+            ..Default::default()
+        }
+    }
+
+    fn create_test_transaction() -> Transaction {
+        Transaction {
+            version: 1,
+            txn_data: Some(TxnData::User(UserTransaction {
+                request: Some(UserTransactionRequest {
+                    payload: Some(TransactionPayload::default()),
+                    signature: Some(Signature::default()),
+                    ..Default::default()
+                }),
+                events: vec![
+                    event("0x1", "coin", "DepositEvent"),
+                    event("0x1", "coin", "WithdrawEvent"),
+                    event("0x1", "account", "KeyRotation"),
+                ],
+            })),
+            info: Some(TransactionInfo {
+                changes: vec![
+                    write_resource("0x1", "coin", "CoinStore"),
+                    write_resource("0x1", "account", "Account"),
+                    WriteSetChange {
+                        change: Some(Change::WriteTableItem(WriteTableItem::default())),
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn user_transaction(txn: &Transaction) -> &UserTransaction {
+        match &txn.txn_data {
+            Some(TxnData::User(user_transaction)) => user_transaction,
+            _ => panic!("Expected user transaction"),
+        }
+    }
+
+    #[test]
+    fn test_empty_mask_strips_everything() {
+        let mask = FieldMask::try_from(&TransactionFieldMask::default()).unwrap();
+        let mut txn = create_test_transaction();
+        mask.apply(&mut txn);
+
+        let user_transaction = user_transaction(&txn);
+        assert_eq!(user_transaction.request.as_ref().unwrap().payload, None);
+        assert_eq!(user_transaction.request.as_ref().unwrap().signature, None);
+        assert!(user_transaction.events.is_empty());
+        assert!(txn.info.as_ref().unwrap().changes.is_empty());
+        assert_eq!(txn.version, 1);
+    }
+
+    #[test]
+    fn test_full_mask_keeps_everything() {
+        let mask = FieldMask::try_from(&TransactionFieldMask {
+            include_payload: true,
+            include_signature: true,
+            include_events: true,
+            include_changes: true,
+            ..Default::default()
+        })
+        .unwrap();
+        let mut txn = create_test_transaction();
+        mask.apply(&mut txn);
+        assert_eq!(txn, create_test_transaction());
+    }
+
+    #[test]
+    fn test_mask_keeps_selected_types() {
+        let mask = FieldMask::try_from(&TransactionFieldMask {
+            include_payload: true,
+            include_events: true,
+            event_types: vec!["0x1::coin::*".to_string()],
+            include_changes: true,
+            change_types: vec!["0x1::account::Account".to_string()],
+            ..Default::default()
+        })
+        .unwrap();
+        let mut txn = create_test_transaction();
+        mask.apply(&mut txn);
+
+        let user_transaction = user_transaction(&txn);
+        assert_ne!(user_transaction.request.as_ref().unwrap().payload, None);
+        assert_eq!(user_transaction.request.as_ref().unwrap().signature, None);
+        assert_eq!(user_transaction.events, vec![
+            event("0x1", "coin", "DepositEvent"),
+            event("0x1", "coin", "WithdrawEvent"),
+        ]);
+        assert_eq!(txn.info.as_ref().unwrap().changes, vec![write_resource(
+            "0x1", "account", "Account"
+        )]);
+    }
+
+    #[test]
+    fn test_invalid_type_pattern() {
+        assert!(FieldMask::try_from(&TransactionFieldMask {
+            include_events: true,
+            event_types: vec!["0x1::coin".to_string()],
+            ..Default::default()
+        })
+        .is_err());
+    }
+}
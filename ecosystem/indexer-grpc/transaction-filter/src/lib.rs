@@ -3,11 +3,14 @@
 
 pub mod boolean_transaction_filter;
 pub mod errors;
+pub mod expression;
+pub mod field_mask;
 pub mod filters;
 pub mod traits;
 
 // Re-exports for convenience.
 pub use boolean_transaction_filter::BooleanTransactionFilter;
+pub use field_mask::FieldMask;
 pub use filters::*;
 pub use traits::Filterable;
 
@@ -0,0 +1,397 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{CheckResult, Checker, CheckerError, CommonCheckerConfig};
+use crate::{
+    get_provider,
+    provider::{api_index::ApiIndexProvider, Provider, ProviderCollection},
+};
+use anyhow::Result;
+use aptos_rest_client::{
+    aptos_api_types::{VersionedEvent, ViewRequest},
+    Client as AptosRestClient, Resource,
+};
+use aptos_sdk::types::account_address::AccountAddress;
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::{max, min},
+    collections::BTreeMap,
+};
+
+const RESOURCES_ENDPOINT: &str = "/accounts/{address}/resources";
+const VIEW_ENDPOINT: &str = "/view";
+const EVENTS_ENDPOINT: &str = "/accounts/{address}/events/{event_handle}/{field_name}";
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ApiConformanceCheckerConfig {
+    #[serde(flatten)]
+    pub common: CommonCheckerConfig,
+
+    /// Accounts whose resources we compare between the baseline and target.
+    #[serde(default = "ApiConformanceCheckerConfig::default_accounts")]
+    pub accounts: Vec<AccountAddress>,
+
+    /// View functions we call on both the baseline and target.
+    #[serde(default = "ApiConformanceCheckerConfig::default_view_requests")]
+    pub view_requests: Vec<ViewRequest>,
+
+    /// Event streams we compare between the baseline and target.
+    #[serde(default = "ApiConformanceCheckerConfig::default_event_streams")]
+    pub event_streams: Vec<EventStream>,
+
+    /// How many of the latest events to compare for each event stream.
+    #[serde(default = "ApiConformanceCheckerConfig::default_num_events")]
+    pub num_events: u16,
+}
+
+impl ApiConformanceCheckerConfig {
+    fn default_accounts() -> Vec<AccountAddress> {
+        vec![AccountAddress::ONE]
+    }
+
+    fn default_view_requests() -> Vec<ViewRequest> {
+        vec![ViewRequest {
+            function: "0x1::chain_id::get".parse().unwrap(),
+            type_arguments: vec![],
+            arguments: vec![],
+        }]
+    }
+
+    fn default_event_streams() -> Vec<EventStream> {
+        vec![EventStream {
+            address: AccountAddress::ONE,
+            event_handle: "0x1::block::BlockResource".to_string(),
+            field_name: "new_block_events".to_string(),
+        }]
+    }
+
+    fn default_num_events() -> u16 {
+        10
+    }
+}
+
+/// An event stream, identified by the struct holding the event handle and the
+/// name of the event handle field in that struct.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct EventStream {
+    pub address: AccountAddress,
+    pub event_handle: String,
+    pub field_name: String,
+}
+
+#[derive(Debug)]
+pub struct ApiConformanceChecker {
+    config: ApiConformanceCheckerConfig,
+}
+
+impl ApiConformanceChecker {
+    pub fn new(config: ApiConformanceCheckerConfig) -> Self {
+        Self { config }
+    }
+
+    /// Fetch the resources of an account at a version, keyed by resource type.
+    async fn get_resources(
+        client: &AptosRestClient,
+        address: AccountAddress,
+        version: u64,
+    ) -> Result<BTreeMap<String, Resource>> {
+        Ok(client
+            .get_account_resources_at_version(address, version)
+            .await?
+            .into_inner()
+            .into_iter()
+            .map(|resource| (resource.resource_type.to_string(), resource))
+            .collect())
+    }
+
+    async fn check_resources(
+        &self,
+        baseline_client: &AptosRestClient,
+        target_client: &AptosRestClient,
+        version: u64,
+    ) -> Result<CheckResult, CheckerError> {
+        let mut mismatches = vec![];
+        for address in &self.config.accounts {
+            let baseline_resources = Self::get_resources(baseline_client, *address, version)
+                .await
+                .map_err(|e| CheckerError::NonRetryableEndpointError(RESOURCES_ENDPOINT, e))?;
+            let target_resources = match Self::get_resources(target_client, *address, version).await
+            {
+                Ok(target_resources) => target_resources,
+                Err(err) => {
+                    return Ok(Self::build_result(
+                        "Failed to fetch account resources".to_string(),
+                        0,
+                        format!(
+                            "Failed to fetch the resources of account {} at version {} \
+                            from your node: {:#}",
+                            address, version, err
+                        ),
+                    ));
+                },
+            };
+            for (resource_type, baseline_resource) in &baseline_resources {
+                if target_resources.get(resource_type) != Some(baseline_resource) {
+                    mismatches.push(format!("{}/{}", address, resource_type));
+                }
+            }
+            for resource_type in target_resources.keys() {
+                if !baseline_resources.contains_key(resource_type) {
+                    mismatches.push(format!("{}/{}", address, resource_type));
+                }
+            }
+        }
+
+        Ok(if mismatches.is_empty() {
+            Self::build_result(
+                "Account resources match the baseline".to_string(),
+                100,
+                format!(
+                    "The resources of {} account(s) at version {} are identical on \
+                    your node and the baseline node.",
+                    self.config.accounts.len(),
+                    version
+                ),
+            )
+        } else {
+            Self::build_result(
+                "Account resources differ from the baseline".to_string(),
+                0,
+                format!(
+                    "At version {}, these resources on your node were missing, \
+                    unexpected, or different compared to the baseline node: {}",
+                    version,
+                    mismatches.join(", ")
+                ),
+            )
+        })
+    }
+
+    async fn check_view_functions(
+        &self,
+        baseline_client: &AptosRestClient,
+        target_client: &AptosRestClient,
+        version: u64,
+    ) -> Result<CheckResult, CheckerError> {
+        let mut mismatches = vec![];
+        for view_request in &self.config.view_requests {
+            let baseline_output = baseline_client
+                .view(view_request, Some(version))
+                .await
+                .map_err(|e| CheckerError::NonRetryableEndpointError(VIEW_ENDPOINT, e.into()))?
+                .into_inner();
+            match target_client.view(view_request, Some(version)).await {
+                Ok(target_output) => {
+                    if target_output.into_inner() != baseline_output {
+                        mismatches.push(view_request.function.to_string());
+                    }
+                },
+                Err(err) => {
+                    return Ok(Self::build_result(
+                        "Failed to call view function".to_string(),
+                        0,
+                        format!(
+                            "Failed to call view function {} at version {} on your node: {:#}",
+                            view_request.function, version, err
+                        ),
+                    ));
+                },
+            }
+        }
+
+        Ok(if mismatches.is_empty() {
+            Self::build_result(
+                "View functions match the baseline".to_string(),
+                100,
+                format!(
+                    "The output of {} view function(s) at version {} is identical on \
+                    your node and the baseline node.",
+                    self.config.view_requests.len(),
+                    version
+                ),
+            )
+        } else {
+            Self::build_result(
+                "View functions differ from the baseline".to_string(),
+                0,
+                format!(
+                    "At version {}, these view functions returned different output on \
+                    your node compared to the baseline node: {}",
+                    version,
+                    mismatches.join(", ")
+                ),
+            )
+        })
+    }
+
+    async fn get_events(
+        &self,
+        client: &AptosRestClient,
+        event_stream: &EventStream,
+        start: Option<u64>,
+    ) -> Result<Vec<VersionedEvent>> {
+        Ok(client
+            .get_account_events(
+                event_stream.address,
+                &event_stream.event_handle,
+                &event_stream.field_name,
+                start,
+                Some(self.config.num_events),
+            )
+            .await?
+            .into_inner())
+    }
+
+    async fn check_events(
+        &self,
+        baseline_client: &AptosRestClient,
+        target_client: &AptosRestClient,
+    ) -> Result<CheckResult, CheckerError> {
+        let mut mismatches = vec![];
+        let mut num_compared = 0;
+        for event_stream in &self.config.event_streams {
+            let stream_name = format!(
+                "{}/{}/{}",
+                event_stream.address, event_stream.event_handle, event_stream.field_name
+            );
+
+            // Get the latest events from the target first, then get the events
+            // with the same sequence numbers from the baseline. Events never change
+            // once emitted, so these should be identical even if the nodes are at
+            // slightly different versions.
+            let target_events = match self.get_events(target_client, event_stream, None).await {
+                Ok(target_events) => target_events,
+                Err(err) => {
+                    return Ok(Self::build_result(
+                        "Failed to fetch events".to_string(),
+                        0,
+                        format!(
+                            "Failed to fetch events from {} on your node: {:#}",
+                            stream_name, err
+                        ),
+                    ));
+                },
+            };
+            let start = match target_events.first() {
+                Some(event) => event.sequence_number.0,
+                None => continue,
+            };
+            let baseline_events: BTreeMap<u64, VersionedEvent> = self
+                .get_events(baseline_client, event_stream, Some(start))
+                .await
+                .map_err(|e| CheckerError::NonRetryableEndpointError(EVENTS_ENDPOINT, e))?
+                .into_iter()
+                .map(|event| (event.sequence_number.0, event))
+                .collect();
+
+            for target_event in &target_events {
+                if let Some(baseline_event) = baseline_events.get(&target_event.sequence_number.0) {
+                    num_compared += 1;
+                    if baseline_event != target_event {
+                        mismatches.push(format!(
+                            "{} (sequence number {})",
+                            stream_name, target_event.sequence_number
+                        ));
+                    }
+                }
+            }
+        }
+
+        Ok(if mismatches.is_empty() {
+            Self::build_result(
+                "Events match the baseline".to_string(),
+                100,
+                format!(
+                    "{} event(s) from {} event stream(s) are identical on your node \
+                    and the baseline node.",
+                    num_compared,
+                    self.config.event_streams.len()
+                ),
+            )
+        } else {
+            Self::build_result(
+                "Events differ from the baseline".to_string(),
+                0,
+                format!(
+                    "These events on your node were different compared to the \
+                    baseline node: {}",
+                    mismatches.join(", ")
+                ),
+            )
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl Checker for ApiConformanceChecker {
+    /// Assert that the target node returns the same account resources, view
+    /// function output, and events as the baseline node.
+    async fn check(
+        &self,
+        providers: &ProviderCollection,
+    ) -> Result<Vec<CheckResult>, CheckerError> {
+        // Assert we have both a baseline and target API index provider.
+        let baseline_api_index_provider = get_provider!(
+            providers.baseline_api_index_provider,
+            self.config.common.required,
+            ApiIndexProvider
+        );
+
+        let target_api_index_provider = get_provider!(
+            providers.target_api_index_provider,
+            self.config.common.required,
+            ApiIndexProvider
+        );
+
+        let baseline_index = baseline_api_index_provider.provide().await?;
+        let target_index = match target_api_index_provider.provide().await {
+            Ok(response) => response,
+            Err(err) => {
+                return Ok(vec![Self::build_result(
+                    "Failed to determine ledger version of your node".to_string(),
+                    0,
+                    format!("There was an error querying your node's API: {:#}", err),
+                )]);
+            },
+        };
+
+        // Resources and view functions are compared at a version both nodes have.
+        let oldest_shared_version = max(
+            baseline_index.oldest_ledger_version.0,
+            target_index.oldest_ledger_version.0,
+        );
+        let latest_shared_version = min(
+            baseline_index.ledger_version.0,
+            target_index.ledger_version.0,
+        );
+        if oldest_shared_version > latest_shared_version {
+            return Ok(vec![Self::build_result(
+                "Unable to compare API responses".to_string(),
+                0,
+                format!(
+                    "There is no ledger version that both your node and the baseline \
+                    node have. The baseline node has versions {} to {} and your node \
+                    has versions {} to {}. Likely this means your node is too out of \
+                    sync with the network, but it could also indicate an \
+                    over-aggressive pruner.",
+                    baseline_index.oldest_ledger_version,
+                    baseline_index.ledger_version,
+                    target_index.oldest_ledger_version,
+                    target_index.ledger_version,
+                ),
+            )]);
+        }
+
+        let baseline_client = &baseline_api_index_provider.client;
+        let target_client = &target_api_index_provider.client;
+        Ok(vec![
+            self.check_resources(baseline_client, target_client, latest_shared_version)
+                .await?,
+            self.check_view_functions(baseline_client, target_client, latest_shared_version)
+                .await?,
+            self.check_events(baseline_client, target_client).await?,
+        ])
+    }
+}
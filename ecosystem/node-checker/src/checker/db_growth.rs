@@ -0,0 +1,174 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{CheckResult, Checker, CheckerError, CommonCheckerConfig};
+use crate::{
+    get_provider,
+    provider::{metrics::MetricsProvider, Provider, ProviderCollection},
+};
+use anyhow::Result;
+use prometheus_parse::{Scrape, Value};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+// TODO: When we have it, switch to using a crate that unifies metric names.
+// As it is now, these metric names could change and we'd never catch them here
+// at compile time. The sharded DBs report their properties under one metric per
+// shard, so we match on the metric name prefix.
+const METRIC_PREFIXES: &[&str] = &[
+    "aptos_rocksdb_properties",
+    "aptos_state_kv_db_properties_",
+    "aptos_state_merkle_db_properties_",
+];
+const PROPERTY_NAME_LABEL: &str = "property_name";
+const TOTAL_SST_FILES_SIZE_PROPERTY: &str = "aptos_rocksdb_total-sst-files-size";
+
+const SECONDS_PER_DAY: f64 = 60.0 * 60.0 * 24.0;
+const BYTES_PER_GIB: f64 = 1024.0 * 1024.0 * 1024.0;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct DbGrowthCheckerConfig {
+    #[serde(flatten)]
+    pub common: CommonCheckerConfig,
+
+    /// If set, nodes whose DB grows faster than this many GiB per day get a
+    /// lower score. If not set, the checker only reports the growth rate.
+    #[serde(default)]
+    pub maximum_growth_gib_per_day: Option<f64>,
+}
+
+#[derive(Debug)]
+pub struct DbGrowthChecker {
+    config: DbGrowthCheckerConfig,
+}
+
+impl DbGrowthChecker {
+    pub fn new(config: DbGrowthCheckerConfig) -> Self {
+        Self { config }
+    }
+
+    /// Sum the total SST file size of every column family in every DB.
+    fn get_db_size(metrics: &Scrape) -> Option<u64> {
+        let mut db_size = None;
+        for sample in &metrics.samples {
+            if !METRIC_PREFIXES
+                .iter()
+                .any(|prefix| sample.metric.starts_with(prefix))
+            {
+                continue;
+            }
+            if sample.labels.get(PROPERTY_NAME_LABEL) != Some(TOTAL_SST_FILES_SIZE_PROPERTY) {
+                continue;
+            }
+            if let Value::Gauge(v) | Value::Untyped(v) = sample.value {
+                *db_size.get_or_insert(0) += v.round() as u64;
+            }
+        }
+        db_size
+    }
+
+    fn build_check_result(
+        &self,
+        previous_db_size: u64,
+        latest_db_size: u64,
+        elapsed: Duration,
+    ) -> CheckResult {
+        let growth_bytes = latest_db_size as f64 - previous_db_size as f64;
+        let growth_gib_per_day =
+            growth_bytes / elapsed.as_secs_f64() * SECONDS_PER_DAY / BYTES_PER_GIB;
+        let explanation = format!(
+            "Your node's DB went from {:.2} GiB to {:.2} GiB over {} seconds, \
+            which extrapolates to {:.2} GiB per day. Note that compactions make \
+            the DB size jump around, so this is only a rough estimate.",
+            previous_db_size as f64 / BYTES_PER_GIB,
+            latest_db_size as f64 / BYTES_PER_GIB,
+            elapsed.as_secs(),
+            growth_gib_per_day,
+        );
+        match self.config.maximum_growth_gib_per_day {
+            Some(maximum) if growth_gib_per_day > maximum => Self::build_result(
+                "DB is growing quickly".to_string(),
+                50,
+                format!(
+                    "{} This is more than the expected maximum of {:.2} GiB per day, \
+                    make sure your node has enough disk space and that its pruner is \
+                    enabled.",
+                    explanation, maximum
+                ),
+            ),
+            _ => Self::build_result("DB growth rate".to_string(), 100, explanation),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Checker for DbGrowthChecker {
+    /// Report how quickly the DB of the target node is growing, based on the
+    /// RocksDB properties it reports in its metrics. The DB size is measured
+    /// twice, `check_delay_secs` of the metrics provider apart. The DB size jumps
+    /// around with compactions, so longer delays give a better estimate.
+    async fn check(
+        &self,
+        providers: &ProviderCollection,
+    ) -> Result<Vec<CheckResult>, CheckerError> {
+        let target_metrics_provider = get_provider!(
+            providers.target_metrics_provider,
+            self.config.common.required,
+            MetricsProvider
+        );
+
+        let first_scrape = match target_metrics_provider.provide().await {
+            Ok(scrape) => scrape,
+            Err(e) => {
+                return Ok(vec![Self::build_result(
+                    "Failed to check DB growth".to_string(),
+                    0,
+                    format!(
+                        "Failed to scrape metrics from your node (1st time): {:#}",
+                        e
+                    ),
+                )])
+            },
+        };
+        let start = Instant::now();
+
+        tokio::time::sleep(target_metrics_provider.config.common.check_delay()).await;
+
+        let second_scrape = match target_metrics_provider.provide().await {
+            Ok(scrape) => scrape,
+            Err(e) => {
+                return Ok(vec![Self::build_result(
+                    "Failed to check DB growth".to_string(),
+                    0,
+                    format!(
+                        "Failed to scrape metrics from your node (2nd time): {:#}",
+                        e
+                    ),
+                )])
+            },
+        };
+        let elapsed = start.elapsed();
+
+        match (
+            Self::get_db_size(&first_scrape),
+            Self::get_db_size(&second_scrape),
+        ) {
+            (Some(previous_db_size), Some(latest_db_size)) => Ok(vec![self.build_check_result(
+                previous_db_size,
+                latest_db_size,
+                elapsed,
+            )]),
+            _ => Ok(vec![Self::build_result(
+                "DB size metrics missing".to_string(),
+                0,
+                format!(
+                    "The metrics from your node are missing the {} property of the \
+                    RocksDB properties metrics, so we can't determine how quickly its \
+                    DB is growing.",
+                    TOTAL_SST_FILES_SIZE_PROPERTY
+                ),
+            )]),
+        }
+    }
+}
@@ -0,0 +1,133 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{CheckResult, Checker, CheckerError, CommonCheckerConfig};
+use crate::{
+    get_provider,
+    provider::{
+        api_index::ApiIndexProvider, indexer_grpc::IndexerGrpcProvider, Provider,
+        ProviderCollection,
+    },
+};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct IndexerGrpcCheckerConfig {
+    #[serde(flatten)]
+    pub common: CommonCheckerConfig,
+
+    /// How many versions the transactions served by the indexer gRPC service
+    /// may lag behind the baseline node.
+    #[serde(default = "IndexerGrpcCheckerConfig::default_version_delta_tolerance")]
+    pub version_delta_tolerance: u64,
+}
+
+impl IndexerGrpcCheckerConfig {
+    fn default_version_delta_tolerance() -> u64 {
+        5000
+    }
+}
+
+#[derive(Debug)]
+pub struct IndexerGrpcChecker {
+    config: IndexerGrpcCheckerConfig,
+}
+
+impl IndexerGrpcChecker {
+    pub fn new(config: IndexerGrpcCheckerConfig) -> Self {
+        Self { config }
+    }
+}
+
+#[async_trait::async_trait]
+impl Checker for IndexerGrpcChecker {
+    /// Assert that the indexer gRPC service of the target node is reachable,
+    /// is for the same chain as the baseline, and serves recent transactions.
+    async fn check(
+        &self,
+        providers: &ProviderCollection,
+    ) -> Result<Vec<CheckResult>, CheckerError> {
+        let baseline_api_index_provider = get_provider!(
+            providers.baseline_api_index_provider,
+            self.config.common.required,
+            ApiIndexProvider
+        );
+
+        let target_indexer_grpc_provider = get_provider!(
+            providers.target_indexer_grpc_provider,
+            self.config.common.required,
+            IndexerGrpcProvider
+        );
+
+        let output = match target_indexer_grpc_provider.provide().await {
+            Ok(output) => output,
+            Err(err) => {
+                return Ok(vec![Self::build_result(
+                    "Failed to fetch transactions from the indexer gRPC service".to_string(),
+                    0,
+                    format!(
+                        "We could not fetch the latest transaction from the indexer gRPC \
+                        service at {}. Make sure the service is enabled and the port is \
+                        open. Error: {:#}",
+                        target_indexer_grpc_provider.url, err
+                    ),
+                )]);
+            },
+        };
+
+        let baseline_index = baseline_api_index_provider.provide().await?;
+        if output.chain_id != baseline_index.chain_id as u32 {
+            return Ok(vec![Self::build_result(
+                "Indexer gRPC service is for the wrong chain".to_string(),
+                0,
+                format!(
+                    "The indexer gRPC service on your node reported chain ID {}, but the \
+                    baseline node has chain ID {}.",
+                    output.chain_id, baseline_index.chain_id
+                ),
+            )]);
+        }
+
+        if output.version != output.requested_version {
+            return Ok(vec![Self::build_result(
+                "Indexer gRPC service returned the wrong transaction".to_string(),
+                0,
+                format!(
+                    "We asked the indexer gRPC service on your node for the transaction \
+                    at version {}, but it returned the transaction at version {}.",
+                    output.requested_version, output.version
+                ),
+            )]);
+        }
+
+        // We convert to i64 to avoid potential overflow if the target is ahead of the baseline.
+        let delta_from_baseline = baseline_index.ledger_version.0 as i64 - output.version as i64;
+        if delta_from_baseline > self.config.version_delta_tolerance as i64 {
+            Ok(vec![Self::build_result(
+                "Indexer gRPC service is lagging".to_string(),
+                50,
+                format!(
+                    "The indexer gRPC service on your node returned the transaction at \
+                    version {}, but this is {} versions behind the baseline node, more \
+                    than the allowed lag of {}.",
+                    output.version, delta_from_baseline, self.config.version_delta_tolerance
+                ),
+            )])
+        } else {
+            Ok(vec![Self::build_result(
+                "Indexer gRPC service is serving recent transactions".to_string(),
+                100,
+                format!(
+                    "The indexer gRPC service on your node returned the transaction at \
+                    version {}, which is within the allowed lag of {} versions of the \
+                    baseline node's latest version ({}).",
+                    output.version,
+                    self.config.version_delta_tolerance,
+                    baseline_index.ledger_version
+                ),
+            )])
+        }
+    }
+}
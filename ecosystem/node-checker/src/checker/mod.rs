@@ -1,15 +1,19 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
+mod api_conformance;
 mod build_version;
 mod consensus_proposals;
 mod consensus_round;
 mod consensus_timeouts;
+mod db_growth;
 mod handshake;
 mod hardware;
+mod indexer_grpc;
 mod latency;
 mod minimum_peers;
 mod node_identity;
+mod pruner_window;
 mod state_sync_version;
 mod tps;
 mod traits;
@@ -17,15 +21,19 @@ mod transaction_correctness;
 mod types;
 
 use self::{
+    api_conformance::{ApiConformanceChecker, ApiConformanceCheckerConfig},
     build_version::{BuildVersionChecker, BuildVersionCheckerConfig},
     consensus_proposals::{ConsensusProposalsChecker, ConsensusProposalsCheckerConfig},
     consensus_round::{ConsensusRoundChecker, ConsensusRoundCheckerConfig},
     consensus_timeouts::{ConsensusTimeoutsChecker, ConsensusTimeoutsCheckerConfig},
+    db_growth::{DbGrowthChecker, DbGrowthCheckerConfig},
     handshake::{HandshakeChecker, HandshakeCheckerConfig},
     hardware::{HardwareChecker, HardwareCheckerConfig},
+    indexer_grpc::{IndexerGrpcChecker, IndexerGrpcCheckerConfig},
     latency::{LatencyChecker, LatencyCheckerConfig},
     minimum_peers::{MinimumPeersChecker, MinimumPeersCheckerConfig},
     node_identity::{NodeIdentityChecker, NodeIdentityCheckerConfig},
+    pruner_window::{PrunerWindowChecker, PrunerWindowCheckerConfig},
     state_sync_version::{StateSyncVersionChecker, StateSyncVersionCheckerConfig},
     tps::{TpsChecker, TpsCheckerConfig},
     transaction_correctness::{TransactionCorrectnessChecker, TransactionCorrectnessCheckerConfig},
@@ -40,15 +48,19 @@ pub use types::{CheckResult, CheckSummary};
 #[serde(tag = "type")]
 #[serde(deny_unknown_fields)]
 pub enum CheckerConfig {
+    ApiConformance(ApiConformanceCheckerConfig),
     BuildVersion(BuildVersionCheckerConfig),
     ConsensusProposals(ConsensusProposalsCheckerConfig),
     ConsensusRound(ConsensusRoundCheckerConfig),
     ConsensusTimeouts(ConsensusTimeoutsCheckerConfig),
+    DbGrowth(DbGrowthCheckerConfig),
     Handshake(HandshakeCheckerConfig),
     Hardware(HardwareCheckerConfig),
+    IndexerGrpc(IndexerGrpcCheckerConfig),
     Latency(LatencyCheckerConfig),
     MinimumPeers(MinimumPeersCheckerConfig),
     NodeIdentity(NodeIdentityCheckerConfig),
+    PrunerWindow(PrunerWindowCheckerConfig),
     StateSyncVersion(StateSyncVersionCheckerConfig),
     Tps(TpsCheckerConfig),
     TransactionCorrectness(TransactionCorrectnessCheckerConfig),
@@ -57,17 +69,21 @@ pub enum CheckerConfig {
 impl CheckerConfig {
     pub fn try_into_boxed_checker(self) -> Result<Box<dyn Checker>, anyhow::Error> {
         match self {
+            Self::ApiConformance(config) => Ok(Box::new(ApiConformanceChecker::new(config))),
             Self::BuildVersion(config) => Ok(Box::new(BuildVersionChecker::new(config))),
             Self::ConsensusProposals(config) => {
                 Ok(Box::new(ConsensusProposalsChecker::new(config)))
             },
             Self::ConsensusRound(config) => Ok(Box::new(ConsensusRoundChecker::new(config))),
             Self::ConsensusTimeouts(config) => Ok(Box::new(ConsensusTimeoutsChecker::new(config))),
+            Self::DbGrowth(config) => Ok(Box::new(DbGrowthChecker::new(config))),
             Self::Handshake(config) => Ok(Box::new(HandshakeChecker::new(config))),
             Self::Hardware(config) => Ok(Box::new(HardwareChecker::new(config))),
+            Self::IndexerGrpc(config) => Ok(Box::new(IndexerGrpcChecker::new(config))),
             Self::Latency(config) => Ok(Box::new(LatencyChecker::new(config))),
             Self::MinimumPeers(config) => Ok(Box::new(MinimumPeersChecker::new(config))),
             Self::NodeIdentity(config) => Ok(Box::new(NodeIdentityChecker::new(config))),
+            Self::PrunerWindow(config) => Ok(Box::new(PrunerWindowChecker::new(config))),
             Self::StateSyncVersion(config) => Ok(Box::new(StateSyncVersionChecker::new(config))),
             Self::Tps(config) => Ok(Box::new(TpsChecker::new(config)?)),
             Self::TransactionCorrectness(config) => {
@@ -0,0 +1,183 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{CheckResult, Checker, CheckerError, CommonCheckerConfig};
+use crate::{
+    get_provider,
+    provider::{
+        api_index::ApiIndexProvider,
+        metrics::{get_metric_value, Label},
+        Provider, ProviderCollection,
+    },
+};
+use anyhow::Result;
+use aptos_config::config::PrunerConfig;
+use serde::{Deserialize, Serialize};
+
+// The key of the pruner config in the response from the /info endpoint.
+const INFO_KEY: &str = "storage_pruner_config";
+
+// TODO: When we have it, switch to using a crate that unifies metric names.
+// As it is now, this metric name could change and we'd never catch it here
+// at compile time.
+const PRUNE_WINDOW_METRIC: &str = "aptos_storage_prune_window";
+
+const LEDGER_PRUNER_LABEL: Label = Label {
+    key: "pruner_name",
+    value: "ledger_pruner",
+};
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct PrunerWindowCheckerConfig {
+    #[serde(flatten)]
+    pub common: CommonCheckerConfig,
+
+    /// If set, nodes with a ledger prune window smaller than this many versions
+    /// get a lower score. If not set, the checker only reports the window.
+    #[serde(default)]
+    pub minimum_ledger_prune_window: Option<u64>,
+}
+
+#[derive(Debug)]
+pub struct PrunerWindowChecker {
+    config: PrunerWindowCheckerConfig,
+}
+
+/// Where we learned the configured ledger prune window from.
+enum PruneWindowSource {
+    /// The node's pruner config, from the /info endpoint.
+    Info(PrunerConfig),
+    /// The prune window metric. This is only set if the ledger pruner is enabled.
+    Metrics(u64),
+    /// Neither was available.
+    Unknown,
+}
+
+impl PrunerWindowChecker {
+    pub fn new(config: PrunerWindowCheckerConfig) -> Self {
+        Self { config }
+    }
+
+    fn build_check_result(
+        &self,
+        source: PruneWindowSource,
+        available_versions: u64,
+    ) -> CheckResult {
+        let (configured_window, source_explanation) = match source {
+            PruneWindowSource::Info(pruner_config) => {
+                if !pruner_config.ledger_pruner_config.enable {
+                    return Self::build_result(
+                        "Ledger pruner is disabled".to_string(),
+                        100,
+                        format!(
+                            "According to the /info endpoint your node's ledger pruner is \
+                            disabled, so it keeps the full ledger history. Your node's API \
+                            currently serves {} versions.",
+                            available_versions
+                        ),
+                    );
+                }
+                (
+                    pruner_config.ledger_pruner_config.prune_window,
+                    format!(
+                        "According to the /info endpoint your node's ledger prune window \
+                        is {} versions and its state merkle prune window is {} versions.",
+                        pruner_config.ledger_pruner_config.prune_window,
+                        pruner_config.state_merkle_pruner_config.prune_window,
+                    ),
+                )
+            },
+            PruneWindowSource::Metrics(prune_window) => (
+                prune_window,
+                format!(
+                    "According to the {} metric your node's ledger prune window is {} versions.",
+                    PRUNE_WINDOW_METRIC, prune_window
+                ),
+            ),
+            PruneWindowSource::Unknown => (
+                available_versions,
+                "We could not determine your node's configured ledger prune window from \
+                either the /info endpoint or its metrics, so we use the versions your \
+                node's API serves instead."
+                    .to_string(),
+            ),
+        };
+
+        let explanation = format!(
+            "{} Your node's API currently serves {} versions.",
+            source_explanation, available_versions
+        );
+        match self.config.minimum_ledger_prune_window {
+            Some(minimum) if configured_window < minimum => Self::build_result(
+                "Ledger prune window is too small".to_string(),
+                50,
+                format!(
+                    "{} This is less than the recommended minimum of {} versions, \
+                    so your node might not be able to serve historical data that \
+                    clients expect.",
+                    explanation, minimum
+                ),
+            ),
+            _ => Self::build_result("Ledger prune window".to_string(), 100, explanation),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Checker for PrunerWindowChecker {
+    /// Report the pruner window of the target node, preferring the pruner
+    /// config from the /info endpoint and falling back to metrics.
+    async fn check(
+        &self,
+        providers: &ProviderCollection,
+    ) -> Result<Vec<CheckResult>, CheckerError> {
+        let target_api_index_provider = get_provider!(
+            providers.target_api_index_provider,
+            self.config.common.required,
+            ApiIndexProvider
+        );
+
+        let index_response = match target_api_index_provider.provide().await {
+            Ok(response) => response,
+            Err(err) => {
+                return Ok(vec![Self::build_result(
+                    "Failed to determine the pruner window".to_string(),
+                    0,
+                    format!("There was an error querying your node's API: {:#}", err),
+                )]);
+            },
+        };
+        let available_versions = index_response
+            .ledger_version
+            .0
+            .saturating_sub(index_response.oldest_ledger_version.0)
+            + 1;
+
+        // Older nodes don't include the pruner config in /info, so we don't treat
+        // a failure here as a problem with the node.
+        let pruner_config = target_api_index_provider
+            .client
+            .get_node_info()
+            .await
+            .ok()
+            .and_then(|mut info| info.remove(INFO_KEY))
+            .and_then(|value| serde_json::from_value::<PrunerConfig>(value).ok());
+
+        let source = match pruner_config {
+            Some(pruner_config) => PruneWindowSource::Info(pruner_config),
+            None => match &providers.target_metrics_provider {
+                Some(target_metrics_provider) => match target_metrics_provider.provide().await {
+                    Ok(scrape) => {
+                        get_metric_value(&scrape, PRUNE_WINDOW_METRIC, Some(&LEDGER_PRUNER_LABEL))
+                            .map_or(PruneWindowSource::Unknown, PruneWindowSource::Metrics)
+                    },
+                    Err(_) => PruneWindowSource::Unknown,
+                },
+                None => PruneWindowSource::Unknown,
+            },
+        };
+
+        Ok(vec![self.build_check_result(source, available_versions)])
+    }
+}
@@ -25,6 +25,10 @@ pub struct NodeAddress {
     /// Validator communication port.
     noise_port: Option<u16>,
 
+    /// Indexer gRPC port. This is the port the indexer gRPC fullnode service
+    /// listens on, if the node runs it.
+    indexer_grpc_port: Option<u16>,
+
     /// Public key for the node. This is used for the HandshakeChecker.
     /// If that Checker is not enabled, this is not necessary.
     public_key: Option<x25519::PublicKey>,
@@ -40,6 +44,7 @@ impl NodeAddress {
         api_port: Option<u16>,
         metrics_port: Option<u16>,
         noise_port: Option<u16>,
+        indexer_grpc_port: Option<u16>,
         public_key: Option<x25519::PublicKey>,
     ) -> Self {
         Self {
@@ -47,6 +52,7 @@ impl NodeAddress {
             api_port,
             metrics_port,
             noise_port,
+            indexer_grpc_port,
             public_key,
             cookie_store: Arc::new(Jar::default()),
         }
@@ -66,6 +72,10 @@ impl NodeAddress {
         self.noise_port
     }
 
+    pub fn get_indexer_grpc_port(&self) -> Option<u16> {
+        self.indexer_grpc_port
+    }
+
     pub fn get_public_key(&self) -> Option<x25519::PublicKey> {
         self.public_key
     }
@@ -80,6 +90,15 @@ impl NodeAddress {
         Ok(url)
     }
 
+    pub fn get_indexer_grpc_url(&self) -> Result<Url> {
+        let mut url = self.url.clone();
+        url.set_port(Some(self.indexer_grpc_port.context(
+            "Can't build indexer gRPC URL without an indexer gRPC port",
+        )?))
+        .unwrap();
+        Ok(url)
+    }
+
     pub fn get_metrics_url(&self, path: &str) -> Result<Url> {
         let mut url = self.url.clone();
         url.set_port(Some(
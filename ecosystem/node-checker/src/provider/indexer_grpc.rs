@@ -0,0 +1,166 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! This Provider talks to the indexer gRPC fullnode service, the stream that indexer
+//! infrastructure pulls transactions from. It asks the service for the transaction at
+//! the latest version the node's API knows about, which tells us both that the service
+//! is reachable and that it is serving recent data.
+
+use super::{
+    api_index::ApiIndexProvider,
+    cache::OutputCache,
+    traits::{Provider, ProviderError},
+    CommonProviderConfig,
+};
+use anyhow::{anyhow, Context, Result};
+use aptos_protos::internal::fullnode::v1::{
+    fullnode_data_client::FullnodeDataClient, transactions_from_node_response::Response,
+    GetTransactionsFromNodeRequest,
+};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::{sync::Arc, time::Duration};
+use url::Url;
+
+const GET_TRANSACTIONS_ENDPOINT: &str =
+    "/aptos.internal.fullnode.v1.FullnodeData/GetTransactionsFromNode";
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct IndexerGrpcProviderConfig {
+    #[serde(default, flatten)]
+    pub common: CommonProviderConfig,
+
+    /// How long to wait for the service to return the requested transaction. The
+    /// service waits for versions it doesn't have yet rather than failing, so a
+    /// service that is behind shows up as a timeout.
+    #[serde(default = "IndexerGrpcProviderConfig::default_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+impl IndexerGrpcProviderConfig {
+    fn default_timeout_secs() -> u64 {
+        10
+    }
+}
+
+impl Default for IndexerGrpcProviderConfig {
+    fn default() -> Self {
+        Self {
+            common: CommonProviderConfig::default(),
+            timeout_secs: Self::default_timeout_secs(),
+        }
+    }
+}
+
+/// What the indexer gRPC service returned when asked for the latest version.
+#[derive(Clone, Debug)]
+pub struct IndexerGrpcOutput {
+    /// The chain ID the service reported.
+    pub chain_id: u32,
+
+    /// The version we asked for, the latest version according to the node's API.
+    pub requested_version: u64,
+
+    /// The version of the transaction the service returned.
+    pub version: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct IndexerGrpcProvider {
+    pub config: IndexerGrpcProviderConfig,
+
+    /// This has the port already rolled into it.
+    pub url: Url,
+
+    /// An API index provider. We use this to find the latest version to ask for.
+    pub api_index_provider: Arc<ApiIndexProvider>,
+
+    // This output cache helps prevent the Provider from overfetching the data within
+    // a short window of time. Downstream Checkers should be aware of this behaviour.
+    output_cache: Arc<OutputCache<IndexerGrpcOutput>>,
+}
+
+impl IndexerGrpcProvider {
+    pub fn new(
+        config: IndexerGrpcProviderConfig,
+        url: Url,
+        api_index_provider: Arc<ApiIndexProvider>,
+    ) -> Self {
+        let output_cache = Arc::new(OutputCache::new(Duration::from_millis(
+            config.common.cache_ttl_ms,
+        )));
+        Self {
+            config,
+            url,
+            api_index_provider,
+            output_cache,
+        }
+    }
+
+    /// Fetch the transaction at the given version from the indexer gRPC service.
+    pub async fn get_transaction(&self, version: u64) -> Result<IndexerGrpcOutput, ProviderError> {
+        let mut client = FullnodeDataClient::connect(self.url.to_string())
+            .await
+            .with_context(|| format!("Failed to connect to {}", self.url))
+            .map_err(|e| ProviderError::RetryableEndpointError(GET_TRANSACTIONS_ENDPOINT, e))?;
+        let mut stream = client
+            .get_transactions_from_node(GetTransactionsFromNodeRequest {
+                starting_version: Some(version),
+                transactions_count: Some(1),
+            })
+            .await
+            .with_context(|| format!("Failed to start a transaction stream from {}", self.url))
+            .map_err(|e| ProviderError::RetryableEndpointError(GET_TRANSACTIONS_ENDPOINT, e))?
+            .into_inner();
+
+        let read_transaction = async {
+            // The service starts with a status message and may send more of them,
+            // so we keep reading until we get a transaction.
+            while let Some(response) = stream.message().await? {
+                if let Some(Response::Data(data)) = response.response {
+                    if let Some(transaction) = data.transactions.first() {
+                        return Ok(IndexerGrpcOutput {
+                            chain_id: response.chain_id,
+                            requested_version: version,
+                            version: transaction.version,
+                        });
+                    }
+                }
+            }
+            Err::<_, anyhow::Error>(anyhow!(
+                "The stream ended without returning the transaction at version {}",
+                version
+            ))
+        };
+
+        tokio::time::timeout(
+            Duration::from_secs(self.config.timeout_secs),
+            read_transaction,
+        )
+        .await
+        .map_err(|_| {
+            anyhow!(
+                "Timed out after {} seconds waiting for the transaction at version {}",
+                self.config.timeout_secs,
+                version
+            )
+        })
+        .and_then(|result| result)
+        .map_err(|e| ProviderError::NonRetryableEndpointError(GET_TRANSACTIONS_ENDPOINT, e))
+    }
+}
+
+#[async_trait]
+impl Provider for IndexerGrpcProvider {
+    type Output = IndexerGrpcOutput;
+
+    async fn provide(&self) -> Result<Self::Output, ProviderError> {
+        let version = self.api_index_provider.provide().await?.ledger_version.0;
+        self.output_cache.get(self.get_transaction(version)).await
+    }
+
+    fn explanation() -> &'static str {
+        "The indexer gRPC port or the API port was not included in the request."
+    }
+}
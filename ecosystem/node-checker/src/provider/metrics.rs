@@ -107,7 +107,7 @@ pub struct Label<'a> {
 /// a metric with the given metric name. If no label was given, we return that
 /// metric immediately. If a label was given, we search for a metric that
 /// has that label.
-fn get_metric_value(
+pub fn get_metric_value(
     metrics: &Scrape,
     metric_name: &str,
     expected_label: Option<&Label>,
@@ -7,6 +7,7 @@
 pub mod api_index;
 mod cache;
 mod helpers;
+pub mod indexer_grpc;
 pub mod metrics;
 pub mod noise;
 mod provider_collection;
@@ -14,7 +15,8 @@ pub mod system_information;
 mod traits;
 
 use self::{
-    api_index::ApiIndexProviderConfig, metrics::MetricsProviderConfig, noise::NoiseProviderConfig,
+    api_index::ApiIndexProviderConfig, indexer_grpc::IndexerGrpcProviderConfig,
+    metrics::MetricsProviderConfig, noise::NoiseProviderConfig,
     system_information::SystemInformationProviderConfig,
 };
 pub use helpers::MISSING_PROVIDER_MESSAGE;
@@ -37,6 +39,8 @@ pub struct ProviderConfigs {
     pub system_information: SystemInformationProviderConfig,
 
     pub noise: NoiseProviderConfig,
+
+    pub indexer_grpc: IndexerGrpcProviderConfig,
 }
 
 #[derive(Copy, Clone, Debug, Deserialize, Serialize)]
@@ -2,8 +2,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use super::{
-    api_index::ApiIndexProvider, metrics::MetricsProvider, noise::NoiseProvider,
-    system_information::SystemInformationProvider,
+    api_index::ApiIndexProvider, indexer_grpc::IndexerGrpcProvider, metrics::MetricsProvider,
+    noise::NoiseProvider, system_information::SystemInformationProvider,
 };
 use std::sync::Arc;
 
@@ -50,6 +50,9 @@ pub struct ProviderCollection {
 
     /// Provider that wraps functionality for connecting to the node via noise.
     pub target_noise_provider: Option<NoiseProvider>,
+
+    /// Provider that fetches recent transactions from the indexer gRPC service.
+    pub target_indexer_grpc_provider: Option<IndexerGrpcProvider>,
 }
 
 impl ProviderCollection {
@@ -63,6 +66,7 @@ impl ProviderCollection {
             target_system_information_provider: None,
             baseline_noise_provider: None,
             target_noise_provider: None,
+            target_indexer_grpc_provider: None,
         }
     }
 }
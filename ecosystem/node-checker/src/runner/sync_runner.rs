@@ -6,8 +6,9 @@ use crate::{
     checker::{CheckResult, Checker, CheckerError},
     configuration::NodeAddress,
     provider::{
-        api_index::ApiIndexProvider, metrics::MetricsProvider, noise::NoiseProvider,
-        system_information::SystemInformationProvider, ProviderCollection, ProviderConfigs,
+        api_index::ApiIndexProvider, indexer_grpc::IndexerGrpcProvider, metrics::MetricsProvider,
+        noise::NoiseProvider, system_information::SystemInformationProvider, ProviderCollection,
+        ProviderConfigs,
     },
     CheckSummary,
 };
@@ -126,6 +127,15 @@ impl Runner for SyncRunner {
             ));
             provider_collection.target_api_index_provider = Some(api_index_provider.clone());
 
+            // We need the API to know which version to ask the indexer gRPC service for.
+            if let Ok(indexer_grpc_url) = target_node_address.get_indexer_grpc_url() {
+                provider_collection.target_indexer_grpc_provider = Some(IndexerGrpcProvider::new(
+                    self.provider_configs.indexer_grpc.clone(),
+                    indexer_grpc_url,
+                    api_index_provider.clone(),
+                ));
+            }
+
             // From here, since we have an API provider, we can try to make a noise provider.
             if let (Some(_), Some(_)) = (
                 target_node_address.get_noise_port(),
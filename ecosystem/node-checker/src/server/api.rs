@@ -39,6 +39,8 @@ impl<R: Runner> Api<R> {
         api_port: Query<Option<u16>>,
         /// If given, we will assume that clients can communicate with your node via noise at the given port.
         noise_port: Query<Option<u16>>,
+        /// If given, we will assume the indexer gRPC fullnode service is available at the given port.
+        indexer_grpc_port: Query<Option<u16>>,
         /// A public key for the node, e.g. 0x44fd1324c66371b4788af0b901c9eb8088781acb29e6b8b9c791d5d9838fbe1f.
         /// This is only necessary for certain checkers, e.g. HandshakeChecker.
         public_key: Query<Option<String>>,
@@ -83,6 +85,7 @@ impl<R: Runner> Api<R> {
             api_port.0,
             metrics_port.0,
             noise_port.0,
+            indexer_grpc_port.0,
             public_key,
         );
 
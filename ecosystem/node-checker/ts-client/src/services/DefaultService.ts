@@ -26,6 +26,7 @@ export class DefaultService {
         metricsPort,
         apiPort,
         noisePort,
+        indexerGrpcPort,
         publicKey,
     }: {
         /**
@@ -48,6 +49,10 @@ export class DefaultService {
          * If given, we will assume that clients can communicate with your node via noise at the given port.
          */
         noisePort?: number,
+        /**
+         * If given, we will assume the indexer gRPC fullnode service is available at the given port.
+         */
+        indexerGrpcPort?: number,
         /**
          * A public key for the node, e.g. 0x44fd1324c66371b4788af0b901c9eb8088781acb29e6b8b9c791d5d9838fbe1f.
          * This is only necessary for certain checkers, e.g. HandshakeChecker.
@@ -63,6 +68,7 @@ export class DefaultService {
                 'metrics_port': metricsPort,
                 'api_port': apiPort,
                 'noise_port': noisePort,
+                'indexer_grpc_port': indexerGrpcPort,
                 'public_key': publicKey,
             },
         });
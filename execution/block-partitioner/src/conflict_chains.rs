@@ -0,0 +1,81 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_types::transaction::{
+    analyzed_transaction::{declared_read_write_hints, StorageLocation},
+    signature_verified_transaction::SignatureVerifiedTransaction,
+    use_case::{UseCaseAwareTransaction, UseCaseKey},
+    Transaction,
+};
+use move_core_types::account_address::AccountAddress;
+use std::collections::HashMap;
+
+/// An entity that two transactions of a block are predicted to conflict on.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+enum ConflictKey {
+    /// Transactions of the same sender always conflict, on the sequence number.
+    Sender(AccountAddress),
+    /// A storage location declared in the read or write hints of the transaction.
+    Location(StorageLocation),
+    /// A contract called by transactions without declared hints (the use case of the transaction
+    /// shuffler), conservatively predicted to conflict with every other call to the contract.
+    Contract(AccountAddress),
+}
+
+/// Predicts the conflict chains of a block executed without sharding, i.e., for every transaction,
+/// the closest preceding transaction it is predicted to conflict with (see `ConflictChains` in the
+/// block executor). A transaction conflicts with a preceding one if it reads or writes an entity
+/// the preceding one writes, based on:
+/// - the declared read and write hints, if known for the transaction,
+/// - the sender,
+/// - the called contract, if the hints are not known and the contract is not a framework one.
+///
+/// Non-user transactions (e.g. block metadata) are predicted to conflict with all transactions.
+/// Invalid transactions are not executed, so they do not conflict with any transaction.
+pub fn predict_conflict_chains<'a>(
+    txns: impl IntoIterator<Item = &'a SignatureVerifiedTransaction>,
+) -> Vec<Option<usize>> {
+    let mut last_writers: HashMap<ConflictKey, usize> = HashMap::new();
+    let mut last_barrier: Option<usize> = None;
+
+    txns.into_iter()
+        .enumerate()
+        .map(|(txn_idx, txn)| {
+            let signed_txn = match txn {
+                SignatureVerifiedTransaction::Valid(Transaction::UserTransaction(signed_txn)) => {
+                    signed_txn
+                },
+                SignatureVerifiedTransaction::Valid(_) => {
+                    last_barrier = Some(txn_idx);
+                    return txn_idx.checked_sub(1);
+                },
+                SignatureVerifiedTransaction::Invalid(_) => return None,
+            };
+
+            let mut reads = vec![];
+            let mut writes = vec![ConflictKey::Sender(signed_txn.sender())];
+            match declared_read_write_hints(signed_txn) {
+                Some((read_hints, write_hints)) => {
+                    reads.extend(read_hints.into_iter().map(ConflictKey::Location));
+                    writes.extend(write_hints.into_iter().map(ConflictKey::Location));
+                },
+                None => {
+                    if let UseCaseKey::ContractAddress(address) = signed_txn.parse_use_case() {
+                        writes.push(ConflictKey::Contract(address));
+                    }
+                },
+            }
+
+            let predecessor = reads
+                .iter()
+                .chain(writes.iter())
+                .filter_map(|key| last_writers.get(key).copied())
+                .chain(last_barrier)
+                .max();
+            for key in writes {
+                last_writers.insert(key, txn_idx);
+            }
+            predecessor
+        })
+        .collect()
+}
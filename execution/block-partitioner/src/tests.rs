@@ -2,15 +2,27 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    conflict_chains::predict_conflict_chains,
     test_utils::{
         create_non_conflicting_p2p_transaction, create_signed_p2p_transaction,
-        generate_test_account, verify_partitioner_output,
+        generate_test_account, generate_test_account_for_address, verify_partitioner_output,
+        TestAccount,
     },
     v2::config::PartitionerV2Config,
     PartitionerConfig,
 };
-use aptos_types::{block_executor::partitioner::SubBlocksForShard, transaction::Transaction};
-use move_core_types::account_address::AccountAddress;
+use aptos_crypto::{HashValue, PrivateKey, SigningKey};
+use aptos_types::{
+    block_executor::partitioner::SubBlocksForShard,
+    chain_id::ChainId,
+    transaction::{
+        signature_verified_transaction::SignatureVerifiedTransaction, EntryFunction,
+        RawTransaction, SignedTransaction, Transaction, TransactionPayload,
+    },
+};
+use move_core_types::{
+    account_address::AccountAddress, identifier::Identifier, language_storage::ModuleId,
+};
 use rand::{rngs::OsRng, Rng};
 use std::{collections::HashMap, sync::Mutex};
 
@@ -143,3 +155,74 @@ fn test_no_conflict_across_shards_in_non_last_rounds() {
         }
     }
 }
+
+fn create_contract_call(sender: &mut TestAccount, contract: AccountAddress) -> Transaction {
+    let raw_transaction = RawTransaction::new(
+        sender.account_address,
+        sender.sequence_number,
+        TransactionPayload::EntryFunction(EntryFunction::new(
+            ModuleId::new(contract, Identifier::new("counter").unwrap()),
+            Identifier::new("increment").unwrap(),
+            vec![],
+            vec![],
+        )),
+        0,
+        0,
+        0,
+        ChainId::new(10),
+    );
+    sender.sequence_number += 1;
+    Transaction::UserTransaction(SignedTransaction::new(
+        raw_transaction.clone(),
+        sender.private_key.public_key().clone(),
+        sender.private_key.sign(&raw_transaction).unwrap(),
+    ))
+}
+
+#[test]
+// Checks that every transaction is chained to the closest preceding transaction it shares a
+// sender, a declared storage location or a called contract with.
+fn test_conflict_chains() {
+    let mut accounts: Vec<_> = (0..6).map(|_| generate_test_account()).collect();
+    let contract = AccountAddress::random();
+    let other_contract = AccountAddress::random();
+    let mut p2p = |sender: usize, receiver: usize| -> Transaction {
+        let receiver = generate_test_account_for_address(accounts[receiver].account_address);
+        create_signed_p2p_transaction(&mut accounts[sender], vec![&receiver])
+            .remove(0)
+            .into_txn()
+            .into_inner()
+    };
+    let mut txns = vec![
+        Transaction::StateCheckpoint(HashValue::zero()),
+        p2p(0, 1),
+        p2p(2, 3),
+        p2p(2, 1),
+    ];
+    let mut callers: Vec<_> = (0..2).map(|_| generate_test_account()).collect();
+    txns.push(create_contract_call(&mut callers[0], contract));
+    txns.push(create_contract_call(&mut callers[1], contract));
+    txns.push(create_contract_call(&mut callers[1], other_contract));
+
+    let mut txns: Vec<_> = txns
+        .into_iter()
+        .map(SignatureVerifiedTransaction::Valid)
+        .collect();
+    txns.push(SignatureVerifiedTransaction::Invalid(p2p(4, 5)));
+
+    assert_eq!(predict_conflict_chains(&txns), vec![
+        None,
+        // Block metadata and checkpoints conflict with all transactions.
+        Some(0),
+        Some(0),
+        // Same sender as 2, same receiver as 1.
+        Some(2),
+        Some(0),
+        // Same contract as 4.
+        Some(4),
+        // Same sender as 5.
+        Some(5),
+        // Invalid transactions are not executed.
+        None,
+    ]);
+}
@@ -0,0 +1,288 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Renders execution traces saved by the block executor (see `--execution-trace-dir`) as conflict
+//! graphs in Graphviz DOT format. Every transaction is a node labeled with its number of
+//! incarnations, and every abort is a red edge from the transaction whose write invalidated the
+//! read to the aborted transaction, labeled with the conflicting key.
+
+use anyhow::{bail, Context};
+use aptos_block_executor::trace::BlockTrace;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Write,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Loads the block trace in the given file, or all block traces in the given directory, sorted
+/// by file name.
+pub fn load_block_traces(path: &Path) -> anyhow::Result<Vec<(PathBuf, BlockTrace)>> {
+    let mut paths = if path.is_dir() {
+        fs::read_dir(path)
+            .with_context(|| format!("Unable to list {:?}", path))?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .filter(|path| path.extension().map_or(false, |ext| ext == "json"))
+            .collect()
+    } else {
+        vec![path.to_path_buf()]
+    };
+    paths.sort();
+    if paths.is_empty() {
+        bail!("No block traces found in {:?}", path);
+    }
+
+    paths
+        .into_iter()
+        .map(|path| BlockTrace::load(&path).map(|trace| (path, trace)))
+        .collect()
+}
+
+#[derive(Debug, Default, Eq, PartialEq)]
+struct AbortEdge {
+    count: usize,
+    keys: BTreeMap<String, usize>,
+}
+
+/// Conflict graph of a single block.
+pub struct ConflictGraph {
+    /// Number of incarnations of every transaction.
+    incarnations: Vec<u32>,
+    /// Aborts, keyed by (aborting transaction, aborted transaction).
+    abort_edges: BTreeMap<(u32, u32), AbortEdge>,
+    /// Aborts whose cause is unknown, per aborted transaction.
+    unattributed_aborts: BTreeMap<u32, usize>,
+    /// Read-after-write dependencies of the committed incarnations, keyed by (writer, reader).
+    dependency_edges: BTreeMap<(u32, u32), usize>,
+}
+
+impl ConflictGraph {
+    pub fn new(trace: &BlockTrace, include_dependencies: bool) -> Self {
+        let mut abort_edges: BTreeMap<_, AbortEdge> = BTreeMap::new();
+        let mut unattributed_aborts = BTreeMap::new();
+        for txn in &trace.transactions {
+            for abort in &txn.aborts {
+                match abort.aborted_by {
+                    Some(aborted_by) => {
+                        let edge = abort_edges.entry((aborted_by, txn.txn_idx)).or_default();
+                        edge.count += 1;
+                        if let Some(key) = &abort.conflicting_key {
+                            *edge.keys.entry(key.clone()).or_default() += 1;
+                        }
+                    },
+                    None => *unattributed_aborts.entry(txn.txn_idx).or_default() += 1,
+                }
+            }
+        }
+
+        let mut dependency_edges = BTreeMap::new();
+        if include_dependencies {
+            let mut last_writer: HashMap<&str, u32> = HashMap::new();
+            for txn in &trace.transactions {
+                for key in &txn.reads {
+                    if let Some(writer) = last_writer.get(key.as_str()) {
+                        *dependency_edges.entry((*writer, txn.txn_idx)).or_default() += 1;
+                    }
+                }
+                for key in &txn.writes {
+                    last_writer.insert(key, txn.txn_idx);
+                }
+            }
+        }
+
+        Self {
+            incarnations: trace
+                .transactions
+                .iter()
+                .map(|txn| txn.incarnations)
+                .collect(),
+            abort_edges,
+            unattributed_aborts,
+            dependency_edges,
+        }
+    }
+
+    pub fn num_aborts(&self) -> usize {
+        self.abort_edges
+            .values()
+            .map(|edge| edge.count)
+            .sum::<usize>()
+            + self.unattributed_aborts.values().sum::<usize>()
+    }
+
+    pub fn to_dot(&self, name: &str) -> String {
+        let mut dot = String::new();
+        writeln!(dot, "digraph \"{}\" {{", escape(name)).unwrap();
+        writeln!(dot, "  node [shape=box];").unwrap();
+        for (txn_idx, incarnations) in self.incarnations.iter().enumerate() {
+            let unattributed = self
+                .unattributed_aborts
+                .get(&(txn_idx as u32))
+                .map_or(String::new(), |count| {
+                    format!("\\n{} unattributed aborts", count)
+                });
+            let color = if *incarnations > 1 { "red" } else { "black" };
+            writeln!(
+                dot,
+                "  {} [label=\"txn {}\\n{} incarnations{}\", color={}];",
+                txn_idx, txn_idx, incarnations, unattributed, color
+            )
+            .unwrap();
+        }
+        for ((from, to), edge) in &self.abort_edges {
+            let keys = edge
+                .keys
+                .iter()
+                .map(|(key, count)| format!("{} (x{})", escape(key), count))
+                .collect::<Vec<_>>()
+                .join("\\n");
+            writeln!(
+                dot,
+                "  {} -> {} [color=red, penwidth={}, label=\"{}\"];",
+                from,
+                to,
+                edge.count.min(10),
+                keys
+            )
+            .unwrap();
+        }
+        for ((from, to), count) in &self.dependency_edges {
+            writeln!(
+                dot,
+                "  {} -> {} [style=dashed, color=gray, label=\"{}\"];",
+                from, to, count
+            )
+            .unwrap();
+        }
+        writeln!(dot, "}}").unwrap();
+        dot
+    }
+}
+
+/// Escapes a string to be used within a quoted DOT identifier.
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Returns the keys that caused the most aborts in the trace, with their number of aborts.
+pub fn hot_keys(trace: &BlockTrace, num_keys: usize) -> Vec<(String, usize)> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for abort in trace.transactions.iter().flat_map(|txn| &txn.aborts) {
+        if let Some(key) = &abort.conflicting_key {
+            *counts.entry(key).or_default() += 1;
+        }
+    }
+    let mut counts: Vec<_> = counts
+        .into_iter()
+        .map(|(key, count)| (key.to_string(), count))
+        .collect();
+    counts.sort_by(|(key1, count1), (key2, count2)| count2.cmp(count1).then(key1.cmp(key2)));
+    counts.truncate(num_keys);
+    counts
+}
+
+/// Writes the conflict graph of every block trace found at the given path into the output
+/// directory, and prints a summary of the aborts of every block.
+pub fn render_conflict_graphs(
+    trace_path: &Path,
+    output_dir: &Path,
+    include_dependencies: bool,
+    num_hot_keys: usize,
+) -> anyhow::Result<()> {
+    fs::create_dir_all(output_dir).with_context(|| format!("Unable to create {:?}", output_dir))?;
+    for (path, trace) in load_block_traces(trace_path)? {
+        let name = path.file_stem().map_or("block".to_string(), |stem| {
+            stem.to_string_lossy().to_string()
+        });
+        let graph = ConflictGraph::new(&trace, include_dependencies);
+        let output_path = output_dir.join(format!("{}.dot", name));
+        fs::write(&output_path, graph.to_dot(&name))
+            .with_context(|| format!("Unable to write {:?}", output_path))?;
+
+        println!(
+            "{}: {} txns, {} aborts, written to {:?}",
+            name,
+            trace.transactions.len(),
+            graph.num_aborts(),
+            output_path
+        );
+        for (key, count) in hot_keys(&trace, num_hot_keys) {
+            println!("    {} aborts on {}", count, key);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_block_executor::trace::{AbortTrace, TransactionTrace};
+
+    fn txn(
+        txn_idx: u32,
+        reads: &[&str],
+        writes: &[&str],
+        aborts: Vec<AbortTrace>,
+    ) -> TransactionTrace {
+        TransactionTrace {
+            txn_idx,
+            incarnations: aborts.len() as u32 + 1,
+            reads: reads.iter().map(|key| key.to_string()).collect(),
+            writes: writes.iter().map(|key| key.to_string()).collect(),
+            aborts,
+        }
+    }
+
+    fn abort(incarnation: u32, key: Option<&str>, aborted_by: Option<u32>) -> AbortTrace {
+        AbortTrace {
+            incarnation,
+            conflicting_key: key.map(|key| key.to_string()),
+            aborted_by,
+        }
+    }
+
+    fn trace() -> BlockTrace {
+        BlockTrace {
+            num_workers: 4,
+            transactions: vec![
+                txn(0, &["a"], &["a"], vec![]),
+                txn(1, &["a"], &["a"], vec![abort(0, Some("a"), Some(0))]),
+                txn(2, &["a", "b"], &["b"], vec![
+                    abort(0, Some("a"), Some(1)),
+                    abort(1, Some("a"), Some(1)),
+                    abort(2, None, None),
+                ]),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_conflict_graph() {
+        let graph = ConflictGraph::new(&trace(), true);
+        assert_eq!(graph.incarnations, vec![1, 2, 4]);
+        assert_eq!(graph.num_aborts(), 4);
+        assert_eq!(graph.abort_edges[&(1, 2)].count, 2);
+        assert_eq!(graph.abort_edges[&(1, 2)].keys["a"], 2);
+        assert_eq!(graph.unattributed_aborts[&2], 1);
+        assert_eq!(graph.dependency_edges.keys().collect::<Vec<_>>(), vec![
+            &(0, 1),
+            &(1, 2)
+        ]);
+
+        let dot = graph.to_dot("block");
+        assert!(dot.contains("1 -> 2 [color=red, penwidth=2, label=\"a (x2)\"];"));
+        assert!(dot.contains("0 -> 1 [style=dashed"));
+        assert_eq!(escape("Key(\"a\")"), "Key(\\\"a\\\")");
+        assert!(!ConflictGraph::new(&trace(), false)
+            .to_dot("block")
+            .contains("style=dashed"));
+    }
+
+    #[test]
+    fn test_hot_keys() {
+        assert_eq!(hot_keys(&trace(), 10), vec![("a".to_string(), 3)]);
+        assert!(hot_keys(&trace(), 0).is_empty());
+    }
+}
@@ -0,0 +1,379 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Checks that parallel execution is deterministic on real Move workloads. Blocks are generated by
+//! transaction-generator-lib against a DB created by the benchmark, and every block is executed
+//! sequentially and then in parallel at several concurrency levels, on the same parent state. The
+//! transaction outputs and state roots of every parallel execution must be equal to the ones of
+//! the sequential execution, which is then committed to the DB before the next block.
+//!
+//! Different interleavings of parallel execution are exercised by injecting random delays before
+//! the execution and validation tasks of the block executor, which requires the `failpoints`
+//! feature. The delays are drawn from a seeded RNG, and the seed is logged and reported with any
+//! difference found.
+
+use crate::{
+    create_checkpoint, init_db, init_workload, pipeline::PipelineConfig,
+    transaction_executor::BENCHMARKS_BLOCK_EXECUTOR_ONCHAIN_CONFIG,
+    transaction_generator::TransactionGenerator,
+};
+use anyhow::{bail, ensure, Context};
+use aptos_block_executor::txn_provider::default::DefaultTxnProvider;
+use aptos_config::config::PrunerConfig;
+use aptos_crypto::HashValue;
+use aptos_executor::workflow::{
+    do_get_execution_output::DoGetExecutionOutput, ApplyExecutionOutput,
+};
+use aptos_executor_types::state_compute_result::StateComputeResult;
+use aptos_logger::{info, warn};
+use aptos_storage_interface::{
+    state_store::state_view::async_proof_fetcher::AsyncProofFetcher, DbReaderWriter,
+};
+use aptos_transaction_generator_lib::TransactionType;
+use aptos_types::{
+    block_executor::{
+        config::{
+            BlockExecutorConfig, BlockExecutorConfigFromOnchain, BlockExecutorLocalConfig,
+            BlockExecutorModuleCacheLocalConfig,
+        },
+        partitioner::ExecutableTransactions,
+        transaction_slice_metadata::TransactionSliceMetadata,
+    },
+    on_chain_config::Features,
+    state_store::{StateView, StateViewId},
+    transaction::{
+        signature_verified_transaction::SignatureVerifiedTransaction, BlockOutput, Transaction,
+        TransactionOutput,
+    },
+    vm_status::VMStatus,
+};
+use aptos_vm::{aptos_vm::AptosVMBlockExecutor, AptosVM, VMBlockExecutor};
+use rand::{rngs::StdRng, thread_rng, Rng, SeedableRng};
+use std::{
+    path::Path,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc, Arc, Mutex,
+    },
+    time::Duration,
+};
+
+/// Fail points of the block executor at which delays are injected.
+const DELAY_FAIL_POINTS: [&str; 2] = [
+    "block-executor-before-execution",
+    "block-executor-before-validation",
+];
+
+/// [AptosVMBlockExecutor] executing blocks at a fixed concurrency level, without falling back to
+/// sequential execution, so that failures of parallel execution are not hidden.
+struct FixedConcurrencyAptosVMBlockExecutor {
+    executor: AptosVMBlockExecutor,
+    concurrency_level: usize,
+}
+
+impl FixedConcurrencyAptosVMBlockExecutor {
+    fn new_with_concurrency_level(concurrency_level: usize) -> Self {
+        Self {
+            executor: AptosVMBlockExecutor::new(),
+            concurrency_level,
+        }
+    }
+}
+
+impl VMBlockExecutor for FixedConcurrencyAptosVMBlockExecutor {
+    fn new() -> Self {
+        Self::new_with_concurrency_level(AptosVM::get_concurrency_level())
+    }
+
+    fn execute_block(
+        &self,
+        txn_provider: &DefaultTxnProvider<SignatureVerifiedTransaction>,
+        state_view: &(impl StateView + Sync),
+        onchain_config: BlockExecutorConfigFromOnchain,
+        transaction_slice_metadata: TransactionSliceMetadata,
+    ) -> Result<BlockOutput<TransactionOutput>, VMStatus> {
+        let config = BlockExecutorConfig {
+            local: BlockExecutorLocalConfig {
+                concurrency_level: self.concurrency_level,
+                allow_fallback: false,
+                discard_failed_blocks: false,
+                conflict_aware_execution: AptosVM::get_conflict_aware_execution(),
+                module_cache_config: BlockExecutorModuleCacheLocalConfig::default(),
+                execution_trace_dir: None,
+            },
+            onchain: onchain_config,
+        };
+        self.executor.execute_block_with_config(
+            txn_provider,
+            state_view,
+            config,
+            transaction_slice_metadata,
+        )
+    }
+}
+
+/// Generates `num_blocks` blocks of the given transaction mix against a checkpoint of the DB in
+/// `source_dir`, and checks that executing every block in parallel at each of the given
+/// concurrency levels gives the same outputs and state root as executing it sequentially.
+///
+/// If `delay_percentage` is not zero, that percentage of the execution and validation tasks of
+/// parallel execution is delayed by a millisecond. The delays are drawn from an RNG seeded with
+/// `delay_seed`, or with a random seed if none is given.
+#[allow(clippy::too_many_arguments)]
+pub fn fuzz_parallel_execution(
+    block_size: usize,
+    num_blocks: usize,
+    transaction_mix: Vec<(TransactionType, usize)>,
+    transactions_per_sender: usize,
+    num_main_signer_accounts: usize,
+    concurrency_levels: &[usize],
+    delay_percentage: u32,
+    delay_seed: Option<u64>,
+    source_dir: impl AsRef<Path>,
+    checkpoint_dir: impl AsRef<Path>,
+    pruner_config: PrunerConfig,
+    init_features: Features,
+) -> anyhow::Result<()> {
+    ensure!(
+        concurrency_levels.iter().all(|level| *level > 1),
+        "Concurrency levels must be greater than 1 to execute in parallel, got {:?}",
+        concurrency_levels
+    );
+
+    create_checkpoint(source_dir.as_ref(), checkpoint_dir.as_ref(), false);
+    let (mut config, genesis_key) =
+        aptos_genesis::test_utils::test_config_with_custom_features(init_features);
+    config.storage.dir = checkpoint_dir.as_ref().to_path_buf();
+    config.storage.storage_pruner_config = pruner_config;
+    let db = init_db(&config);
+    let root_account = Arc::new(TransactionGenerator::read_root_account(genesis_key, &db));
+
+    let num_existing_accounts = TransactionGenerator::read_meta(&source_dir);
+    let accounts_cache = TransactionGenerator::gen_user_account_cache(
+        db.reader.clone(),
+        std::cmp::min(num_existing_accounts, num_main_signer_accounts),
+        0,
+        false,
+    );
+    let (main_signer_accounts, burner_accounts) = accounts_cache.split(num_main_signer_accounts);
+    let (transaction_generator_creator, phase) = init_workload::<AptosVMBlockExecutor>(
+        transaction_mix,
+        root_account.clone(),
+        main_signer_accounts,
+        burner_accounts,
+        db.clone(),
+        &PipelineConfig::default(),
+    );
+
+    let (block_sender, block_receiver) = mpsc::sync_channel(1);
+    let mut generator = TransactionGenerator::new_with_existing_db(
+        db.clone(),
+        Arc::into_inner(root_account).unwrap(),
+        block_sender,
+        &source_dir,
+        Some(num_main_signer_accounts),
+        1,
+        false,
+    );
+
+    let delay_seed = delay_seed.unwrap_or_else(|| thread_rng().gen());
+    let num_injected_delays = inject_delays(delay_percentage, delay_seed);
+    let result = std::thread::scope(|scope| {
+        let checker = scope.spawn(move || {
+            let result = check_blocks(&db, &block_receiver, concurrency_levels);
+            // Keep receiving the generated blocks after a failure, so that the generator stops.
+            block_receiver.iter().for_each(drop);
+            result
+        });
+        generator.run_workload(
+            block_size,
+            num_blocks,
+            vec![transaction_generator_creator.create_transaction_generator()],
+            phase,
+            transactions_per_sender,
+        );
+        generator.drop_sender();
+        checker.join().expect("Execution checker panicked")
+    });
+    remove_injected_delays();
+
+    let num_checked_blocks = result.with_context(|| {
+        format!(
+            "Delaying {}% of the tasks with delay seed {}",
+            delay_percentage, delay_seed
+        )
+    })?;
+    info!(
+        "Parallel execution at concurrency levels {:?} matched sequential execution on {} blocks, \
+        with {} injected delays",
+        concurrency_levels,
+        num_checked_blocks,
+        num_injected_delays.load(Ordering::Relaxed)
+    );
+    Ok(())
+}
+
+/// Delays `delay_percentage` percent of the hits of the delay fail points by a millisecond, and
+/// returns the number of delays injected so far. Whether a hit is delayed is drawn from an RNG
+/// seeded with `delay_seed`, so re-running with the same seed injects the same sequence of delays,
+/// although which tasks they land on still depends on how the worker threads are scheduled.
+fn inject_delays(delay_percentage: u32, delay_seed: u64) -> Arc<AtomicUsize> {
+    let num_injected_delays = Arc::new(AtomicUsize::new(0));
+    if delay_percentage == 0 {
+        return num_injected_delays;
+    }
+    if !fail::has_failpoints() {
+        warn!("Built without the failpoints feature, no delays are injected in parallel execution");
+        return num_injected_delays;
+    }
+
+    info!(
+        "Delaying {}% of the tasks at fail points {:?} with delay seed {}",
+        delay_percentage, DELAY_FAIL_POINTS, delay_seed
+    );
+    let rng = Arc::new(Mutex::new(StdRng::seed_from_u64(delay_seed)));
+    for fail_point in DELAY_FAIL_POINTS {
+        let rng = rng.clone();
+        let num_injected_delays = num_injected_delays.clone();
+        fail::cfg_callback(fail_point, move || {
+            if rng.lock().unwrap().gen_range(0, 100) < delay_percentage {
+                num_injected_delays.fetch_add(1, Ordering::Relaxed);
+                std::thread::sleep(Duration::from_millis(1));
+            }
+        })
+        .unwrap();
+    }
+    num_injected_delays
+}
+
+fn remove_injected_delays() {
+    for fail_point in DELAY_FAIL_POINTS {
+        fail::remove(fail_point);
+    }
+}
+
+/// Executes and compares every block received until the sender is dropped, and returns the number
+/// of blocks checked.
+fn check_blocks(
+    db: &DbReaderWriter,
+    block_receiver: &mpsc::Receiver<Vec<Transaction>>,
+    concurrency_levels: &[usize],
+) -> anyhow::Result<usize> {
+    // Every configuration keeps its own executor, so that module caches are kept across blocks the
+    // same way as in production.
+    let sequential_executor = FixedConcurrencyAptosVMBlockExecutor::new_with_concurrency_level(1);
+    let parallel_executors: Vec<_> = concurrency_levels
+        .iter()
+        .map(|level| FixedConcurrencyAptosVMBlockExecutor::new_with_concurrency_level(*level))
+        .collect();
+
+    let mut parent_block_id = HashValue::zero();
+    let mut num_blocks = 0;
+    for txns in block_receiver.iter() {
+        let block_id = HashValue::random();
+        let txns: Vec<SignatureVerifiedTransaction> = txns.into_iter().map(Into::into).collect();
+
+        let expected = execute_block(db, &sequential_executor, &txns, parent_block_id, block_id)
+            .context("Sequential execution failed")?;
+        for executor in &parallel_executors {
+            execute_block(db, executor, &txns, parent_block_id, block_id)
+                .and_then(|result| diff_results(&expected, &result))
+                .with_context(|| {
+                    format!(
+                        "Parallel execution of block {} (number {}) at concurrency level {}",
+                        block_id, num_blocks, executor.concurrency_level
+                    )
+                })?;
+        }
+
+        db.writer
+            .save_transactions(expected.as_chunk_to_commit(), None, true)
+            .context("Failed to commit block")?;
+        parent_block_id = block_id;
+        num_blocks += 1;
+    }
+    Ok(num_blocks)
+}
+
+fn execute_block(
+    db: &DbReaderWriter,
+    executor: &FixedConcurrencyAptosVMBlockExecutor,
+    txns: &[SignatureVerifiedTransaction],
+    parent_block_id: HashValue,
+    block_id: HashValue,
+) -> anyhow::Result<StateComputeResult> {
+    let ledger_summary = db.reader.get_pre_committed_ledger_summary()?;
+    let state_view = ledger_summary.verified_state_view(
+        StateViewId::BlockExecution { block_id },
+        db.reader.clone(),
+        Arc::new(AsyncProofFetcher::new(db.reader.clone())),
+    )?;
+    let execution_output = DoGetExecutionOutput::by_transaction_execution(
+        executor,
+        ExecutableTransactions::Unsharded(txns.to_vec()),
+        state_view,
+        BENCHMARKS_BLOCK_EXECUTOR_ONCHAIN_CONFIG,
+        TransactionSliceMetadata::block(parent_block_id, block_id),
+    )?;
+    Ok(ApplyExecutionOutput::run(execution_output, &ledger_summary)?.expect_complete_result())
+}
+
+/// Returns an error describing the first difference between the expected (sequential) and the
+/// actual (parallel) result of executing a block.
+fn diff_results(expected: &StateComputeResult, actual: &StateComputeResult) -> anyhow::Result<()> {
+    let (expected_output, actual_output) = (&expected.execution_output, &actual.execution_output);
+    for (idx, (expected_status, actual_status)) in expected_output
+        .statuses_for_input_txns
+        .iter()
+        .zip(&actual_output.statuses_for_input_txns)
+        .enumerate()
+    {
+        ensure!(
+            expected_status == actual_status,
+            "Status of transaction {} differs: sequential {:?}, parallel {:?}",
+            idx,
+            expected_status,
+            actual_status
+        );
+    }
+
+    let (expected_to_commit, actual_to_commit) =
+        (&expected_output.to_commit, &actual_output.to_commit);
+    for (idx, (expected_txn_output, actual_txn_output)) in expected_to_commit
+        .transaction_outputs
+        .iter()
+        .zip(&actual_to_commit.transaction_outputs)
+        .enumerate()
+    {
+        if expected_txn_output != actual_txn_output {
+            bail!(
+                "Output of transaction {} ({:?}) differs:\nsequential: {:?}\nparallel: {:?}",
+                idx,
+                expected_to_commit.transactions[idx],
+                expected_txn_output,
+                actual_txn_output
+            );
+        }
+    }
+    ensure!(
+        expected_to_commit.transaction_outputs.len() == actual_to_commit.transaction_outputs.len(),
+        "Number of transactions to commit differs: sequential {}, parallel {}",
+        expected_to_commit.transaction_outputs.len(),
+        actual_to_commit.transaction_outputs.len()
+    );
+
+    ensure!(
+        expected.state_checkpoint_output.state_checkpoint_hashes
+            == actual.state_checkpoint_output.state_checkpoint_hashes,
+        "State roots differ: sequential {:?}, parallel {:?}",
+        expected.state_checkpoint_output.state_checkpoint_hashes,
+        actual.state_checkpoint_output.state_checkpoint_hashes
+    );
+    ensure!(
+        expected.root_hash() == actual.root_hash(),
+        "Ledger roots differ: sequential {}, parallel {}",
+        expected.root_hash(),
+        actual.root_hash()
+    );
+    Ok(())
+}
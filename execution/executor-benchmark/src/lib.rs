@@ -4,9 +4,11 @@
 
 mod account_generator;
 pub mod block_preparation;
+pub mod conflict_graph;
 pub mod db_access;
 pub mod db_generator;
 mod db_reliable_submitter;
+pub mod execution_fuzzer;
 mod ledger_update_stage;
 mod metrics;
 pub mod native;
@@ -327,7 +329,7 @@ where
 
         drop(db_gen_init_transaction_executor);
 
-        result
+        result.expect("Failed to create transaction generators")
     });
 
     info!("Waiting for init to finish");
@@ -813,6 +815,7 @@ mod tests {
         transaction::{Transaction, TransactionPayload},
     };
     use aptos_vm::{aptos_vm::AptosVMBlockExecutor, AptosVM, VMBlockExecutor};
+    use fail::FailScenario;
     use itertools::Itertools;
     use move_core_types::language_storage::StructTag;
     use rand::thread_rng;
@@ -1119,6 +1122,54 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_fuzz_parallel_execution() {
+        aptos_logger::Logger::new().init();
+        // Fail points are global, so tests configuring them must not run concurrently.
+        let scenario = FailScenario::setup();
+
+        let storage_dir = TempPath::new();
+        let checkpoint_dir = TempPath::new();
+        let features = default_benchmark_features();
+
+        crate::db_generator::create_db_with_accounts::<AptosVMBlockExecutor>(
+            100,             /* num_accounts */
+            100_000_000_000, /* init_account_balance */
+            5,               /* block_size */
+            storage_dir.as_ref(),
+            NO_OP_STORAGE_PRUNER_CONFIG, /* prune_window */
+            false,
+            false,
+            PipelineConfig::default(),
+            features.clone(),
+            false,
+        );
+
+        crate::execution_fuzzer::fuzz_parallel_execution(
+            10, /* block_size */
+            10, /* num_blocks */
+            vec![(
+                TransactionTypeArg::ModifyGlobalResource.materialize(
+                    1,
+                    true,
+                    WorkflowProgress::MoveByPhases,
+                ),
+                1,
+            )],
+            2,       /* transactions per sender */
+            25,      /* num_main_signer_accounts */
+            &[2, 4], /* concurrency_levels */
+            20,      /* delay_percentage */
+            Some(0), /* delay_seed */
+            storage_dir.as_ref(),
+            checkpoint_dir,
+            NO_OP_STORAGE_PRUNER_CONFIG,
+            features,
+        )
+        .unwrap();
+        scenario.teardown();
+    }
+
     #[test]
     fn test_native_vm_benchmark_transaction() {
         test_generic_benchmark::<NativeVMBlockExecutor>(
@@ -35,7 +35,10 @@ use aptos_metrics_core::{register_int_gauge, IntGauge};
 use aptos_profiler::{ProfilerConfig, ProfilerHandler};
 use aptos_push_metrics::MetricsPusher;
 use aptos_transaction_generator_lib::{args::TransactionTypeArg, WorkflowProgress};
-use aptos_types::on_chain_config::{FeatureFlag, Features};
+use aptos_types::{
+    block_executor::config::ConflictAwareExecution,
+    on_chain_config::{FeatureFlag, Features},
+};
 use aptos_vm::{aptos_vm::AptosVMBlockExecutor, AptosVM, VMBlockExecutor};
 use aptos_vm_environment::prod_configs::set_paranoid_type_checks;
 use clap::{Parser, Subcommand, ValueEnum};
@@ -321,6 +324,17 @@ struct Opt {
 
     #[clap(long)]
     skip_paranoid_checks: bool,
+
+    /// If set, parallel execution saves a trace of every block (read and write sets, incarnations
+    /// and aborts of every transaction) in this directory. See the conflict-graph command.
+    #[clap(long, value_parser)]
+    execution_trace_dir: Option<PathBuf>,
+
+    /// Whether parallel execution is scheduled along conflict chains predicted from the declared
+    /// read and write sets of the transactions: disabled, fallback (only while recent blocks are
+    /// contended, or when re-executing a block after a failed parallel execution) or always.
+    #[clap(long, default_value = "disabled")]
+    conflict_aware_execution: ConflictAwareExecution,
 }
 
 impl Opt {
@@ -429,6 +443,88 @@ enum Command {
         #[clap(long, default_value_t = 1000000)]
         init_account_balance: u64,
     },
+    /// Executes blocks of the workload sequentially and in parallel at several concurrency
+    /// levels, and fails if the outputs or state roots differ. Build with the failpoints feature
+    /// to inject scheduling delays.
+    FuzzParallelExecution {
+        #[clap(long, default_value_t = 1000)]
+        blocks: usize,
+
+        #[clap(long, default_value_t = 1000000)]
+        main_signer_accounts: usize,
+
+        /// Workload (transaction type), generated by transaction-generator-lib
+        #[clap(
+            long,
+            value_enum,
+            num_args = 1..,
+            required = true,
+            ignore_case = true
+        )]
+        transaction_type: Vec<TransactionTypeArg>,
+
+        #[clap(long, num_args = 0..)]
+        transaction_weights: Vec<usize>,
+
+        #[clap(long, default_value_t = 1)]
+        module_working_set_size: usize,
+
+        #[clap(long)]
+        use_sender_account_pool: bool,
+
+        /// Concurrency levels to execute every block at, compared to sequential execution
+        #[clap(long, num_args = 1.., default_values_t = vec![2, 4, 8])]
+        concurrency_levels: Vec<usize>,
+
+        /// Percentage of execution and validation tasks delayed by a millisecond
+        #[clap(long, default_value_t = 10)]
+        delay_percentage: u32,
+
+        /// Seed of the RNG deciding which tasks are delayed, random if not set. The seed used is
+        /// logged, to replay the same sequence of delays.
+        #[clap(long)]
+        delay_seed: Option<u64>,
+
+        #[clap(long, value_parser)]
+        data_dir: PathBuf,
+
+        #[clap(long, value_parser)]
+        checkpoint_dir: PathBuf,
+
+        #[clap(
+            long,
+            num_args=1..,
+            value_delimiter = ' ',
+            help = "Optional custom enabling/disabling of the feature flags in the Move source. Enable / disable flags cannot overlap.\
+            Sample usage: --enable-feature=V1 --disable-feature=V2 V3 where V1, V2, V3 are FeatureFlag enum variants.")]
+        enable_feature: Vec<FeatureFlag>,
+
+        #[clap(
+            long,
+            num_args=1..,
+            value_delimiter = ' ',
+            help = "Optional custom enabling/disabling of the feature flags in the Move source. Enable / disable flags cannot overlap.\
+            Sample usage: --enable-feature=V1 --disable-feature=V2 V3 where V1, V2, V3 are FeatureFlag enum variants.")]
+        disable_feature: Vec<FeatureFlag>,
+    },
+    /// Renders the block traces saved with --execution-trace-dir as conflict graphs (DOT files)
+    ConflictGraph {
+        /// A block trace file, or a directory of block traces
+        #[clap(long, value_parser)]
+        trace_path: PathBuf,
+
+        /// Directory to write a DOT file per block into
+        #[clap(long, value_parser)]
+        output_dir: PathBuf,
+
+        /// Also draw read-after-write dependencies between committed transactions
+        #[clap(long)]
+        include_dependencies: bool,
+
+        /// Number of keys causing the most aborts to print per block
+        #[clap(long, default_value_t = 10)]
+        num_hot_keys: usize,
+    },
 }
 
 fn get_init_features(
@@ -558,6 +654,61 @@ where
                 opt.use_keyless_accounts,
             );
         },
+        Command::FuzzParallelExecution {
+            blocks,
+            main_signer_accounts,
+            transaction_type,
+            transaction_weights,
+            module_working_set_size,
+            use_sender_account_pool,
+            concurrency_levels,
+            delay_percentage,
+            delay_seed,
+            data_dir,
+            checkpoint_dir,
+            enable_feature,
+            disable_feature,
+        } => {
+            let mix_per_phase = TransactionTypeArg::args_to_transaction_mix_per_phase(
+                &transaction_type,
+                &transaction_weights,
+                &[],
+                module_working_set_size,
+                use_sender_account_pool,
+                WorkflowProgress::MoveByPhases,
+            );
+            assert!(mix_per_phase.len() == 1);
+
+            aptos_executor_benchmark::execution_fuzzer::fuzz_parallel_execution(
+                opt.block_size,
+                blocks,
+                mix_per_phase[0].clone(),
+                opt.transactions_per_sender,
+                main_signer_accounts,
+                &concurrency_levels,
+                delay_percentage,
+                delay_seed,
+                data_dir,
+                checkpoint_dir,
+                opt.pruner_opt.pruner_config(),
+                get_init_features(enable_feature, disable_feature),
+            )
+            .expect("Parallel execution differs from sequential execution");
+        },
+        Command::ConflictGraph {
+            trace_path,
+            output_dir,
+            include_dependencies,
+            num_hot_keys,
+        } => {
+            aptos_executor_benchmark::conflict_graph::render_conflict_graphs(
+                &trace_path,
+                &output_dir,
+                include_dependencies,
+                num_hot_keys,
+            )
+            .expect("Failed to render conflict graphs");
+        },
     }
 }
 
@@ -622,6 +773,10 @@ fn main() {
     if opt.skip_paranoid_checks {
         set_paranoid_type_checks(false);
     }
+    if let Some(execution_trace_dir) = &opt.execution_trace_dir {
+        AptosVM::set_execution_trace_dir_once(execution_trace_dir.clone());
+    }
+    AptosVM::set_conflict_aware_execution_once(opt.conflict_aware_execution);
     AptosVM::set_num_shards_once(execution_shards);
     AptosVM::set_concurrency_level_once(execution_threads_per_shard);
     NativeConfig::set_concurrency_level_once(execution_threads_per_shard);
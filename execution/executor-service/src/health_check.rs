@@ -0,0 +1,201 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Health checks of the remote executor shards. The coordinator periodically pings every shard on
+//! a dedicated channel, which is served by a separate thread on the shard, so that a shard busy
+//! executing a block still responds. Every response carries the block request the shard is
+//! executing, which allows the coordinator to detect shards stuck on a request it gave up on. In
+//! turn, every health check carries the last request the coordinator gave up on the shard
+//! executing, so that the shard stops waiting for messages of that request and recovers.
+
+use crate::{remote_cordinator_client::RequestStatus, HealthCheckRequest, HealthCheckResponse};
+use aptos_infallible::Mutex;
+use aptos_logger::{info, warn};
+use aptos_secure_net::network_controller::{Message, NetworkController};
+use aptos_types::block_executor::partitioner::ShardId;
+use crossbeam_channel::{Receiver, Sender};
+use std::{
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Number of consecutive health checks a shard may miss before it is considered unhealthy.
+const MAX_MISSED_HEALTH_CHECKS: u32 = 3;
+
+fn health_check_type(shard_id: ShardId) -> String {
+    format!("health_check_{}", shard_id)
+}
+
+fn health_check_result_type(shard_id: ShardId) -> String {
+    format!("health_check_result_{}", shard_id)
+}
+
+#[derive(Debug)]
+struct ShardStatus {
+    /// Time of the last health check response, None if the shard failed since then.
+    last_response: Option<Instant>,
+    /// The block request the shard was executing at its last health check response.
+    executing_request_id: Option<u64>,
+    /// The last block request the shard failed to execute in time.
+    failed_request_id: Option<u64>,
+}
+
+/// Tracks the health of the remote executor shards, on the coordinator.
+pub struct ShardHealthChecker {
+    statuses: Arc<Vec<Mutex<ShardStatus>>>,
+    health_check_txs: Arc<Vec<Mutex<Sender<Message>>>>,
+    health_check_result_rxs: Arc<Vec<Receiver<Message>>>,
+    interval: Duration,
+    stopped: Arc<AtomicBool>,
+}
+
+impl ShardHealthChecker {
+    pub fn new(
+        controller: &mut NetworkController,
+        remote_shard_addresses: &[SocketAddr],
+        interval: Duration,
+    ) -> Self {
+        let (health_check_txs, health_check_result_rxs) = remote_shard_addresses
+            .iter()
+            .enumerate()
+            .map(|(shard_id, address)| {
+                let tx = controller.create_outbound_channel(*address, health_check_type(shard_id));
+                let rx = controller.create_inbound_channel(health_check_result_type(shard_id));
+                (Mutex::new(tx), rx)
+            })
+            .unzip();
+        // Shards are assumed to be healthy until they miss their first health checks.
+        let now = Instant::now();
+        let statuses = remote_shard_addresses
+            .iter()
+            .map(|_| {
+                Mutex::new(ShardStatus {
+                    last_response: Some(now),
+                    executing_request_id: None,
+                    failed_request_id: None,
+                })
+            })
+            .collect();
+
+        Self {
+            statuses: Arc::new(statuses),
+            health_check_txs: Arc::new(health_check_txs),
+            health_check_result_rxs: Arc::new(health_check_result_rxs),
+            interval,
+            stopped: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Starts the thread periodically sending health checks to the shards.
+    pub fn start(&self) -> thread::JoinHandle<()> {
+        let statuses = self.statuses.clone();
+        let health_check_txs = self.health_check_txs.clone();
+        let health_check_result_rxs = self.health_check_result_rxs.clone();
+        let interval = self.interval;
+        let stopped = self.stopped.clone();
+        thread::Builder::new()
+            .name("remote-shard-health-checker".to_string())
+            .spawn(move || {
+                while !stopped.load(Ordering::Relaxed) {
+                    for (status, rx) in statuses.iter().zip(health_check_result_rxs.iter()) {
+                        while let Ok(message) = rx.try_recv() {
+                            let response: HealthCheckResponse =
+                                bcs::from_bytes(&message.to_bytes()).unwrap();
+                            let mut status = status.lock();
+                            status.last_response = Some(Instant::now());
+                            status.executing_request_id = response.executing_request_id;
+                        }
+                    }
+                    for (status, tx) in statuses.iter().zip(health_check_txs.iter()) {
+                        let request = HealthCheckRequest {
+                            abandoned_request_id: status.lock().failed_request_id,
+                        };
+                        let message = Message::new(bcs::to_bytes(&request).unwrap());
+                        if tx.lock().send(message).is_err() {
+                            // The network controller was shut down.
+                            return;
+                        }
+                    }
+                    thread::sleep(interval);
+                }
+            })
+            .expect("Failed to spawn thread")
+    }
+
+    /// Returns whether the shard responded to its recent health checks, and is not still
+    /// executing a block request it failed to execute in time.
+    pub fn is_healthy(&self, shard_id: ShardId) -> bool {
+        let status = self.statuses[shard_id].lock();
+        let responsive = status.last_response.map_or(false, |last_response| {
+            last_response.elapsed() < self.interval * MAX_MISSED_HEALTH_CHECKS
+        });
+        let stuck = match (status.executing_request_id, status.failed_request_id) {
+            (Some(executing), Some(failed)) => executing <= failed,
+            _ => false,
+        };
+        responsive && !stuck
+    }
+
+    /// Marks the shard as unhealthy after it failed to execute the given block request, until it
+    /// responds to a new health check without executing that request anymore.
+    pub fn mark_failed(&self, shard_id: ShardId, request_id: u64) {
+        warn!(
+            "Remote executor shard {} failed to execute block request {}",
+            shard_id, request_id
+        );
+        let mut status = self.statuses[shard_id].lock();
+        status.last_response = None;
+        status.failed_request_id = Some(request_id);
+    }
+
+    pub fn stop(&self) {
+        self.stopped.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Responds to the health checks of the coordinator, on a shard.
+pub struct HealthCheckResponder {
+    shard_id: ShardId,
+    health_check_rx: Receiver<Message>,
+    health_check_result_tx: Sender<Message>,
+    request_status: Arc<RequestStatus>,
+}
+
+impl HealthCheckResponder {
+    pub fn new(
+        shard_id: ShardId,
+        controller: &mut NetworkController,
+        coordinator_address: SocketAddr,
+        request_status: Arc<RequestStatus>,
+    ) -> Self {
+        Self {
+            shard_id,
+            health_check_rx: controller.create_inbound_channel(health_check_type(shard_id)),
+            health_check_result_tx: controller
+                .create_outbound_channel(coordinator_address, health_check_result_type(shard_id)),
+            request_status,
+        }
+    }
+
+    /// Responds to health checks until the network controller is shut down.
+    pub fn start(&self) {
+        while let Ok(message) = self.health_check_rx.recv() {
+            let request: HealthCheckRequest = bcs::from_bytes(&message.to_bytes()).unwrap();
+            self.request_status
+                .set_abandoned_request_id(request.abandoned_request_id);
+            let response = HealthCheckResponse {
+                executing_request_id: self.request_status.executing_request_id(),
+            };
+            let message = Message::new(bcs::to_bytes(&response).unwrap());
+            if self.health_check_result_tx.send(message).is_err() {
+                break;
+            }
+        }
+        info!("Health check responder of shard {} stopped", self.shard_id);
+    }
+}
@@ -9,9 +9,11 @@ use aptos_types::{
     transaction::{analyzed_transaction::AnalyzedTransaction, TransactionOutput},
     vm_status::VMStatus,
 };
+use aptos_vm::sharded_block_executor::messages::CrossShardMsg;
 use serde::{Deserialize, Serialize};
 
 mod error;
+mod health_check;
 pub mod local_executor_helper;
 mod metrics;
 pub mod process_executor_service;
@@ -30,12 +32,14 @@ mod thread_executor_service;
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct RemoteExecutionResult {
+    /// The id of the block request this is the result of.
+    pub request_id: u64,
     pub inner: Result<Vec<Vec<TransactionOutput>>, VMStatus>,
 }
 
 impl RemoteExecutionResult {
-    pub fn new(inner: Result<Vec<Vec<TransactionOutput>>, VMStatus>) -> Self {
-        Self { inner }
+    pub fn new(request_id: u64, inner: Result<Vec<Vec<TransactionOutput>>, VMStatus>) -> Self {
+        Self { request_id, inner }
     }
 }
 
@@ -46,6 +50,9 @@ pub enum RemoteExecutionRequest {
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct ExecuteBlockCommand {
+    /// Identifies the block request, so that late results of a request the coordinator gave up
+    /// on are not mistaken for the results of a later one.
+    pub(crate) request_id: u64,
     pub(crate) sub_blocks: SubBlocksForShard<AnalyzedTransaction>,
     pub(crate) concurrency_level: usize,
     pub(crate) onchain_config: BlockExecutorConfigFromOnchain,
@@ -63,29 +70,69 @@ impl ExecuteBlockCommand {
     }
 }
 
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct HealthCheckRequest {
+    /// The last block request the coordinator gave up on the shard executing, if any.
+    pub(crate) abandoned_request_id: Option<u64>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct HealthCheckResponse {
+    /// The block request the shard is executing, if any.
+    pub(crate) executing_request_id: Option<u64>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RemoteCrossShardMsg {
+    /// The block request the message was sent while executing.
+    pub(crate) request_id: u64,
+    pub(crate) msg: CrossShardMsg,
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct RemoteKVRequest {
     pub(crate) shard_id: ShardId,
+    /// The block request the state values are read for.
+    pub(crate) request_id: u64,
     pub(crate) keys: Vec<StateKey>,
 }
 
 impl RemoteKVRequest {
-    pub fn new(shard_id: ShardId, keys: Vec<StateKey>) -> Self {
-        Self { shard_id, keys }
+    pub fn new(shard_id: ShardId, request_id: u64, keys: Vec<StateKey>) -> Self {
+        Self {
+            shard_id,
+            request_id,
+            keys,
+        }
     }
 
-    pub fn into(self) -> (ShardId, Vec<StateKey>) {
-        (self.shard_id, self.keys)
+    pub fn into(self) -> (ShardId, u64, Vec<StateKey>) {
+        (self.shard_id, self.request_id, self.keys)
     }
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct RemoteKVResponse {
+    pub(crate) request_id: u64,
     pub(crate) inner: Vec<(StateKey, Option<StateValue>)>,
+    /// The error the state values could not be read with, in which case they are all None.
+    pub(crate) error: Option<String>,
 }
 
 impl RemoteKVResponse {
-    pub fn new(inner: Vec<(StateKey, Option<StateValue>)>) -> Self {
-        Self { inner }
+    pub fn new(request_id: u64, inner: Vec<(StateKey, Option<StateValue>)>) -> Self {
+        Self {
+            request_id,
+            inner,
+            error: None,
+        }
+    }
+
+    pub fn new_error(request_id: u64, keys: Vec<StateKey>, error: String) -> Self {
+        Self {
+            request_id,
+            inner: keys.into_iter().map(|key| (key, None)).collect(),
+            error: Some(error),
+        }
     }
 }
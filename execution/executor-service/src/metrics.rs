@@ -44,3 +44,17 @@ pub static REMOTE_EXECUTOR_REMOTE_KV_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
     )
     .unwrap()
 });
+
+pub static REMOTE_EXECUTOR_FALLBACK_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        // metric name
+        "remote_executor_fallback_count",
+        // metric description
+        "Number of blocks executed locally instead of on the remote shards, by reason: \
+         1. unhealthy_shard: a shard was unhealthy before the block was sent; \
+         2. shard_failure: a shard failed or timed out executing the block; ",
+        // metric labels (dimensions)
+        &["reason"],
+    )
+    .unwrap()
+});
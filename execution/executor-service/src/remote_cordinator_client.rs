@@ -4,6 +4,7 @@ use crate::{
     metrics::REMOTE_EXECUTOR_TIMER, remote_state_view::RemoteStateViewClient, ExecuteBlockCommand,
     RemoteExecutionRequest, RemoteExecutionResult,
 };
+use aptos_infallible::Mutex;
 use aptos_secure_net::network_controller::{Message, NetworkController};
 use aptos_types::{
     block_executor::partitioner::ShardId, state_store::state_key::StateKey,
@@ -16,11 +17,44 @@ use crossbeam_channel::{Receiver, Sender};
 use rayon::prelude::*;
 use std::{net::SocketAddr, sync::Arc};
 
+/// The block requests of a shard, shared with its health check responder and cross shard client.
+#[derive(Debug, Default)]
+pub struct RequestStatus {
+    /// The block request being executed, if any.
+    executing_request_id: Mutex<Option<u64>>,
+    /// The last block request the coordinator gave up on the shard executing, as reported by its
+    /// health checks.
+    abandoned_request_id: Mutex<Option<u64>>,
+}
+
+impl RequestStatus {
+    pub fn executing_request_id(&self) -> Option<u64> {
+        *self.executing_request_id.lock()
+    }
+
+    pub fn set_abandoned_request_id(&self, abandoned_request_id: Option<u64>) {
+        *self.abandoned_request_id.lock() = abandoned_request_id;
+    }
+
+    /// Returns true iff the coordinator gave up on the block request being executed, in which
+    /// case the shard stops waiting for the messages of that request.
+    pub fn is_executing_request_abandoned(&self) -> bool {
+        match (
+            self.executing_request_id(),
+            *self.abandoned_request_id.lock(),
+        ) {
+            (Some(executing), Some(abandoned)) => executing <= abandoned,
+            _ => false,
+        }
+    }
+}
+
 pub struct RemoteCoordinatorClient {
     state_view_client: Arc<RemoteStateViewClient>,
     command_rx: Receiver<Message>,
     result_tx: Sender<Message>,
     shard_id: ShardId,
+    request_status: Arc<RequestStatus>,
 }
 
 impl RemoteCoordinatorClient {
@@ -43,9 +77,14 @@ impl RemoteCoordinatorClient {
             command_rx,
             result_tx,
             shard_id,
+            request_status: Arc::new(RequestStatus::default()),
         }
     }
 
+    pub fn request_status(&self) -> Arc<RequestStatus> {
+        self.request_status.clone()
+    }
+
     // Extract all the state keys from the execute block command. It is possible that there are duplicate state keys.
     // We are not de-duplicating them here to avoid the overhead of deduplication. The state view server will deduplicate
     // the state keys.
@@ -91,11 +130,13 @@ impl CoordinatorClient<RemoteStateViewClient> for RemoteCoordinatorClient {
 
                 match request {
                     RemoteExecutionRequest::ExecuteBlock(command) => {
+                        *self.request_status.executing_request_id.lock() = Some(command.request_id);
                         let init_prefetch_timer = REMOTE_EXECUTOR_TIMER
                             .with_label_values(&[&self.shard_id.to_string(), "init_prefetch"])
                             .start_timer();
                         let state_keys = Self::extract_state_keys(&command);
-                        self.state_view_client.init_for_block(state_keys);
+                        self.state_view_client
+                            .init_for_block(command.request_id, state_keys);
                         drop(init_prefetch_timer);
 
                         let (sub_blocks, concurrency, onchain_config) = command.into();
@@ -113,7 +154,13 @@ impl CoordinatorClient<RemoteStateViewClient> for RemoteCoordinatorClient {
     }
 
     fn send_execution_result(&self, result: Result<Vec<Vec<TransactionOutput>>, VMStatus>) {
-        let remote_execution_result = RemoteExecutionResult::new(result);
+        let request_id = self
+            .request_status
+            .executing_request_id
+            .lock()
+            .take()
+            .expect("Execution result sent without an executing block request");
+        let remote_execution_result = RemoteExecutionResult::new(request_id, result);
         let output_message = bcs::to_bytes(&remote_execution_result).unwrap();
         self.result_tx.send(Message::new(output_message)).unwrap();
     }
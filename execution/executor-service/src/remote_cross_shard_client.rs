@@ -1,25 +1,47 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
+use crate::{remote_cordinator_client::RequestStatus, RemoteCrossShardMsg};
+use aptos_logger::{trace, warn};
 use aptos_secure_net::network_controller::{Message, NetworkController};
 use aptos_types::block_executor::partitioner::{RoundId, ShardId, MAX_ALLOWED_PARTITIONING_ROUNDS};
 use aptos_vm::sharded_block_executor::{
     cross_shard_client::CrossShardClient, messages::CrossShardMsg,
 };
-use crossbeam_channel::{Receiver, Sender};
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
 use std::{
+    cmp::Ordering,
+    collections::VecDeque,
     net::SocketAddr,
     sync::{Arc, Mutex},
+    time::Duration,
 };
 
+/// How often a shard waiting for cross shard messages checks whether the coordinator gave up on
+/// the block request being executed.
+const ABANDONED_REQUEST_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+struct CrossShardReceiver {
+    rx: Receiver<Message>,
+    // Messages of a later block request, received while executing a request that was abandoned.
+    pending: VecDeque<RemoteCrossShardMsg>,
+}
+
 pub struct RemoteCrossShardClient {
     // The senders of cross-shard messages to other shards per round.
     message_txs: Arc<Vec<Vec<Mutex<Sender<Message>>>>>,
     // The receivers of cross shard messages from other shards per round.
-    message_rxs: Arc<Vec<Mutex<Receiver<Message>>>>,
+    message_rxs: Arc<Vec<Mutex<CrossShardReceiver>>>,
+    // Cross shard messages are tagged with the block request being executed, so that the messages
+    // of abandoned requests are told apart.
+    request_status: Arc<RequestStatus>,
 }
 
 impl RemoteCrossShardClient {
-    pub fn new(controller: &mut NetworkController, shard_addresses: Vec<SocketAddr>) -> Self {
+    pub fn new(
+        controller: &mut NetworkController,
+        shard_addresses: Vec<SocketAddr>,
+        request_status: Arc<RequestStatus>,
+    ) -> Self {
         let mut message_txs = vec![];
         let mut message_rxs = vec![];
         // Create outbound channels for each shard per round.
@@ -37,14 +59,24 @@ impl RemoteCrossShardClient {
         for round in 0..MAX_ALLOWED_PARTITIONING_ROUNDS {
             let message_type = format!("cross_shard_{}", round);
             let rx = controller.create_inbound_channel(message_type);
-            message_rxs.push(Mutex::new(rx));
+            message_rxs.push(Mutex::new(CrossShardReceiver {
+                rx,
+                pending: VecDeque::new(),
+            }));
         }
 
         Self {
             message_txs: Arc::new(message_txs),
             message_rxs: Arc::new(message_rxs),
+            request_status,
         }
     }
+
+    fn executing_request_id(&self) -> u64 {
+        self.request_status
+            .executing_request_id()
+            .expect("Cross shard messages are only exchanged while executing a block request")
+    }
 }
 
 impl CrossShardClient for RemoteCrossShardClient {
@@ -53,15 +85,67 @@ impl CrossShardClient for RemoteCrossShardClient {
     }
 
     fn send_cross_shard_msg(&self, shard_id: ShardId, round: RoundId, msg: CrossShardMsg) {
+        let msg = RemoteCrossShardMsg {
+            request_id: self.executing_request_id(),
+            msg,
+        };
         let input_message = bcs::to_bytes(&msg).unwrap();
         let tx = self.message_txs[shard_id][round].lock().unwrap();
         tx.send(Message::new(input_message)).unwrap();
     }
 
     fn receive_cross_shard_msg(&self, current_round: RoundId) -> CrossShardMsg {
-        let rx = self.message_rxs[current_round].lock().unwrap();
-        let message = rx.recv().unwrap();
-        let msg: CrossShardMsg = bcs::from_bytes(&message.to_bytes()).unwrap();
-        msg
+        let request_id = self.executing_request_id();
+        let mut receiver = self.message_rxs[current_round].lock().unwrap();
+        receiver
+            .pending
+            .retain(|pending_msg| pending_msg.request_id >= request_id);
+        if let Some(pending_msg) = receiver.pending.pop_front() {
+            if pending_msg.request_id == request_id {
+                return pending_msg.msg;
+            }
+            receiver.pending.push_front(pending_msg);
+        }
+
+        loop {
+            match receiver.rx.recv_timeout(ABANDONED_REQUEST_CHECK_INTERVAL) {
+                Ok(message) => {
+                    let msg: RemoteCrossShardMsg = bcs::from_bytes(&message.to_bytes()).unwrap();
+                    match msg.request_id.cmp(&request_id) {
+                        Ordering::Equal => return msg.msg,
+                        Ordering::Less => trace!(
+                            "Dropping cross shard message of abandoned block request {} for round {}",
+                            msg.request_id,
+                            current_round
+                        ),
+                        Ordering::Greater => {
+                            // Other shards moved on to a later request, so this one was abandoned.
+                            warn!(
+                                "Received cross shard message of block request {} while executing \
+                                 block request {}, aborting it",
+                                msg.request_id, request_id
+                            );
+                            receiver.pending.push_back(msg);
+                            return CrossShardMsg::AbortMsg;
+                        },
+                    }
+                },
+                Err(RecvTimeoutError::Timeout) => {
+                    if self.request_status.is_executing_request_abandoned() {
+                        warn!(
+                            "Coordinator gave up on block request {}, aborting it",
+                            request_id
+                        );
+                        return CrossShardMsg::AbortMsg;
+                    }
+                },
+                Err(RecvTimeoutError::Disconnected) => {
+                    panic!(
+                        "Cross shard channel for round {} disconnected",
+                        current_round
+                    )
+                },
+            }
+        }
     }
 }
@@ -2,15 +2,17 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 use crate::{
+    health_check::ShardHealthChecker, metrics::REMOTE_EXECUTOR_FALLBACK_COUNT,
     remote_state_view_service::RemoteStateViewService, ExecuteBlockCommand, RemoteExecutionRequest,
     RemoteExecutionResult,
 };
-use aptos_logger::{info, trace};
+use aptos_logger::{info, trace, warn};
 use aptos_secure_net::network_controller::{Message, NetworkController};
 use aptos_storage_interface::state_store::state_view::cached_state_view::CachedStateView;
 use aptos_types::{
     block_executor::{
-        config::BlockExecutorConfigFromOnchain, partitioner::PartitionedTransactions,
+        config::BlockExecutorConfigFromOnchain,
+        partitioner::{PartitionedTransactions, ShardId},
     },
     state_store::StateView,
     transaction::TransactionOutput,
@@ -18,14 +20,19 @@ use aptos_types::{
 };
 use aptos_vm::sharded_block_executor::{
     executor_client::{ExecutorClient, ShardedExecutionOutput},
+    local_executor_shard::{LocalExecutorClient, LocalExecutorService},
     ShardedBlockExecutor,
 };
-use crossbeam_channel::{Receiver, Sender};
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
 use once_cell::sync::{Lazy, OnceCell};
 use std::{
     net::{IpAddr, Ipv4Addr, SocketAddr},
-    sync::{Arc, Mutex},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
     thread,
+    time::{Duration, Instant},
 };
 
 pub static COORDINATOR_PORT: u16 = 52200;
@@ -72,6 +79,25 @@ pub static REMOTE_SHARDED_BLOCK_EXECUTOR: Lazy<
     ))
 });
 
+/// Failure handling configuration of the remote executor client.
+#[derive(Clone, Debug)]
+pub struct RemoteExecutorConfig {
+    /// How long to wait for the shards to execute a block, before executing it locally instead.
+    pub execution_timeout: Duration,
+    /// How often the shards are health checked. A shard missing a few health checks in a row is
+    /// considered unhealthy.
+    pub health_check_interval: Duration,
+}
+
+impl Default for RemoteExecutorConfig {
+    fn default() -> Self {
+        Self {
+            execution_timeout: Duration::from_secs(30),
+            health_check_interval: Duration::from_secs(1),
+        }
+    }
+}
+
 #[allow(dead_code)]
 pub struct RemoteExecutorClient<S: StateView + Sync + Send + 'static> {
     // The network controller used to create channels to send and receive messages. We want the
@@ -85,17 +111,38 @@ pub struct RemoteExecutorClient<S: StateView + Sync + Send + 'static> {
     result_rxs: Vec<Receiver<Message>>,
     // Thread pool used to pre-fetch the state values for the block in parallel and create an in-memory state view.
     thread_pool: Arc<rayon::ThreadPool>,
+    config: RemoteExecutorConfig,
+    health_checker: ShardHealthChecker,
+    // Identifies the block requests sent to the shards.
+    next_request_id: AtomicU64,
+    // Executes the blocks the remote shards fail to execute, created on the first failure.
+    local_fallback_client: OnceCell<LocalExecutorClient<S>>,
 
     phantom: std::marker::PhantomData<S>,
     _join_handle: Option<thread::JoinHandle<()>>,
+    _health_check_join_handle: Option<thread::JoinHandle<()>>,
 }
 
 #[allow(dead_code)]
 impl<S: StateView + Sync + Send + 'static> RemoteExecutorClient<S> {
     pub fn new(
+        remote_shard_addresses: Vec<SocketAddr>,
+        controller: NetworkController,
+        num_threads: Option<usize>,
+    ) -> Self {
+        Self::new_with_config(
+            remote_shard_addresses,
+            controller,
+            num_threads,
+            RemoteExecutorConfig::default(),
+        )
+    }
+
+    pub fn new_with_config(
         remote_shard_addresses: Vec<SocketAddr>,
         mut controller: NetworkController,
         num_threads: Option<usize>,
+        config: RemoteExecutorConfig,
     ) -> Self {
         let num_threads = num_threads.unwrap_or_else(num_cpus::get);
         let thread_pool = Arc::new(
@@ -119,6 +166,12 @@ impl<S: StateView + Sync + Send + 'static> RemoteExecutorClient<S> {
             })
             .unzip();
 
+        let health_checker = ShardHealthChecker::new(
+            controller_mut_ref,
+            &remote_shard_addresses,
+            config.health_check_interval,
+        );
+
         let state_view_service = Arc::new(RemoteStateViewService::new(
             controller_mut_ref,
             remote_shard_addresses,
@@ -133,14 +186,20 @@ impl<S: StateView + Sync + Send + 'static> RemoteExecutorClient<S> {
             .unwrap();
 
         controller.start();
+        let health_check_join_handle = health_checker.start();
 
         Self {
             network_controller: controller,
             state_view_service,
             _join_handle: Some(join_handle),
+            _health_check_join_handle: Some(health_check_join_handle),
             command_txs: Arc::new(command_txs),
             result_rxs,
             thread_pool,
+            config,
+            health_checker,
+            next_request_id: AtomicU64::new(0),
+            local_fallback_client: OnceCell::new(),
             phantom: std::marker::PhantomData,
         }
     }
@@ -161,15 +220,75 @@ impl<S: StateView + Sync + Send + 'static> RemoteExecutorClient<S> {
         ))
     }
 
-    fn get_output_from_shards(&self) -> Result<Vec<Vec<Vec<TransactionOutput>>>, VMStatus> {
+    /// Waits for the results of the block request from all the shards. Returns None for the
+    /// shards which did not execute the block before the deadline, or became unhealthy.
+    fn get_output_from_shards(
+        &self,
+        request_id: u64,
+        deadline: Instant,
+    ) -> Vec<Option<Result<Vec<Vec<TransactionOutput>>, VMStatus>>> {
         trace!("RemoteExecutorClient Waiting for results");
-        let mut results = vec![];
-        for rx in self.result_rxs.iter() {
-            let received_bytes = rx.recv().unwrap().to_bytes();
-            let result: RemoteExecutionResult = bcs::from_bytes(&received_bytes).unwrap();
-            results.push(result.inner?);
+        self.result_rxs
+            .iter()
+            .enumerate()
+            .map(|(shard_id, rx)| self.get_output_from_shard(shard_id, rx, request_id, deadline))
+            .collect()
+    }
+
+    fn get_output_from_shard(
+        &self,
+        shard_id: ShardId,
+        rx: &Receiver<Message>,
+        request_id: u64,
+        deadline: Instant,
+    ) -> Option<Result<Vec<Vec<TransactionOutput>>, VMStatus>> {
+        loop {
+            let timeout = deadline
+                .saturating_duration_since(Instant::now())
+                .min(self.config.health_check_interval);
+            match rx.recv_timeout(timeout) {
+                Ok(message) => {
+                    let result: RemoteExecutionResult =
+                        bcs::from_bytes(&message.to_bytes()).unwrap();
+                    if result.request_id == request_id {
+                        return Some(result.inner);
+                    }
+                    // The result of a block request the coordinator already gave up on.
+                    warn!(
+                        "Discarding result of block request {} from shard {}",
+                        result.request_id, shard_id
+                    );
+                },
+                Err(RecvTimeoutError::Timeout) => {
+                    if Instant::now() >= deadline || !self.health_checker.is_healthy(shard_id) {
+                        return None;
+                    }
+                },
+                Err(RecvTimeoutError::Disconnected) => return None,
+            }
         }
-        Ok(results)
+    }
+
+    /// Executes the block on local executor shards, for when the remote shards can't. The whole
+    /// block is executed, as the sub-blocks of a failed shard depend on cross shard messages from
+    /// the other shards. Execution being deterministic, the output is identical to the output of
+    /// the remote shards.
+    fn execute_block_locally(
+        &self,
+        state_view: Arc<S>,
+        transactions: PartitionedTransactions,
+        concurrency_level_per_shard: usize,
+        onchain_config: BlockExecutorConfigFromOnchain,
+    ) -> Result<ShardedExecutionOutput, VMStatus> {
+        let local_client = self.local_fallback_client.get_or_init(|| {
+            LocalExecutorService::setup_local_executor_shards(self.num_shards(), None)
+        });
+        local_client.execute_block(
+            state_view,
+            transactions,
+            concurrency_level_per_shard,
+            onchain_config,
+        )
     }
 }
 
@@ -185,34 +304,94 @@ impl<S: StateView + Sync + Send + 'static> ExecutorClient<S> for RemoteExecutorC
         concurrency_level_per_shard: usize,
         onchain_config: BlockExecutorConfigFromOnchain,
     ) -> Result<ShardedExecutionOutput, VMStatus> {
+        let unhealthy_shards = (0..self.num_shards())
+            .filter(|shard_id| !self.health_checker.is_healthy(*shard_id))
+            .collect::<Vec<_>>();
+        if !unhealthy_shards.is_empty() {
+            warn!(
+                "RemoteExecutorClient executing block locally, unhealthy shards: {:?}",
+                unhealthy_shards
+            );
+            REMOTE_EXECUTOR_FALLBACK_COUNT
+                .with_label_values(&["unhealthy_shard"])
+                .inc();
+            return self.execute_block_locally(
+                state_view,
+                transactions,
+                concurrency_level_per_shard,
+                onchain_config,
+            );
+        }
+
         trace!("RemoteExecutorClient Sending block to shards");
-        self.state_view_service.set_state_view(state_view);
+        let request_id = self.next_request_id.fetch_add(1, Ordering::Relaxed);
+        let deadline = Instant::now() + self.config.execution_timeout;
+        self.state_view_service
+            .set_state_view(request_id, state_view.clone());
         let (sub_blocks, global_txns) = transactions.into();
         if !global_txns.is_empty() {
             panic!("Global transactions are not supported yet");
         }
-        for (shard_id, sub_blocks) in sub_blocks.into_iter().enumerate() {
-            let senders = self.command_txs.clone();
-            let execution_request = RemoteExecutionRequest::ExecuteBlock(ExecuteBlockCommand {
-                sub_blocks,
-                concurrency_level: concurrency_level_per_shard,
-                onchain_config: onchain_config.clone(),
-            });
-
-            senders[shard_id]
-                .lock()
-                .unwrap()
-                .send(Message::new(bcs::to_bytes(&execution_request).unwrap()))
-                .unwrap();
-        }
+        // The sub-blocks are kept around, in case the block has to be executed locally.
+        let sub_blocks = sub_blocks
+            .into_iter()
+            .enumerate()
+            .map(|(shard_id, sub_blocks)| {
+                let senders = self.command_txs.clone();
+                let execution_request = RemoteExecutionRequest::ExecuteBlock(ExecuteBlockCommand {
+                    request_id,
+                    sub_blocks,
+                    concurrency_level: concurrency_level_per_shard,
+                    onchain_config: onchain_config.clone(),
+                });
 
-        let execution_results = self.get_output_from_shards()?;
+                senders[shard_id]
+                    .lock()
+                    .unwrap()
+                    .send(Message::new(bcs::to_bytes(&execution_request).unwrap()))
+                    .unwrap();
+                let RemoteExecutionRequest::ExecuteBlock(command) = execution_request;
+                command.sub_blocks
+            })
+            .collect::<Vec<_>>();
 
+        let execution_results = self.get_output_from_shards(request_id, deadline);
         self.state_view_service.drop_state_view();
+
+        let failed_shards = execution_results
+            .iter()
+            .enumerate()
+            .filter(|(_, result)| result.is_none())
+            .map(|(shard_id, _)| shard_id)
+            .collect::<Vec<_>>();
+        if !failed_shards.is_empty() {
+            for shard_id in failed_shards.iter() {
+                self.health_checker.mark_failed(*shard_id, request_id);
+            }
+            warn!(
+                "RemoteExecutorClient executing block locally, failed shards: {:?}",
+                failed_shards
+            );
+            REMOTE_EXECUTOR_FALLBACK_COUNT
+                .with_label_values(&["shard_failure"])
+                .inc();
+            return self.execute_block_locally(
+                state_view,
+                PartitionedTransactions::new(sub_blocks, global_txns),
+                concurrency_level_per_shard,
+                onchain_config,
+            );
+        }
+
+        let execution_results = execution_results
+            .into_iter()
+            .map(|result| result.expect("Results of failed shards are handled above"))
+            .collect::<Result<Vec<_>, _>>()?;
         Ok(ShardedExecutionOutput::new(execution_results, vec![]))
     }
 
     fn shutdown(&mut self) {
+        self.health_checker.stop();
         self.network_controller.shutdown();
     }
 }
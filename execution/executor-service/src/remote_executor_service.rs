@@ -2,8 +2,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    remote_cordinator_client::RemoteCoordinatorClient,
-    remote_cross_shard_client::RemoteCrossShardClient, remote_state_view::RemoteStateViewClient,
+    health_check::HealthCheckResponder,
+    remote_cordinator_client::{RemoteCoordinatorClient, RequestStatus},
+    remote_cross_shard_client::RemoteCrossShardClient,
+    remote_state_view::RemoteStateViewClient,
 };
 use aptos_secure_net::network_controller::NetworkController;
 use aptos_types::block_executor::partitioner::ShardId;
@@ -16,6 +18,8 @@ pub struct ExecutorService {
     shard_id: ShardId,
     controller: NetworkController,
     executor_service: Arc<ShardedExecutorService<RemoteStateViewClient>>,
+    health_check_responder: Arc<HealthCheckResponder>,
+    request_status: Arc<RequestStatus>,
 }
 
 impl ExecutorService {
@@ -34,9 +38,17 @@ impl ExecutorService {
             &mut controller,
             coordinator_address,
         ));
+        let request_status = coordinator_client.request_status();
+        let health_check_responder = Arc::new(HealthCheckResponder::new(
+            shard_id,
+            &mut controller,
+            coordinator_address,
+            request_status.clone(),
+        ));
         let cross_shard_client = Arc::new(RemoteCrossShardClient::new(
             &mut controller,
             remote_shard_addresses,
+            request_status.clone(),
         ));
 
         let executor_service = Arc::new(ShardedExecutorService::new(
@@ -51,9 +63,16 @@ impl ExecutorService {
             shard_id,
             controller,
             executor_service,
+            health_check_responder,
+            request_status,
         }
     }
 
+    /// The block requests of the shard.
+    pub fn request_status(&self) -> Arc<RequestStatus> {
+        self.request_status.clone()
+    }
+
     pub fn start(&mut self) {
         self.controller.start();
         let thread_name = format!("ExecutorService-{}", self.shard_id);
@@ -64,6 +83,16 @@ impl ExecutorService {
                 executor_service_clone.start();
             })
             .expect("Failed to spawn thread");
+
+        // Health checks are served on their own thread, so that they are answered while the shard
+        // is executing a block.
+        let health_check_responder = self.health_check_responder.clone();
+        thread::Builder::new()
+            .name(format!("HealthCheckResponder-{}", self.shard_id))
+            .spawn(move || {
+                health_check_responder.start();
+            })
+            .expect("Failed to spawn thread");
     }
 
     pub fn shutdown(&mut self) {
@@ -13,30 +13,47 @@ use std::{
 
 extern crate itertools;
 use crate::metrics::{REMOTE_EXECUTOR_REMOTE_KV_COUNT, REMOTE_EXECUTOR_TIMER};
-use aptos_logger::trace;
+use aptos_logger::{trace, warn};
 use aptos_types::{
     block_executor::partitioner::ShardId,
     state_store::{
-        state_storage_usage::StateStorageUsage, state_value::StateValue, StateViewResult,
-        TStateView,
+        errors::StateViewError, state_storage_usage::StateStorageUsage, state_value::StateValue,
+        StateViewResult, TStateView,
     },
 };
 use dashmap::DashMap;
+use once_cell::sync::OnceCell;
 use rayon::ThreadPool;
 
 pub static REMOTE_STATE_KEY_BATCH_SIZE: usize = 200;
 
 pub struct RemoteStateView {
+    // The block request the state values are read for.
+    request_id: u64,
     state_values: DashMap<StateKey, RemoteStateValue>,
+    // Set if the coordinator failed to read state values, which then fail all the reads.
+    error: OnceCell<String>,
 }
 
 impl RemoteStateView {
-    pub fn new() -> Self {
+    pub fn new(request_id: u64) -> Self {
         Self {
+            request_id,
             state_values: DashMap::new(),
+            error: OnceCell::new(),
         }
     }
 
+    pub fn request_id(&self) -> u64 {
+        self.request_id
+    }
+
+    /// Records the error the coordinator failed to read state values with. Must be called before
+    /// the values are set, so that the reads waiting for them fail.
+    pub fn set_error(&self, error: String) {
+        self.error.get_or_init(|| error);
+    }
+
     pub fn has_state_key(&self, state_key: &StateKey) -> bool {
         self.state_values.contains_key(state_key)
     }
@@ -61,7 +78,11 @@ impl RemoteStateView {
             // case we explicitly drop the value to relinquish the read lock on the value. Cloning the
             // value should be in expensive as this is just cloning the underlying Arc.
             drop(value);
-            return Ok(value_clone.get_value());
+            let state_value = value_clone.get_value();
+            if let Some(error) = self.error.get() {
+                return Err(StateViewError::Other(error.clone()));
+            }
+            return Ok(state_value);
         }
         Ok(None)
     }
@@ -93,7 +114,8 @@ impl RemoteStateViewClient {
         let result_rx = controller.create_inbound_channel(kv_response_type.to_string());
         let command_tx =
             controller.create_outbound_channel(coordinator_address, kv_request_type.to_string());
-        let state_view = Arc::new(RwLock::new(RemoteStateView::new()));
+        // Replaced by the state view of every block request before it is executed.
+        let state_view = Arc::new(RwLock::new(RemoteStateView::new(0)));
         let state_value_receiver = RemoteStateValueReceiver::new(
             shard_id,
             state_view.clone(),
@@ -115,8 +137,8 @@ impl RemoteStateViewClient {
         }
     }
 
-    pub fn init_for_block(&self, state_keys: Vec<StateKey>) {
-        *self.state_view.write().unwrap() = RemoteStateView::new();
+    pub fn init_for_block(&self, request_id: u64, state_keys: Vec<StateKey>) {
+        *self.state_view.write().unwrap() = RemoteStateView::new(request_id);
         REMOTE_EXECUTOR_REMOTE_KV_COUNT
             .with_label_values(&[&self.shard_id.to_string(), "prefetch_kv"])
             .inc_by(state_keys.len() as u64);
@@ -130,6 +152,7 @@ impl RemoteStateViewClient {
         shard_id: ShardId,
         state_keys: Vec<StateKey>,
     ) {
+        let request_id = state_view_clone.read().unwrap().request_id();
         state_keys.clone().into_iter().for_each(|state_key| {
             state_view_clone.read().unwrap().insert_state_key(state_key);
         });
@@ -139,7 +162,7 @@ impl RemoteStateViewClient {
             .for_each(|state_keys| {
                 let sender = kv_tx.clone();
                 thread_pool.spawn(move || {
-                    Self::send_state_value_request(shard_id, sender, state_keys);
+                    Self::send_state_value_request(shard_id, request_id, sender, state_keys);
                 });
             });
     }
@@ -171,10 +194,11 @@ impl RemoteStateViewClient {
 
     fn send_state_value_request(
         shard_id: ShardId,
+        request_id: u64,
         sender: Arc<Sender<Message>>,
         state_keys: Vec<StateKey>,
     ) {
-        let request = RemoteKVRequest::new(shard_id, state_keys);
+        let request = RemoteKVRequest::new(shard_id, request_id, state_keys);
         let request_message = bcs::to_bytes(&request).unwrap();
         sender.send(Message::new(request_message)).unwrap();
     }
@@ -258,11 +282,26 @@ impl RemoteStateValueReceiver {
             .with_label_values(&[&shard_id.to_string(), "kv_responses"])
             .inc();
         let state_view_lock = state_view.read().unwrap();
+        if response.request_id != state_view_lock.request_id() {
+            trace!(
+                "Dropping state values of abandoned block request {} for shard {}",
+                response.request_id,
+                shard_id
+            );
+            return;
+        }
         trace!(
             "Received state values for shard {} with size {}",
             shard_id,
             response.inner.len()
         );
+        if let Some(error) = response.error {
+            warn!(
+                "Failed to read state values for shard {}: {}",
+                shard_id, error
+            );
+            state_view_lock.set_error(error);
+        }
         response
             .inner
             .into_iter()
@@ -10,15 +10,16 @@ use std::{
 
 extern crate itertools;
 use crate::metrics::REMOTE_EXECUTOR_TIMER;
-use aptos_logger::trace;
-use aptos_types::state_store::{StateView, TStateView};
+use aptos_logger::{trace, warn};
+use aptos_types::state_store::{state_key::StateKey, StateView, TStateView};
 use itertools::Itertools;
 
 pub struct RemoteStateViewService<S: StateView + Sync + Send + 'static> {
     kv_rx: Receiver<Message>,
     kv_tx: Arc<Vec<Sender<Message>>>,
     thread_pool: Arc<rayon::ThreadPool>,
-    state_view: Arc<RwLock<Option<Arc<S>>>>,
+    // The state view of the block request being executed, with its id.
+    state_view: Arc<RwLock<Option<(u64, Arc<S>)>>>,
 }
 
 impl<S: StateView + Sync + Send + 'static> RemoteStateViewService<S> {
@@ -51,9 +52,9 @@ impl<S: StateView + Sync + Send + 'static> RemoteStateViewService<S> {
         }
     }
 
-    pub fn set_state_view(&self, state_view: Arc<S>) {
+    pub fn set_state_view(&self, request_id: u64, state_view: Arc<S>) {
         let mut state_view_lock = self.state_view.write().unwrap();
-        *state_view_lock = Some(state_view);
+        *state_view_lock = Some((request_id, state_view));
     }
 
     pub fn drop_state_view(&self) {
@@ -73,7 +74,7 @@ impl<S: StateView + Sync + Send + 'static> RemoteStateViewService<S> {
 
     pub fn handle_message(
         message: Message,
-        state_view: Arc<RwLock<Option<Arc<S>>>>,
+        state_view: Arc<RwLock<Option<(u64, Arc<S>)>>>,
         kv_tx: Arc<Vec<Sender<Message>>>,
     ) {
         // we don't know the shard id until we deserialize the message, so lets default it to 0
@@ -86,27 +87,33 @@ impl<S: StateView + Sync + Send + 'static> RemoteStateViewService<S> {
         let req: RemoteKVRequest = bcs::from_bytes(&message.data).unwrap();
         drop(bcs_deser_timer);
 
-        let (shard_id, state_keys) = req.into();
+        let (shard_id, request_id, state_keys) = req.into();
         trace!(
             "remote state view service - received request for shard {} with {} keys",
             shard_id,
             state_keys.len()
         );
-        let resp = state_keys
-            .into_iter()
-            .map(|state_key| {
-                let state_value = state_view
-                    .read()
-                    .unwrap()
-                    .as_ref()
-                    .unwrap()
-                    .get_state_value(&state_key)
-                    .unwrap();
-                (state_key, state_value)
-            })
-            .collect_vec();
-        let len = resp.len();
-        let resp = RemoteKVResponse::new(resp);
+        let len = state_keys.len();
+        let current_state_view = state_view.read().unwrap().clone();
+        let resp = match current_state_view {
+            Some((state_view_request_id, state_view)) if state_view_request_id == request_id => {
+                Self::read_state_values(request_id, state_view.as_ref(), state_keys)
+            },
+            // A shard may still be executing a block the coordinator gave up on, in which case
+            // there is no state view to serve its requests from anymore. The shard is answered
+            // with an error, so that it does not wait for the state values forever.
+            _ => {
+                warn!(
+                    "remote state view service - no state view for block request {} of shard {}",
+                    request_id, shard_id
+                );
+                RemoteKVResponse::new_error(
+                    request_id,
+                    state_keys,
+                    format!("Block request {} was abandoned", request_id),
+                )
+            },
+        };
         let bcs_ser_timer = REMOTE_EXECUTOR_TIMER
             .with_label_values(&["0", "kv_resp_ser"])
             .start_timer();
@@ -120,4 +127,22 @@ impl<S: StateView + Sync + Send + 'static> RemoteStateViewService<S> {
         let message = Message::new(resp);
         kv_tx[shard_id].send(message).unwrap();
     }
+
+    fn read_state_values(
+        request_id: u64,
+        state_view: &S,
+        state_keys: Vec<StateKey>,
+    ) -> RemoteKVResponse {
+        let state_values: Result<Vec<_>, _> = state_keys
+            .iter()
+            .map(|state_key| state_view.get_state_value(state_key))
+            .collect();
+        match state_values {
+            Ok(state_values) => RemoteKVResponse::new(
+                request_id,
+                state_keys.into_iter().zip(state_values).collect(),
+            ),
+            Err(error) => RemoteKVResponse::new_error(request_id, state_keys, error.to_string()),
+        }
+    }
 }
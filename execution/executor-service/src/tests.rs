@@ -2,14 +2,25 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    remote_executor_client::RemoteExecutorClient, test_utils,
+    health_check::HealthCheckResponder,
+    metrics::REMOTE_EXECUTOR_FALLBACK_COUNT,
+    remote_cordinator_client::RequestStatus,
+    remote_executor_client::{RemoteExecutorClient, RemoteExecutorConfig},
+    test_utils,
     thread_executor_service::ThreadExecutorService,
 };
 use aptos_config::utils;
 use aptos_language_e2e_tests::data_store::FakeDataStore;
-use aptos_secure_net::network_controller::NetworkController;
+use aptos_secure_net::network_controller::{Message, NetworkController};
+use aptos_types::block_executor::partitioner::ShardId;
 use aptos_vm::sharded_block_executor::ShardedBlockExecutor;
-use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use crossbeam_channel::Receiver;
+use std::{
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    sync::Arc,
+    thread,
+    time::{Duration, Instant},
+};
 
 pub fn create_thread_remote_executor_shards(
     num_shards: usize,
@@ -17,6 +28,73 @@ pub fn create_thread_remote_executor_shards(
 ) -> (
     RemoteExecutorClient<FakeDataStore>,
     Vec<ThreadExecutorService>,
+) {
+    create_thread_remote_executor_shards_with_config(
+        num_shards,
+        num_threads,
+        RemoteExecutorConfig::default(),
+    )
+}
+
+pub fn create_thread_remote_executor_shards_with_config(
+    num_shards: usize,
+    num_threads: Option<usize>,
+    config: RemoteExecutorConfig,
+) -> (
+    RemoteExecutorClient<FakeDataStore>,
+    Vec<ThreadExecutorService>,
+) {
+    let (remote_executor_client, remote_executor_services, _) =
+        create_remote_executor_shards(num_shards, num_threads, config, None);
+    (remote_executor_client, remote_executor_services)
+}
+
+/// A shard that responds to the health checks of the coordinator, but never executes the blocks
+/// it is sent, as if it were stuck.
+struct UnresponsiveExecutorService {
+    controller: NetworkController,
+    // Holds the execute commands sent to the shard, which are never executed.
+    _command_rx: Receiver<Message>,
+}
+
+impl UnresponsiveExecutorService {
+    fn new(shard_id: ShardId, self_address: SocketAddr, coordinator_address: SocketAddr) -> Self {
+        let mut controller = NetworkController::new(
+            format!("unresponsive_executor_service-{}", shard_id),
+            self_address,
+            5000,
+        );
+        let command_rx = controller.create_inbound_channel(format!("execute_command_{}", shard_id));
+        let health_check_responder = HealthCheckResponder::new(
+            shard_id,
+            &mut controller,
+            coordinator_address,
+            Arc::new(RequestStatus::default()),
+        );
+        controller.start();
+        thread::spawn(move || health_check_responder.start());
+        Self {
+            controller,
+            _command_rx: command_rx,
+        }
+    }
+
+    fn shutdown(&mut self) {
+        self.controller.shutdown();
+    }
+}
+
+/// Creates the coordinator and the shards, with the given shard (if any) being unresponsive. The
+/// returned thread executor services are the ones of the other shards.
+fn create_remote_executor_shards(
+    num_shards: usize,
+    num_threads: Option<usize>,
+    config: RemoteExecutorConfig,
+    unresponsive_shard_id: Option<ShardId>,
+) -> (
+    RemoteExecutorClient<FakeDataStore>,
+    Vec<ThreadExecutorService>,
+    Option<UnresponsiveExecutorService>,
 ) {
     // First create the coordinator.
     let listen_port = utils::get_available_port();
@@ -36,7 +114,15 @@ pub fn create_thread_remote_executor_shards(
     let num_threads =
         num_threads.unwrap_or_else(|| (num_cpus::get() as f64 / num_shards as f64).ceil() as usize);
 
+    let unresponsive_executor_service = unresponsive_shard_id.map(|shard_id| {
+        UnresponsiveExecutorService::new(
+            shard_id,
+            remote_shard_addresses[shard_id],
+            coordinator_address,
+        )
+    });
     let remote_executor_services = (0..num_shards)
+        .filter(|shard_id| Some(*shard_id) != unresponsive_shard_id)
         .map(|shard_id| {
             ThreadExecutorService::new(
                 shard_id,
@@ -49,14 +135,23 @@ pub fn create_thread_remote_executor_shards(
         .collect::<Vec<_>>();
 
     let remote_executor_client =
-        RemoteExecutorClient::new(remote_shard_addresses, controller, None);
-    (remote_executor_client, remote_executor_services)
+        RemoteExecutorClient::new_with_config(remote_shard_addresses, controller, None, config);
+    (
+        remote_executor_client,
+        remote_executor_services,
+        unresponsive_executor_service,
+    )
+}
+
+fn failure_test_config() -> RemoteExecutorConfig {
+    RemoteExecutorConfig {
+        execution_timeout: Duration::from_secs(2),
+        health_check_interval: Duration::from_millis(100),
+    }
 }
 
 #[test]
 fn test_sharded_block_executor_no_conflict() {
-    use std::thread;
-
     let num_shards = 8;
     let (executor_client, mut executor_services) =
         create_thread_remote_executor_shards(num_shards, Some(2));
@@ -75,8 +170,6 @@ fn test_sharded_block_executor_no_conflict() {
 
 #[test]
 fn test_sharded_block_executor_with_conflict() {
-    use std::thread;
-
     let num_shards = 8;
     let (executor_client, mut executor_services) =
         create_thread_remote_executor_shards(num_shards, Some(2));
@@ -92,3 +185,110 @@ fn test_sharded_block_executor_with_conflict() {
         executor_service.shutdown();
     });
 }
+
+#[test]
+fn test_sharded_block_executor_with_unresponsive_shard() {
+    let num_shards = 4;
+    let (executor_client, mut executor_services, unresponsive_executor_service) =
+        create_remote_executor_shards(num_shards, Some(2), failure_test_config(), Some(0));
+    let mut unresponsive_executor_service = unresponsive_executor_service.unwrap();
+    let sharded_block_executor = ShardedBlockExecutor::new(executor_client);
+
+    // wait for the servers to be ready before sending messages
+    thread::sleep(std::time::Duration::from_millis(10));
+
+    // The unresponsive shard never returns its result, the block must then be executed locally.
+    let fallback_count = REMOTE_EXECUTOR_FALLBACK_COUNT.with_label_values(&["shard_failure"]);
+    let num_fallbacks = fallback_count.get();
+    test_utils::sharded_block_executor_with_conflict(sharded_block_executor, 2);
+    assert!(fallback_count.get() > num_fallbacks);
+
+    // The other shards must give up on the abandoned block, instead of waiting for the cross
+    // shard messages of the unresponsive shard forever.
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while executor_services.iter().any(|executor_service| {
+        executor_service
+            .request_status()
+            .executing_request_id()
+            .is_some()
+    }) {
+        assert!(
+            Instant::now() < deadline,
+            "Shards are still executing the abandoned block"
+        );
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    unresponsive_executor_service.shutdown();
+    executor_services.iter_mut().for_each(|executor_service| {
+        executor_service.shutdown();
+    });
+}
+
+#[test]
+fn test_sharded_block_executor_with_shard_killed_before_block() {
+    let num_shards = 4;
+    let config = failure_test_config();
+    let (executor_client, mut executor_services) =
+        create_thread_remote_executor_shards_with_config(num_shards, Some(2), config.clone());
+    let sharded_block_executor = ShardedBlockExecutor::new(executor_client);
+
+    // Kill a shard and wait for it to miss its health checks, the block must then be executed
+    // locally without being sent to the shards.
+    executor_services[num_shards - 1].shutdown();
+    thread::sleep(config.health_check_interval * 5);
+
+    let fallback_count = REMOTE_EXECUTOR_FALLBACK_COUNT.with_label_values(&["unhealthy_shard"]);
+    let num_fallbacks = fallback_count.get();
+    test_utils::sharded_block_executor_with_conflict(sharded_block_executor, 2);
+    assert!(fallback_count.get() > num_fallbacks);
+
+    executor_services[..num_shards - 1]
+        .iter_mut()
+        .for_each(|executor_service| {
+            executor_service.shutdown();
+        });
+}
+
+#[test]
+fn test_sharded_block_executor_with_shard_killed_during_block() {
+    let num_shards = 4;
+    let (executor_client, mut executor_services) = create_thread_remote_executor_shards_with_config(
+        num_shards,
+        Some(2),
+        failure_test_config(),
+    );
+    let sharded_block_executor = ShardedBlockExecutor::new(executor_client);
+
+    // wait for the servers to be ready before sending messages
+    thread::sleep(std::time::Duration::from_millis(10));
+
+    // Kill a shard as soon as it starts executing the block, the block must then be executed
+    // locally and its output must match the sequential execution.
+    let mut killed_executor_service = executor_services.pop().unwrap();
+    let killer = thread::spawn(move || {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while killed_executor_service
+            .request_status()
+            .executing_request_id()
+            .is_none()
+        {
+            assert!(
+                Instant::now() < deadline,
+                "Shard never started executing the block"
+            );
+            thread::sleep(Duration::from_millis(1));
+        }
+        killed_executor_service.shutdown();
+    });
+
+    let fallback_count = REMOTE_EXECUTOR_FALLBACK_COUNT.with_label_values(&["shard_failure"]);
+    let num_fallbacks = fallback_count.get();
+    test_utils::sharded_block_executor_with_conflict(sharded_block_executor, 2);
+    assert!(fallback_count.get() > num_fallbacks);
+    killer.join().unwrap();
+
+    executor_services.iter_mut().for_each(|executor_service| {
+        executor_service.shutdown();
+    });
+}
@@ -1,8 +1,8 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
-use crate::remote_executor_service::ExecutorService;
+use crate::{remote_cordinator_client::RequestStatus, remote_executor_service::ExecutorService};
 use aptos_types::block_executor::partitioner::ShardId;
-use std::net::SocketAddr;
+use std::{net::SocketAddr, sync::Arc};
 
 /// This is a simple implementation of RemoteExecutorService that runs the executor service in a
 /// separate thread. This should be used for testing only.
@@ -35,6 +35,10 @@ impl ThreadExecutorService {
         }
     }
 
+    pub fn request_status(&self) -> Arc<RequestStatus> {
+        self.executor_service.request_status()
+    }
+
     pub fn shutdown(&mut self) {
         self.executor_service.shutdown()
     }
@@ -28,18 +28,30 @@ class TransactionsInStorage(_message.Message):
     ) -> None: ...
 
 class GetTransactionsRequest(_message.Message):
-    __slots__ = ["starting_version", "transactions_count", "batch_size"]
+    __slots__ = [
+        "starting_version",
+        "transactions_count",
+        "batch_size",
+        "transaction_filter",
+        "field_mask",
+    ]
     STARTING_VERSION_FIELD_NUMBER: _ClassVar[int]
     TRANSACTIONS_COUNT_FIELD_NUMBER: _ClassVar[int]
     BATCH_SIZE_FIELD_NUMBER: _ClassVar[int]
+    TRANSACTION_FILTER_FIELD_NUMBER: _ClassVar[int]
+    FIELD_MASK_FIELD_NUMBER: _ClassVar[int]
     starting_version: int
     transactions_count: int
     batch_size: int
+    transaction_filter: str
+    field_mask: TransactionFieldMask
     def __init__(
         self,
         starting_version: _Optional[int] = ...,
         transactions_count: _Optional[int] = ...,
         batch_size: _Optional[int] = ...,
+        transaction_filter: _Optional[str] = ...,
+        field_mask: _Optional[_Union[TransactionFieldMask, _Mapping]] = ...,
     ) -> None: ...
 
 class TransactionsResponse(_message.Message):
@@ -57,3 +69,34 @@ class TransactionsResponse(_message.Message):
         ] = ...,
         chain_id: _Optional[int] = ...,
     ) -> None: ...
+
+class TransactionFieldMask(_message.Message):
+    __slots__ = [
+        "include_payload",
+        "include_signature",
+        "include_events",
+        "event_types",
+        "include_changes",
+        "change_types",
+    ]
+    INCLUDE_PAYLOAD_FIELD_NUMBER: _ClassVar[int]
+    INCLUDE_SIGNATURE_FIELD_NUMBER: _ClassVar[int]
+    INCLUDE_EVENTS_FIELD_NUMBER: _ClassVar[int]
+    EVENT_TYPES_FIELD_NUMBER: _ClassVar[int]
+    INCLUDE_CHANGES_FIELD_NUMBER: _ClassVar[int]
+    CHANGE_TYPES_FIELD_NUMBER: _ClassVar[int]
+    include_payload: bool
+    include_signature: bool
+    include_events: bool
+    event_types: _containers.RepeatedScalarFieldContainer[str]
+    include_changes: bool
+    change_types: _containers.RepeatedScalarFieldContainer[str]
+    def __init__(
+        self,
+        include_payload: bool = ...,
+        include_signature: bool = ...,
+        include_events: bool = ...,
+        event_types: _Optional[_Iterable[str]] = ...,
+        include_changes: bool = ...,
+        change_types: _Optional[_Iterable[str]] = ...,
+    ) -> None: ...
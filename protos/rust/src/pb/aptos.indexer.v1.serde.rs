@@ -19,6 +19,12 @@ impl serde::Serialize for GetTransactionsRequest {
         if self.batch_size.is_some() {
             len += 1;
         }
+        if self.transaction_filter.is_some() {
+            len += 1;
+        }
+        if self.field_mask.is_some() {
+            len += 1;
+        }
         let mut struct_ser = serializer.serialize_struct("aptos.indexer.v1.GetTransactionsRequest", len)?;
         if let Some(v) = self.starting_version.as_ref() {
             struct_ser.serialize_field("startingVersion", ToString::to_string(&v).as_str())?;
@@ -29,6 +35,12 @@ impl serde::Serialize for GetTransactionsRequest {
         if let Some(v) = self.batch_size.as_ref() {
             struct_ser.serialize_field("batchSize", ToString::to_string(&v).as_str())?;
         }
+        if let Some(v) = self.transaction_filter.as_ref() {
+            struct_ser.serialize_field("transactionFilter", v)?;
+        }
+        if let Some(v) = self.field_mask.as_ref() {
+            struct_ser.serialize_field("fieldMask", v)?;
+        }
         struct_ser.end()
     }
 }
@@ -45,6 +57,10 @@ impl<'de> serde::Deserialize<'de> for GetTransactionsRequest {
             "transactionsCount",
             "batch_size",
             "batchSize",
+            "transaction_filter",
+            "transactionFilter",
+            "field_mask",
+            "fieldMask",
         ];
 
         #[allow(clippy::enum_variant_names)]
@@ -52,6 +68,8 @@ impl<'de> serde::Deserialize<'de> for GetTransactionsRequest {
             StartingVersion,
             TransactionsCount,
             BatchSize,
+            TransactionFilter,
+            FieldMask,
         }
         impl<'de> serde::Deserialize<'de> for GeneratedField {
             fn deserialize<D>(deserializer: D) -> std::result::Result<GeneratedField, D::Error>
@@ -76,6 +94,8 @@ impl<'de> serde::Deserialize<'de> for GetTransactionsRequest {
                             "startingVersion" | "starting_version" => Ok(GeneratedField::StartingVersion),
                             "transactionsCount" | "transactions_count" => Ok(GeneratedField::TransactionsCount),
                             "batchSize" | "batch_size" => Ok(GeneratedField::BatchSize),
+                            "transactionFilter" | "transaction_filter" => Ok(GeneratedField::TransactionFilter),
+                            "fieldMask" | "field_mask" => Ok(GeneratedField::FieldMask),
                             _ => Err(serde::de::Error::unknown_field(value, FIELDS)),
                         }
                     }
@@ -98,6 +118,8 @@ impl<'de> serde::Deserialize<'de> for GetTransactionsRequest {
                 let mut starting_version__ = None;
                 let mut transactions_count__ = None;
                 let mut batch_size__ = None;
+                let mut transaction_filter__ = None;
+                let mut field_mask__ = None;
                 while let Some(k) = map.next_key()? {
                     match k {
                         GeneratedField::StartingVersion => {
@@ -124,18 +146,214 @@ impl<'de> serde::Deserialize<'de> for GetTransactionsRequest {
                                 map.next_value::<::std::option::Option<::pbjson::private::NumberDeserialize<_>>>()?.map(|x| x.0)
                             ;
                         }
+                        GeneratedField::TransactionFilter => {
+                            if transaction_filter__.is_some() {
+                                return Err(serde::de::Error::duplicate_field("transactionFilter"));
+                            }
+                            transaction_filter__ = map.next_value()?;
+                        }
+                        GeneratedField::FieldMask => {
+                            if field_mask__.is_some() {
+                                return Err(serde::de::Error::duplicate_field("fieldMask"));
+                            }
+                            field_mask__ = map.next_value()?;
+                        }
                     }
                 }
                 Ok(GetTransactionsRequest {
                     starting_version: starting_version__,
                     transactions_count: transactions_count__,
                     batch_size: batch_size__,
+                    transaction_filter: transaction_filter__,
+                    field_mask: field_mask__,
                 })
             }
         }
         deserializer.deserialize_struct("aptos.indexer.v1.GetTransactionsRequest", FIELDS, GeneratedVisitor)
     }
 }
+impl serde::Serialize for TransactionFieldMask {
+    #[allow(deprecated)]
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::SerializeStruct;
+        let mut len = 0;
+        if self.include_payload {
+            len += 1;
+        }
+        if self.include_signature {
+            len += 1;
+        }
+        if self.include_events {
+            len += 1;
+        }
+        if !self.event_types.is_empty() {
+            len += 1;
+        }
+        if self.include_changes {
+            len += 1;
+        }
+        if !self.change_types.is_empty() {
+            len += 1;
+        }
+        let mut struct_ser = serializer.serialize_struct("aptos.indexer.v1.TransactionFieldMask", len)?;
+        if self.include_payload {
+            struct_ser.serialize_field("includePayload", &self.include_payload)?;
+        }
+        if self.include_signature {
+            struct_ser.serialize_field("includeSignature", &self.include_signature)?;
+        }
+        if self.include_events {
+            struct_ser.serialize_field("includeEvents", &self.include_events)?;
+        }
+        if !self.event_types.is_empty() {
+            struct_ser.serialize_field("eventTypes", &self.event_types)?;
+        }
+        if self.include_changes {
+            struct_ser.serialize_field("includeChanges", &self.include_changes)?;
+        }
+        if !self.change_types.is_empty() {
+            struct_ser.serialize_field("changeTypes", &self.change_types)?;
+        }
+        struct_ser.end()
+    }
+}
+impl<'de> serde::Deserialize<'de> for TransactionFieldMask {
+    #[allow(deprecated)]
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        const FIELDS: &[&str] = &[
+            "include_payload",
+            "includePayload",
+            "include_signature",
+            "includeSignature",
+            "include_events",
+            "includeEvents",
+            "event_types",
+            "eventTypes",
+            "include_changes",
+            "includeChanges",
+            "change_types",
+            "changeTypes",
+        ];
+
+        #[allow(clippy::enum_variant_names)]
+        enum GeneratedField {
+            IncludePayload,
+            IncludeSignature,
+            IncludeEvents,
+            EventTypes,
+            IncludeChanges,
+            ChangeTypes,
+        }
+        impl<'de> serde::Deserialize<'de> for GeneratedField {
+            fn deserialize<D>(deserializer: D) -> std::result::Result<GeneratedField, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                struct GeneratedVisitor;
+
+                impl<'de> serde::de::Visitor<'de> for GeneratedVisitor {
+                    type Value = GeneratedField;
+
+                    fn expecting(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                        write!(formatter, "expected one of: {:?}", &FIELDS)
+                    }
+
+                    #[allow(unused_variables)]
+                    fn visit_str<E>(self, value: &str) -> std::result::Result<GeneratedField, E>
+                    where
+                        E: serde::de::Error,
+                    {
+                        match value {
+                            "includePayload" | "include_payload" => Ok(GeneratedField::IncludePayload),
+                            "includeSignature" | "include_signature" => Ok(GeneratedField::IncludeSignature),
+                            "includeEvents" | "include_events" => Ok(GeneratedField::IncludeEvents),
+                            "eventTypes" | "event_types" => Ok(GeneratedField::EventTypes),
+                            "includeChanges" | "include_changes" => Ok(GeneratedField::IncludeChanges),
+                            "changeTypes" | "change_types" => Ok(GeneratedField::ChangeTypes),
+                            _ => Err(serde::de::Error::unknown_field(value, FIELDS)),
+                        }
+                    }
+                }
+                deserializer.deserialize_identifier(GeneratedVisitor)
+            }
+        }
+        struct GeneratedVisitor;
+        impl<'de> serde::de::Visitor<'de> for GeneratedVisitor {
+            type Value = TransactionFieldMask;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                formatter.write_str("struct aptos.indexer.v1.TransactionFieldMask")
+            }
+
+            fn visit_map<V>(self, mut map: V) -> std::result::Result<TransactionFieldMask, V::Error>
+                where
+                    V: serde::de::MapAccess<'de>,
+            {
+                let mut include_payload__ = None;
+                let mut include_signature__ = None;
+                let mut include_events__ = None;
+                let mut event_types__ = None;
+                let mut include_changes__ = None;
+                let mut change_types__ = None;
+                while let Some(k) = map.next_key()? {
+                    match k {
+                        GeneratedField::IncludePayload => {
+                            if include_payload__.is_some() {
+                                return Err(serde::de::Error::duplicate_field("includePayload"));
+                            }
+                            include_payload__ = Some(map.next_value()?);
+                        }
+                        GeneratedField::IncludeSignature => {
+                            if include_signature__.is_some() {
+                                return Err(serde::de::Error::duplicate_field("includeSignature"));
+                            }
+                            include_signature__ = Some(map.next_value()?);
+                        }
+                        GeneratedField::IncludeEvents => {
+                            if include_events__.is_some() {
+                                return Err(serde::de::Error::duplicate_field("includeEvents"));
+                            }
+                            include_events__ = Some(map.next_value()?);
+                        }
+                        GeneratedField::EventTypes => {
+                            if event_types__.is_some() {
+                                return Err(serde::de::Error::duplicate_field("eventTypes"));
+                            }
+                            event_types__ = Some(map.next_value()?);
+                        }
+                        GeneratedField::IncludeChanges => {
+                            if include_changes__.is_some() {
+                                return Err(serde::de::Error::duplicate_field("includeChanges"));
+                            }
+                            include_changes__ = Some(map.next_value()?);
+                        }
+                        GeneratedField::ChangeTypes => {
+                            if change_types__.is_some() {
+                                return Err(serde::de::Error::duplicate_field("changeTypes"));
+                            }
+                            change_types__ = Some(map.next_value()?);
+                        }
+                    }
+                }
+                Ok(TransactionFieldMask {
+                    include_payload: include_payload__.unwrap_or_default(),
+                    include_signature: include_signature__.unwrap_or_default(),
+                    include_events: include_events__.unwrap_or_default(),
+                    event_types: event_types__.unwrap_or_default(),
+                    include_changes: include_changes__.unwrap_or_default(),
+                    change_types: change_types__.unwrap_or_default(),
+                })
+            }
+        }
+        deserializer.deserialize_struct("aptos.indexer.v1.TransactionFieldMask", FIELDS, GeneratedVisitor)
+    }
+}
 impl serde::Serialize for TransactionsInStorage {
     #[allow(deprecated)]
     fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
@@ -38,7 +38,22 @@ export interface GetTransactionsRequest {
    * Optional; number of transactions in each `TransactionsResponse` for current stream.
    * If not present, default to 1000. If larger than 1000, request will be rejected.
    */
-  batchSize?: bigint | undefined;
+  batchSize?:
+    | bigint
+    | undefined;
+  /**
+   * Optional; if set, only transactions that match this filter expression are returned in
+   * full, the others are stripped of their payload, signature, events and changes. For
+   * example: `sender == 0x1 && event.type ~ "0x1::coin::*"`.
+   */
+  transactionFilter?:
+    | string
+    | undefined;
+  /**
+   * Optional; if set, only the parts of each transaction selected by the mask are returned.
+   * If not present, transactions are returned in full.
+   */
+  fieldMask?: TransactionFieldMask | undefined;
 }
 
 /** TransactionsResponse is a batch of transactions. */
@@ -51,6 +66,41 @@ export interface TransactionsResponse {
   chainId?: bigint | undefined;
 }
 
+/**
+ * TransactionFieldMask selects the parts of each transaction to return. Transactions always
+ * include their version, timestamp, info and type, the parts below are only included if selected.
+ */
+export interface TransactionFieldMask {
+  /** Optional; if true, the payload of user transactions is included. */
+  includePayload?:
+    | boolean
+    | undefined;
+  /** Optional; if true, the signature of user transactions is included. */
+  includeSignature?:
+    | boolean
+    | undefined;
+  /** Optional; if true, events are included. */
+  includeEvents?:
+    | boolean
+    | undefined;
+  /**
+   * Optional; if not empty, only events whose type matches one of these patterns are included,
+   * e.g. `0x1::coin::DepositEvent` or `0x1::coin::*`. Only used if `include_events` is true.
+   */
+  eventTypes?:
+    | string[]
+    | undefined;
+  /** Optional; if true, write set changes are included. */
+  includeChanges?:
+    | boolean
+    | undefined;
+  /**
+   * Optional; if not empty, only resource changes whose type matches one of these patterns are
+   * included, e.g. `0x1::coin::CoinStore`. Only used if `include_changes` is true.
+   */
+  changeTypes?: string[] | undefined;
+}
+
 function createBaseTransactionsInStorage(): TransactionsInStorage {
   return { transactions: [], startingVersion: undefined };
 }
@@ -167,7 +217,13 @@ export const TransactionsInStorage = {
 };
 
 function createBaseGetTransactionsRequest(): GetTransactionsRequest {
-  return { startingVersion: undefined, transactionsCount: undefined, batchSize: undefined };
+  return {
+    startingVersion: undefined,
+    transactionsCount: undefined,
+    batchSize: undefined,
+    transactionFilter: undefined,
+    fieldMask: undefined,
+  };
 }
 
 export const GetTransactionsRequest = {
@@ -190,6 +246,12 @@ export const GetTransactionsRequest = {
       }
       writer.uint32(24).uint64(message.batchSize.toString());
     }
+    if (message.transactionFilter !== undefined) {
+      writer.uint32(34).string(message.transactionFilter);
+    }
+    if (message.fieldMask !== undefined) {
+      TransactionFieldMask.encode(message.fieldMask, writer.uint32(42).fork()).ldelim();
+    }
     return writer;
   },
 
@@ -221,6 +283,20 @@ export const GetTransactionsRequest = {
 
           message.batchSize = longToBigint(reader.uint64() as Long);
           continue;
+        case 4:
+          if (tag !== 34) {
+            break;
+          }
+
+          message.transactionFilter = reader.string();
+          continue;
+        case 5:
+          if (tag !== 42) {
+            break;
+          }
+
+          message.fieldMask = TransactionFieldMask.decode(reader, reader.uint32());
+          continue;
       }
       if ((tag & 7) === 4 || tag === 0) {
         break;
@@ -269,6 +345,8 @@ export const GetTransactionsRequest = {
       startingVersion: isSet(object.startingVersion) ? BigInt(object.startingVersion) : undefined,
       transactionsCount: isSet(object.transactionsCount) ? BigInt(object.transactionsCount) : undefined,
       batchSize: isSet(object.batchSize) ? BigInt(object.batchSize) : undefined,
+      transactionFilter: isSet(object.transactionFilter) ? globalThis.String(object.transactionFilter) : undefined,
+      fieldMask: isSet(object.fieldMask) ? TransactionFieldMask.fromJSON(object.fieldMask) : undefined,
     };
   },
 
@@ -283,6 +361,12 @@ export const GetTransactionsRequest = {
     if (message.batchSize !== undefined) {
       obj.batchSize = message.batchSize.toString();
     }
+    if (message.transactionFilter !== undefined) {
+      obj.transactionFilter = message.transactionFilter;
+    }
+    if (message.fieldMask !== undefined) {
+      obj.fieldMask = TransactionFieldMask.toJSON(message.fieldMask);
+    }
     return obj;
   },
 
@@ -294,6 +378,10 @@ export const GetTransactionsRequest = {
     message.startingVersion = object.startingVersion ?? undefined;
     message.transactionsCount = object.transactionsCount ?? undefined;
     message.batchSize = object.batchSize ?? undefined;
+    message.transactionFilter = object.transactionFilter ?? undefined;
+    message.fieldMask = (object.fieldMask !== undefined && object.fieldMask !== null)
+      ? TransactionFieldMask.fromPartial(object.fieldMask)
+      : undefined;
     return message;
   },
 };
@@ -413,6 +501,189 @@ export const TransactionsResponse = {
   },
 };
 
+function createBaseTransactionFieldMask(): TransactionFieldMask {
+  return {
+    includePayload: false,
+    includeSignature: false,
+    includeEvents: false,
+    eventTypes: [],
+    includeChanges: false,
+    changeTypes: [],
+  };
+}
+
+export const TransactionFieldMask = {
+  encode(message: TransactionFieldMask, writer: _m0.Writer = _m0.Writer.create()): _m0.Writer {
+    if (message.includePayload === true) {
+      writer.uint32(8).bool(message.includePayload);
+    }
+    if (message.includeSignature === true) {
+      writer.uint32(16).bool(message.includeSignature);
+    }
+    if (message.includeEvents === true) {
+      writer.uint32(24).bool(message.includeEvents);
+    }
+    if (message.eventTypes !== undefined && message.eventTypes.length !== 0) {
+      for (const v of message.eventTypes) {
+        writer.uint32(34).string(v!);
+      }
+    }
+    if (message.includeChanges === true) {
+      writer.uint32(40).bool(message.includeChanges);
+    }
+    if (message.changeTypes !== undefined && message.changeTypes.length !== 0) {
+      for (const v of message.changeTypes) {
+        writer.uint32(50).string(v!);
+      }
+    }
+    return writer;
+  },
+
+  decode(input: _m0.Reader | Uint8Array, length?: number): TransactionFieldMask {
+    const reader = input instanceof _m0.Reader ? input : _m0.Reader.create(input);
+    let end = length === undefined ? reader.len : reader.pos + length;
+    const message = createBaseTransactionFieldMask();
+    while (reader.pos < end) {
+      const tag = reader.uint32();
+      switch (tag >>> 3) {
+        case 1:
+          if (tag !== 8) {
+            break;
+          }
+
+          message.includePayload = reader.bool();
+          continue;
+        case 2:
+          if (tag !== 16) {
+            break;
+          }
+
+          message.includeSignature = reader.bool();
+          continue;
+        case 3:
+          if (tag !== 24) {
+            break;
+          }
+
+          message.includeEvents = reader.bool();
+          continue;
+        case 4:
+          if (tag !== 34) {
+            break;
+          }
+
+          message.eventTypes!.push(reader.string());
+          continue;
+        case 5:
+          if (tag !== 40) {
+            break;
+          }
+
+          message.includeChanges = reader.bool();
+          continue;
+        case 6:
+          if (tag !== 50) {
+            break;
+          }
+
+          message.changeTypes!.push(reader.string());
+          continue;
+      }
+      if ((tag & 7) === 4 || tag === 0) {
+        break;
+      }
+      reader.skipType(tag & 7);
+    }
+    return message;
+  },
+
+  // encodeTransform encodes a source of message objects.
+  // Transform<TransactionFieldMask, Uint8Array>
+  async *encodeTransform(
+    source:
+      | AsyncIterable<TransactionFieldMask | TransactionFieldMask[]>
+      | Iterable<TransactionFieldMask | TransactionFieldMask[]>,
+  ): AsyncIterable<Uint8Array> {
+    for await (const pkt of source) {
+      if (globalThis.Array.isArray(pkt)) {
+        for (const p of (pkt as any)) {
+          yield* [TransactionFieldMask.encode(p).finish()];
+        }
+      } else {
+        yield* [TransactionFieldMask.encode(pkt as any).finish()];
+      }
+    }
+  },
+
+  // decodeTransform decodes a source of encoded messages.
+  // Transform<Uint8Array, TransactionFieldMask>
+  async *decodeTransform(
+    source: AsyncIterable<Uint8Array | Uint8Array[]> | Iterable<Uint8Array | Uint8Array[]>,
+  ): AsyncIterable<TransactionFieldMask> {
+    for await (const pkt of source) {
+      if (globalThis.Array.isArray(pkt)) {
+        for (const p of (pkt as any)) {
+          yield* [TransactionFieldMask.decode(p)];
+        }
+      } else {
+        yield* [TransactionFieldMask.decode(pkt as any)];
+      }
+    }
+  },
+
+  fromJSON(object: any): TransactionFieldMask {
+    return {
+      includePayload: isSet(object.includePayload) ? globalThis.Boolean(object.includePayload) : false,
+      includeSignature: isSet(object.includeSignature) ? globalThis.Boolean(object.includeSignature) : false,
+      includeEvents: isSet(object.includeEvents) ? globalThis.Boolean(object.includeEvents) : false,
+      eventTypes: globalThis.Array.isArray(object?.eventTypes)
+        ? object.eventTypes.map((e: any) => globalThis.String(e))
+        : [],
+      includeChanges: isSet(object.includeChanges) ? globalThis.Boolean(object.includeChanges) : false,
+      changeTypes: globalThis.Array.isArray(object?.changeTypes)
+        ? object.changeTypes.map((e: any) => globalThis.String(e))
+        : [],
+    };
+  },
+
+  toJSON(message: TransactionFieldMask): unknown {
+    const obj: any = {};
+    if (message.includePayload === true) {
+      obj.includePayload = message.includePayload;
+    }
+    if (message.includeSignature === true) {
+      obj.includeSignature = message.includeSignature;
+    }
+    if (message.includeEvents === true) {
+      obj.includeEvents = message.includeEvents;
+    }
+    if (message.eventTypes?.length) {
+      obj.eventTypes = message.eventTypes;
+    }
+    if (message.includeChanges === true) {
+      obj.includeChanges = message.includeChanges;
+    }
+    if (message.changeTypes?.length) {
+      obj.changeTypes = message.changeTypes;
+    }
+    return obj;
+  },
+
+  create(base?: DeepPartial<TransactionFieldMask>): TransactionFieldMask {
+    return TransactionFieldMask.fromPartial(base ?? {});
+  },
+  fromPartial(object: DeepPartial<TransactionFieldMask>): TransactionFieldMask {
+    const message = createBaseTransactionFieldMask();
+    message.includePayload = object.includePayload ?? false;
+    message.includeSignature = object.includeSignature ?? false;
+    message.includeEvents = object.includeEvents ?? false;
+    message.eventTypes = object.eventTypes?.map((e) => e) || [];
+    message.includeChanges = object.includeChanges ?? false;
+    message.changeTypes = object.changeTypes?.map((e) => e) || [];
+    return message;
+  },
+};
+
 export type RawDataService = typeof RawDataService;
 export const RawDataService = {
   /** Get transactions batch without any filtering from starting version and end if transaction count is present. */
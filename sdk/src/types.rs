@@ -12,12 +12,15 @@ use crate::{
     transaction_builder::TransactionBuilder,
     types::{
         account_address::AccountAddress,
-        transaction::{authenticator::AuthenticationKey, RawTransaction, SignedTransaction},
+        transaction::{
+            authenticator::AuthenticationKey, RawTransaction, RawTransactionWithData,
+            SignedTransaction,
+        },
     },
 };
 use anyhow::{Context, Result};
 use aptos_crypto::{ed25519::Ed25519Signature, secp256r1_ecdsa, PrivateKey, SigningKey};
-use aptos_ledger::AptosLedgerError;
+use aptos_ledger::{AptosLedger, AptosLedgerError};
 use aptos_rest_client::{Client, PepperRequest, ProverRequest};
 pub use aptos_types::*;
 use aptos_types::{
@@ -35,7 +38,10 @@ use rand::Rng;
 use serde::{Deserialize, Serialize};
 use std::{
     str::FromStr,
-    sync::atomic::{AtomicU64, Ordering},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
     time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
@@ -447,15 +453,13 @@ pub struct HardwareWalletAccount {
     hardware_wallet_type: HardwareWalletType,
     /// Same as LocalAccount's sequence_number.
     sequence_number: u64,
+    /// The device to sign with. If not set, the first Ledger device found is opened on each request.
+    ledger: Option<Arc<AptosLedger>>,
 }
 
 impl TransactionSigner for HardwareWalletAccount {
     fn sign_transaction(&self, txn: RawTransaction) -> Result<SignedTransaction> {
-        let signature = self.sign_arbitrary_message(
-            signing_message(&txn)
-                .expect("Unable to convert txn to signing message.")
-                .as_ref(),
-        )?;
+        let signature = self.sign_raw_transaction(&txn)?;
         Ok(SignedTransaction::new(
             txn,
             self.public_key().clone(),
@@ -467,16 +471,7 @@ impl TransactionSigner for HardwareWalletAccount {
         &mut self,
         builder: TransactionBuilder,
     ) -> Result<SignedTransaction> {
-        let two_minutes = Duration::from_secs(2 * 60);
-        let current_time = SystemTime::now().duration_since(UNIX_EPOCH)? + two_minutes;
-        let seconds = current_time.as_secs();
-
-        let raw_txn = builder
-            .sender(self.address())
-            .sequence_number(self.sequence_number())
-            .expiration_timestamp_secs(seconds)
-            .build();
-        *self.sequence_number_mut() += 1;
+        let raw_txn = self.build_transaction(builder)?;
         self.sign_transaction(raw_txn)
     }
 }
@@ -495,9 +490,17 @@ impl HardwareWalletAccount {
             derivation_path,
             hardware_wallet_type,
             sequence_number,
+            ledger: None,
         }
     }
 
+    /// Signs with the given device instead of opening the first Ledger device found, e.g., to
+    /// keep a single connection open or to sign with a mock device in tests
+    pub fn with_ledger(mut self, ledger: Arc<AptosLedger>) -> Self {
+        self.ledger = Some(ledger);
+        self
+    }
+
     /// Create a new account from a Ledger device.
     /// This requires the Ledger device to be connected, unlocked and the Aptos app to be opened
     pub fn from_ledger(
@@ -545,7 +548,67 @@ impl HardwareWalletAccount {
         &self,
         message: &[u8],
     ) -> Result<Ed25519Signature, AptosLedgerError> {
-        aptos_ledger::sign_message(&self.derivation_path, message)
+        self.with_device(|ledger| ledger.sign_message(&self.derivation_path, message))
+    }
+
+    /// Builds the next transaction of this account, leaving the user two minutes to review it
+    /// on the device before it expires
+    pub fn build_transaction(&mut self, builder: TransactionBuilder) -> Result<RawTransaction> {
+        let two_minutes = Duration::from_secs(2 * 60);
+        let current_time = SystemTime::now().duration_since(UNIX_EPOCH)? + two_minutes;
+        let seconds = current_time.as_secs();
+
+        let raw_txn = builder
+            .sender(self.address())
+            .sequence_number(self.sequence_number())
+            .expiration_timestamp_secs(seconds)
+            .build();
+        *self.sequence_number_mut() += 1;
+        Ok(raw_txn)
+    }
+
+    /// Signs a transaction, which the device parses and displays for review before signing
+    pub fn sign_raw_transaction(
+        &self,
+        txn: &RawTransaction,
+    ) -> Result<Ed25519Signature, AptosLedgerError> {
+        self.with_device(|ledger| ledger.sign_transaction(&self.derivation_path, txn))
+    }
+
+    /// Signs a multi-agent transaction as one of its secondary signers
+    pub fn sign_multi_agent_transaction(
+        &self,
+        txn: RawTransaction,
+        secondary_signer_addresses: Vec<AccountAddress>,
+    ) -> Result<Ed25519Signature, AptosLedgerError> {
+        let txn = RawTransactionWithData::new_multi_agent(txn, secondary_signer_addresses);
+        self.with_device(|ledger| ledger.sign_transaction_with_data(&self.derivation_path, &txn))
+    }
+
+    /// Signs a fee payer transaction, either as its sender, one of its secondary signers or
+    /// its fee payer
+    pub fn sign_fee_payer_transaction(
+        &self,
+        txn: RawTransaction,
+        secondary_signer_addresses: Vec<AccountAddress>,
+        fee_payer_address: AccountAddress,
+    ) -> Result<Ed25519Signature, AptosLedgerError> {
+        let txn = RawTransactionWithData::new_fee_payer(
+            txn,
+            secondary_signer_addresses,
+            fee_payer_address,
+        );
+        self.with_device(|ledger| ledger.sign_transaction_with_data(&self.derivation_path, &txn))
+    }
+
+    fn with_device<T>(
+        &self,
+        f: impl FnOnce(&AptosLedger) -> Result<T, AptosLedgerError>,
+    ) -> Result<T, AptosLedgerError> {
+        match &self.ledger {
+            Some(ledger) => f(ledger),
+            None => f(&AptosLedger::open()?),
+        }
     }
 }
 
@@ -1059,6 +1122,52 @@ mod tests {
         assert!(LocalAccount::from_derive_path(derive_path, "", 0).is_err());
     }
 
+    #[test]
+    fn test_hardware_wallet_account_signing() {
+        use aptos_ledger::MockLedgerTransport;
+        use aptos_types::{chain_id::ChainId, transaction::authenticator::AccountAuthenticator};
+
+        let derivation_path = aptos_ledger::DERIVATION_PATH.replace("{index}", "0");
+        let mock = MockLedgerTransport::new([1; 32]);
+        let public_key = mock.public_key(&derivation_path);
+        let address = AuthenticationKey::ed25519(&public_key).account_address();
+        let account = HardwareWalletAccount::new(
+            address,
+            public_key.clone(),
+            derivation_path,
+            HardwareWalletType::Ledger,
+            0,
+        )
+        .with_ledger(Arc::new(AptosLedger::new(mock)));
+
+        let txn = TransactionBuilder::new(
+            crate::transaction_builder::aptos_stdlib::aptos_coin_transfer(AccountAddress::ONE, 1),
+            u64::MAX,
+            ChainId::test(),
+        )
+        .sender(address)
+        .sequence_number(0)
+        .build();
+        account
+            .sign_transaction(txn.clone())
+            .unwrap()
+            .verify_signature()
+            .unwrap();
+
+        let fee_payer_signature = account
+            .sign_fee_payer_transaction(txn.clone(), vec![], address)
+            .unwrap();
+        let signed_txn = SignedTransaction::new_fee_payer(
+            txn,
+            AccountAuthenticator::ed25519(public_key.clone(), fee_payer_signature.clone()),
+            vec![],
+            vec![],
+            address,
+            AccountAuthenticator::ed25519(public_key, fee_payer_signature),
+        );
+        signed_txn.verify_signature().unwrap();
+    }
+
     #[test]
     fn test_create_account_from_private_key() {
         let key = AccountKey::generate(&mut rand::rngs::OsRng);
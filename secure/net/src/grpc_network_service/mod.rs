@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::network_controller::{metrics::NETWORK_HANDLER_TIMER, Message, MessageType};
-use aptos_logger::{error, info};
+use aptos_logger::{error, info, sample, sample::SampleRate, warn};
 use aptos_protos::remote_executor::v1::{
     network_message_service_client::NetworkMessageServiceClient,
     network_message_service_server::{NetworkMessageService, NetworkMessageServiceServer},
@@ -13,6 +13,7 @@ use std::{
     collections::HashMap,
     net::SocketAddr,
     sync::{Arc, Mutex},
+    time::Duration,
 };
 use tokio::{runtime::Runtime, sync::oneshot};
 use tonic::{
@@ -121,18 +122,26 @@ pub struct GRPCNetworkMessageServiceClientWrapper {
 }
 
 impl GRPCNetworkMessageServiceClientWrapper {
-    pub fn new(rt: &Runtime, remote_addr: SocketAddr) -> Self {
+    pub fn new(rt: &Runtime, remote_addr: SocketAddr, rpc_timeout_ms: u64) -> Self {
         Self {
             remote_addr: remote_addr.to_string(),
-            remote_channel: rt
-                .block_on(async { Self::get_channel(format!("http://{}", remote_addr)).await }),
+            remote_channel: rt.block_on(async {
+                Self::get_channel(format!("http://{}", remote_addr), rpc_timeout_ms).await
+            }),
         }
     }
 
-    async fn get_channel(remote_addr: String) -> NetworkMessageServiceClient<Channel> {
+    async fn get_channel(
+        remote_addr: String,
+        rpc_timeout_ms: u64,
+    ) -> NetworkMessageServiceClient<Channel> {
         info!("Trying to connect to remote server at {:?}", remote_addr);
+        // Bound the time spent on a remote node that is down, so that sending to it does not stall.
+        let timeout = Duration::from_millis(rpc_timeout_ms);
         let conn = tonic::transport::Endpoint::new(remote_addr)
             .unwrap()
+            .connect_timeout(timeout)
+            .timeout(timeout)
             .connect_lazy();
         NetworkMessageServiceClient::new(conn).max_decoding_message_size(MAX_MESSAGE_SIZE)
     }
@@ -148,14 +157,18 @@ impl GRPCNetworkMessageServiceClientWrapper {
             message_type: mt.get_type(),
         });
         // TODO: Retry with exponential backoff on failures
-        match self.remote_channel.simple_msg_exchange(request).await {
-            Ok(_) => {},
-            Err(e) => {
-                panic!(
-                    "Error '{}' sending message to {} on node {:?}",
+        // The message is dropped on failure, as the remote node may be down, and panicking would
+        // stop the outbound handler for all the other remote nodes. Receivers are expected to
+        // time out on missing messages.
+        if let Err(e) = self.remote_channel.simple_msg_exchange(request).await {
+            // Messages are sent periodically to a node that is down (e.g., health checks).
+            sample!(
+                SampleRate::Duration(Duration::from_secs(10)),
+                warn!(
+                    "Error '{}' sending message to {} on node {:?}, dropping it",
                     e, self.remote_addr, sender_addr
-                );
-            },
+                )
+            );
         }
     }
 }
@@ -190,7 +203,7 @@ fn basic_test() {
         server_shutdown_rx,
     );
 
-    let mut grpc_client = GRPCNetworkMessageServiceClientWrapper::new(&rt, server_addr);
+    let mut grpc_client = GRPCNetworkMessageServiceClientWrapper::new(&rt, server_addr, 1000);
 
     let client_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), utils::get_available_port());
     let test_message_content = "test1".as_bytes().to_vec();
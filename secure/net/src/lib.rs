@@ -15,10 +15,18 @@
 //!
 //! Internally both the client and server leverage a NetworkStream that communications in blocks
 //! where a block is a length prefixed array of bytes.
+//!
+//! Optionally, the client and server can be configured with Noise keys. In that case, each new
+//! connection starts with a Noise IK handshake, in which both ends authenticate with their static
+//! x25519 keys, and all subsequent blocks are encrypted.
 
 pub mod grpc_network_service;
 pub mod network_controller;
 
+use aptos_crypto::{
+    noise::{self, NoiseConfig, NoiseError, NoiseSession},
+    x25519,
+};
 use aptos_logger::{info, trace, warn, Schema};
 use aptos_metrics_core::{register_int_counter_vec, IntCounterVec};
 use once_cell::sync::Lazy;
@@ -59,6 +67,7 @@ enum LogEvent {
     ConnectionAttempt,
     ConnectionSuccessful,
     ConnectionFailed,
+    HandshakeFailed,
     DisconnectedPeerOnRead,
     DisconnectedPeerOnWrite,
     Shutdown,
@@ -141,10 +150,30 @@ pub enum Error {
     NetworkError(#[from] std::io::Error),
     #[error("No active stream")]
     NoActiveStream,
+    #[error("Noise error: {0}")]
+    NoiseError(#[from] NoiseError),
     #[error("Overflow error: {0}")]
     OverflowError(String),
     #[error("Remote stream cleanly closed")]
     RemoteStreamClosed,
+    #[error("Remote peer authenticated with an unexpected public key: {0}")]
+    UnexpectedRemotePeer(x25519::PublicKey),
+}
+
+/// The keys used to mutually authenticate a connection and encrypt its traffic. Each end holds
+/// its own static private key and the static public key it expects the other end to use.
+pub struct NoiseKeys {
+    config: NoiseConfig,
+    remote_public_key: x25519::PublicKey,
+}
+
+impl NoiseKeys {
+    pub fn new(private_key: x25519::PrivateKey, remote_public_key: x25519::PublicKey) -> Self {
+        Self {
+            config: NoiseConfig::new(private_key),
+            remote_public_key,
+        }
+    }
 }
 
 pub struct NetworkClient {
@@ -153,6 +182,7 @@ pub struct NetworkClient {
     stream: Option<NetworkStream>,
     /// Read, Write, Connect timeout in milliseconds.
     timeout_ms: u64,
+    noise_keys: Option<NoiseKeys>,
 }
 
 impl NetworkClient {
@@ -162,9 +192,16 @@ impl NetworkClient {
             server,
             stream: None,
             timeout_ms,
+            noise_keys: None,
         }
     }
 
+    /// Authenticate and encrypt all connections to the server using the given Noise keys.
+    pub fn with_noise_keys(mut self, noise_keys: NoiseKeys) -> Self {
+        self.noise_keys = Some(noise_keys);
+        self
+    }
+
     fn increment_counter(&self, method: Method, result: MethodResult) {
         increment_counter(&self.service, NetworkMode::Client, method, result)
     }
@@ -256,7 +293,21 @@ impl NetworkClient {
 
             let stream = stream?;
             stream.set_nodelay(true)?;
-            self.stream = Some(NetworkStream::new(stream, self.server, self.timeout_ms));
+            let mut stream = NetworkStream::new(stream, self.server, self.timeout_ms);
+            if let Some(noise_keys) = &self.noise_keys {
+                if let Err(err) = stream.initiate_handshake(self.service.as_bytes(), noise_keys) {
+                    self.increment_counter(Method::Connect, MethodResult::Failure);
+                    warn!(SecureNetLogSchema::new(
+                        &self.service,
+                        NetworkMode::Client,
+                        LogEvent::HandshakeFailed,
+                    )
+                    .error(&err)
+                    .remote_peer(&self.server));
+                    return Err(err);
+                }
+            }
+            self.stream = Some(stream);
             self.increment_counter(Method::Connect, MethodResult::Success);
             info!(SecureNetLogSchema::new(
                 &self.service,
@@ -276,6 +327,7 @@ pub struct NetworkServer {
     stream: Option<NetworkStream>,
     /// Read, Write, Connect timeout in milliseconds.
     timeout_ms: u64,
+    noise_keys: Option<NoiseKeys>,
 }
 
 impl NetworkServer {
@@ -286,9 +338,17 @@ impl NetworkServer {
             listener: Some(listener.unwrap()),
             stream: None,
             timeout_ms,
+            noise_keys: None,
         }
     }
 
+    /// Only accept clients that authenticate with the expected Noise key, and encrypt all
+    /// traffic with them.
+    pub fn with_noise_keys(mut self, noise_keys: NoiseKeys) -> Self {
+        self.noise_keys = Some(noise_keys);
+        self
+    }
+
     fn increment_counter(&self, method: Method, result: MethodResult) {
         increment_counter(&self.service, NetworkMode::Server, method, result)
     }
@@ -389,6 +449,22 @@ impl NetworkServer {
                 },
             };
 
+            stream.set_nodelay(true)?;
+            let mut stream = NetworkStream::new(stream, stream_addr, self.timeout_ms);
+            if let Some(noise_keys) = &self.noise_keys {
+                if let Err(err) = stream.respond_to_handshake(self.service.as_bytes(), noise_keys) {
+                    self.increment_counter(Method::Connect, MethodResult::Failure);
+                    warn!(SecureNetLogSchema::new(
+                        &self.service,
+                        NetworkMode::Server,
+                        LogEvent::HandshakeFailed,
+                    )
+                    .error(&err)
+                    .remote_peer(&stream_addr));
+                    return Err(err);
+                }
+            }
+
             self.increment_counter(Method::Connect, MethodResult::Success);
             info!(SecureNetLogSchema::new(
                 &self.service,
@@ -397,19 +473,23 @@ impl NetworkServer {
             )
             .remote_peer(&stream_addr));
 
-            stream.set_nodelay(true)?;
-            self.stream = Some(NetworkStream::new(stream, stream_addr, self.timeout_ms));
+            self.stream = Some(stream);
         }
 
         self.stream.as_mut().ok_or(Error::NoActiveStream)
     }
 }
 
+/// The largest plaintext that fits into a single Noise message.
+const MAX_NOISE_PLAINTEXT_SIZE: usize = noise::MAX_SIZE_NOISE_MSG - noise::AES_GCM_TAGLEN;
+
 struct NetworkStream {
     stream: TcpStream,
     remote: SocketAddr,
     buffer: Vec<u8>,
     temp_buffer: [u8; 1024],
+    /// Set once a Noise handshake completes, after which all blocks are encrypted.
+    session: Option<NoiseSession>,
 }
 
 impl NetworkStream {
@@ -424,11 +504,94 @@ impl NetworkStream {
             remote,
             buffer: Vec::new(),
             temp_buffer: [0; 1024],
+            session: None,
         }
     }
 
-    /// Blocking read until able to successfully read an entire message
+    /// Performs the initiator side of the Noise IK handshake. This authenticates the responder
+    /// through its expected public key, and the responder checks our public key in return.
+    fn initiate_handshake(&mut self, prologue: &[u8], noise_keys: &NoiseKeys) -> Result<(), Error> {
+        let mut rng = rand::rngs::OsRng;
+        let mut init_message = vec![0u8; noise::handshake_init_msg_len(0)];
+        let handshake_state = noise_keys.config.initiate_connection(
+            &mut rng,
+            prologue,
+            noise_keys.remote_public_key,
+            None,
+            &mut init_message,
+        )?;
+        self.write_block(&init_message)?;
+
+        let response = self.read_block()?;
+        let (_, session) = noise_keys
+            .config
+            .finalize_connection(handshake_state, &response)?;
+        self.session = Some(session);
+        Ok(())
+    }
+
+    /// Performs the responder side of the Noise IK handshake, rejecting any initiator that does
+    /// not authenticate with the expected public key.
+    fn respond_to_handshake(
+        &mut self,
+        prologue: &[u8],
+        noise_keys: &NoiseKeys,
+    ) -> Result<(), Error> {
+        let init_message = self.read_block()?;
+        let (remote_public_key, handshake_state, _) = noise_keys
+            .config
+            .parse_client_init_message(prologue, &init_message)?;
+        if remote_public_key != noise_keys.remote_public_key {
+            return Err(Error::UnexpectedRemotePeer(remote_public_key));
+        }
+
+        let mut rng = rand::rngs::OsRng;
+        let mut response = vec![0u8; noise::handshake_resp_msg_len(0)];
+        let session =
+            noise_keys
+                .config
+                .respond_to_client(&mut rng, handshake_state, None, &mut response)?;
+        self.write_block(&response)?;
+        self.session = Some(session);
+        Ok(())
+    }
+
+    /// Blocking read until able to successfully read an entire message. If a Noise session is
+    /// established, the message is decrypted one Noise message at a time.
     pub fn read(&mut self) -> Result<Vec<u8>, Error> {
+        let mut block = self.read_block()?;
+        let session = match &mut self.session {
+            Some(session) => session,
+            None => return Ok(block),
+        };
+
+        let mut message = Vec::with_capacity(block.len());
+        for ciphertext in block.chunks_mut(noise::MAX_SIZE_NOISE_MSG) {
+            message.extend_from_slice(session.read_message_in_place(ciphertext)?);
+        }
+        Ok(message)
+    }
+
+    /// Blocking write until able to successfully send an entire message. If a Noise session is
+    /// established, the message is encrypted one Noise message at a time.
+    pub fn write(&mut self, data: &[u8]) -> Result<(), Error> {
+        let session = match &mut self.session {
+            Some(session) => session,
+            None => return self.write_block(data),
+        };
+
+        let mut block = Vec::with_capacity(data.len() + noise::AES_GCM_TAGLEN);
+        for plaintext in data.chunks(MAX_NOISE_PLAINTEXT_SIZE) {
+            let start = block.len();
+            block.extend_from_slice(plaintext);
+            let authentication_tag = session.write_message_in_place(&mut block[start..])?;
+            block.extend_from_slice(&authentication_tag);
+        }
+        self.write_block(&block)
+    }
+
+    /// Blocking read until able to successfully read an entire block
+    fn read_block(&mut self) -> Result<Vec<u8>, Error> {
         let result = self.read_buffer();
         if !result.is_empty() {
             return Ok(result);
@@ -456,8 +619,8 @@ impl NetworkStream {
         Ok(self.stream.shutdown(Shutdown::Both)?)
     }
 
-    /// Blocking write until able to successfully send an entire message
-    pub fn write(&mut self, data: &[u8]) -> Result<(), Error> {
+    /// Blocking write until able to successfully send an entire block
+    fn write_block(&mut self, data: &[u8]) -> Result<(), Error> {
         let u32_max = u32::max_value() as usize;
         if u32_max <= data.len() {
             return Err(Error::DataTooLarge(data.len()));
@@ -517,6 +680,7 @@ impl NetworkStream {
 mod test {
     use super::*;
     use aptos_config::utils;
+    use aptos_crypto::Uniform;
     use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 
     /// Read, Write, Connect timeout in milliseconds.
@@ -655,4 +819,60 @@ mod test {
         let result2 = server2.read().unwrap();
         assert_eq!(data2, result2);
     }
+
+    #[test]
+    fn test_noise_ping() {
+        let server_port = utils::get_available_port();
+        let server_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), server_port);
+        let mut rng = rand::rngs::OsRng;
+        let client_key = x25519::PrivateKey::generate(&mut rng);
+        let server_keys = NoiseKeys::new(
+            x25519::PrivateKey::generate(&mut rng),
+            client_key.public_key(),
+        );
+        let client_keys = NoiseKeys::new(client_key, server_keys.config.public_key());
+
+        // The handshake blocks until both ends participate, so the server runs on its own thread
+        let server = thread::spawn(move || {
+            let mut server = NetworkServer::new("test".to_string(), server_addr, TIMEOUT)
+                .with_noise_keys(server_keys);
+            let request = server.read().unwrap();
+            server.write(&request).unwrap();
+        });
+        let mut client = NetworkClient::new("test".to_string(), server_addr, TIMEOUT)
+            .with_noise_keys(client_keys);
+
+        // Larger than a single Noise message
+        let data: Vec<u8> = (0..200_000).map(|i| (i % 251) as u8).collect();
+        client.write(&data).unwrap();
+        let result = client.read().unwrap();
+        assert_eq!(data, result);
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_noise_unexpected_client() {
+        let server_port = utils::get_available_port();
+        let server_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), server_port);
+        let mut rng = rand::rngs::OsRng;
+        let server_key = x25519::PrivateKey::generate(&mut rng);
+        let client_key = x25519::PrivateKey::generate(&mut rng);
+        let other_key = x25519::PrivateKey::generate(&mut rng);
+        let client_keys = NoiseKeys::new(client_key, server_key.public_key());
+        let server_keys = NoiseKeys::new(server_key, other_key.public_key());
+
+        let server = thread::spawn(move || {
+            let mut server = NetworkServer::new("test".to_string(), server_addr, TIMEOUT)
+                .with_noise_keys(server_keys);
+            server.read().unwrap_err()
+        });
+        let mut client = NetworkClient::new("test".to_string(), server_addr, TIMEOUT)
+            .with_noise_keys(client_keys);
+
+        client.write(&[0, 1, 2, 3]).unwrap_err();
+        assert!(matches!(
+            server.join().unwrap(),
+            Error::UnexpectedRemotePeer(_)
+        ));
+    }
 }
@@ -97,7 +97,8 @@ impl NetworkController {
             listen_addr,
             timeout_ms,
         )));
-        let outbound_handler = OutboundHandler::new(service, listen_addr, inbound_handler.clone());
+        let outbound_handler =
+            OutboundHandler::new(service, listen_addr, inbound_handler.clone(), timeout_ms);
         info!("Network controller created for node {}", listen_addr);
         Self {
             inbound_handler,
@@ -15,7 +15,10 @@ use std::{
     net::SocketAddr,
     sync::{Arc, Mutex},
 };
-use tokio::runtime::Runtime;
+use tokio::{
+    runtime::Runtime,
+    sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender},
+};
 
 pub struct OutboundHandler {
     _service: String,
@@ -24,6 +27,7 @@ pub struct OutboundHandler {
     // Used to route outgoing messages to correct network client with the correct message type
     handlers: Vec<(Receiver<Message>, SocketAddr, MessageType)>,
     inbound_handler: Arc<Mutex<InboundHandler>>,
+    rpc_timeout_ms: u64,
 }
 
 impl OutboundHandler {
@@ -31,6 +35,7 @@ impl OutboundHandler {
         service: String,
         listen_addr: SocketAddr,
         inbound_handler: Arc<Mutex<InboundHandler>>,
+        rpc_timeout_ms: u64,
     ) -> Self {
         Self {
             _service: service,
@@ -38,6 +43,7 @@ impl OutboundHandler {
             address: listen_addr,
             handlers: Vec::new(),
             inbound_handler,
+            rpc_timeout_ms,
         }
     }
 
@@ -65,19 +71,22 @@ impl OutboundHandler {
             MessageType::new("stop_task".to_string()),
         ));
 
-        // Create a grpc client for each remote address
-        let mut grpc_clients: HashMap<SocketAddr, GRPCNetworkMessageServiceClientWrapper> =
+        // Prepare for objects to be moved into the async block (&mut self cannot be moved into the
+        // async block)
+        let address = self.address;
+
+        // Create a grpc client for each remote address, with its own task sending the messages,
+        // so that a remote node that is down does not delay the messages to the other ones.
+        let mut remote_senders: HashMap<SocketAddr, UnboundedSender<(Message, MessageType)>> =
             HashMap::new();
         self.remote_addresses.iter().for_each(|remote_addr| {
-            grpc_clients.insert(
-                *remote_addr,
-                GRPCNetworkMessageServiceClientWrapper::new(rt, *remote_addr),
-            );
+            let grpc_client =
+                GRPCNetworkMessageServiceClientWrapper::new(rt, *remote_addr, self.rpc_timeout_ms);
+            let (remote_sender, remote_receiver) = unbounded_channel();
+            rt.spawn(Self::send_to_remote(grpc_client, address, remote_receiver));
+            remote_senders.insert(*remote_addr, remote_sender);
         });
 
-        // Prepare for objects to be moved into the async block (&mut self cannot be moved into the
-        // async block)
-        let address = self.address;
         let inbound_handler = self.inbound_handler.clone();
         // Moving the handlers out of self is fine because once 'start()' is called we do not intend
         // to register any more handlers. A reference count like Arc<Mutex> has issues of being
@@ -85,26 +94,39 @@ impl OutboundHandler {
         // the cost of the mutex when there is no contention
         let outbound_handlers = mem::take(self.handlers.as_mut());
 
-        // TODO: Consider using multiple tasks for outbound handlers
-        rt.spawn(async move {
+        // Waiting for outgoing messages blocks, so it is done outside of the async workers, which
+        // send the messages.
+        rt.spawn_blocking(move || {
             info!("Starting outbound handler at {}", address.to_string());
             Self::process_one_outgoing_message(
                 outbound_handlers,
                 &address,
                 inbound_handler.clone(),
-                &mut grpc_clients,
-            )
-            .await;
+                &remote_senders,
+            );
             info!("Stopping outbound handler at {}", address.to_string());
         });
         Some(stop_signal_tx)
     }
 
-    async fn process_one_outgoing_message(
+    /// Sends the messages to a remote node in order, until the outbound handler stops.
+    async fn send_to_remote(
+        mut grpc_client: GRPCNetworkMessageServiceClientWrapper,
+        socket_addr: SocketAddr,
+        mut receiver: UnboundedReceiver<(Message, MessageType)>,
+    ) {
+        while let Some((msg, message_type)) = receiver.recv().await {
+            grpc_client
+                .send_message(socket_addr, msg, &message_type)
+                .await;
+        }
+    }
+
+    fn process_one_outgoing_message(
         outbound_handlers: Vec<(Receiver<Message>, SocketAddr, MessageType)>,
         socket_addr: &SocketAddr,
         inbound_handler: Arc<Mutex<InboundHandler>>,
-        grpc_clients: &mut HashMap<SocketAddr, GRPCNetworkMessageServiceClientWrapper>,
+        remote_senders: &HashMap<SocketAddr, UnboundedSender<(Message, MessageType)>>,
     ) {
         loop {
             let mut select = Select::new();
@@ -151,12 +173,11 @@ impl OutboundHandler {
                     .lock()
                     .unwrap()
                     .send_incoming_message_to_handler(message_type, msg);
-            } else {
-                grpc_clients
-                    .get_mut(remote_addr)
-                    .unwrap()
-                    .send_message(*socket_addr, msg, message_type)
-                    .await;
+            } else if remote_senders[remote_addr]
+                .send((msg, message_type.clone()))
+                .is_err()
+            {
+                warn!("Sending task for {} stopped, dropping message", remote_addr);
             }
         }
     }
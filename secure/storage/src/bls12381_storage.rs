@@ -0,0 +1,53 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::Error;
+use aptos_crypto::bls12381;
+use enum_dispatch::enum_dispatch;
+
+/// Bls12381Storage provides an abstraction for keeping BLS12-381 keys (e.g., consensus keys) as
+/// key handles that the storage signs with, so the private keys never leave it. Storage engines
+/// that can't do so rely on the default implementations, which report that BLS12-381 keys are not
+/// supported, and callers are expected to keep such keys as regular (KVStorage) values instead.
+#[enum_dispatch]
+pub trait Bls12381Storage {
+    /// Returns true iff BLS12-381 keys can be kept in this storage.
+    fn supports_bls12381_keys(&self) -> bool {
+        false
+    }
+
+    /// Securely generates a new named BLS12-381 private key, replacing any existing key with the
+    /// same name.
+    fn create_bls12381_key(&mut self, _name: &str) -> Result<bls12381::PublicKey, Error> {
+        Err(unsupported())
+    }
+
+    /// Imports the BLS12-381 private key and stores it at the provided name, replacing any
+    /// existing key with the same name. The key can't be exported again.
+    fn import_bls12381_key(
+        &mut self,
+        _name: &str,
+        _key: bls12381::PrivateKey,
+    ) -> Result<(), Error> {
+        Err(unsupported())
+    }
+
+    /// Returns the BLS12-381 public key stored at 'name'.
+    fn get_bls12381_public_key(&self, _name: &str) -> Result<bls12381::PublicKey, Error> {
+        Err(unsupported())
+    }
+
+    /// Signs the provided securely-hashable struct, using the 'named' BLS12-381 private key.
+    // The FQDNs on the next line help macros, don't remove them
+    fn sign_bls12381<T: aptos_crypto::hash::CryptoHash + serde::Serialize>(
+        &self,
+        _name: &str,
+        _message: &T,
+    ) -> Result<bls12381::Signature, Error> {
+        Err(unsupported())
+    }
+}
+
+fn unsupported() -> Error {
+    Error::InternalError("BLS12-381 keys are not supported by this storage".to_string())
+}
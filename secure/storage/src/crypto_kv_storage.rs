@@ -119,8 +119,8 @@ fn new_ed25519_key_pair() -> (Ed25519PrivateKey, Ed25519PublicKey) {
     (private_key, public_key)
 }
 
-/// Private helper method to get the name of the previous version of the given key pair, as held in
-/// secure cryptographic storage.
-fn get_previous_version_name(name: &str) -> String {
+/// Helper method to get the name of the previous version of the given key pair, as held in secure
+/// cryptographic storage.
+pub(crate) fn get_previous_version_name(name: &str) -> String {
     format!("{}_previous", name)
 }
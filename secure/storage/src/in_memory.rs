@@ -2,7 +2,7 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{CryptoKVStorage, Error, GetResponse, KVStorage};
+use crate::{Bls12381Storage, CryptoKVStorage, Error, GetResponse, KVStorage};
 use aptos_time_service::{TimeService, TimeServiceTrait};
 use serde::{de::DeserializeOwned, Serialize};
 use std::collections::HashMap;
@@ -65,3 +65,5 @@ impl KVStorage for InMemoryStorage {
 }
 
 impl CryptoKVStorage for InMemoryStorage {}
+
+impl Bls12381Storage for InMemoryStorage {}
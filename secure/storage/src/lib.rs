@@ -4,6 +4,7 @@
 
 #![forbid(unsafe_code)]
 
+mod bls12381_storage;
 mod crypto_kv_storage;
 mod crypto_storage;
 mod error;
@@ -11,11 +12,13 @@ mod in_memory;
 mod kv_storage;
 mod namespaced;
 mod on_disk;
+mod pkcs11;
 mod policy;
 mod storage;
 mod vault;
 
 pub use crate::{
+    bls12381_storage::Bls12381Storage,
     crypto_kv_storage::CryptoKVStorage,
     crypto_storage::{CryptoStorage, PublicKeyResponse},
     error::Error,
@@ -23,6 +26,7 @@ pub use crate::{
     kv_storage::{GetResponse, KVStorage},
     namespaced::Namespaced,
     on_disk::OnDiskStorage,
+    pkcs11::{Bls12381Mechanisms, Pkcs11Storage},
     policy::{Capability, Identity, Permission, Policy},
     storage::Storage,
     vault::VaultStorage,
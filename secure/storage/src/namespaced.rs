@@ -2,8 +2,9 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{CryptoStorage, Error, GetResponse, KVStorage, PublicKeyResponse};
+use crate::{Bls12381Storage, CryptoStorage, Error, GetResponse, KVStorage, PublicKeyResponse};
 use aptos_crypto::{
+    bls12381,
     ed25519::{Ed25519PrivateKey, Ed25519PublicKey, Ed25519Signature},
     hash::CryptoHash,
 };
@@ -133,6 +134,32 @@ impl<S: CryptoStorage> CryptoStorage for Namespaced<S> {
     }
 }
 
+impl<S: Bls12381Storage> Bls12381Storage for Namespaced<S> {
+    fn supports_bls12381_keys(&self) -> bool {
+        self.inner.supports_bls12381_keys()
+    }
+
+    fn create_bls12381_key(&mut self, name: &str) -> Result<bls12381::PublicKey, Error> {
+        self.inner.create_bls12381_key(&self.namespaced(name))
+    }
+
+    fn import_bls12381_key(&mut self, name: &str, key: bls12381::PrivateKey) -> Result<(), Error> {
+        self.inner.import_bls12381_key(&self.namespaced(name), key)
+    }
+
+    fn get_bls12381_public_key(&self, name: &str) -> Result<bls12381::PublicKey, Error> {
+        self.inner.get_bls12381_public_key(&self.namespaced(name))
+    }
+
+    fn sign_bls12381<T: CryptoHash + Serialize>(
+        &self,
+        name: &str,
+        message: &T,
+    ) -> Result<bls12381::Signature, Error> {
+        self.inner.sign_bls12381(&self.namespaced(name), message)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
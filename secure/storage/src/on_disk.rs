@@ -2,7 +2,7 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{CryptoKVStorage, Error, GetResponse, KVStorage};
+use crate::{Bls12381Storage, CryptoKVStorage, Error, GetResponse, KVStorage};
 use aptos_temppath::TempPath;
 use aptos_time_service::{TimeService, TimeServiceTrait};
 use serde::{de::DeserializeOwned, Serialize};
@@ -100,3 +100,5 @@ impl KVStorage for OnDiskStorage {
 }
 
 impl CryptoKVStorage for OnDiskStorage {}
+
+impl Bls12381Storage for OnDiskStorage {}
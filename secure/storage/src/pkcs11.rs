@@ -0,0 +1,541 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    crypto_kv_storage::get_previous_version_name, Bls12381Storage, CryptoStorage, Error,
+    GetResponse, KVStorage, PublicKeyResponse,
+};
+use aptos_crypto::{
+    bls12381,
+    ed25519::{Ed25519PrivateKey, Ed25519PublicKey, Ed25519Signature},
+    hash::CryptoHash,
+    signing_message, PrivateKey,
+};
+use aptos_infallible::{Mutex, MutexGuard};
+use aptos_time_service::{TimeService, TimeServiceTrait};
+use cryptoki::{
+    context::{CInitializeArgs, Pkcs11},
+    error::RvError,
+    mechanism::{vendor_defined::VendorDefinedMechanism, Mechanism, MechanismType},
+    object::{Attribute, AttributeType, KeyType, ObjectClass, ObjectHandle},
+    session::{Session, UserType},
+    types::AuthPin,
+};
+use once_cell::sync::{Lazy, OnceCell};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+/// All objects created by this storage engine carry this application tag, which keeps the key
+/// value data separate from any other data objects on the token.
+const APPLICATION: &[u8] = b"aptos-secure-storage";
+
+/// The DER encoding of the edwards25519 curve OID (1.3.101.112), used as the EC parameters of
+/// Ed25519 keys.
+const ED25519_EC_PARAMS: &[u8] = &[0x06, 0x03, 0x2B, 0x65, 0x70];
+
+/// The tag and length of the DER OCTET STRING that most modules wrap Ed25519 EC points in.
+const ED25519_EC_POINT_PREFIX: &[u8] = &[0x04, 0x20];
+
+/// A PKCS#11 module can only be initialized once per process (C_Initialize fails with
+/// CKR_CRYPTOKI_ALREADY_INITIALIZED afterwards), so all storages using the same module share a
+/// single initialized context.
+static CONTEXTS: Lazy<Mutex<HashMap<PathBuf, Pkcs11>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// PKCS#11 has no standard mechanisms for BLS12-381, so devices that support it do so through
+/// vendor-defined key types and mechanisms. These are the vendor-defined values (i.e., the offsets
+/// from CKK_VENDOR_DEFINED and CKM_VENDOR_DEFINED) to use. The signing mechanism is expected to
+/// hash to G2 using the same ciphersuite as `aptos_crypto::bls12381`, and the public key's
+/// CKA_VALUE is expected to hold the compressed G1 point.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Bls12381Mechanisms {
+    pub key_type: u64,
+    pub key_pair_gen: u64,
+    pub sign: u64,
+}
+
+/// Pkcs11Storage keeps keys in a hardware security module (or any other PKCS#11 token, such as
+/// SoftHSM for testing). Keys are generated inside the token as sensitive, non-extractable
+/// objects, so signing happens inside the device and private keys never leave it. Exporting
+/// private keys is therefore not supported. Non-key data is stored as private data objects on the
+/// token, which serves the KVStorage interface.
+///
+/// Key rotation follows CryptoKVStorage: the current key is labelled with its name and the
+/// previous version with the "_previous" suffix. Each private key object is identified by its
+/// public key (CKA_ID), and the public key and its creation time are kept in a data object with
+/// the same label for cheap lookups. BLS12-381 keys are kept the same way, but aren't rotated.
+///
+/// The session to the token is opened on first use, so failures to read the user PIN, load the
+/// module or log in are returned by the storage operations (e.g., `available`), which then retry.
+pub struct Pkcs11Storage {
+    library_path: PathBuf,
+    token_label: String,
+    user_pin: Result<String, String>,
+    session: OnceCell<Mutex<Session>>,
+    bls12381_mechanisms: Option<Bls12381Mechanisms>,
+    time_service: TimeService,
+}
+
+impl Pkcs11Storage {
+    /// Creates a storage for the token with the given label, using the PKCS#11 module at
+    /// `library_path`. The user PIN is passed as the result of reading it, so that a failure to
+    /// read it surfaces like any other failure to open the session.
+    pub fn new(
+        library_path: PathBuf,
+        token_label: String,
+        user_pin: Result<String, String>,
+        bls12381_mechanisms: Option<Bls12381Mechanisms>,
+    ) -> Self {
+        Self {
+            library_path,
+            token_label,
+            user_pin,
+            session: OnceCell::new(),
+            bls12381_mechanisms,
+            time_service: TimeService::real(),
+        }
+    }
+
+    /// Returns the logged in session to the token, opening it if needed.
+    fn session(&self) -> Result<MutexGuard<'_, Session>, Error> {
+        let session = self
+            .session
+            .get_or_try_init(|| self.open_session().map(Mutex::new))?;
+        Ok(session.lock())
+    }
+
+    fn open_session(&self) -> Result<Session, Error> {
+        let user_pin = self.user_pin.clone().map_err(|error| {
+            Error::InternalError(format!("Unable to read PKCS#11 user PIN: {}", error))
+        })?;
+        let pkcs11 = context(&self.library_path)?;
+
+        let mut slot = None;
+        for candidate in pkcs11.get_slots_with_token()? {
+            if pkcs11.get_token_info(candidate)?.label() == self.token_label {
+                slot = Some(candidate);
+                break;
+            }
+        }
+        let slot = slot.ok_or_else(|| {
+            Error::InternalError(format!("No PKCS#11 token with label: {}", self.token_label))
+        })?;
+
+        // The login state is shared by all sessions of the process to a token, so another
+        // storage may have logged in already.
+        let session = pkcs11.open_rw_session(slot)?;
+        match session.login(UserType::User, Some(&AuthPin::new(user_pin))) {
+            Ok(()) | Err(cryptoki::error::Error::Pkcs11(RvError::UserAlreadyLoggedIn)) => {
+                Ok(session)
+            },
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    fn bls12381_key_type(&self) -> Result<KeyType, Error> {
+        Ok(KeyType::new_vendor_defined(
+            self.bls12381_mechanisms()?.key_type,
+        )?)
+    }
+
+    fn bls12381_mechanisms(&self) -> Result<&Bls12381Mechanisms, Error> {
+        self.bls12381_mechanisms.as_ref().ok_or_else(|| {
+            Error::InternalError("BLS12-381 mechanisms are not configured".to_string())
+        })
+    }
+
+    /// Destroys the named BLS12-381 key, if it exists.
+    fn destroy_bls12381_key(&self, session: &Session, name: &str) -> Result<(), Error> {
+        destroy_objects(
+            session,
+            &private_key_template(name, self.bls12381_key_type()?),
+        )?;
+        destroy_objects(
+            session,
+            &data_template(&bls12381_public_key_data_label(name)),
+        )
+    }
+
+    /// Generates a new Ed25519 key pair inside the device and labels it with the given name.
+    fn generate_ed25519_key(
+        &self,
+        session: &Session,
+        name: &str,
+    ) -> Result<Ed25519PublicKey, Error> {
+        let mut public_template = public_key_template(name, KeyType::EC_EDWARDS);
+        public_template.push(Attribute::EcParams(ED25519_EC_PARAMS.to_vec()));
+        let (public_handle, private_handle) = session.generate_key_pair(
+            &Mechanism::EccEdwardsKeyPairGen,
+            &public_template,
+            &private_key_template(name, KeyType::EC_EDWARDS),
+        )?;
+
+        let ec_point = read_bytes(session, public_handle, AttributeType::EcPoint)?;
+        session.destroy_object(public_handle)?;
+        let public_key_bytes = ec_point
+            .strip_prefix(ED25519_EC_POINT_PREFIX)
+            .unwrap_or(&ec_point);
+        let public_key = Ed25519PublicKey::try_from(public_key_bytes)
+            .map_err(|e| Error::SerializationError(e.to_string()))?;
+
+        session.update_attributes(private_handle, &[Attribute::Id(
+            public_key.to_bytes().to_vec(),
+        )])?;
+        self.write_public_key(session, name, &public_key)?;
+        Ok(public_key)
+    }
+
+    fn write_public_key(
+        &self,
+        session: &Session,
+        name: &str,
+        public_key: &Ed25519PublicKey,
+    ) -> Result<(), Error> {
+        let response = PublicKeyResponse {
+            last_update: self.time_service.now_secs(),
+            public_key: public_key.clone(),
+        };
+        write_data(session, &public_key_data_label(name), &response)
+    }
+
+    fn read_public_key(&self, session: &Session, name: &str) -> Result<PublicKeyResponse, Error> {
+        read_data(session, &public_key_data_label(name))?
+            .ok_or_else(|| Error::KeyNotSet(name.to_string()))
+    }
+
+    /// Destroys the named Ed25519 key pair, if it exists.
+    fn destroy_ed25519_key(&self, session: &Session, name: &str) -> Result<(), Error> {
+        destroy_objects(session, &private_key_template(name, KeyType::EC_EDWARDS))?;
+        destroy_objects(session, &data_template(&public_key_data_label(name)))
+    }
+
+    /// Renames the named Ed25519 key pair, replacing any existing key pair with the new name.
+    fn rename_ed25519_key(&self, session: &Session, from: &str, to: &str) -> Result<(), Error> {
+        self.destroy_ed25519_key(session, to)?;
+        for handle in session.find_objects(&private_key_template(from, KeyType::EC_EDWARDS))? {
+            session.update_attributes(handle, &[Attribute::Label(to.as_bytes().to_vec())])?;
+        }
+        for handle in session.find_objects(&data_template(&public_key_data_label(from)))? {
+            session.update_attributes(handle, &[Attribute::Label(
+                public_key_data_label(to).into_bytes(),
+            )])?;
+        }
+        Ok(())
+    }
+
+    fn sign_with_key(
+        &self,
+        session: &Session,
+        private_key: ObjectHandle,
+        message: &[u8],
+    ) -> Result<Ed25519Signature, Error> {
+        let signature = session.sign(&Mechanism::Eddsa, private_key, message)?;
+        Ed25519Signature::try_from(signature.as_slice())
+            .map_err(|e| Error::SerializationError(e.to_string()))
+    }
+}
+
+impl KVStorage for Pkcs11Storage {
+    fn available(&self) -> Result<(), Error> {
+        self.session()?.get_session_info()?;
+        Ok(())
+    }
+
+    fn get<V: DeserializeOwned>(&self, key: &str) -> Result<GetResponse<V>, Error> {
+        read_data(&self.session()?, key)?.ok_or_else(|| Error::KeyNotSet(key.to_string()))
+    }
+
+    fn set<V: Serialize>(&mut self, key: &str, value: V) -> Result<(), Error> {
+        let now = self.time_service.now_secs();
+        write_data(&self.session()?, key, &GetResponse::new(value, now))
+    }
+
+    /// Destroys every object on the token, not just the ones created by this storage engine, so
+    /// this must only be used against a dedicated test token.
+    #[cfg(any(test, feature = "testing"))]
+    fn reset_and_clear(&mut self) -> Result<(), Error> {
+        let session = self.session()?;
+        for handle in session.find_objects(&[])? {
+            session.destroy_object(handle)?;
+        }
+        Ok(())
+    }
+}
+
+impl CryptoStorage for Pkcs11Storage {
+    fn create_key(&mut self, name: &str) -> Result<Ed25519PublicKey, Error> {
+        let session = self.session()?;
+        self.destroy_ed25519_key(&session, name)?;
+        self.generate_ed25519_key(&session, name)
+    }
+
+    fn export_private_key(&self, _name: &str) -> Result<Ed25519PrivateKey, Error> {
+        Err(Error::PermissionDenied)
+    }
+
+    /// Imports the key as a sensitive, non-extractable object, so it can't be exported again.
+    fn import_private_key(&mut self, name: &str, key: Ed25519PrivateKey) -> Result<(), Error> {
+        let public_key = key.public_key();
+        let mut template = private_key_template(name, KeyType::EC_EDWARDS);
+        template.extend([
+            Attribute::EcParams(ED25519_EC_PARAMS.to_vec()),
+            Attribute::Value(key.to_bytes().to_vec()),
+            Attribute::Id(public_key.to_bytes().to_vec()),
+        ]);
+
+        let session = self.session()?;
+        self.destroy_ed25519_key(&session, name)?;
+        session.create_object(&template)?;
+        self.write_public_key(&session, name, &public_key)
+    }
+
+    fn export_private_key_for_version(
+        &self,
+        _name: &str,
+        _version: Ed25519PublicKey,
+    ) -> Result<Ed25519PrivateKey, Error> {
+        Err(Error::PermissionDenied)
+    }
+
+    fn get_public_key(&self, name: &str) -> Result<PublicKeyResponse, Error> {
+        self.read_public_key(&self.session()?, name)
+    }
+
+    fn get_public_key_previous_version(&self, name: &str) -> Result<Ed25519PublicKey, Error> {
+        match self.read_public_key(&self.session()?, &get_previous_version_name(name)) {
+            Ok(response) => Ok(response.public_key),
+            Err(Error::KeyNotSet(_)) => Err(Error::KeyVersionNotFound(
+                name.into(),
+                "previous version".into(),
+            )),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn rotate_key(&mut self, name: &str) -> Result<Ed25519PublicKey, Error> {
+        let session = self.session()?;
+        self.read_public_key(&session, name)?;
+        self.rename_ed25519_key(&session, name, &get_previous_version_name(name))?;
+        self.generate_ed25519_key(&session, name)
+    }
+
+    fn sign<T: CryptoHash + Serialize>(
+        &self,
+        name: &str,
+        message: &T,
+    ) -> Result<Ed25519Signature, Error> {
+        let session = self.session()?;
+        let private_key = find_object(&session, &private_key_template(name, KeyType::EC_EDWARDS))?
+            .ok_or_else(|| Error::KeyNotSet(name.to_string()))?;
+        let message =
+            signing_message(message).map_err(|e| Error::SerializationError(e.to_string()))?;
+        self.sign_with_key(&session, private_key, &message)
+    }
+
+    fn sign_using_version<T: CryptoHash + Serialize>(
+        &self,
+        name: &str,
+        version: Ed25519PublicKey,
+        message: &T,
+    ) -> Result<Ed25519Signature, Error> {
+        let session = self.session()?;
+        let mut private_key = None;
+        for label in [name.to_string(), get_previous_version_name(name)] {
+            let mut template = private_key_template(&label, KeyType::EC_EDWARDS);
+            template.push(Attribute::Id(version.to_bytes().to_vec()));
+            private_key = find_object(&session, &template)?;
+            if private_key.is_some() {
+                break;
+            }
+        }
+        let private_key = private_key
+            .ok_or_else(|| Error::KeyVersionNotFound(name.into(), version.to_string()))?;
+        let message =
+            signing_message(message).map_err(|e| Error::SerializationError(e.to_string()))?;
+        self.sign_with_key(&session, private_key, &message)
+    }
+}
+
+impl Bls12381Storage for Pkcs11Storage {
+    fn supports_bls12381_keys(&self) -> bool {
+        self.bls12381_mechanisms.is_some()
+    }
+
+    /// Requires the device to support BLS12-381 through the configured vendor-defined mechanisms.
+    fn create_bls12381_key(&mut self, name: &str) -> Result<bls12381::PublicKey, Error> {
+        let key_type = self.bls12381_key_type()?;
+        let key_pair_gen = VendorDefinedMechanism::new::<()>(
+            MechanismType::new_vendor_defined(self.bls12381_mechanisms()?.key_pair_gen)?,
+            None,
+        );
+
+        let session = self.session()?;
+        self.destroy_bls12381_key(&session, name)?;
+        let (public_handle, private_handle) = session.generate_key_pair(
+            &Mechanism::VendorDefined(key_pair_gen),
+            &public_key_template(name, key_type),
+            &private_key_template(name, key_type),
+        )?;
+        let public_key = read_bytes(&session, public_handle, AttributeType::Value)?;
+        session.destroy_object(public_handle)?;
+        let public_key = bls12381::PublicKey::try_from(public_key.as_slice())
+            .map_err(|e| Error::SerializationError(e.to_string()))?;
+
+        session.update_attributes(private_handle, &[Attribute::Id(
+            public_key.to_bytes().to_vec(),
+        )])?;
+        write_data(&session, &bls12381_public_key_data_label(name), &public_key)?;
+        Ok(public_key)
+    }
+
+    /// Imports the key as a sensitive, non-extractable object, so it can't be exported again.
+    fn import_bls12381_key(&mut self, name: &str, key: bls12381::PrivateKey) -> Result<(), Error> {
+        let public_key = key.public_key();
+        let mut template = private_key_template(name, self.bls12381_key_type()?);
+        template.extend([
+            Attribute::Value(key.to_bytes().to_vec()),
+            Attribute::Id(public_key.to_bytes().to_vec()),
+        ]);
+
+        let session = self.session()?;
+        self.destroy_bls12381_key(&session, name)?;
+        session.create_object(&template)?;
+        write_data(&session, &bls12381_public_key_data_label(name), &public_key)
+    }
+
+    fn get_bls12381_public_key(&self, name: &str) -> Result<bls12381::PublicKey, Error> {
+        read_data(&self.session()?, &bls12381_public_key_data_label(name))?
+            .ok_or_else(|| Error::KeyNotSet(name.to_string()))
+    }
+
+    fn sign_bls12381<T: CryptoHash + Serialize>(
+        &self,
+        name: &str,
+        message: &T,
+    ) -> Result<bls12381::Signature, Error> {
+        let key_type = self.bls12381_key_type()?;
+        let sign = VendorDefinedMechanism::new::<()>(
+            MechanismType::new_vendor_defined(self.bls12381_mechanisms()?.sign)?,
+            None,
+        );
+
+        let session = self.session()?;
+        let private_key = find_object(&session, &private_key_template(name, key_type))?
+            .ok_or_else(|| Error::KeyNotSet(name.to_string()))?;
+        let message =
+            signing_message(message).map_err(|e| Error::SerializationError(e.to_string()))?;
+        let signature = session.sign(&Mechanism::VendorDefined(sign), private_key, &message)?;
+        bls12381::Signature::try_from(signature.as_slice())
+            .map_err(|e| Error::SerializationError(e.to_string()))
+    }
+}
+
+impl From<cryptoki::error::Error> for Error {
+    fn from(error: cryptoki::error::Error) -> Self {
+        Self::InternalError(format!("PKCS#11 error: {}", error))
+    }
+}
+
+/// The public key of a key pair is kept in a data object next to the private key.
+fn public_key_data_label(name: &str) -> String {
+    format!("{}_public_key", name)
+}
+
+/// BLS12-381 public keys are kept apart from Ed25519 ones, so both kinds of keys can share a name.
+fn bls12381_public_key_data_label(name: &str) -> String {
+    format!("{}_bls12381_public_key", name)
+}
+
+fn public_key_template(name: &str, key_type: KeyType) -> Vec<Attribute> {
+    vec![
+        Attribute::Class(ObjectClass::PUBLIC_KEY),
+        Attribute::KeyType(key_type),
+        Attribute::Label(name.as_bytes().to_vec()),
+        Attribute::Token(false),
+        Attribute::Verify(true),
+    ]
+}
+
+/// Private keys are sensitive and can't be extracted, which keeps them inside the device.
+fn private_key_template(name: &str, key_type: KeyType) -> Vec<Attribute> {
+    vec![
+        Attribute::Class(ObjectClass::PRIVATE_KEY),
+        Attribute::KeyType(key_type),
+        Attribute::Label(name.as_bytes().to_vec()),
+        Attribute::Token(true),
+        Attribute::Private(true),
+        Attribute::Sensitive(true),
+        Attribute::Extractable(false),
+        Attribute::Sign(true),
+    ]
+}
+
+fn data_template(label: &str) -> Vec<Attribute> {
+    vec![
+        Attribute::Class(ObjectClass::DATA),
+        Attribute::Application(APPLICATION.to_vec()),
+        Attribute::Label(label.as_bytes().to_vec()),
+        Attribute::Token(true),
+        Attribute::Private(true),
+    ]
+}
+
+/// Returns the shared context of the PKCS#11 module at `library_path`, loading and initializing
+/// the module if this is its first use.
+fn context(library_path: &Path) -> Result<Pkcs11, Error> {
+    let mut contexts = CONTEXTS.lock();
+    if let Some(pkcs11) = contexts.get(library_path) {
+        return Ok(pkcs11.clone());
+    }
+
+    let pkcs11 = Pkcs11::new(library_path)?;
+    pkcs11.initialize(CInitializeArgs::OsThreads)?;
+    contexts.insert(library_path.to_path_buf(), pkcs11.clone());
+    Ok(pkcs11)
+}
+
+fn find_object(session: &Session, template: &[Attribute]) -> Result<Option<ObjectHandle>, Error> {
+    Ok(session.find_objects(template)?.into_iter().next())
+}
+
+fn destroy_objects(session: &Session, template: &[Attribute]) -> Result<(), Error> {
+    for handle in session.find_objects(template)? {
+        session.destroy_object(handle)?;
+    }
+    Ok(())
+}
+
+fn read_bytes(
+    session: &Session,
+    handle: ObjectHandle,
+    attribute_type: AttributeType,
+) -> Result<Vec<u8>, Error> {
+    match session.get_attributes(handle, &[attribute_type])?.pop() {
+        Some(Attribute::Value(bytes)) | Some(Attribute::EcPoint(bytes)) => Ok(bytes),
+        _ => Err(Error::InternalError(format!(
+            "PKCS#11 object is missing attribute: {}",
+            attribute_type
+        ))),
+    }
+}
+
+fn read_data<T: DeserializeOwned>(session: &Session, label: &str) -> Result<Option<T>, Error> {
+    match find_object(session, &data_template(label))? {
+        Some(handle) => {
+            let bytes = read_bytes(session, handle, AttributeType::Value)?;
+            Ok(Some(serde_json::from_slice(&bytes)?))
+        },
+        None => Ok(None),
+    }
+}
+
+fn write_data<T: Serialize>(session: &Session, label: &str, value: &T) -> Result<(), Error> {
+    let mut template = data_template(label);
+    destroy_objects(session, &template)?;
+    template.push(Attribute::Value(serde_json::to_vec(value)?));
+    session.create_object(&template)?;
+    Ok(())
+}
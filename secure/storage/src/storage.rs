@@ -2,10 +2,13 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 use crate::{
-    CryptoStorage, Error, GetResponse, InMemoryStorage, KVStorage, Namespaced, OnDiskStorage,
-    PublicKeyResponse, VaultStorage,
+    Bls12381Storage, CryptoStorage, Error, GetResponse, InMemoryStorage, KVStorage, Namespaced,
+    OnDiskStorage, Pkcs11Storage, PublicKeyResponse, VaultStorage,
+};
+use aptos_crypto::{
+    bls12381,
+    ed25519::{Ed25519PrivateKey, Ed25519PublicKey, Ed25519Signature},
 };
-use aptos_crypto::ed25519::{Ed25519PrivateKey, Ed25519PublicKey, Ed25519Signature};
 use enum_dispatch::enum_dispatch;
 use serde::{de::DeserializeOwned, Serialize};
 
@@ -15,12 +18,13 @@ use serde::{de::DeserializeOwned, Serialize};
 
 /// This is a hack that allows us to convert from SecureBackend into a useable
 /// T: Storage. This boilerplate can be 100% generated by a proc macro.
-#[enum_dispatch(KVStorage, CryptoStorage)]
+#[enum_dispatch(KVStorage, CryptoStorage, Bls12381Storage)]
 pub enum Storage {
     VaultStorage(VaultStorage),
     InMemoryStorage(InMemoryStorage),
     NamespacedStorage(Namespaced<Box<Storage>>),
     OnDiskStorage(OnDiskStorage),
+    Pkcs11Storage(Pkcs11Storage),
 }
 
 impl KVStorage for Box<Storage> {
@@ -92,3 +96,29 @@ impl CryptoStorage for Box<Storage> {
         Storage::sign_using_version(self, name, version, message)
     }
 }
+
+impl Bls12381Storage for Box<Storage> {
+    fn supports_bls12381_keys(&self) -> bool {
+        Storage::supports_bls12381_keys(self)
+    }
+
+    fn create_bls12381_key(&mut self, name: &str) -> Result<bls12381::PublicKey, Error> {
+        Storage::create_bls12381_key(self, name)
+    }
+
+    fn import_bls12381_key(&mut self, name: &str, key: bls12381::PrivateKey) -> Result<(), Error> {
+        Storage::import_bls12381_key(self, name, key)
+    }
+
+    fn get_bls12381_public_key(&self, name: &str) -> Result<bls12381::PublicKey, Error> {
+        Storage::get_bls12381_public_key(self, name)
+    }
+
+    fn sign_bls12381<T: aptos_crypto::hash::CryptoHash + Serialize>(
+        &self,
+        name: &str,
+        message: &T,
+    ) -> Result<bls12381::Signature, Error> {
+        Storage::sign_bls12381(self, name, message)
+    }
+}
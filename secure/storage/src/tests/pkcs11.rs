@@ -0,0 +1,220 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    Bls12381Mechanisms, Bls12381Storage, CryptoStorage, Error, KVStorage, Pkcs11Storage, Storage,
+};
+use aptos_crypto::{
+    bls12381, ed25519::Ed25519PrivateKey, test_utils::TestAptosCrypto, PrivateKey, Signature,
+    Uniform,
+};
+use std::path::PathBuf;
+
+/// Environment variables that point the tests at a PKCS#11 token
+const LIBRARY_ENV: &str = "APTOS_PKCS11_LIBRARY";
+const TOKEN_LABEL_ENV: &str = "APTOS_PKCS11_TOKEN_LABEL";
+const USER_PIN_ENV: &str = "APTOS_PKCS11_USER_PIN";
+/// The JSON encoded Bls12381Mechanisms of the token, if it supports BLS12-381 keys
+const BLS12381_MECHANISMS_ENV: &str = "APTOS_PKCS11_BLS12381_MECHANISMS";
+
+/// Pkcs11Storage data names
+const CRYPTO_NAME: &str = "crypto_key";
+const IMPORTED_NAME: &str = "imported_key";
+const U64_KEY: &str = "u64_key";
+
+/// This holds the canonical list of PKCS#11 storage tests. The tests share a single session,
+/// as a PKCS#11 module can only be initialized once per process, so they are run sequentially
+/// and the token is cleared after each test.
+const PKCS11_TESTS: &[fn(&mut Storage)] = &[
+    test_pkcs11_bls12381,
+    test_pkcs11_export_denied,
+    test_pkcs11_get_set,
+    test_pkcs11_import_sign,
+    test_pkcs11_rotate_sign_using_version,
+    test_pkcs11_shared_context,
+    test_pkcs11_sign_verify,
+];
+
+/// A test for verifying Pkcs11Storage properly implements the SecureStorage API. This test
+/// depends on a PKCS#11 token, which can be created locally with SoftHSM:
+///
+/// softhsm2-util --init-token --free --label aptos --pin 1234 --so-pin 5678
+/// APTOS_PKCS11_LIBRARY=/usr/lib/softhsm/libsofthsm2.so APTOS_PKCS11_TOKEN_LABEL=aptos \
+///     APTOS_PKCS11_USER_PIN=1234 cargo test -p aptos-secure-storage pkcs11
+#[test]
+fn execute_storage_tests_pkcs11() {
+    let mut storage = match create_pkcs11() {
+        Some(storage) => Storage::from(storage),
+        None => return,
+    };
+    storage.reset_and_clear().unwrap();
+
+    for test in PKCS11_TESTS.iter() {
+        test(&mut storage);
+        storage.reset_and_clear().unwrap();
+    }
+}
+
+/// Failures to read the user PIN or load the module are returned by the storage operations.
+#[test]
+fn test_pkcs11_unavailable() {
+    let storage = Pkcs11Storage::new(
+        PathBuf::from("/nonexistent/libpkcs11.so"),
+        "aptos".to_string(),
+        Err("no such file".to_string()),
+        None,
+    );
+    assert!(matches!(storage.available(), Err(Error::InternalError(_))));
+
+    let storage = Pkcs11Storage::new(
+        PathBuf::from("/nonexistent/libpkcs11.so"),
+        "aptos".to_string(),
+        Ok("1234".to_string()),
+        None,
+    );
+    assert!(matches!(storage.available(), Err(Error::InternalError(_))));
+    assert!(matches!(
+        storage.get::<u64>(U64_KEY),
+        Err(Error::InternalError(_))
+    ));
+}
+
+fn create_pkcs11() -> Option<Pkcs11Storage> {
+    let library_path = PathBuf::from(std::env::var(LIBRARY_ENV).ok()?);
+    let token_label = std::env::var(TOKEN_LABEL_ENV).ok()?;
+    let user_pin = std::env::var(USER_PIN_ENV).ok()?;
+    let bls12381_mechanisms = std::env::var(BLS12381_MECHANISMS_ENV)
+        .ok()
+        .map(|mechanisms| serde_json::from_str::<Bls12381Mechanisms>(&mechanisms).unwrap());
+    Some(Pkcs11Storage::new(
+        library_path,
+        token_label,
+        Ok(user_pin),
+        bls12381_mechanisms,
+    ))
+}
+
+/// BLS12-381 keys are only supported by tokens with vendor-defined mechanisms for them.
+fn test_pkcs11_bls12381(storage: &mut Storage) {
+    if !storage.supports_bls12381_keys() {
+        assert!(storage.create_bls12381_key(CRYPTO_NAME).is_err());
+        return;
+    }
+
+    let public_key = storage.create_bls12381_key(CRYPTO_NAME).unwrap();
+    assert_eq!(
+        storage.get_bls12381_public_key(CRYPTO_NAME).unwrap(),
+        public_key
+    );
+    let message = TestAptosCrypto("Hello, World".to_string());
+    let signature = storage.sign_bls12381(CRYPTO_NAME, &message).unwrap();
+    assert!(signature.verify(&message, &public_key).is_ok());
+
+    let private_key = bls12381::PrivateKey::generate_for_testing();
+    let public_key = private_key.public_key();
+    storage
+        .import_bls12381_key(IMPORTED_NAME, private_key)
+        .unwrap();
+    assert_eq!(
+        storage.get_bls12381_public_key(IMPORTED_NAME).unwrap(),
+        public_key
+    );
+    let signature = storage.sign_bls12381(IMPORTED_NAME, &message).unwrap();
+    assert!(signature.verify(&message, &public_key).is_ok());
+    assert_eq!(
+        storage
+            .get::<bls12381::PrivateKey>(IMPORTED_NAME)
+            .unwrap_err(),
+        Error::KeyNotSet(IMPORTED_NAME.to_string())
+    );
+}
+
+/// Private keys never leave the device, so exporting them must fail.
+fn test_pkcs11_export_denied(storage: &mut Storage) {
+    let public_key = storage.create_key(CRYPTO_NAME).unwrap();
+    assert_eq!(
+        storage.export_private_key(CRYPTO_NAME).unwrap_err(),
+        Error::PermissionDenied
+    );
+    assert_eq!(
+        storage
+            .export_private_key_for_version(CRYPTO_NAME, public_key)
+            .unwrap_err(),
+        Error::PermissionDenied
+    );
+}
+
+fn test_pkcs11_get_set(storage: &mut Storage) {
+    assert_eq!(
+        storage.get::<u64>(U64_KEY).unwrap_err(),
+        Error::KeyNotSet(U64_KEY.to_string())
+    );
+
+    storage.set(U64_KEY, 10u64).unwrap();
+    assert_eq!(storage.get::<u64>(U64_KEY).unwrap().value, 10);
+
+    storage.set(U64_KEY, 20u64).unwrap();
+    assert_eq!(storage.get::<u64>(U64_KEY).unwrap().value, 20);
+}
+
+fn test_pkcs11_import_sign(storage: &mut Storage) {
+    let private_key = Ed25519PrivateKey::generate_for_testing();
+    let public_key = private_key.public_key();
+    storage
+        .import_private_key(IMPORTED_NAME, private_key)
+        .unwrap();
+    assert_eq!(
+        storage.get_public_key(IMPORTED_NAME).unwrap().public_key,
+        public_key
+    );
+
+    let message = TestAptosCrypto("Hello, World".to_string());
+    let signature = storage.sign(IMPORTED_NAME, &message).unwrap();
+    assert!(signature.verify(&message, &public_key).is_ok());
+}
+
+fn test_pkcs11_rotate_sign_using_version(storage: &mut Storage) {
+    let public_key = storage.create_key(CRYPTO_NAME).unwrap();
+    let message = TestAptosCrypto("Hello, World".to_string());
+    let signature = storage.sign(CRYPTO_NAME, &message).unwrap();
+
+    let new_public_key = storage.rotate_key(CRYPTO_NAME).unwrap();
+    assert_ne!(public_key, new_public_key);
+    assert_eq!(
+        storage.get_public_key(CRYPTO_NAME).unwrap().public_key,
+        new_public_key
+    );
+    assert_eq!(
+        storage
+            .get_public_key_previous_version(CRYPTO_NAME)
+            .unwrap(),
+        public_key
+    );
+
+    let previous_signature = storage
+        .sign_using_version(CRYPTO_NAME, public_key.clone(), &message)
+        .unwrap();
+    assert_eq!(signature, previous_signature);
+
+    let new_signature = storage
+        .sign_using_version(CRYPTO_NAME, new_public_key.clone(), &message)
+        .unwrap();
+    assert!(new_signature.verify(&message, &new_public_key).is_ok());
+}
+
+/// Storages for the same module share its context, as it can only be initialized once.
+fn test_pkcs11_shared_context(storage: &mut Storage) {
+    let other_storage = create_pkcs11().unwrap();
+    storage.set(U64_KEY, 10u64).unwrap();
+    assert_eq!(other_storage.get::<u64>(U64_KEY).unwrap().value, 10);
+}
+
+fn test_pkcs11_sign_verify(storage: &mut Storage) {
+    let public_key = storage.create_key(CRYPTO_NAME).unwrap();
+    let message = TestAptosCrypto("Hello, World".to_string());
+    let signature = storage.sign(CRYPTO_NAME, &message).unwrap();
+    assert!(signature.verify(&message, &public_key).is_ok());
+
+    let other_message = TestAptosCrypto("Goodbye, World".to_string());
+    assert!(signature.verify(&other_message, &public_key).is_err());
+}
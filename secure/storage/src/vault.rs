@@ -3,7 +3,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    namespaced::NAMESPACE_SEPARATOR, CryptoStorage, Error, GetResponse, KVStorage,
+    namespaced::NAMESPACE_SEPARATOR, Bls12381Storage, CryptoStorage, Error, GetResponse, KVStorage,
     PublicKeyResponse,
 };
 use aptos_crypto::{
@@ -191,6 +191,8 @@ impl KVStorage for VaultStorage {
     }
 }
 
+impl Bls12381Storage for VaultStorage {}
+
 impl CryptoStorage for VaultStorage {
     fn create_key(&mut self, name: &str) -> Result<Ed25519PublicKey, Error> {
         let ns_name = self.crypto_name(name);
@@ -0,0 +1,20 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+#![no_main]
+use aptos_transaction_filter::{expression, Filterable};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &str| {
+    // Parsing must never panic, and anything it accepts must be a valid filter.
+    match expression::parse(data) {
+        Ok(filter) => {
+            filter
+                .is_valid()
+                .expect("Parsed filter expressions must be valid");
+        },
+        Err(e) => {
+            let _ = e.to_string();
+        },
+    }
+});
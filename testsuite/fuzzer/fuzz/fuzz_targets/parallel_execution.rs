@@ -0,0 +1,62 @@
+#![no_main]
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_language_e2e_tests::{
+    account::Account, common_transactions::peer_to_peer_txn, executor::FakeExecutor,
+};
+use arbitrary::Arbitrary;
+use libfuzzer_sys::{fuzz_target, Corpus};
+
+// Few accounts and long blocks, so that transactions conflict and get re-executed.
+const NUM_ACCOUNTS: usize = 8;
+const MAX_NUM_TXNS: usize = 100;
+const INITIAL_BALANCE: u64 = 1_000_000_000;
+
+#[derive(Arbitrary, Debug)]
+struct Transfer {
+    sender: u8,
+    receiver: u8,
+    // Amounts above the balance of the sender make the transfer abort.
+    amount: u32,
+}
+
+#[derive(Arbitrary, Debug)]
+struct FuzzData {
+    transfers: Vec<Transfer>,
+}
+
+// Executes a block of transfers between a few accounts sequentially and in parallel, which the
+// executor compares, panicking if the outputs differ.
+fn run(fuzz_data: FuzzData) -> Corpus {
+    if fuzz_data.transfers.is_empty() || fuzz_data.transfers.len() > MAX_NUM_TXNS {
+        return Corpus::Reject;
+    }
+
+    let mut executor = FakeExecutor::from_head_genesis().set_parallel();
+    let accounts: Vec<Account> = executor.create_accounts(NUM_ACCOUNTS, INITIAL_BALANCE, 0);
+    let mut sequence_numbers = [0; NUM_ACCOUNTS];
+    let txns = fuzz_data
+        .transfers
+        .iter()
+        .map(|transfer| {
+            let sender = transfer.sender as usize % NUM_ACCOUNTS;
+            let receiver = transfer.receiver as usize % NUM_ACCOUNTS;
+            let txn = peer_to_peer_txn(
+                &accounts[sender],
+                &accounts[receiver],
+                sequence_numbers[sender],
+                transfer.amount as u64,
+                100,
+            );
+            sequence_numbers[sender] += 1;
+            txn
+        })
+        .collect();
+    executor
+        .execute_block(txns)
+        .expect("Block execution must not fail");
+    Corpus::Keep
+}
+
+fuzz_target!(|fuzz_data: FuzzData| -> Corpus { run(fuzz_data) });
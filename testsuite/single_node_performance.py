@@ -135,6 +135,7 @@ class RunGroupKeyExtra:
     skip_commit_override: bool = field(default=False)
     single_block_dst_working_set: bool = field(default=False)
     execution_sharding: bool = field(default=False)
+    conflict_aware_execution: Optional[str] = field(default=None)
 
 
 @dataclass
@@ -185,6 +186,7 @@ TESTS = [
     RunGroupConfig(key=RunGroupKey("account-resource32-b"), included_in=Flow.CONTINUOUS),
     RunGroupConfig(key=RunGroupKey("modify-global-resource"), included_in=LAND_BLOCKING_AND_C | Flow.REPRESENTATIVE),
     RunGroupConfig(key=RunGroupKey("modify-global-resource", module_working_set_size=DEFAULT_MODULE_WORKING_SET_SIZE), included_in=Flow.CONTINUOUS),
+    RunGroupConfig(expected_tps=2300, key=RunGroupKey("modify-global-resource-conflict-aware"), key_extra=RunGroupKeyExtra(transaction_type_override="modify-global-resource", conflict_aware_execution="fallback"), included_in=Flow.CONTINUOUS, waived=True),
     RunGroupConfig(key=RunGroupKey("publish-package"), included_in=LAND_BLOCKING_AND_C | Flow.REPRESENTATIVE | Flow.MAINNET),
     RunGroupConfig(key=RunGroupKey("mix_publish_transfer"), key_extra=RunGroupKeyExtra(
         transaction_type_override="publish-package apt-fa-transfer",
@@ -769,6 +771,11 @@ with tempfile.TemporaryDirectory() as tmpdirname:
         else:
             raise Exception(f"executor type not supported {test.key.executor_type}")
 
+        if test.key_extra.conflict_aware_execution:
+            pipeline_extra_args.append(
+                f"--conflict-aware-execution {test.key_extra.conflict_aware_execution}"
+            )
+
         if test.key_extra.execution_sharding:
             pipeline_extra_args.append(
                 f"--num-executor-shards {number_of_execution_threads}"
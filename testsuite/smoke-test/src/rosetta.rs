@@ -114,6 +114,7 @@ async fn setup_test(
             validator.rest_api_endpoint(),
         )),
         HashSet::new(),
+        false,
     )
     .await
     .unwrap();
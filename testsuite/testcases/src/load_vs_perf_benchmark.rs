@@ -229,6 +229,7 @@ impl TransactionWorkload {
                 EmitJobMode::MaxLoad { mempool_backlog } =>
                     format!("B:{:.1}k", mempool_backlog as f32 / 1000.0),
                 EmitJobMode::ConstTps { tps } => format!("T:{:.1}k", tps as f32 / 1000.0),
+                EmitJobMode::OpenLoop { tps } => format!("O:{:.1}k", tps as f32 / 1000.0),
                 EmitJobMode::WaveTps { average_tps, .. } =>
                     format!("T:~{:.1}k", average_tps as f32 / 1000.0),
             },
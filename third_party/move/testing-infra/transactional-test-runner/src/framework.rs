@@ -188,7 +188,11 @@ pub trait MoveTestAdapter<'a>: Sized {
         pre_compiled_deps_v1: Option<&'a (FullyCompiledProgram, Vec<PackagePaths>)>,
         pre_compiled_deps_v2: Option<&'a PrecompiledFilesModules>,
         init_data: Option<TaskInput<(InitCommand, Self::ExtraInitArgs)>>,
-    ) -> (Self, Option<String>);
+    ) -> Result<(Self, Option<String>)>;
+    /// Called once all tasks of a test have been run.
+    fn finish(&mut self) -> Result<()> {
+        Ok(())
+    }
     fn publish_module(
         &mut self,
         module: CompiledModule,
@@ -1046,13 +1050,14 @@ where
             pre_compiled_deps_v1,
             pre_compiled_deps_v2,
             init_opt,
-        );
+        )?;
         if let Some(result) = result_opt {
             writeln!(output, "\ninit:\n{}", result)?;
         }
         for task in tasks {
             handle_known_task(&mut output, &mut adapter, task);
         }
+        adapter.finish()?;
         // Extract any bytecode outputs, they should not be part of the diff.
         static BYTECODE_REX: Lazy<Regex> = Lazy::new(|| {
             Regex::new("(?m)== BEGIN Bytecode ==(.|\n|\r)*== END Bytecode ==").unwrap()
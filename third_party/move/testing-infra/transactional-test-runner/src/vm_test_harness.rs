@@ -149,7 +149,7 @@ impl<'a> MoveTestAdapter<'a> for SimpleVMTestAdapter<'a> {
         pre_compiled_deps_v1: Option<&'a (FullyCompiledProgram, Vec<PackagePaths>)>,
         pre_compiled_deps_v2: Option<&'a PrecompiledFilesModules>,
         task_opt: Option<TaskInput<(InitCommand, EmptyCommand)>>,
-    ) -> (Self, Option<String>) {
+    ) -> Result<(Self, Option<String>)> {
         let additional_mapping = match task_opt.map(|t| t.command) {
             Some((InitCommand { named_addresses }, _)) => {
                 verify_and_create_named_address_mapping(named_addresses).unwrap()
@@ -273,7 +273,7 @@ impl<'a> MoveTestAdapter<'a> for SimpleVMTestAdapter<'a> {
                 .compiled_state
                 .add_and_generate_interface_file(module.clone())
         }
-        (adapter, None)
+        Ok((adapter, None))
     }
 
     fn publish_module(
@@ -2,7 +2,7 @@
 // Parts of the project are originally copyright (c) Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use anyhow::Context;
+use anyhow::{bail, Context};
 use clap::Parser;
 use codespan::Span;
 use codespan_reporting::{diagnostic::Severity, term::termcolor::WriteColor};
@@ -20,6 +20,8 @@ use move_stackless_bytecode::{
 };
 use std::{collections::BTreeMap, fs, io::Write, mem, path::Path, rc::Rc};
 
+pub mod round_trip;
+
 #[derive(Parser, Clone, Debug, Default)]
 #[clap(author, version, about)]
 pub struct Options {
@@ -57,6 +59,17 @@ pub struct Options {
     #[clap(long, default_value = "")]
     pub source_map_dir: String,
 
+    /// Verify the decompiled modules by recompiling them and comparing the result with the
+    /// original bytecode. Every discrepancy found is reported. Only supported for modules.
+    #[clap(long, default_value = "false", conflicts_with = "script")]
+    pub verify: bool,
+
+    /// Additional bytecode files of modules the inputs depend on, which are needed
+    /// to recompile the decompiled sources with `--verify`. The other inputs are
+    /// always available as dependencies.
+    #[clap(long, num_args = 0.., requires = "verify")]
+    pub dependencies: Vec<String>,
+
     /// Input files, interpreted as serialized versions of a module
     /// or script, depending on the `--script` flag.
     pub inputs: Vec<String>,
@@ -85,6 +98,17 @@ impl Decompiler {
     where
         W: Write + WriteColor,
     {
+        if self.options.verify && self.options.script {
+            bail!("verification of decompiled scripts is not supported")
+        }
+        let verify_dependencies = if self.options.verify {
+            let mut paths = self.options.inputs.clone();
+            paths.extend(self.options.dependencies.iter().cloned());
+            round_trip::read_modules(&paths)?
+        } else {
+            vec![]
+        };
+        let mut failed_verifications = 0;
         for input_path in mem::take(&mut self.options.inputs) {
             let input_file = Path::new(Path::new(&input_path).file_name().unwrap_or_default())
                 .to_string_lossy()
@@ -100,7 +124,40 @@ impl Decompiler {
                 let module = CompiledModule::deserialize(&bytes)
                     .with_context(|| format!("deserializing module `{}`", input_path))?;
                 let source_map = self.get_source_map(&input_path, &input_file, &bytes);
-                self.decompile_module(module, source_map)
+                let output = self.decompile_module(module.clone(), source_map);
+                if self.options.verify && !self.env.has_errors() {
+                    // Failures are reported per module, so that the remaining modules are still
+                    // decompiled and verified.
+                    match round_trip::verify_round_trip(
+                        &module,
+                        &output,
+                        &verify_dependencies,
+                        self.options.language_version,
+                    ) {
+                        Ok(discrepancies) if discrepancies.is_empty() => {},
+                        Ok(discrepancies) => {
+                            failed_verifications += 1;
+                            writeln!(
+                                error_writer,
+                                "verification of `{}` failed with {} discrepancies:",
+                                input_path,
+                                discrepancies.len()
+                            )?;
+                            for discrepancy in discrepancies {
+                                writeln!(error_writer, "  {}", discrepancy)?;
+                            }
+                        },
+                        Err(err) => {
+                            failed_verifications += 1;
+                            writeln!(
+                                error_writer,
+                                "verification of `{}` failed: {:#}",
+                                input_path, err
+                            )?;
+                        },
+                    }
+                }
+                output
             };
             self.env.check_diag(
                 error_writer,
@@ -113,6 +170,12 @@ impl Decompiler {
             fs::write(&out_file, output)
                 .with_context(|| format!("writing `{}`", out_file.display()))?;
         }
+        if failed_verifications > 0 {
+            bail!(
+                "verification failed for {} decompiled modules",
+                failed_verifications
+            )
+        }
         Ok(())
     }
 
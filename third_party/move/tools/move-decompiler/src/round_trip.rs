@@ -0,0 +1,1025 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Round-trip verification of decompiled code: the decompiled source is recompiled with the
+//! Move 2 compiler, and the result is compared structurally with the original bytecode. Struct
+//! layouts and function signatures must match exactly. Function bodies are compared by their
+//! control flow graphs, normalized so that differences in code generation (e.g. how values are
+//! moved between locals, or how branches are laid out) are not reported.
+
+use anyhow::{anyhow, bail, Context};
+use codespan_reporting::term::termcolor::Buffer;
+use move_binary_format::{
+    access::ModuleAccess,
+    file_format::{
+        Bytecode, CodeOffset, FieldDefinition, FunctionDefinition, FunctionHandleIndex,
+        ModuleHandleIndex, SignatureIndex, SignatureToken, StructDefinition, StructDefinitionIndex,
+        StructFieldInformation, StructHandleIndex, StructTypeParameter,
+    },
+    CompiledModule,
+};
+use move_compiler::{compiled_unit::AnnotatedCompiledUnit, interface_generator};
+use move_core_types::language_storage::ModuleId;
+use move_model::metadata::LanguageVersion;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt, fs, mem,
+};
+
+/// A difference between the original module and the recompiled one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Discrepancy {
+    /// The module member (struct or function) which differs, or the module itself.
+    pub item: String,
+    pub message: String,
+}
+
+impl fmt::Display for Discrepancy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.item, self.message)
+    }
+}
+
+/// Recompiles `source`, the decompiled source of `original`, and compares the result with
+/// `original`. The `dependencies` must contain all modules used by `original`; they are
+/// provided to the compiler as interfaces only.
+///
+/// Returns an error if the source does not compile, and otherwise the list of discrepancies,
+/// which is empty if the round-trip succeeded.
+pub fn verify_round_trip(
+    original: &CompiledModule,
+    source: &str,
+    dependencies: &[CompiledModule],
+    language_version: Option<LanguageVersion>,
+) -> anyhow::Result<Vec<Discrepancy>> {
+    let recompiled = recompile(original.self_id(), source, dependencies, language_version)?;
+    Ok(compare_modules(original, &recompiled))
+}
+
+fn recompile(
+    module_id: ModuleId,
+    source: &str,
+    dependencies: &[CompiledModule],
+    language_version: Option<LanguageVersion>,
+) -> anyhow::Result<CompiledModule> {
+    let temp_dir = tempfile::tempdir()?;
+    let source_path = temp_dir.path().join("decompiled.move");
+    fs::write(&source_path, source)?;
+
+    let deps_dir = temp_dir.path().join("deps");
+    fs::create_dir(&deps_dir)?;
+    let no_named_addresses: BTreeMap<ModuleId, String> = BTreeMap::new();
+    for (idx, dep) in dependencies
+        .iter()
+        .filter(|dep| dep.self_id() != module_id)
+        .enumerate()
+    {
+        let (_, interface) = interface_generator::write_module_to_string(&no_named_addresses, dep)?;
+        fs::write(deps_dir.join(format!("dep{}.move", idx)), interface)?;
+    }
+
+    let options = move_compiler_v2::Options {
+        sources: vec![source_path.display().to_string()],
+        dependencies: vec![deps_dir.display().to_string()],
+        ..Default::default()
+    }
+    .set_language_version(language_version.unwrap_or_else(LanguageVersion::latest));
+    let mut error_writer = Buffer::no_color();
+    let (_, units) =
+        move_compiler_v2::run_move_compiler(&mut error_writer, options).map_err(|err| {
+            anyhow!(
+                "decompiled source does not compile: {:#}\n{}",
+                err,
+                String::from_utf8_lossy(&error_writer.into_inner())
+            )
+        })?;
+    for unit in units {
+        if let AnnotatedCompiledUnit::Module(unit) = unit {
+            if unit.named_module.module.self_id() == module_id {
+                return Ok(unit.named_module.module);
+            }
+        }
+    }
+    bail!("decompiled source does not contain module `{}`", module_id)
+}
+
+/// Compares two versions of a module structurally, returning all differences.
+pub fn compare_modules(original: &CompiledModule, recompiled: &CompiledModule) -> Vec<Discrepancy> {
+    let mut discrepancies = vec![];
+    let module_name = original.self_id().short_str_lossless();
+    let mut report = |item: &str, message: String| {
+        discrepancies.push(Discrepancy {
+            item: item.to_string(),
+            message,
+        })
+    };
+
+    if original.self_id() != recompiled.self_id() {
+        report(
+            &module_name,
+            format!(
+                "module id differs: `{}` was recompiled as `{}`",
+                module_name,
+                recompiled.self_id().short_str_lossless()
+            ),
+        );
+        return discrepancies;
+    }
+
+    let friends = |m: &CompiledModule| -> BTreeSet<String> {
+        m.immediate_friends()
+            .iter()
+            .map(|id| id.short_str_lossless())
+            .collect()
+    };
+    compare(
+        &mut report,
+        &module_name,
+        "friends",
+        friends(original),
+        friends(recompiled),
+    );
+
+    // Structs
+    let original_structs = structs_by_name(original);
+    let recompiled_structs = structs_by_name(recompiled);
+    for (name, def) in &original_structs {
+        let item = format!("{}::{}", module_name, name);
+        let Some(recompiled_def) = recompiled_structs.get(name) else {
+            report(&item, "struct is missing".to_string());
+            continue;
+        };
+        compare(
+            &mut report,
+            &item,
+            "struct declaration",
+            StructView::new(original, def),
+            StructView::new(recompiled, recompiled_def),
+        );
+    }
+    for name in recompiled_structs.keys() {
+        if !original_structs.contains_key(name) {
+            report(
+                &format!("{}::{}", module_name, name),
+                "unexpected struct".to_string(),
+            );
+        }
+    }
+
+    // Functions
+    let original_functions = functions_by_name(original);
+    let recompiled_functions = functions_by_name(recompiled);
+    for (name, def) in &original_functions {
+        let item = format!("{}::{}", module_name, name);
+        let Some(recompiled_def) = recompiled_functions.get(name) else {
+            report(&item, "function is missing".to_string());
+            continue;
+        };
+        compare(
+            &mut report,
+            &item,
+            "function signature",
+            FunctionView::new(original, def),
+            FunctionView::new(recompiled, recompiled_def),
+        );
+        compare_code(
+            &mut report,
+            &item,
+            original,
+            def,
+            recompiled,
+            recompiled_def,
+        );
+    }
+    for name in recompiled_functions.keys() {
+        if !original_functions.contains_key(name) {
+            report(
+                &format!("{}::{}", module_name, name),
+                "unexpected function".to_string(),
+            );
+        }
+    }
+
+    discrepancies
+}
+
+fn compare<T: PartialEq + fmt::Debug>(
+    report: &mut impl FnMut(&str, String),
+    item: &str,
+    what: &str,
+    original: T,
+    recompiled: T,
+) {
+    if original != recompiled {
+        report(
+            item,
+            format!(
+                "{} differs:\n  original:   {:?}\n  recompiled: {:?}",
+                what, original, recompiled
+            ),
+        )
+    }
+}
+
+fn compare_code(
+    report: &mut impl FnMut(&str, String),
+    item: &str,
+    original: &CompiledModule,
+    original_def: &FunctionDefinition,
+    recompiled: &CompiledModule,
+    recompiled_def: &FunctionDefinition,
+) {
+    let original_code = CodeView::new(original, original_def);
+    let recompiled_code = CodeView::new(recompiled, recompiled_def);
+    if original_code.blocks.len() != recompiled_code.blocks.len() {
+        report(
+            item,
+            format!(
+                "control flow differs: the original code has {} block(s) and the recompiled \
+                 code {}",
+                original_code.blocks.len(),
+                recompiled_code.blocks.len()
+            ),
+        );
+    }
+    // Only the first differing block is reported, as the blocks after it are usually shifted.
+    if let Some((idx, (original_block, recompiled_block))) = original_code
+        .blocks
+        .iter()
+        .zip(recompiled_code.blocks.iter())
+        .enumerate()
+        .find(|(_, (original_block, recompiled_block))| original_block != recompiled_block)
+    {
+        compare(
+            report,
+            item,
+            &format!("block {}", idx),
+            original_block,
+            recompiled_block,
+        );
+    }
+}
+
+fn structs_by_name(m: &CompiledModule) -> BTreeMap<String, &StructDefinition> {
+    m.struct_defs()
+        .iter()
+        .map(|def| {
+            let name = m.identifier_at(m.struct_handle_at(def.struct_handle).name);
+            (name.to_string(), def)
+        })
+        .collect()
+}
+
+fn functions_by_name(m: &CompiledModule) -> BTreeMap<String, &FunctionDefinition> {
+    m.function_defs()
+        .iter()
+        .map(|def| {
+            let name = m.identifier_at(m.function_handle_at(def.function).name);
+            (name.to_string(), def)
+        })
+        .collect()
+}
+
+/// Module independent representation of a struct declaration.
+#[derive(Debug, PartialEq)]
+struct StructView {
+    abilities: String,
+    type_parameters: Vec<String>,
+    layout: Option<Vec<(String, Vec<(String, String)>)>>,
+}
+
+impl StructView {
+    fn new(m: &CompiledModule, def: &StructDefinition) -> Self {
+        let handle = m.struct_handle_at(def.struct_handle);
+        let fields = |fields: &[FieldDefinition]| {
+            fields
+                .iter()
+                .map(|field| {
+                    (
+                        m.identifier_at(field.name).to_string(),
+                        type_str(m, &field.signature.0),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        let layout = match &def.field_information {
+            StructFieldInformation::Native => None,
+            StructFieldInformation::Declared(declared) => {
+                Some(vec![(String::new(), fields(declared))])
+            },
+            StructFieldInformation::DeclaredVariants(variants) => Some(
+                variants
+                    .iter()
+                    .map(|variant| {
+                        (
+                            m.identifier_at(variant.name).to_string(),
+                            fields(&variant.fields),
+                        )
+                    })
+                    .collect(),
+            ),
+        };
+        Self {
+            abilities: format!("{:?}", handle.abilities),
+            type_parameters: handle
+                .type_parameters
+                .iter()
+                .map(
+                    |StructTypeParameter {
+                         constraints,
+                         is_phantom,
+                     }| {
+                        format!(
+                            "{:?}{}",
+                            constraints,
+                            if *is_phantom { " phantom" } else { "" }
+                        )
+                    },
+                )
+                .collect(),
+            layout,
+        }
+    }
+}
+
+/// Module independent representation of a function signature.
+#[derive(Debug, PartialEq)]
+struct FunctionView {
+    visibility: String,
+    is_entry: bool,
+    is_native: bool,
+    type_parameters: Vec<String>,
+    parameters: Vec<String>,
+    returns: Vec<String>,
+    acquires: BTreeSet<String>,
+}
+
+impl FunctionView {
+    fn new(m: &CompiledModule, def: &FunctionDefinition) -> Self {
+        let handle = m.function_handle_at(def.function);
+        Self {
+            visibility: format!("{:?}", def.visibility),
+            is_entry: def.is_entry,
+            is_native: def.is_native(),
+            type_parameters: handle
+                .type_parameters
+                .iter()
+                .map(|abilities| format!("{:?}", abilities))
+                .collect(),
+            parameters: signature_strs(m, handle.parameters),
+            returns: signature_strs(m, handle.return_),
+            acquires: def
+                .acquires_global_resources
+                .iter()
+                .map(|idx| struct_def_str(m, *idx))
+                .collect(),
+        }
+    }
+}
+
+/// Module independent representation of a function body, as its control flow graph.
+///
+/// Instructions which only move values between locals and the stack are left out, as they depend
+/// on code generation rather than on the source. For the same reason, blocks which only jump to
+/// another block are skipped, a block is merged with its successor if it is the only predecessor
+/// of it, and a negation right before a branch is folded into the branch. Blocks are numbered in
+/// depth-first order from the entry, visiting the target taken on `true` first, so that the
+/// numbering does not depend on how the blocks are laid out. Borrowed locals are represented by
+/// their type unless they are parameters.
+#[derive(Debug, PartialEq)]
+struct CodeView {
+    blocks: Vec<BlockView>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct BlockView {
+    operations: Vec<String>,
+    exit: Exit,
+}
+
+/// How control leaves a block.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Exit {
+    Abort,
+    Goto(Successor),
+    Branch {
+        if_true: Successor,
+        if_false: Successor,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Successor {
+    Block(usize),
+    Return,
+}
+
+impl Exit {
+    fn successors(&self) -> Vec<usize> {
+        let block = |successor: &Successor| match successor {
+            Successor::Block(idx) => Some(*idx),
+            Successor::Return => None,
+        };
+        match self {
+            Exit::Abort => vec![],
+            Exit::Goto(successor) => block(successor).into_iter().collect(),
+            Exit::Branch { if_true, if_false } => {
+                block(if_true).into_iter().chain(block(if_false)).collect()
+            },
+        }
+    }
+
+    fn map(self, f: impl Fn(Successor) -> Successor) -> Exit {
+        match self {
+            Exit::Abort => Exit::Abort,
+            Exit::Goto(successor) => Exit::Goto(f(successor)),
+            Exit::Branch { if_true, if_false } => Exit::Branch {
+                if_true: f(if_true),
+                if_false: f(if_false),
+            },
+        }
+    }
+}
+
+impl CodeView {
+    fn new(m: &CompiledModule, def: &FunctionDefinition) -> Self {
+        let Some(code) = &def.code else {
+            return Self { blocks: vec![] };
+        };
+        let num_params = m
+            .signature_at(m.function_handle_at(def.function).parameters)
+            .len();
+        let declared_locals = &m.signature_at(code.locals).0;
+        let mut local = |idx: u8| -> String {
+            let idx = idx as usize;
+            if idx < num_params {
+                format!("p{}", idx)
+            } else {
+                format!(
+                    "local: {}",
+                    declared_locals
+                        .get(idx - num_params)
+                        .map(|ty| type_str(m, ty))
+                        .unwrap_or_default()
+                )
+            }
+        };
+
+        // Split the code into basic blocks
+        let mut starts = BTreeSet::from([0]);
+        for (offset, instr) in code.code.iter().enumerate() {
+            if let Some(target) = instr.offset() {
+                starts.insert(*target as usize);
+            }
+            if instr.is_branch() {
+                starts.insert(offset + 1);
+            }
+        }
+        starts.retain(|start| *start < code.code.len());
+        let starts = starts.into_iter().collect::<Vec<_>>();
+        let block_at = |offset: &CodeOffset| {
+            Successor::Block(
+                starts
+                    .binary_search(&(*offset as usize))
+                    .expect("branch target starts a block"),
+            )
+        };
+        let mut blocks = vec![];
+        for (idx, start) in starts.iter().enumerate() {
+            use Bytecode::*;
+            let end = starts.get(idx + 1).copied().unwrap_or(code.code.len());
+            let instrs = &code.code[*start..end];
+            let mut operations = vec![];
+            let mut negated = false;
+            for (pos, instr) in instrs.iter().enumerate() {
+                match instr {
+                    CopyLoc(_) | MoveLoc(_) | StLoc(_) | Pop | Nop | FreezeRef => {},
+                    Ret | Abort | Branch(_) | BrTrue(_) | BrFalse(_) => {},
+                    Not if matches!(instrs.get(pos + 1), Some(BrTrue(_) | BrFalse(_))) => {
+                        negated = true
+                    },
+                    _ => operations.push(instruction_str(m, instr, &mut local)),
+                }
+            }
+            let next = Successor::Block(idx + 1);
+            let exit = match instrs.last() {
+                Some(Ret) => Exit::Goto(Successor::Return),
+                Some(Abort) => Exit::Abort,
+                Some(Branch(target)) => Exit::Goto(block_at(target)),
+                Some(BrTrue(target)) if negated => Exit::Branch {
+                    if_true: next,
+                    if_false: block_at(target),
+                },
+                Some(BrTrue(target)) => Exit::Branch {
+                    if_true: block_at(target),
+                    if_false: next,
+                },
+                Some(BrFalse(target)) if negated => Exit::Branch {
+                    if_true: block_at(target),
+                    if_false: next,
+                },
+                Some(BrFalse(target)) => Exit::Branch {
+                    if_true: next,
+                    if_false: block_at(target),
+                },
+                _ => Exit::Goto(next),
+            };
+            blocks.push(BlockView { operations, exit });
+        }
+        Self::normalize(blocks)
+    }
+
+    /// Normalizes the basic blocks of a function, with the entry block first.
+    fn normalize(mut blocks: Vec<BlockView>) -> Self {
+        let entry = match Self::resolve(&blocks, Successor::Block(0)) {
+            Successor::Block(entry) => entry,
+            Successor::Return => {
+                return Self {
+                    blocks: vec![BlockView {
+                        operations: vec![],
+                        exit: Exit::Goto(Successor::Return),
+                    }],
+                }
+            },
+        };
+        let exits = blocks
+            .iter()
+            .map(|block| {
+                block
+                    .exit
+                    .map(|successor| Self::resolve(&blocks, successor))
+            })
+            .collect::<Vec<_>>();
+        for (block, exit) in blocks.iter_mut().zip(exits) {
+            block.exit = exit;
+        }
+
+        // Count the predecessors of the reachable blocks. The entry has an implicit one.
+        let mut predecessors = vec![0; blocks.len()];
+        predecessors[entry] += 1;
+        let mut reached = vec![false; blocks.len()];
+        let mut todo = vec![entry];
+        while let Some(idx) = todo.pop() {
+            if !mem::replace(&mut reached[idx], true) {
+                for successor in blocks[idx].exit.successors() {
+                    predecessors[successor] += 1;
+                    todo.push(successor);
+                }
+            }
+        }
+
+        // Number the blocks in depth-first order, merging each block with the successors it is
+        // the only predecessor of.
+        let mut numbers = vec![None; blocks.len()];
+        let mut merged = vec![];
+        let mut todo = vec![entry];
+        while let Some(idx) = todo.pop() {
+            if numbers[idx].is_some() {
+                continue;
+            }
+            numbers[idx] = Some(merged.len());
+            let mut block = blocks[idx].clone();
+            while let Exit::Goto(Successor::Block(next)) = block.exit {
+                if predecessors[next] != 1 {
+                    break;
+                }
+                block
+                    .operations
+                    .extend(blocks[next].operations.iter().cloned());
+                block.exit = blocks[next].exit;
+            }
+            // Visit the target taken on `true` first
+            todo.extend(block.exit.successors().into_iter().rev());
+            merged.push(block);
+        }
+        Self {
+            blocks: merged
+                .into_iter()
+                .map(|block| BlockView {
+                    operations: block.operations,
+                    exit: block.exit.map(|successor| match successor {
+                        Successor::Block(idx) => Successor::Block(
+                            numbers[idx].expect("successor of a reachable block is numbered"),
+                        ),
+                        Successor::Return => Successor::Return,
+                    }),
+                })
+                .collect(),
+        }
+    }
+
+    /// Follows the jumps through blocks without operations, starting at `successor`.
+    fn resolve(blocks: &[BlockView], mut successor: Successor) -> Successor {
+        let mut visited = BTreeSet::new();
+        while let Successor::Block(idx) = successor {
+            match blocks[idx].exit {
+                Exit::Goto(next) if blocks[idx].operations.is_empty() && visited.insert(idx) => {
+                    successor = next
+                },
+                _ => break,
+            }
+        }
+        successor
+    }
+}
+
+fn instruction_str(
+    m: &CompiledModule,
+    instr: &Bytecode,
+    local: &mut impl FnMut(u8) -> String,
+) -> String {
+    use Bytecode::*;
+    let struct_inst = |idx| {
+        let inst = m.struct_instantiation_at(idx);
+        format!(
+            "{}{}",
+            struct_def_str(m, inst.def),
+            type_args_str(m, inst.type_parameters)
+        )
+    };
+    let field = |idx| {
+        let handle = m.field_handle_at(idx);
+        let def = m.struct_def_at(handle.owner);
+        let name = match &def.field_information {
+            StructFieldInformation::Declared(fields) => fields
+                .get(handle.field as usize)
+                .map(|field| m.identifier_at(field.name).to_string())
+                .unwrap_or_default(),
+            _ => handle.field.to_string(),
+        };
+        format!("{}.{}", struct_def_str(m, handle.owner), name)
+    };
+    let variant_field = |idx| {
+        let handle = m.variant_field_handle_at(idx);
+        format!(
+            "{}{:?}.{}",
+            struct_def_str(m, handle.struct_index),
+            handle.variants,
+            handle.field
+        )
+    };
+    let variant = |idx| {
+        let handle = m.struct_variant_handle_at(idx);
+        format!(
+            "{}::{}",
+            struct_def_str(m, handle.struct_index),
+            handle.variant
+        )
+    };
+    match instr {
+        MutBorrowLoc(idx) => format!("MutBorrowLoc({})", local(*idx)),
+        ImmBorrowLoc(idx) => format!("ImmBorrowLoc({})", local(*idx)),
+        LdConst(idx) => {
+            let constant = m.constant_at(*idx);
+            format!(
+                "LdConst({}, {})",
+                type_str(m, &constant.type_),
+                hex::encode(&constant.data)
+            )
+        },
+        Call(idx) => format!("Call({})", function_str(m, *idx)),
+        CallGeneric(idx) => {
+            let inst = m.function_instantiation_at(*idx);
+            format!(
+                "Call({}{})",
+                function_str(m, inst.handle),
+                type_args_str(m, inst.type_parameters)
+            )
+        },
+        Pack(idx) => format!("Pack({})", struct_def_str(m, *idx)),
+        PackGeneric(idx) => format!("Pack({})", struct_inst(*idx)),
+        Unpack(idx) => format!("Unpack({})", struct_def_str(m, *idx)),
+        UnpackGeneric(idx) => format!("Unpack({})", struct_inst(*idx)),
+        PackVariant(idx) => format!("PackVariant({})", variant(*idx)),
+        UnpackVariant(idx) => format!("UnpackVariant({})", variant(*idx)),
+        TestVariant(idx) => format!("TestVariant({})", variant(*idx)),
+        PackVariantGeneric(idx) | UnpackVariantGeneric(idx) | TestVariantGeneric(idx) => {
+            let inst = m.struct_variant_instantiation_at(*idx);
+            let op = match instr {
+                PackVariantGeneric(_) => "PackVariant",
+                UnpackVariantGeneric(_) => "UnpackVariant",
+                _ => "TestVariant",
+            };
+            format!(
+                "{}({}{})",
+                op,
+                variant(inst.handle),
+                type_args_str(m, inst.type_parameters)
+            )
+        },
+        MutBorrowField(idx) => format!("MutBorrowField({})", field(*idx)),
+        ImmBorrowField(idx) => format!("ImmBorrowField({})", field(*idx)),
+        MutBorrowFieldGeneric(idx) | ImmBorrowFieldGeneric(idx) => {
+            let inst = m.field_instantiation_at(*idx);
+            let op = match instr {
+                MutBorrowFieldGeneric(_) => "MutBorrowField",
+                _ => "ImmBorrowField",
+            };
+            format!(
+                "{}({}{})",
+                op,
+                field(inst.handle),
+                type_args_str(m, inst.type_parameters)
+            )
+        },
+        MutBorrowVariantField(idx) => format!("MutBorrowVariantField({})", variant_field(*idx)),
+        ImmBorrowVariantField(idx) => format!("ImmBorrowVariantField({})", variant_field(*idx)),
+        MutBorrowVariantFieldGeneric(idx) | ImmBorrowVariantFieldGeneric(idx) => {
+            let inst = m.variant_field_instantiation_at(*idx);
+            let op = match instr {
+                MutBorrowVariantFieldGeneric(_) => "MutBorrowVariantField",
+                _ => "ImmBorrowVariantField",
+            };
+            format!(
+                "{}({}{})",
+                op,
+                variant_field(inst.handle),
+                type_args_str(m, inst.type_parameters)
+            )
+        },
+        MutBorrowGlobal(idx) => format!("MutBorrowGlobal({})", struct_def_str(m, *idx)),
+        ImmBorrowGlobal(idx) => format!("ImmBorrowGlobal({})", struct_def_str(m, *idx)),
+        Exists(idx) => format!("Exists({})", struct_def_str(m, *idx)),
+        MoveFrom(idx) => format!("MoveFrom({})", struct_def_str(m, *idx)),
+        MoveTo(idx) => format!("MoveTo({})", struct_def_str(m, *idx)),
+        MutBorrowGlobalGeneric(idx) => format!("MutBorrowGlobal({})", struct_inst(*idx)),
+        ImmBorrowGlobalGeneric(idx) => format!("ImmBorrowGlobal({})", struct_inst(*idx)),
+        ExistsGeneric(idx) => format!("Exists({})", struct_inst(*idx)),
+        MoveFromGeneric(idx) => format!("MoveFrom({})", struct_inst(*idx)),
+        MoveToGeneric(idx) => format!("MoveTo({})", struct_inst(*idx)),
+        VecPack(idx, n) => format!("VecPack({}, {})", signature_str(m, *idx), n),
+        VecUnpack(idx, n) => format!("VecUnpack({}, {})", signature_str(m, *idx), n),
+        VecLen(idx) => format!("VecLen({})", signature_str(m, *idx)),
+        VecImmBorrow(idx) => format!("VecImmBorrow({})", signature_str(m, *idx)),
+        VecMutBorrow(idx) => format!("VecMutBorrow({})", signature_str(m, *idx)),
+        VecPushBack(idx) => format!("VecPushBack({})", signature_str(m, *idx)),
+        VecPopBack(idx) => format!("VecPopBack({})", signature_str(m, *idx)),
+        VecSwap(idx) => format!("VecSwap({})", signature_str(m, *idx)),
+        // The remaining instructions do not refer to any pool of the module.
+        _ => format!("{:?}", instr),
+    }
+}
+
+fn function_str(m: &CompiledModule, idx: FunctionHandleIndex) -> String {
+    let handle = m.function_handle_at(idx);
+    format!(
+        "{}::{}",
+        module_str(m, handle.module),
+        m.identifier_at(handle.name)
+    )
+}
+
+fn struct_def_str(m: &CompiledModule, idx: StructDefinitionIndex) -> String {
+    struct_handle_str(m, m.struct_def_at(idx).struct_handle)
+}
+
+fn struct_handle_str(m: &CompiledModule, idx: StructHandleIndex) -> String {
+    let handle = m.struct_handle_at(idx);
+    format!(
+        "{}::{}",
+        module_str(m, handle.module),
+        m.identifier_at(handle.name)
+    )
+}
+
+fn module_str(m: &CompiledModule, idx: ModuleHandleIndex) -> String {
+    m.module_id_for_handle(m.module_handle_at(idx))
+        .short_str_lossless()
+}
+
+fn signature_strs(m: &CompiledModule, idx: SignatureIndex) -> Vec<String> {
+    m.signature_at(idx)
+        .0
+        .iter()
+        .map(|ty| type_str(m, ty))
+        .collect()
+}
+
+fn signature_str(m: &CompiledModule, idx: SignatureIndex) -> String {
+    signature_strs(m, idx).join(", ")
+}
+
+fn type_args_str(m: &CompiledModule, idx: SignatureIndex) -> String {
+    format!("<{}>", signature_str(m, idx))
+}
+
+fn type_str(m: &CompiledModule, ty: &SignatureToken) -> String {
+    use SignatureToken::*;
+    match ty {
+        Vector(ty) => format!("vector<{}>", type_str(m, ty)),
+        Struct(idx) => struct_handle_str(m, *idx),
+        StructInstantiation(idx, type_args) => format!(
+            "{}<{}>",
+            struct_handle_str(m, *idx),
+            type_args
+                .iter()
+                .map(|ty| type_str(m, ty))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        Reference(ty) => format!("&{}", type_str(m, ty)),
+        MutableReference(ty) => format!("&mut {}", type_str(m, ty)),
+        TypeParameter(idx) => format!("T{}", idx),
+        Bool => "bool".to_string(),
+        U8 => "u8".to_string(),
+        U16 => "u16".to_string(),
+        U32 => "u32".to_string(),
+        U64 => "u64".to_string(),
+        U128 => "u128".to_string(),
+        U256 => "u256".to_string(),
+        Address => "address".to_string(),
+        Signer => "signer".to_string(),
+    }
+}
+
+/// Reads all modules which can serve as dependencies of each other from the given bytecode files.
+pub fn read_modules(paths: &[String]) -> anyhow::Result<Vec<CompiledModule>> {
+    paths
+        .iter()
+        .map(|path| {
+            let bytes = fs::read(path).with_context(|| format!("reading `{}`", path))?;
+            CompiledModule::deserialize(&bytes)
+                .with_context(|| format!("deserializing module `{}`", path))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Decompiler, Options};
+    use move_core_types::{account_address::AccountAddress, identifier::Identifier};
+
+    const ORIGINAL: &str = r#"
+        module 0x42::m {
+            struct R has key { value: u64 }
+
+            fun publish(s: &signer, value: u64) {
+                let r = R { value: value + 1 };
+                move_to(s, r)
+            }
+
+            fun value(addr: address): u64 acquires R {
+                if (exists<R>(addr)) borrow_global<R>(addr).value else 0
+            }
+        }
+    "#;
+
+    fn compile(source: &str) -> CompiledModule {
+        let module_id = ModuleId::new(
+            AccountAddress::from_hex_literal("0x42").unwrap(),
+            Identifier::new("m").unwrap(),
+        );
+        recompile(module_id, source, &[], None).unwrap()
+    }
+
+    fn decompile(module: &CompiledModule) -> String {
+        let mut decompiler = Decompiler::new(Options::default());
+        let mut bytes = vec![];
+        module.serialize(&mut bytes).unwrap();
+        let source_map = decompiler.empty_source_map("m", &bytes);
+        decompiler.decompile_module(module.clone(), source_map)
+    }
+
+    #[test]
+    fn test_matching_round_trip() {
+        let original = compile(ORIGINAL);
+        // Same operations, but different locals and branch layout.
+        let source = r#"
+            module 0x42::m {
+                struct R has key { value: u64 }
+
+                fun publish(s: &signer, value: u64) {
+                    move_to(s, R { value: value + 1 })
+                }
+
+                fun value(addr: address): u64 acquires R {
+                    if (!exists<R>(addr)) return 0;
+                    let r = borrow_global<R>(addr);
+                    r.value
+                }
+            }
+        "#;
+        assert_eq!(
+            verify_round_trip(&original, source, &[], None).unwrap(),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn test_mismatching_round_trip() {
+        let original = compile(ORIGINAL);
+        let source = r#"
+            module 0x42::m {
+                struct R has key { value: u64 }
+
+                fun publish(s: &signer, value: u64) {
+                    move_to(s, R { value: value + 2 })
+                }
+
+                fun value(addr: address): u64 {
+                    if (addr == @0x1) 1 else 0
+                }
+            }
+        "#;
+        let discrepancies = verify_round_trip(&original, source, &[], None).unwrap();
+        let items = discrepancies
+            .iter()
+            .map(|discrepancy| discrepancy.item.as_str())
+            .collect::<Vec<_>>();
+        // The constant in `publish` differs; `value` has a different signature (no acquires)
+        // and different code, both of which are reported.
+        assert_eq!(
+            items
+                .iter()
+                .filter(|item| **item == "0x42::m::publish")
+                .count(),
+            1
+        );
+        assert!(
+            items
+                .iter()
+                .filter(|item| **item == "0x42::m::value")
+                .count()
+                >= 2
+        );
+        assert!(discrepancies
+            .iter()
+            .any(|discrepancy| discrepancy.message.contains("Exists(0x42::m::R)")));
+    }
+
+    #[test]
+    fn test_swapped_branches_round_trip() {
+        let original = compile(
+            r#"
+            module 0x42::m {
+                fun f(): u64 { 1 }
+                fun g(): u64 { 2 }
+
+                fun pick(c: bool): u64 {
+                    if (c) f() else g()
+                }
+            }
+        "#,
+        );
+        // Performs the same operations, but on the opposite conditions.
+        let source = r#"
+            module 0x42::m {
+                fun f(): u64 { 1 }
+                fun g(): u64 { 2 }
+
+                fun pick(c: bool): u64 {
+                    if (c) g() else f()
+                }
+            }
+        "#;
+        let discrepancies = verify_round_trip(&original, source, &[], None).unwrap();
+        assert_eq!(discrepancies.len(), 1);
+        assert_eq!(discrepancies[0].item, "0x42::m::pick");
+    }
+
+    #[test]
+    fn test_decompiled_round_trip() {
+        let original = compile(
+            r#"
+            module 0x42::m {
+                struct R has key { value: u64 }
+
+                fun sum_even(n: u64): u64 {
+                    let i = 0;
+                    let sum = 0;
+                    while (i < n) {
+                        if (i % 2 == 0) sum = sum + i;
+                        i = i + 1;
+                    };
+                    sum
+                }
+
+                fun clamp(x: u64, max: u64): u64 {
+                    assert!(max > 0, 1);
+                    if (x > max) max else x
+                }
+
+                fun update(addr: address, value: u64) acquires R {
+                    if (!exists<R>(addr)) abort 2;
+                    let r = borrow_global_mut<R>(addr);
+                    if (value > r.value) r.value = value
+                }
+            }
+        "#,
+        );
+        let source = decompile(&original);
+        assert_eq!(
+            verify_round_trip(&original, &source, &[], None).unwrap(),
+            vec![],
+            "decompiled source:\n{}",
+            source
+        );
+    }
+
+    #[test]
+    fn test_uncompilable_round_trip() {
+        let original = compile(ORIGINAL);
+        assert!(verify_round_trip(&original, "module 0x42::m {", &[], None).is_err());
+    }
+}
@@ -3,6 +3,8 @@
 
 use crate::on_chain_config::BlockGasLimitType;
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use strum_macros::EnumString;
 
 /// Local, per-node configurations for module cache. While caches can be persisted across multiple
 /// block executions, these configurations allow to specify cache sizes, etc.
@@ -30,6 +32,23 @@ impl Default for BlockExecutorModuleCacheLocalConfig {
     }
 }
 
+/// When to execute blocks in parallel along predicted conflict chains, i.e., with the execution
+/// of every transaction waiting for the closest preceding transaction it is predicted to
+/// conflict with. Predictions are only used for scheduling, and do not affect the output.
+#[derive(Clone, Copy, Debug, Default, Deserialize, EnumString, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum ConflictAwareExecution {
+    #[default]
+    Disabled,
+    /// Execute blocks along conflict chains while recent blocks re-execute many transactions,
+    /// and re-execute blocks whose parallel execution failed along conflict chains, before
+    /// falling back to sequential execution (requires allow_fallback).
+    Fallback,
+    /// Execute all blocks along conflict chains, e.g., to benchmark hot-spot workloads.
+    Always,
+}
+
 /// Local, per-node configuration.
 #[derive(Clone, Debug)]
 pub struct BlockExecutorLocalConfig {
@@ -40,20 +59,30 @@ pub struct BlockExecutorLocalConfig {
     // If true, we will discard the failed blocks and continue with the next block.
     // (allow_fallback needs to be set)
     pub discard_failed_blocks: bool,
+    // Whether blocks are executed in parallel with the transactions predicted to conflict
+    // executed in order.
+    pub conflict_aware_execution: ConflictAwareExecution,
     pub module_cache_config: BlockExecutorModuleCacheLocalConfig,
+    // If specified, parallel execution records the read and write sets, incarnations and aborts
+    // of every transaction, and saves them to a file per block in this directory.
+    pub execution_trace_dir: Option<PathBuf>,
 }
 
 impl BlockExecutorLocalConfig {
     /// Returns a new config with specified concurrency level and:
     ///   - Allowed fallback to sequential execution from parallel.
     ///   - Not allowed discards of failed blocks.
+    ///   - No conflict aware execution.
     ///   - Default module cache configs.
+    ///   - No execution traces.
     pub fn default_with_concurrency_level(concurrency_level: usize) -> Self {
         Self {
             concurrency_level,
             allow_fallback: true,
             discard_failed_blocks: false,
+            conflict_aware_execution: ConflictAwareExecution::Disabled,
             module_cache_config: BlockExecutorModuleCacheLocalConfig::default(),
+            execution_trace_dir: None,
         }
     }
 }
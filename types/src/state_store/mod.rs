@@ -18,6 +18,7 @@ use std::ops::Deref;
 
 pub mod errors;
 pub mod state_key;
+pub mod state_overrides;
 pub mod state_storage_usage;
 pub mod state_value;
 pub mod table;
@@ -0,0 +1,270 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    account_address::AccountAddress,
+    on_chain_config::CurrentTimeMicroseconds,
+    state_store::{
+        state_key::StateKey, state_storage_usage::StateStorageUsage, state_value::StateValue,
+        table::TableHandle, StateView, StateViewId, StateViewResult, TStateView,
+    },
+    write_set::WriteSet,
+};
+use bytes::Bytes;
+use move_core_types::{identifier::IdentStr, language_storage::StructTag};
+use std::collections::{BTreeMap, HashMap};
+
+/// A set of state values which shadow the ones in the underlying state. Used when simulating
+/// transactions against hypothetical state, e.g., a different account balance, an upgraded
+/// module or a different block timestamp.
+#[derive(Clone, Debug, Default)]
+pub struct StateOverrides {
+    /// Overridden values. A [None] value means the state key is treated as non-existent.
+    values: HashMap<StateKey, Option<StateValue>>,
+}
+
+impl StateOverrides {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns the overridden value for the key, if the key is overridden. The inner [None]
+    /// means the key has been overridden as deleted.
+    pub fn get(&self, state_key: &StateKey) -> Option<&Option<StateValue>> {
+        self.values.get(state_key)
+    }
+
+    /// Overrides the value stored at the given state key. If the key exists in the underlying
+    /// state, its metadata is kept.
+    pub fn set(&mut self, state_key: StateKey, bytes: Bytes) {
+        self.values
+            .insert(state_key, Some(StateValue::new_legacy(bytes)));
+    }
+
+    /// Makes the given state key appear as non-existent.
+    pub fn remove(&mut self, state_key: StateKey) {
+        self.values.insert(state_key, None);
+    }
+
+    /// Overrides a resource which is stored under its own state key, i.e., is not a member of a
+    /// resource group.
+    pub fn set_resource(
+        &mut self,
+        address: &AccountAddress,
+        struct_tag: &StructTag,
+        bytes: Bytes,
+    ) -> anyhow::Result<()> {
+        self.set(StateKey::resource(address, struct_tag)?, bytes);
+        Ok(())
+    }
+
+    /// Overrides a member of a resource group. Other members of the group are kept as they are
+    /// in the overrides (if the group has already been overridden) or in the base state view.
+    pub fn set_resource_group_member(
+        &mut self,
+        base_view: &impl StateView,
+        address: &AccountAddress,
+        group_tag: &StructTag,
+        struct_tag: &StructTag,
+        bytes: Bytes,
+    ) -> anyhow::Result<()> {
+        let state_key = StateKey::resource_group(address, group_tag);
+        let group_bytes = match self.values.get(&state_key) {
+            Some(group) => group.as_ref().map(|group| group.bytes().clone()),
+            None => base_view.get_state_value_bytes(&state_key)?,
+        };
+        let mut group: BTreeMap<StructTag, Bytes> = match group_bytes {
+            Some(group_bytes) => bcs::from_bytes(&group_bytes)?,
+            None => BTreeMap::new(),
+        };
+        group.insert(struct_tag.clone(), bytes);
+        self.set(state_key, bcs::to_bytes(&group)?.into());
+        Ok(())
+    }
+
+    /// Overrides the code of a module.
+    pub fn set_module(&mut self, address: &AccountAddress, name: &IdentStr, bytes: Bytes) {
+        self.set(StateKey::module(address, name), bytes);
+    }
+
+    /// Overrides a table item, where the key is the BCS-serialized table key.
+    pub fn set_table_item(&mut self, handle: &TableHandle, key: &[u8], bytes: Bytes) {
+        self.set(StateKey::table_item(handle, key), bytes);
+    }
+
+    /// Applies the writes of an executed transaction on top of the overrides, so that later
+    /// transactions observe them.
+    pub fn apply_write_set(&mut self, write_set: &WriteSet) {
+        for (state_key, write_op) in write_set {
+            self.values
+                .insert(state_key.clone(), write_op.state_value_ref().cloned());
+        }
+    }
+
+    /// Overrides the on-chain timestamp, which is the timestamp of the block the simulated
+    /// transactions are executed in.
+    pub fn set_timestamp_usecs(&mut self, timestamp_usecs: u64) -> anyhow::Result<()> {
+        let timestamp = CurrentTimeMicroseconds {
+            microseconds: timestamp_usecs,
+        };
+        self.set(
+            StateKey::on_chain_config::<CurrentTimeMicroseconds>()?,
+            bcs::to_bytes(&timestamp)?.into(),
+        );
+        Ok(())
+    }
+}
+
+/// A [StateView] which layers [StateOverrides] on top of another state view.
+pub struct StateViewWithOverrides<'a, S> {
+    base_view: &'a S,
+    overrides: &'a StateOverrides,
+}
+
+impl<'a, S: StateView> StateViewWithOverrides<'a, S> {
+    pub fn new(base_view: &'a S, overrides: &'a StateOverrides) -> Self {
+        Self {
+            base_view,
+            overrides,
+        }
+    }
+}
+
+impl<'a, S: StateView> TStateView for StateViewWithOverrides<'a, S> {
+    type Key = StateKey;
+
+    fn id(&self) -> StateViewId {
+        self.base_view.id()
+    }
+
+    fn get_state_value(&self, state_key: &StateKey) -> StateViewResult<Option<StateValue>> {
+        match self.overrides.get(state_key) {
+            Some(Some(state_value)) if state_value.metadata().is_none() => {
+                // Keep the metadata of the existing value (if any), so that storage fees and
+                // refunds are computed the same way as without the override.
+                Ok(Some(match self.base_view.get_state_value(state_key)? {
+                    Some(mut base_value) => {
+                        base_value.set_bytes(state_value.bytes().clone());
+                        base_value
+                    },
+                    None => state_value.clone(),
+                }))
+            },
+            Some(state_value) => Ok(state_value.clone()),
+            None => self.base_view.get_state_value(state_key),
+        }
+    }
+
+    fn get_usage(&self) -> StateViewResult<StateStorageUsage> {
+        self.base_view.get_usage()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state_store::MockStateView;
+    use move_core_types::ident_str;
+
+    fn test_struct_tag(name: &str) -> StructTag {
+        StructTag {
+            address: AccountAddress::ONE,
+            module: ident_str!("test").to_owned(),
+            name: IdentStr::new(name).unwrap().to_owned(),
+            type_args: vec![],
+        }
+    }
+
+    #[test]
+    fn test_overrides_shadow_base_view() {
+        let address = AccountAddress::random();
+        let overridden_key = StateKey::resource(&address, &test_struct_tag("A")).unwrap();
+        let removed_key = StateKey::resource(&address, &test_struct_tag("B")).unwrap();
+        let untouched_key = StateKey::resource(&address, &test_struct_tag("C")).unwrap();
+
+        let base_view = MockStateView::new(
+            [
+                (overridden_key.clone(), StateValue::from(vec![1])),
+                (removed_key.clone(), StateValue::from(vec![2])),
+                (untouched_key.clone(), StateValue::from(vec![3])),
+            ]
+            .into_iter()
+            .collect(),
+        );
+
+        let mut overrides = StateOverrides::new();
+        overrides
+            .set_resource(&address, &test_struct_tag("A"), vec![10].into())
+            .unwrap();
+        overrides.remove(removed_key.clone());
+        overrides.set_module(&address, ident_str!("test"), vec![20].into());
+
+        let view = StateViewWithOverrides::new(&base_view, &overrides);
+        let get = |key: &StateKey| view.get_state_value_bytes(key).unwrap().map(|b| b.to_vec());
+        assert_eq!(get(&overridden_key), Some(vec![10]));
+        assert_eq!(get(&removed_key), None);
+        assert_eq!(get(&untouched_key), Some(vec![3]));
+        assert_eq!(
+            get(&StateKey::module(&address, ident_str!("test"))),
+            Some(vec![20])
+        );
+    }
+
+    #[test]
+    fn test_resource_group_member_override_keeps_other_members() {
+        let address = AccountAddress::random();
+        let group_tag = test_struct_tag("Group");
+        let group_key = StateKey::resource_group(&address, &group_tag);
+
+        let group: BTreeMap<StructTag, Bytes> = [
+            (test_struct_tag("A"), Bytes::from(vec![1])),
+            (test_struct_tag("B"), Bytes::from(vec![2])),
+        ]
+        .into_iter()
+        .collect();
+        let base_view = MockStateView::new(
+            [(
+                group_key.clone(),
+                StateValue::from(bcs::to_bytes(&group).unwrap()),
+            )]
+            .into_iter()
+            .collect(),
+        );
+
+        let mut overrides = StateOverrides::new();
+        overrides
+            .set_resource_group_member(
+                &base_view,
+                &address,
+                &group_tag,
+                &test_struct_tag("A"),
+                vec![10].into(),
+            )
+            .unwrap();
+        overrides
+            .set_resource_group_member(
+                &base_view,
+                &address,
+                &group_tag,
+                &test_struct_tag("C"),
+                vec![30].into(),
+            )
+            .unwrap();
+
+        let view = StateViewWithOverrides::new(&base_view, &overrides);
+        let group_bytes = view.get_state_value_bytes(&group_key).unwrap().unwrap();
+        let group: BTreeMap<StructTag, Bytes> = bcs::from_bytes(&group_bytes).unwrap();
+        assert_eq!(group.len(), 3);
+        assert_eq!(group[&test_struct_tag("A")].to_vec(), vec![10]);
+        assert_eq!(group[&test_struct_tag("B")].to_vec(), vec![2]);
+        assert_eq!(group[&test_struct_tag("C")].to_vec(), vec![30]);
+    }
+}
@@ -7,8 +7,8 @@ use crate::{
     on_chain_config::{CurrentTimeMicroseconds, Features, TransactionFeeBurnCap},
     state_store::{state_key::StateKey, table::TableHandle},
     transaction::{
-        signature_verified_transaction::SignatureVerifiedTransaction, Transaction,
-        TransactionPayload,
+        signature_verified_transaction::SignatureVerifiedTransaction, SignedTransaction,
+        Transaction, TransactionPayload,
     },
     AptosCoinType, CoinType,
 };
@@ -241,45 +241,53 @@ trait AnalyzedTransactionProvider {
     fn get_read_write_hints(&self) -> (Vec<StorageLocation>, Vec<StorageLocation>);
 }
 
+/// Returns the read and write hints of a user transaction, if they can be derived from its
+/// payload. Unlike `AnalyzedTransaction::new`, never panics for unsupported transactions.
+pub fn declared_read_write_hints(
+    signed_txn: &SignedTransaction,
+) -> Option<(Vec<StorageLocation>, Vec<StorageLocation>)> {
+    let func = match signed_txn.payload() {
+        TransactionPayload::EntryFunction(func) => func,
+        _ => return None,
+    };
+    let sender_address = signed_txn.sender();
+    match (
+        *func.module().address(),
+        func.module().name().as_str(),
+        func.function().as_str(),
+    ) {
+        (AccountAddress::ONE, "coin", "transfer") => {
+            let receiver_address = bcs::from_bytes(func.args().first()?).ok()?;
+            Some(rw_set_for_coin_transfer(
+                sender_address,
+                receiver_address,
+                true,
+            ))
+        },
+        (AccountAddress::ONE, "aptos_account", "transfer") => {
+            let receiver_address = bcs::from_bytes(func.args().first()?).ok()?;
+            Some(rw_set_for_coin_transfer(
+                sender_address,
+                receiver_address,
+                false,
+            ))
+        },
+        (AccountAddress::ONE, "aptos_account", "create_account") => {
+            let receiver_address = bcs::from_bytes(func.args().first()?).ok()?;
+            Some(rw_set_for_create_account(sender_address, receiver_address))
+        },
+        _ => None,
+    }
+}
+
 impl AnalyzedTransactionProvider for Transaction {
     fn get_read_write_hints(&self) -> (Vec<StorageLocation>, Vec<StorageLocation>) {
         match self {
             Transaction::UserTransaction(signed_txn) => match signed_txn.payload() {
-                TransactionPayload::EntryFunction(func) => {
-                    match (
-                        *func.module().address(),
-                        func.module().name().as_str(),
-                        func.function().as_str(),
-                    ) {
-                        (AccountAddress::ONE, "coin", "transfer") => {
-                            let sender_address = signed_txn.sender();
-                            let receiver_address = bcs::from_bytes(&func.args()[0]).unwrap();
-                            rw_set_for_coin_transfer(
-                                sender_address,
-                                receiver_address,
-                                true,
-                            )
-                        },
-                        (AccountAddress::ONE, "aptos_account", "transfer") => {
-                            let sender_address = signed_txn.sender();
-                            let receiver_address = bcs::from_bytes(&func.args()[0]).unwrap();
-                            rw_set_for_coin_transfer(
-                                sender_address,
-                                receiver_address,
-                                false,
-                            )
-                        },
-                        (AccountAddress::ONE, "aptos_account", "create_account") => {
-                            let sender_address = signed_txn.sender();
-                            let receiver_address = bcs::from_bytes(&func.args()[0]).unwrap();
-                            rw_set_for_create_account(
-                                sender_address,
-                                receiver_address,
-                            )
-                        },
-                        _ => todo!("Only coin transfer and create account transactions are supported for now")
-                    }
-                },
+                TransactionPayload::EntryFunction(_) => declared_read_write_hints(signed_txn)
+                    .unwrap_or_else(|| {
+                        todo!("Only coin transfer and create account transactions are supported for now")
+                    }),
                 _ => todo!("Only entry function transactions are supported for now"),
             },
             _ => empty_rw_set(),